@@ -1,5 +1,7 @@
 use rust_yu_lib::cleaner;
-use rust_yu_lib::cleaner::models::CleanResult;
+use rust_yu_lib::cleaner::archive::{self, RestoreSummary};
+use rust_yu_lib::cleaner::models::{CleanResult, SafetyVerdict};
+use rust_yu_lib::cleaner::session::{self, CleanSession};
 use rust_yu_lib::scanner::models::Trace;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +12,24 @@ pub struct CleanOptions {
     pub traces: Vec<Trace>,
     pub confirm: bool,
     pub preview: bool,
+    /// 是否同时删除属于其他用户的痕迹
+    #[serde(default)]
+    pub include_other_users: bool,
+    /// 是否同时删除可能包含用户数据的痕迹
+    #[serde(default)]
+    pub include_user_data: bool,
+    /// 是否同时删除游戏存档、文档等用户创建的内容
+    #[serde(default)]
+    pub include_user_content: bool,
+    /// 删除文件和 AppData 痕迹前先压缩归档到隔离区
+    #[serde(default)]
+    pub archive: bool,
+    /// 按大小和修改时间筛选
+    #[serde(default)]
+    pub filter: cleaner::models::CleanFilter,
+    /// 扫描建议但用户取消勾选的痕迹，记录为清理反馈
+    #[serde(default)]
+    pub deselected: Vec<Trace>,
 }
 
 #[tauri::command]
@@ -19,9 +39,72 @@ pub async fn clean_traces(options: CleanOptions) -> Result<Vec<CleanResult>, Com
         return Ok(vec![]);
     }
 
-    let results = cleaner::clean_traces(options.traces, options.confirm)
+    let clean_options = cleaner::models::CleanOptions {
+        include_other_users: options.include_other_users,
+        include_user_data: options.include_user_data,
+        include_user_content: options.include_user_content,
+        archive: options.archive,
+    };
+    if !options.confirm {
+        return Err(CommandError::new("需要确认才能执行清理".to_string()));
+    }
+
+    // 通过会话执行，界面关闭或部分失败后可以恢复
+    let program_name = options
+        .traces
+        .first()
+        .map(|trace| trace.program_name.clone())
+        .unwrap_or_default();
+    let (traces, _) = cleaner::selection::select_traces(options.traces, &options.filter);
+    let mut session = CleanSession::new(&program_name, traces, clean_options);
+    let results = cleaner::run_clean_session(&mut session)
         .await
         .map_err(CommandError::from)?;
-
+    cleaner::record_feedback(&results, &options.deselected);
     Ok(results)
 }
+
+/// 未完成的清理会话，最近更新的在前
+#[tauri::command]
+pub async fn list_clean_sessions() -> Result<Vec<CleanSession>, CommandError> {
+    session::list_sessions().map_err(CommandError::from)
+}
+
+/// 恢复清理会话，只处理剩余和失败的项
+#[tauri::command]
+pub async fn resume_clean_session(session_id: String) -> Result<Vec<CleanResult>, CommandError> {
+    let mut session = session::load_session(&session_id).map_err(CommandError::from)?;
+    cleaner::run_clean_session(&mut session)
+        .await
+        .map_err(CommandError::from)
+}
+
+/// 放弃清理会话
+#[tauri::command]
+pub async fn discard_clean_session(session_id: String) -> Result<(), CommandError> {
+    session::delete_session(&session_id).map_err(CommandError::from)
+}
+
+/// 模拟清理，返回每个痕迹是否会被安全规则拦截，不删除任何内容
+#[tauri::command]
+pub async fn simulate_clean(
+    traces: Vec<Trace>,
+    include_other_users: Option<bool>,
+    include_user_data: Option<bool>,
+    include_user_content: Option<bool>,
+) -> Result<Vec<SafetyVerdict>, CommandError> {
+    let clean_options = cleaner::models::CleanOptions {
+        include_other_users: include_other_users.unwrap_or(false),
+        include_user_data: include_user_data.unwrap_or(false),
+        include_user_content: include_user_content.unwrap_or(false),
+        archive: false,
+    };
+
+    Ok(cleaner::simulate_clean(&traces, clean_options))
+}
+
+/// 将删除前归档的痕迹解压回原始位置，已存在的文件不会被覆盖
+#[tauri::command]
+pub async fn restore_clean_archive(archive_path: String) -> Result<RestoreSummary, CommandError> {
+    archive::restore_archive(std::path::Path::new(&archive_path)).map_err(CommandError::from)
+}
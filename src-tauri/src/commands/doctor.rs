@@ -0,0 +1,32 @@
+use rust_yu_lib::lister::storage_usage::{self, StorageArea, StorageInfo};
+use rust_yu_lib::lister::{integrity::IntegrityReport, storage};
+
+use super::CommandError;
+
+/// 检查本地存储的完整性，`repair` 时修复发现的问题
+#[tauri::command]
+pub async fn verify_storage(repair: bool) -> Result<IntegrityReport, CommandError> {
+    // 需要遍历图标缓存和报告目录，放到阻塞线程中执行
+    tokio::task::spawn_blocking(move || storage::verify(repair))
+        .await
+        .map_err(|error| CommandError::new(format!("存储检查任务失败: {}", error)))?
+        .map_err(CommandError::from)
+}
+
+/// rust-yu 的存储位置及各区域占用的磁盘空间
+#[tauri::command]
+pub async fn get_storage_info() -> Result<StorageInfo, CommandError> {
+    tokio::task::spawn_blocking(storage_usage::storage_info)
+        .await
+        .map_err(|error| CommandError::new(format!("统计存储占用失败: {}", error)))?
+        .map_err(CommandError::from)
+}
+
+/// 清空存储区域，返回释放的字节数
+#[tauri::command]
+pub async fn clear_storage_area(area: StorageArea) -> Result<u64, CommandError> {
+    tokio::task::spawn_blocking(move || storage_usage::clear_area(area))
+        .await
+        .map_err(|error| CommandError::new(format!("清空存储区域失败: {}", error)))?
+        .map_err(CommandError::from)
+}
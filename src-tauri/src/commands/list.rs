@@ -1,5 +1,8 @@
 use rust_yu_lib::lister;
-use rust_yu_lib::lister::models::{InstallSource, ListProgramsQuery, ProgramListResponse};
+use rust_yu_lib::lister::models::{
+    InstallSource, InstalledProgram, ListProgramsQuery, ProgramListResponse,
+};
+use rust_yu_lib::modules::common::priority::{self, PriorityProfile};
 use serde::{Deserialize, Serialize};
 
 use super::CommandError;
@@ -9,6 +12,16 @@ pub struct ListOptions {
     pub source: Option<String>,
     pub search: Option<String>,
     pub refresh: Option<bool>,
+    /// 只返回带有全部这些标签的程序
+    pub tags: Option<Vec<String>>,
+    /// 置顶程序排在最前
+    pub pinned_first: Option<bool>,
+    /// 只返回疑似预装软件
+    pub bloatware_only: Option<bool>,
+    /// 只返回未签名或签名无效的程序
+    pub unsigned_only: Option<bool>,
+    /// 优先级配置 (interactive/background)，后台刷新时使用 background
+    pub priority: Option<String>,
 }
 
 #[tauri::command]
@@ -20,18 +33,70 @@ pub async fn list_programs(
             "registry" => InstallSource::Registry,
             "msi" => InstallSource::Msi,
             "store" => InstallSource::Store,
+            "features" => InstallSource::Feature,
             _ => InstallSource::Registry,
         }
     });
 
     let search = options.as_ref().and_then(|o| o.search.clone());
     let refresh = options.as_ref().and_then(|o| o.refresh).unwrap_or(false);
+    let tags = options
+        .as_ref()
+        .and_then(|o| o.tags.clone())
+        .unwrap_or_default();
+    let pinned_first = options
+        .as_ref()
+        .and_then(|o| o.pinned_first)
+        .unwrap_or(false);
+    let bloatware_only = options
+        .as_ref()
+        .and_then(|o| o.bloatware_only)
+        .unwrap_or(false);
+    let unsigned_only = options
+        .as_ref()
+        .and_then(|o| o.unsigned_only)
+        .unwrap_or(false);
+    let profile = parse_priority(options.as_ref().and_then(|o| o.priority.as_deref()))?;
 
     let query = ListProgramsQuery {
         source,
         search,
         refresh,
         cache_ttl_seconds: rust_yu_lib::lister::storage::DEFAULT_CACHE_TTL_SECONDS,
+        allow_stale: true,
+        tags,
+        pinned_first,
+        bloatware_only,
+        unsigned_only,
+    };
+
+    let join_result = tauri::async_runtime::spawn_blocking(move || {
+        let _priority = priority::enter(profile);
+        lister::list_programs_with_cache(query)
+    })
+    .await
+    .map_err(|error| CommandError::new(format!("程序列表任务执行失败: {}", error)))?;
+
+    join_result.map_err(CommandError::from)
+}
+
+/// 解析前端传入的优先级配置，未指定时为 interactive
+pub fn parse_priority(value: Option<&str>) -> Result<PriorityProfile, CommandError> {
+    value
+        .map(str::parse::<PriorityProfile>)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(CommandError::from)
+}
+
+/// 精简建议：疑似预装或捆绑软件，按评分从高到低排列
+#[tauri::command]
+pub async fn get_debloat_suggestions() -> Result<Vec<InstalledProgram>, CommandError> {
+    let query = ListProgramsQuery {
+        cache_ttl_seconds: rust_yu_lib::lister::storage::DEFAULT_CACHE_TTL_SECONDS,
+        allow_stale: true,
+        bloatware_only: true,
+        ..Default::default()
     };
 
     let join_result =
@@ -39,5 +104,5 @@ pub async fn list_programs(
             .await
             .map_err(|error| CommandError::new(format!("程序列表任务执行失败: {}", error)))?;
 
-    join_result.map_err(CommandError::from)
+    Ok(join_result.map_err(CommandError::from)?.programs)
 }
@@ -1,15 +1,29 @@
 pub mod clean;
+pub mod doctor;
 pub mod error;
 pub mod list;
+pub mod preview;
 pub mod report;
+pub mod review;
 pub mod scan;
 pub mod search;
+pub mod settings;
+pub mod suites;
+pub mod tags;
 pub mod uninstall;
+pub mod user_data;
 
 pub use clean::*;
+pub use doctor::*;
 pub use error::*;
 pub use list::*;
+pub use preview::*;
 pub use report::*;
+pub use review::*;
 pub use scan::*;
 pub use search::*;
+pub use settings::*;
+pub use suites::*;
+pub use tags::*;
 pub use uninstall::*;
+pub use user_data::*;
@@ -0,0 +1,24 @@
+use rust_yu_lib::scanner::models::Trace;
+use rust_yu_lib::scanner::preview::{self, FileTracePreview, RegistryTracePreview};
+
+use super::CommandError;
+
+/// 只读预览注册表痕迹中的值和直接子项
+#[tauri::command]
+pub async fn preview_registry_trace(trace: Trace) -> Result<RegistryTracePreview, CommandError> {
+    preview::preview_registry_trace(&trace).map_err(CommandError::from)
+}
+
+/// 预览文件痕迹的时间、所有者、属性和签名，目录额外列出最大的子项
+#[tauri::command]
+pub async fn preview_file_trace(
+    trace: Trace,
+    top_children: Option<usize>,
+) -> Result<FileTracePreview, CommandError> {
+    let top_children = top_children.unwrap_or(preview::DEFAULT_TOP_CHILDREN);
+    // 目录需要统计子项大小，放到阻塞线程中执行
+    tokio::task::spawn_blocking(move || preview::preview_file_trace(&trace, top_children))
+        .await
+        .map_err(|error| CommandError::new(format!("预览任务失败: {}", error)))?
+        .map_err(CommandError::from)
+}
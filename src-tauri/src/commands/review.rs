@@ -0,0 +1,90 @@
+use rust_yu_lib::cleaner;
+use rust_yu_lib::cleaner::models::CleanResult;
+use rust_yu_lib::cleaner::review::{self, ReviewSession, ReviewStatus};
+use rust_yu_lib::cleaner::session::CleanSession;
+use rust_yu_lib::scanner::models::Trace;
+
+use super::CommandError;
+
+/// 为扫描结果创建审核会话
+#[tauri::command]
+pub async fn create_review_session(
+    program_name: String,
+    traces: Vec<Trace>,
+) -> Result<ReviewSession, CommandError> {
+    let review = ReviewSession::new(&program_name, traces);
+    review::save_review(&review).map_err(CommandError::from)?;
+    Ok(review)
+}
+
+/// 所有审核会话，最近更新的在前
+#[tauri::command]
+pub async fn list_review_sessions() -> Result<Vec<ReviewSession>, CommandError> {
+    review::list_reviews().map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn get_review_session(review_id: String) -> Result<ReviewSession, CommandError> {
+    review::load_review(&review_id).map_err(CommandError::from)
+}
+
+/// 标注痕迹的审核结论
+#[tauri::command]
+pub async fn annotate_trace(
+    review_id: String,
+    trace_id: String,
+    status: ReviewStatus,
+    comment: Option<String>,
+    reviewer: Option<String>,
+) -> Result<ReviewSession, CommandError> {
+    let mut review = review::load_review(&review_id).map_err(CommandError::from)?;
+    review
+        .annotate(&trace_id, status, comment, reviewer)
+        .map_err(CommandError::from)?;
+    review::save_review(&review).map_err(CommandError::from)?;
+    Ok(review)
+}
+
+#[tauri::command]
+pub async fn delete_review_session(review_id: String) -> Result<(), CommandError> {
+    review::delete_review(&review_id).map_err(CommandError::from)
+}
+
+/// 只清理审核会话中已批准的痕迹
+///
+/// 仍有待审核的痕迹时拒绝执行，除非调用方明确允许
+#[tauri::command]
+pub async fn clean_approved_traces(
+    review_id: String,
+    allow_unresolved: Option<bool>,
+    include_other_users: Option<bool>,
+    include_user_data: Option<bool>,
+    include_user_content: Option<bool>,
+    archive: Option<bool>,
+) -> Result<Vec<CleanResult>, CommandError> {
+    let review = review::load_review(&review_id).map_err(CommandError::from)?;
+    let unresolved = review.unresolved_count();
+    if unresolved > 0 && !allow_unresolved.unwrap_or(false) {
+        return Err(CommandError::new(format!(
+            "还有 {} 项痕迹未完成审核",
+            unresolved
+        )));
+    }
+
+    let clean_options = cleaner::models::CleanOptions {
+        include_other_users: include_other_users.unwrap_or(false),
+        include_user_data: include_user_data.unwrap_or(false),
+        include_user_content: include_user_content.unwrap_or(false),
+        archive: archive.unwrap_or(false),
+    };
+    let mut session = CleanSession::new(
+        &review.program_name,
+        review.approved_traces(),
+        clean_options,
+    );
+    let results = cleaner::run_clean_session(&mut session)
+        .await
+        .map_err(CommandError::from)?;
+    cleaner::record_feedback(&results, &review.rejected_traces());
+    Ok(results)
+}
@@ -1,20 +1,111 @@
+use std::sync::Arc;
+
+use rust_yu_lib::modules::common::priority;
 use rust_yu_lib::scanner;
-use rust_yu_lib::scanner::models::Trace;
+use rust_yu_lib::scanner::models::{Confidence, ScanProgress, ScanSummary, Trace};
+use rust_yu_lib::scanner::options as scan_options;
+use rust_yu_lib::scanner::profiles::{self, ProfileScope};
 use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
 
+use super::list::parse_priority;
 use super::CommandError;
 
+/// 扫描进度事件，载荷为 [`ScanProgress`]
+const SCAN_PROGRESS_EVENT: &str = "scan://progress";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ScanOptions {
     pub program_name: String,
     pub trace_types: Option<Vec<String>>,
+    pub min_confidence: Option<String>,
 }
 
 #[tauri::command]
 pub async fn scan_traces(
+    app: AppHandle,
     program_name: String,
     trace_types: Option<Vec<String>>,
+    min_confidence: Option<String>,
+    priority: Option<String>,
+    all_users: Option<bool>,
+    load_offline_hives: Option<bool>,
+    options: Option<scan_options::ScanOptions>,
 ) -> Result<Vec<Trace>, CommandError> {
+    let profile = parse_priority(priority.as_deref())?;
+    let scope = ProfileScope::from_flags(
+        all_users.unwrap_or(false),
+        load_offline_hives.unwrap_or(false),
+    );
+    let summary = priority::scope(
+        profile,
+        profiles::scope(
+            scope,
+            run_scan(
+                &program_name,
+                trace_types,
+                min_confidence.as_deref(),
+                &options.unwrap_or_default(),
+                Some(progress_emitter(app)),
+            ),
+        ),
+    )
+    .await?;
+    Ok(summary.traces)
+}
+
+/// 扫描痕迹并返回摘要，包含因置信度过低被过滤的数量
+#[tauri::command]
+pub async fn scan_trace_summary(
+    app: AppHandle,
+    program_name: String,
+    trace_types: Option<Vec<String>>,
+    min_confidence: Option<String>,
+    priority: Option<String>,
+    all_users: Option<bool>,
+    load_offline_hives: Option<bool>,
+    options: Option<scan_options::ScanOptions>,
+) -> Result<ScanSummary, CommandError> {
+    let profile = parse_priority(priority.as_deref())?;
+    let scope = ProfileScope::from_flags(
+        all_users.unwrap_or(false),
+        load_offline_hives.unwrap_or(false),
+    );
+    priority::scope(
+        profile,
+        profiles::scope(
+            scope,
+            run_scan(
+                &program_name,
+                trace_types,
+                min_confidence.as_deref(),
+                &options.unwrap_or_default(),
+                Some(progress_emitter(app)),
+            ),
+        ),
+    )
+    .await
+}
+
+/// 把扫描进度作为 `scan://progress` 事件发给前端
+fn progress_emitter(app: AppHandle) -> scanner::ProgressCallback {
+    Arc::new(move |progress: &ScanProgress| {
+        if let Err(error) = app.emit(SCAN_PROGRESS_EVENT, progress) {
+            tracing::debug!("发送扫描进度失败: {}", error);
+        }
+    })
+}
+
+/// 执行扫描，供 Tauri 命令和开发模式 HTTP 接口共用
+///
+/// `options` 中未指定的深度、上限和时限使用默认值；提供 `progress` 时报告扫描进度
+pub async fn run_scan(
+    program_name: &str,
+    trace_types: Option<Vec<String>>,
+    min_confidence: Option<&str>,
+    options: &scan_options::ScanOptions,
+    progress: Option<scanner::ProgressCallback>,
+) -> Result<ScanSummary, CommandError> {
     use rust_yu_lib::scanner::models::TraceType;
 
     let types = trace_types.map(|t| {
@@ -25,14 +116,42 @@ pub async fn scan_traces(
                 "file" => Some(TraceType::File),
                 "appdata" => Some(TraceType::AppData),
                 "shortcut" => Some(TraceType::Shortcut),
+                "activex" => Some(TraceType::ActiveX),
+                "usage_history" => Some(TraceType::UsageHistory),
+                "network_setting" => Some(TraceType::NetworkSetting),
+                "firewall_rule" => Some(TraceType::FirewallRule),
+                "environment_variable" => Some(TraceType::EnvironmentVariable),
+                "context_menu" => Some(TraceType::ContextMenu),
+                "credential" => Some(TraceType::Credential),
+                "event_log_source" => Some(TraceType::EventLogSource),
+                "wmi_provider" => Some(TraceType::WmiProvider),
+                "defender_exclusion" => Some(TraceType::DefenderExclusion),
                 _ => None,
             })
             .collect()
     });
-
-    let traces = scanner::scan_all_traces(&program_name, types)
-        .await
+    let min_confidence = min_confidence
+        .map(str::parse::<Confidence>)
+        .transpose()
         .map_err(CommandError::from)?;
 
-    Ok(traces)
+    // 程序已卸载时使用保存的记录，按其安装目录和发布者定位残留
+    let summary = match rust_yu_lib::lister::find_program_record(program_name) {
+        Some(program) => {
+            scanner::scan_program_traces(&program, types, min_confidence, options, progress).await
+        }
+        None => {
+            scanner::scan_all_traces_with_summary(
+                program_name,
+                &[],
+                types,
+                min_confidence,
+                options,
+                progress,
+            )
+            .await
+        }
+    };
+
+    summary.map_err(CommandError::from)
 }
@@ -1,5 +1,6 @@
 use rust_yu_lib::lister;
 use rust_yu_lib::lister::models::InstalledProgram;
+use rust_yu_lib::lister::saved_programs::ProgramSnapshotRecord;
 use serde::{Deserialize, Serialize};
 
 use super::CommandError;
@@ -14,6 +15,18 @@ pub struct SearchOptions {
 pub async fn search_programs(query: String) -> Result<Vec<InstalledProgram>, CommandError> {
     // 搜索功能使用 list_all_programs 的 search 参数
     let programs = lister::list_all_programs(None, Some(&query)).map_err(CommandError::from)?;
+    if !programs.is_empty() {
+        return Ok(programs);
+    }
 
-    Ok(programs)
+    // 已卸载的程序从保存的记录中查找
+    lister::storage::search_programs_with_fallback(&query).map_err(CommandError::from)
+}
+
+/// 程序卸载前保存的历史记录，最新的在前
+#[tauri::command]
+pub async fn get_saved_program_history(
+    program_name: String,
+) -> Result<Vec<ProgramSnapshotRecord>, CommandError> {
+    lister::saved_programs::snapshot_history(&program_name).map_err(CommandError::from)
 }
@@ -0,0 +1,7 @@
+use rust_yu_lib::modules::common::format::{self, Formatter};
+
+/// 配置文件中设置的显示格式，界面按相同的小数分隔符、大小单位和日期格式显示
+#[tauri::command]
+pub async fn get_format_settings() -> Formatter {
+    format::formatter().clone()
+}
@@ -0,0 +1,17 @@
+use rust_yu_lib::lister::suites::{self, SoftwareSuite};
+
+use super::CommandError;
+
+/// 列出已安装的软件套件
+#[tauri::command]
+pub async fn list_suites() -> Result<Vec<SoftwareSuite>, CommandError> {
+    suites::list_suites().map_err(CommandError::from)
+}
+
+/// 查找程序所属的套件，成员按卸载顺序排列
+#[tauri::command]
+pub async fn get_program_suite(
+    program_name: String,
+) -> Result<Option<SoftwareSuite>, CommandError> {
+    suites::find_suite(&program_name).map_err(CommandError::from)
+}
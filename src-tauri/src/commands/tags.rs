@@ -0,0 +1,44 @@
+use rust_yu_lib::lister::{self, models::InstalledProgram, tags};
+
+use super::CommandError;
+
+pub use tags::TagSummary;
+
+fn find_program(program_name: &str) -> Result<InstalledProgram, CommandError> {
+    lister::find_program_by_name(program_name)
+        .ok_or_else(|| CommandError::new(format!("未找到程序: {}", program_name)))
+}
+
+#[tauri::command]
+pub async fn list_tags() -> Result<Vec<TagSummary>, CommandError> {
+    tags::list_tags().map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn get_program_tags(program_name: String) -> Result<Vec<String>, CommandError> {
+    let mut programs = vec![find_program(&program_name)?];
+    tags::apply_tags(&mut programs).map_err(CommandError::from)?;
+    Ok(programs.remove(0).tags)
+}
+
+#[tauri::command]
+pub async fn add_program_tag(program_name: String, tag: String) -> Result<(), CommandError> {
+    let program = find_program(&program_name)?;
+    tags::add_tag(&program, &tag).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn remove_program_tag(program_name: String, tag: String) -> Result<bool, CommandError> {
+    let program = find_program(&program_name)?;
+    tags::remove_tag(&program, &tag).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn rename_tag(old_tag: String, new_tag: String) -> Result<usize, CommandError> {
+    tags::rename_tag(&old_tag, &new_tag).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn delete_tag(tag: String) -> Result<usize, CommandError> {
+    tags::delete_tag(&tag).map_err(CommandError::from)
+}
@@ -0,0 +1,71 @@
+use rust_yu_lib::lister::{
+    self,
+    ignore_rules::{self, IgnoreRule, IgnoreRuleKind},
+    kept_traces::{self, KeptTrace},
+    models::InstalledProgram,
+    notes, pins,
+};
+
+use super::CommandError;
+
+/// 程序详情：包含标签和备注
+#[tauri::command]
+pub async fn get_program_detail(program_name: String) -> Result<InstalledProgram, CommandError> {
+    lister::get_program_detail(&program_name)
+        .ok_or_else(|| CommandError::new(format!("未找到程序: {}", program_name)))
+}
+
+/// 设置程序备注，内容为空时删除
+#[tauri::command]
+pub async fn set_program_note(program_name: String, note: String) -> Result<(), CommandError> {
+    let program = lister::find_program_by_name(&program_name)
+        .ok_or_else(|| CommandError::new(format!("未找到程序: {}", program_name)))?;
+    notes::set_note(&program, &note).map_err(CommandError::from)
+}
+
+/// 置顶或取消置顶程序
+#[tauri::command]
+pub async fn set_program_pinned(program_name: String, pinned: bool) -> Result<(), CommandError> {
+    let program = lister::find_program_by_name(&program_name)
+        .ok_or_else(|| CommandError::new(format!("未找到程序: {}", program_name)))?;
+    pins::set_pinned(&program, pinned).map_err(CommandError::from)
+}
+
+/// 标记或取消标记程序的保留痕迹，之后扫描该程序时不再报告
+#[tauri::command]
+pub async fn set_trace_kept(
+    program_name: String,
+    path: String,
+    kept: bool,
+) -> Result<bool, CommandError> {
+    kept_traces::set_kept(&program_name, &path, kept).map_err(CommandError::from)
+}
+
+/// 程序的保留痕迹，未指定程序时返回全部
+#[tauri::command]
+pub async fn list_kept_traces(
+    program_name: Option<String>,
+) -> Result<Vec<KeptTrace>, CommandError> {
+    kept_traces::list_kept(program_name.as_deref()).map_err(CommandError::from)
+}
+
+/// 全局忽略规则，供设置页展示
+#[tauri::command]
+pub async fn list_ignore_rules() -> Result<Vec<IgnoreRule>, CommandError> {
+    ignore_rules::list_rules().map_err(CommandError::from)
+}
+
+/// 添加全局忽略规则，之后扫描任何程序时命中的痕迹都不再报告
+#[tauri::command]
+pub async fn add_ignore_rule(
+    kind: IgnoreRuleKind,
+    pattern: String,
+) -> Result<IgnoreRule, CommandError> {
+    ignore_rules::add_rule(kind, &pattern).map_err(CommandError::from)
+}
+
+/// 删除全局忽略规则，返回规则是否存在
+#[tauri::command]
+pub async fn remove_ignore_rule(id: i64) -> Result<bool, CommandError> {
+    ignore_rules::remove_rule(id).map_err(CommandError::from)
+}
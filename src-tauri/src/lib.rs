@@ -4,72 +4,138 @@ use commands::*;
 
 use std::path::PathBuf;
 
-use tauri::Manager;
-use warp::Filter;
+use tauri::{Emitter, Manager};
+use warp::{Filter, Reply};
+
+/// 程序列表缓存后台刷新完成事件
+const PROGRAM_LIST_REFRESHED_EVENT: &str = "program-list-refreshed";
+
+/// NDJSON 响应的内容类型
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
 
 #[derive(Debug, serde::Deserialize)]
 struct IconFileQuery {
     path: String,
 }
 
+#[derive(Debug, serde::Deserialize)]
+struct ProgramsQuery {
+    /// 输出格式，`ndjson` 时逐行流式输出
+    format: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct TraceScanQuery {
+    program: String,
+    min_confidence: Option<String>,
+}
+
+/// 程序列表接口返回的字段
+fn program_api_json(p: &rust_yu_lib::lister::models::InstalledProgram) -> serde_json::Value {
+    serde_json::json!({
+        "id": p.id,
+        "name": p.name,
+        "publisher": p.publisher,
+        "version": p.version,
+        "install_location": p.install_location,
+        "install_date": p.install_date,
+        "uninstall_string": p.uninstall_string,
+        "install_source": p.install_source.to_string(),
+        "size": p.size,
+        "icon_path": p.icon_path,
+        "icon_cache_path_32": p.icon_cache_path_32,
+        "icon_cache_path_48": p.icon_cache_path_48,
+        "size_last_updated_at": p.size_last_updated_at,
+        "icon_data_url": p.icon_data_url,
+        "icon_data_url_32": p.icon_data_url_32,
+        "icon_data_url_48": p.icon_data_url_48,
+        "estimated_size": p.estimated_size,
+        "install_date_source": p.install_date_source,
+        "install_date_confidence": p.install_date_confidence,
+        "icon_source": p.icon_source,
+        "icon_confidence": p.icon_confidence,
+        "size_source": p.size_source,
+        "size_confidence": p.size_confidence,
+        "metadata_confidence": p.metadata_confidence,
+    })
+}
+
+/// 以 NDJSON 逐行发送程序列表，客户端无需等待完整响应即可开始处理
+fn ndjson_programs_response(
+    result: Result<rust_yu_lib::lister::models::ProgramListResponse, rust_yu_lib::UninstallerError>,
+) -> warp::reply::Response {
+    let (mut sender, body) = warp::hyper::Body::channel();
+    tokio::spawn(async move {
+        let lines: Box<dyn Iterator<Item = serde_json::Value> + Send> = match result {
+            Ok(program_response) => Box::new(
+                program_response
+                    .programs
+                    .into_iter()
+                    .map(|p| program_api_json(&p)),
+            ),
+            Err(e) => Box::new(std::iter::once(
+                serde_json::json!({ "error": e.to_string() }),
+            )),
+        };
+        for line in lines {
+            let mut chunk = line.to_string().into_bytes();
+            chunk.push(b'\n');
+            // 客户端断开后停止发送
+            if sender.send_data(chunk.into()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut response = warp::reply::Response::new(body);
+    response.headers_mut().insert(
+        warp::http::header::CONTENT_TYPE,
+        warp::http::HeaderValue::from_static(NDJSON_CONTENT_TYPE),
+    );
+    response
+}
+
 // 启动 HTTP API 服务器（用于开发模式）
 fn start_api_server() {
     use rust_yu_lib::modules::lister;
 
-    // 获取程序列表的 API 路由
+    // 获取程序列表的 API 路由，`?format=ndjson` 时逐行流式输出
     let programs_route = warp::path!("api" / "programs")
         .and(warp::get())
-        .map(move || {
+        .and(warp::query::<ProgramsQuery>())
+        .map(move |query: ProgramsQuery| {
             // 调用缓存版本接口，避免每次请求都重复做图标/大小计算
-            let query = lister::models::ListProgramsQuery {
+            let query_options = lister::models::ListProgramsQuery {
                 source: Some(lister::models::InstallSource::Registry),
                 search: None,
                 refresh: false,
                 cache_ttl_seconds: lister::storage::DEFAULT_CACHE_TTL_SECONDS,
+                allow_stale: true,
+                tags: Vec::new(),
+                pinned_first: false,
+                bloatware_only: false,
+                unsigned_only: false,
             };
-            let result = lister::list_programs_with_cache(query);
+            let result = lister::list_programs_with_cache(query_options);
+            if let Err(e) = &result {
+                tracing::error!("Failed to list programs: {}", e);
+            }
+
+            if query.format.as_deref() == Some("ndjson") {
+                return ndjson_programs_response(result);
+            }
 
             // 转换为 API 响应格式
             let response: Vec<serde_json::Value> = match result {
                 Ok(program_response) => program_response
                     .programs
                     .iter()
-                    .map(|p| {
-                        serde_json::json!({
-                            "id": p.id,
-                            "name": p.name,
-                            "publisher": p.publisher,
-                            "version": p.version,
-                            "install_location": p.install_location,
-                            "install_date": p.install_date,
-                            "uninstall_string": p.uninstall_string,
-                            "install_source": p.install_source.to_string(),
-                            "size": p.size,
-                            "icon_path": p.icon_path,
-                            "icon_cache_path_32": p.icon_cache_path_32,
-                            "icon_cache_path_48": p.icon_cache_path_48,
-                            "size_last_updated_at": p.size_last_updated_at,
-                            "icon_data_url": p.icon_data_url,
-                            "icon_data_url_32": p.icon_data_url_32,
-                            "icon_data_url_48": p.icon_data_url_48,
-                            "estimated_size": p.estimated_size,
-                            "install_date_source": p.install_date_source,
-                            "install_date_confidence": p.install_date_confidence,
-                            "icon_source": p.icon_source,
-                            "icon_confidence": p.icon_confidence,
-                            "size_source": p.size_source,
-                            "size_confidence": p.size_confidence,
-                            "metadata_confidence": p.metadata_confidence,
-                        })
-                    })
+                    .map(program_api_json)
                     .collect(),
-                Err(e) => {
-                    tracing::error!("Failed to list programs: {}", e);
-                    vec![serde_json::json!({ "error": e.to_string() })]
-                }
+                Err(e) => vec![serde_json::json!({ "error": e.to_string() })],
             };
 
-            warp::reply::json(&response)
+            warp::reply::json(&response).into_response()
         });
 
     // 读取图标缓存文件（仅允许 icon-cache 目录）
@@ -117,13 +183,55 @@ fn start_api_server() {
             }
         });
 
+    // 扫描程序痕迹，可按最低置信度过滤
+    let traces_route = warp::path!("api" / "traces")
+        .and(warp::get())
+        .and(warp::query::<TraceScanQuery>())
+        .then(|query: TraceScanQuery| async move {
+            match run_scan(
+                &query.program,
+                None,
+                query.min_confidence.as_deref(),
+                &rust_yu_lib::scanner::options::ScanOptions::default(),
+                None,
+            )
+            .await
+            {
+                Ok(summary) => warp::reply::json(&summary),
+                Err(error) => {
+                    tracing::error!("Failed to scan traces: {}", error.message);
+                    warp::reply::json(&serde_json::json!({ "error": error.message }))
+                }
+            }
+        });
+
+    // rust-yu 自身的存储位置与占用
+    let storage_route = warp::path!("api" / "storage")
+        .and(warp::get())
+        .then(|| async move {
+            match tokio::task::spawn_blocking(rust_yu_lib::lister::storage_usage::storage_info)
+                .await
+            {
+                Ok(Ok(info)) => warp::reply::json(&info),
+                Ok(Err(error)) => {
+                    tracing::error!("Failed to read storage info: {}", error);
+                    warp::reply::json(&serde_json::json!({ "error": error.to_string() }))
+                }
+                Err(error) => warp::reply::json(&serde_json::json!({ "error": error.to_string() })),
+            }
+        });
+
     // 仅开发调试使用：允许本地前端页面跨域读取程序列表
     let cors = warp::cors()
         .allow_any_origin()
         .allow_methods(vec!["GET"])
         .allow_headers(vec!["content-type"]);
 
-    let routes = programs_route.or(icon_route).with(cors);
+    let routes = programs_route
+        .or(icon_route)
+        .or(traces_route)
+        .or(storage_route)
+        .with(cors);
 
     // 在后台线程启动服务器
     std::thread::spawn(move || {
@@ -154,16 +262,63 @@ pub fn run() {
         .setup(|app| {
             tracing::info!("Rust Yu Tauri 应用启动");
             let _ = app.get_webview_window("main");
+
+            // 程序列表缓存后台刷新完成后通知前端重新拉取
+            let app_handle = app.handle().clone();
+            rust_yu_lib::lister::set_cache_refresh_listener(move |state| {
+                if let Err(error) = app_handle.emit(PROGRAM_LIST_REFRESHED_EVENT, state) {
+                    tracing::warn!("发送程序列表刷新事件失败: {}", error);
+                }
+            });
+            // 大目录的大小在空闲时分段算完
+            rust_yu_lib::lister::start_idle_size_scan();
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             list_programs,
+            get_debloat_suggestions,
             search_programs,
+            get_saved_program_history,
             scan_traces,
+            scan_trace_summary,
+            preview_registry_trace,
+            preview_file_trace,
             clean_traces,
+            list_clean_sessions,
+            resume_clean_session,
+            discard_clean_session,
+            simulate_clean,
+            restore_clean_archive,
+            create_review_session,
+            list_review_sessions,
+            get_review_session,
+            annotate_trace,
+            delete_review_session,
+            clean_approved_traces,
             uninstall_program,
             get_reports,
             delete_report,
+            get_leftover_statistics,
+            verify_storage,
+            get_storage_info,
+            clear_storage_area,
+            get_format_settings,
+            list_tags,
+            get_program_tags,
+            add_program_tag,
+            remove_program_tag,
+            rename_tag,
+            delete_tag,
+            get_program_detail,
+            set_program_note,
+            set_program_pinned,
+            set_trace_kept,
+            list_kept_traces,
+            list_ignore_rules,
+            add_ignore_rule,
+            remove_ignore_rule,
+            list_suites,
+            get_program_suite,
         ])
         .run(tauri::generate_context!())
         .expect("启动 Tauri 应用时出错");
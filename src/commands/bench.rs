@@ -0,0 +1,141 @@
+use crate::modules::common::profiling::StageTiming;
+use crate::modules::scanner::models::{Trace, TraceType};
+use crate::modules::{cleaner, lister, scanner};
+use anyhow::Result;
+use clap::Parser;
+use serde::Serialize;
+
+/// 清理基准使用的临时文件数量
+const CLEAN_BENCH_FILES: usize = 50;
+
+#[derive(Parser, Debug)]
+pub struct BenchCommand {
+    /// 用于残留扫描基准的程序名称 (不指定则跳过扫描阶段)
+    pub program_name: Option<String>,
+
+    /// 过滤来源 (registry|msi|store|features|standard)
+    #[arg(long, default_value = "standard")]
+    pub source: String,
+
+    /// 输出格式 (table/json)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    program_count: usize,
+    trace_count: Option<usize>,
+    timings: Vec<StageTiming>,
+}
+
+pub async fn execute(cmd: BenchCommand) -> Result<()> {
+    let source = match cmd.source.as_str() {
+        "registry" => Some(lister::models::InstallSource::Registry),
+        "msi" => Some(lister::models::InstallSource::Msi),
+        "store" => Some(lister::models::InstallSource::Store),
+        "features" => Some(lister::models::InstallSource::Feature),
+        _ => None,
+    };
+
+    let mut timings = Vec::new();
+
+    // 1. 程序列表：绕过缓存以测量完整的枚举与元数据补全
+    let query = lister::models::ListProgramsQuery {
+        source,
+        search: None,
+        refresh: true,
+        cache_ttl_seconds: lister::storage::DEFAULT_CACHE_TTL_SECONDS,
+        allow_stale: false,
+        tags: Vec::new(),
+        pinned_first: false,
+        bloatware_only: false,
+        unsigned_only: false,
+    };
+    let response = lister::list_programs_with_cache(query)?;
+    let program_count = response.programs.len();
+    timings.extend(response.timings);
+
+    // 2. 残留扫描：每个扫描器单独计时
+    let trace_count = match &cmd.program_name {
+        Some(name) => {
+            let aliases = lister::find_program_aliases(name);
+            let summary = scanner::scan_all_traces_with_summary(
+                name,
+                &aliases,
+                None,
+                None,
+                &scanner::options::ScanOptions::default(),
+                None,
+            )
+            .await?;
+            timings.extend(summary.timings);
+            timings.push(StageTiming {
+                stage: "scan_total".to_string(),
+                duration_ms: summary.duration_ms,
+            });
+            Some(summary.traces.len())
+        }
+        None => None,
+    };
+
+    // 3. 清理：删除临时目录中生成的文件，不触及真实痕迹
+    let clean_timing = bench_cleaning().await?;
+    timings.push(clean_timing);
+
+    let report = BenchReport {
+        program_count,
+        trace_count,
+        timings,
+    };
+
+    match cmd.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print_table(&report),
+    }
+
+    Ok(())
+}
+
+/// 在临时目录中生成文件并测量清理耗时
+async fn bench_cleaning() -> Result<StageTiming> {
+    let dir = std::env::temp_dir().join(format!("rust-yu-bench-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+
+    let mut traces = Vec::with_capacity(CLEAN_BENCH_FILES);
+    for index in 0..CLEAN_BENCH_FILES {
+        let path = dir.join(format!("trace-{}.tmp", index));
+        std::fs::write(&path, b"rust-yu bench")?;
+        traces.push(Trace::new(
+            "rust-yu-bench".to_string(),
+            TraceType::File,
+            path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let started_at = std::time::Instant::now();
+    let result = cleaner::clean_traces(traces, true).await;
+    let timing = StageTiming::new("cleaning", started_at.elapsed());
+
+    let _ = std::fs::remove_dir_all(&dir);
+    result?;
+
+    Ok(timing)
+}
+
+fn print_table(report: &BenchReport) {
+    println!("\n{}", "=".repeat(50));
+    println!("{:<30} {:>15}", "阶段", "耗时 (ms)");
+    println!("{}", "=".repeat(50));
+
+    for timing in &report.timings {
+        println!("{:<30} {:>15.1}", timing.stage, timing.duration_ms);
+    }
+
+    println!("{}", "=".repeat(50));
+    println!("程序数: {}", report.program_count);
+    if let Some(count) = report.trace_count {
+        println!("痕迹数: {}", count);
+    }
+    println!();
+}
@@ -1,12 +1,24 @@
+use crate::modules::common::{format, utils};
+use crate::modules::lister::models::{InstalledProgram, InstallerKind};
+use crate::modules::uninstaller::command::{self as uninstall_command, ExitOutcome};
+use crate::modules::uninstaller::{clickonce, signature, validation};
 use crate::modules::{cleaner, lister, reporter, scanner};
 use anyhow::Result;
-use chrono::Utc;
 use clap::Parser;
 
 #[derive(Parser, Debug)]
 pub struct CleanCommand {
     /// 程序名称、ID 或卸载命令
-    pub target: String,
+    #[arg(required_unless_present_any = ["resume", "review", "restore_archive"])]
+    pub target: Option<String>,
+
+    /// 恢复中断的清理会话，只处理剩余和失败的项
+    #[arg(long, conflicts_with_all = ["from_file", "uninstall", "computer"])]
+    pub resume: Option<String>,
+
+    /// 只清理审核会话中已批准的痕迹（界面或 API 中完成审核）
+    #[arg(long, conflicts_with_all = ["resume", "from_file", "uninstall", "computer"])]
+    pub review: Option<String>,
 
     /// 确认删除 (不指定则预览)
     #[arg(long)]
@@ -16,6 +28,38 @@ pub struct CleanCommand {
     #[arg(long, default_value = "all")]
     pub trace_type: String,
 
+    /// 只删除这些类型的痕迹，逗号分隔 (registry/files/appdata/shortcuts/activex/history/network/firewall/env/menu/cred/eventlog/wmi/defender/startup)，优先于 --trace-type；网络设置影响整机联网，只在显式指定 network 时清理
+    #[arg(long, value_delimiter = ',')]
+    pub types: Vec<String>,
+
+    /// 只删除达到该置信度的痕迹 (high/medium/low)；远程清理默认 high
+    #[arg(long)]
+    pub min_confidence: Option<String>,
+
+    /// 只删除不小于该大小的痕迹 (如 100MB)，大小未知的痕迹会被跳过
+    #[arg(long, value_parser = parse_size_arg)]
+    pub min_size: Option<u64>,
+
+    /// 只删除不大于该大小的痕迹 (如 1GB)，大小未知的痕迹会被跳过
+    #[arg(long, value_parser = parse_size_arg)]
+    pub max_size: Option<u64>,
+
+    /// 只删除至少这么多天未修改的痕迹
+    #[arg(long)]
+    pub min_age_days: Option<u64>,
+
+    /// 只删除最近这么多天内修改过的痕迹
+    #[arg(long)]
+    pub max_age_days: Option<u64>,
+
+    /// 从 `search --output` 导出的文件（JSON 或审核页面）读取痕迹，不再重新扫描；带审核结果时只清理已批准的痕迹
+    #[arg(long)]
+    pub from_file: Option<String>,
+
+    /// 删除从文件读取的、校验判定为过时的痕迹（默认跳过）
+    #[arg(long)]
+    pub allow_stale: bool,
+
     /// 排除的痕迹 ID (可多次指定)
     #[arg(long)]
     pub exclude: Vec<String>,
@@ -24,7 +68,7 @@ pub struct CleanCommand {
     #[arg(long)]
     pub report: bool,
 
-    /// 报告输出路径
+    /// 额外输出 HTML 报告的路径（报告总会保存到报告目录）
     #[arg(long)]
     pub report_path: Option<String>,
 
@@ -35,30 +79,203 @@ pub struct CleanCommand {
     /// 卸载命令 (如果 target 不是已安装的程序)
     #[arg(long)]
     pub uninstall_string: Option<String>,
+
+    /// 允许执行校验判定为可疑的卸载命令 (危险命令始终拒绝)
+    #[arg(long)]
+    pub allow_suspicious: bool,
+
+    /// 严格签名模式：卸载程序未签名或签名者与发布者不一致时拒绝执行
+    #[arg(long)]
+    pub strict_signature: bool,
+
+    /// 同时删除属于其他用户的痕迹 (需与 --confirm 一起使用)
+    #[arg(long)]
+    pub include_other_users: bool,
+
+    /// 同时删除可能包含用户数据的痕迹 (需与 --confirm 一起使用)
+    #[arg(long)]
+    pub include_user_data: bool,
+
+    /// 同时删除游戏存档、文档等用户创建的内容 (需与 --confirm 一起使用)
+    #[arg(long)]
+    pub include_user_content: bool,
+
+    /// 同时扫描其他用户的 AppData 和已登录用户的注册表 (需要管理员权限)
+    #[arg(long)]
+    pub all_users: bool,
+
+    /// 扫描其他用户时临时加载未登录用户的 NTUSER.DAT
+    #[arg(long, requires = "all_users")]
+    pub load_offline_hives: bool,
+
+    /// 删除文件和 AppData 痕迹前先压缩归档到隔离区，之后可用 --restore-archive 恢复
+    #[arg(long)]
+    pub archive: bool,
+
+    /// 将归档中的痕迹解压回原始位置（不覆盖已存在的文件）
+    #[arg(long, conflicts_with_all = ["resume", "review", "from_file", "uninstall", "computer"])]
+    pub restore_archive: Option<String>,
+
+    /// 远程计算机名，通过 PowerShell 远程处理 (WinRM) 操作该计算机
+    #[arg(long)]
+    pub computer: Option<String>,
+
+    #[command(flatten)]
+    pub scan: super::search::ScanArgs,
 }
 
 pub async fn execute(cmd: CleanCommand) -> Result<()> {
+    if let Some(zip_path) = &cmd.restore_archive {
+        let summary = cleaner::archive::restore_archive(std::path::Path::new(zip_path))?;
+        for path in &summary.traces {
+            println!("  已恢复: {}", path);
+        }
+        println!(
+            "\n恢复 {} 个文件，跳过 {} 个已存在的文件",
+            summary.files_restored, summary.files_skipped
+        );
+        return Ok(());
+    }
+
+    if let Some(session_id) = &cmd.resume {
+        let mut session = match cleaner::session::load_session(session_id) {
+            Ok(session) => session,
+            Err(error) => {
+                let sessions = cleaner::session::list_sessions().unwrap_or_default();
+                if !sessions.is_empty() {
+                    println!("未完成的清理会话:");
+                    for session in &sessions {
+                        println!(
+                            "  {}  {}  剩余 {} 项，失败 {} 项",
+                            session.id,
+                            session.program_name,
+                            session.pending.len(),
+                            session.failed.len()
+                        );
+                    }
+                    println!();
+                }
+                return Err(error.into());
+            }
+        };
+        println!(
+            "恢复清理会话 {} ({}): 剩余 {} 项，失败待重试 {} 项\n",
+            session.id,
+            session.program_name,
+            session.pending.len(),
+            session.failed.len()
+        );
+        if !cmd.confirm {
+            println!("使用 --confirm 确认继续清理");
+            return Ok(());
+        }
+        // 恢复的会话已在首次执行时记录反馈
+        return run_session(&mut session, &cmd, Vec::new(), None).await;
+    }
+
+    if let Some(review_id) = &cmd.review {
+        let review = match cleaner::review::load_review(review_id) {
+            Ok(review) => review,
+            Err(error) => {
+                let reviews = cleaner::review::list_reviews().unwrap_or_default();
+                if !reviews.is_empty() {
+                    println!("审核会话:");
+                    for review in &reviews {
+                        println!(
+                            "  {}  {}  已批准 {} 项，待审核 {} 项",
+                            review.id,
+                            review.program_name,
+                            review.approved_traces().len(),
+                            review.unresolved_count()
+                        );
+                    }
+                    println!();
+                }
+                return Err(error.into());
+            }
+        };
+        let approved = review.approved_traces();
+        println!(
+            "审核会话 {} ({}): 已批准 {} 项，共 {} 项",
+            review.id,
+            review.program_name,
+            approved.len(),
+            review.traces.len()
+        );
+        let unresolved = review.unresolved_count();
+        if unresolved > 0 {
+            println!("  还有 {} 项未完成审核，只清理已批准的项", unresolved);
+        }
+        println!();
+        if approved.is_empty() {
+            println!("没有已批准的痕迹");
+            return Ok(());
+        }
+        if !cmd.confirm {
+            for trace in &approved {
+                println!(
+                    "  [{}] {}{}",
+                    trace.trace_type,
+                    trace.path,
+                    trace_markers(trace)
+                );
+            }
+            println!("\n使用 --confirm 确认清理");
+            return Ok(());
+        }
+        let options = cleaner::models::CleanOptions {
+            include_other_users: cmd.include_other_users,
+            include_user_data: cmd.include_user_data,
+            include_user_content: cmd.include_user_content,
+            archive: cmd.archive,
+        };
+        let mut session =
+            cleaner::session::CleanSession::new(&review.program_name, approved, options);
+        let rejected = review.rejected_traces();
+        return run_session(&mut session, &cmd, Vec::new(), Some(rejected.as_slice())).await;
+    }
+
+    let target = cmd.target.clone().unwrap_or_default();
+    scanner::profiles::set_process_scope(scanner::profiles::ProfileScope::from_flags(
+        cmd.all_users,
+        cmd.load_offline_hives,
+    ));
+    if let Some(computer) = &cmd.computer {
+        // 远程清理不支持其他筛选，宁可报错也不能静默扩大删除范围
+        if !cmd.types.is_empty() || !clean_filter(&cmd).is_empty() {
+            anyhow::bail!("远程清理暂不支持 --types 和大小、时间筛选");
+        }
+        // 远程按名称搜索，默认只删除高置信度的痕迹
+        let min_confidence = cmd
+            .min_confidence
+            .as_deref()
+            .map(str::parse::<scanner::models::Confidence>)
+            .transpose()?
+            .unwrap_or(scanner::models::Confidence::High);
+        return super::remote::clean(computer, &target, cmd.confirm, &cmd.exclude, min_confidence);
+    }
+
     // 1. 如果指定了 --uninstall，先尝试卸载程序
     if cmd.uninstall {
-        println!("正在尝试卸载程序: {}\n", cmd.target);
+        println!("正在尝试卸载程序: {}\n", target);
 
         let uninstall_result = if let Some(uninstall_str) = &cmd.uninstall_string {
             // 使用指定的卸载命令
-            run_uninstall_command(uninstall_str).await
+            run_uninstall_command(uninstall_str, None, &cmd).await
         } else {
             // 搜索已安装的程序并获取卸载命令
-            let programs = lister::list_all_programs(None, Some(&cmd.target))?;
+            let programs = lister::list_all_programs(None, Some(&target))?;
             if let Some(program) = programs
                 .iter()
-                .find(|p| p.name.to_lowercase().contains(&cmd.target.to_lowercase()))
+                .find(|p| p.name.to_lowercase().contains(&target.to_lowercase()))
             {
                 if let Some(uninstall_str) = &program.uninstall_string {
-                    run_uninstall_command(uninstall_str).await
+                    run_uninstall_command(uninstall_str, Some(program), &cmd).await
                 } else {
                     anyhow::bail!("程序没有卸载命令")
                 }
             } else {
-                anyhow::bail!("未找到程序: {}", cmd.target)
+                anyhow::bail!("未找到程序: {}", target)
             }
         };
 
@@ -69,27 +286,73 @@ pub async fn execute(cmd: CleanCommand) -> Result<()> {
     }
 
     // 2. 搜索残留痕迹
-    println!("正在搜索残留痕迹...");
-    let trace_types = match cmd.trace_type.as_str() {
-        "registry" => vec![scanner::models::TraceType::RegistryKey],
-        "files" => vec![scanner::models::TraceType::File],
-        "appdata" => vec![scanner::models::TraceType::AppData],
-        "shortcuts" => vec![scanner::models::TraceType::Shortcut],
-        _ => vec![
-            scanner::models::TraceType::RegistryKey,
-            scanner::models::TraceType::File,
-            scanner::models::TraceType::AppData,
-            scanner::models::TraceType::Shortcut,
-        ],
+    let min_confidence = cmd
+        .min_confidence
+        .as_deref()
+        .map(str::parse::<scanner::models::Confidence>)
+        .transpose()?;
+    let trace_types = if cmd.types.is_empty() {
+        default_trace_types(&cmd.trace_type)
+    } else {
+        cmd.types
+            .iter()
+            .map(|name| parse_trace_type(name))
+            .collect::<Result<Vec<_>>>()?
     };
 
-    let all_traces = scanner::scan_all_traces(&cmd.target, Some(trace_types)).await?;
+    let (traces, scanners) = match &cmd.from_file {
+        Some(path) => (
+            load_exported_traces(path, &target, &cmd, &trace_types, min_confidence)?,
+            Vec::new(),
+        ),
+        None => {
+            println!("正在搜索残留痕迹...");
+            let (bar, on_progress) = super::search::scan_progress_bar();
+            // 有程序记录时按安装目录、卸载程序目录和产品代码一并匹配
+            let scan = match lister::find_program_record(&target) {
+                Some(program) => {
+                    scanner::scan_program_traces(
+                        &program,
+                        Some(trace_types),
+                        min_confidence,
+                        &cmd.scan.to_options(),
+                        Some(on_progress),
+                    )
+                    .await
+                }
+                None => {
+                    let aliases = lister::find_program_aliases(&target);
+                    scanner::scan_all_traces_with_summary(
+                        &target,
+                        &aliases,
+                        Some(trace_types),
+                        min_confidence,
+                        &cmd.scan.to_options(),
+                        Some(on_progress),
+                    )
+                    .await
+                }
+            };
+            bar.finish_and_clear();
+            let summary = scan?;
+            if summary.filtered_count > 0 {
+                println!("已跳过 {} 个置信度较低的痕迹", summary.filtered_count);
+            }
+            super::search::print_scanner_stats(&summary.scanners, false);
+            (summary.traces, summary.scanners)
+        }
+    };
 
-    // 过滤存在的和排除的
-    let traces_to_clean: Vec<_> = all_traces
+    // 过滤存在的和排除的，排除的痕迹作为清理反馈记录
+    let (excluded, traces_to_clean): (Vec<_>, Vec<_>) = traces
         .into_iter()
-        .filter(|t| t.exists && !cmd.exclude.contains(&t.id))
-        .collect();
+        .filter(|t| t.exists)
+        .partition(|t| cmd.exclude.contains(&t.id));
+    let (traces_to_clean, filtered_out) =
+        cleaner::selection::select_traces(traces_to_clean, &clean_filter(&cmd));
+    if filtered_out > 0 {
+        println!("已按大小或修改时间跳过 {} 个痕迹", filtered_out);
+    }
 
     println!("找到 {} 个残留痕迹\n", traces_to_clean.len());
 
@@ -98,28 +361,102 @@ pub async fn execute(cmd: CleanCommand) -> Result<()> {
         println!("=== 预览模式 ===");
         println!("使用 --confirm 确认删除\n");
 
-        for trace in &traces_to_clean {
-            let size = trace.size.map(|s| format_size(s)).unwrap_or_default();
+        let options = cleaner::models::CleanOptions {
+            include_other_users: cmd.include_other_users,
+            include_user_data: cmd.include_user_data,
+            include_user_content: cmd.include_user_content,
+            archive: cmd.archive,
+        };
+        let verdicts = cleaner::simulate_clean(&traces_to_clean, options);
+
+        for (trace, verdict) in traces_to_clean.iter().zip(&verdicts) {
+            let size = trace
+                .size
+                .map(utils::format_size)
+                .or_else(|| trace.registry_stats.map(|stats| stats.to_string()))
+                .unwrap_or_default();
             println!(
-                "  [{:12}] {} {}",
+                "  [{:12}] {} {}{}",
                 format!("{:?}", trace.trace_type),
                 trace.path,
                 if !size.is_empty() {
                     format!("({})", size)
                 } else {
                     String::new()
-                }
+                },
+                trace_markers(trace)
             );
+            if let Some(reason) = &verdict.reason {
+                println!("      将被拦截: {}", reason);
+            }
         }
 
         println!("\n共 {} 项", traces_to_clean.len());
+
+        let other_user_count = traces_to_clean.iter().filter(|t| t.owner.is_some()).count();
+        if other_user_count > 0 {
+            println!(
+                "其中 {} 项属于其他用户，需额外指定 --include-other-users 才会删除",
+                other_user_count
+            );
+        }
+
+        let user_data_count = traces_to_clean
+            .iter()
+            .filter(|t| t.user_data.is_some())
+            .count();
+        if user_data_count > 0 {
+            println!(
+                "其中 {} 项可能包含用户数据，需额外指定 --include-user-data 才会删除",
+                user_data_count
+            );
+        }
+
+        let cloud_sync_count = traces_to_clean
+            .iter()
+            .filter(|t| t.cloud_sync.is_some())
+            .count();
+        if cloud_sync_count > 0 {
+            println!(
+                "其中 {} 项位于 OneDrive 或漫游配置文件等同步目录，删除会同步到云端或服务器",
+                cloud_sync_count
+            );
+        }
         return Ok(());
     }
 
     // 4. 执行删除
     println!("=== 开始清理 ===\n");
 
-    let clean_results = cleaner::clean_traces(traces_to_clean, true).await?;
+    let options = cleaner::models::CleanOptions {
+        include_other_users: cmd.include_other_users,
+        include_user_data: cmd.include_user_data,
+        include_user_content: cmd.include_user_content,
+        archive: cmd.archive,
+    };
+    let mut session = cleaner::session::CleanSession::new(&target, traces_to_clean, options);
+    run_session(&mut session, &cmd, scanners, Some(excluded.as_slice())).await
+}
+
+/// 执行清理会话并输出统计；中断或有失败项时会话保留，可用 --resume 继续
+///
+/// `skipped` 为用户排除或审核拒绝的痕迹，与删除结果一起记录为清理反馈；为 None 时不记录
+async fn run_session(
+    session: &mut cleaner::session::CleanSession,
+    cmd: &CleanCommand,
+    scanners: Vec<scanner::models::ScannerStats>,
+    skipped: Option<&[scanner::models::Trace]>,
+) -> Result<()> {
+    println!("清理会话: {} (中断后可用 --resume 继续)", session.id);
+    if session.options.archive {
+        let zip_path = cleaner::archive::archive_path(&session.program_name, &session.id)?;
+        println!("删除前归档到: {}", zip_path.display());
+    }
+    println!();
+    let clean_results = cleaner::run_clean_session(session).await?;
+    if let Some(skipped) = skipped {
+        cleaner::record_feedback(&clean_results, skipped);
+    }
 
     // 5. 统计结果
     let success_count = clean_results.iter().filter(|r| r.success).count();
@@ -129,41 +466,109 @@ pub async fn execute(cmd: CleanCommand) -> Result<()> {
     println!("\n--- 清理完成 ---");
     println!("  成功: {}", success_count);
     println!("  失败: {}", failed_count);
-    println!("  释放空间: {}", format_size(total_freed));
+    println!("  释放空间: {}", utils::format_size(total_freed));
+    if !session.is_finished() {
+        println!(
+            "\n失败的项已保存到清理会话 {}，解决问题后可用 --resume {} 重试",
+            session.id, session.id
+        );
+    }
 
     // 6. 生成报告
     if cmd.report {
-        let report = reporter::models::UninstallerReport {
-            id: uuid::Uuid::new_v4().to_string(),
-            program_name: cmd.target.clone(),
-            generated_at: Utc::now(),
-            traces_found: vec![],
-            traces_removed: clean_results,
-            total_size_freed: total_freed,
-            success: failed_count == 0,
-            warnings: vec![],
-        };
-
-        let report_path = cmd
-            .report_path
-            .unwrap_or_else(|| format!("uninstall_report_{}.html", cmd.target));
-        let html = reporter::html::generate_html_report(&report)?;
-        std::fs::write(&report_path, html)?;
-        println!("\n报告已生成: {}", report_path);
+        let report = reporter::models::UninstallerReport::new(session.program_name.clone())
+            .with_results(clean_results)
+            .with_scanner_stats(scanners);
+
+        let stored = reporter::storage::save_report(&report)?;
+        println!(
+            "\n报告已生成: {}",
+            stored.html_path.as_deref().unwrap_or(&stored.json_path)
+        );
+
+        if let Some(report_path) = &cmd.report_path {
+            let html = reporter::html::generate_html_report(&report)?;
+            std::fs::write(report_path, html)?;
+            println!("报告已另存到: {}", report_path);
+        }
     }
 
     Ok(())
 }
 
-async fn run_uninstall_command(uninstall_string: &str) -> Result<()> {
-    // 处理常见的卸载命令格式
-    let cmd = if uninstall_string.to_lowercase().starts_with("msiexec") {
-        // MSI 卸载
-        format!("{} /quiet /norestart", uninstall_string)
-    } else {
-        // 普通卸载命令
-        uninstall_string.to_string()
-    };
+/// 读取导出文件中的痕迹，按类型和置信度筛选，并校验痕迹是否已过时
+fn load_exported_traces(
+    path: &str,
+    target: &str,
+    cmd: &CleanCommand,
+    trace_types: &[scanner::models::TraceType],
+    min_confidence: Option<scanner::models::Confidence>,
+) -> Result<Vec<scanner::models::Trace>> {
+    let export = scanner::export::load_trace_export(std::path::Path::new(path))?;
+    println!(
+        "从 {} 读取 {} 个痕迹 (格式版本 {})",
+        path,
+        export.traces.len(),
+        export.version
+    );
+    if !export.program_name.is_empty() && !export.program_name.eq_ignore_ascii_case(target) {
+        println!(
+            "注意: 导出文件针对的程序是 \"{}\"，与 \"{}\" 不同",
+            export.program_name, target
+        );
+    }
+
+    let exported_at = export.exported_at;
+    if let Some(approved) = &export.approved {
+        println!(
+            "导出文件已经过审核: 批准 {} 项，共 {} 项，只清理已批准的痕迹",
+            approved.len(),
+            export.traces.len()
+        );
+    }
+
+    let traces: Vec<_> = export
+        .into_selected_traces()
+        .into_iter()
+        .filter(|trace| trace_types.contains(&trace.trace_type))
+        .filter(|trace| min_confidence.is_none_or(|min| trace.confidence.meets(min)))
+        .collect();
+
+    let (mut valid, stale) = cleaner::revalidate::revalidate_traces(traces, target, exported_at);
+    if !stale.is_empty() {
+        println!(
+            "\n{} 个痕迹自扫描 ({}) 以来已变化:",
+            stale.len(),
+            format::format_datetime(&exported_at)
+        );
+        for item in &stale {
+            println!("  {}  {}", item.trace.path, item.reason);
+        }
+        if cmd.allow_stale {
+            println!("已指定 --allow-stale，仍然清理这些痕迹");
+            valid.extend(stale.into_iter().map(|item| item.trace));
+        } else {
+            println!("这些痕迹将被跳过，重新扫描或指定 --allow-stale 后再清理");
+        }
+        println!();
+    }
+
+    Ok(valid)
+}
+
+async fn run_uninstall_command(
+    uninstall_string: &str,
+    program: Option<&InstalledProgram>,
+    options: &CleanCommand,
+) -> Result<()> {
+    let publisher = program.and_then(|p| p.publisher.as_deref());
+    let kind = program.map_or(InstallerKind::Unknown, |p| p.installer_kind);
+
+    let checked = validation::ensure_uninstall_allowed(uninstall_string, options.allow_suspicious)?;
+    signature::check_command_signature(&checked, publisher, options.strict_signature)?;
+
+    // 按安装程序技术处理命令格式（MSI 静默卸载）
+    let cmd = uninstall_command::prepare_uninstall_command(uninstall_string, kind, false);
 
     tracing::info!("执行卸载命令: {}", cmd);
 
@@ -171,33 +576,109 @@ async fn run_uninstall_command(uninstall_string: &str) -> Result<()> {
     {
         use std::process::Command as StdCommand;
 
-        let output = if cmd.contains("msiexec") {
-            StdCommand::new("cmd").args(["/C", &cmd]).output()?
-        } else {
-            StdCommand::new("cmd").args(["/C", &cmd]).output()?
-        };
+        let output = StdCommand::new("cmd").args(["/C", &cmd]).output()?;
+        let outcome = output
+            .status
+            .code()
+            .map(|code| uninstall_command::classify_exit_code(kind, code));
+
+        match outcome {
+            Some(ExitOutcome::Success | ExitOutcome::AlreadyRemoved) => {}
+            Some(ExitOutcome::RebootRequired) => println!("卸载完成，需要重启计算机"),
+            _ => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                anyhow::bail!("卸载命令执行失败: {}", stderr);
+            }
+        }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("卸载命令执行失败: {}", stderr);
+        if kind == InstallerKind::ClickOnce {
+            clickonce::clean_online_app_cache()?;
         }
     }
 
     Ok(())
 }
 
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
+/// `--trace-type` 对应的痕迹类型，未知取值时扫描全部默认类型
+fn default_trace_types(trace_type: &str) -> Vec<scanner::models::TraceType> {
+    match trace_type {
+        "registry" | "files" | "appdata" | "shortcuts" => {
+            parse_trace_type(trace_type).into_iter().collect()
+        }
+        _ => vec![
+            scanner::models::TraceType::RegistryKey,
+            scanner::models::TraceType::RegistryValue,
+            scanner::models::TraceType::File,
+            scanner::models::TraceType::AppData,
+            scanner::models::TraceType::Shortcut,
+            scanner::models::TraceType::ActiveX,
+            scanner::models::TraceType::UsageHistory,
+            scanner::models::TraceType::FirewallRule,
+            scanner::models::TraceType::EnvironmentVariable,
+            scanner::models::TraceType::ContextMenu,
+            scanner::models::TraceType::Credential,
+            scanner::models::TraceType::EventLogSource,
+            scanner::models::TraceType::WmiProvider,
+            scanner::models::TraceType::DefenderExclusion,
+        ],
+    }
+}
+
+/// 解析 `--types` 中的单个类型，未知取值直接报错，避免脚本因拼写错误误删
+fn parse_trace_type(name: &str) -> Result<scanner::models::TraceType> {
+    match name.trim().to_lowercase().as_str() {
+        "registry" => Ok(scanner::models::TraceType::RegistryKey),
+        "files" => Ok(scanner::models::TraceType::File),
+        "appdata" => Ok(scanner::models::TraceType::AppData),
+        "shortcuts" => Ok(scanner::models::TraceType::Shortcut),
+        "activex" => Ok(scanner::models::TraceType::ActiveX),
+        "history" => Ok(scanner::models::TraceType::UsageHistory),
+        "network" => Ok(scanner::models::TraceType::NetworkSetting),
+        "firewall" => Ok(scanner::models::TraceType::FirewallRule),
+        "env" => Ok(scanner::models::TraceType::EnvironmentVariable),
+        "menu" => Ok(scanner::models::TraceType::ContextMenu),
+        "cred" => Ok(scanner::models::TraceType::Credential),
+        "eventlog" => Ok(scanner::models::TraceType::EventLogSource),
+        "wmi" => Ok(scanner::models::TraceType::WmiProvider),
+        "defender" => Ok(scanner::models::TraceType::DefenderExclusion),
+        "startup" => Ok(scanner::models::TraceType::RegistryValue),
+        other => anyhow::bail!(
+            "无效的痕迹类型: {}（可选 registry/files/appdata/shortcuts/activex/history/network/firewall/env/menu/cred/eventlog/wmi/defender/startup）",
+            other
+        ),
+    }
+}
+
+/// 预览中附加在痕迹后的提示标记（其他用户、可能含用户数据）
+pub fn trace_markers(trace: &scanner::models::Trace) -> String {
+    let mut markers = String::new();
+    if let Some(owner) = &trace.owner {
+        markers.push_str(&format!(" [用户: {}]", owner));
+    }
+    if trace.user_data.is_some() {
+        markers.push_str(" [可能含用户数据]");
     }
+    if trace.category == scanner::models::TraceCategory::UserContent {
+        markers.push_str(" [用户内容]");
+    }
+    if trace.category == scanner::models::TraceCategory::Privacy {
+        markers.push_str(" [隐私]");
+    }
+    if trace.cloud_sync.is_some() {
+        markers.push_str(" [云同步]");
+    }
+    markers
+}
+
+fn clean_filter(cmd: &CleanCommand) -> cleaner::models::CleanFilter {
+    cleaner::models::CleanFilter {
+        min_size: cmd.min_size,
+        max_size: cmd.max_size,
+        min_age_days: cmd.min_age_days,
+        max_age_days: cmd.max_age_days,
+    }
+}
+
+fn parse_size_arg(value: &str) -> Result<u64, String> {
+    crate::modules::common::utils::parse_size(value).map_err(|e| e.to_string())
 }
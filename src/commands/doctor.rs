@@ -0,0 +1,94 @@
+//! doctor 命令 - 检查并修复本地存储
+
+use crate::modules::common::utils;
+use crate::modules::lister::storage;
+use crate::modules::lister::storage_usage::{self, StorageArea};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct DoctorCommand {
+    /// 修复发现的问题：重建损坏的缓存、备份损坏的数据文件、删除悬空引用和孤立文件
+    #[arg(long)]
+    pub repair: bool,
+
+    /// 显示 rust-yu 各存储区域占用的磁盘空间
+    #[arg(long)]
+    pub usage: bool,
+
+    /// 清空存储区域 (cache/icons/quarantine/reports)，可多次指定
+    #[arg(long)]
+    pub clear: Vec<String>,
+
+    /// 输出格式 (table/json)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+pub async fn execute(cmd: DoctorCommand) -> Result<()> {
+    if cmd.usage || !cmd.clear.is_empty() {
+        return show_usage(&cmd);
+    }
+
+    let report = storage::verify(cmd.repair)?;
+
+    if cmd.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("存储目录: {}\n", storage::get_storage_root_dir()?.display());
+    if report.is_healthy() {
+        println!("未发现问题");
+        return Ok(());
+    }
+
+    for issue in &report.issues {
+        let status = match (cmd.repair, issue.repaired) {
+            (false, _) => "",
+            (true, true) => " [已修复]",
+            (true, false) => " [修复失败]",
+        };
+        println!("  [{}] {}{}", issue.component, issue.description, status);
+    }
+
+    println!("\n共 {} 个问题", report.issues.len());
+    if !cmd.repair {
+        println!("使用 --repair 修复");
+    } else if report.unresolved() > 0 {
+        println!("{} 个问题未能修复", report.unresolved());
+    }
+
+    Ok(())
+}
+
+/// 清空指定的存储区域并显示各区域占用
+fn show_usage(cmd: &DoctorCommand) -> Result<()> {
+    for name in &cmd.clear {
+        let area: StorageArea = name.parse()?;
+        let freed = storage_usage::clear_area(area)?;
+        println!("已清空{}，释放 {}", area.label(), utils::format_size(freed));
+    }
+    if !cmd.clear.is_empty() {
+        println!();
+    }
+
+    let info = storage_usage::storage_info()?;
+    if cmd.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("存储目录: {}", info.root);
+    println!("总占用: {}\n", utils::format_size(info.total_size));
+    for usage in &info.areas {
+        println!(
+            "  {:<8} {:>12}  {:>6} 个文件  {}",
+            usage.area.label(),
+            utils::format_size(usage.size),
+            usage.file_count,
+            usage.path
+        );
+    }
+    Ok(())
+}
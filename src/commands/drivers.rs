@@ -0,0 +1,137 @@
+//! drivers 命令 - 列出、删除第三方驱动包
+
+use crate::modules::cleaner;
+use crate::modules::scanner::drivers::{self, DriverPackage};
+use crate::modules::scanner::models::{Confidence, Trace, TraceType};
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+pub struct DriversCommand {
+    #[command(subcommand)]
+    pub action: DriversAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DriversAction {
+    /// 列出第三方驱动包
+    List {
+        /// 只列出提供商与该发布者一致的驱动包
+        #[arg(long)]
+        publisher: Option<String>,
+
+        /// 程序名，与 --publisher 一起用于判断关联程度
+        #[arg(long)]
+        program: Option<String>,
+
+        /// 输出格式 (table/json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// 从驱动存储中删除驱动包 (仍被设备使用的驱动包会被拒绝)
+    Delete {
+        /// 发布名，如 oem12.inf
+        published_name: String,
+
+        /// 确认删除 (不指定则只显示驱动包信息)
+        #[arg(long)]
+        confirm: bool,
+    },
+}
+
+pub async fn execute(cmd: DriversCommand) -> Result<()> {
+    match cmd.action {
+        DriversAction::List {
+            publisher,
+            program,
+            format,
+        } => list(publisher.as_deref(), program.as_deref(), &format),
+        DriversAction::Delete {
+            published_name,
+            confirm,
+        } => delete(&published_name, confirm).await,
+    }
+}
+
+fn list(publisher: Option<&str>, program: Option<&str>, format: &str) -> Result<()> {
+    let packages: Vec<(DriverPackage, Option<Confidence>)> = drivers::list_driver_packages()?
+        .into_iter()
+        .filter_map(|package| match publisher {
+            Some(_) => {
+                let confidence =
+                    drivers::correlate(&package, program.unwrap_or_default(), publisher)?;
+                Some((package, Some(confidence)))
+            }
+            None => Some((package, None)),
+        })
+        .collect();
+
+    if format == "json" {
+        let items: Vec<&DriverPackage> = packages.iter().map(|(p, _)| p).collect();
+        println!("{}", serde_json::to_string_pretty(&items)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "=".repeat(100));
+    println!(
+        "{:<12} {:<32} {:<22} {:<16} {:<8}",
+        "发布名", "原始名", "提供商", "版本", "关联"
+    );
+    println!("{}", "=".repeat(100));
+    for (package, confidence) in &packages {
+        let relation = match confidence {
+            Some(Confidence::High) => "高",
+            Some(Confidence::Medium) => "中",
+            Some(Confidence::Low) => "低",
+            None => "",
+        };
+        println!(
+            "{:<12} {:<32} {:<22} {:<16} {:<8}",
+            package.published_name,
+            package.original_name,
+            package.provider,
+            package.version,
+            relation
+        );
+    }
+    println!("{}", "=".repeat(100));
+    println!("总计: {} 个驱动包\n", packages.len());
+
+    Ok(())
+}
+
+async fn delete(published_name: &str, confirm: bool) -> Result<()> {
+    if !drivers::is_oem_inf(published_name) {
+        bail!("只能删除第三方驱动包 (oemN.inf): {}", published_name);
+    }
+
+    let package = drivers::list_driver_packages()?
+        .into_iter()
+        .find(|p| p.published_name.eq_ignore_ascii_case(published_name))
+        .ok_or_else(|| anyhow::anyhow!("未找到驱动包: {}", published_name))?;
+
+    println!(
+        "驱动包: {} ({}, {}, {})",
+        package.published_name, package.original_name, package.provider, package.class_name
+    );
+
+    if !confirm {
+        println!("使用 --confirm 确认删除");
+        return Ok(());
+    }
+
+    let trace = Trace::new(
+        package.provider.clone(),
+        TraceType::Driver,
+        package.published_name.clone(),
+    );
+    let results = cleaner::clean_traces(vec![trace], true).await?;
+    match results.first() {
+        Some(result) if result.success => println!("已删除驱动包: {}", result.path),
+        Some(result) => bail!("删除失败: {}", result.error.clone().unwrap_or_default()),
+        None => bail!("删除失败"),
+    }
+
+    Ok(())
+}
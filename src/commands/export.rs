@@ -0,0 +1,80 @@
+//! export 命令 - 导出 Intune / SCCM 可导入的程序清单
+
+use crate::modules::lister::{self, models::ListProgramsQuery, storage};
+use crate::modules::reporter::inventory::{self, InventoryStatus};
+use anyhow::{bail, Result};
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct ExportCommand {
+    /// 导出格式 (intune/sccm)
+    #[arg(long, default_value = "intune")]
+    pub format: String,
+
+    /// 输出文件路径 (不指定则输出到标准输出)
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 只导出名称包含关键词的程序
+    #[arg(short, long)]
+    pub search: Option<String>,
+
+    /// 不包含已卸载的程序
+    #[arg(long)]
+    pub installed_only: bool,
+}
+
+pub async fn execute(cmd: ExportCommand) -> Result<()> {
+    let query = ListProgramsQuery {
+        source: None,
+        search: cmd.search.clone(),
+        refresh: false,
+        cache_ttl_seconds: storage::DEFAULT_CACHE_TTL_SECONDS,
+        allow_stale: false,
+        tags: Vec::new(),
+        pinned_first: false,
+        bloatware_only: false,
+        unsigned_only: false,
+    };
+    let installed = lister::list_programs_with_cache(query)?.programs;
+
+    let history = if cmd.installed_only {
+        Vec::new()
+    } else {
+        let keyword = cmd.search.as_deref().unwrap_or_default().to_lowercase();
+        storage::get_program_history()?
+            .into_iter()
+            .filter(|entry| entry.program.name.to_lowercase().contains(&keyword))
+            .collect()
+    };
+
+    let records = inventory::build_inventory(&installed, &history);
+
+    let content = match cmd.format.as_str() {
+        "intune" => inventory::to_intune_json(&records)?,
+        "sccm" => {
+            let computer = std::env::var("COMPUTERNAME").unwrap_or_default();
+            inventory::to_sccm_csv(&records, &computer)
+        }
+        other => bail!("未知的导出格式: {} (可选 intune/sccm)", other),
+    };
+
+    match &cmd.output {
+        Some(path) => {
+            std::fs::write(path, content)?;
+            let removed = records
+                .iter()
+                .filter(|r| r.status == InventoryStatus::Removed)
+                .count();
+            println!(
+                "已导出 {} 个程序 (其中 {} 个已卸载) 到 {}",
+                records.len(),
+                removed,
+                path
+            );
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
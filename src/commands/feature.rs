@@ -0,0 +1,119 @@
+//! feature 命令 - 列出、启用或禁用 Windows 可选功能和功能按需
+
+use crate::modules::lister::features::{self, FeatureKind};
+use crate::modules::uninstaller::features as feature_state;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+pub struct FeatureCommand {
+    #[command(subcommand)]
+    pub action: FeatureAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FeatureAction {
+    /// 列出可选功能和功能按需
+    List {
+        /// 同时列出未启用的功能
+        #[arg(long)]
+        all: bool,
+
+        /// 搜索关键词
+        #[arg(short, long)]
+        search: Option<String>,
+
+        /// 输出格式 (table/json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// 启用功能
+    Enable {
+        /// 功能名或 Capability 名称
+        name: String,
+    },
+
+    /// 禁用（移除）功能
+    Disable {
+        /// 功能名或 Capability 名称
+        name: String,
+
+        /// 确认执行 (不指定则只显示将执行的命令)
+        #[arg(long)]
+        confirm: bool,
+    },
+}
+
+pub async fn execute(cmd: FeatureCommand) -> Result<()> {
+    match cmd.action {
+        FeatureAction::List {
+            all,
+            search,
+            format,
+        } => list(all, search.as_deref(), &format),
+        FeatureAction::Enable { name } => change(&name, true, true),
+        FeatureAction::Disable { name, confirm } => change(&name, false, confirm),
+    }
+}
+
+fn list(all: bool, search: Option<&str>, format: &str) -> Result<()> {
+    let keyword = search.unwrap_or_default().to_lowercase();
+    let items: Vec<_> = features::list_windows_features()?
+        .into_iter()
+        .filter(|f| all || f.enabled)
+        .filter(|f| f.name.to_lowercase().contains(&keyword))
+        .collect();
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&items)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "=".repeat(90));
+    println!("{:<60} {:<12} {:<15}", "名称", "类型", "状态");
+    println!("{}", "=".repeat(90));
+    for item in &items {
+        let kind = match item.kind {
+            FeatureKind::OptionalFeature => "可选功能",
+            FeatureKind::Capability => "功能按需",
+        };
+        println!("{:<60} {:<12} {:<15}", item.name, kind, item.state);
+    }
+    println!("{}", "=".repeat(90));
+    println!("总计: {} 项\n", items.len());
+
+    Ok(())
+}
+
+fn change(name: &str, enable: bool, confirm: bool) -> Result<()> {
+    let feature =
+        features::find_feature(name)?.ok_or_else(|| anyhow::anyhow!("未找到功能: {}", name))?;
+
+    if feature.enabled == enable {
+        println!(
+            "{} 已经是{}状态",
+            feature.name,
+            if enable { "启用" } else { "禁用" }
+        );
+        return Ok(());
+    }
+
+    if !confirm {
+        println!("将执行: {}", feature.disable_command());
+        println!("使用 --confirm 确认禁用");
+        return Ok(());
+    }
+
+    let result = feature_state::set_feature_state(&feature, enable)?;
+    println!(
+        "已{}: {}",
+        if result.enabled { "启用" } else { "禁用" },
+        result.name
+    );
+    if result.restart_required {
+        println!("需要重启计算机才能完成更改");
+    }
+
+    Ok(())
+}
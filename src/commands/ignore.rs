@@ -0,0 +1,65 @@
+//! ignore 命令 - 管理对所有程序生效的痕迹忽略规则
+
+use crate::modules::lister::ignore_rules::{self, IgnoreRuleKind};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+pub struct IgnoreCommand {
+    #[command(subcommand)]
+    pub action: IgnoreAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IgnoreAction {
+    /// 添加忽略规则，之后扫描任何程序时命中的痕迹都不再报告
+    Add {
+        /// 路径或注册表项前缀、通配符或正则表达式
+        pattern: String,
+
+        /// 规则类别：prefix（前缀，其下内容一并忽略）、glob（通配符）、regex（正则表达式）
+        #[arg(long, default_value = "prefix")]
+        kind: IgnoreRuleKind,
+    },
+
+    /// 列出全部忽略规则
+    List,
+
+    /// 按编号删除忽略规则
+    Remove {
+        /// `ignore list` 显示的规则编号
+        id: i64,
+    },
+}
+
+pub async fn execute(cmd: IgnoreCommand) -> Result<()> {
+    match cmd.action {
+        IgnoreAction::Add { pattern, kind } => {
+            let rule = ignore_rules::add_rule(kind, &pattern)?;
+            println!(
+                "已添加忽略规则 #{} [{}] {}",
+                rule.id, rule.kind, rule.pattern
+            );
+        }
+        IgnoreAction::List => {
+            let rules = ignore_rules::list_rules()?;
+            if rules.is_empty() {
+                println!("没有忽略规则");
+                return Ok(());
+            }
+            for rule in &rules {
+                println!("  #{:<4} [{}] {}", rule.id, rule.kind, rule.pattern);
+            }
+            println!("总计: {} 条规则", rules.len());
+        }
+        IgnoreAction::Remove { id } => {
+            if ignore_rules::remove_rule(id)? {
+                println!("已删除忽略规则 #{}", id);
+            } else {
+                println!("没有编号为 {} 的忽略规则", id);
+            }
+        }
+    }
+
+    Ok(())
+}
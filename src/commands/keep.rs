@@ -0,0 +1,52 @@
+//! keep 命令 - 标记程序的某些痕迹为有意保留，之后扫描和清理时跳过
+
+use crate::modules::lister::kept_traces;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct KeepCommand {
+    /// 程序名称，与 search/clean 使用的名称一致
+    pub program: Option<String>,
+
+    /// 要保留的路径或注册表项，保留目录时其下内容一并保留
+    pub path: Option<String>,
+
+    /// 取消保留
+    #[arg(long)]
+    pub remove: bool,
+
+    /// 列出保留的痕迹，未指定程序时列出全部
+    #[arg(long)]
+    pub list: bool,
+}
+
+pub async fn execute(cmd: KeepCommand) -> Result<()> {
+    let (program, path) = match (cmd.program.as_deref(), cmd.path.as_deref()) {
+        (program, _) if cmd.list => return print_kept(program),
+        (Some(program), Some(path)) => (program, path),
+        _ => anyhow::bail!("请指定程序名称和要保留的路径，或使用 --list 查看"),
+    };
+
+    let changed = kept_traces::set_kept(program, path, !cmd.remove)?;
+    match (cmd.remove, changed) {
+        (false, true) => println!("已保留 {} 的痕迹: {}", program, path),
+        (false, false) => println!("已经保留过: {}", path),
+        (true, true) => println!("已取消保留: {}", path),
+        (true, false) => println!("未保留过该路径: {}", path),
+    }
+    Ok(())
+}
+
+fn print_kept(program: Option<&str>) -> Result<()> {
+    let kept = kept_traces::list_kept(program)?;
+    if kept.is_empty() {
+        println!("没有保留的痕迹");
+        return Ok(());
+    }
+
+    for item in kept {
+        println!("  [{}] {}", item.program_name, item.path);
+    }
+    Ok(())
+}
@@ -0,0 +1,191 @@
+//! leftovers 命令 - 搜索并清理在 rust-yu 之外卸载的程序残留
+
+use crate::modules::common::utils;
+use crate::modules::scanner::leftovers;
+use crate::modules::scanner::models::Confidence;
+use crate::modules::uninstaller::arp;
+use crate::modules::{cleaner, lister};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+pub struct LeftoversCommand {
+    /// 确认删除全部残留 (不指定则预览)
+    #[arg(long)]
+    pub confirm: bool,
+
+    /// 只删除达到该置信度的残留 (high/medium/low)
+    #[arg(long, default_value = "high")]
+    pub min_confidence: String,
+
+    /// 输出格式 (table/json)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+
+    /// 将汇总报告保存为 JSON 文件
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 额外导入的程序清单快照（如重装系统前导出的快照），其中当前未安装的程序一并搜索，可重复指定
+    #[arg(long = "snapshot")]
+    pub snapshots: Vec<PathBuf>,
+}
+
+pub async fn execute(cmd: LeftoversCommand) -> Result<()> {
+    let json = cmd.format == "json";
+    let min_confidence: Confidence = cmd.min_confidence.parse()?;
+    if !json {
+        println!("正在对比安装历史和 Windows 卸载记录，搜索已卸载程序的残留...\n");
+    }
+
+    let report = leftovers::hunt_leftovers(&cmd.snapshots).await?;
+
+    if let Some(output) = &cmd.output {
+        std::fs::write(output, serde_json::to_string_pretty(&report)?)?;
+        status(json, format_args!("报告已保存到: {}", output));
+    }
+
+    match cmd.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print_report(&report),
+    }
+
+    if report.programs.is_empty() {
+        return Ok(());
+    }
+
+    if !cmd.confirm {
+        status(
+            json,
+            format_args!(
+                "使用 --confirm 清理以上达到 {:?} 置信度的残留",
+                min_confidence
+            ),
+        );
+        return Ok(());
+    }
+
+    status(json, format_args!("=== 开始批量清理 ===\n"));
+    let mut success_count = 0;
+    let mut failed_count = 0;
+    let mut kept_count = 0;
+    let mut total_freed = 0;
+
+    for removed in report.programs {
+        let (traces, kept): (Vec<_>, Vec<_>) = removed
+            .traces
+            .into_iter()
+            .partition(|trace| trace.confidence.meets(min_confidence));
+        kept_count += kept.len();
+
+        let results = cleaner::clean_traces(traces, true).await?;
+        let succeeded = results.iter().filter(|r| r.success).count();
+
+        success_count += succeeded;
+        failed_count += results.len() - succeeded;
+        total_freed += results.iter().map(|r| r.bytes_freed).sum::<u64>();
+
+        // 低于置信度而保留的残留下次仍需报告
+        let fully_cleaned = succeeded == results.len() && kept.is_empty();
+
+        // 卸载程序已不存在的条目在残留清理干净后从程序列表中移除
+        if removed.broken_entry && fully_cleaned {
+            match arp::remove_arp_entry(&removed.program) {
+                Ok(Some(removal)) => status(
+                    json,
+                    format_args!(
+                        "  已删除失效的卸载条目: {} (备份: {})",
+                        removed.program.name, removal.backup_path
+                    ),
+                ),
+                Ok(None) => {}
+                Err(e) => {
+                    failed_count += 1;
+                    status(
+                        json,
+                        format_args!("  删除失效的卸载条目失败 {}: {}", removed.program.name, e),
+                    );
+                    continue;
+                }
+            }
+        }
+
+        // 残留已清理的程序不再出现在后续报告中
+        if fully_cleaned {
+            lister::storage::delete_program_history(&removed.program.name)?;
+        }
+    }
+
+    status(json, format_args!("--- 清理完成 ---"));
+    status(json, format_args!("  成功: {}", success_count));
+    status(json, format_args!("  失败: {}", failed_count));
+    status(
+        json,
+        format_args!("  低于 {:?} 置信度而保留: {}", min_confidence, kept_count),
+    );
+    status(
+        json,
+        format_args!("  释放空间: {}", utils::format_size(total_freed)),
+    );
+
+    Ok(())
+}
+
+/// 提示信息：JSON 输出时写到标准错误，保证标准输出只有 JSON
+fn status(json: bool, message: std::fmt::Arguments) {
+    if json {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+fn print_report(report: &leftovers::LeftoverReport) {
+    if report.programs.is_empty() {
+        println!("未发现已卸载程序的残留");
+        return;
+    }
+
+    for removed in &report.programs {
+        println!(
+            "■ {} ({} 项, {})",
+            removed.program.name,
+            removed.traces.len(),
+            utils::format_size(removed.total_size)
+        );
+        if let Some(last_seen_at) = &removed.last_seen_at {
+            println!("  最后出现于: {}", last_seen_at);
+        }
+        if let Some(record) = &removed.removal_record {
+            match &record.removed_at {
+                Some(removed_at) => {
+                    println!("  来源: {} (卸载于 {})", record.source.label(), removed_at)
+                }
+                None => println!("  来源: {}", record.source.label()),
+            }
+        }
+        if removed.broken_entry {
+            println!("  卸载程序已不存在，清理后将删除其卸载条目 (先备份到隔离区)");
+        }
+        for trace in &removed.traces {
+            println!(
+                "  [{:12}] {}{}",
+                format!("{:?}", trace.trace_type),
+                trace.path,
+                trace
+                    .registry_stats
+                    .map(|stats| format!(" ({})", stats))
+                    .unwrap_or_default()
+            );
+        }
+        println!();
+    }
+
+    println!(
+        "共 {} 个已卸载程序，{} 项残留，约 {}\n",
+        report.programs.len(),
+        report.total_traces,
+        utils::format_size(report.total_size)
+    );
+}
@@ -1,15 +1,22 @@
-use crate::modules::lister::{self, models::InstalledProgram};
+use crate::modules::common::format;
+use crate::modules::lister::{
+    self,
+    models::{InstalledProgram, StartupImpact},
+};
+use crate::modules::uninstaller::signature::SignatureStatus;
 use anyhow::Result;
 use clap::Parser;
+use std::io::Write;
 
 #[derive(Parser, Debug)]
 pub struct ListCommand {
-    /// 输出格式 (table/json)
+    /// 输出格式 (table/json/ndjson)，ndjson 每行一个程序，边序列化边输出
     #[arg(long, default_value = "table")]
     pub format: String,
 
-    /// 过滤来源 (registry|msi|store|standard|all)
+    /// 过滤来源 (registry|msi|store|features|standard|all)
     /// standard = registry (不包括商店应用和 MSI，MSI 较慢)
+    /// features = Windows 可选功能和功能按需 (需要管理员权限)
     #[arg(long, default_value = "standard")]
     pub source: String,
 
@@ -17,16 +24,39 @@ pub struct ListCommand {
     #[arg(short, long)]
     pub search: Option<String>,
 
-    /// 排序字段 (name|date|size)
+    /// 排序字段 (name|date|size|startup|pinned|relevance)
+    /// pinned = 置顶程序在前，其余按名称排序
+    /// startup = 按启动影响排序，影响大的在前
+    /// relevance = 按搜索相关度排序，需配合 --search 使用
     #[arg(long, default_value = "name")]
     pub sort_by: String,
 
     /// 按升序排序
     #[arg(long)]
     pub ascending: bool,
+
+    /// 只列出带有该标签的程序，可重复指定（需同时带有全部标签）
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+
+    /// 只列出疑似预装或捆绑软件，并显示评分和原因
+    #[arg(long)]
+    pub bloatware: bool,
+
+    /// 只列出主程序未签名或签名无效的程序，并显示签名者和证书有效期
+    #[arg(long)]
+    pub unsigned: bool,
+
+    /// 远程计算机名，通过 PowerShell 远程处理 (WinRM) 操作该计算机
+    #[arg(long)]
+    pub computer: Option<String>,
 }
 
 pub async fn execute(cmd: ListCommand) -> Result<()> {
+    if let Some(computer) = &cmd.computer {
+        return super::remote::list(computer, cmd.search.as_deref(), &cmd.format);
+    }
+
     tracing::info!(
         "列出已安装程序, source: {}, search: {:?}",
         cmd.source,
@@ -37,6 +67,7 @@ pub async fn execute(cmd: ListCommand) -> Result<()> {
         "registry" => Some(lister::models::InstallSource::Registry),
         "msi" => Some(lister::models::InstallSource::Msi),
         "store" => Some(lister::models::InstallSource::Store),
+        "features" => Some(lister::models::InstallSource::Feature),
         "standard" => None, // registry + msi (不包括 store)
         _ => None,
     };
@@ -46,24 +77,54 @@ pub async fn execute(cmd: ListCommand) -> Result<()> {
         search: cmd.search.clone(),
         refresh: false,
         cache_ttl_seconds: lister::storage::DEFAULT_CACHE_TTL_SECONDS,
+        // CLI 进程执行完即退出，无法等待后台刷新
+        allow_stale: false,
+        tags: cmd.tags.clone(),
+        // 置顶排序在下方与其他排序方式一起处理
+        pinned_first: false,
+        bloatware_only: cmd.bloatware,
+        unsigned_only: cmd.unsigned,
     };
     let mut programs = lister::list_programs_with_cache(query)?.programs;
 
     // 排序
     match cmd.sort_by.as_str() {
-        "name" => programs.sort_by(|a, b| a.name.cmp(&b.name)),
+        "name" | "pinned" => programs.sort_by(|a, b| a.name.cmp(&b.name)),
         "date" => programs.sort_by(|a, b| a.install_date.cmp(&b.install_date)),
         "size" => programs.sort_by(|a, b| b.size.cmp(&a.size)),
+        "startup" => programs.sort_by_key(|p| (p.startup_impact, p.startup_entries.len())),
         _ => {}
     }
 
-    if !cmd.ascending {
+    // 相关度结果已按分数从高到低排列，只有显式要求升序时才反转
+    let relevance_order = cmd.sort_by == "relevance" && cmd.search.is_some();
+    let reverse = if relevance_order {
+        cmd.ascending
+    } else {
+        !cmd.ascending
+    };
+    if reverse {
         programs.reverse();
     }
+    if cmd.sort_by == "pinned" {
+        lister::pins::float_pinned(&mut programs);
+    }
 
     match cmd.format.as_str() {
         "json" => {
-            println!("{}", serde_json::to_string_pretty(&programs)?);
+            // 直接写入标准输出，不在内存中拼出完整字符串
+            let mut out = std::io::stdout().lock();
+            serde_json::to_writer_pretty(&mut out, &programs)?;
+            writeln!(out)?;
+        }
+        "ndjson" => {
+            write_ndjson(&programs)?;
+        }
+        _ if cmd.bloatware => {
+            print_bloatware_table(&programs);
+        }
+        _ if cmd.unsigned => {
+            print_signature_table(&programs);
         }
         _ => {
             print_table(&programs);
@@ -73,35 +134,130 @@ pub async fn execute(cmd: ListCommand) -> Result<()> {
     Ok(())
 }
 
+/// 每行输出一个程序的 JSON；下游关闭管道（如 `| head`）时正常结束
+fn write_ndjson(programs: &[InstalledProgram]) -> Result<()> {
+    let mut out = std::io::stdout().lock();
+    for program in programs {
+        serde_json::to_writer(&mut out, program)?;
+        if let Err(error) = writeln!(out) {
+            if error.kind() == std::io::ErrorKind::BrokenPipe {
+                return Ok(());
+            }
+            return Err(error.into());
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
 fn print_table(programs: &[InstalledProgram]) {
-    println!("\n{}", "=".repeat(100));
+    println!("\n{}", "=".repeat(112));
     println!(
-        "{:<45} {:<25} {:<15} {:<12}",
-        "名称", "发布者", "版本", "来源"
+        "{:<45} {:<25} {:<15} {:<12} {:<10}",
+        "名称", "发布者", "版本", "来源", "启动影响"
     );
-    println!("{}", "=".repeat(100));
+    println!("{}", "=".repeat(112));
 
     for p in programs {
         let source = match p.install_source {
             lister::models::InstallSource::Registry => "注册表",
             lister::models::InstallSource::Msi => "MSI",
             lister::models::InstallSource::Store => "商店应用",
+            lister::models::InstallSource::Feature => "系统功能",
             lister::models::InstallSource::Unknown => "未知",
         };
+        let impact = match p.startup_impact {
+            StartupImpact::None => String::new(),
+            StartupImpact::Low => format!("低 ({})", p.startup_entries.len()),
+            StartupImpact::Medium => format!("中 ({})", p.startup_entries.len()),
+            StartupImpact::High => format!("高 ({})", p.startup_entries.len()),
+        };
 
         println!(
-            "{:<45} {:<25} {:<15} {:<12}",
-            truncate_string(&p.name, 44),
+            "{:<45} {:<25} {:<15} {:<12} {:<10}",
+            if p.pinned {
+                format!("★ {}", truncate_string(&p.name, 42))
+            } else {
+                truncate_string(&p.name, 44)
+            },
             truncate_string(&p.publisher.clone().unwrap_or_default(), 24),
             truncate_string(&p.version.clone().unwrap_or_default(), 14),
-            source
+            source,
+            impact
         );
     }
 
-    println!("{}", "=".repeat(100));
+    println!("{}", "=".repeat(112));
     println!("总计: {} 个程序\n", programs.len());
 }
 
+fn print_signature_table(programs: &[InstalledProgram]) {
+    let now = chrono::Utc::now();
+    println!("\n{}", "=".repeat(110));
+    println!(
+        "{:<40} {:<25} {:<10} {:<20} 文件",
+        "名称", "发布者", "签名", "证书有效期至"
+    );
+    println!("{}", "=".repeat(110));
+
+    for p in programs {
+        let Some(check) = &p.signature else {
+            continue;
+        };
+        let status = match check.status {
+            SignatureStatus::Valid => "有效",
+            SignatureStatus::Unsigned => "未签名",
+            SignatureStatus::Invalid => "无效",
+            SignatureStatus::Unknown => "未知",
+        };
+        let not_after = check
+            .cert_not_after
+            .map(|not_after| {
+                let date = format::format_date(not_after.date_naive());
+                if check.certificate_expired_at(now) {
+                    format!("{} (已过期)", date)
+                } else {
+                    date
+                }
+            })
+            .unwrap_or_default();
+
+        println!(
+            "{:<40} {:<25} {:<10} {:<20} {}",
+            truncate_string(&p.name, 39),
+            truncate_string(&p.publisher.clone().unwrap_or_default(), 24),
+            status,
+            not_after,
+            check.path
+        );
+    }
+
+    println!("{}", "=".repeat(110));
+    println!(
+        "共 {} 个未签名或签名无效的程序，建议优先审查\n",
+        programs.len()
+    );
+}
+
+fn print_bloatware_table(programs: &[InstalledProgram]) {
+    println!("\n{}", "=".repeat(100));
+    println!("{:<45} {:<25} {:<6} 原因", "名称", "发布者", "评分");
+    println!("{}", "=".repeat(100));
+
+    for p in programs {
+        println!(
+            "{:<45} {:<25} {:<6} {}",
+            truncate_string(&p.name, 44),
+            truncate_string(&p.publisher.clone().unwrap_or_default(), 24),
+            p.bloatware_score,
+            p.bloatware_reasons.join("; ")
+        );
+    }
+
+    println!("{}", "=".repeat(100));
+    println!("共 {} 个疑似预装软件，卸载前请确认用途\n", programs.len());
+}
+
 fn truncate_string(s: &str, max_len: usize) -> String {
     // 使用 char 边界来正确处理 Unicode 字符（包括中文）
     if s.chars().count() > max_len {
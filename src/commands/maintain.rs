@@ -0,0 +1,63 @@
+//! maintain 命令 - 执行一次定期维护（通常由计划任务调用）
+
+use crate::modules::common::utils;
+use crate::modules::maintenance::{self, MaintenanceOptions};
+use crate::modules::scanner::models::Confidence;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct MaintainCommand {
+    /// 清理临时目录残留所需的最低置信度 (high/medium/low)
+    #[arg(long, default_value = "high")]
+    pub min_confidence: String,
+
+    /// 报告目录保留的最新报告数量
+    #[arg(long, default_value_t = maintenance::DEFAULT_KEEP_REPORTS)]
+    pub keep_reports: usize,
+
+    /// 输出格式 (table/json)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+pub async fn execute(cmd: MaintainCommand) -> Result<()> {
+    let options = MaintenanceOptions {
+        min_confidence: parse_confidence(&cmd.min_confidence)?,
+        keep_reports: cmd.keep_reports,
+    };
+
+    let summary = maintenance::run_maintenance(options).await;
+
+    if cmd.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    let purged_ok = summary.purged.iter().filter(|r| r.success).count();
+    println!("=== 定期维护完成 ===");
+    println!("  缓存程序数: {}", summary.programs_cached);
+    println!(
+        "  残留: {} 个已卸载程序，{} 项",
+        summary.leftover_programs, summary.leftover_traces
+    );
+    println!(
+        "  临时目录清理: {}/{} 项，释放 {}",
+        purged_ok,
+        summary.purged.len(),
+        utils::format_size(summary.bytes_freed)
+    );
+    println!("  删除旧报告: {}", summary.reports_removed);
+    if let Some(path) = &summary.report_path {
+        println!("  报告: {}", path);
+    }
+    for warning in &summary.warnings {
+        println!("  警告: {}", warning);
+    }
+
+    Ok(())
+}
+
+fn parse_confidence(value: &str) -> Result<Confidence> {
+    Ok(value.parse()?)
+}
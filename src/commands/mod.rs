@@ -1,8 +1,26 @@
+pub mod bench;
 pub mod clean;
+pub mod doctor;
+pub mod drivers;
+pub mod export;
+pub mod feature;
+pub mod ignore;
+pub mod keep;
+pub mod leftovers;
 pub mod list;
+pub mod maintain;
+pub mod note;
+pub mod pin;
+pub mod provisioned;
+pub mod remote;
 pub mod report;
+pub mod schedule;
 pub mod search;
+pub mod self_update;
+pub mod snapshot;
+pub mod tag;
 pub mod uninstall;
+pub mod watch;
 
 use clap::Subcommand;
 
@@ -15,11 +33,62 @@ pub enum Command {
     Search(search::SearchCommand),
 
     /// 清理程序残留痕迹
-    Clean(clean::CleanCommand),
+    Clean(Box<clean::CleanCommand>),
 
     /// 查看卸载报告
     Report(report::ReportCommand),
 
     /// 卸载程序并清理残留
     Uninstall(uninstall::UninstallCommand),
+
+    /// 搜索并清理在 rust-yu 之外卸载的程序残留
+    Leftovers(leftovers::LeftoversCommand),
+
+    /// 测量各阶段耗时，用于定位慢环境和性能回归
+    Bench(bench::BenchCommand),
+
+    /// 监视新安装的程序，记录安装日志供卸载时精确清理
+    Watch(watch::WatchCommand),
+
+    /// 执行一次定期维护：刷新缓存、清理临时目录残留并轮换报告
+    Maintain(maintain::MaintainCommand),
+
+    /// 注册或删除定期维护计划任务
+    Schedule(schedule::ScheduleCommand),
+
+    /// 导出 Intune / SCCM 可导入的程序清单和卸载结果
+    Export(export::ExportCommand),
+
+    /// 列出、启用或禁用 Windows 可选功能和功能按需
+    Feature(feature::FeatureCommand),
+
+    /// 列出、删除第三方驱动包
+    Drivers(drivers::DriversCommand),
+
+    /// 列出、移除为所有用户预配的商店应用
+    Provisioned(provisioned::ProvisionedCommand),
+
+    /// 为程序添加、移除、整理自定义标签
+    Tag(tag::TagCommand),
+
+    /// 查看或编辑程序备注
+    Note(note::NoteCommand),
+
+    /// 置顶或取消置顶程序
+    Pin(pin::PinCommand),
+
+    /// 标记程序的痕迹为有意保留，之后扫描和清理时跳过
+    Keep(keep::KeepCommand),
+
+    /// 管理对所有程序生效的痕迹忽略规则
+    Ignore(ignore::IgnoreCommand),
+
+    /// 导出、导入程序清单快照，用于迁移和重装前后对比
+    Snapshot(snapshot::SnapshotCommand),
+
+    /// 检查本地缓存、程序记录、图标缓存和报告的完整性，可选修复
+    Doctor(doctor::DoctorCommand),
+
+    /// 检查新版本，下载并替换当前程序
+    SelfUpdate(self_update::SelfUpdateCommand),
 }
@@ -0,0 +1,42 @@
+//! note 命令 - 查看或编辑程序备注
+
+use crate::modules::lister::{self, notes};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct NoteCommand {
+    /// 程序名称
+    pub program: String,
+
+    /// 备注内容 (不指定则显示当前备注)
+    pub text: Option<String>,
+
+    /// 删除备注
+    #[arg(long, conflicts_with = "text")]
+    pub clear: bool,
+}
+
+pub async fn execute(cmd: NoteCommand) -> Result<()> {
+    let program = lister::get_program_detail(&cmd.program)
+        .ok_or_else(|| anyhow::anyhow!("未找到程序: {}", cmd.program))?;
+
+    if cmd.clear {
+        notes::set_note(&program, "")?;
+        println!("已删除 {} 的备注", program.name);
+        return Ok(());
+    }
+
+    match cmd.text {
+        Some(text) => {
+            notes::set_note(&program, &text)?;
+            println!("已更新 {} 的备注", program.name);
+        }
+        None => match &program.note {
+            Some(note) => println!("{}:\n{}", program.name, note),
+            None => println!("{} 暂无备注", program.name),
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! pin 命令 - 置顶或取消置顶程序
+
+use crate::modules::lister::{self, pins};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct PinCommand {
+    /// 程序名称
+    pub program: String,
+
+    /// 取消置顶
+    #[arg(long)]
+    pub remove: bool,
+}
+
+pub async fn execute(cmd: PinCommand) -> Result<()> {
+    let program = lister::find_program_by_name(&cmd.program)
+        .ok_or_else(|| anyhow::anyhow!("未找到程序: {}", cmd.program))?;
+
+    pins::set_pinned(&program, !cmd.remove)?;
+    if cmd.remove {
+        println!("已取消置顶: {}", program.name);
+    } else {
+        println!("已置顶: {}", program.name);
+        println!("使用 list --sort-by pinned 查看置顶程序");
+    }
+
+    Ok(())
+}
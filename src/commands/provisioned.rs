@@ -0,0 +1,103 @@
+//! provisioned 命令 - 列出、移除为所有用户预配的商店应用
+
+use crate::modules::lister::store;
+use crate::modules::uninstaller::store as store_uninstaller;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+pub struct ProvisionedCommand {
+    #[command(subcommand)]
+    pub action: ProvisionedAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProvisionedAction {
+    /// 列出预配包 (需要管理员权限)
+    List {
+        /// 搜索关键词
+        #[arg(short, long)]
+        search: Option<String>,
+
+        /// 输出格式 (table/json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// 移除预配包，并为所有用户卸载已安装的副本 (需要管理员权限)
+    Remove {
+        /// 包名或完整包名，如 Microsoft.BingNews
+        name: String,
+
+        /// 只移除预配包，保留各用户已安装的副本
+        #[arg(long)]
+        keep_installed: bool,
+
+        /// 确认移除 (不指定则只显示预配包信息)
+        #[arg(long)]
+        confirm: bool,
+    },
+}
+
+pub async fn execute(cmd: ProvisionedCommand) -> Result<()> {
+    match cmd.action {
+        ProvisionedAction::List { search, format } => list(search.as_deref(), &format),
+        ProvisionedAction::Remove {
+            name,
+            keep_installed,
+            confirm,
+        } => remove(&name, keep_installed, confirm),
+    }
+}
+
+fn list(search: Option<&str>, format: &str) -> Result<()> {
+    let keyword = search.unwrap_or_default().to_lowercase();
+    let packages: Vec<_> = store::list_provisioned_packages()?
+        .into_iter()
+        .filter(|p| p.display_name.to_lowercase().contains(&keyword))
+        .collect();
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&packages)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "=".repeat(90));
+    println!("{:<50} {:<20} {:<15}", "包名", "版本", "发布者 ID");
+    println!("{}", "=".repeat(90));
+    for package in &packages {
+        println!(
+            "{:<50} {:<20} {:<15}",
+            package.display_name,
+            package.version.clone().unwrap_or_default(),
+            package.publisher_id.clone().unwrap_or_default()
+        );
+    }
+    println!("{}", "=".repeat(90));
+    println!("总计: {} 个预配包\n", packages.len());
+
+    Ok(())
+}
+
+fn remove(name: &str, keep_installed: bool, confirm: bool) -> Result<()> {
+    let package = store::find_provisioned_package(name)?
+        .ok_or_else(|| anyhow::anyhow!("未找到预配包: {}", name))?;
+    store_uninstaller::check_removable(&package)?;
+
+    println!("预配包: {}", package.package_name);
+    if !confirm {
+        if keep_installed {
+            println!("将只移除预配包，已为各用户安装的副本保留");
+        } else {
+            println!("将移除预配包，并为所有用户卸载已安装的副本");
+        }
+        println!("使用 --confirm 确认移除");
+        return Ok(());
+    }
+
+    store_uninstaller::remove_provisioned_package(&package, !keep_installed)?;
+    println!("已移除: {}", package.display_name);
+    println!("新用户登录时将不再自动安装该应用");
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::modules::remote::{
+    self,
+    runner::{RemoteRunner, ScriptRunner},
+};
+use crate::modules::scanner::models::{Confidence, Trace};
+use anyhow::Result;
+
+/// 列出远程计算机上的已安装程序
+pub fn list(computer: &str, search: Option<&str>, format: &str) -> Result<()> {
+    let runner = RemoteRunner::new(computer)?;
+    println!("正在读取 {} 上的已安装程序...", computer);
+
+    let mut programs = remote::list_programs(&runner)?;
+    if let Some(keyword) = search {
+        let keyword = keyword.to_lowercase();
+        programs.retain(|p| p.name.to_lowercase().contains(&keyword));
+    }
+
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&programs)?),
+        _ => {
+            println!("\n{}", "=".repeat(90));
+            println!("{:<45} {:<25} {:<15}", "名称", "发布者", "版本");
+            println!("{}", "=".repeat(90));
+            for p in &programs {
+                println!(
+                    "{:<45} {:<25} {:<15}",
+                    p.name,
+                    p.publisher.clone().unwrap_or_default(),
+                    p.version.clone().unwrap_or_default()
+                );
+            }
+            println!("{}", "=".repeat(90));
+            println!("{} 上共 {} 个程序\n", computer, programs.len());
+        }
+    }
+
+    Ok(())
+}
+
+/// 卸载远程计算机上的程序，可选清理残留
+pub fn uninstall(
+    computer: &str,
+    target: &str,
+    confirm: bool,
+    clean: bool,
+    allow_suspicious: bool,
+) -> Result<()> {
+    let runner = RemoteRunner::new(computer)?;
+    println!("=== 卸载 {} 上的程序: {} ===\n", computer, target);
+
+    let target_lower = target.to_lowercase();
+    let programs = remote::list_programs(&runner)?;
+    let program = programs
+        .iter()
+        .find(|p| p.name.to_lowercase() == target_lower)
+        .or_else(|| {
+            programs
+                .iter()
+                .find(|p| p.name.to_lowercase().contains(&target_lower))
+        })
+        .ok_or_else(|| anyhow::anyhow!("未在 {} 上找到程序: {}", computer, target))?;
+
+    println!("  - 找到程序: {}", program.name);
+    if let Some(uninstall_string) = &program.uninstall_string {
+        println!("  - 卸载命令: {}", uninstall_string);
+    }
+
+    if !confirm {
+        println!("\n使用 --confirm 确认卸载");
+        return Ok(());
+    }
+
+    let exit_code = remote::uninstall_program(&runner, program, allow_suspicious)?;
+    // 3010 表示卸载成功但需要重启
+    if exit_code == 0 || exit_code == 3010 {
+        println!("\n卸载命令已执行 (退出码 {})", exit_code);
+    } else {
+        anyhow::bail!("卸载命令执行失败，退出码: {}", exit_code);
+    }
+
+    if clean {
+        // 卸载的 --confirm 不代表同意删除残留：先预览，再单独确认
+        println!();
+        let traces = report_traces(
+            &runner,
+            remote::scan_traces(&runner, &program.name)?,
+            Confidence::High,
+        );
+        if !traces.is_empty() && super::uninstall::confirm_prompt("是否删除以上残留?")? {
+            delete_traces(&runner, &traces)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 清理远程计算机上的残留痕迹，只删除达到 `min_confidence` 的痕迹
+pub fn clean(
+    computer: &str,
+    target: &str,
+    confirm: bool,
+    exclude: &[String],
+    min_confidence: Confidence,
+) -> Result<()> {
+    let runner = RemoteRunner::new(computer)?;
+    let traces = remote::scan_traces(&runner, target)?
+        .into_iter()
+        .filter(|t| !exclude.contains(&t.id))
+        .collect::<Vec<_>>();
+    let traces = report_traces(&runner, traces, min_confidence);
+
+    if !confirm {
+        println!("\n=== 预览模式 ===");
+        println!("使用 --confirm 确认删除");
+        return Ok(());
+    }
+
+    delete_traces(&runner, &traces)
+}
+
+/// 列出将删除的痕迹，返回达到 `min_confidence` 的部分
+fn report_traces(
+    runner: &RemoteRunner,
+    mut traces: Vec<Trace>,
+    min_confidence: Confidence,
+) -> Vec<Trace> {
+    let found = traces.len();
+    traces.retain(|trace| trace.confidence.meets(min_confidence));
+    println!("在 {} 上找到 {} 个残留痕迹\n", runner.target(), found);
+    if traces.len() < found {
+        println!(
+            "  {} 个痕迹低于 {:?} 置信度，不会删除 (可用 --min-confidence 调整)\n",
+            found - traces.len(),
+            min_confidence
+        );
+    }
+    for trace in &traces {
+        println!(
+            "  [{:12}] {}{}",
+            format!("{:?}", trace.trace_type),
+            trace.path,
+            if trace.is_critical {
+                " [关键项，将跳过]"
+            } else {
+                ""
+            }
+        );
+    }
+
+    traces
+}
+
+fn delete_traces(runner: &RemoteRunner, traces: &[Trace]) -> Result<()> {
+    let results = remote::clean_traces(runner, traces)?;
+    let success_count = results.iter().filter(|r| r.success).count();
+    for result in results.iter().filter(|r| !r.success) {
+        println!(
+            "  删除失败: {} ({})",
+            result.path,
+            result.error.clone().unwrap_or_default()
+        );
+    }
+
+    println!("\n--- 清理完成 ---");
+    println!("  成功: {}", success_count);
+    println!("  失败: {}", results.len() - success_count);
+
+    Ok(())
+}
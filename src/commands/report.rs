@@ -1,112 +1,132 @@
+use crate::modules::common::utils;
+use crate::modules::reporter::{self, statistics, storage};
+use crate::modules::scanner::models::Confidence;
 use anyhow::Result;
 use clap::Parser;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 pub struct ReportCommand {
-    /// 报告文件路径或程序名
+    /// 报告文件路径、报告 ID 或程序名
     pub identifier: String,
 
     /// 查看所有报告列表
     #[arg(long)]
     pub list: bool,
 
+    /// 按程序最近一次扫描汇总剩余残留的数量和大小
+    #[arg(long)]
+    pub stats: bool,
+
     /// 输出 HTML 文件
     #[arg(short, long)]
     pub html: Option<String>,
 }
 
 pub async fn execute(cmd: ReportCommand) -> Result<()> {
-    let reports_dir = get_reports_dir()?;
+    if cmd.stats {
+        return print_statistics();
+    }
 
     if cmd.list {
         // 列出所有报告
-        println!("卸载报告目录: {}\n", reports_dir.display());
+        println!("卸载报告目录: {}\n", storage::reports_dir().display());
 
-        if !reports_dir.exists() {
+        let reports = storage::list_reports()?;
+        if reports.is_empty() {
             println!("暂无报告文件");
             return Ok(());
         }
 
-        let entries = std::fs::read_dir(&reports_dir)?;
-        let mut count = 0;
-
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map(|e| e == "html").unwrap_or(false) {
-                count += 1;
-                let metadata = std::fs::metadata(&path)?;
-                let modified = metadata
-                    .modified()?
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .ok()
-                    .and_then(|d| {
-                        chrono::DateTime::<chrono::Utc>::from_timestamp(d.as_secs() as i64, 0)
-                    })
-                    .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
-                    .unwrap_or_default();
-
-                println!(
-                    "  {}  (修改时间: {})",
-                    path.file_name().unwrap().to_string_lossy(),
-                    modified
-                );
-            }
+        for report in &reports {
+            println!(
+                "  {}  {}  (生成时间: {})",
+                report.id, report.program_name, report.generated_at
+            );
         }
 
-        println!("\n共 {} 个报告", count);
+        println!("\n共 {} 个报告", reports.len());
         return Ok(());
     }
 
-    // 尝试作为文件路径或程序名加载报告
+    // 作为文件路径
     let report_path = PathBuf::from(&cmd.identifier);
-
-    if report_path.exists() {
-        // 作为文件路径
+    if report_path.is_file() {
         let content = std::fs::read_to_string(&report_path)?;
         print_html_content(&content, &cmd.html)?;
-    } else {
-        // 作为程序名，搜索报告
-        let search_pattern = format!("uninstall_report_{}.html", cmd.identifier);
-        let found = reports_dir.join(&search_pattern);
+        return Ok(());
+    }
 
-        if found.exists() {
-            let content = std::fs::read_to_string(&found)?;
+    // 作为报告 ID 或程序名
+    let found = storage::find_reports(&cmd.identifier)?;
+    match found.as_slice() {
+        [] => {
+            println!("未找到报告: {}", cmd.identifier);
+            println!("使用 --list 查看所有报告");
+        }
+        [report] => {
+            let content = match &report.html_path {
+                Some(html_path) => std::fs::read_to_string(html_path)?,
+                None => reporter::html::generate_html_report(&storage::load_report(&report.id)?)?,
+            };
             print_html_content(&content, &cmd.html)?;
-        } else {
-            // 搜索所有匹配的报告
-            if reports_dir.exists() {
-                let entries = std::fs::read_dir(&reports_dir)?;
-                let mut found_reports = Vec::new();
-
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().map(|e| e == "html").unwrap_or(false) {
-                        let filename = path.file_name().unwrap().to_string_lossy().to_lowercase();
-                        if filename.contains(&cmd.identifier.to_lowercase()) {
-                            found_reports.push(path);
-                        }
-                    }
-                }
-
-                if found_reports.is_empty() {
-                    println!("未找到报告: {}", cmd.identifier);
-                    println!("使用 --list 查看所有报告");
-                } else {
-                    println!("找到以下报告:");
-                    for report in &found_reports {
-                        println!("  {}", report.display());
-                    }
-                }
-            } else {
-                println!("报告目录不存在");
+        }
+        reports => {
+            println!("找到以下报告:");
+            for report in reports {
+                println!(
+                    "  {}  {}  (生成时间: {})",
+                    report.id, report.program_name, report.generated_at
+                );
             }
+            println!("\n使用报告 ID 查看指定报告");
         }
     }
 
     Ok(())
 }
 
+fn print_statistics() -> Result<()> {
+    let statistics = statistics::leftover_statistics()?;
+    if statistics.program_count == 0 {
+        println!("报告中没有剩余的残留");
+        return Ok(());
+    }
+
+    println!(
+        "共 {} 个程序留下 {} 项残留，约 {}\n",
+        statistics.program_count,
+        statistics.trace_count,
+        utils::format_size(statistics.total_size)
+    );
+    println!("按类型:");
+    for group in &statistics.by_type {
+        let confidence = match group.confidence {
+            Confidence::High => "高",
+            Confidence::Medium => "中",
+            Confidence::Low => "低",
+        };
+        println!(
+            "  {:<20} 置信度{}  {:>6} 项  {}",
+            group.trace_type.to_string(),
+            confidence,
+            group.count,
+            utils::format_size(group.total_size)
+        );
+    }
+    println!("\n按程序:");
+    for program in &statistics.programs {
+        println!(
+            "  {}  {} 项  {}  (报告 {})",
+            program.program_name,
+            program.trace_count,
+            utils::format_size(program.total_size),
+            program.report_id
+        );
+    }
+    Ok(())
+}
+
 fn print_html_content(content: &str, output_path: &Option<String>) -> Result<()> {
     if let Some(path) = output_path {
         std::fs::write(path, content)?;
@@ -118,12 +138,3 @@ fn print_html_content(content: &str, output_path: &Option<String>) -> Result<()>
 
     Ok(())
 }
-
-fn get_reports_dir() -> Result<PathBuf> {
-    let dir = dirs::data_local_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("rust-yu")
-        .join("reports");
-
-    Ok(dir)
-}
@@ -0,0 +1,81 @@
+//! schedule 命令 - 注册或删除定期维护计划任务
+
+use crate::modules::maintenance::{self, scheduler};
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+pub struct ScheduleCommand {
+    #[command(subcommand)]
+    pub action: ScheduleAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ScheduleAction {
+    /// 注册计划任务，已存在时覆盖
+    Register {
+        /// 运行频率 (daily/weekly/monthly)
+        #[arg(long, default_value = "weekly")]
+        frequency: String,
+
+        /// 开始时间 (HH:MM)
+        #[arg(long, default_value = "03:00")]
+        time: String,
+
+        /// 清理临时目录残留所需的最低置信度 (high/medium/low)
+        #[arg(long, default_value = "high")]
+        min_confidence: String,
+
+        /// 报告目录保留的最新报告数量
+        #[arg(long, default_value_t = maintenance::DEFAULT_KEEP_REPORTS)]
+        keep_reports: usize,
+    },
+
+    /// 删除计划任务
+    Unregister,
+
+    /// 查看计划任务是否已注册
+    Status,
+}
+
+pub async fn execute(cmd: ScheduleCommand) -> Result<()> {
+    match cmd.action {
+        ScheduleAction::Register {
+            frequency,
+            time,
+            min_confidence,
+            keep_reports,
+        } => {
+            let frequency = match frequency.to_lowercase().as_str() {
+                "daily" => scheduler::ScheduleFrequency::Daily,
+                "weekly" => scheduler::ScheduleFrequency::Weekly,
+                "monthly" => scheduler::ScheduleFrequency::Monthly,
+                other => bail!("无效的运行频率: {}（可选 daily/weekly/monthly）", other),
+            };
+
+            let executable = std::env::current_exe()?;
+            let args = vec![
+                format!("--min-confidence {}", min_confidence),
+                format!("--keep-reports {}", keep_reports),
+            ];
+            scheduler::register_task(&executable, frequency, &time, &args)?;
+
+            println!("已注册计划任务: {}", scheduler::TASK_NAME);
+            println!("  频率: {:?}，开始时间: {}", frequency, time);
+            println!("  维护结果将写入报告目录，可用 `rust-yu report --list` 查看");
+        }
+        ScheduleAction::Unregister => {
+            scheduler::unregister_task()?;
+            println!("已删除计划任务: {}", scheduler::TASK_NAME);
+        }
+        ScheduleAction::Status => {
+            if scheduler::is_task_registered() {
+                println!("计划任务已注册: {}", scheduler::TASK_NAME);
+            } else {
+                println!("计划任务未注册");
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,27 +1,130 @@
-use crate::modules::scanner;
+use crate::modules::common::progress;
+use crate::modules::scanner::models::ScanProgress;
+use crate::modules::scanner::options::{self, ScanOptions};
+use crate::modules::{lister, scanner};
 use anyhow::Result;
-use clap::Parser;
+use clap::{Args, Parser};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 pub struct SearchCommand {
     /// 程序名称 (必需)
     pub program_name: String,
 
-    /// 搜索类型 (all|registry|files|shortcuts|appdata)
+    /// 搜索类型 (all|registry|files|shortcuts|appdata|activex|history|network|firewall|env|menu|cred|eventlog|wmi|defender|startup)
     #[arg(long, default_value = "all")]
     pub trace_type: String,
 
-    /// 输出文件路径
+    /// 导出痕迹到文件，可用 `clean --from-file` 读取；扩展名为 .html 时写出可离线审核的页面
     #[arg(short, long)]
     pub output: Option<String>,
 
     /// 详细输出
     #[arg(short, long)]
     pub verbose: bool,
+
+    /// 同时扫描其他用户的 AppData 和已登录用户的注册表 (需要管理员权限)
+    #[arg(long)]
+    pub all_users: bool,
+
+    /// 扫描其他用户时临时加载未登录用户的 NTUSER.DAT
+    #[arg(long, requires = "all_users")]
+    pub load_offline_hives: bool,
+
+    #[command(flatten)]
+    pub scan: ScanArgs,
+}
+
+/// 扫描深度、额外目录和上限，`search` 与 `clean` 共用
+#[derive(Args, Debug, Clone)]
+pub struct ScanArgs {
+    /// Program Files、桌面等目录的遍历深度
+    #[arg(long, default_value_t = options::DEFAULT_FILESYSTEM_DEPTH)]
+    pub file_depth: usize,
+
+    /// AppData 目录的遍历深度
+    #[arg(long, default_value_t = options::DEFAULT_APPDATA_DEPTH)]
+    pub appdata_depth: usize,
+
+    /// 开始菜单和桌面快捷方式的遍历深度
+    #[arg(long, default_value_t = options::DEFAULT_SHORTCUT_DEPTH)]
+    pub shortcut_depth: usize,
+
+    /// 注册表项的递归深度
+    #[arg(long, default_value_t = options::DEFAULT_REGISTRY_DEPTH)]
+    pub registry_depth: u32,
+
+    /// 额外扫描的目录，如程序装在 D:\Tools 下 (可多次指定)
+    #[arg(long = "extra-root")]
+    pub extra_roots: Vec<std::path::PathBuf>,
+
+    /// 每个目录或注册表根最多遍历的项数
+    #[arg(long)]
+    pub max_entries: Option<usize>,
+
+    /// 等待扫描器结束的时限 (秒)，超时的扫描器结果不完整
+    #[arg(long, default_value_t = options::DEFAULT_TIMEOUT_SECS)]
+    pub scan_timeout: u64,
+
+    /// 不报告低置信度的痕迹
+    #[arg(long)]
+    pub skip_low_confidence: bool,
+}
+
+impl ScanArgs {
+    pub fn to_options(&self) -> ScanOptions {
+        ScanOptions {
+            filesystem_depth: self.file_depth,
+            appdata_depth: self.appdata_depth,
+            shortcut_depth: self.shortcut_depth,
+            registry_depth: self.registry_depth,
+            extra_roots: self.extra_roots.clone(),
+            max_entries: self.max_entries,
+            timeout_secs: self.scan_timeout,
+            include_low_confidence: !self.skip_low_confidence,
+        }
+    }
+}
+
+/// 扫描进度条及更新它的回调，扫描结束后调用 `finish_and_clear`
+///
+/// 输出 JSON 进度时隐藏，避免与进度事件混在 stderr 上；stderr 不是终端时 indicatif 自动隐藏
+pub fn scan_progress_bar() -> (ProgressBar, scanner::ProgressCallback) {
+    let bar = if progress::enabled() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(0)
+    };
+    bar.set_style(
+        ProgressStyle::with_template("{spinner} [{bar:24}] {pos}/{len} 个扫描器 {wide_msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+    );
+    bar.enable_steady_tick(Duration::from_millis(120));
+
+    let handle = bar.clone();
+    let on_progress: scanner::ProgressCallback = Arc::new(move |event: &ScanProgress| {
+        handle.set_length(event.total as u64);
+        handle.set_position(event.completed as u64);
+        handle.set_message(match &event.current_path {
+            Some(path) => format!(
+                "已发现 {} 项 | {}: {}",
+                event.items_found, event.label, path
+            ),
+            None => format!("已发现 {} 项 | {} 完成", event.items_found, event.label),
+        });
+    });
+    (bar, on_progress)
 }
 
 pub async fn execute(cmd: SearchCommand) -> Result<()> {
     println!("正在搜索 \"{}\" 的残留痕迹...\n", cmd.program_name);
+    scanner::profiles::set_process_scope(scanner::profiles::ProfileScope::from_flags(
+        cmd.all_users,
+        cmd.load_offline_hives,
+    ));
 
     let trace_types = match cmd.trace_type.as_str() {
         "registry" => vec![scanner::models::TraceType::RegistryKey],
@@ -31,70 +134,248 @@ pub async fn execute(cmd: SearchCommand) -> Result<()> {
         ],
         "shortcuts" => vec![scanner::models::TraceType::Shortcut],
         "appdata" => vec![scanner::models::TraceType::AppData],
+        "activex" => vec![scanner::models::TraceType::ActiveX],
+        "history" => vec![scanner::models::TraceType::UsageHistory],
+        "network" => vec![scanner::models::TraceType::NetworkSetting],
+        "firewall" => vec![scanner::models::TraceType::FirewallRule],
+        "env" => vec![scanner::models::TraceType::EnvironmentVariable],
+        "menu" => vec![scanner::models::TraceType::ContextMenu],
+        "cred" => vec![scanner::models::TraceType::Credential],
+        "eventlog" => vec![scanner::models::TraceType::EventLogSource],
+        "wmi" => vec![scanner::models::TraceType::WmiProvider],
+        "defender" => vec![scanner::models::TraceType::DefenderExclusion],
+        "startup" => vec![
+            scanner::models::TraceType::RegistryValue,
+            scanner::models::TraceType::Shortcut,
+        ],
         _ => vec![
             scanner::models::TraceType::RegistryKey,
+            scanner::models::TraceType::RegistryValue,
             scanner::models::TraceType::File,
             scanner::models::TraceType::AppData,
             scanner::models::TraceType::Shortcut,
+            scanner::models::TraceType::ActiveX,
+            scanner::models::TraceType::UsageHistory,
+            scanner::models::TraceType::NetworkSetting,
+            scanner::models::TraceType::FirewallRule,
+            scanner::models::TraceType::EnvironmentVariable,
+            scanner::models::TraceType::ContextMenu,
+            scanner::models::TraceType::Credential,
+            scanner::models::TraceType::EventLogSource,
+            scanner::models::TraceType::WmiProvider,
+            scanner::models::TraceType::DefenderExclusion,
         ],
     };
 
-    let traces = scanner::scan_all_traces(&cmd.program_name, Some(trace_types)).await?;
-
-    // 过滤只显示存在的
-    let existing_traces: Vec<_> = traces.into_iter().filter(|t| t.exists).collect();
+    // 程序已卸载时使用保存的记录，按其安装目录和发布者定位残留
+    let record = lister::find_program_record(&cmd.program_name);
+    if let Some(program) = &record {
+        if !program.aliases.is_empty() {
+            println!("同时匹配别名: {}", program.aliases.join(", "));
+        }
+        if let Some(location) = &program.install_location {
+            println!("记录的安装目录: {}", location);
+        }
+        println!();
+    }
 
-    println!("找到 {} 个痕迹:\n", existing_traces.len());
+    // 流式接收扫描结果，发现即输出
+    let scan_options = cmd.scan.to_options();
+    let (bar, on_progress) = scan_progress_bar();
+    let (mut receiver, scan_stats) = match &record {
+        Some(program) => scanner::scan_traces_stream_with_stats(
+            &program.name,
+            Some(program),
+            &program.aliases,
+            Some(trace_types),
+            &scan_options,
+            Some(on_progress),
+        ),
+        None => scanner::scan_traces_stream_with_stats(
+            &cmd.program_name,
+            None,
+            &[],
+            Some(trace_types),
+            &scan_options,
+            Some(on_progress),
+        ),
+    };
+    let mut existing_traces = Vec::new();
 
     // 按类型分组输出
     let mut registry_count = 0;
     let mut file_count = 0;
     let mut appdata_count = 0;
     let mut shortcut_count = 0;
+    let mut activex_count = 0;
+    let mut history_count = 0;
+    let mut network_count = 0;
+    let mut firewall_count = 0;
+    let mut env_count = 0;
+    let mut menu_count = 0;
+    let mut credential_count = 0;
+    let mut event_log_count = 0;
+    let mut wmi_count = 0;
+    let mut defender_count = 0;
+    let mut startup_count = 0;
 
-    for trace in &existing_traces {
+    while let Some(trace) = receiver.recv().await {
         match trace.trace_type {
             scanner::models::TraceType::RegistryKey => registry_count += 1,
+            scanner::models::TraceType::RegistryValue => startup_count += 1,
             scanner::models::TraceType::File => file_count += 1,
             scanner::models::TraceType::AppData => appdata_count += 1,
             scanner::models::TraceType::Shortcut => shortcut_count += 1,
+            scanner::models::TraceType::ActiveX => activex_count += 1,
+            scanner::models::TraceType::UsageHistory => history_count += 1,
+            scanner::models::TraceType::NetworkSetting => network_count += 1,
+            scanner::models::TraceType::FirewallRule => firewall_count += 1,
+            scanner::models::TraceType::EnvironmentVariable => env_count += 1,
+            scanner::models::TraceType::ContextMenu => menu_count += 1,
+            scanner::models::TraceType::Credential => credential_count += 1,
+            scanner::models::TraceType::EventLogSource => event_log_count += 1,
+            scanner::models::TraceType::WmiProvider => wmi_count += 1,
+            scanner::models::TraceType::DefenderExclusion => defender_count += 1,
             _ => {}
         }
 
-        if cmd.verbose {
-            let confidence = match trace.confidence {
-                scanner::models::Confidence::High => "高",
-                scanner::models::Confidence::Medium => "中",
-                scanner::models::Confidence::Low => "低",
-            };
+        // 输出痕迹时暂时收起进度条，避免与其重叠
+        bar.suspend(|| print_trace(&trace, cmd.verbose));
 
-            println!(
-                "  [{:12}] {} (置信度: {})",
-                format!("{:?}", trace.trace_type),
-                trace.path,
-                confidence
-            );
-        } else {
-            println!(
-                "  [{:12}] {}",
-                format!("{:?}", trace.trace_type),
-                trace.path
-            );
+        // 仅在需要保存结果时保留痕迹，避免深度扫描时内存持续增长
+        if cmd.output.is_some() {
+            existing_traces.push(trace);
         }
     }
+    bar.finish_and_clear();
 
     println!("\n--- 统计 ---");
+    println!(
+        "  共找到: {}",
+        registry_count
+            + file_count
+            + appdata_count
+            + shortcut_count
+            + activex_count
+            + history_count
+            + network_count
+            + firewall_count
+            + env_count
+            + menu_count
+            + credential_count
+            + event_log_count
+            + wmi_count
+            + defender_count
+            + startup_count
+    );
     println!("  注册表: {}", registry_count);
     println!("  文件: {}", file_count);
     println!("  AppData: {}", appdata_count);
     println!("  快捷方式: {}", shortcut_count);
+    println!("  ActiveX/加载项: {}", activex_count);
+    println!("  使用记录: {}", history_count);
+    println!("  网络设置: {}", network_count);
+    println!("  防火墙规则: {}", firewall_count);
+    println!("  环境变量: {}", env_count);
+    println!("  右键菜单: {}", menu_count);
+    println!("  凭据: {}", credential_count);
+    println!("  事件日志源: {}", event_log_count);
+    println!("  WMI 提供程序: {}", wmi_count);
+    println!("  Defender 排除项: {}", defender_count);
+    println!("  自启动项: {}", startup_count);
+
+    // channel 已关闭，所有扫描器都已结束
+    print_scanner_stats(&scan_stats.snapshot(), cmd.verbose);
 
     // 保存到文件
     if let Some(output) = &cmd.output {
-        let json = serde_json::to_string_pretty(&existing_traces)?;
-        std::fs::write(output, json)?;
+        let export = scanner::export::TraceExport::new(&cmd.program_name, existing_traces);
+        scanner::export::save_trace_export(std::path::Path::new(output), &export)?;
         println!("\n结果已保存到: {}", output);
     }
 
     Ok(())
 }
+
+/// 输出一条痕迹，`verbose` 时带命中原因和删除影响
+fn print_trace(trace: &scanner::models::Trace, verbose: bool) {
+    if verbose {
+        let confidence = match trace.confidence {
+            scanner::models::Confidence::High => "高",
+            scanner::models::Confidence::Medium => "中",
+            scanner::models::Confidence::Low => "低",
+        };
+
+        println!(
+            "  [{:12}] {} (置信度: {}，{} 分)",
+            format!("{:?}", trace.trace_type),
+            trace.path,
+            confidence,
+            trace.score
+        );
+        println!("      命中原因: {}", trace.risk.match_reason);
+        if let Some(stats) = &trace.registry_stats {
+            println!("      注册表内容: {}", stats);
+        }
+        println!(
+            "      删除影响: {}{}{}",
+            trace.risk.impact,
+            if trace.risk.shared {
+                "；可能被共用"
+            } else {
+                ""
+            },
+            if trace.risk.reversible {
+                "；可恢复"
+            } else {
+                "；不可恢复"
+            }
+        );
+        for note in &trace.risk.notes {
+            println!("      注意: {}", note);
+        }
+    } else {
+        println!(
+            "  [{:12}] {}{}",
+            format!("{:?}", trace.trace_type),
+            trace.path,
+            trace
+                .registry_stats
+                .map(|stats| format!(" ({})", stats))
+                .unwrap_or_default()
+        );
+    }
+}
+
+/// 输出扫描器统计：失败或超时的扫描器总是列出，`verbose` 时列出全部
+pub fn print_scanner_stats(scanners: &[scanner::models::ScannerStats], verbose: bool) {
+    let incomplete: Vec<_> = scanners.iter().filter(|s| s.is_incomplete()).collect();
+    if !incomplete.is_empty() {
+        println!("\n以下扫描器未正常完成，结果可能不完整:");
+        for scanner in incomplete {
+            let reason = match &scanner.error {
+                Some(error) => error.clone(),
+                None => format!("超时 ({:.0} ms)", scanner.duration_ms),
+            };
+            println!("  {} [{}]: {}", scanner.label, scanner.pattern, reason);
+        }
+    }
+
+    if verbose && !scanners.is_empty() {
+        println!("\n--- 扫描器统计 ---");
+        println!(
+            "  {:<10} {:<20} {:>10} {:>8} {:>8}",
+            "扫描器", "名称", "耗时(ms)", "候选", "命中"
+        );
+        for scanner in scanners {
+            println!(
+                "  {:<10} {:<20} {:>10.1} {:>8} {:>8}",
+                scanner.label,
+                scanner.pattern,
+                scanner.duration_ms,
+                scanner.examined,
+                scanner.matched
+            );
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! self-update 命令 - 检查新版本，下载并替换当前程序
+
+use crate::modules::common::utils;
+use crate::modules::maintenance::update;
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+pub struct SelfUpdateCommand {
+    /// 只检查并显示更新说明，不下载
+    #[arg(long)]
+    pub check: bool,
+
+    /// 发布源地址 (默认读取配置文件的 update.feed，未配置时使用项目的 GitHub Releases)
+    #[arg(long)]
+    pub feed: Option<String>,
+
+    /// 输出格式 (table/json)，只用于 --check
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+pub async fn execute(cmd: SelfUpdateCommand) -> Result<()> {
+    let feed = update::release_feed(cmd.feed.as_deref());
+    let check = update::check_for_update(&feed)?;
+
+    if cmd.check && cmd.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&check)?);
+        return Ok(());
+    }
+
+    println!("当前版本: {}", check.current_version);
+    println!(
+        "最新版本: {}{}",
+        check.latest.version,
+        check
+            .latest
+            .published_at
+            .as_deref()
+            .map(|date| format!(" (发布于 {})", date))
+            .unwrap_or_default()
+    );
+    if !check.update_available {
+        println!("已是最新版本");
+        return Ok(());
+    }
+
+    if !check.latest.changelog.is_empty() {
+        println!("\n--- 更新说明 ---");
+        for line in check.latest.changelog.lines() {
+            println!("  {}", line);
+        }
+        println!();
+    }
+
+    if cmd.check {
+        println!("有可用的新版本，运行 `rust-yu self-update` 安装");
+        return Ok(());
+    }
+
+    if let Some(asset) = &check.latest.asset {
+        println!(
+            "正在下载 {}{}...",
+            asset.name,
+            asset
+                .size
+                .map(|size| format!(" ({})", utils::format_size(size)))
+                .unwrap_or_default()
+        );
+    }
+    let old = update::install_update(&check.latest)?;
+    println!("已更新到 {}", check.latest.version);
+    println!("旧版本已保存为 {}，下次更新时删除", old.display());
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! snapshot 命令 - 导出、导入程序清单快照
+
+use crate::modules::lister::{
+    self,
+    models::ListProgramsQuery,
+    snapshot::{self, SnapshotDiff},
+    storage,
+};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+pub struct SnapshotCommand {
+    #[command(subcommand)]
+    pub action: SnapshotAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotAction {
+    /// 导出当前程序清单（含补全的元数据）
+    Export {
+        /// 快照文件路径
+        file: PathBuf,
+
+        /// 重新扫描而不使用缓存
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// 导入快照并与当前程序清单对比，列出需要重新安装的程序
+    Import {
+        /// 快照文件路径
+        file: PathBuf,
+
+        /// 输出格式 (table/json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+}
+
+pub async fn execute(cmd: SnapshotCommand) -> Result<()> {
+    match cmd.action {
+        SnapshotAction::Export { file, refresh } => {
+            let programs = list_current(refresh)?;
+            let snapshot = snapshot::build_snapshot(&programs);
+            snapshot::write_snapshot(&file, &snapshot)?;
+            println!(
+                "已导出 {} 个程序到 {}",
+                snapshot.programs.len(),
+                file.display()
+            );
+        }
+        SnapshotAction::Import { file, format } => {
+            let imported = snapshot::read_snapshot(&file)?;
+            let current = list_current(false)?;
+            let diff = snapshot::compare(&imported.programs, &current);
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                println!(
+                    "快照: {} ({}，{} 个程序)",
+                    file.display(),
+                    imported.computer_name.as_deref().unwrap_or("未知计算机"),
+                    imported.programs.len()
+                );
+                println!("生成时间: {}", imported.generated_at);
+                print_diff(&diff);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn list_current(refresh: bool) -> Result<Vec<lister::models::InstalledProgram>> {
+    let query = ListProgramsQuery {
+        refresh,
+        cache_ttl_seconds: storage::DEFAULT_CACHE_TTL_SECONDS,
+        ..ListProgramsQuery::default()
+    };
+    Ok(lister::list_programs_with_cache(query)?.programs)
+}
+
+fn print_diff(diff: &SnapshotDiff) {
+    println!("\n需要重新安装 ({}):", diff.missing.len());
+    for program in &diff.missing {
+        println!(
+            "  - {} {}  {}",
+            program.name,
+            program.version.as_deref().unwrap_or_default(),
+            program.publisher.as_deref().unwrap_or_default()
+        );
+    }
+
+    println!("\n版本变化 ({}):", diff.version_changed.len());
+    for change in &diff.version_changed {
+        println!(
+            "  ~ {}: {} -> {}",
+            change.name,
+            change.snapshot_version.as_deref().unwrap_or("?"),
+            change.current_version.as_deref().unwrap_or("?")
+        );
+    }
+
+    println!("\n快照中没有的程序 ({}):", diff.added.len());
+    for program in &diff.added {
+        println!(
+            "  + {} {}",
+            program.name,
+            program.version.as_deref().unwrap_or_default()
+        );
+    }
+
+    println!("\n未变化: {} 个程序\n", diff.unchanged);
+}
@@ -0,0 +1,100 @@
+//! tag 命令 - 为程序添加、移除、整理自定义标签
+
+use crate::modules::lister::{self, models::InstalledProgram, tags};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+pub struct TagCommand {
+    #[command(subcommand)]
+    pub action: TagAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagAction {
+    /// 为程序添加标签
+    Add {
+        /// 程序名称
+        program: String,
+
+        /// 标签，可指定多个
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// 移除程序的标签
+    Remove {
+        /// 程序名称
+        program: String,
+
+        /// 标签，可指定多个
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// 列出所有标签；指定程序时只列出该程序的标签
+    List {
+        /// 程序名称
+        program: Option<String>,
+    },
+
+    /// 重命名标签，新标签已存在时合并
+    Rename { old: String, new: String },
+
+    /// 从所有程序上删除标签
+    Delete { tag: String },
+}
+
+pub async fn execute(cmd: TagCommand) -> Result<()> {
+    match cmd.action {
+        TagAction::Add {
+            program,
+            tags: names,
+        } => {
+            let program = find_program(&program)?;
+            for tag in &names {
+                tags::add_tag(&program, tag)?;
+            }
+            println!("已为 {} 添加标签: {}", program.name, names.join(", "));
+        }
+        TagAction::Remove {
+            program,
+            tags: names,
+        } => {
+            let program = find_program(&program)?;
+            for tag in &names {
+                if !tags::remove_tag(&program, tag)? {
+                    println!("{} 没有标签: {}", program.name, tag);
+                }
+            }
+        }
+        TagAction::List {
+            program: Some(program),
+        } => {
+            let mut programs = vec![find_program(&program)?];
+            tags::apply_tags(&mut programs)?;
+            println!("{}: {}", programs[0].name, programs[0].tags.join(", "));
+        }
+        TagAction::List { program: None } => {
+            let summaries = tags::list_tags()?;
+            for summary in &summaries {
+                println!("{:<30} {} 个程序", summary.tag, summary.program_count);
+            }
+            println!("总计: {} 个标签", summaries.len());
+        }
+        TagAction::Rename { old, new } => {
+            let count = tags::rename_tag(&old, &new)?;
+            println!("已将 {} 个程序的标签 {} 重命名为 {}", count, old, new);
+        }
+        TagAction::Delete { tag } => {
+            let count = tags::delete_tag(&tag)?;
+            println!("已从 {} 个程序上删除标签 {}", count, tag);
+        }
+    }
+
+    Ok(())
+}
+
+fn find_program(name: &str) -> Result<InstalledProgram> {
+    lister::find_program_by_name(name).ok_or_else(|| anyhow::anyhow!("未找到程序: {}", name))
+}
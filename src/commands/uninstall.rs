@@ -1,8 +1,15 @@
 //! uninstall 命令 - 卸载程序并清理残留
 
-use crate::modules::common::utils;
+use crate::modules::common::{progress, utils};
+use crate::modules::lister::health::HealthStatus;
+use crate::modules::lister::models::{InstallScope, InstalledProgram, InstallerKind};
 use crate::modules::lister::storage;
-use crate::modules::{cleaner, lister, scanner};
+use crate::modules::scanner::options::ScanOptions;
+use crate::modules::uninstaller::command::{self as uninstall_command, ExitOutcome};
+use crate::modules::uninstaller::{
+    arp, clickonce, license, package_cache, sandbox, signature, simulation, validation,
+};
+use crate::modules::{cleaner, lister, reporter, scanner};
 use anyhow::Result;
 use clap::Parser;
 
@@ -32,16 +39,89 @@ pub struct UninstallCommand {
     #[arg(long)]
     pub uninstall_string: Option<String>,
 
+    /// 允许执行校验判定为可疑的卸载命令 (危险命令始终拒绝)
+    #[arg(long)]
+    pub allow_suspicious: bool,
+
+    /// 严格签名模式：卸载程序未签名或签名者与发布者不一致时拒绝执行
+    #[arg(long)]
+    pub strict_signature: bool,
+
+    /// 同时删除属于其他用户的痕迹 (与 --confirm 一起使用时生效)
+    #[arg(long)]
+    pub include_other_users: bool,
+
+    /// 同时删除可能包含用户数据的痕迹 (与 --confirm 一起使用时生效)
+    #[arg(long)]
+    pub include_user_data: bool,
+
+    /// 同时删除游戏存档、文档等用户创建的内容 (与 --confirm 一起使用时生效)
+    #[arg(long)]
+    pub include_user_content: bool,
+
+    /// 卸载前将授权、序列号等激活数据备份到隔离区
+    #[arg(long)]
+    pub backup_license: bool,
+
+    /// 清理时一并搜索该程序的第三方驱动包 (按发布者关联，需要管理员权限)
+    #[arg(long)]
+    pub drivers: bool,
+
     /// 输出格式 (table/json)
     #[arg(long, default_value = "table")]
     pub format: String,
+
+    /// 远程计算机名，通过 PowerShell 远程处理 (WinRM) 操作该计算机
+    #[arg(long)]
+    pub computer: Option<String>,
+
+    /// 卸载程序所在的整个套件：先卸载更新程序、插件等辅助组件，再卸载主程序
+    #[arg(long)]
+    pub suite: bool,
+
+    /// 按安装程序技术 (NSIS、Inno Setup 等) 追加静默参数，不显示卸载界面 (MSI 始终静默)
+    #[arg(long)]
+    pub silent: bool,
+
+    /// 模拟卸载：不执行任何命令，生成报告说明将执行的命令、预计残留、释放空间、需要提升权限和会被拦截的项
+    #[arg(long)]
+    pub simulate: bool,
+
+    /// 实验性：模拟时先在 Windows 沙盒中试运行卸载命令，记录退出码、删除的文件和启动的进程 (需与 --simulate 一起使用)
+    #[arg(long, requires = "simulate")]
+    pub sandbox_trial: bool,
 }
 
-pub async fn execute(cmd: UninstallCommand) -> Result<()> {
+pub async fn execute(mut cmd: UninstallCommand) -> Result<()> {
+    if cmd.simulate {
+        if cmd.computer.is_some() || cmd.suite {
+            anyhow::bail!("模拟卸载暂不支持 --computer 和 --suite");
+        }
+        return simulate_uninstall(&cmd).await;
+    }
+
+    if let Some(computer) = &cmd.computer {
+        if cmd.suite {
+            anyhow::bail!("远程卸载暂不支持 --suite");
+        }
+        return super::remote::uninstall(
+            computer,
+            &cmd.target,
+            cmd.confirm,
+            cmd.clean,
+            cmd.allow_suspicious,
+        );
+    }
+
+    if cmd.suite {
+        cmd.target = uninstall_suite_helpers(&cmd).await?;
+    }
+
     println!("=== 卸载程序: {} ===\n", cmd.target);
 
     // 1. 查找程序并保存注册表信息
     println!("[1/4] 搜索程序并保存注册表信息...");
+    progress::advance("uninstall", 0, 4, Some("find_program"));
     let program = find_and_save_program(&cmd.target, cmd.uninstall_string.as_deref())?;
 
     if let Some(prog) = &program {
@@ -52,43 +132,88 @@ pub async fn execute(cmd: UninstallCommand) -> Result<()> {
         if let Some(version) = &prog.version {
             println!("  - 版本: {}", version);
         }
+        if prog.installer_kind != InstallerKind::Unknown {
+            println!("  - 安装程序: {}", prog.installer_kind);
+        }
+        if prog.install_scope != InstallScope::Unknown {
+            println!("  - 安装范围: {}", prog.install_scope);
+        }
+        if prog.install_scope.requires_elevation() && !utils::is_elevated() {
+            println!("  - 警告: 该程序为所有用户安装，卸载可能需要以管理员身份运行");
+        }
         if let Some(location) = &prog.install_location {
             println!("  - 安装位置: {}", location);
         }
+        if let Some(source) = &prog.installer_source {
+            println!("  - 安装包位置: {}", source);
+        }
+        warn_if_running(prog);
+        warn_if_unhealthy(prog);
     } else {
         println!("  - 未在已安装程序中找到，将尝试直接执行卸载命令");
     }
 
+    if cmd.backup_license {
+        backup_license_data(&cmd.target, program.as_ref()).await;
+    }
+
     // 2. 执行卸载命令并等待
     println!("\n[2/4] 执行卸载命令并等待进程结束...");
+    progress::advance("uninstall", 1, 4, Some("run_uninstaller"));
 
     let uninstall_str = program
         .as_ref()
         .and_then(|p| p.uninstall_string.clone())
-        .or(cmd.uninstall_string);
+        .or_else(|| cmd.uninstall_string.clone());
 
-    if let Some(uninstall_str) = uninstall_str {
-        println!("  - 卸载命令: {}", uninstall_str);
+    // 卸载程序已被删除的条目无法执行卸载，直接按强制移除处理
+    let broken_entry = program.as_ref().is_some_and(arp::is_broken_entry);
 
-        // 执行卸载并等待进程组结束
-        match run_uninstall_with_wait(&uninstall_str, cmd.timeout).await {
-            Ok(_) => {
-                println!("  - 卸载进程已结束");
-            }
-            Err(e) => {
-                println!("  - 警告: 卸载进程等待超时或出错: {}", e);
-            }
-        }
+    if broken_entry {
+        println!("  - 卸载程序已不存在，跳过卸载命令，按强制移除处理");
+    } else if let Some(uninstall_str) = uninstall_str {
+        run_checked_uninstall(&uninstall_str, program.as_ref(), &cmd).await?;
     } else {
         println!("  - 未找到卸载命令");
     }
 
     // 3. 如果需要清理残留
+    progress::advance("uninstall", 2, 4, Some("clean_leftovers"));
     if cmd.clean {
         println!("\n[3/4] 搜索残留痕迹...");
 
-        // 搜索残留
-        let traces = scanner::scan_all_traces(&cmd.target, None).await?;
+        // 搜索残留：有程序记录时同时按安装目录、发布者以及 Squirrel 应用目录定位
+        let (bar, on_progress) = super::search::scan_progress_bar();
+        let scan = match program.as_ref() {
+            Some(program) => scanner::scan_program_traces(
+                program,
+                None,
+                None,
+                &ScanOptions::default(),
+                Some(on_progress),
+            )
+            .await
+            .map(|summary| summary.traces),
+            None => {
+                scanner::scan_all_traces(
+                    &cmd.target,
+                    None,
+                    None,
+                    &ScanOptions::default(),
+                    Some(on_progress),
+                )
+                .await
+            }
+        };
+        bar.finish_and_clear();
+        let mut traces = scan?;
+        if cmd.drivers {
+            let publisher = program.as_ref().and_then(|p| p.publisher.as_deref());
+            match scanner::drivers::scan_driver_traces(&cmd.target, publisher) {
+                Ok(driver_traces) => traces.extend(driver_traces),
+                Err(e) => println!("  - 警告: 枚举驱动包失败: {}", e),
+            }
+        }
         let existing_traces: Vec<_> = traces.into_iter().filter(|t| t.exists).collect();
 
         println!("  - 找到 {} 个残留痕迹\n", existing_traces.len());
@@ -100,7 +225,15 @@ pub async fn execute(cmd: UninstallCommand) -> Result<()> {
             if cmd.confirm {
                 // 直接执行清理
                 println!("  执行清理中...\n");
-                let results = cleaner::clean_traces(existing_traces.clone(), true).await?;
+                let options = cleaner::models::CleanOptions {
+                    include_other_users: cmd.include_other_users,
+                    include_user_data: cmd.include_user_data,
+                    include_user_content: cmd.include_user_content,
+                    archive: false,
+                };
+                let results =
+                    cleaner::clean_traces_with_options(existing_traces.clone(), true, options)
+                        .await?;
 
                 // 统计结果
                 let success_count = results.iter().filter(|r| r.success).count();
@@ -120,7 +253,7 @@ pub async fn execute(cmd: UninstallCommand) -> Result<()> {
                         .map(|s| utils::format_size(s))
                         .unwrap_or_default();
                     println!(
-                        "  [{}] {:12} {} {}",
+                        "  [{}] {:12} {} {}{}",
                         i + 1,
                         format!("{:?}", trace.trace_type),
                         trace.path,
@@ -128,7 +261,8 @@ pub async fn execute(cmd: UninstallCommand) -> Result<()> {
                             format!("({})", size)
                         } else {
                             String::new()
-                        }
+                        },
+                        super::clean::trace_markers(trace)
                     );
                 }
 
@@ -163,8 +297,41 @@ pub async fn execute(cmd: UninstallCommand) -> Result<()> {
                         .filter_map(|&i| existing_traces.get(i - 1).cloned())
                         .collect();
 
+                    // 选中了其他用户、可能含用户数据或用户内容的痕迹时单独确认
+                    let other_user_count = traces_to_delete
+                        .iter()
+                        .filter(|t| t.owner.is_some())
+                        .count();
+                    let user_data_count = traces_to_delete
+                        .iter()
+                        .filter(|t| t.user_data.is_some())
+                        .count();
+                    let user_content_count = traces_to_delete
+                        .iter()
+                        .filter(|t| t.category == scanner::models::TraceCategory::UserContent)
+                        .count();
+                    let options = cleaner::models::CleanOptions {
+                        include_other_users: other_user_count > 0
+                            && confirm_prompt(&format!(
+                                "其中 {} 项属于其他用户，确认一并删除?",
+                                other_user_count
+                            ))?,
+                        include_user_data: user_data_count > 0
+                            && confirm_prompt(&format!(
+                                "其中 {} 项可能包含用户数据，确认一并删除?",
+                                user_data_count
+                            ))?,
+                        include_user_content: user_content_count > 0
+                            && confirm_prompt(&format!(
+                                "其中 {} 项是存档、文档等用户内容，确认一并删除?",
+                                user_content_count
+                            ))?,
+                        archive: false,
+                    };
+
                     println!("\n  删除 {} 项...\n", traces_to_delete.len());
-                    let results = cleaner::clean_traces(traces_to_delete, true).await?;
+                    let results =
+                        cleaner::clean_traces_with_options(traces_to_delete, true, options).await?;
 
                     let success_count = results.iter().filter(|r| r.success).count();
                     println!("  成功删除: {}", success_count);
@@ -175,7 +342,29 @@ pub async fn execute(cmd: UninstallCommand) -> Result<()> {
         println!("\n[3/4] 跳过清理 (未指定 --clean)");
     }
 
+    // 强制移除完成后删除失效的卸载条目，使其不再出现在系统的应用列表中
+    if let Some(prog) = program.as_ref().filter(|_| broken_entry) {
+        if cmd.clean && cmd.confirm {
+            match arp::remove_arp_entry(prog) {
+                Ok(Some(removal)) => println!(
+                    "\n  - 已删除失效的卸载条目: {} (备份: {})",
+                    removal.registry_key, removal.backup_path
+                ),
+                Ok(None) => {}
+                Err(e) => println!("\n  - 警告: 删除失效的卸载条目失败: {}", e),
+            }
+        } else {
+            println!("\n  - 卸载条目已失效，使用 --clean --confirm 可在清理后将其删除");
+        }
+    }
+
+    // 厂商卸载程序遗留的安装包缓存
+    if let Some(prog) = &program {
+        offer_package_cache_cleanup(prog, &cmd).await?;
+    }
+
     // 4. 清理保存的程序信息
+    progress::advance("uninstall", 3, 4, Some("cleanup_record"));
     if !cmd.preserve {
         println!("\n[4/4] 清理保存的程序信息...");
         storage::delete_saved_program(&cmd.target)?;
@@ -188,25 +377,402 @@ pub async fn execute(cmd: UninstallCommand) -> Result<()> {
     storage::invalidate_scan_cache_for_program(&cmd.target)?;
     println!("  - 已失效安装列表缓存");
 
+    progress::advance("uninstall", 4, 4, None);
     println!("\n=== 卸载完成 ===");
     Ok(())
 }
 
+/// 卸载完成后报告遗留的安装包缓存：指定 --clean 时询问是否删除，与 --confirm 一起使用时直接删除
+async fn offer_package_cache_cleanup(
+    program: &InstalledProgram,
+    cmd: &UninstallCommand,
+) -> Result<()> {
+    if package_cache::is_still_installed(program) {
+        return Ok(());
+    }
+    let traces = package_cache::cached_installer_traces(program);
+    if traces.is_empty() {
+        return Ok(());
+    }
+
+    let total_size: u64 = traces.iter().filter_map(|trace| trace.size).sum();
+    println!(
+        "\n  - 发现 {} 个遗留的安装包缓存 ({}):",
+        traces.len(),
+        utils::format_size(total_size)
+    );
+    for trace in &traces {
+        println!("      {}", trace.path);
+    }
+
+    if !cmd.clean {
+        println!("  - 使用 --clean 可在卸载后删除安装包缓存");
+        return Ok(());
+    }
+    if !cmd.confirm && !confirm_prompt("是否删除这些安装包缓存?")? {
+        return Ok(());
+    }
+
+    let results =
+        cleaner::clean_traces_with_options(traces, true, cleaner::models::CleanOptions::default())
+            .await?;
+    let success_count = results.iter().filter(|result| result.success).count();
+    let freed: u64 = results.iter().map(|result| result.bytes_freed).sum();
+    println!(
+        "  - 已删除 {} 个安装包缓存，释放 {}",
+        success_count,
+        utils::format_size(freed)
+    );
+    Ok(())
+}
+
+/// 安装目录下有进程在运行时提醒用户先关闭，被占用的文件可能删不掉
+fn warn_if_running(program: &InstalledProgram) {
+    let running = lister::processes::running_in(program);
+    if running.is_empty() {
+        return;
+    }
+    let names: Vec<String> = running
+        .iter()
+        .map(|process| format!("{} (PID {})", process.file_name(), process.pid))
+        .collect();
+    println!(
+        "  - 警告: 程序正在运行: {}，建议先关闭，否则部分文件可能无法删除",
+        names.join(", ")
+    );
+}
+
+/// 安装不完整时列出缺失的文件，便于判断应先修复安装还是强制移除
+fn warn_if_unhealthy(program: &InstalledProgram) {
+    let health = &program.health;
+    if !matches!(health.status, HealthStatus::Degraded | HealthStatus::Broken) {
+        return;
+    }
+    println!("  - 警告: 安装{}", health.status);
+    for issue in &health.issues {
+        println!("      {}", issue.description);
+    }
+}
+
+/// 模拟卸载并保存报告，不执行卸载命令，也不删除任何内容
+async fn simulate_uninstall(cmd: &UninstallCommand) -> Result<()> {
+    println!("=== 模拟卸载: {} ===\n", cmd.target);
+
+    let program = find_program(&cmd.target, cmd.uninstall_string.as_deref())?
+        .ok_or_else(|| anyhow::anyhow!("未找到程序: {}", cmd.target))?;
+    println!("  - 找到程序: {}", program.name);
+    warn_if_running(&program);
+    warn_if_unhealthy(&program);
+
+    println!("  - 搜索痕迹...");
+    let scan =
+        scanner::scan_program_traces(&program, None, None, &ScanOptions::default(), None).await?;
+    let mut traces = scan.traces;
+    if cmd.drivers {
+        match scanner::drivers::scan_driver_traces(&cmd.target, program.publisher.as_deref()) {
+            Ok(driver_traces) => traces.extend(driver_traces),
+            Err(e) => println!("  - 警告: 枚举驱动包失败: {}", e),
+        }
+    }
+
+    let options = simulation::SimulationOptions {
+        silent: cmd.silent,
+        allow_suspicious: cmd.allow_suspicious,
+        strict_signature: cmd.strict_signature,
+        clean: cleaner::models::CleanOptions {
+            include_other_users: cmd.include_other_users,
+            include_user_data: cmd.include_user_data,
+            include_user_content: cmd.include_user_content,
+            archive: false,
+        },
+    };
+    let mut result = simulation::simulate_uninstall(&program, &traces, options);
+    if cmd.sandbox_trial {
+        run_sandbox_trial(&program, &mut result, cmd.timeout).await;
+    }
+
+    println!();
+    match &result.command {
+        Some(command) => println!("  卸载命令: {}", command),
+        None => println!("  卸载命令: (不执行)"),
+    }
+    if let Some(reason) = &result.command_refused {
+        println!("  - 警告: 将拒绝执行: {}", reason);
+    } else if let Some(problem) = &result.signature_problem {
+        println!("  - 警告: {}", problem);
+    }
+    for note in &result.notes {
+        println!("  - {}", note);
+    }
+    if let Some(trial) = &result.sandbox {
+        println!("  沙盒试运行:");
+        match trial.exit_code {
+            Some(code) => println!("    退出码: {} ({} 秒)", code, trial.duration_secs),
+            None => println!("    未在限定时间内结束"),
+        }
+        for finding in &trial.findings {
+            println!("    - {}", finding);
+        }
+    }
+    println!("  卸载程序预计移除: {} 项", result.removed_by_uninstaller);
+    println!("  预计残留: {} 项", result.remaining.len());
+    println!("  清理时拦截: {} 项", result.blocked.len());
+    println!(
+        "  需要管理员权限: {} 项{}",
+        result.elevation_required.len(),
+        if result.elevated {
+            "（当前已提升）"
+        } else {
+            ""
+        }
+    );
+    println!(
+        "  预计释放空间: {}",
+        utils::format_size(result.estimated_reclaimed)
+    );
+
+    let report = reporter::models::UninstallerReport::new(program.name.clone())
+        .with_traces(traces)
+        .with_simulation(result)
+        .with_scanner_stats(scan.scanners);
+    let stored = reporter::storage::save_report(&report)?;
+    println!(
+        "\n模拟报告已生成: {}",
+        stored.html_path.as_deref().unwrap_or(&stored.json_path)
+    );
+
+    Ok(())
+}
+
+/// 在 Windows 沙盒中试运行将要执行的卸载命令，结果附加到模拟结果中
+async fn run_sandbox_trial(
+    program: &InstalledProgram,
+    result: &mut simulation::UninstallSimulation,
+    timeout: u64,
+) {
+    let Some(command) = result.command.clone() else {
+        result
+            .notes
+            .push("没有卸载命令，跳过沙盒试运行".to_string());
+        return;
+    };
+    if !sandbox::sandbox_available() {
+        result
+            .notes
+            .push("Windows 沙盒不可用，跳过试运行".to_string());
+        return;
+    }
+
+    println!("  - 正在 Windows 沙盒中试运行卸载命令，完成后沙盒会自动关闭...");
+    let program = program.clone();
+    let trial = tokio::task::spawn_blocking(move || {
+        sandbox::run_sandbox_trial(&program, &command, std::time::Duration::from_secs(timeout))
+    })
+    .await;
+    match trial {
+        Ok(Ok(trial)) => result.sandbox = Some(trial),
+        Ok(Err(e)) => result.notes.push(format!("沙盒试运行失败: {}", e)),
+        Err(e) => result.notes.push(format!("沙盒试运行失败: {}", e)),
+    }
+}
+
+/// 校验卸载命令和签名后执行，并等待进程组结束
+async fn run_checked_uninstall(
+    uninstall_str: &str,
+    program: Option<&InstalledProgram>,
+    cmd: &UninstallCommand,
+) -> Result<()> {
+    println!("  - 卸载命令: {}", uninstall_str);
+    let publisher = program.and_then(|p| p.publisher.as_deref());
+    let kind = program.map_or(InstallerKind::Unknown, |p| p.installer_kind);
+
+    // 执行前校验命令，拒绝危险命令，可疑命令需显式允许
+    let checked = validation::ensure_uninstall_allowed(uninstall_str, cmd.allow_suspicious)?;
+
+    // 校验卸载程序签名，防止卸载项被劫持
+    if let Some(check) =
+        signature::check_command_signature(&checked, publisher, cmd.strict_signature)?
+    {
+        if let Some(signer) = &check.signer {
+            println!("  - 签名者: {}", signer);
+        }
+        if let Some(problem) = check.problem() {
+            println!("  - 警告: {}", problem);
+        }
+    }
+
+    // 执行卸载并等待进程组结束
+    match run_uninstall_with_wait(uninstall_str, kind, cmd.silent, cmd.timeout).await {
+        Ok(None) | Ok(Some(ExitOutcome::Success)) => {
+            println!("  - 卸载进程已结束");
+        }
+        Ok(Some(ExitOutcome::RebootRequired)) => {
+            println!("  - 卸载完成，需要重启计算机");
+        }
+        Ok(Some(ExitOutcome::AlreadyRemoved)) => {
+            println!("  - 程序已不存在，无需卸载");
+        }
+        Ok(Some(ExitOutcome::Cancelled)) => {
+            println!("  - 警告: 卸载已被取消");
+        }
+        Ok(Some(ExitOutcome::Failed)) => {
+            println!("  - 警告: 卸载程序返回失败");
+        }
+        Err(e) => {
+            println!("  - 警告: 卸载进程等待超时或出错: {}", e);
+        }
+    }
+
+    // ClickOnce 卸载只移除卸载项，部署文件要清理联机应用缓存才会删除
+    if kind == InstallerKind::ClickOnce {
+        match clickonce::clean_online_app_cache() {
+            Ok(()) => println!("  - 已清理 ClickOnce 应用缓存"),
+            Err(e) => println!("  - 警告: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// 依次卸载套件中的辅助组件，返回主程序名称，随后按普通流程卸载并清理主程序
+async fn uninstall_suite_helpers(cmd: &UninstallCommand) -> Result<String> {
+    let suite = lister::suites::find_suite(&cmd.target)?
+        .ok_or_else(|| anyhow::anyhow!("未找到包含 {} 的套件", cmd.target))?;
+
+    println!("=== 卸载套件: {} ===\n", suite.name);
+    println!("  安装目录: {}", suite.install_root);
+    for (index, member) in suite.members.iter().enumerate() {
+        let role = match member.role {
+            lister::suites::SuiteRole::Main => "主程序",
+            lister::suites::SuiteRole::Helper => "组件",
+        };
+        println!("  {}. [{}] {}", index + 1, role, member.program.name);
+    }
+
+    for member in &suite.members {
+        if member.role == lister::suites::SuiteRole::Main {
+            continue;
+        }
+
+        let program = &member.program;
+        println!("\n--- 卸载组件: {} ---", program.name);
+        if arp::is_broken_entry(program) {
+            println!("  - 卸载程序已不存在，跳过");
+            continue;
+        }
+        let Some(uninstall_str) = &program.uninstall_string else {
+            println!("  - 未找到卸载命令，跳过");
+            continue;
+        };
+
+        storage::save_program_snapshot(std::slice::from_ref(program))?;
+        run_checked_uninstall(uninstall_str, Some(program), cmd).await?;
+        storage::invalidate_scan_cache_for_program(&program.name)?;
+    }
+
+    println!();
+    Ok(suite.name)
+}
+
+/// 询问用户是否确认，输入 y 视为确认
+pub(super) fn confirm_prompt(message: &str) -> Result<bool> {
+    use std::io::Write;
+
+    println!("\n  {} (y/N)", message);
+    print!("  > ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
 /// 查找程序并保存注册表信息
+/// 卸载前备份授权数据，失败只提示不中断卸载
+async fn backup_license_data(target: &str, program: Option<&lister::models::InstalledProgram>) {
+    println!("\n  备份授权数据...");
+
+    let trace_types = vec![
+        scanner::models::TraceType::RegistryKey,
+        scanner::models::TraceType::File,
+        scanner::models::TraceType::AppData,
+    ];
+    let scan = match program {
+        Some(program) => scanner::scan_program_traces(
+            program,
+            Some(trace_types),
+            None,
+            &ScanOptions::default(),
+            None,
+        )
+        .await
+        .map(|summary| summary.traces),
+        None => {
+            scanner::scan_all_traces(
+                target,
+                Some(trace_types),
+                None,
+                &ScanOptions::default(),
+                None,
+            )
+            .await
+        }
+    };
+    let mut traces = match scan {
+        Ok(traces) => traces,
+        Err(e) => {
+            println!("  - 警告: 搜索授权数据失败: {}", e);
+            return;
+        }
+    };
+
+    // 安装目录不一定以程序名命名，按程序记录扫描时公共目录不会报告，单独加入
+    if let Some(location) = program
+        .and_then(|p| p.install_location.clone())
+        .filter(|location| !traces.iter().any(|trace| trace.path == *location))
+    {
+        traces.push(scanner::models::Trace::new(
+            target.to_string(),
+            scanner::models::TraceType::File,
+            location,
+        ));
+    }
+
+    match license::backup_license_data(target, &traces) {
+        Ok(Some(backup)) => {
+            println!("  - 已备份 {} 项授权数据", backup.items.len());
+            println!("  - 备份目录: {}", backup.archive_dir);
+        }
+        Ok(None) => println!("  - 未发现授权数据"),
+        Err(e) => println!("  - 警告: 备份授权数据失败: {}", e),
+    }
+}
+
 fn find_and_save_program(
     target: &str,
     uninstall_string: Option<&str>,
+) -> Result<Option<lister::models::InstalledProgram>> {
+    let program = find_program(target, uninstall_string)?;
+    if let Some(program) = &program {
+        // 保存到存储
+        storage::save_program_snapshot(std::slice::from_ref(program))?;
+    }
+    Ok(program)
+}
+
+/// 查找程序，不保存
+fn find_program(
+    target: &str,
+    uninstall_string: Option<&str>,
 ) -> Result<Option<lister::models::InstalledProgram>> {
     // 如果提供了 uninstall_string，直接创建程序信息
     if let Some(uninstall_str) = uninstall_string {
-        let program = lister::models::InstalledProgram::new(
+        let mut program = lister::models::InstalledProgram::new(
             target.to_string(),
             lister::models::InstallSource::Registry,
         );
-        let mut prog = program;
-        prog.uninstall_string = Some(uninstall_str.to_string());
-        storage::save_program_snapshot(&[prog.clone()])?;
-        return Ok(Some(prog));
+        program.uninstall_string = Some(uninstall_str.to_string());
+        return Ok(Some(program));
     }
 
     // 搜索已安装的程序
@@ -218,33 +784,37 @@ fn find_and_save_program(
         .into_iter()
         .find(|p| p.name.to_lowercase().contains(&target_lower));
 
-    if let Some(program) = matched {
-        // 保存到存储
-        storage::save_program_snapshot(&[program.clone()])?;
-        Ok(Some(program))
-    } else {
-        Ok(None)
-    }
+    // 程序列表中没有时再按 Windows 功能查找，卸载命令即 DISM 禁用命令
+    Ok(match matched {
+        Some(program) => Some(program),
+        None => lister::features::find_feature(target)
+            .unwrap_or_else(|error| {
+                tracing::debug!("查找 Windows 功能失败: {}", error);
+                None
+            })
+            .filter(|feature| feature.enabled)
+            .map(|feature| feature.to_program()),
+    })
 }
 
-/// 执行卸载命令并等待进程组结束
-async fn run_uninstall_with_wait(uninstall_string: &str, timeout_secs: u64) -> Result<()> {
+/// 执行卸载命令并等待进程组结束，返回按安装程序技术解释的退出码（无法获取时为 None）
+async fn run_uninstall_with_wait(
+    uninstall_string: &str,
+    kind: InstallerKind,
+    silent: bool,
+    timeout_secs: u64,
+) -> Result<Option<ExitOutcome>> {
     use std::process::{Command, Stdio};
     use std::time::Duration;
 
-    // 处理常见的卸载命令格式
-    let cmd_str = if uninstall_string.to_lowercase().starts_with("msiexec") {
-        format!("{} /quiet /norestart", uninstall_string)
-    } else {
-        uninstall_string.to_string()
-    };
+    let cmd_str = uninstall_command::prepare_uninstall_command(uninstall_string, kind, silent);
 
     tracing::info!("执行卸载命令: {}", cmd_str);
 
     // 使用 spawn 而不是 output，这样我们可以获取 PID
     #[cfg(windows)]
     {
-        let child = Command::new("cmd")
+        let mut child = Command::new("cmd")
             .args(["/C", &cmd_str])
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -258,17 +828,22 @@ async fn run_uninstall_with_wait(uninstall_string: &str, timeout_secs: u64) -> R
 
         // 额外等待一段时间，确保清理完成
         tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let outcome = child
+            .try_wait()?
+            .and_then(|status| status.code())
+            .map(|code| uninstall_command::classify_exit_code(kind, code));
+        Ok(outcome)
     }
 
     #[cfg(not(windows))]
     {
+        let _ = timeout_secs;
         let output = Command::new("cmd").args(["/C", &cmd_str]).output()?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("卸载命令执行失败: {}", stderr);
-        }
+        Ok(output
+            .status
+            .code()
+            .map(|code| uninstall_command::classify_exit_code(kind, code)))
     }
-
-    Ok(())
 }
@@ -0,0 +1,46 @@
+//! watch 命令 - 监视新安装的程序并记录安装日志
+
+use crate::modules::watcher;
+use anyhow::Result;
+use clap::Parser;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Parser, Debug)]
+pub struct WatchCommand {
+    /// 轮询间隔（秒）
+    #[arg(long, default_value_t = watcher::DEFAULT_POLL_INTERVAL_SECONDS)]
+    pub interval: u64,
+}
+
+pub async fn execute(cmd: WatchCommand) -> Result<()> {
+    println!("正在监视新安装的程序，按 Ctrl+C 停止...\n");
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let interval = Duration::from_secs(cmd.interval.max(1));
+
+    let watcher_stop = stop.clone();
+    let handle = tokio::task::spawn_blocking(move || {
+        watcher::watch_installations(interval, &watcher_stop, |log, path| {
+            println!("检测到新安装: {}", log.program_name);
+            if let Some(version) = &log.version {
+                println!("  版本: {}", version);
+            }
+            println!("  新增目录: {}", log.new_paths.len());
+            println!("  新增注册表项: {}", log.new_registry_keys.len());
+            let unattributed = log.unattributed_paths.len() + log.unattributed_registry_keys.len();
+            if unattributed > 0 {
+                println!("  同期其他变化: {} (无法确认属于该程序)", unattributed);
+            }
+            println!("  安装日志: {}\n", path.display());
+        })
+    });
+
+    tokio::signal::ctrl_c().await?;
+    stop.store(true, Ordering::Relaxed);
+    println!("\n正在停止监视...");
+
+    handle.await??;
+    Ok(())
+}
@@ -5,5 +5,9 @@ pub use modules::cleaner;
 pub use modules::common::error::UninstallerError;
 pub use modules::common::utils;
 pub use modules::lister;
+pub use modules::maintenance;
+pub use modules::remote;
 pub use modules::reporter;
 pub use modules::scanner;
+pub use modules::uninstaller;
+pub use modules::watcher;
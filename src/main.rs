@@ -16,6 +16,14 @@ struct Cli {
     /// 详细输出模式
     #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// 运行优先级 (interactive|background)，background 以低 CPU 与 I/O 优先级运行
+    #[arg(long, global = true, default_value = "interactive")]
+    priority: modules::common::priority::PriorityProfile,
+
+    /// 进度输出方式 (text|json)，json 时向 stderr 逐行输出进度事件，供包装脚本显示进度
+    #[arg(long, global = true, default_value = "text")]
+    progress: modules::common::progress::ProgressMode,
 }
 
 #[tokio::main]
@@ -31,15 +39,37 @@ async fn main() -> Result<()> {
         modules::common::logging::init_logging(true);
     }
 
+    modules::common::priority::set_process_profile(cli.priority);
+    modules::common::progress::set_mode(cli.progress);
+
     // 执行命令
     let result = match cli.command {
         commands::Command::List(cmd) => commands::list::execute(cmd).await,
         commands::Command::Search(cmd) => commands::search::execute(cmd).await,
-        commands::Command::Clean(cmd) => commands::clean::execute(cmd).await,
+        commands::Command::Clean(cmd) => commands::clean::execute(*cmd).await,
         commands::Command::Report(cmd) => commands::report::execute(cmd).await,
         commands::Command::Uninstall(cmd) => commands::uninstall::execute(cmd).await,
+        commands::Command::Leftovers(cmd) => commands::leftovers::execute(cmd).await,
+        commands::Command::Bench(cmd) => commands::bench::execute(cmd).await,
+        commands::Command::Watch(cmd) => commands::watch::execute(cmd).await,
+        commands::Command::Maintain(cmd) => commands::maintain::execute(cmd).await,
+        commands::Command::Schedule(cmd) => commands::schedule::execute(cmd).await,
+        commands::Command::Export(cmd) => commands::export::execute(cmd).await,
+        commands::Command::Feature(cmd) => commands::feature::execute(cmd).await,
+        commands::Command::Drivers(cmd) => commands::drivers::execute(cmd).await,
+        commands::Command::Provisioned(cmd) => commands::provisioned::execute(cmd).await,
+        commands::Command::Tag(cmd) => commands::tag::execute(cmd).await,
+        commands::Command::Note(cmd) => commands::note::execute(cmd).await,
+        commands::Command::Pin(cmd) => commands::pin::execute(cmd).await,
+        commands::Command::Keep(cmd) => commands::keep::execute(cmd).await,
+        commands::Command::Ignore(cmd) => commands::ignore::execute(cmd).await,
+        commands::Command::Snapshot(cmd) => commands::snapshot::execute(cmd).await,
+        commands::Command::Doctor(cmd) => commands::doctor::execute(cmd).await,
+        commands::Command::SelfUpdate(cmd) => commands::self_update::execute(cmd).await,
     };
 
+    modules::common::progress::finish(result.as_ref().err().map(|e| e.to_string()).as_deref());
+
     match result {
         Ok(_) => {}
         Err(e) => {
@@ -0,0 +1,101 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::activex::{
+    self, BHO_KEYS, CLSID_KEYS, DISTRIBUTION_UNITS_KEY, TOOLBAR_KEYS,
+};
+use crate::modules::scanner::models::Trace;
+use winreg::enums::*;
+use winreg::RegKey;
+
+/// 当前用户的 IE 工具栏与加载项设置，值名或子项名为 CLSID
+const USER_TOOLBAR_KEYS: &[&str] = &[
+    r"Software\Microsoft\Internet Explorer\Toolbar\WebBrowser",
+    r"Software\Microsoft\Internet Explorer\Toolbar\ShellBrowser",
+];
+const USER_EXT_KEYS: &[&str] = &[
+    r"Software\Microsoft\Windows\CurrentVersion\Ext\Settings",
+    r"Software\Microsoft\Windows\CurrentVersion\Ext\Stats",
+];
+
+/// 注销 ActiveX 控件或浏览器加载项
+///
+/// 删除该 CLSID 的 BHO、Code Store Database、工具栏和 COM 类注册，
+/// 控件文件只在位于 `Downloaded Program Files` 时删除，其他位置的文件交给文件痕迹处理
+pub async fn delete_activex_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let Some(clsid) = activex::clsid_from_path(&trace.path) else {
+        return Err(UninstallerError::Other(format!(
+            "无法从路径中识别 CLSID: {}",
+            trace.path
+        )));
+    };
+
+    // 注册表项删除后无法再查到文件，先收集
+    let files = activex::control_files(clsid);
+
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let mut errors = Vec::new();
+    let mut record = |result: std::io::Result<()>, location: String| {
+        if let Err(e) = result {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                errors.push(format!("{}: {}", location, e));
+            }
+        }
+    };
+
+    for key in BHO_KEYS {
+        let path = format!(r"{}\{}", key, clsid);
+        record(hklm.delete_subkey_all(&path), format!(r"HKLM\{}", path));
+    }
+    let path = format!(r"{}\{}", DISTRIBUTION_UNITS_KEY, clsid);
+    record(hklm.delete_subkey_all(&path), format!(r"HKLM\{}", path));
+
+    for key in TOOLBAR_KEYS {
+        record(delete_value(&hklm, key, clsid), format!(r"HKLM\{}", key));
+    }
+    for key in USER_TOOLBAR_KEYS {
+        record(delete_value(&hkcu, key, clsid), format!(r"HKCU\{}", key));
+    }
+    for key in USER_EXT_KEYS {
+        let path = format!(r"{}\{}", key, clsid);
+        record(hkcu.delete_subkey_all(&path), format!(r"HKCU\{}", path));
+    }
+
+    for (hkey, key) in CLSID_KEYS {
+        let path = format!(r"{}\{}", key, clsid);
+        let (root, hive) = if *hkey == HKEY_LOCAL_MACHINE {
+            (&hklm, "HKLM")
+        } else {
+            (&hkcu, "HKCU")
+        };
+        record(root.delete_subkey_all(&path), format!(r"{}\{}", hive, path));
+    }
+
+    let mut bytes_freed = 0;
+    for file in &files {
+        let size = std::fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+        match std::fs::remove_file(file) {
+            Ok(()) => bytes_freed += size,
+            Err(e) => errors.push(format!("{}: {}", file.display(), e)),
+        }
+    }
+
+    if errors.is_empty() {
+        tracing::info!("已注销 ActiveX 控件: {}", clsid);
+    } else {
+        tracing::error!("注销 ActiveX 控件 {} 未完成: {}", clsid, errors.join("; "));
+    }
+
+    Ok(CleanResult {
+        trace_id: trace.id.clone(),
+        path: trace.path.clone(),
+        success: errors.is_empty(),
+        error: (!errors.is_empty()).then(|| errors.join("; ")),
+        bytes_freed,
+    })
+}
+
+fn delete_value(root: &RegKey, key: &str, name: &str) -> std::io::Result<()> {
+    root.open_subkey_with_flags(key, KEY_SET_VALUE)?
+        .delete_value(name)
+}
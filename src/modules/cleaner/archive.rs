@@ -0,0 +1,336 @@
+//! 删除前压缩归档
+//!
+//! 开启归档后，文件和 AppData 痕迹在删除前先压缩写入隔离区中的 ZIP，每次清理一个归档，
+//! 以程序名和清理会话 id（含时间戳）命名。相比原样复制，大的配置和缓存目录能节省不少空间。
+//! 每个痕迹存放在归档内以痕迹 id 命名的目录下，并记录原始路径，恢复时按原路径解压。
+//! 恢复会话时失败的痕迹会再次归档：已有完整记录的痕迹直接跳过，上次中途失败留下的目录
+//! 不再复用，改写到 `<痕迹 id>~<序号>` 目录，避免归档内出现重名条目。
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::quarantine;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::models::Trace;
+
+/// 每个痕迹目录下记录原始路径的文件
+const MANIFEST_FILE_NAME: &str = "trace.json";
+
+/// 每个痕迹目录下存放数据的子目录
+const DATA_DIR_NAME: &str = "data";
+
+/// 归档中记录的痕迹信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedTrace {
+    pub trace_id: String,
+    /// 删除前的原始路径
+    pub path: String,
+    pub is_dir: bool,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    /// 恢复的痕迹原始路径
+    pub traces: Vec<String>,
+    pub files_restored: usize,
+    /// 目标位置已存在、未覆盖的文件数
+    pub files_skipped: usize,
+}
+
+/// 一次清理的归档文件位置，如 `quarantine/foo_archive_20240101120000-1a2b3c4d.zip`
+pub fn archive_path(program_name: &str, clean_id: &str) -> Result<PathBuf, UninstallerError> {
+    Ok(quarantine::get_quarantine_dir()?.join(format!(
+        "{}_archive_{}.zip",
+        quarantine::sanitize_file_name(program_name),
+        clean_id
+    )))
+}
+
+/// 将文件或目录痕迹压缩写入归档，归档不存在时新建，返回写入的原始字节数
+///
+/// 数据写完后才写入记录文件，归档中已有该痕迹的记录时不再重复写入
+pub fn archive_trace(zip_path: &Path, trace: &Trace) -> Result<u64, UninstallerError> {
+    let source = Path::new(&trace.path);
+    let metadata = std::fs::symlink_metadata(source)?;
+    let existing = entry_names(zip_path)?;
+    if existing
+        .iter()
+        .filter_map(|name| name.strip_suffix(&format!("/{}", MANIFEST_FILE_NAME)))
+        .any(|dir| is_trace_dir(dir, &trace.id))
+    {
+        tracing::info!("痕迹已在归档中，跳过: {}", trace.path);
+        return Ok(0);
+    }
+    let trace_dir = unused_trace_dir(&existing, &trace.id);
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(zip_path)?;
+    let mut writer = if file.metadata()?.len() == 0 {
+        ZipWriter::new(file)
+    } else {
+        ZipWriter::new_append(file).map_err(zip_error)?
+    };
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let data_prefix = format!("{}/{}", trace_dir, DATA_DIR_NAME);
+    let mut bytes = 0;
+    if metadata.is_dir() {
+        for entry in walkdir::WalkDir::new(source).min_depth(1) {
+            let entry = entry.map_err(|error| UninstallerError::Other(error.to_string()))?;
+            let Ok(relative) = entry.path().strip_prefix(source) else {
+                continue;
+            };
+            let name = format!("{}/{}", data_prefix, entry_name(relative));
+            if entry.file_type().is_dir() {
+                writer.add_directory(name, options).map_err(zip_error)?;
+            } else if entry.file_type().is_file() {
+                bytes += add_file(&mut writer, entry.path(), &name, options)?;
+            }
+        }
+    } else {
+        let file_name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".to_string());
+        bytes += add_file(
+            &mut writer,
+            source,
+            &format!("{}/{}", data_prefix, file_name),
+            options,
+        )?;
+    }
+
+    let manifest = ArchivedTrace {
+        trace_id: trace.id.clone(),
+        path: trace.path.clone(),
+        is_dir: metadata.is_dir(),
+        archived_at: Utc::now(),
+    };
+    writer
+        .start_file(format!("{}/{}", trace_dir, MANIFEST_FILE_NAME), options)
+        .map_err(zip_error)?;
+    let json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    writer.write_all(&json)?;
+
+    writer.finish().map_err(zip_error)?;
+    Ok(bytes)
+}
+
+/// 归档中已有的条目名，归档不存在或为空时返回空列表
+fn entry_names(zip_path: &Path) -> Result<Vec<String>, UninstallerError> {
+    match std::fs::metadata(zip_path) {
+        Ok(metadata) if metadata.len() > 0 => {}
+        _ => return Ok(Vec::new()),
+    }
+    let archive = ZipArchive::new(File::open(zip_path)?).map_err(zip_error)?;
+    Ok(archive.file_names().map(str::to_string).collect())
+}
+
+/// 归档内的目录是否属于该痕迹：`<痕迹 id>` 或重试时的 `<痕迹 id>~<序号>`
+fn is_trace_dir(dir: &str, trace_id: &str) -> bool {
+    dir.strip_prefix(trace_id).is_some_and(|rest| {
+        rest.is_empty()
+            || rest
+                .strip_prefix('~')
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// 痕迹在归档内尚未使用的目录名
+fn unused_trace_dir(existing: &[String], trace_id: &str) -> String {
+    let used = |dir: &str| {
+        existing
+            .iter()
+            .any(|name| name.split('/').next() == Some(dir))
+    };
+    if !used(trace_id) {
+        return trace_id.to_string();
+    }
+    (1..)
+        .map(|attempt| format!("{}~{}", trace_id, attempt))
+        .find(|dir| !used(dir))
+        .unwrap_or_default()
+}
+
+/// 读取归档中的痕迹记录
+pub fn list_archived_traces(zip_path: &Path) -> Result<Vec<ArchivedTrace>, UninstallerError> {
+    Ok(archived_traces_by_dir(zip_path)?
+        .into_iter()
+        .map(|(_, trace)| trace)
+        .collect())
+}
+
+/// 归档中的痕迹记录及其所在目录
+fn archived_traces_by_dir(
+    zip_path: &Path,
+) -> Result<Vec<(String, ArchivedTrace)>, UninstallerError> {
+    let mut archive = ZipArchive::new(File::open(zip_path)?).map_err(zip_error)?;
+    let mut traces = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(zip_error)?;
+        let Some(dir) = entry
+            .name()
+            .strip_suffix(&format!("/{}", MANIFEST_FILE_NAME))
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        if let Ok(trace) = serde_json::from_str::<ArchivedTrace>(&content) {
+            traces.push((dir, trace));
+        }
+    }
+    Ok(traces)
+}
+
+/// 将归档中的痕迹解压回原始位置，已存在的文件不会被覆盖
+///
+/// 没有记录文件的目录（归档中途失败留下的）不会恢复
+pub fn restore_archive(zip_path: &Path) -> Result<RestoreSummary, UninstallerError> {
+    let traces = archived_traces_by_dir(zip_path)?;
+    let mut archive = ZipArchive::new(File::open(zip_path)?).map_err(zip_error)?;
+    let mut summary = RestoreSummary::default();
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(zip_error)?;
+        let Some((trace_dir, relative)) = split_data_entry(entry.name()) else {
+            continue;
+        };
+        let Some((_, trace)) = traces.iter().find(|(dir, _)| dir == trace_dir) else {
+            continue;
+        };
+        let target = if trace.is_dir {
+            Path::new(&trace.path).join(relative)
+        } else {
+            PathBuf::from(&trace.path)
+        };
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else if target.exists() {
+            summary.files_skipped += 1;
+        } else {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::io::copy(&mut entry, &mut File::create(&target)?)?;
+            summary.files_restored += 1;
+        }
+        if !summary.traces.contains(&trace.path) {
+            summary.traces.push(trace.path.clone());
+        }
+    }
+
+    tracing::info!(
+        "已从归档恢复 {} 个文件: {}",
+        summary.files_restored,
+        zip_path.display()
+    );
+    Ok(summary)
+}
+
+fn add_file(
+    writer: &mut ZipWriter<File>,
+    path: &Path,
+    name: &str,
+    options: SimpleFileOptions,
+) -> Result<u64, UninstallerError> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    writer
+        .start_file(name, options.large_file(size >= u32::MAX as u64))
+        .map_err(zip_error)?;
+    Ok(std::io::copy(&mut file, writer)?)
+}
+
+/// 归档内的条目名使用 `/` 分隔
+fn entry_name(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 拆分数据条目名 `<痕迹目录>/data/<相对路径>`，相对路径含 `..` 等非普通部分时拒绝
+fn split_data_entry(name: &str) -> Option<(&str, PathBuf)> {
+    let (trace_id, rest) = name.split_once('/')?;
+    let relative = rest
+        .strip_prefix(DATA_DIR_NAME)?
+        .strip_prefix('/')?
+        .trim_end_matches('/');
+    if trace_id.is_empty() || relative.is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(relative);
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        .then_some((trace_id, path))
+}
+
+fn zip_error(error: zip::result::ZipError) -> UninstallerError {
+    UninstallerError::Other(format!("ZIP 归档失败: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_entries_stay_inside_trace() {
+        let (id, path) = split_data_entry("abc/data/cache/index.db").unwrap();
+        assert_eq!(id, "abc");
+        assert_eq!(path, PathBuf::from("cache/index.db"));
+        assert!(split_data_entry("abc/trace.json").is_none());
+        assert!(split_data_entry("abc/data/").is_none());
+        assert!(split_data_entry("abc/data/../../evil.dll").is_none());
+        assert_eq!(
+            entry_name(Path::new("cache").join("index.db").as_path()),
+            "cache/index.db"
+        );
+    }
+
+    #[test]
+    fn rearchiving_a_trace_skips_or_uses_a_fresh_dir() {
+        let dir = std::env::temp_dir().join(format!("rust-yu-archive-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("settings.ini");
+        std::fs::write(&source, "a=1").unwrap();
+        let zip_path = dir.join("archive.zip");
+        let trace = Trace::new(
+            "Acme".to_string(),
+            crate::modules::scanner::models::TraceType::File,
+            source.to_string_lossy().to_string(),
+        );
+
+        // 恢复会话时再次归档同一痕迹不会因重名条目失败
+        assert_eq!(archive_trace(&zip_path, &trace).unwrap(), 3);
+        assert_eq!(archive_trace(&zip_path, &trace).unwrap(), 0);
+        assert_eq!(list_archived_traces(&zip_path).unwrap().len(), 1);
+
+        // 上次中途失败只留下数据条目时换用新目录
+        let existing = vec![format!("{}/data/settings.ini", trace.id)];
+        assert_eq!(
+            unused_trace_dir(&existing, &trace.id),
+            format!("{}~1", trace.id)
+        );
+        assert!(is_trace_dir(&format!("{}~1", trace.id), &trace.id));
+        assert!(!is_trace_dir(&format!("{}x", trace.id), &trace.id));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::scanner::models::Trace;
+use winreg::RegKey;
+
+/// 删除右键菜单项的键
+///
+/// 只删除 `shell\<动作>` 或 `ContextMenuHandlers\<名称>` 本身（安全检查已确认路径形式），
+/// 菜单项已不存在时视为成功。删除后通知资源管理器刷新关联，右键菜单立即更新
+pub async fn delete_context_menu_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let (hkey, subkey) = utils::parse_registry_path(&trace.path)
+        .ok_or_else(|| UninstallerError::Registry(format!("无效的注册表路径: {}", trace.path)))?;
+
+    let result = match RegKey::predef(hkey).delete_subkey_all(subkey) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Err(
+            UninstallerError::PermissionDenied(format!("删除 {} 需要管理员权限", trace.path)),
+        ),
+        Err(e) => Err(UninstallerError::Registry(e.to_string())),
+    };
+
+    match result {
+        Ok(()) => {
+            notify_association_change();
+            tracing::info!("已删除右键菜单项: {}", trace.path);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("删除右键菜单项失败 {}: {}", trace.path, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
+
+/// 通知资源管理器文件关联已改变
+#[cfg(windows)]
+fn notify_association_change() {
+    use windows::Win32::UI::Shell::{SHChangeNotify, SHCNE_ASSOCCHANGED, SHCNF_IDLIST};
+
+    unsafe {
+        SHChangeNotify(SHCNE_ASSOCCHANGED, SHCNF_IDLIST, None, None);
+    }
+}
+
+#[cfg(not(windows))]
+fn notify_association_change() {}
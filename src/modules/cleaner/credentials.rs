@@ -0,0 +1,61 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::credentials::parse_credential_path;
+use crate::modules::scanner::models::Trace;
+
+/// 删除凭据管理器中保存的凭据
+///
+/// 按路径中记录的类型和目标名称调用 `CredDelete`，凭据已不存在时视为成功
+pub async fn delete_credential_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let (credential_type, target_name) = parse_credential_path(&trace.path)
+        .ok_or_else(|| UninstallerError::Other(format!("无效的凭据路径: {}", trace.path)))?;
+
+    match delete_credential(credential_type, target_name) {
+        Ok(()) => {
+            tracing::info!("已删除凭据: {}", trace.path);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("删除凭据失败 {}: {}", trace.path, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
+
+#[cfg(windows)]
+fn delete_credential(credential_type: u32, target_name: &str) -> Result<(), UninstallerError> {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::ERROR_NOT_FOUND;
+    use windows::Win32::Security::Credentials::{CredDeleteW, CRED_TYPE};
+
+    match unsafe {
+        CredDeleteW(
+            &HSTRING::from(target_name),
+            CRED_TYPE(credential_type),
+            None,
+        )
+    } {
+        Ok(()) => Ok(()),
+        Err(e) if e.code() == ERROR_NOT_FOUND.to_hresult() => Ok(()),
+        Err(e) => Err(UninstallerError::Other(format!("CredDelete 失败: {}", e))),
+    }
+}
+
+#[cfg(not(windows))]
+fn delete_credential(_credential_type: u32, _target_name: &str) -> Result<(), UninstallerError> {
+    Err(UninstallerError::Other(
+        "仅支持在 Windows 上删除凭据".to_string(),
+    ))
+}
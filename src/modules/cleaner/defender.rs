@@ -0,0 +1,47 @@
+use super::models::CleanResult;
+use super::network::run_command;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::defender::parse_exclusion_path;
+use crate::modules::scanner::models::Trace;
+
+/// 移除程序留下的 Defender 排除项
+///
+/// `Exclusions` 键受篡改保护，直接写注册表会失败，因此通过 `Remove-MpPreference` 移除；
+/// 排除项已不存在时该命令同样成功
+pub async fn delete_defender_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let Some((kind, exclusion)) = parse_exclusion_path(&trace.path) else {
+        return Err(UninstallerError::CriticalSystemItem(format!(
+            "不是 Defender 排除项: {}",
+            trace.path
+        )));
+    };
+
+    // 排除项来自注册表值名，拼入命令前转义单引号
+    let script = format!(
+        "Remove-MpPreference {} '{}' -ErrorAction Stop",
+        kind.parameter(),
+        exclusion.replace('\'', "''")
+    );
+    match run_command("powershell", &["-NoProfile", "-Command", &script]) {
+        Ok(()) => {
+            tracing::info!("已移除 Defender 排除项: {}", trace.path);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("移除 Defender 排除项失败 {}: {}", trace.path, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
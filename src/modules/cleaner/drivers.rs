@@ -0,0 +1,49 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::drivers::is_oem_inf;
+use crate::modules::scanner::models::Trace;
+use std::process::Command;
+
+/// 从驱动存储中删除驱动包
+///
+/// 只处理 oemN.inf 形式的第三方驱动包，且不使用 `/force`：
+/// 仍有设备在使用该驱动时 pnputil 会拒绝删除，避免卸载后硬件失去驱动
+pub async fn delete_driver_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    if !is_oem_inf(&trace.path) {
+        return Err(UninstallerError::CriticalSystemItem(format!(
+            "只能删除第三方驱动包 (oemN.inf): {}",
+            trace.path
+        )));
+    }
+
+    let output = Command::new("pnputil")
+        .args(["/delete-driver", &trace.path])
+        .output()?;
+
+    if output.status.success() {
+        tracing::info!("已删除驱动包: {}", trace.path);
+        return Ok(CleanResult {
+            trace_id: trace.id.clone(),
+            path: trace.path.clone(),
+            success: true,
+            error: None,
+            bytes_freed: 0,
+        });
+    }
+
+    let message = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string();
+    tracing::error!("删除驱动包失败 {}: {}", trace.path, message);
+
+    Ok(CleanResult {
+        trace_id: trace.id.clone(),
+        path: trace.path.clone(),
+        success: false,
+        error: Some(format!("驱动包可能仍在被设备使用: {}", message)),
+        bytes_freed: 0,
+    })
+}
@@ -0,0 +1,174 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::scanner::environment::{parse_environment_path, segment_key, split_segments};
+use crate::modules::scanner::models::Trace;
+use winreg::enums::*;
+use winreg::{RegKey, RegValue};
+
+/// 从环境变量中移除痕迹对应的片段
+///
+/// 保留原值类型（`REG_EXPAND_SZ` 中的 `%变量%` 不会被展开写回）和其余片段的原始顺序与写法；
+/// 移除后为空的变量整个删除。修改后广播 `WM_SETTINGCHANGE`，之后启动的程序即可看到新值
+pub async fn delete_environment_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let Some((key_path, name, segment)) = parse_environment_path(&trace.path) else {
+        return Err(UninstallerError::Other(format!(
+            "无效的环境变量痕迹路径: {}",
+            trace.path
+        )));
+    };
+
+    match remove_segment(key_path, name, segment) {
+        Ok(()) => {
+            broadcast_environment_change();
+            tracing::info!("已从环境变量 {} 中移除: {}", name, segment);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("修改环境变量失败 {}: {}", trace.path, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
+
+fn remove_segment(key_path: &str, name: &str, segment: &str) -> Result<(), UninstallerError> {
+    let (hkey, subkey) = utils::parse_registry_path(key_path)
+        .ok_or_else(|| UninstallerError::Registry(format!("无效的注册表路径: {}", key_path)))?;
+    let key = RegKey::predef(hkey)
+        .open_subkey_with_flags(subkey, KEY_READ | KEY_SET_VALUE)
+        .map_err(|e| map_open_error(key_path, e))?;
+
+    let raw = match key.get_raw_value(name) {
+        Ok(raw) => raw,
+        // 变量已被删除
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(UninstallerError::Registry(format!(
+                "读取环境变量 {} 失败: {}",
+                name, e
+            )))
+        }
+    };
+    if !matches!(raw.vtype, REG_SZ | REG_EXPAND_SZ) {
+        return Err(UninstallerError::Registry(format!(
+            "环境变量 {} 不是字符串类型",
+            name
+        )));
+    }
+
+    let value = utils::decode_reg_string(&raw.bytes);
+    let Some(remaining) = without_segment(&value, segment) else {
+        return Ok(());
+    };
+
+    if remaining.is_empty() {
+        return key
+            .delete_value(name)
+            .map_err(|e| UninstallerError::Registry(format!("删除环境变量 {} 失败: {}", name, e)));
+    }
+
+    key.set_raw_value(
+        name,
+        &RegValue {
+            bytes: encode_utf16(&remaining),
+            vtype: raw.vtype,
+        },
+    )
+    .map_err(|e| UninstallerError::Registry(format!("写入环境变量 {} 失败: {}", name, e)))
+}
+
+fn map_open_error(key_path: &str, error: std::io::Error) -> UninstallerError {
+    if error.kind() == std::io::ErrorKind::PermissionDenied {
+        UninstallerError::PermissionDenied(format!("修改 {} 需要管理员权限", key_path))
+    } else {
+        UninstallerError::Registry(format!("打开 {} 失败: {}", key_path, error))
+    }
+}
+
+/// 去掉与 `segment` 指向同一目录的全部片段，值中没有该片段时返回 None
+///
+/// 空片段（连续的 `;`）一并去掉，末尾的 `;` 保留
+fn without_segment(value: &str, segment: &str) -> Option<String> {
+    let target = segment_key(segment);
+    let parts: Vec<&str> = split_segments(value).collect();
+    let kept: Vec<&str> = parts
+        .iter()
+        .copied()
+        .filter(|part| segment_key(part) != target)
+        .collect();
+    if kept.len() == parts.len() {
+        return None;
+    }
+
+    let mut remaining = kept.join(";");
+    if value.trim_end().ends_with(';') && !remaining.is_empty() {
+        remaining.push(';');
+    }
+    Some(remaining)
+}
+
+fn encode_utf16(value: &str) -> Vec<u8> {
+    value
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+/// 通知资源管理器等顶层窗口重新读取环境变量
+#[cfg(windows)]
+fn broadcast_environment_change() {
+    use windows::core::w;
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE,
+    };
+
+    let area = w!("Environment");
+    unsafe {
+        SendMessageTimeoutW(
+            HWND_BROADCAST,
+            WM_SETTINGCHANGE,
+            WPARAM(0),
+            LPARAM(area.as_ptr() as isize),
+            SMTO_ABORTIFHUNG,
+            5000,
+            None,
+        );
+    }
+}
+
+#[cfg(not(windows))]
+fn broadcast_environment_change() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_only_the_matching_segment() {
+        let value = r"%SystemRoot%\system32;D:\Apps\Acme\bin\;D:\Tools;";
+        assert_eq!(
+            without_segment(value, r"d:\apps\acme\bin").as_deref(),
+            Some(r"%SystemRoot%\system32;D:\Tools;")
+        );
+        assert_eq!(without_segment(value, r"D:\Apps\Other"), None);
+        assert_eq!(
+            without_segment(r"D:\Apps\Acme", r"D:\Apps\Acme").as_deref(),
+            Some("")
+        );
+        assert_eq!(utils::decode_reg_string(&encode_utf16("a;b")), "a;b");
+    }
+}
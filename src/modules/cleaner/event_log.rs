@@ -0,0 +1,71 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::scanner::models::Trace;
+use winreg::enums::*;
+use winreg::RegKey;
+
+/// 注销事件日志源
+///
+/// 只删除 `EventLog\<日志>\<源>` 本身（安全检查已确认路径形式），源已不存在时视为成功。
+/// 删除后顺带从日志的 `Sources` 列表中移除该源，列表由事件日志服务维护，更新失败不影响结果
+pub async fn delete_event_log_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let (hkey, subkey) = utils::parse_registry_path(&trace.path)
+        .ok_or_else(|| UninstallerError::Registry(format!("无效的注册表路径: {}", trace.path)))?;
+
+    let result = match RegKey::predef(hkey).delete_subkey_all(subkey) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Err(
+            UninstallerError::PermissionDenied(format!("删除 {} 需要管理员权限", trace.path)),
+        ),
+        Err(e) => Err(UninstallerError::Registry(e.to_string())),
+    };
+
+    match result {
+        Ok(()) => {
+            if let Some((log_path, source)) = subkey.rsplit_once('\\') {
+                remove_from_sources(hkey, log_path, source);
+            }
+            tracing::info!("已注销事件源: {}", trace.path);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("注销事件源失败 {}: {}", trace.path, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
+
+/// 从日志键的 `Sources` 多字符串值中移除源名称
+fn remove_from_sources(hkey: winreg::HKEY, log_path: &str, source: &str) {
+    let Ok(log_key) = RegKey::predef(hkey).open_subkey_with_flags(log_path, KEY_READ | KEY_WRITE)
+    else {
+        return;
+    };
+    let Ok(sources) = log_key.get_value::<Vec<String>, _>("Sources") else {
+        return;
+    };
+    let remaining: Vec<String> = sources
+        .iter()
+        .filter(|name| !name.eq_ignore_ascii_case(source))
+        .cloned()
+        .collect();
+    if remaining.len() != sources.len() {
+        if let Err(e) = log_key.set_value("Sources", &remaining) {
+            tracing::warn!("更新事件日志 Sources 列表失败 {}: {}", log_path, e);
+        }
+    }
+}
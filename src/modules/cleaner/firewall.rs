@@ -0,0 +1,49 @@
+use super::models::CleanResult;
+use super::network::run_command;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::firewall::firewall_rule_id;
+use crate::modules::scanner::models::Trace;
+
+/// 删除程序留下的防火墙规则
+///
+/// 按规则 ID 删除，`netsh advfirewall firewall delete rule` 只能按显示名称删除，
+/// 会连带删除其他程序的同名规则。规则已不存在时视为成功
+pub async fn delete_firewall_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let Some(rule_id) = firewall_rule_id(&trace.path) else {
+        return Err(UninstallerError::CriticalSystemItem(format!(
+            "不是防火墙规则: {}",
+            trace.path
+        )));
+    };
+
+    // 规则 ID 来自注册表值名，拼入命令前转义单引号
+    let script = format!(
+        "$rule = Get-NetFirewallRule -Name '{0}' -ErrorAction SilentlyContinue; \
+         if ($rule) {{ Remove-NetFirewallRule -Name '{0}' -ErrorAction Stop }}",
+        rule_id.replace('\'', "''")
+    );
+    let result = run_command("powershell", &["-NoProfile", "-Command", &script]);
+
+    match result {
+        Ok(()) => {
+            tracing::info!("已删除防火墙规则: {}", trace.path);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("删除防火墙规则失败 {}: {}", trace.path, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
@@ -1,17 +1,50 @@
+pub mod activex;
+pub mod archive;
+pub mod context_menu;
+pub mod credentials;
+pub mod defender;
+pub mod drivers;
+pub mod environment;
+pub mod event_log;
 pub mod filesystem;
+pub mod firewall;
 pub mod models;
+pub mod network;
+pub mod quarantine;
+pub mod reg_export;
 pub mod registry;
+pub mod revalidate;
+pub mod review;
 pub mod safety;
+pub mod scheduled_tasks;
+pub mod selection;
+pub mod services;
+pub mod session;
 pub mod shortcuts;
+pub mod usage_history;
+pub mod wmi;
 
 use crate::modules::common::error::UninstallerError;
-use crate::modules::scanner::models::{Trace, TraceType};
-use models::CleanResult;
+use crate::modules::common::progress;
+use crate::modules::lister::trace_feedback;
+use crate::modules::scanner::models::{Trace, TraceCategory, TraceType};
+use models::{CleanOptions, CleanResult, SafetyVerdict};
 
-/// 清理痕迹
+/// 清理痕迹，属于其他用户、可能包含用户数据或属于用户内容的痕迹会被跳过
 pub async fn clean_traces(
     traces: Vec<Trace>,
     confirm: bool,
+) -> Result<Vec<CleanResult>, UninstallerError> {
+    clean_traces_with_options(traces, confirm, CleanOptions::default()).await
+}
+
+/// 清理痕迹
+///
+/// 属于其他用户、可能包含用户数据或属于用户内容的痕迹只有在 `options` 中单独确认后才会删除
+pub async fn clean_traces_with_options(
+    traces: Vec<Trace>,
+    confirm: bool,
+    options: CleanOptions,
 ) -> Result<Vec<CleanResult>, UninstallerError> {
     if !confirm {
         return Err(UninstallerError::PermissionDenied(
@@ -19,52 +52,238 @@ pub async fn clean_traces(
         ));
     }
 
+    // 同一秒内的两次清理不能写入同一个归档
+    let archive_path = match (options.archive, traces.first()) {
+        (true, Some(first)) => Some(archive::archive_path(
+            &first.program_name,
+            &session::new_session_id(),
+        )?),
+        _ => None,
+    };
+    let guard = safety::SharedRuntimeGuard::new();
+    let total = traces.len();
     let mut results = Vec::new();
-
     for trace in traces {
-        // 安全检查
-        if let Err(e) = safety::pre_delete_check(&trace) {
-            tracing::warn!("跳过关键系统项: {}", e);
-            results.push(CleanResult {
+        let blocked = blocking_reason(&trace, options, &guard);
+        results.push(clean_one(&trace, blocked, archive_path.as_deref()).await);
+        progress::advance("clean", results.len(), total, Some(&trace.path));
+    }
+
+    Ok(results)
+}
+
+/// 执行清理会话中剩余和失败的项，每处理一项保存一次会话
+///
+/// 全部成功后删除会话文件；仍有失败项时保留会话，之后可再次恢复
+pub async fn run_clean_session(
+    session: &mut session::CleanSession,
+) -> Result<Vec<CleanResult>, UninstallerError> {
+    session.requeue_failed();
+    session::save_session(session)?;
+
+    // 恢复会话时继续写入同一个归档
+    let archive_path = if session.options.archive {
+        Some(archive::archive_path(&session.program_name, &session.id)?)
+    } else {
+        None
+    };
+    let guard = safety::SharedRuntimeGuard::new();
+    let total = session.pending.len();
+    let mut completed = 0;
+    while let Some(trace) = session.pending.first().cloned() {
+        let blocked = blocking_reason(&trace, session.options, &guard);
+        let result = clean_one(&trace, blocked, archive_path.as_deref()).await;
+        session.pending.remove(0);
+        completed += 1;
+        progress::advance("clean", completed, total, Some(&trace.path));
+        session.record(trace, result);
+        session::save_session(session)?;
+    }
+
+    if session.is_finished() {
+        session::delete_session(&session.id)?;
+    }
+    Ok(session.results.clone())
+}
+
+/// 记录清理反馈，之后扫描时据此校准置信度
+///
+/// 删除成功的痕迹计为删除，`skipped` 为建议后被取消勾选或审核拒绝的痕迹；记录失败不影响清理结果
+pub fn record_feedback(results: &[CleanResult], skipped: &[Trace]) {
+    let deleted: Vec<String> = results
+        .iter()
+        .filter(|result| result.success)
+        .map(|result| result.path.clone())
+        .collect();
+    let skipped: Vec<String> = skipped.iter().map(|trace| trace.path.clone()).collect();
+    if let Err(e) = trace_feedback::record_feedback(&deleted, &skipped) {
+        tracing::warn!("记录清理反馈失败: {}", e);
+    }
+}
+
+/// 清理单个痕迹，`blocked` 为安全检查给出的拦截原因；失败和拦截都记录在结果中
+///
+/// 指定了 `archive` 时，文件和 AppData 痕迹先压缩写入该归档，归档失败则不删除
+async fn clean_one(
+    trace: &Trace,
+    blocked: Option<String>,
+    archive: Option<&std::path::Path>,
+) -> CleanResult {
+    let failed = |error: String| CleanResult {
+        trace_id: trace.id.clone(),
+        path: trace.path.clone(),
+        success: false,
+        error: Some(error),
+        bytes_freed: 0,
+    };
+
+    if let Some(reason) = blocked {
+        tracing::warn!("跳过 {}: {}", trace.path, reason);
+        return failed(reason);
+    }
+    if let Some(reason) = &trace.cloud_sync {
+        tracing::warn!("{}: {}", trace.path, reason);
+    }
+    if let Some(zip_path) = archive {
+        let archivable = matches!(trace.trace_type, TraceType::File | TraceType::AppData)
+            && std::path::Path::new(&trace.path).exists();
+        if archivable {
+            if let Err(e) = archive::archive_trace(zip_path, trace) {
+                tracing::error!("归档失败 {}: {}", trace.path, e);
+                return failed(format!("归档失败，未删除: {}", e));
+            }
+        }
+    }
+
+    let result = match trace.trace_type {
+        TraceType::RegistryKey => registry::delete_registry_trace(trace).await,
+        TraceType::RegistryValue => registry::delete_registry_trace(trace).await,
+        TraceType::File | TraceType::AppData => filesystem::delete_file_trace(trace).await,
+        TraceType::Shortcut => shortcuts::delete_shortcut_trace(trace).await,
+        TraceType::Driver => drivers::delete_driver_trace(trace).await,
+        TraceType::ActiveX => activex::delete_activex_trace(trace).await,
+        TraceType::UsageHistory => usage_history::delete_usage_history_trace(trace).await,
+        TraceType::NetworkSetting => network::delete_network_trace(trace).await,
+        TraceType::FirewallRule => firewall::delete_firewall_trace(trace).await,
+        TraceType::EnvironmentVariable => environment::delete_environment_trace(trace).await,
+        TraceType::ContextMenu => context_menu::delete_context_menu_trace(trace).await,
+        TraceType::Credential => credentials::delete_credential_trace(trace).await,
+        TraceType::EventLogSource => event_log::delete_event_log_trace(trace).await,
+        TraceType::WmiProvider => wmi::delete_wmi_trace(trace).await,
+        TraceType::DefenderExclusion => defender::delete_defender_trace(trace).await,
+        TraceType::Service => services::delete_service_trace(trace).await,
+        TraceType::ScheduledTask => scheduled_tasks::delete_scheduled_task_trace(trace).await,
+    };
+
+    result.unwrap_or_else(|e| failed(e.to_string()))
+}
+
+/// 模拟清理：只运行安全检查与确认规则，不删除任何内容
+///
+/// 返回每个痕迹是否会被拦截及原因，GUI 可据此提前置灰受保护的项
+pub fn simulate_clean(traces: &[Trace], options: CleanOptions) -> Vec<SafetyVerdict> {
+    simulate_clean_with_guard(traces, options, &safety::SharedRuntimeGuard::new())
+}
+
+/// 使用指定的共享运行时保护执行模拟清理
+fn simulate_clean_with_guard(
+    traces: &[Trace],
+    options: CleanOptions,
+    guard: &safety::SharedRuntimeGuard,
+) -> Vec<SafetyVerdict> {
+    traces
+        .iter()
+        .map(|trace| {
+            let reason = blocking_reason(trace, options, guard);
+            SafetyVerdict {
                 trace_id: trace.id.clone(),
                 path: trace.path.clone(),
-                success: false,
-                error: Some(format!("跳过关键系统项: {}", e)),
-                bytes_freed: 0,
-            });
-            continue;
+                blocked: reason.is_some(),
+                reason,
+            }
+        })
+        .collect()
+}
+
+/// 判断痕迹在清理时是否会被拦截，返回拦截原因
+fn blocking_reason(
+    trace: &Trace,
+    options: CleanOptions,
+    guard: &safety::SharedRuntimeGuard,
+) -> Option<String> {
+    // 其他用户的数据需要单独确认，避免在共享电脑上误删他人设置
+    if let Some(owner) = trace.owner.as_deref() {
+        if !options.include_other_users {
+            return Some(format!("属于其他用户 {}，需要单独确认后才能删除", owner));
         }
+    }
 
-        let result = match trace.trace_type {
-            TraceType::RegistryKey => registry::delete_registry_trace(&trace).await,
-            TraceType::RegistryValue => registry::delete_registry_trace(&trace).await,
-            TraceType::File | TraceType::AppData => filesystem::delete_file_trace(&trace).await,
-            TraceType::Shortcut => shortcuts::delete_shortcut_trace(&trace).await,
-            _ => {
-                results.push(CleanResult {
-                    trace_id: trace.id.clone(),
-                    path: trace.path.clone(),
-                    success: false,
-                    error: Some("不支持的痕迹类型".to_string()),
-                    bytes_freed: 0,
-                });
-                continue;
-            }
-        };
-
-        match result {
-            Ok(r) => results.push(r),
-            Err(e) => {
-                results.push(CleanResult {
-                    trace_id: trace.id.clone(),
-                    path: trace.path.clone(),
-                    success: false,
-                    error: Some(e.to_string()),
-                    bytes_freed: 0,
-                });
-            }
+    // 可能混有用户文档或近期仍在使用的数据，需要显式确认
+    if let Some(reason) = trace.user_data.as_deref() {
+        if !options.include_user_data {
+            return Some(format!(
+                "可能包含用户数据 ({})，需要显式确认后才能删除",
+                reason
+            ));
         }
     }
 
-    Ok(results)
+    // 存档、文档等用户内容默认保留，需要单独开启
+    if trace.category == TraceCategory::UserContent && !options.include_user_content {
+        return Some("属于用户创建的内容，需要单独开启后才能删除".to_string());
+    }
+
+    // 安全检查
+    if let Err(e) = safety::pre_delete_check(trace) {
+        return Some(format!("跳过关键系统项: {}", e));
+    }
+
+    // 共享运行时目录仍被其他程序使用时不删除
+    if let Err(e) = guard.check(trace) {
+        return Some(format!("跳过共享运行时: {}", e));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulate_clean_reports_blocked_traces_without_deleting() {
+        let normal = Trace::new(
+            "Foo".to_string(),
+            TraceType::File,
+            r"D:\Apps\Foo\cache".to_string(),
+        );
+        let critical = Trace::new(
+            "Foo".to_string(),
+            TraceType::File,
+            r"C:\Windows\System32\foo.dll".to_string(),
+        );
+        let mut other_user = normal.clone();
+        other_user.owner = Some("Bob".to_string());
+
+        let guard = safety::SharedRuntimeGuard::with_programs(Vec::new());
+        let verdicts = simulate_clean_with_guard(
+            &[normal, critical, other_user.clone()],
+            CleanOptions::default(),
+            &guard,
+        );
+
+        assert!(!verdicts[0].blocked);
+        assert!(verdicts[1].blocked);
+        assert!(verdicts[2].blocked);
+
+        let allowed = simulate_clean_with_guard(
+            &[other_user],
+            CleanOptions {
+                include_other_users: true,
+                ..CleanOptions::default()
+            },
+            &guard,
+        );
+        assert!(!allowed[0].blocked);
+    }
 }
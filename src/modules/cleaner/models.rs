@@ -9,3 +9,56 @@ pub struct CleanResult {
     pub error: Option<String>,
     pub bytes_freed: u64,
 }
+
+/// 清理选项，控制需要单独确认的痕迹是否一并删除
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CleanOptions {
+    /// 删除属于其他用户的痕迹
+    #[serde(default)]
+    pub include_other_users: bool,
+    /// 删除可能包含用户数据的痕迹
+    #[serde(default)]
+    pub include_user_data: bool,
+    /// 删除游戏存档、文档等用户创建的内容
+    #[serde(default)]
+    pub include_user_content: bool,
+    /// 删除文件和 AppData 痕迹前先压缩归档到隔离区
+    #[serde(default)]
+    pub archive: bool,
+}
+
+/// 按大小和最后修改时间筛选要清理的痕迹，未设置的条件不参与筛选
+///
+/// 设置了大小条件时，大小未知的痕迹会被排除；设置了时间条件时，修改时间未知的痕迹会被排除
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanFilter {
+    /// 只清理不小于该大小（字节）的痕迹
+    #[serde(default)]
+    pub min_size: Option<u64>,
+    /// 只清理不大于该大小（字节）的痕迹
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    /// 只清理至少这么多天未修改的痕迹
+    #[serde(default)]
+    pub min_age_days: Option<u64>,
+    /// 只清理最近这么多天内修改过的痕迹
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+}
+
+impl CleanFilter {
+    /// 是否没有设置任何条件
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 模拟清理时单个痕迹的判定结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetyVerdict {
+    pub trace_id: String,
+    pub path: String,
+    /// 清理时是否会被拦截
+    pub blocked: bool,
+    pub reason: Option<String>,
+}
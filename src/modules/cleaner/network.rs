@@ -0,0 +1,100 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::models::Trace;
+use crate::modules::scanner::network::{
+    self, AUTO_CONFIG_URL_VALUE, PROXY_ENABLE_VALUE, PROXY_SERVER_VALUE, WINHTTP_SETTINGS_VALUE,
+    WININET_KEY,
+};
+use std::process::Command;
+use winreg::enums::*;
+use winreg::RegKey;
+
+/// 还原程序留下的网络设置
+///
+/// 只处理扫描时归属于程序的那一项：PAC 地址只删除 `AutoConfigURL`，代理服务器删除地址并关闭代理开关，
+/// WinHTTP 代理通过 `netsh winhttp reset proxy` 恢复直连
+pub async fn delete_network_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    if !network::is_network_setting_path(&trace.path) {
+        return Err(UninstallerError::CriticalSystemItem(format!(
+            "不是可还原的网络设置: {}",
+            trace.path
+        )));
+    }
+
+    let value_name = trace.path.rsplit('\\').next().unwrap_or_default();
+    let result = if value_name.eq_ignore_ascii_case(WINHTTP_SETTINGS_VALUE) {
+        run_command("netsh", &["winhttp", "reset", "proxy"])
+    } else {
+        reset_wininet_value(value_name)
+    };
+
+    match result {
+        Ok(()) => {
+            tracing::info!("已还原网络设置: {}", trace.path);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("还原网络设置失败 {}: {}", trace.path, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
+
+fn reset_wininet_value(value_name: &str) -> Result<(), UninstallerError> {
+    let key = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey_with_flags(WININET_KEY, KEY_READ | KEY_SET_VALUE)
+        .map_err(|e| UninstallerError::Registry(format!("打开 Internet Settings 失败: {}", e)))?;
+
+    if value_name.eq_ignore_ascii_case(PROXY_SERVER_VALUE) {
+        // 只剩开关没有地址时浏览器会报代理错误，一并关闭
+        key.set_value(PROXY_ENABLE_VALUE, &0u32)
+            .map_err(|e| UninstallerError::Registry(format!("关闭代理失败: {}", e)))?;
+    }
+
+    let name = if value_name.eq_ignore_ascii_case(PROXY_SERVER_VALUE) {
+        PROXY_SERVER_VALUE
+    } else {
+        AUTO_CONFIG_URL_VALUE
+    };
+    match key.delete_value(name) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(UninstallerError::Registry(format!(
+            "删除 {} 失败: {}",
+            name, e
+        ))),
+    }
+}
+
+/// 运行外部命令，失败时返回其错误输出
+pub(super) fn run_command(program: &str, args: &[&str]) -> Result<(), UninstallerError> {
+    let output = Command::new(program).args(args).output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() {
+        stdout
+    } else {
+        stderr
+    };
+    Err(UninstallerError::Other(format!(
+        "{} 执行失败: {}",
+        program,
+        message.trim()
+    )))
+}
@@ -0,0 +1,45 @@
+//! 隔离区
+//!
+//! 卸载或删除前导出的数据统一存放在存储目录下的 quarantine 中，
+//! 每次导出一个以程序名、用途和时间戳命名的子目录，便于之后手动恢复。
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::lister::storage;
+use chrono::Utc;
+use std::path::PathBuf;
+
+const QUARANTINE_DIR_NAME: &str = "quarantine";
+
+/// 获取隔离区根目录
+pub fn get_quarantine_dir() -> Result<PathBuf, UninstallerError> {
+    let dir = storage::get_storage_root_dir()?.join(QUARANTINE_DIR_NAME);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 为一次导出创建子目录，如 `foo_license_20240101_120000`
+pub fn create_entry_dir(program_name: &str, purpose: &str) -> Result<PathBuf, UninstallerError> {
+    let name = format!(
+        "{}_{}_{}",
+        sanitize_file_name(program_name),
+        purpose,
+        Utc::now().format("%Y%m%d_%H%M%S")
+    );
+    let dir = get_quarantine_dir()?.join(name);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 将名称中不能用于文件名的字符替换为下划线
+pub fn sanitize_file_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
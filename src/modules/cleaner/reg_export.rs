@@ -0,0 +1,155 @@
+//! 导出 regedit 格式的 .reg 文件
+//!
+//! 删除或修改注册表前先导出到隔离区，双击导出的文件即可恢复。
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use std::path::Path;
+use winreg::enums::*;
+use winreg::{RegKey, RegValue};
+
+/// .reg 文件头
+const REG_FILE_HEADER: &str = "Windows Registry Editor Version 5.00";
+
+/// 逐行构建 .reg 文件内容
+#[derive(Debug, Default)]
+pub struct RegFileBuilder {
+    lines: Vec<String>,
+    current_key: Option<String>,
+}
+
+impl RegFileBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 是否还没有写入任何值或键
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// 写入一个值，键与上一个值不同时先写键头
+    pub fn push_value(&mut self, key_path: &str, name: &str, value: &RegValue) {
+        self.push_key(key_path);
+        self.lines.push(format_reg_value(name, value));
+    }
+
+    /// 写入键头（与上一个键相同时忽略）
+    pub fn push_key(&mut self, key_path: &str) {
+        if self.current_key.as_deref() != Some(key_path) {
+            self.lines.push(String::new());
+            self.lines
+                .push(format!("[{}]", utils::full_registry_path(key_path)));
+            self.current_key = Some(key_path.to_string());
+        }
+    }
+
+    /// 递归导出整个键（含所有子键和值），键不存在时返回 false
+    pub fn push_key_recursive(&mut self, key_path: &str) -> bool {
+        let Some((hkey, subpath)) = utils::parse_registry_path(key_path) else {
+            return false;
+        };
+        let Ok(key) = RegKey::predef(hkey).open_subkey(subpath) else {
+            return false;
+        };
+        self.push_open_key(&key, key_path);
+        true
+    }
+
+    fn push_open_key(&mut self, key: &RegKey, key_path: &str) {
+        self.push_key(key_path);
+        for (name, value) in key.enum_values().filter_map(|value| value.ok()) {
+            self.lines.push(format_reg_value(&name, &value));
+        }
+
+        for child in key.enum_keys().filter_map(|name| name.ok()) {
+            if let Ok(child_key) = key.open_subkey(&child) {
+                self.push_open_key(&child_key, &format!(r"{}\{}", key_path, child));
+            }
+        }
+    }
+
+    /// 写入文件；regedit 要求 UTF-16 LE 并带 BOM
+    pub fn write_to(&self, path: &Path) -> Result<(), UninstallerError> {
+        let content = std::iter::once(REG_FILE_HEADER.to_string())
+            .chain(self.lines.iter().cloned())
+            .collect::<Vec<_>>()
+            .join("\r\n")
+            + "\r\n";
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in content.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+/// 按 regedit 导出格式输出一个值
+pub fn format_reg_value(name: &str, value: &RegValue) -> String {
+    let name = if name.is_empty() {
+        "@".to_string()
+    } else {
+        format!("\"{}\"", escape_reg_string(name))
+    };
+
+    let data = match value.vtype {
+        REG_SZ => {
+            let text = utils::decode_reg_string(&value.bytes);
+            format!("\"{}\"", escape_reg_string(&text))
+        }
+        REG_DWORD if value.bytes.len() == 4 => {
+            let number = u32::from_le_bytes([
+                value.bytes[0],
+                value.bytes[1],
+                value.bytes[2],
+                value.bytes[3],
+            ]);
+            format!("dword:{:08x}", number)
+        }
+        REG_BINARY => format!("hex:{}", hex_bytes(&value.bytes)),
+        _ => format!(
+            "hex({:x}):{}",
+            value.vtype.clone() as u32,
+            hex_bytes(&value.bytes)
+        ),
+    };
+
+    format!("{}={}", name, data)
+}
+
+fn escape_reg_string(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', "\\\"")
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_reg_values_like_regedit() {
+        let text: Vec<u8> = "AB\\C\0"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        let value = RegValue {
+            bytes: text,
+            vtype: REG_SZ,
+        };
+        assert_eq!(format_reg_value("Serial", &value), r#""Serial"="AB\\C""#);
+
+        let value = RegValue {
+            bytes: 42u32.to_le_bytes().to_vec(),
+            vtype: REG_DWORD,
+        };
+        assert_eq!(format_reg_value("", &value), "@=dword:0000002a");
+    }
+}
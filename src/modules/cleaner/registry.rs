@@ -2,6 +2,8 @@ use super::models::CleanResult;
 use crate::modules::common::error::UninstallerError;
 use crate::modules::common::utils;
 use crate::modules::scanner::models::{Trace, TraceType};
+use crate::modules::scanner::profiles;
+use crate::modules::scanner::registry::measure_registry_key;
 use winreg::enums::*;
 use winreg::RegKey;
 
@@ -23,6 +25,28 @@ pub async fn delete_registry_trace(trace: &Trace) -> Result<CleanResult, Uninsta
         }
     };
 
+    // 未登录用户的配置单元需要临时加载，删除完成后卸载
+    let _hive = match profiles::mount_hive_for_path(path) {
+        Ok(hive) => hive,
+        Err(e) => {
+            return Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            });
+        }
+    };
+
+    // 删除前统计整项数据量，作为释放空间计入报告
+    let bytes_freed = match trace.trace_type {
+        TraceType::RegistryKey => measure_registry_key(path)
+            .map(|stats| stats.data_size)
+            .unwrap_or(0),
+        _ => 0,
+    };
+
     // 删除操作
     let result = match trace.trace_type {
         TraceType::RegistryKey => delete_registry_key(hkey, &subkey_path),
@@ -47,7 +71,7 @@ pub async fn delete_registry_trace(trace: &Trace) -> Result<CleanResult, Uninsta
                 path: path.clone(),
                 success: true,
                 error: None,
-                bytes_freed: 0,
+                bytes_freed,
             })
         }
         Err(e) => {
@@ -0,0 +1,138 @@
+//! 导入痕迹的时效校验
+//!
+//! 从文件读取的痕迹可能是很久以前扫描的：路径可能已被删除，或者程序已重新安装，
+//! 同一路径如今属于新的安装。删除前逐项确认痕迹仍然存在、仍与程序相关，
+//! 且在扫描之后没有被修改（文件修改时间、注册表项最后写入时间）。
+
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+use winreg::RegKey;
+
+use crate::modules::common::{format, utils};
+use crate::modules::scanner::matching;
+use crate::modules::scanner::models::{Confidence, Trace, TraceType};
+
+/// 时间比较的容差，避免文件系统时间精度带来的误判
+const MODIFIED_TOLERANCE_SECS: i64 = 2;
+
+/// 校验未通过的痕迹
+#[derive(Debug, Clone)]
+pub struct StaleTrace {
+    pub trace: Trace,
+    pub reason: String,
+}
+
+/// 将痕迹分为仍然有效的和已过时的
+pub fn revalidate_traces(
+    traces: Vec<Trace>,
+    program_name: &str,
+    scanned_at: DateTime<Utc>,
+) -> (Vec<Trace>, Vec<StaleTrace>) {
+    let mut valid = Vec::new();
+    let mut stale = Vec::new();
+
+    for trace in traces {
+        match stale_reason(&trace, program_name, scanned_at) {
+            Some(reason) => stale.push(StaleTrace { trace, reason }),
+            None => valid.push(trace),
+        }
+    }
+
+    (valid, stale)
+}
+
+fn stale_reason(trace: &Trace, program_name: &str, scanned_at: DateTime<Utc>) -> Option<String> {
+    if !program_matches(trace, program_name) {
+        return Some(format!("痕迹属于程序 \"{}\"", trace.program_name));
+    }
+
+    // 非高置信度的痕迹是按名称匹配到的，路径本身必须仍包含程序名
+    if trace.confidence != Confidence::High
+        && !matching::name_matches(&trace.path, &trace.program_name)
+    {
+        return Some("路径已不再与程序名匹配".to_string());
+    }
+
+    let modified_at = match trace.trace_type {
+        TraceType::File | TraceType::AppData | TraceType::Shortcut => {
+            let Ok(metadata) = std::fs::metadata(Path::new(&trace.path)) else {
+                return Some("路径已不存在".to_string());
+            };
+            metadata.modified().ok().map(DateTime::<Utc>::from)
+        }
+        TraceType::RegistryKey => match registry_key_last_write(&trace.path) {
+            Some(last_write) => last_write,
+            None => return Some("注册表项已不存在".to_string()),
+        },
+        TraceType::RegistryValue => match registry_value_key_last_write(&trace.path) {
+            Some(last_write) => last_write,
+            None => return Some("注册表值已不存在".to_string()),
+        },
+        TraceType::ActiveX => match registry_key_last_write(&trace.path)
+            .or_else(|| registry_value_key_last_write(&trace.path))
+        {
+            Some(last_write) => last_write,
+            None => return Some("控件注册已不存在".to_string()),
+        },
+        // 服务、计划任务和驱动由各自的清理步骤确认存在
+        _ => None,
+    };
+
+    modified_at
+        .filter(|modified_at| is_modified_after(*modified_at, scanned_at))
+        .map(|modified_at| {
+            format!(
+                "扫描后被修改 ({})，可能已重新安装",
+                format::format_datetime(&modified_at)
+            )
+        })
+}
+
+/// 痕迹记录的程序是否为要清理的程序
+fn program_matches(trace: &Trace, program_name: &str) -> bool {
+    trace.program_name.eq_ignore_ascii_case(program_name)
+        || matching::name_matches(&trace.program_name, program_name)
+        || matching::name_matches(program_name, &trace.program_name)
+}
+
+fn is_modified_after(modified_at: DateTime<Utc>, scanned_at: DateTime<Utc>) -> bool {
+    modified_at > scanned_at + Duration::seconds(MODIFIED_TOLERANCE_SECS)
+}
+
+/// 注册表项的最后写入时间；项不存在时返回 `None`，时间无法读取时返回 `Some(None)`
+pub(super) fn registry_key_last_write(path: &str) -> Option<Option<DateTime<Utc>>> {
+    let (hkey, subpath) = utils::parse_registry_path(path)?;
+    let key = RegKey::predef(hkey).open_subkey(subpath).ok()?;
+    Some(key.query_info().ok().and_then(|info| {
+        utils::filetime_to_utc(
+            info.last_write_time.dwLowDateTime,
+            info.last_write_time.dwHighDateTime,
+        )
+    }))
+}
+
+/// 注册表值所在项的最后写入时间，值不存在时返回 `None`
+pub(super) fn registry_value_key_last_write(path: &str) -> Option<Option<DateTime<Utc>>> {
+    let (key_path, value_name) = path.rsplit_once('\\')?;
+    let (hkey, subpath) = utils::parse_registry_path(key_path)?;
+    let key = RegKey::predef(hkey).open_subkey(subpath).ok()?;
+    key.get_raw_value(value_name).ok()?;
+    registry_key_last_write(key_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_filetime_and_applies_tolerance() {
+        // 2020-01-01 00:00:00 UTC
+        let ticks: u64 = 132_223_104_000_000_000;
+        let time = utils::filetime_to_utc(ticks as u32, (ticks >> 32) as u32).unwrap();
+        assert_eq!(time.to_rfc3339(), "2020-01-01T00:00:00+00:00");
+
+        assert!(!is_modified_after(time + Duration::seconds(1), time));
+        assert!(is_modified_after(time + Duration::minutes(5), time));
+    }
+}
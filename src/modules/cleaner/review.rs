@@ -0,0 +1,249 @@
+//! 痕迹审核
+//!
+//! 双人流程中，技术人员扫描后创建审核会话，程序所有者逐项标记为“批准”“拒绝”或“待复核”
+//! 并可附带备注。会话持久化保存，界面或 API 随时可以继续审核，最终只清理已批准的痕迹。
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::lister::storage;
+use crate::modules::scanner::models::Trace;
+
+/// 审核会话文件所在的子目录
+const REVIEWS_DIR_NAME: &str = "review_sessions";
+
+/// 审核结论
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Approved,
+    Rejected,
+    NeedsReview,
+}
+
+/// 单个痕迹的审核标注
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceAnnotation {
+    pub trace_id: String,
+    pub status: ReviewStatus,
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// 审核人，由界面或 API 调用方填写
+    #[serde(default)]
+    pub reviewer: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 审核会话：一次扫描结果及其标注
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewSession {
+    pub id: String,
+    pub program_name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub traces: Vec<Trace>,
+    /// 每个痕迹最多一条，未标注的痕迹视为待审核
+    #[serde(default)]
+    pub annotations: Vec<TraceAnnotation>,
+}
+
+impl ReviewSession {
+    #[allow(dead_code)]
+    pub fn new(program_name: &str, traces: Vec<Trace>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: format!(
+                "{}-{}",
+                now.format("%Y%m%d%H%M%S"),
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            program_name: program_name.to_string(),
+            created_at: now,
+            updated_at: now,
+            traces,
+            annotations: Vec::new(),
+        }
+    }
+
+    /// 标注痕迹，覆盖之前的标注
+    #[allow(dead_code)]
+    pub fn annotate(
+        &mut self,
+        trace_id: &str,
+        status: ReviewStatus,
+        comment: Option<String>,
+        reviewer: Option<String>,
+    ) -> Result<(), UninstallerError> {
+        if !self.traces.iter().any(|trace| trace.id == trace_id) {
+            return Err(UninstallerError::NotFound(format!(
+                "审核会话中没有该痕迹: {}",
+                trace_id
+            )));
+        }
+        let now = Utc::now();
+        self.annotations
+            .retain(|annotation| annotation.trace_id != trace_id);
+        self.annotations.push(TraceAnnotation {
+            trace_id: trace_id.to_string(),
+            status,
+            comment: comment.filter(|c| !c.trim().is_empty()),
+            reviewer: reviewer.filter(|r| !r.trim().is_empty()),
+            updated_at: now,
+        });
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// 痕迹的标注
+    pub fn annotation(&self, trace_id: &str) -> Option<&TraceAnnotation> {
+        self.annotations
+            .iter()
+            .find(|annotation| annotation.trace_id == trace_id)
+    }
+
+    /// 已批准的痕迹
+    pub fn approved_traces(&self) -> Vec<Trace> {
+        self.traces
+            .iter()
+            .filter(|trace| {
+                self.annotation(&trace.id)
+                    .is_some_and(|annotation| annotation.status == ReviewStatus::Approved)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 已拒绝的痕迹
+    pub fn rejected_traces(&self) -> Vec<Trace> {
+        self.traces
+            .iter()
+            .filter(|trace| {
+                self.annotation(&trace.id)
+                    .is_some_and(|annotation| annotation.status == ReviewStatus::Rejected)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 尚未给出批准或拒绝结论的痕迹数
+    pub fn unresolved_count(&self) -> usize {
+        self.traces
+            .iter()
+            .filter(|trace| {
+                self.annotation(&trace.id)
+                    .is_none_or(|annotation| annotation.status == ReviewStatus::NeedsReview)
+            })
+            .count()
+    }
+}
+
+/// 审核会话目录
+pub fn reviews_dir() -> Result<PathBuf, UninstallerError> {
+    let dir = storage::get_storage_root_dir()?.join(REVIEWS_DIR_NAME);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 保存审核会话；先写临时文件再替换
+#[allow(dead_code)]
+pub fn save_review(review: &ReviewSession) -> Result<(), UninstallerError> {
+    let dir = reviews_dir()?;
+    let path = dir.join(format!("{}.json", review.id));
+    let temp_path = dir.join(format!("{}.json.tmp", review.id));
+
+    let json = serde_json::to_string(review)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    std::fs::write(&temp_path, json)?;
+    std::fs::rename(&temp_path, &path)?;
+    Ok(())
+}
+
+/// 按 id 读取审核会话
+pub fn load_review(id: &str) -> Result<ReviewSession, UninstallerError> {
+    let path = reviews_dir()?.join(format!("{}.json", id.trim()));
+    if !path.exists() {
+        return Err(UninstallerError::NotFound(format!(
+            "审核会话不存在: {}",
+            id
+        )));
+    }
+    let content = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&content).map_err(|error| UninstallerError::Serde(error.to_string()))
+}
+
+/// 所有审核会话，最近更新的在前
+pub fn list_reviews() -> Result<Vec<ReviewSession>, UninstallerError> {
+    let mut reviews: Vec<ReviewSession> = std::fs::read_dir(reviews_dir()?)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            serde_json::from_str(&content).ok()
+        })
+        .collect();
+    reviews.sort_by_key(|review| std::cmp::Reverse(review.updated_at));
+    Ok(reviews)
+}
+
+/// 删除审核会话
+#[allow(dead_code)]
+pub fn delete_review(id: &str) -> Result<(), UninstallerError> {
+    let path = reviews_dir()?.join(format!("{}.json", id.trim()));
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::scanner::models::TraceType;
+
+    #[test]
+    fn only_approved_traces_are_cleaned() {
+        let traces: Vec<Trace> = ["a", "b", "c"]
+            .iter()
+            .map(|name| {
+                Trace::new(
+                    "Demo".to_string(),
+                    TraceType::File,
+                    format!(r"C:\Demo\{}", name),
+                )
+            })
+            .collect();
+        let mut review = ReviewSession::new("Demo", traces.clone());
+        assert_eq!(review.unresolved_count(), 3);
+
+        review
+            .annotate(&traces[0].id, ReviewStatus::Rejected, None, None)
+            .unwrap();
+        review
+            .annotate(
+                &traces[0].id,
+                ReviewStatus::Approved,
+                Some("确认可删".to_string()),
+                Some("owner".to_string()),
+            )
+            .unwrap();
+        review
+            .annotate(&traces[1].id, ReviewStatus::Rejected, None, None)
+            .unwrap();
+        review
+            .annotate(&traces[2].id, ReviewStatus::NeedsReview, None, None)
+            .unwrap();
+        assert!(review
+            .annotate("missing", ReviewStatus::Approved, None, None)
+            .is_err());
+
+        assert_eq!(review.annotations.len(), 3);
+        assert_eq!(review.unresolved_count(), 1);
+        let approved = review.approved_traces();
+        assert_eq!(approved.len(), 1);
+        assert_eq!(approved[0].id, traces[0].id);
+    }
+}
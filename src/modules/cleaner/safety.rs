@@ -1,5 +1,9 @@
 use crate::modules::common::error::UninstallerError;
-use crate::modules::scanner::models::{Trace, TraceType};
+use crate::modules::common::utils;
+use crate::modules::lister::{self, models::InstalledProgram};
+use crate::modules::scanner::models::{Trace, TraceCategory, TraceType};
+use crate::modules::scanner::{context_menu, credentials, defender, environment, event_log, wmi};
+use std::cell::OnceCell;
 
 /// 关键系统路径黑名单
 const CRITICAL_PATHS: &[&str] = &[
@@ -25,6 +29,127 @@ const CRITICAL_REGISTRY_PATHS: &[&str] = &[
     r"HKLM\SECURITY",
 ];
 
+/// 不允许停止或删除的系统服务（小写）
+const CRITICAL_SERVICES: &[&str] = &[
+    "audiosrv",
+    "bfe",
+    "bits",
+    "cryptsvc",
+    "dcomlaunch",
+    "dhcp",
+    "dnscache",
+    "eventlog",
+    "lanmanserver",
+    "lanmanworkstation",
+    "lsm",
+    "mpssvc",
+    "msiserver",
+    "netlogon",
+    "nsi",
+    "plugplay",
+    "power",
+    "profsvc",
+    "rpceptmapper",
+    "rpcss",
+    "samss",
+    "schedule",
+    "seclogon",
+    "sens",
+    "spooler",
+    "themes",
+    "trustedinstaller",
+    "winmgmt",
+    "windefend",
+    "wuauserv",
+];
+
+/// 系统自带计划任务所在的文件夹（小写）
+const CRITICAL_TASK_FOLDERS: &[&str] = &[r"\microsoft"];
+
+/// 共享运行时目录片段（小写）
+///
+/// 这些目录名常与应用名子串重合（如 "Edge"、"WebView"），但由多个程序共用
+const SHARED_RUNTIME_MARKERS: &[&str] = &[
+    r"\microsoft\edgewebview",
+    r"\microsoft\edge\",
+    r"\microsoft\edgeupdate",
+    r"\microsoft\edgecore",
+    r"\common files\",
+    r"\microsoft shared\",
+    r"\microsoft sdks\",
+    r"\windows kits\",
+    r"\reference assemblies\",
+    r"\dotnet\",
+    r"\microsoft visual studio\shared\",
+    r"\java\jre",
+    r"\java\jdk",
+];
+
+/// 可能被多个程序共用的路径片段（小写）
+const SHARED_PATH_MARKERS: &[&str] = &[r"\common files\", r"\programdata\", r"\shared\"];
+
+/// 补全痕迹的删除影响、共享与可恢复性说明
+pub fn explain_risk(trace: &mut Trace) {
+    let path_lower = trace.path.to_lowercase();
+    let is_dir = std::path::Path::new(&trace.path).is_dir();
+
+    trace.risk.impact = match trace.trace_type {
+        TraceType::RegistryKey => "删除注册表项及其全部子项，可能影响程序设置或文件关联",
+        TraceType::RegistryValue => "删除单个注册表值",
+        TraceType::AppData => "删除用户配置与缓存，重新安装后需要重新配置",
+        TraceType::Shortcut => "删除快捷方式，不影响程序文件",
+        TraceType::File if is_dir => "删除目录及其全部内容",
+        TraceType::File => "删除文件",
+        TraceType::ActiveX => "注销控件的全部注册，并删除 Downloaded Program Files 中的控件文件",
+        TraceType::UsageHistory => "从使用记录列表中移除该条目，不影响程序和文档",
+        TraceType::NetworkSetting => "还原代理设置，可能影响网络连接",
+        TraceType::FirewallRule => "删除防火墙规则，对应程序的端口或联网权限将恢复默认",
+        TraceType::EnvironmentVariable => "从环境变量中移除该路径，变量中的其他内容保持不变",
+        TraceType::ContextMenu => "从资源管理器右键菜单中移除该项，不影响程序文件",
+        TraceType::Credential => "删除保存的登录凭据，相关程序或网站需要重新登录",
+        TraceType::EventLogSource => "注销事件源，已写入的日志保留，但事件查看器将无法显示其描述",
+        TraceType::WmiProvider => {
+            "注销 WMI 提供程序或移除自动恢复 MOF 条目，依赖该提供程序的监控脚本将无法查询"
+        }
+        TraceType::DefenderExclusion => "移除 Defender 排除项，该路径或进程将重新受到实时保护",
+        TraceType::Service => "停止并删除服务，依赖该服务的程序将无法启动",
+        TraceType::ScheduledTask => "删除计划任务，不影响程序文件",
+        _ => "暂不支持清理该类型",
+    }
+    .to_string();
+
+    // 清理为永久删除，只有快捷方式可以简单地重新创建
+    trace.risk.reversible = trace.trace_type == TraceType::Shortcut;
+
+    trace.risk.shared = trace.owner.is_some()
+        || is_shared_runtime_path(&trace.path)
+        || path_lower.starts_with(r"hkcr\")
+        || SHARED_PATH_MARKERS
+            .iter()
+            .any(|marker| path_lower.contains(marker));
+
+    let mut notes = Vec::new();
+    if let Err(e) = pre_delete_check(trace) {
+        notes.push(format!("清理时会被拦截: {}", e));
+    }
+    if let Some(owner) = &trace.owner {
+        notes.push(format!("属于其他用户 {}", owner));
+    }
+    if let Some(reason) = &trace.user_data {
+        notes.push(format!("可能包含用户数据: {}", reason));
+    }
+    if let Some(reason) = &trace.cloud_sync {
+        notes.push(reason.clone());
+    }
+    if trace.category == TraceCategory::UserContent {
+        notes.push("属于用户创建的内容（存档、文档或导出的配置）".to_string());
+    }
+    if trace.trace_type == TraceType::NetworkSetting {
+        notes.push("网络设置可能仍被其他程序或公司策略使用，请确认后再清理".to_string());
+    }
+    trace.risk.notes = notes;
+}
+
 /// 删除前检查
 pub fn pre_delete_check(trace: &Trace) -> Result<(), UninstallerError> {
     // 检查是否标记为关键项
@@ -37,7 +162,7 @@ pub fn pre_delete_check(trace: &Trace) -> Result<(), UninstallerError> {
 
     // 根据类型进行特定检查
     match trace.trace_type {
-        TraceType::RegistryKey | TraceType::RegistryValue => {
+        TraceType::RegistryKey | TraceType::RegistryValue | TraceType::ActiveX => {
             if is_critical_registry(&trace.path) {
                 return Err(UninstallerError::CriticalSystemItem(
                     "不能删除关键系统注册表项".to_string(),
@@ -51,12 +176,163 @@ pub fn pre_delete_check(trace: &Trace) -> Result<(), UninstallerError> {
                 ));
             }
         }
+        TraceType::Service if is_critical_service(super::services::service_name(&trace.path)) => {
+            return Err(UninstallerError::CriticalSystemItem(
+                "不能删除关键系统服务".to_string(),
+            ));
+        }
+        TraceType::EnvironmentVariable if is_critical_environment_segment(&trace.path) => {
+            return Err(UninstallerError::CriticalSystemItem(
+                "不能从环境变量中移除系统目录".to_string(),
+            ));
+        }
+        TraceType::ContextMenu
+            if is_critical_registry(&trace.path)
+                || !context_menu::is_context_menu_path(&trace.path) =>
+        {
+            return Err(UninstallerError::CriticalSystemItem(
+                "只能删除右键菜单项本身，不能删除其所在的类".to_string(),
+            ));
+        }
+        TraceType::Credential
+            if credentials::parse_credential_path(&trace.path)
+                .is_none_or(|(_, target)| credentials::is_system_credential(target)) =>
+        {
+            return Err(UninstallerError::CriticalSystemItem(
+                "不能删除 Windows 账户使用的凭据".to_string(),
+            ));
+        }
+        TraceType::EventLogSource if !event_log::is_event_source_path(&trace.path) => {
+            return Err(UninstallerError::CriticalSystemItem(
+                "只能删除单个事件源，不能删除事件日志本身".to_string(),
+            ));
+        }
+        TraceType::WmiProvider
+            if wmi::parse_provider_path(&trace.path).is_none()
+                && wmi::parse_mof_path(&trace.path).is_none() =>
+        {
+            return Err(UninstallerError::CriticalSystemItem(
+                "只能删除单个 WMI 提供程序或自动恢复 MOF 条目".to_string(),
+            ));
+        }
+        TraceType::DefenderExclusion if defender::parse_exclusion_path(&trace.path).is_none() => {
+            return Err(UninstallerError::CriticalSystemItem(
+                "只能移除单个 Defender 排除项".to_string(),
+            ));
+        }
+        TraceType::ScheduledTask if is_critical_task(&trace.path) => {
+            return Err(UninstallerError::CriticalSystemItem(
+                "不能删除系统自带的计划任务".to_string(),
+            ));
+        }
         _ => {}
     }
 
     Ok(())
 }
 
+/// 是否位于共享运行时目录
+pub fn is_shared_runtime_path(path: &str) -> bool {
+    // 补上末尾分隔符，使目录本身也能命中 `\common files\` 这类片段
+    let path_lower = format!("{}\\", normalize_dir(path));
+    SHARED_RUNTIME_MARKERS
+        .iter()
+        .any(|marker| path_lower.contains(marker))
+}
+
+/// 共享运行时保护
+///
+/// 命中共享运行时目录的痕迹，只有在没有其他已安装程序引用时才允许删除。
+/// 已安装程序列表在首次需要时才从注册表读取，一次清理内复用。
+#[derive(Default)]
+pub struct SharedRuntimeGuard {
+    programs: OnceCell<Option<Vec<InstalledProgram>>>,
+}
+
+impl SharedRuntimeGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用给定的已安装程序列表，不再读取注册表
+    #[allow(dead_code)]
+    pub fn with_programs(programs: Vec<InstalledProgram>) -> Self {
+        Self {
+            programs: OnceCell::from(Some(programs)),
+        }
+    }
+
+    /// 检查痕迹是否为仍被引用的共享运行时目录
+    pub fn check(&self, trace: &Trace) -> Result<(), UninstallerError> {
+        if !matches!(
+            trace.trace_type,
+            TraceType::File | TraceType::AppData | TraceType::Shortcut
+        ) || !is_shared_runtime_path(&trace.path)
+        {
+            return Ok(());
+        }
+
+        let programs =
+            self.programs
+                .get_or_init(|| match lister::registry::list_registry_programs() {
+                    Ok(programs) => Some(programs),
+                    Err(e) => {
+                        tracing::warn!("读取已安装程序失败，共享运行时目录将一律保护: {}", e);
+                        None
+                    }
+                });
+
+        let referenced_by = match programs {
+            Some(programs) => find_referencing_program(trace, programs),
+            None => Some("无法确认引用情况".to_string()),
+        };
+
+        match referenced_by {
+            Some(name) => Err(UninstallerError::CriticalSystemItem(format!(
+                "共享运行时目录仍被其他程序使用 ({}): {}",
+                name, trace.path
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 查找引用该路径的其他已安装程序
+///
+/// 只排除与痕迹所属程序同名的条目；按子串排除会让 "Edge" 把 WebView2 Runtime 也当成自己
+fn find_referencing_program(trace: &Trace, programs: &[InstalledProgram]) -> Option<String> {
+    let trace_path = normalize_dir(&trace.path);
+
+    programs
+        .iter()
+        .filter(|program| !program.name.eq_ignore_ascii_case(trace.program_name.trim()))
+        .find(|program| {
+            [
+                program.install_location.as_deref(),
+                program.uninstall_string.as_deref(),
+                program.icon_path.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|reference| normalize_dir(reference.trim_start_matches('"')))
+            .filter(|reference| !reference.is_empty())
+            .any(|reference| {
+                // 程序位于该目录内，或该目录位于程序安装目录内
+                utils::is_same_or_child(&reference, &trace_path)
+                    || utils::is_same_or_child(&trace_path, &reference)
+            })
+        })
+        .map(|program| program.name.clone())
+}
+
+/// 小写并去除末尾分隔符
+fn normalize_dir(path: &str) -> String {
+    path.to_lowercase()
+        .replace('/', "\\")
+        .trim_end_matches('\\')
+        .to_string()
+}
+
 /// 检查是否为关键系统路径
 fn is_critical_path(path: &str) -> bool {
     let path_upper = path.to_uppercase();
@@ -83,6 +359,26 @@ fn is_critical_registry(path: &str) -> bool {
     false
 }
 
+/// 环境变量痕迹移除的片段是否为系统目录
+fn is_critical_environment_segment(path: &str) -> bool {
+    environment::parse_environment_path(path)
+        .is_none_or(|(_, _, segment)| is_critical_path(&environment::segment_key(segment)))
+}
+
+/// 检查是否为关键系统服务
+fn is_critical_service(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    name.is_empty() || CRITICAL_SERVICES.contains(&name.as_str())
+}
+
+/// 检查是否为系统自带的计划任务
+fn is_critical_task(task_path: &str) -> bool {
+    let path_lower = format!(r"\{}", task_path.trim().trim_start_matches('\\')).to_lowercase();
+    CRITICAL_TASK_FOLDERS
+        .iter()
+        .any(|folder| path_lower.starts_with(folder))
+}
+
 /// 列出所有关键路径（用于显示）
 #[allow(dead_code)]
 pub fn get_critical_paths() -> &'static [&'static str] {
@@ -94,3 +390,65 @@ pub fn get_critical_paths() -> &'static [&'static str] {
 pub fn get_critical_registry_paths() -> &'static [&'static str] {
     CRITICAL_REGISTRY_PATHS
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    fn program(name: &str, location: &str) -> InstalledProgram {
+        let mut program = InstalledProgram::new(name.to_string(), InstallSource::Registry);
+        program.install_location = Some(location.to_string());
+        program
+    }
+
+    #[test]
+    fn shared_runtime_blocked_while_referenced_by_other_program() {
+        let trace = Trace::new(
+            "Edge".to_string(),
+            TraceType::File,
+            r"C:\Program Files (x86)\Microsoft\EdgeWebView".to_string(),
+        );
+        let webview = program(
+            "Microsoft Edge WebView2 Runtime",
+            r"C:\Program Files (x86)\Microsoft\EdgeWebView\Application",
+        );
+
+        let guard = SharedRuntimeGuard::with_programs(vec![webview]);
+        assert!(guard.check(&trace).is_err());
+
+        let guard = SharedRuntimeGuard::with_programs(Vec::new());
+        assert!(guard.check(&trace).is_ok());
+    }
+
+    #[test]
+    fn critical_services_and_tasks_are_blocked() {
+        let service = Trace::new(
+            "Schedule".to_string(),
+            TraceType::Service,
+            r"HKLM\SYSTEM\CurrentControlSet\Services\Schedule".to_string(),
+        );
+        assert!(pre_delete_check(&service).is_err());
+
+        let service = Trace::new(
+            "Acme".to_string(),
+            TraceType::Service,
+            "AcmeUpdater".to_string(),
+        );
+        assert!(pre_delete_check(&service).is_ok());
+
+        assert!(is_critical_task(
+            r"\Microsoft\Windows\Defrag\ScheduledDefrag"
+        ));
+        assert!(!is_critical_task(r"\Acme\AcmeUpdateTask"));
+    }
+
+    #[test]
+    fn shared_runtime_path_matches_directory_itself() {
+        assert!(is_shared_runtime_path(r"C:\Program Files\Common Files"));
+        assert!(is_shared_runtime_path(
+            r"C:\Program Files (x86)\Common Files\Foo\bar.dll"
+        ));
+        assert!(!is_shared_runtime_path(r"C:\Program Files\Foo"));
+    }
+}
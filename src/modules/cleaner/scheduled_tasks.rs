@@ -0,0 +1,83 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::models::Trace;
+use std::process::Command;
+
+/// 删除计划任务
+///
+/// 痕迹路径为任务在任务计划程序中的完整路径（如 `\Vendor\UpdateTask`），
+/// 通过 `schtasks /Delete` 注销；任务已不存在视为成功
+pub async fn delete_scheduled_task_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let task_path = task_path(&trace.path);
+    if task_path.len() <= 1 {
+        return Err(UninstallerError::NotFound(format!(
+            "无效的计划任务路径: {}",
+            trace.path
+        )));
+    }
+
+    if !task_exists(&task_path) {
+        tracing::info!("计划任务已不存在: {}", task_path);
+        return Ok(success(trace));
+    }
+
+    let output = Command::new("schtasks")
+        .args(["/Delete", "/TN", &task_path, "/F"])
+        .output()?;
+    if output.status.success() {
+        tracing::info!("已删除计划任务: {}", task_path);
+        return Ok(success(trace));
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    tracing::error!("删除计划任务失败 {}: {}", task_path, stderr);
+    let error = if stderr.to_lowercase().contains("access is denied") || stderr.contains("拒绝访问")
+    {
+        format!("需要管理员权限: {}", stderr)
+    } else {
+        format!("schtasks 执行失败: {}", stderr)
+    };
+    Ok(CleanResult {
+        trace_id: trace.id.clone(),
+        path: trace.path.clone(),
+        success: false,
+        error: Some(error),
+        bytes_freed: 0,
+    })
+}
+
+/// 规范化任务路径：统一为反斜杠并以 `\` 开头
+fn task_path(path: &str) -> String {
+    let path = path.trim().replace('/', "\\");
+    format!(r"\{}", path.trim_start_matches('\\'))
+}
+
+fn task_exists(task_path: &str) -> bool {
+    Command::new("schtasks")
+        .args(["/Query", "/TN", task_path])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+fn success(trace: &Trace) -> CleanResult {
+    CleanResult {
+        trace_id: trace.id.clone(),
+        path: trace.path.clone(),
+        success: true,
+        error: None,
+        bytes_freed: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_task_path() {
+        assert_eq!(task_path("Vendor/UpdateTask"), r"\Vendor\UpdateTask");
+        assert_eq!(task_path(r"\Vendor\UpdateTask"), r"\Vendor\UpdateTask");
+        assert_eq!(task_path(""), r"\");
+    }
+}
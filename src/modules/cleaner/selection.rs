@@ -0,0 +1,162 @@
+//! 按大小和时间筛选要清理的痕迹
+//!
+//! 用于“只删除大于 100 MB 的缓存目录”“只删除一年以上没动过的项”这类定向清理。
+//! 大小取扫描时记录的文件大小或注册表数据量；时间取文件的修改时间（目录取其中最新的一项）
+//! 或注册表项的最后写入时间。目录中任何一个文件最近被改过，整个目录都视为最近使用过。
+
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+use walkdir::WalkDir;
+
+use super::models::CleanFilter;
+use super::revalidate::{registry_key_last_write, registry_value_key_last_write};
+use crate::modules::common::utils;
+use crate::modules::scanner::models::{Trace, TraceType};
+
+/// 计算目录最新修改时间时最多检查的条目数，超出时按已检查的部分计算
+const MAX_AGE_SCAN_ENTRIES: usize = 20_000;
+
+/// 按条件筛选痕迹，返回保留的痕迹和被排除的数量
+pub fn select_traces(traces: Vec<Trace>, filter: &CleanFilter) -> (Vec<Trace>, usize) {
+    if filter.is_empty() {
+        return (traces, 0);
+    }
+
+    let now = Utc::now();
+    let total = traces.len();
+    let selected: Vec<Trace> = traces
+        .into_iter()
+        .filter(|trace| {
+            let needs_age = filter.min_age_days.is_some() || filter.max_age_days.is_some();
+            matches_filter(
+                filter,
+                trace_size(trace),
+                needs_age.then(|| last_modified(trace)).flatten(),
+                now,
+            )
+        })
+        .collect();
+    let skipped = total - selected.len();
+    (selected, skipped)
+}
+
+/// 痕迹大小：文件取字节数，注册表取数据量，未知时为 None
+fn trace_size(trace: &Trace) -> Option<u64> {
+    trace
+        .size
+        .or_else(|| trace.registry_stats.map(|stats| stats.data_size))
+}
+
+/// 痕迹的最后修改时间，无法读取时为 None
+fn last_modified(trace: &Trace) -> Option<DateTime<Utc>> {
+    match trace.trace_type {
+        TraceType::File | TraceType::AppData | TraceType::Shortcut => {
+            newest_modified(Path::new(&trace.path))
+        }
+        _ if utils::parse_registry_path(&trace.path).is_some() => {
+            registry_key_last_write(&trace.path)
+                .or_else(|| registry_value_key_last_write(&trace.path))
+                .flatten()
+        }
+        _ => None,
+    }
+}
+
+/// 文件的修改时间；目录取自身及其中所有条目里最新的修改时间
+fn newest_modified(path: &Path) -> Option<DateTime<Utc>> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+    if !metadata.is_dir() {
+        return modified;
+    }
+
+    WalkDir::new(path)
+        .min_depth(1)
+        .into_iter()
+        .flatten()
+        .take(MAX_AGE_SCAN_ENTRIES)
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .map(DateTime::<Utc>::from)
+        .chain(modified)
+        .max()
+}
+
+fn matches_filter(
+    filter: &CleanFilter,
+    size: Option<u64>,
+    modified_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    if filter.min_size.is_some() || filter.max_size.is_some() {
+        let Some(size) = size else {
+            return false;
+        };
+        if filter.min_size.is_some_and(|min| size < min)
+            || filter.max_size.is_some_and(|max| size > max)
+        {
+            return false;
+        }
+    }
+
+    if filter.min_age_days.is_some() || filter.max_age_days.is_some() {
+        let Some(modified_at) = modified_at else {
+            return false;
+        };
+        let age = now - modified_at;
+        if filter
+            .min_age_days
+            .is_some_and(|days| age < Duration::days(days as i64))
+            || filter
+                .max_age_days
+                .is_some_and(|days| age > Duration::days(days as i64))
+        {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_size_and_age() {
+        let now = Utc::now();
+        let big = Some(200 * 1024 * 1024);
+        let old = Some(now - Duration::days(400));
+        let recent = Some(now - Duration::days(3));
+
+        let large_only = CleanFilter {
+            min_size: Some(100 * 1024 * 1024),
+            ..Default::default()
+        };
+        assert!(matches_filter(&large_only, big, None, now));
+        assert!(!matches_filter(&large_only, Some(1024), None, now));
+        assert!(!matches_filter(&large_only, None, None, now));
+
+        let stale_only = CleanFilter {
+            min_age_days: Some(365),
+            ..Default::default()
+        };
+        assert!(matches_filter(&stale_only, None, old, now));
+        assert!(!matches_filter(&stale_only, None, recent, now));
+        assert!(!matches_filter(&stale_only, None, None, now));
+
+        let recent_small = CleanFilter {
+            max_size: Some(1024),
+            max_age_days: Some(7),
+            ..Default::default()
+        };
+        assert!(matches_filter(&recent_small, Some(10), recent, now));
+        assert!(!matches_filter(&recent_small, Some(10), old, now));
+        assert!(!matches_filter(&recent_small, big, recent, now));
+
+        assert!(CleanFilter::default().is_empty());
+        let (kept, skipped) = select_traces(Vec::new(), &large_only);
+        assert!(kept.is_empty());
+        assert_eq!(skipped, 0);
+    }
+}
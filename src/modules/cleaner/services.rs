@@ -0,0 +1,147 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::models::Trace;
+use std::process::Command;
+use std::time::Duration;
+
+/// 服务注册位置，痕迹路径可以是服务名或该位置下的注册表项
+const SERVICES_KEY: &str = r"HKLM\SYSTEM\CurrentControlSet\Services\";
+
+/// `sc` 返回的错误码：服务未启动
+const ERROR_SERVICE_NOT_ACTIVE: i32 = 1062;
+
+/// `sc` 返回的错误码：服务不存在
+const ERROR_SERVICE_DOES_NOT_EXIST: i32 = 1060;
+
+/// `sc` 返回的错误码：服务已标记为删除
+const ERROR_SERVICE_MARKED_FOR_DELETE: i32 = 1072;
+
+/// 等待服务停止的次数和间隔
+const STOP_POLL_ATTEMPTS: u32 = 20;
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 停止并删除服务
+///
+/// 先 `sc stop` 并等待服务进入 STOPPED 状态，再 `sc delete`；服务已不存在视为成功。
+/// 仍有句柄打开时服务会被标记为删除，重启后才真正移除
+pub async fn delete_service_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let name = service_name(&trace.path);
+    if name.is_empty() || name.contains(['\\', '/']) {
+        return Err(UninstallerError::NotFound(format!(
+            "无效的服务名: {}",
+            trace.path
+        )));
+    }
+
+    let result = match stop_service(name).await {
+        Ok(()) => run_sc(&["delete", name]),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(code) => {
+            if code == Some(ERROR_SERVICE_MARKED_FOR_DELETE) {
+                tracing::warn!("服务 {} 已标记为删除，重启后生效", name);
+            } else {
+                tracing::info!("已删除服务: {}", name);
+            }
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("删除服务失败 {}: {}", name, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
+
+/// 从痕迹路径取出服务名
+pub fn service_name(path: &str) -> &str {
+    let path = path.trim().trim_end_matches('\\');
+    path.get(..SERVICES_KEY.len())
+        .filter(|head| head.eq_ignore_ascii_case(SERVICES_KEY))
+        .map(|_| &path[SERVICES_KEY.len()..])
+        .unwrap_or(path)
+}
+
+/// 停止服务并等待其退出，服务未运行时直接返回
+async fn stop_service(name: &str) -> Result<(), UninstallerError> {
+    if run_sc(&["stop", name])? == Some(ERROR_SERVICE_NOT_ACTIVE) {
+        return Ok(());
+    }
+
+    for _ in 0..STOP_POLL_ATTEMPTS {
+        let output = Command::new("sc").args(["query", name]).output()?;
+        if !output.status.success() || is_stopped(&String::from_utf8_lossy(&output.stdout)) {
+            return Ok(());
+        }
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+    }
+
+    Err(UninstallerError::Timeout(format!(
+        "等待服务 {} 停止超时",
+        name
+    )))
+}
+
+/// 执行 `sc`，返回可忽略的错误码；其他失败转为错误
+fn run_sc(args: &[&str]) -> Result<Option<i32>, UninstallerError> {
+    let output = Command::new("sc").args(args).output()?;
+    match output.status.code() {
+        Some(0) => Ok(None),
+        Some(
+            code @ (ERROR_SERVICE_NOT_ACTIVE
+            | ERROR_SERVICE_DOES_NOT_EXIST
+            | ERROR_SERVICE_MARKED_FOR_DELETE),
+        ) => Ok(Some(code)),
+        Some(5) => Err(UninstallerError::PermissionDenied(format!(
+            "sc {} 需要管理员权限",
+            args.join(" ")
+        ))),
+        _ => Err(UninstallerError::Other(format!(
+            "sc {} 执行失败: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stdout).trim()
+        ))),
+    }
+}
+
+/// `sc query` 输出中 STATE 行的状态码，1 为 STOPPED；状态名随系统语言变化，只看数字
+fn is_stopped(output: &str) -> bool {
+    output.lines().any(|line| {
+        line.split_once(':')
+            .map(|(_, value)| value.split_whitespace().next() == Some("1"))
+            .unwrap_or(false)
+            && line.trim_start().to_uppercase().starts_with("STATE")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_service_name_and_state() {
+        assert_eq!(
+            service_name(r"HKLM\SYSTEM\CurrentControlSet\Services\AcmeUpdater"),
+            "AcmeUpdater"
+        );
+        assert_eq!(service_name("AcmeUpdater"), "AcmeUpdater");
+
+        let stopped = "SERVICE_NAME: AcmeUpdater\n        TYPE               : 10  WIN32_OWN_PROCESS\n        STATE              : 1  STOPPED\n";
+        assert!(is_stopped(stopped));
+        assert!(!is_stopped(
+            &stopped.replace("1  STOPPED", "3  STOP_PENDING")
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+//! 可恢复的清理会话
+//!
+//! 清理开始前把待删除的痕迹写入会话文件，每处理完一项就更新一次。进程崩溃、被取消，
+//! 或因权限不足部分失败时，会话文件保留下来，`clean --resume <会话>` 只处理剩余和失败的项，
+//! 不必重新扫描。全部成功后会话文件自动删除。
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::models::{CleanOptions, CleanResult};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::lister::storage;
+use crate::modules::scanner::models::Trace;
+
+/// 会话文件所在的子目录
+const SESSIONS_DIR_NAME: &str = "clean_sessions";
+
+/// 清理会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanSession {
+    pub id: String,
+    pub program_name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub options: CleanOptions,
+    /// 尚未处理的痕迹
+    pub pending: Vec<Trace>,
+    /// 已处理但删除失败的痕迹，恢复时重新尝试
+    #[serde(default)]
+    pub failed: Vec<Trace>,
+    /// 所有已处理痕迹的结果，同一痕迹重试后以最后一次为准
+    #[serde(default)]
+    pub results: Vec<CleanResult>,
+}
+
+/// 生成会话 id，便于在命令行输入：时间戳加短随机后缀
+pub fn new_session_id() -> String {
+    format!(
+        "{}-{}",
+        Utc::now().format("%Y%m%d%H%M%S"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    )
+}
+
+impl CleanSession {
+    pub fn new(program_name: &str, traces: Vec<Trace>, options: CleanOptions) -> Self {
+        let now = Utc::now();
+        Self {
+            id: new_session_id(),
+            program_name: program_name.to_string(),
+            created_at: now,
+            updated_at: now,
+            options,
+            pending: traces,
+            failed: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// 没有剩余或失败的痕迹
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty() && self.failed.is_empty()
+    }
+
+    /// 将失败的痕迹放回待处理列表
+    pub fn requeue_failed(&mut self) {
+        let failed = std::mem::take(&mut self.failed);
+        self.pending.extend(failed);
+    }
+
+    /// 记录一项的处理结果
+    pub fn record(&mut self, trace: Trace, result: CleanResult) {
+        self.results
+            .retain(|existing| existing.trace_id != result.trace_id);
+        if !result.success {
+            self.failed.push(trace);
+        }
+        self.results.push(result);
+        self.updated_at = Utc::now();
+    }
+}
+
+/// 会话文件目录
+pub fn sessions_dir() -> Result<PathBuf, UninstallerError> {
+    let dir = storage::get_storage_root_dir()?.join(SESSIONS_DIR_NAME);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 保存会话；先写临时文件再替换，中途崩溃不会留下损坏的会话
+pub fn save_session(session: &CleanSession) -> Result<(), UninstallerError> {
+    let dir = sessions_dir()?;
+    let path = dir.join(format!("{}.json", session.id));
+    let temp_path = dir.join(format!("{}.json.tmp", session.id));
+
+    let json = serde_json::to_string(session)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    std::fs::write(&temp_path, json)?;
+    std::fs::rename(&temp_path, &path)?;
+    Ok(())
+}
+
+/// 按 id 读取会话
+pub fn load_session(id: &str) -> Result<CleanSession, UninstallerError> {
+    let path = sessions_dir()?.join(format!("{}.json", id.trim()));
+    if !path.exists() {
+        return Err(UninstallerError::NotFound(format!(
+            "清理会话不存在: {}",
+            id
+        )));
+    }
+    let content = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&content).map_err(|error| UninstallerError::Serde(error.to_string()))
+}
+
+/// 未完成的会话，最近更新的在前
+pub fn list_sessions() -> Result<Vec<CleanSession>, UninstallerError> {
+    let mut sessions: Vec<CleanSession> = std::fs::read_dir(sessions_dir()?)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            serde_json::from_str(&content).ok()
+        })
+        .collect();
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+    Ok(sessions)
+}
+
+/// 删除会话文件
+pub fn delete_session(id: &str) -> Result<(), UninstallerError> {
+    let path = sessions_dir()?.join(format!("{}.json", id.trim()));
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::scanner::models::TraceType;
+
+    fn result(trace: &Trace, success: bool) -> CleanResult {
+        CleanResult {
+            trace_id: trace.id.clone(),
+            path: trace.path.clone(),
+            success,
+            error: None,
+            bytes_freed: 0,
+        }
+    }
+
+    #[test]
+    fn failed_traces_are_retried_on_resume() {
+        let first = Trace::new(
+            "Demo".to_string(),
+            TraceType::File,
+            r"C:\Demo\a".to_string(),
+        );
+        let second = Trace::new(
+            "Demo".to_string(),
+            TraceType::File,
+            r"C:\Demo\b".to_string(),
+        );
+        let mut session = CleanSession::new(
+            "Demo",
+            vec![first.clone(), second.clone()],
+            CleanOptions::default(),
+        );
+
+        session.pending.clear();
+        session.record(first.clone(), result(&first, true));
+        session.record(second.clone(), result(&second, false));
+        assert!(!session.is_finished());
+
+        session.requeue_failed();
+        assert_eq!(session.pending.len(), 1);
+        let retried = session.pending.remove(0);
+        session.record(retried, result(&second, true));
+        assert!(session.is_finished());
+        assert_eq!(session.results.len(), 2);
+        assert!(session.results.iter().all(|result| result.success));
+    }
+}
@@ -0,0 +1,141 @@
+use super::models::CleanResult;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::scanner::models::Trace;
+use crate::modules::scanner::usage_history::{self, MRU_LIST_EX_VALUE, MRU_LIST_VALUE};
+use std::path::Path;
+use winreg::enums::*;
+use winreg::{RegKey, RegValue};
+
+/// MRUListEx 的结束标记
+const MRU_LIST_EX_END: u32 = u32::MAX;
+
+/// 从使用记录中移除条目
+///
+/// 注册表条目先从排序值（`MRUList`/`MRUListEx`）中去掉再删除值本身，列表中不会残留指向
+/// 不存在条目的引用，其余条目保持原有顺序；跳转列表文件整体删除，系统会在下次使用时重建
+pub async fn delete_usage_history_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let freed = if utils::parse_registry_path(&trace.path).is_some() {
+        remove_mru_entry(&trace.path)?;
+        0
+    } else {
+        remove_jump_list(&trace.path)?
+    };
+
+    tracing::info!("已移除使用记录: {}", trace.path);
+    Ok(CleanResult {
+        trace_id: trace.id.clone(),
+        path: trace.path.clone(),
+        success: true,
+        error: None,
+        bytes_freed: freed,
+    })
+}
+
+fn remove_mru_entry(path: &str) -> Result<(), UninstallerError> {
+    let (key_path, value_name) = path
+        .rsplit_once('\\')
+        .ok_or_else(|| UninstallerError::Registry(format!("无效的使用记录路径: {}", path)))?;
+    let (hkey, subkey) = utils::parse_registry_path(key_path)
+        .ok_or_else(|| UninstallerError::Registry(format!("无效的使用记录路径: {}", path)))?;
+    if hkey != HKEY_CURRENT_USER {
+        return Err(UninstallerError::PermissionDenied(format!(
+            "只编辑当前用户的使用记录: {}",
+            path
+        )));
+    }
+
+    let key = RegKey::predef(hkey)
+        .open_subkey_with_flags(subkey, KEY_READ | KEY_SET_VALUE)
+        .map_err(|e| UninstallerError::Registry(format!("打开 {} 失败: {}", key_path, e)))?;
+
+    if let Ok(list) = key.get_value::<String, _>(MRU_LIST_VALUE) {
+        key.set_value(MRU_LIST_VALUE, &remove_from_mru_list(&list, value_name))
+            .map_err(|e| UninstallerError::Registry(format!("更新 MRUList 失败: {}", e)))?;
+    } else if let (Ok(list), Ok(index)) = (
+        key.get_raw_value(MRU_LIST_EX_VALUE),
+        value_name.parse::<u32>(),
+    ) {
+        let updated = RegValue {
+            bytes: remove_from_mru_list_ex(&list.bytes, index),
+            vtype: list.vtype,
+        };
+        key.set_raw_value(MRU_LIST_EX_VALUE, &updated)
+            .map_err(|e| UninstallerError::Registry(format!("更新 MRUListEx 失败: {}", e)))?;
+    }
+
+    match key.delete_value(value_name) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(UninstallerError::Registry(format!(
+            "删除 {} 失败: {}",
+            path, e
+        ))),
+    }
+}
+
+/// 删除跳转列表文件，返回释放的字节数
+fn remove_jump_list(path: &str) -> Result<u64, UninstallerError> {
+    let file = Path::new(path);
+    let in_jump_list_dir = file.parent().is_some_and(|parent| {
+        usage_history::jump_list_dirs().iter().any(|dir| {
+            utils::normalize_path(&dir.to_string_lossy())
+                .eq_ignore_ascii_case(&utils::normalize_path(&parent.to_string_lossy()))
+        })
+    });
+    if !usage_history::is_jump_list_file(file) || !in_jump_list_dir {
+        return Err(UninstallerError::PermissionDenied(format!(
+            "不是当前用户的跳转列表文件: {}",
+            path
+        )));
+    }
+
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    match std::fs::remove_file(file) {
+        Ok(()) => Ok(size),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 从 `MRUList`（每个字母对应一个值名）中去掉指定条目
+fn remove_from_mru_list(list: &str, value_name: &str) -> String {
+    let mut chars = value_name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) => list.chars().filter(|c| *c != letter).collect(),
+        _ => list.to_string(),
+    }
+}
+
+/// 从 `MRUListEx`（小端 u32 索引数组，以 0xFFFFFFFF 结尾）中去掉指定索引
+fn remove_from_mru_list_ex(bytes: &[u8], index: u32) -> Vec<u8> {
+    let mut entries: Vec<u32> = bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .take_while(|entry| *entry != MRU_LIST_EX_END)
+        .filter(|entry| *entry != index)
+        .collect();
+    entries.push(MRU_LIST_EX_END);
+    entries.into_iter().flat_map(u32::to_le_bytes).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_entries_from_mru_lists() {
+        assert_eq!(remove_from_mru_list("cbad", "b"), "cad");
+        assert_eq!(remove_from_mru_list("cbad", "MRUList"), "cbad");
+
+        let list: Vec<u8> = [3u32, 0, 7, MRU_LIST_EX_END]
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        let expected: Vec<u8> = [3u32, 7, MRU_LIST_EX_END]
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        assert_eq!(remove_from_mru_list_ex(&list, 0), expected);
+    }
+}
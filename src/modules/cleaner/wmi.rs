@@ -0,0 +1,80 @@
+use super::models::CleanResult;
+use super::network::run_command;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::models::Trace;
+use crate::modules::scanner::wmi::{
+    parse_mof_path, parse_provider_path, AUTORECOVER_MOFS_VALUE, CIMOM_KEY, WMI_NAMESPACE,
+};
+use winreg::enums::*;
+use winreg::RegKey;
+
+/// 删除程序留下的 WMI 提供程序注册或自动恢复 MOF 条目
+///
+/// 提供程序按名称删除 `__Win32Provider` 实例，已不存在时视为成功；
+/// MOF 条目只从 `Autorecover MOFs` 列表中移除，不删除 MOF 文件本身
+pub async fn delete_wmi_trace(trace: &Trace) -> Result<CleanResult, UninstallerError> {
+    let result = if let Some(name) = parse_provider_path(&trace.path) {
+        // 名称来自 WMI 查询结果，拼入 WQL 和命令前分别转义单引号
+        let filter = format!("Name='{}'", name.replace('\'', "\\'"));
+        let script = format!(
+            "Get-CimInstance -Namespace '{}' -ClassName __Win32Provider -Filter '{}' \
+             -ErrorAction SilentlyContinue | Remove-CimInstance -ErrorAction Stop",
+            WMI_NAMESPACE,
+            filter.replace('\'', "''")
+        );
+        run_command("powershell", &["-NoProfile", "-Command", &script])
+    } else if let Some(mof) = parse_mof_path(&trace.path) {
+        remove_autorecover_mof(mof)
+    } else {
+        return Err(UninstallerError::CriticalSystemItem(format!(
+            "不是 WMI 提供程序: {}",
+            trace.path
+        )));
+    };
+
+    match result {
+        Ok(()) => {
+            tracing::info!("已删除 WMI 注册: {}", trace.path);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: true,
+                error: None,
+                bytes_freed: 0,
+            })
+        }
+        Err(e) => {
+            tracing::error!("删除 WMI 注册失败 {}: {}", trace.path, e);
+            Ok(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(e.to_string()),
+                bytes_freed: 0,
+            })
+        }
+    }
+}
+
+/// 从 `Autorecover MOFs` 多字符串值中移除条目，条目已不在列表中时视为成功
+fn remove_autorecover_mof(mof: &str) -> Result<(), UninstallerError> {
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey_with_flags(CIMOM_KEY, KEY_READ | KEY_WRITE)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => UninstallerError::PermissionDenied(
+                "修改 WMI 自动恢复列表需要管理员权限".to_string(),
+            ),
+            _ => UninstallerError::Registry(e.to_string()),
+        })?;
+    let mofs: Vec<String> = key.get_value(AUTORECOVER_MOFS_VALUE).unwrap_or_default();
+    let remaining: Vec<String> = mofs
+        .iter()
+        .filter(|entry| !entry.trim().eq_ignore_ascii_case(mof.trim()))
+        .cloned()
+        .collect();
+    if remaining.len() != mofs.len() {
+        key.set_value(AUTORECOVER_MOFS_VALUE, &remaining)
+            .map_err(|e| UninstallerError::Registry(e.to_string()))?;
+    }
+    Ok(())
+}
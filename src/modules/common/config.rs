@@ -0,0 +1,67 @@
+//! 用户配置
+//!
+//! 配置保存在存储目录下的 `config.json`，文件缺失或无法解析时使用默认值。
+//! 首次读取后缓存，修改配置文件后需重新启动程序生效。
+//!
+//! ```json
+//! {
+//!   "format": { "locale": "de-DE", "size_units": "iec" },
+//!   "update": {
+//!     "feed": "https://mirror.example.com/rust-yu/latest.json",
+//!     "publisher": "Example Corp"
+//!   }
+//! }
+//! ```
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::UninstallerError;
+use super::format::FormatSettings;
+use crate::modules::lister::storage;
+use crate::modules::maintenance::update::UpdateSettings;
+
+/// 配置文件名
+const CONFIG_FILE_NAME: &str = "config.json";
+
+/// 用户配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppConfig {
+    /// 数字、大小和日期的显示格式
+    #[serde(default)]
+    pub format: FormatSettings,
+    /// 自更新使用的发布源
+    #[serde(default)]
+    pub update: UpdateSettings,
+}
+
+/// 配置文件位置
+pub fn config_path() -> Result<PathBuf, UninstallerError> {
+    Ok(storage::get_storage_root_dir()?.join(CONFIG_FILE_NAME))
+}
+
+/// 当前配置（进程内缓存）
+pub fn config() -> &'static AppConfig {
+    static CONFIG: OnceLock<AppConfig> = OnceLock::new();
+    CONFIG.get_or_init(load_config)
+}
+
+/// 从配置文件读取配置，失败时记录警告并返回默认值
+pub fn load_config() -> AppConfig {
+    let Ok(path) = config_path() else {
+        return AppConfig::default();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return AppConfig::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|error| {
+        tracing::warn!(
+            "配置文件无法解析，使用默认配置 {}: {}",
+            path.display(),
+            error
+        );
+        AppConfig::default()
+    })
+}
@@ -35,6 +35,9 @@ pub enum UninstallerError {
 
     #[error("序列化错误: {0}")]
     Serde(String),
+
+    #[error("卸载命令不安全: {0}")]
+    UnsafeUninstallCommand(String),
 }
 
 impl serde::Serialize for UninstallerError {
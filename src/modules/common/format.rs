@@ -0,0 +1,202 @@
+//! 数字、大小和日期的显示格式
+//!
+//! 命令行输出、报告和 API 返回的显示文本统一在这里格式化。小数分隔符、大小单位
+//! （资源管理器式 GB、IEC 的 GiB 或十进制 GB）和日期格式可在配置文件中设置，
+//! 未单独设置的项按 `locale` 取该区域的习惯，均未设置时保持原有的显示方式。
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use super::config;
+
+/// 默认日期格式
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 日期时间中的时间部分
+const TIME_FORMAT: &str = "%H:%M:%S";
+
+/// 大小单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeUnits {
+    /// 按 1024 进位、显示为 KB/MB/GB，与资源管理器一致
+    #[default]
+    Windows,
+    /// 按 1024 进位、显示为 KiB/MiB/GiB
+    Iec,
+    /// 按 1000 进位、显示为 kB/MB/GB
+    Decimal,
+}
+
+/// 配置文件中的格式设置，未设置的项按 `locale` 或默认值决定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FormatSettings {
+    /// 区域，如 `zh-CN`、`en-US`、`de-DE`
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub decimal_separator: Option<char>,
+    #[serde(default)]
+    pub size_units: Option<SizeUnits>,
+    /// chrono 格式的日期部分，如 `%d.%m.%Y`
+    #[serde(default)]
+    pub date_format: Option<String>,
+}
+
+/// 解析后的显示格式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Formatter {
+    pub decimal_separator: char,
+    pub size_units: SizeUnits,
+    pub date_format: String,
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self::from_settings(&FormatSettings::default())
+    }
+}
+
+impl Formatter {
+    pub fn from_settings(settings: &FormatSettings) -> Self {
+        let (locale_separator, locale_date_format) = settings
+            .locale
+            .as_deref()
+            .map(locale_defaults)
+            .unwrap_or(('.', DEFAULT_DATE_FORMAT));
+        Self {
+            decimal_separator: settings.decimal_separator.unwrap_or(locale_separator),
+            size_units: settings.size_units.unwrap_or_default(),
+            date_format: settings
+                .date_format
+                .clone()
+                .filter(|format| !format.trim().is_empty())
+                .unwrap_or_else(|| locale_date_format.to_string()),
+        }
+    }
+
+    /// 格式化文件大小
+    pub fn size(&self, bytes: u64) -> String {
+        let (base, units): (u64, [&str; 4]) = match self.size_units {
+            SizeUnits::Windows => (1024, ["KB", "MB", "GB", "TB"]),
+            SizeUnits::Iec => (1024, ["KiB", "MiB", "GiB", "TiB"]),
+            SizeUnits::Decimal => (1000, ["kB", "MB", "GB", "TB"]),
+        };
+        if bytes < base {
+            return format!("{} B", bytes);
+        }
+
+        let mut value = bytes as f64 / base as f64;
+        let mut unit = 0;
+        while value >= base as f64 && unit < units.len() - 1 {
+            value /= base as f64;
+            unit += 1;
+        }
+        format!("{} {}", self.decimal(value, 2), units[unit])
+    }
+
+    /// 按小数分隔符格式化小数
+    pub fn decimal(&self, value: f64, precision: usize) -> String {
+        let text = format!("{:.*}", precision, value);
+        if self.decimal_separator == '.' {
+            text
+        } else {
+            text.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+
+    pub fn date(&self, date: NaiveDate) -> String {
+        date.format(&self.date_format).to_string()
+    }
+
+    pub fn datetime<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        datetime
+            .format(&format!("{} {}", self.date_format, TIME_FORMAT))
+            .to_string()
+    }
+}
+
+/// 按配置文件设置的格式
+pub fn formatter() -> &'static Formatter {
+    static FORMATTER: OnceLock<Formatter> = OnceLock::new();
+    FORMATTER.get_or_init(|| Formatter::from_settings(&config::config().format))
+}
+
+/// 格式化文件大小
+pub fn format_size(bytes: u64) -> String {
+    formatter().size(bytes)
+}
+
+/// 格式化日期
+pub fn format_date(date: NaiveDate) -> String {
+    formatter().date(date)
+}
+
+/// 格式化日期时间
+pub fn format_datetime<Tz: TimeZone>(datetime: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    formatter().datetime(datetime)
+}
+
+/// 区域习惯的小数分隔符和日期格式
+fn locale_defaults(locale: &str) -> (char, &'static str) {
+    let locale = locale.trim().replace('_', "-").to_lowercase();
+    let language = locale.split('-').next().unwrap_or_default();
+
+    let separator = match language {
+        "de" | "fr" | "es" | "it" | "pt" | "nl" | "ru" | "uk" | "pl" | "cs" | "tr" | "sv"
+        | "da" | "nb" | "fi" => ',',
+        _ => '.',
+    };
+    let date_format = match language {
+        "en" if locale == "en" || locale == "en-us" => "%m/%d/%Y",
+        "en" | "fr" | "es" | "it" | "pt" => "%d/%m/%Y",
+        "de" | "ru" | "uk" | "pl" | "cs" | "tr" | "da" | "nb" | "fi" => "%d.%m.%Y",
+        "nl" => "%d-%m-%Y",
+        _ => DEFAULT_DATE_FORMAT,
+    };
+    (separator, date_format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_by_locale_and_units() {
+        let default = Formatter::default();
+        assert_eq!(default.size(512), "512 B");
+        assert_eq!(default.size(1536), "1.50 KB");
+        assert_eq!(default.size(3 * 1024 * 1024 * 1024), "3.00 GB");
+
+        let german = Formatter::from_settings(&FormatSettings {
+            locale: Some("de_DE".to_string()),
+            size_units: Some(SizeUnits::Iec),
+            ..FormatSettings::default()
+        });
+        assert_eq!(german.size(1536), "1,50 KiB");
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        assert_eq!(german.date(date), "09.03.2024");
+
+        let us = Formatter::from_settings(&FormatSettings {
+            locale: Some("en-US".to_string()),
+            size_units: Some(SizeUnits::Decimal),
+            date_format: Some("%Y/%m/%d".to_string()),
+            ..FormatSettings::default()
+        });
+        assert_eq!(us.size(1_500_000), "1.50 MB");
+        assert_eq!(us.date(date), "2024/03/09");
+        assert_eq!(
+            us.datetime(&date.and_hms_opt(8, 5, 0).unwrap().and_utc()),
+            "2024/03/09 08:05:00"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! 已知文件夹的实际位置
+//!
+//! 桌面、文档常被重定向到 OneDrive（“备份”功能）或网络共享（文件夹重定向、漫游配置文件），
+//! 此时 `%USERPROFILE%\Desktop` 可能为空或不存在。这里按 `User Shell Folders` 取实际位置，
+//! 并识别 OneDrive 同步目录和网络位置：这些目录中的删除会同步到云端或服务器，
+//! 清理前需要提醒用户。
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+
+/// 当前用户已知文件夹的实际位置
+const USER_SHELL_FOLDERS_KEY: &str =
+    r"Software\Microsoft\Windows\CurrentVersion\Explorer\User Shell Folders";
+
+/// OneDrive 各账户的同步根目录
+const ONEDRIVE_ACCOUNTS_KEY: &str = r"Software\Microsoft\OneDrive\Accounts";
+
+/// OneDrive 设置的同步根目录环境变量
+const ONEDRIVE_ENV_VARS: &[&str] = &["OneDrive", "OneDriveConsumer", "OneDriveCommercial"];
+
+/// 用户配置文件列表，漫游配置文件在 `CentralProfile` 中记录服务器位置
+const PROFILE_LIST_KEY: &str = r"SOFTWARE\Microsoft\Windows NT\CurrentVersion\ProfileList";
+
+/// 可能被重定向、且删除会同步到服务器的用户文件夹（`User Shell Folders` 中的值名）
+const REDIRECTABLE_FOLDERS: &[&str] = &["Desktop", "Personal", "AppData", "My Pictures"];
+
+/// 同步目录及其说明
+struct SyncRoot {
+    /// 小写，以 `\` 结尾
+    prefix: String,
+    reason: String,
+}
+
+/// 用户桌面：重定向后的位置在前，原位置仍存在时一并返回
+pub fn desktop_dirs() -> Vec<PathBuf> {
+    user_folder_dirs("Desktop", "Desktop")
+}
+
+/// 用户文档：重定向后的位置在前，原位置仍存在时一并返回
+pub fn documents_dirs() -> Vec<PathBuf> {
+    user_folder_dirs("Personal", "Documents")
+}
+
+/// 路径位于 OneDrive 或重定向到网络位置的文件夹中时返回说明
+pub fn cloud_sync_reason(path: &str) -> Option<String> {
+    static ROOTS: OnceLock<Vec<SyncRoot>> = OnceLock::new();
+    let roots = ROOTS.get_or_init(sync_roots);
+    let path_lower = path.replace('/', "\\").to_lowercase();
+    roots
+        .iter()
+        .find(|root| {
+            path_lower.starts_with(&root.prefix) || path_lower == root.prefix.trim_end_matches('\\')
+        })
+        .map(|root| root.reason.clone())
+}
+
+/// `value_name` 为 `User Shell Folders` 中的值名，`default_name` 为配置文件下的默认目录名
+fn user_folder_dirs(value_name: &str, default_name: &str) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(redirected) = shell_folder(value_name) {
+        dirs.push(redirected);
+    }
+    if let Some(home) = dirs::home_dir() {
+        let default = home.join(default_name);
+        let already = dirs.iter().any(|dir| {
+            dir.to_string_lossy()
+                .eq_ignore_ascii_case(&default.to_string_lossy())
+        });
+        if !already && default.exists() {
+            dirs.push(default);
+        }
+    }
+    dirs
+}
+
+/// 已知文件夹的实际位置（已展开环境变量）
+fn shell_folder(value_name: &str) -> Option<PathBuf> {
+    let raw: String = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(USER_SHELL_FOLDERS_KEY)
+        .ok()?
+        .get_value(value_name)
+        .ok()?;
+    let expanded = expand_windows_env_vars(raw.trim());
+    (!expanded.is_empty()).then(|| PathBuf::from(expanded))
+}
+
+fn sync_roots() -> Vec<SyncRoot> {
+    let mut roots = Vec::new();
+    let mut push = |path: &str, reason: String| {
+        let prefix = format!(r"{}\", path.trim().trim_end_matches('\\').to_lowercase());
+        if prefix.len() > 1 && !roots.iter().any(|root: &SyncRoot| root.prefix == prefix) {
+            roots.push(SyncRoot { prefix, reason });
+        }
+    };
+
+    for name in ONEDRIVE_ENV_VARS {
+        if let Ok(path) = std::env::var(name) {
+            push(
+                &path,
+                "位于 OneDrive 同步目录，删除会同步到云端".to_string(),
+            );
+        }
+    }
+    if let Ok(accounts) = RegKey::predef(HKEY_CURRENT_USER).open_subkey(ONEDRIVE_ACCOUNTS_KEY) {
+        for account in accounts.enum_keys().flatten() {
+            let folder: Option<String> = accounts
+                .open_subkey(&account)
+                .and_then(|key| key.get_value("UserFolder"))
+                .ok();
+            if let Some(folder) = folder.filter(|f| !f.trim().is_empty()) {
+                push(
+                    &folder,
+                    "位于 OneDrive 同步目录，删除会同步到云端".to_string(),
+                );
+            }
+        }
+    }
+
+    for value_name in REDIRECTABLE_FOLDERS {
+        let Some(folder) = shell_folder(value_name) else {
+            continue;
+        };
+        let folder = folder.to_string_lossy().to_string();
+        if is_network_path(&folder) {
+            push(
+                &folder,
+                format!(
+                    "位于重定向到网络位置的文件夹 ({})，删除会同步到服务器",
+                    folder
+                ),
+            );
+        }
+    }
+
+    // 漫游配置文件注销时把 AppData\Roaming 上传到服务器
+    if let Some(server) = roaming_profile_server() {
+        if let Ok(app_data) = std::env::var("APPDATA") {
+            push(
+                &app_data,
+                format!("位于漫游配置文件 ({})，删除会在注销时同步到服务器", server),
+            );
+        }
+    }
+
+    roots
+}
+
+/// 当前用户使用漫游配置文件时返回服务器上的配置文件位置
+fn roaming_profile_server() -> Option<String> {
+    let profile = std::env::var("USERPROFILE").ok()?;
+    let profiles = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(PROFILE_LIST_KEY)
+        .ok()?;
+    profiles.enum_keys().flatten().find_map(|sid| {
+        let key = profiles.open_subkey(&sid).ok()?;
+        let image_path: String = key.get_value("ProfileImagePath").ok()?;
+        if !expand_windows_env_vars(&image_path).eq_ignore_ascii_case(&profile) {
+            return None;
+        }
+        key.get_value::<String, _>("CentralProfile")
+            .ok()
+            .filter(|server| !server.trim().is_empty())
+    })
+}
+
+/// UNC 路径（`\\server\share`），排除 `\\?\` 和 `\\.\` 形式的本地设备路径
+fn is_network_path(path: &str) -> bool {
+    path.starts_with(r"\\") && !path.starts_with(r"\\?\") && !path.starts_with(r"\\.\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_network_paths() {
+        assert!(is_network_path(r"\\fileserver\profiles$\alice\Desktop"));
+        assert!(!is_network_path(r"\\?\C:\Users\alice\Desktop"));
+        assert!(!is_network_path(r"\\.\pipe\foo"));
+        assert!(!is_network_path(r"C:\Users\alice\OneDrive\Desktop"));
+    }
+}
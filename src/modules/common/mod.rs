@@ -1,3 +1,10 @@
+pub mod config;
 pub mod error;
+pub mod format;
+pub mod known_folders;
 pub mod logging;
+pub mod priority;
+pub mod profiling;
+pub mod progress;
+pub mod shell_link;
 pub mod utils;
@@ -0,0 +1,192 @@
+//! 扫描任务的 CPU 与 I/O 优先级
+//!
+//! 界面在后台刷新列表或计算大小时不应拖慢整机。`Background` 配置下，执行扫描的线程进入
+//! Windows 的后台处理模式（`THREAD_MODE_BACKGROUND_BEGIN`），同时降低 CPU、I/O 和内存优先级；
+//! 命令行指定 `--priority background` 时整个进程进入后台模式。
+//!
+//! 配置按操作选择：异步操作用 [`scope`] 包裹，扫描器在派生阻塞线程时通过 [`current`] 读取配置，
+//! 再在新线程上调用 [`enter`]。
+
+use std::cell::Cell;
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use super::error::UninstallerError;
+
+/// 优先级配置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityProfile {
+    /// 正常优先级，用户正在等待结果
+    #[default]
+    Interactive,
+    /// 低 CPU 与 I/O 优先级，用于后台刷新
+    Background,
+}
+
+impl fmt::Display for PriorityProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriorityProfile::Interactive => write!(f, "interactive"),
+            PriorityProfile::Background => write!(f, "background"),
+        }
+    }
+}
+
+impl FromStr for PriorityProfile {
+    type Err = UninstallerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "interactive" => Ok(PriorityProfile::Interactive),
+            "background" => Ok(PriorityProfile::Background),
+            other => Err(UninstallerError::Other(format!(
+                "未知的优先级配置: {} (可选 interactive/background)",
+                other
+            ))),
+        }
+    }
+}
+
+/// 进程整体处于后台模式
+static PROCESS_BACKGROUND: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// 当前线程的配置
+    static THREAD_PROFILE: Cell<PriorityProfile> = const { Cell::new(PriorityProfile::Interactive) };
+}
+
+tokio::task_local! {
+    /// 当前异步操作的配置
+    static TASK_PROFILE: PriorityProfile;
+}
+
+/// 当前操作的配置：异步操作的配置优先，其次是线程配置和进程配置
+pub fn current() -> PriorityProfile {
+    if let Ok(profile) = TASK_PROFILE.try_with(|profile| *profile) {
+        return profile;
+    }
+    if PROCESS_BACKGROUND.load(Ordering::Relaxed) {
+        return PriorityProfile::Background;
+    }
+    THREAD_PROFILE.with(Cell::get)
+}
+
+/// 以指定配置执行异步操作，期间派生的扫描线程沿用该配置
+#[allow(dead_code)]
+pub async fn scope<F: Future>(profile: PriorityProfile, future: F) -> F::Output {
+    TASK_PROFILE.scope(profile, future).await
+}
+
+/// 线程优先级的恢复守卫
+pub struct PriorityGuard {
+    previous: Option<PriorityProfile>,
+}
+
+impl Drop for PriorityGuard {
+    fn drop(&mut self) {
+        let Some(previous) = self.previous else {
+            return;
+        };
+        set_thread_background(false);
+        THREAD_PROFILE.with(|profile| profile.set(previous));
+    }
+}
+
+/// 当前线程按配置运行，守卫释放时恢复
+///
+/// 线程已处于后台模式或进程已整体进入后台模式时不做任何修改
+pub fn enter(profile: PriorityProfile) -> PriorityGuard {
+    let previous = THREAD_PROFILE.with(Cell::get);
+    if profile != PriorityProfile::Background
+        || previous == PriorityProfile::Background
+        || PROCESS_BACKGROUND.load(Ordering::Relaxed)
+    {
+        return PriorityGuard { previous: None };
+    }
+
+    if !set_thread_background(true) {
+        return PriorityGuard { previous: None };
+    }
+    THREAD_PROFILE.with(|current| current.set(profile));
+    PriorityGuard {
+        previous: Some(previous),
+    }
+}
+
+/// 整个进程按配置运行，用于命令行
+pub fn set_process_profile(profile: PriorityProfile) {
+    if profile != PriorityProfile::Background || PROCESS_BACKGROUND.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    #[cfg(windows)]
+    {
+        use windows::Win32::System::Threading::{
+            GetCurrentProcess, SetPriorityClass, PROCESS_MODE_BACKGROUND_BEGIN,
+        };
+        if let Err(error) =
+            unsafe { SetPriorityClass(GetCurrentProcess(), PROCESS_MODE_BACKGROUND_BEGIN) }
+        {
+            tracing::warn!("进程进入后台模式失败: {}", error);
+        }
+    }
+}
+
+/// 切换当前线程的后台模式，返回是否成功
+fn set_thread_background(background: bool) -> bool {
+    #[cfg(windows)]
+    {
+        use windows::Win32::System::Threading::{
+            GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN,
+            THREAD_MODE_BACKGROUND_END,
+        };
+        let mode = if background {
+            THREAD_MODE_BACKGROUND_BEGIN
+        } else {
+            THREAD_MODE_BACKGROUND_END
+        };
+        if let Err(error) = unsafe { SetThreadPriority(GetCurrentThread(), mode) } {
+            tracing::debug!("切换线程后台模式失败: {}", error);
+            return false;
+        }
+        true
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = background;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_and_guard_track_profile() {
+        assert_eq!(
+            "Background".parse::<PriorityProfile>().unwrap(),
+            PriorityProfile::Background
+        );
+        assert!("idle".parse::<PriorityProfile>().is_err());
+
+        assert_eq!(current(), PriorityProfile::Interactive);
+        {
+            let _guard = enter(PriorityProfile::Background);
+            assert_eq!(current(), PriorityProfile::Background);
+        }
+        assert_eq!(current(), PriorityProfile::Interactive);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let profile = runtime.block_on(scope(PriorityProfile::Background, async { current() }));
+        assert_eq!(profile, PriorityProfile::Background);
+    }
+}
@@ -0,0 +1,28 @@
+//! 阶段耗时统计，用于性能分析与回归对比
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 单个阶段的耗时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub duration_ms: f64,
+}
+
+impl StageTiming {
+    pub fn new(stage: impl Into<String>, duration: Duration) -> Self {
+        Self {
+            stage: stage.into(),
+            duration_ms: duration.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// 执行闭包并记录耗时
+pub fn measure<T>(timings: &mut Vec<StageTiming>, stage: &str, f: impl FnOnce() -> T) -> T {
+    let started_at = Instant::now();
+    let result = f();
+    timings.push(StageTiming::new(stage, started_at.elapsed()));
+    result
+}
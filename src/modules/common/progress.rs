@@ -0,0 +1,173 @@
+//! 机器可读的进度输出
+//!
+//! 命令行指定 `--progress json` 时，扫描、清理、卸载等阶段的进度以 JSON Lines 写到 stderr，
+//! 每行一个事件，stdout 上的正常输出（包括 `--format json` 的结果）不受影响。
+//! 安装程序、远程管理脚本等包装程序可以据此显示自己的进度界面。
+//!
+//! 事件格式：
+//! - `{"event":"progress","stage":"scan","percent":40,"completed":2,"total":5,"current":"注册表"}`
+//! - `{"event":"done","success":true}`，命令结束时输出一次，失败时带 `error`
+//!
+//! 未开启时所有函数都不做任何事，库代码可以直接调用。
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+
+use super::error::UninstallerError;
+
+/// 进度输出方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// 只输出面向人的文本
+    #[default]
+    Text,
+    /// 额外向 stderr 输出 JSON Lines 进度事件
+    Json,
+}
+
+impl fmt::Display for ProgressMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgressMode::Text => write!(f, "text"),
+            ProgressMode::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for ProgressMode {
+    type Err = UninstallerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "text" => Ok(ProgressMode::Text),
+            "json" => Ok(ProgressMode::Json),
+            other => Err(UninstallerError::Other(format!(
+                "未知的进度输出方式: {} (可选 text/json)",
+                other
+            ))),
+        }
+    }
+}
+
+/// 是否输出 JSON 进度
+static JSON_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// 进度事件
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum ProgressEvent<'a> {
+    Progress {
+        stage: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        completed: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        current: Option<&'a str>,
+    },
+    Done {
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<&'a str>,
+    },
+}
+
+/// 设置整个进程的进度输出方式，用于命令行
+pub fn set_mode(mode: ProgressMode) {
+    JSON_PROGRESS.store(mode == ProgressMode::Json, Ordering::SeqCst);
+}
+
+/// 是否输出 JSON 进度
+pub fn enabled() -> bool {
+    JSON_PROGRESS.load(Ordering::Relaxed)
+}
+
+/// 报告进入某个阶段，总量未知
+pub fn stage(stage: &str, current: Option<&str>) {
+    emit(&ProgressEvent::Progress {
+        stage,
+        percent: None,
+        completed: None,
+        total: None,
+        current,
+    });
+}
+
+/// 报告阶段内已完成 `completed` / `total` 项，`current` 为刚处理的项
+pub fn advance(stage: &str, completed: usize, total: usize, current: Option<&str>) {
+    emit(&ProgressEvent::Progress {
+        stage,
+        percent: Some(percent(completed, total)),
+        completed: Some(completed),
+        total: Some(total),
+        current,
+    });
+}
+
+/// 报告命令结束
+pub fn finish(error: Option<&str>) {
+    emit(&ProgressEvent::Done {
+        success: error.is_none(),
+        error,
+    });
+}
+
+fn emit(event: &ProgressEvent<'_>) {
+    if !enabled() {
+        return;
+    }
+    let Ok(line) = serde_json::to_string(event) else {
+        return;
+    };
+    // 包装程序按行读取，逐行刷新；写入失败（管道已关闭）时忽略
+    let mut stderr = std::io::stderr().lock();
+    let _ = writeln!(stderr, "{}", line);
+    let _ = stderr.flush();
+}
+
+/// 完成百分比，总量为 0 时视为已完成
+fn percent(completed: usize, total: usize) -> u8 {
+    if total == 0 {
+        return 100;
+    }
+    (completed.min(total) * 100 / total) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_events_as_tagged_lines() {
+        assert_eq!(percent(2, 5), 40);
+        assert_eq!(percent(7, 5), 100);
+        assert_eq!(percent(0, 0), 100);
+
+        let event = ProgressEvent::Progress {
+            stage: "clean",
+            percent: Some(50),
+            completed: Some(1),
+            total: Some(2),
+            current: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"progress","stage":"clean","percent":50,"completed":1,"total":2}"#
+        );
+        let done = ProgressEvent::Done {
+            success: true,
+            error: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&done).unwrap(),
+            r#"{"event":"done","success":true}"#
+        );
+        assert_eq!("JSON".parse::<ProgressMode>().unwrap(), ProgressMode::Json);
+    }
+}
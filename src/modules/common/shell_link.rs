@@ -0,0 +1,334 @@
+//! 快捷方式（.lnk）解析
+//!
+//! 按 Shell Link 二进制格式读取目标路径和启动参数：优先取 LinkInfo 段的本地路径，
+//! 其次取环境变量数据块（如 `%ProgramFiles%\Acme\acme.exe`）和相对路径。
+//! 只有 IDList 的快捷方式（部分安装程序创建的快捷方式）由 Shell 的 `IShellLinkW` 解析。
+
+use std::path::Path;
+
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+
+/// Shell Link 头部大小，也是文件开头的固定值
+const HEADER_SIZE: usize = 0x4C;
+
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x1;
+const HAS_LINK_INFO: u32 = 0x2;
+const HAS_NAME: u32 = 0x4;
+const HAS_RELATIVE_PATH: u32 = 0x8;
+const HAS_WORKING_DIR: u32 = 0x10;
+const HAS_ARGUMENTS: u32 = 0x20;
+const HAS_ICON_LOCATION: u32 = 0x40;
+const IS_UNICODE: u32 = 0x80;
+
+/// LinkInfo 中 VolumeIDAndLocalBasePath 标志
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+
+/// 环境变量数据块签名
+const ENVIRONMENT_VARIABLE_BLOCK: u32 = 0xA000_0001;
+
+/// 环境变量数据块中 ANSI 目标的长度，Unicode 目标紧随其后
+const ENVIRONMENT_TARGET_ANSI_SIZE: usize = 260;
+
+/// 解析出的快捷方式内容
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellLink {
+    /// 目标路径，已展开环境变量
+    pub target: Option<String>,
+    /// 启动参数
+    pub arguments: Option<String>,
+}
+
+/// 读取快捷方式的目标路径，文件格式无法解析出路径时交给 Shell 解析
+pub fn resolve_target(path: &Path) -> Option<String> {
+    read(path)
+        .and_then(|link| link.target)
+        .or_else(|| resolve_with_shell(path))
+}
+
+/// 按二进制格式读取快捷方式，不是 Shell Link 文件时返回 None
+pub fn read(path: &Path) -> Option<ShellLink> {
+    let data = std::fs::read(path).ok()?;
+    Some(Sections::parse(&data)?.into_link(path.parent()))
+}
+
+/// 各段中与目标有关的字段
+#[derive(Default)]
+struct Sections {
+    local_base_path: Option<String>,
+    environment_target: Option<String>,
+    relative_path: Option<String>,
+    arguments: Option<String>,
+}
+
+impl Sections {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if read_u32(data, 0)? as usize != HEADER_SIZE {
+            return None;
+        }
+        let flags = read_u32(data, 0x14)?;
+        let mut sections = Sections::default();
+        let mut offset = HEADER_SIZE;
+
+        if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            offset += 2 + read_u16(data, offset)? as usize;
+        }
+
+        if flags & HAS_LINK_INFO != 0 {
+            sections.local_base_path = read_link_info(data, offset);
+            offset += read_u32(data, offset)? as usize;
+        }
+
+        // StringData 按固定顺序排列，只取需要的字段
+        let unicode = flags & IS_UNICODE != 0;
+        for flag in [
+            HAS_NAME,
+            HAS_RELATIVE_PATH,
+            HAS_WORKING_DIR,
+            HAS_ARGUMENTS,
+            HAS_ICON_LOCATION,
+        ] {
+            if flags & flag == 0 {
+                continue;
+            }
+            let (value, size) = read_string_data(data, offset, unicode)?;
+            offset += size;
+            match flag {
+                HAS_RELATIVE_PATH => sections.relative_path = Some(value),
+                HAS_ARGUMENTS => sections.arguments = Some(value),
+                _ => {}
+            }
+        }
+
+        // ExtraData 由若干数据块组成，以大小小于 4 的结束块收尾
+        while let Some(block_size) = read_u32(data, offset).map(|size| size as usize) {
+            if block_size < 8 {
+                break;
+            }
+            if read_u32(data, offset + 4) == Some(ENVIRONMENT_VARIABLE_BLOCK) {
+                let start = offset + 8 + ENVIRONMENT_TARGET_ANSI_SIZE;
+                sections.environment_target = data
+                    .get(start..offset + block_size)
+                    .map(read_utf16_z)
+                    .filter(|target| !target.is_empty())
+                    .map(|target| expand_windows_env_vars(&target));
+            }
+            offset += block_size;
+        }
+
+        Some(sections)
+    }
+
+    /// 目标依次取本地路径、环境变量数据块和相对路径；相对路径以快捷方式所在目录为基准
+    fn into_link(self, link_dir: Option<&Path>) -> ShellLink {
+        let relative_target = || {
+            let relative = self
+                .relative_path
+                .as_deref()
+                .filter(|path| !path.is_empty())?;
+            Some(link_dir?.join(relative).to_string_lossy().to_string())
+        };
+        let target = self
+            .local_base_path
+            .clone()
+            .or_else(|| self.environment_target.clone())
+            .filter(|target| !target.is_empty())
+            .or_else(relative_target);
+        ShellLink {
+            target,
+            arguments: self.arguments.filter(|arguments| !arguments.is_empty()),
+        }
+    }
+}
+
+/// LinkInfo 段中的本地路径（LocalBasePath 与 CommonPathSuffix 拼接）
+fn read_link_info(data: &[u8], offset: usize) -> Option<String> {
+    let header_size = read_u32(data, offset + 4)? as usize;
+    let info_flags = read_u32(data, offset + 8)?;
+    if info_flags & VOLUME_ID_AND_LOCAL_BASE_PATH == 0 {
+        return None;
+    }
+
+    let (base, suffix) = if header_size >= 0x24 {
+        let base = offset + read_u32(data, offset + 28)? as usize;
+        let suffix = offset + read_u32(data, offset + 32)? as usize;
+        (
+            read_utf16_z(data.get(base..)?),
+            data.get(suffix..).map(read_utf16_z).unwrap_or_default(),
+        )
+    } else {
+        let base = offset + read_u32(data, offset + 16)? as usize;
+        let suffix = offset + read_u32(data, offset + 24)? as usize;
+        (
+            read_ansi_z(data.get(base..)?),
+            data.get(suffix..).map(read_ansi_z).unwrap_or_default(),
+        )
+    };
+
+    let target = format!("{}{}", base, suffix);
+    (!target.is_empty()).then_some(target)
+}
+
+/// 读取一个 StringData，返回内容和占用的字节数
+fn read_string_data(data: &[u8], offset: usize, unicode: bool) -> Option<(String, usize)> {
+    let count = read_u16(data, offset)? as usize;
+    let size = if unicode { count * 2 } else { count };
+    let bytes = data.get(offset + 2..offset + 2 + size)?;
+    let value = if unicode {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).to_string()
+    };
+    Some((value, 2 + size))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_utf16_z(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn read_ansi_z(bytes: &[u8]) -> String {
+    let bytes: Vec<u8> = bytes
+        .iter()
+        .copied()
+        .take_while(|byte| *byte != 0)
+        .collect();
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// 通过 `IShellLinkW` 解析目标路径，用于只有 IDList 的快捷方式
+#[cfg(windows)]
+fn resolve_with_shell(path: &Path) -> Option<String> {
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, IPersistFile, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED, STGM_READ,
+    };
+    use windows::Win32::UI::Shell::{IShellLinkW, ShellLink as ShellLinkClass};
+
+    unsafe {
+        // 线程已按其他模式初始化时沿用现有模式，只有本次初始化成功才需要反初始化
+        let initialized = CoInitializeEx(None, COINIT_APARTMENTTHREADED).is_ok();
+        let target = (|| {
+            let link: IShellLinkW =
+                CoCreateInstance(&ShellLinkClass, None, CLSCTX_INPROC_SERVER).ok()?;
+            let file: IPersistFile = link.cast().ok()?;
+            file.Load(&HSTRING::from(path.as_os_str()), STGM_READ)
+                .ok()?;
+
+            let mut buffer = [0u16; 1024];
+            // 不传标志时返回展开环境变量后的长路径
+            link.GetPath(&mut buffer, std::ptr::null_mut(), 0).ok()?;
+            let len = buffer
+                .iter()
+                .position(|unit| *unit == 0)
+                .unwrap_or(buffer.len());
+            let target = String::from_utf16_lossy(&buffer[..len]);
+            (!target.is_empty()).then_some(target)
+        })();
+        if initialized {
+            CoUninitialize();
+        }
+        target
+    }
+}
+
+#[cfg(not(windows))]
+fn resolve_with_shell(_path: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    /// 构造带 LinkInfo（可选）、启动参数和环境变量数据块的快捷方式
+    fn build_link(local_path: Option<&str>, arguments: &str, environment: Option<&str>) -> Vec<u8> {
+        let mut flags = HAS_ARGUMENTS | IS_UNICODE;
+        if local_path.is_some() {
+            flags |= HAS_LINK_INFO;
+        }
+
+        let mut data = vec![0u8; HEADER_SIZE];
+        data[0..4].copy_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+        data[0x14..0x18].copy_from_slice(&flags.to_le_bytes());
+
+        if let Some(local_path) = local_path {
+            // 头部 0x1C 字节，随后是 ANSI 的本地路径和空的公共后缀
+            let base_offset = 0x1Cu32;
+            let suffix_offset = base_offset + local_path.len() as u32 + 1;
+            let size = suffix_offset + 1;
+            for value in [size, 0x1C, 1, base_offset, base_offset, 0, suffix_offset] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            data.extend_from_slice(local_path.as_bytes());
+            data.extend_from_slice(&[0, 0]);
+        }
+
+        data.extend_from_slice(&(arguments.encode_utf16().count() as u16).to_le_bytes());
+        data.extend_from_slice(&utf16(arguments));
+
+        if let Some(environment) = environment {
+            let mut block = vec![0u8; 8 + ENVIRONMENT_TARGET_ANSI_SIZE + 520];
+            let block_size = block.len() as u32;
+            block[0..4].copy_from_slice(&block_size.to_le_bytes());
+            block[4..8].copy_from_slice(&ENVIRONMENT_VARIABLE_BLOCK.to_le_bytes());
+            let target = utf16(environment);
+            let start = 8 + ENVIRONMENT_TARGET_ANSI_SIZE;
+            block[start..start + target.len()].copy_from_slice(&target);
+            data.extend_from_slice(&block);
+        }
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data
+    }
+
+    fn parse(data: &[u8]) -> Option<ShellLink> {
+        Sections::parse(data).map(|sections| sections.into_link(None))
+    }
+
+    #[test]
+    fn parses_target_and_arguments() {
+        let link = parse(&build_link(
+            Some(r"D:\Apps\Acme\acme.exe"),
+            "--profile work",
+            None,
+        ));
+        assert_eq!(
+            link,
+            Some(ShellLink {
+                target: Some(r"D:\Apps\Acme\acme.exe".to_string()),
+                arguments: Some("--profile work".to_string()),
+            })
+        );
+
+        // 没有 LinkInfo 时取环境变量数据块
+        let link = parse(&build_link(None, "", Some(r"D:\Tools\Acme\tool.exe")));
+        assert_eq!(
+            link.and_then(|link| link.target).as_deref(),
+            Some(r"D:\Tools\Acme\tool.exe")
+        );
+
+        assert!(parse(b"not a shell link").is_none());
+    }
+}
@@ -1,8 +1,15 @@
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use std::sync::OnceLock;
 use winreg::enums::*;
 use winreg::HKEY;
 
+/// 模糊匹配每个模式字符要求的最低平均得分
+///
+/// skim 算法中单个命中字符基础分为 16，连续命中和词首命中有额外加分，
+/// 分散命中会被间隔惩罚拉低；平均分低于基础分的结果通常是零散字符的巧合命中
+const FUZZY_MIN_SCORE_PER_CHAR: i64 = 16;
+
 /// 规范化路径（处理大小写、斜杠等）
 #[allow(dead_code)]
 pub fn normalize_path(path: &str) -> String {
@@ -39,34 +46,104 @@ pub fn calculate_dir_size(path: &std::path::Path) -> std::io::Result<u64> {
     Ok(size)
 }
 
-/// 模糊匹配字符串
-pub fn fuzzy_match(text: &str, pattern: &str) -> bool {
-    let matcher = SkimMatcherV2::default();
-    matcher.fuzzy_match(text, pattern).is_some()
+/// 全局共享的模糊匹配器（内部缓存按线程隔离，可跨线程复用）
+fn shared_matcher() -> &'static SkimMatcherV2 {
+    static MATCHER: OnceLock<SkimMatcherV2> = OnceLock::new();
+    MATCHER.get_or_init(|| SkimMatcherV2::default().ignore_case())
 }
 
-/// 获取模糊匹配分数
-#[allow(dead_code)]
-pub fn fuzzy_score(text: &str, pattern: &str) -> i64 {
-    let matcher = SkimMatcherV2::default();
-    matcher.fuzzy_match(text, pattern).unwrap_or(0)
+/// 获取模糊匹配分数，未命中或低于最低分数阈值时返回 `None`
+pub fn fuzzy_score(text: &str, pattern: &str) -> Option<i64> {
+    let pattern_len = pattern.chars().filter(|c| !c.is_whitespace()).count() as i64;
+    if pattern_len == 0 {
+        return None;
+    }
+
+    shared_matcher()
+        .fuzzy_match(text, pattern)
+        .filter(|score| *score >= pattern_len * FUZZY_MIN_SCORE_PER_CHAR)
+}
+
+/// 获取归一化的模糊匹配相似度 (0.0 ~ 1.0)
+///
+/// 以模式串与自身匹配的分数作为满分，便于在不同长度的名称之间比较
+pub fn fuzzy_similarity(text: &str, pattern: &str) -> f64 {
+    let perfect = match shared_matcher().fuzzy_match(pattern, pattern) {
+        Some(score) if score > 0 => score,
+        _ => return 0.0,
+    };
+
+    fuzzy_score(text, pattern)
+        .map(|score| (score as f64 / perfect as f64).clamp(0.0, 1.0))
+        .unwrap_or(0.0)
 }
 
-/// 格式化文件大小
+/// 格式化文件大小，格式见 [`format`](super::format)
 pub fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
+    super::format::format_size(bytes)
+}
+
+/// 解析文件大小，如 `100MB`、`1.5 GB`、`512k`，不带单位时为字节数（1K = 1024）
+pub fn parse_size(input: &str) -> Result<u64, crate::modules::common::error::UninstallerError> {
+    let text = input.trim().to_uppercase();
+    let split_at = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split_at);
+    let multiplier: u64 = match unit.trim().trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1024,
+        "M" => 1024 * 1024,
+        "G" => 1024 * 1024 * 1024,
+        "T" => 1024 * 1024 * 1024 * 1024,
+        _ => {
+            return Err(crate::modules::common::error::UninstallerError::Other(
+                format!("无效的大小: {}（示例: 100MB、1.5GB）", input),
+            ))
+        }
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite() && *value >= 0.0)
+        .map(|value| (value * multiplier as f64) as u64)
+        .ok_or_else(|| {
+            crate::modules::common::error::UninstallerError::Other(format!(
+                "无效的大小: {}（示例: 100MB、1.5GB）",
+                input
+            ))
+        })
+}
+
+/// 比较和存储用的路径键：统一分隔符和大小写，去掉首尾空白和末尾的 `\`
+pub(crate) fn path_key(path: &str) -> String {
+    normalize_path(path.trim())
+        .trim_end_matches('\\')
+        .to_lowercase()
+}
+
+/// `path` 是否等于 `parent` 或位于其下，两者需已统一大小写并去掉末尾分隔符
+pub(crate) fn is_same_or_child(path: &str, parent: &str) -> bool {
+    path == parent
+        || path
+            .strip_prefix(parent)
+            .is_some_and(|rest| rest.starts_with('\\'))
+}
+
+/// 文本中第一个 `{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}` 形式的 GUID，保留原始大小写
+pub fn find_guid(text: &str) -> Option<String> {
+    text.match_indices('{').find_map(|(start, _)| {
+        let candidate = text.get(start..start + 38)?;
+        let is_guid =
+            candidate.ends_with('}')
+                && candidate.bytes().skip(1).take(36).enumerate().all(
+                    |(index, byte)| match index {
+                        8 | 13 | 18 | 23 => byte == b'-',
+                        _ => byte.is_ascii_hexdigit(),
+                    },
+                );
+        is_guid.then(|| candidate.to_string())
+    })
 }
 
 /// 检查路径是否为系统关键路径
@@ -108,6 +185,26 @@ pub fn generate_id() -> String {
     uuid::Uuid::new_v4().to_string()
 }
 
+/// FILETIME 纪元（1601-01-01）到 Unix 纪元的秒数
+const FILETIME_UNIX_EPOCH_DIFF_SECS: i64 = 11_644_473_600;
+
+/// 将 FILETIME 的高低位转换为 UTC 时间（精确到秒）
+pub fn filetime_to_utc(low: u32, high: u32) -> Option<chrono::DateTime<chrono::Utc>> {
+    let ticks = (u64::from(high) << 32) | u64::from(low);
+    let secs = (ticks / 10_000_000) as i64 - FILETIME_UNIX_EPOCH_DIFF_SECS;
+    chrono::DateTime::<chrono::Utc>::from_timestamp(secs, 0)
+}
+
+/// 解码 REG_SZ / REG_EXPAND_SZ 的原始数据（UTF-16 LE），字符串在第一个 NUL 处结束
+pub(crate) fn decode_reg_string(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
 /// 解析注册表路径
 pub fn parse_registry_path(path: &str) -> Option<(HKEY, &str)> {
     let path = path.trim();
@@ -125,6 +222,42 @@ pub fn parse_registry_path(path: &str) -> Option<(HKEY, &str)> {
     }
 }
 
+/// 注册表根键缩写与全名
+const REGISTRY_ROOTS: &[(&str, &str)] = &[
+    ("HKLM", "HKEY_LOCAL_MACHINE"),
+    ("HKCU", "HKEY_CURRENT_USER"),
+    ("HKCR", "HKEY_CLASSES_ROOT"),
+    ("HKU", "HKEY_USERS"),
+];
+
+/// 将缩写的根键展开为全名（regedit 和 PowerShell `Registry::` 使用的格式）
+pub fn full_registry_path(path: &str) -> String {
+    let (root, rest) = path.split_once('\\').unwrap_or((path, ""));
+    let root = REGISTRY_ROOTS
+        .iter()
+        .find(|(short, _)| short.eq_ignore_ascii_case(root))
+        .map_or(root, |(_, full)| full);
+    if rest.is_empty() {
+        root.to_string()
+    } else {
+        format!(r"{}\{}", root, rest)
+    }
+}
+
+/// 将全名根键缩写为 `HKLM` 等形式，与扫描器输出的路径保持一致
+pub fn short_registry_path(path: &str) -> String {
+    let (root, rest) = path.split_once('\\').unwrap_or((path, ""));
+    let root = REGISTRY_ROOTS
+        .iter()
+        .find(|(_, full)| full.eq_ignore_ascii_case(root))
+        .map_or(root, |(short, _)| short);
+    if rest.is_empty() {
+        root.to_string()
+    } else {
+        format!(r"{}\{}", root, rest)
+    }
+}
+
 /// 获取 Windows 系统目录
 #[allow(dead_code)]
 pub fn get_system_dirs() -> Vec<std::path::PathBuf> {
@@ -159,8 +292,8 @@ pub fn get_system_dirs() -> Vec<std::path::PathBuf> {
     if let Some(home) = dirs::home_dir() {
         dirs.push(home.join("AppData").join("Roaming"));
         dirs.push(home.join("AppData").join("Local"));
-        dirs.push(home.join("Desktop"));
     }
+    dirs.extend(super::known_folders::desktop_dirs());
 
     // 开始菜单
     if let Ok(program_data) = std::env::var("ProgramData") {
@@ -172,6 +305,19 @@ pub fn get_system_dirs() -> Vec<std::path::PathBuf> {
     dirs
 }
 
+/// 当前进程是否以管理员身份（已提升）运行
+pub fn is_elevated() -> bool {
+    #[cfg(windows)]
+    {
+        unsafe { windows::Win32::UI::Shell::IsUserAnAdmin().as_bool() }
+    }
+
+    #[cfg(not(windows))]
+    {
+        false
+    }
+}
+
 /// 等待进程及其所有子进程结束
 ///
 /// 在 Windows 上，uninstallString 可能启动 msiexec 或其他安装程序
@@ -257,3 +403,80 @@ async fn has_child_processes(parent_pid: u32) -> bool {
         Err(_) => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_rejects_scattered_matches() {
+        assert!(fuzzy_score("Visual Studio Code", "code").is_some());
+        assert!(fuzzy_score("Microsoft Edge", "code").is_none());
+        assert!(fuzzy_score("anything", "").is_none());
+    }
+
+    #[test]
+    fn fuzzy_similarity_prefers_prefix_hits() {
+        let prefix = fuzzy_similarity("vscode-updater", "vscode");
+        let infix = fuzzy_similarity("myvscodeplugin", "vscode");
+        assert!(prefix >= infix);
+        assert!((fuzzy_similarity("vscode", "vscode") - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn path_key_ignores_case_and_separators() {
+        assert_eq!(path_key(r" C:/Tools//App\ "), r"c:\tools\app");
+        assert_eq!(path_key(r"C:\Tools\App"), path_key(r"c:\tools\app\"));
+    }
+
+    #[test]
+    fn same_or_child_requires_separator() {
+        assert!(is_same_or_child(r"c:\tools\app", r"c:\tools\app"));
+        assert!(is_same_or_child(r"c:\tools\app\bin", r"c:\tools\app"));
+        assert!(!is_same_or_child(r"c:\tools\app2", r"c:\tools\app"));
+    }
+
+    #[test]
+    fn finds_first_well_formed_guid() {
+        assert_eq!(
+            find_guid("MsiExec.exe /I{ABC} /X{90120000-001f-0409-0000-0000000FF1CE}").as_deref(),
+            Some("{90120000-001f-0409-0000-0000000FF1CE}")
+        );
+        assert_eq!(find_guid("Uninstall\\{ABC}"), None);
+        assert_eq!(find_guid("{90120000-001F-0409-0000-0000000FF1CE"), None);
+    }
+
+    #[test]
+    fn reg_string_ends_at_first_nul() {
+        let bytes: Vec<u8> = "a;b\0\0x"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(decode_reg_string(&bytes), "a;b");
+        assert_eq!(decode_reg_string(&[b'a', 0, b'b']), "a");
+    }
+
+    #[test]
+    fn registry_root_names_round_trip() {
+        assert_eq!(
+            full_registry_path(r"HKCU\Software\Foo"),
+            r"HKEY_CURRENT_USER\Software\Foo"
+        );
+        assert_eq!(
+            short_registry_path(r"HKEY_LOCAL_MACHINE\SOFTWARE\Foo"),
+            r"HKLM\SOFTWARE\Foo"
+        );
+        assert_eq!(short_registry_path(r"C:\Foo"), r"C:\Foo");
+    }
+
+    #[test]
+    fn parses_human_readable_sizes() {
+        assert_eq!(parse_size("100MB").unwrap(), 100 * 1024 * 1024);
+        assert_eq!(parse_size("1.5 GB").unwrap(), 1536 * 1024 * 1024);
+        assert_eq!(parse_size("512k").unwrap(), 512 * 1024);
+        assert_eq!(parse_size("2048").unwrap(), 2048);
+        assert_eq!(parse_size("1MiB").unwrap(), 1024 * 1024);
+        assert!(parse_size("ten MB").is_err());
+        assert!(parse_size("5 PB").is_err());
+    }
+}
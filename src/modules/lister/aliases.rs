@@ -0,0 +1,460 @@
+//! 程序别名发现
+//!
+//! Electron / WebView2 应用的数据目录通常以 `productName` 或可执行文件的产品名命名
+//! （如 `%APPDATA%\<ProductName>`、`EBWebView`），与控制面板中的显示名称不同。
+//! 这里从 `package.json`（含 `app.asar` 内的）和版本资源中读取这些名称，供扫描器作为别名使用。
+//!
+//! 国内软件常以中文注册显示名（如“微信”），目录和注册表项却用英文名（`WeChat`），反之亦然。
+//! 内置的多语言名称对照表补充这类别名，搜索和扫描时任一名称都能找到程序及其痕迹。
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::models::InstalledProgram;
+
+/// 每个程序最多保留的别名数量，别名越多扫描越慢
+const MAX_ALIASES: usize = 4;
+
+/// 别名最短长度，过短的名称会产生大量误报
+const MIN_ALIAS_LEN: usize = 3;
+
+/// 最多读取版本资源的可执行文件数量
+const MAX_VERSION_EXES: usize = 3;
+
+/// asar 头部 JSON 的大小上限
+const MAX_ASAR_HEADER_SIZE: u32 = 16 * 1024 * 1024;
+
+/// package.json 的大小上限
+const MAX_PACKAGE_JSON_SIZE: u64 = 1024 * 1024;
+
+/// 过于通用、不能作为别名的名称
+const GENERIC_NAMES: &[&str] = &[
+    "app",
+    "application",
+    "electron",
+    "main",
+    "setup",
+    "update",
+    "updater",
+    "launcher",
+    "helper",
+    "uninstall",
+    "uninstaller",
+    "client",
+    "desktop",
+];
+
+/// 同一程序的多语言名称，名称与组内任一项匹配时其余各项作为别名
+///
+/// 按顺序取第一个匹配的组，包含关系的名称（如“企业微信”与“微信”）需把较长的放在前面
+const KNOWN_NAME_GROUPS: &[&[&str]] = &[
+    &["企业微信", "WXWork", "WeCom"],
+    &["微信", "WeChat"],
+    &["QQ音乐", "QQMusic"],
+    &["腾讯视频", "QQLive"],
+    &["腾讯会议", "WeMeet", "Tencent Meeting"],
+    &["网易云音乐", "CloudMusic", "NetEase Cloud Music"],
+    &["网易有道词典", "有道词典", "YoudaoDict"],
+    &["钉钉", "DingTalk"],
+    &["飞书", "Feishu", "Lark"],
+    &["百度网盘", "BaiduNetdisk"],
+    &["阿里旺旺", "AliWangWang"],
+    &["搜狗输入法", "SogouInput"],
+    &["迅雷", "Thunder"],
+    &["爱奇艺", "iQIYI"],
+    &["酷狗音乐", "KuGou"],
+    &["360安全卫士", "360Safe"],
+    &["向日葵", "SunloginClient"],
+];
+
+/// 从对照表和安装目录发现程序别名
+pub fn enrich_aliases(program: &mut InstalledProgram) {
+    let known = known_aliases(&program.name);
+
+    let root = program
+        .install_location
+        .as_deref()
+        .map(str::trim)
+        .filter(|location| !location.is_empty())
+        .map(Path::new)
+        .filter(|root| root.is_dir());
+    let mut candidates = Vec::new();
+    if let Some(root) = root {
+        candidates.extend(package_json_names(root));
+        candidates.extend(version_resource_names(root));
+    }
+
+    program.aliases = normalize_aliases(&program.name, known, candidates);
+}
+
+/// 对照表中与程序名对应的其他语言名称
+///
+/// 程序名等于组内某一项，或以整词包含某一项（如 "Tencent WeChat 3.9"）时返回组内其余各项
+pub fn known_aliases(program_name: &str) -> Vec<String> {
+    let name = program_name.trim().to_lowercase();
+    if name.is_empty() {
+        return Vec::new();
+    }
+
+    KNOWN_NAME_GROUPS
+        .iter()
+        .find_map(|group| {
+            let matched = group
+                .iter()
+                .find(|known| contains_term(&name, &known.to_lowercase()))?;
+            Some(
+                group
+                    .iter()
+                    .filter(|known| *known != matched)
+                    .map(|known| known.to_string())
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// `name` 中是否出现 `term`，且前后不紧邻字母或数字
+fn contains_term(name: &str, term: &str) -> bool {
+    name.match_indices(term).any(|(start, _)| {
+        let before = name[..start].chars().next_back();
+        let after = name[start + term.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric())
+            && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/// 读取 package.json 中的产品名
+fn package_json_names(root: &Path) -> Vec<String> {
+    let resources = root.join("resources");
+    let content = read_small_file(&resources.join("app").join("package.json"))
+        .or_else(|| read_asar_file(&resources.join("app.asar"), "package.json"))
+        .or_else(|| read_small_file(&root.join("package.json")));
+
+    let Some(content) = content else {
+        return Vec::new();
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return Vec::new();
+    };
+
+    let dir_name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    product_names(&json, &dir_name)
+}
+
+/// package.json 中可作为别名的名称
+///
+/// `productName` 和 `desktopName` 是面向用户的产品名；npm 的 `name` 常是 `app`、`main`
+/// 或内部代号，只有与安装目录名一致时才采用
+fn product_names(json: &serde_json::Value, dir_name: &str) -> Vec<String> {
+    let compact = |value: &str| -> String {
+        value
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+
+    let mut names = Vec::new();
+    for key in ["productName", "name", "desktopName"] {
+        let Some(value) = json.get(key).and_then(|value| value.as_str()) else {
+            continue;
+        };
+        // npm 包名可能带作用域，如 @vendor/app
+        let value = value.rsplit('/').next().unwrap_or(value);
+        let value = value.strip_suffix(".desktop").unwrap_or(value);
+        if key == "name" && (compact(value).is_empty() || compact(value) != compact(dir_name)) {
+            continue;
+        }
+        names.push(value.to_string());
+    }
+    names
+}
+
+fn read_small_file(path: &Path) -> Option<String> {
+    let metadata = path.metadata().ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_PACKAGE_JSON_SIZE {
+        return None;
+    }
+    std::fs::read_to_string(path).ok()
+}
+
+/// 从 asar 归档中读取顶层文件
+///
+/// asar 格式：前 16 字节为 Chromium Pickle 头，`[4..8]` 为头部 Pickle 大小，
+/// `[12..16]` 为 JSON 字符串长度，随后是描述文件偏移的 JSON，文件数据紧跟在头部之后
+fn read_asar_file(archive: &Path, name: &str) -> Option<String> {
+    let mut file = File::open(archive).ok()?;
+
+    let mut prefix = [0u8; 16];
+    file.read_exact(&mut prefix).ok()?;
+    let header_pickle_size = u32::from_le_bytes(prefix[4..8].try_into().ok()?);
+    let json_len = u32::from_le_bytes(prefix[12..16].try_into().ok()?);
+    if json_len > MAX_ASAR_HEADER_SIZE || json_len > header_pickle_size {
+        return None;
+    }
+
+    let mut header = vec![0u8; json_len as usize];
+    file.read_exact(&mut header).ok()?;
+    let header: serde_json::Value = serde_json::from_slice(&header).ok()?;
+
+    let entry = header.get("files")?.get(name)?;
+    let size = entry.get("size")?.as_u64()?;
+    let offset: u64 = entry.get("offset")?.as_str()?.parse().ok()?;
+    if size > MAX_PACKAGE_JSON_SIZE {
+        return None;
+    }
+
+    file.seek(SeekFrom::Start(8 + header_pickle_size as u64 + offset))
+        .ok()?;
+    let mut content = vec![0u8; size as usize];
+    file.read_exact(&mut content).ok()?;
+    String::from_utf8(content).ok()
+}
+
+/// 读取安装目录顶层可执行文件的 ProductName / InternalName
+fn version_resource_names(root: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let exes = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+        })
+        .filter(|path| {
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            !stem.contains("unins") && !stem.contains("update")
+        })
+        .take(MAX_VERSION_EXES);
+
+    let mut names = Vec::new();
+    for exe in exes {
+        for field in ["ProductName", "InternalName"] {
+            if let Some(value) = read_version_string(&exe, field) {
+                let value = value.trim();
+                let value = value
+                    .strip_suffix(".exe")
+                    .or_else(|| value.strip_suffix(".EXE"))
+                    .unwrap_or(value);
+                names.push(value.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// 读取可执行文件版本资源中的字符串字段，如 `ProductName`、`ProductVersion`
+#[cfg(windows)]
+pub fn read_version_string(path: &Path, field: &str) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{
+        GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW,
+    };
+
+    let wide_path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    // SAFETY: 所有缓冲区在调用期间有效，VerQueryValueW 返回的指针指向 data 内部
+    unsafe {
+        let size = GetFileVersionInfoSizeW(PCWSTR(wide_path.as_ptr()), None);
+        if size == 0 {
+            return None;
+        }
+
+        let mut data = vec![0u8; size as usize];
+        GetFileVersionInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            None,
+            size,
+            data.as_mut_ptr() as *mut _,
+        )
+        .ok()?;
+
+        // 取第一个语言/代码页
+        let translation: Vec<u16> = r"\VarFileInfo\Translation"
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let mut buffer = std::ptr::null_mut();
+        let mut len = 0u32;
+        if !VerQueryValueW(
+            data.as_ptr() as *const _,
+            PCWSTR(translation.as_ptr()),
+            &mut buffer,
+            &mut len,
+        )
+        .as_bool()
+            || len < 4
+        {
+            return None;
+        }
+        let codes = std::slice::from_raw_parts(buffer as *const u16, 2);
+
+        let query: Vec<u16> = format!(
+            r"\StringFileInfo\{:04x}{:04x}\{}",
+            codes[0], codes[1], field
+        )
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+        if !VerQueryValueW(
+            data.as_ptr() as *const _,
+            PCWSTR(query.as_ptr()),
+            &mut buffer,
+            &mut len,
+        )
+        .as_bool()
+            || len == 0
+        {
+            return None;
+        }
+
+        let value = std::slice::from_raw_parts(buffer as *const u16, len as usize);
+        let value = String::from_utf16_lossy(value);
+        let value = value.trim_end_matches('\0').trim().to_string();
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+#[cfg(not(windows))]
+pub fn read_version_string(_path: &Path, _field: &str) -> Option<String> {
+    None
+}
+
+/// 过滤与显示名相同、过短或过于通用的名称并去重
+///
+/// 对照表中的名称排在前面，且不受最短长度限制（中文名常只有两个字）
+fn normalize_aliases(
+    program_name: &str,
+    known: Vec<String>,
+    candidates: Vec<String>,
+) -> Vec<String> {
+    let program_lower = program_name.trim().to_lowercase();
+    let mut aliases: Vec<String> = Vec::new();
+
+    let known_count = known.len();
+    for (index, candidate) in known.into_iter().chain(candidates).enumerate() {
+        let alias = candidate.trim();
+        let alias_lower = alias.to_lowercase();
+        let too_short = index >= known_count && alias.chars().count() < MIN_ALIAS_LEN;
+        if too_short
+            || alias.is_empty()
+            || alias_lower == program_lower
+            || GENERIC_NAMES.contains(&alias_lower.as_str())
+            || aliases.iter().any(|a| a.to_lowercase() == alias_lower)
+        {
+            continue;
+        }
+        aliases.push(alias.to_string());
+        if aliases.len() >= MAX_ALIASES {
+            break;
+        }
+    }
+
+    aliases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_aliases_drops_duplicates_and_generic_names() {
+        let aliases = normalize_aliases(
+            "Visual Studio Code",
+            Vec::new(),
+            vec![
+                "Code".to_string(),
+                "code".to_string(),
+                "electron".to_string(),
+                "vs".to_string(),
+                "Visual Studio Code".to_string(),
+            ],
+        );
+        assert_eq!(aliases, vec!["Code".to_string()]);
+    }
+
+    #[test]
+    fn known_aliases_map_localized_names() {
+        assert_eq!(known_aliases("微信"), vec!["WeChat".to_string()]);
+        assert_eq!(
+            known_aliases("Tencent WeChat 3.9"),
+            vec!["微信".to_string()]
+        );
+        assert_eq!(
+            known_aliases("企业微信"),
+            vec!["WXWork".to_string(), "WeCom".to_string()]
+        );
+        assert!(known_aliases("WeChatTools").is_empty());
+        assert!(known_aliases("Notepad++").is_empty());
+
+        let aliases = normalize_aliases("WeChat", known_aliases("WeChat"), vec!["WeChat".into()]);
+        assert_eq!(aliases, vec!["微信".to_string()]);
+    }
+
+    #[test]
+    fn npm_name_is_used_only_when_it_matches_install_dir() {
+        let json: serde_json::Value = serde_json::from_str(
+            r#"{"name":"@acme/main","productName":"Acme Notes","desktopName":"acme-notes.desktop"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            product_names(&json, "Acme Notes"),
+            vec!["Acme Notes".to_string(), "acme-notes".to_string()]
+        );
+
+        let json: serde_json::Value = serde_json::from_str(r#"{"name":"obsidian"}"#).unwrap();
+        assert_eq!(
+            product_names(&json, "Obsidian"),
+            vec!["obsidian".to_string()]
+        );
+        assert!(product_names(&json, "Notes").is_empty());
+    }
+
+    #[test]
+    fn reads_package_json_from_asar() {
+        let dir = std::env::temp_dir().join(format!("rust-yu-asar-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let package = br#"{"name":"@acme/notes","productName":"Acme Notes"}"#;
+        let header = format!(
+            r#"{{"files":{{"package.json":{{"size":{},"offset":"0"}}}}}}"#,
+            package.len()
+        );
+        let json_len = header.len() as u32;
+        // Pickle 按 4 字节对齐
+        let padded = (json_len + 3) & !3;
+        let header_pickle_size = 8 + padded;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        bytes.extend_from_slice(&header_pickle_size.to_le_bytes());
+        bytes.extend_from_slice(&(4 + padded).to_le_bytes());
+        bytes.extend_from_slice(&json_len.to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.resize(8 + header_pickle_size as usize, 0);
+        bytes.extend_from_slice(package);
+
+        let archive = dir.join("app.asar");
+        std::fs::write(&archive, bytes).unwrap();
+
+        let content = read_asar_file(&archive, "package.json").unwrap();
+        assert!(content.contains("Acme Notes"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,168 @@
+//! 预装软件识别
+//!
+//! 按启发式规则为程序打分：OEM 厂商、常见捆绑软件、试用版特征，以及与系统同一天安装。
+//! 分数只用于给出精简建议，不会自动卸载任何程序。
+
+use chrono::{DateTime, Local, NaiveDate};
+use winreg::enums::HKEY_LOCAL_MACHINE;
+use winreg::RegKey;
+
+use super::models::{InstallSource, InstalledProgram};
+
+/// 达到该分数的程序视为疑似预装软件
+pub const BLOATWARE_THRESHOLD: u8 = 50;
+
+/// 记录系统安装时间的注册表项
+const WINDOWS_VERSION_KEY: &str = r"SOFTWARE\Microsoft\Windows NT\CurrentVersion";
+
+/// OEM 厂商，按发布者匹配
+const OEM_VENDORS: &[&str] = &[
+    "dell",
+    "hp inc",
+    "hewlett-packard",
+    "hewlett packard",
+    "lenovo",
+    "asus",
+    "asustek",
+    "acer incorporated",
+    "toshiba",
+    "dynabook",
+    "samsung electronics",
+    "sony",
+    "fujitsu",
+    "huawei",
+    "micro-star",
+    "packard bell",
+];
+
+/// 常见的捆绑软件，按名称或发布者匹配
+const BUNDLED_SOFTWARE: &[&str] = &[
+    "mcafee",
+    "norton",
+    "wildtangent",
+    "cyberlink",
+    "candy crush",
+    "king.com",
+    "booking.com",
+    "dropbox promotion",
+    "expressvpn",
+    "weather channel",
+    "power2go",
+    "powerdvd",
+    "virtual store",
+    "amazon assistant",
+];
+
+/// 试用版特征词，按整词匹配，避免 "Industrial" 之类误判
+const TRIAL_WORDS: &[&str] = &["trial", "offer", "offers", "promotion", "promo"];
+
+/// 为程序填充预装软件评分
+pub fn enrich_bloatware(programs: &mut [InstalledProgram]) {
+    let os_install_date = read_os_install_date();
+    for program in programs {
+        let (score, reasons) = score_program(program, os_install_date);
+        program.bloatware_score = score;
+        program.bloatware_reasons = reasons;
+    }
+}
+
+/// 是否达到疑似预装软件的分数线
+pub fn is_bloatware(program: &InstalledProgram) -> bool {
+    program.bloatware_score >= BLOATWARE_THRESHOLD
+}
+
+/// 只保留疑似预装软件，按分数从高到低排列
+pub fn filter_bloatware(programs: &mut Vec<InstalledProgram>) {
+    programs.retain(is_bloatware);
+    programs.sort_by_key(|program| std::cmp::Reverse(program.bloatware_score));
+}
+
+/// 计算评分（0-100）及命中原因
+fn score_program(
+    program: &InstalledProgram,
+    os_install_date: Option<NaiveDate>,
+) -> (u8, Vec<String>) {
+    // 系统功能不是第三方软件，不参与评分
+    if program.install_source == InstallSource::Feature {
+        return (0, Vec::new());
+    }
+
+    let name = program.name.to_lowercase();
+    let publisher = program
+        .publisher
+        .as_deref()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let mut score = 0u32;
+    let mut reasons = Vec::new();
+
+    if let Some(vendor) = OEM_VENDORS
+        .iter()
+        .find(|vendor| publisher.contains(*vendor))
+    {
+        score += 40;
+        reasons.push(format!("OEM 厂商软件: {}", vendor));
+    }
+    if let Some(bundle) = BUNDLED_SOFTWARE
+        .iter()
+        .find(|bundle| name.contains(*bundle) || publisher.contains(*bundle))
+    {
+        score += 40;
+        reasons.push(format!("常见捆绑软件: {}", bundle));
+    }
+    let trial_word = name
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| TRIAL_WORDS.contains(&word));
+    if trial_word || name.contains("试用") {
+        score += 30;
+        reasons.push("名称带有试用或推广字样".to_string());
+    }
+
+    let install_date = program
+        .install_date
+        .as_deref()
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+    if install_date.is_some() && install_date == os_install_date {
+        score += 30;
+        reasons.push("与系统同一天安装".to_string());
+    }
+
+    (score.min(100) as u8, reasons)
+}
+
+/// 系统安装日期；大版本更新会重置该值，因此只作为辅助依据
+fn read_os_install_date() -> Option<NaiveDate> {
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(WINDOWS_VERSION_KEY)
+        .ok()?;
+    let seconds: u32 = key.get_value("InstallDate").ok()?;
+    let installed_at = DateTime::from_timestamp(i64::from(seconds), 0)?;
+    Some(installed_at.with_timezone(&Local).date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_oem_trialware_installed_with_os() {
+        let os_date = NaiveDate::from_ymd_opt(2024, 3, 1);
+
+        let mut program =
+            InstalledProgram::new("McAfee LiveSafe Trial".to_string(), InstallSource::Registry);
+        program.install_date = Some("2024-03-01".to_string());
+        let (score, reasons) = score_program(&program, os_date);
+        assert_eq!(score, 100);
+        assert_eq!(reasons.len(), 3);
+
+        let mut program =
+            InstalledProgram::new("Dell SupportAssist".to_string(), InstallSource::Registry);
+        program.publisher = Some("Dell Inc.".to_string());
+        program.install_date = Some("2024-05-20".to_string());
+        assert_eq!(score_program(&program, os_date).0, 40);
+
+        let program = InstalledProgram::new("7-Zip".to_string(), InstallSource::Registry);
+        assert_eq!(score_program(&program, os_date).0, 0);
+    }
+}
@@ -0,0 +1,347 @@
+//! 可续算的目录大小
+//!
+//! 没有 `EstimatedSize` 的程序要遍历安装目录计算大小。大型程序（游戏、IDE、SDK）的目录动辄
+//! 几十万个文件，一次列表刷新内算不完。这里把遍历拆成多段：每段只处理预算内的目录，
+//! 未遍历的目录和已累计的大小保存在用户数据库中，下次刷新或空闲时从断点继续，
+//! 几次刷新后收敛到准确大小。算完的大小在 [`RESCAN_AFTER_DAYS`] 天内直接复用，
+//! 过期后重新遍历，期间仍返回上次的结果。
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::pool::PooledConnection;
+use super::storage::{self, map_sqlite_error};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+
+const DIRECTORY_SIZES_TABLE_NAME: &str = "directory_sizes";
+
+/// 算完的大小超过该天数后重新遍历
+pub const RESCAN_AFTER_DAYS: i64 = 7;
+
+/// 单段遍历的预算，到达任一上限后在当前目录处理完时停下
+#[derive(Debug, Clone, Copy)]
+pub struct ScanBudget {
+    pub time: Duration,
+    pub max_entries: u64,
+}
+
+impl ScanBudget {
+    /// 列出程序时使用，不能明显拖慢列表
+    pub const INTERACTIVE: ScanBudget = ScanBudget {
+        time: Duration::from_millis(300),
+        max_entries: 20_000,
+    };
+
+    /// 空闲时在后台线程中使用
+    pub const IDLE: ScanBudget = ScanBudget {
+        time: Duration::from_secs(3),
+        max_entries: 200_000,
+    };
+}
+
+/// 一个目录的大小及遍历进度
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectorySize {
+    pub path: String,
+    /// 最近一次完整遍历得到的大小
+    pub size: Option<u64>,
+    pub completed_at: Option<String>,
+    /// 尚未遍历的目录，为空表示没有进行中的遍历
+    pub pending: Vec<String>,
+    /// 进行中的遍历已累计的大小和条目数
+    pub partial_size: u64,
+    pub partial_entries: u64,
+    pub started_at: Option<String>,
+}
+
+impl DirectorySize {
+    fn new(path: &str) -> Self {
+        DirectorySize {
+            path: path.to_string(),
+            ..DirectorySize::default()
+        }
+    }
+
+    /// 是否有进行中的遍历
+    pub fn in_progress(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// 没有可用的结果或结果已过期，且没有进行中的遍历
+    fn needs_scan(&self, now: DateTime<Utc>) -> bool {
+        if self.in_progress() {
+            return false;
+        }
+        let completed_at = self
+            .completed_at
+            .as_deref()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok());
+        match (self.size, completed_at) {
+            (Some(_), Some(completed_at)) => {
+                now.signed_duration_since(completed_at).num_days() >= RESCAN_AFTER_DAYS
+            }
+            _ => true,
+        }
+    }
+
+    fn start(&mut self) {
+        self.pending = vec![self.path.clone()];
+        self.partial_size = 0;
+        self.partial_entries = 0;
+        self.started_at = Some(Utc::now().to_rfc3339());
+    }
+
+    /// 在预算内继续遍历，本段遍历完成时返回 true
+    ///
+    /// 以目录为单位推进，中断时只丢失当前目录之后的进度，无法读取的目录直接跳过
+    fn advance(&mut self, budget: ScanBudget) -> bool {
+        if !self.in_progress() {
+            return false;
+        }
+        let started_at = Instant::now();
+        let mut entries = 0u64;
+
+        while let Some(directory) = self.pending.pop() {
+            if let Ok(read_dir) = std::fs::read_dir(&directory) {
+                for entry in read_dir.filter_map(Result::ok) {
+                    entries += 1;
+                    // 不跟随符号链接和目录联接，避免重复计算或陷入循环
+                    let Ok(file_type) = entry.file_type() else {
+                        continue;
+                    };
+                    if file_type.is_dir() {
+                        self.pending
+                            .push(entry.path().to_string_lossy().to_string());
+                    } else if file_type.is_file() {
+                        if let Ok(metadata) = entry.metadata() {
+                            self.partial_size = self.partial_size.saturating_add(metadata.len());
+                        }
+                    }
+                }
+            }
+            if started_at.elapsed() >= budget.time || entries >= budget.max_entries {
+                break;
+            }
+        }
+        self.partial_entries += entries;
+
+        if self.in_progress() {
+            return false;
+        }
+        self.size = Some(self.partial_size);
+        self.completed_at = Some(Utc::now().to_rfc3339());
+        self.started_at = None;
+        true
+    }
+}
+
+fn open_directory_sizes_connection() -> Result<PooledConnection, UninstallerError> {
+    let connection = storage::open_user_data_connection()?;
+
+    connection
+        .execute_batch(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                path_key TEXT PRIMARY KEY,
+                path TEXT NOT NULL,
+                size_bytes INTEGER,
+                completed_at TEXT,
+                pending_json TEXT NOT NULL DEFAULT '[]',
+                partial_size INTEGER NOT NULL DEFAULT 0,
+                partial_entries INTEGER NOT NULL DEFAULT 0,
+                started_at TEXT
+            );
+            "#,
+            table = DIRECTORY_SIZES_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("初始化目录大小表失败", error))?;
+
+    Ok(connection)
+}
+
+fn read_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<DirectorySize> {
+    let pending_json: String = row.get(4)?;
+    Ok(DirectorySize {
+        path: row.get(0)?,
+        size: row.get::<_, Option<i64>>(1)?.map(|size| size.max(0) as u64),
+        completed_at: row.get(2)?,
+        partial_size: row.get::<_, i64>(3)?.max(0) as u64,
+        pending: serde_json::from_str(&pending_json).unwrap_or_default(),
+        partial_entries: row.get::<_, i64>(5)?.max(0) as u64,
+        started_at: row.get(6)?,
+    })
+}
+
+const SELECT_COLUMNS: &str =
+    "path, size_bytes, completed_at, partial_size, pending_json, partial_entries, started_at";
+
+fn load(
+    connection: &PooledConnection,
+    path: &str,
+) -> Result<Option<DirectorySize>, UninstallerError> {
+    connection
+        .query_row(
+            &format!(
+                "SELECT {} FROM {} WHERE path_key = ?1",
+                SELECT_COLUMNS, DIRECTORY_SIZES_TABLE_NAME
+            ),
+            params![utils::path_key(path)],
+            read_row,
+        )
+        .optional()
+        .map_err(|error| map_sqlite_error("读取目录大小失败", error))
+}
+
+fn save(connection: &PooledConnection, state: &DirectorySize) -> Result<(), UninstallerError> {
+    let pending_json = serde_json::to_string(&state.pending)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    connection
+        .execute(
+            &format!(
+                "INSERT OR REPLACE INTO {} (path_key, path, size_bytes, completed_at, pending_json,
+                 partial_size, partial_entries, started_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                DIRECTORY_SIZES_TABLE_NAME
+            ),
+            params![
+                utils::path_key(&state.path),
+                state.path,
+                state.size.map(|size| size as i64),
+                state.completed_at,
+                pending_json,
+                state.partial_size as i64,
+                state.partial_entries as i64,
+                state.started_at,
+            ],
+        )
+        .map_err(|error| map_sqlite_error("保存目录大小失败", error))?;
+    Ok(())
+}
+
+/// 读取目录大小，需要时在预算内开始或继续遍历并保存进度
+pub fn refresh(path: &Path, budget: ScanBudget) -> Result<DirectorySize, UninstallerError> {
+    let path = path.to_string_lossy().to_string();
+    let connection = open_directory_sizes_connection()?;
+    let mut state = load(&connection, &path)?.unwrap_or_else(|| DirectorySize::new(&path));
+
+    if state.needs_scan(Utc::now()) {
+        state.start();
+    }
+    if state.in_progress() {
+        if state.advance(budget) {
+            tracing::debug!(
+                "目录大小计算完成: {} ({} 个条目)",
+                path,
+                state.partial_entries
+            );
+        }
+        save(&connection, &state)?;
+    }
+    Ok(state)
+}
+
+/// 是否有尚未完成的遍历
+pub fn has_pending() -> bool {
+    open_directory_sizes_connection()
+        .and_then(|connection| {
+            connection
+                .query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM {} WHERE pending_json <> '[]'",
+                        DIRECTORY_SIZES_TABLE_NAME
+                    ),
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+                .map_err(|error| map_sqlite_error("读取目录大小失败", error))
+        })
+        .is_ok_and(|count| count > 0)
+}
+
+/// 空闲时继续所有未完成的遍历，每个目录推进一段，总耗时超过 `max_total` 后停下
+///
+/// 返回本次完成的目录数
+pub fn advance_pending(budget: ScanBudget, max_total: Duration) -> Result<usize, UninstallerError> {
+    let connection = open_directory_sizes_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT {} FROM {} WHERE pending_json <> '[]' ORDER BY started_at",
+            SELECT_COLUMNS, DIRECTORY_SIZES_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取目录大小失败", error))?;
+    let states = statement
+        .query_map([], read_row)
+        .map_err(|error| map_sqlite_error("读取目录大小失败", error))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| map_sqlite_error("解析目录大小失败", error))?;
+    drop(statement);
+
+    let started_at = Instant::now();
+    let mut completed = 0;
+    for mut state in states {
+        if started_at.elapsed() >= max_total {
+            break;
+        }
+        // 目录已被删除（程序已卸载）时不再继续
+        if !Path::new(&state.path).is_dir() {
+            connection
+                .execute(
+                    &format!(
+                        "DELETE FROM {} WHERE path_key = ?1",
+                        DIRECTORY_SIZES_TABLE_NAME
+                    ),
+                    params![utils::path_key(&state.path)],
+                )
+                .map_err(|error| map_sqlite_error("删除目录大小失败", error))?;
+            continue;
+        }
+        if state.advance(budget) {
+            completed += 1;
+        }
+        save(&connection, &state)?;
+    }
+    Ok(completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn converges_across_chunks() {
+        let root = std::env::temp_dir().join(format!("rust-yu-dir-size-{}", uuid::Uuid::new_v4()));
+        for index in 0..4 {
+            let dir = root.join(format!("dir{}", index));
+            assert!(fs::create_dir_all(&dir).is_ok());
+            assert!(fs::write(dir.join("data.bin"), vec![0u8; 1000]).is_ok());
+        }
+
+        let mut state = DirectorySize::new(&root.to_string_lossy());
+        assert!(state.needs_scan(Utc::now()));
+        state.start();
+        let budget = ScanBudget {
+            time: Duration::from_secs(60),
+            max_entries: 1,
+        };
+        let mut chunks = 0;
+        while !state.advance(budget) {
+            assert!(state.in_progress());
+            chunks += 1;
+            assert!(chunks < 100);
+        }
+
+        assert!(chunks > 1);
+        assert_eq!(state.size, Some(4000));
+        assert!(!state.needs_scan(Utc::now()));
+        assert!(state.needs_scan(Utc::now() + chrono::Duration::days(RESCAN_AFTER_DAYS)));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -6,13 +6,19 @@ use std::time::{Duration, Instant, UNIX_EPOCH};
 
 use chrono::NaiveDate;
 use chrono::Utc;
-use walkdir::WalkDir;
 
+use super::aliases;
+use super::bloatware;
+use super::dir_size::{self, ScanBudget};
+use super::health;
+use super::installer;
 use super::models::{InstalledProgram, MetadataConfidence, MetadataSource};
+use super::placeholder_icon::PlaceholderIcon;
+use super::startup;
 use super::storage;
+use super::trust;
+use crate::modules::common::profiling::StageTiming;
 
-const SIZE_SCAN_TIMEOUT: Duration = Duration::from_millis(300);
-const SIZE_SCAN_MAX_ENTRIES: usize = 20_000;
 const ICON_SCAN_MAX_ENTRIES: usize = 128;
 const ICON_SIZE_SMALL: u32 = 32;
 const ICON_SIZE_LARGE: u32 = 48;
@@ -45,6 +51,79 @@ pub fn sanitize_icon_path(raw: &str) -> Option<String> {
 
 /// 对程序元数据做增强和保守降级
 pub fn enrich_program(program: &mut InstalledProgram) {
+    enrich_install_date(program);
+    enrich_icon(program);
+    enrich_size(program);
+    aliases::enrich_aliases(program);
+    program.installer_kind = installer::detect_installer_kind(program);
+    trust::enrich_trust(program);
+    finalize_metadata_confidence(program);
+}
+
+/// 批量增强元数据，检查安装完整性，关联自启动项并计算预装软件评分
+pub fn enrich_programs(programs: &mut [InstalledProgram]) {
+    // 图标增强会丢弃不存在的 DisplayIcon，先检查完整性
+    health::enrich_health(programs);
+    for program in programs.iter_mut() {
+        enrich_program(program);
+    }
+    startup::enrich_startup(programs);
+    bloatware::enrich_bloatware(programs);
+}
+
+/// 批量增强元数据，并按阶段（完整性、日期/大小、图标提取、别名、签名、自启动项）累计耗时
+pub fn enrich_programs_profiled(programs: &mut [InstalledProgram], timings: &mut Vec<StageTiming>) {
+    let mut metadata_elapsed = Duration::ZERO;
+    let mut icon_elapsed = Duration::ZERO;
+    let mut alias_elapsed = Duration::ZERO;
+    let mut signature_elapsed = Duration::ZERO;
+
+    let started_at = Instant::now();
+    health::enrich_health(programs);
+    timings.push(StageTiming::new("health_check", started_at.elapsed()));
+
+    for program in programs.iter_mut() {
+        let started_at = Instant::now();
+        enrich_install_date(program);
+        metadata_elapsed += started_at.elapsed();
+
+        let started_at = Instant::now();
+        enrich_icon(program);
+        icon_elapsed += started_at.elapsed();
+
+        let started_at = Instant::now();
+        aliases::enrich_aliases(program);
+        alias_elapsed += started_at.elapsed();
+
+        let started_at = Instant::now();
+        program.installer_kind = installer::detect_installer_kind(program);
+        metadata_elapsed += started_at.elapsed();
+
+        let started_at = Instant::now();
+        trust::enrich_trust(program);
+        signature_elapsed += started_at.elapsed();
+
+        let started_at = Instant::now();
+        enrich_size(program);
+        finalize_metadata_confidence(program);
+        metadata_elapsed += started_at.elapsed();
+    }
+
+    timings.push(StageTiming::new("enrichment", metadata_elapsed));
+    timings.push(StageTiming::new("icon_extraction", icon_elapsed));
+    timings.push(StageTiming::new("alias_discovery", alias_elapsed));
+    timings.push(StageTiming::new("signature_check", signature_elapsed));
+
+    let started_at = Instant::now();
+    startup::enrich_startup(programs);
+    timings.push(StageTiming::new("startup_discovery", started_at.elapsed()));
+
+    let started_at = Instant::now();
+    bloatware::enrich_bloatware(programs);
+    timings.push(StageTiming::new("bloatware_scoring", started_at.elapsed()));
+}
+
+fn enrich_install_date(program: &mut InstalledProgram) {
     // 安装日期：无效日期必须返回空并降级置信度
     if let Some(raw_date) = program.install_date.clone() {
         if let Some(normalized) = normalize_install_date(&raw_date) {
@@ -60,8 +139,10 @@ pub fn enrich_program(program: &mut InstalledProgram) {
         program.install_date_source = MetadataSource::Unknown;
         program.install_date_confidence = MetadataConfidence::Unknown;
     }
+}
 
-    // 图标：先清洗 DisplayIcon，再从安装目录回退
+fn enrich_icon(program: &mut InstalledProgram) {
+    // 图标：先清洗 DisplayIcon，再从安装目录回退，都提取不到时使用占位图标
     let original_display_icon = program.icon_path.clone();
     let sanitized_icon = program
         .icon_path
@@ -79,20 +160,16 @@ pub fn enrich_program(program: &mut InstalledProgram) {
         let icon_extract_source = original_display_icon
             .as_deref()
             .unwrap_or_else(|| program.icon_path.as_deref().unwrap_or_default());
-        if let Some(icon_assets) = build_icon_assets_from_path(icon_extract_source) {
-            program.icon_cache_path_32 = icon_assets.icon_cache_path_32;
-            program.icon_cache_path_48 = icon_assets.icon_cache_path_48;
-            // 不再缓存/传输 base64 图标，仅保留磁盘缓存路径
-            program.icon_data_url = None;
-            program.icon_data_url_32 = None;
-            program.icon_data_url_48 = None;
-        } else {
-            program.icon_data_url = None;
-            program.icon_data_url_32 = None;
-            program.icon_data_url_48 = None;
-            program.icon_cache_path_32 = None;
-            program.icon_cache_path_48 = None;
-        }
+        // 不再缓存/传输 base64 图标，仅保留磁盘缓存路径
+        program.icon_data_url = None;
+        program.icon_data_url_32 = None;
+        program.icon_data_url_48 = None;
+        let Some(icon_assets) = build_icon_assets_from_path(icon_extract_source) else {
+            apply_placeholder_icon(program);
+            return;
+        };
+        program.icon_cache_path_32 = icon_assets.icon_cache_path_32;
+        program.icon_cache_path_48 = icon_assets.icon_cache_path_48;
         if from_registry {
             program.icon_source = MetadataSource::Registry;
             program.icon_confidence = MetadataConfidence::High;
@@ -105,12 +182,26 @@ pub fn enrich_program(program: &mut InstalledProgram) {
         program.icon_data_url = None;
         program.icon_data_url_32 = None;
         program.icon_data_url_48 = None;
-        program.icon_cache_path_32 = None;
-        program.icon_cache_path_48 = None;
-        program.icon_source = MetadataSource::Unknown;
-        program.icon_confidence = MetadataConfidence::Low;
+        apply_placeholder_icon(program);
     }
+}
 
+/// 使用占位图标，生成失败时不引用任何缓存文件
+fn apply_placeholder_icon(program: &mut InstalledProgram) {
+    let assets = build_placeholder_icon_assets(&program.name);
+    program.icon_source = if assets.is_some() {
+        MetadataSource::Generated
+    } else {
+        MetadataSource::Unknown
+    };
+    program.icon_confidence = MetadataConfidence::Low;
+    program.icon_cache_path_32 = assets
+        .as_ref()
+        .and_then(|assets| assets.icon_cache_path_32.clone());
+    program.icon_cache_path_48 = assets.and_then(|assets| assets.icon_cache_path_48);
+}
+
+fn enrich_size(program: &mut InstalledProgram) {
     // 大小：优先 EstimatedSize，缺失时回退文件系统扫描
     let (resolved_size, size_source, size_confidence) = resolve_program_size(program);
     program.size = resolved_size;
@@ -119,7 +210,9 @@ pub fn enrich_program(program: &mut InstalledProgram) {
     if program.size.is_some() {
         program.size_last_updated_at = Some(Utc::now().to_rfc3339());
     }
+}
 
+fn finalize_metadata_confidence(program: &mut InstalledProgram) {
     program.metadata_confidence = MetadataConfidence::lowest(&[
         program.install_date_confidence,
         program.icon_confidence,
@@ -127,14 +220,7 @@ pub fn enrich_program(program: &mut InstalledProgram) {
     ]);
 }
 
-/// 批量增强元数据
-pub fn enrich_programs(programs: &mut [InstalledProgram]) {
-    for program in programs {
-        enrich_program(program);
-    }
-}
-
-fn extract_icon_path_candidate(raw: &str) -> Option<String> {
+pub(super) fn extract_icon_path_candidate(raw: &str) -> Option<String> {
     extract_icon_path_candidate_with_index(raw).map(|(path, _)| path)
 }
 
@@ -195,7 +281,7 @@ fn extract_icon_path_candidate_with_index(raw: &str) -> Option<(String, i32)> {
 }
 
 #[cfg(windows)]
-fn expand_windows_env_vars(input: &str) -> String {
+pub(crate) fn expand_windows_env_vars(input: &str) -> String {
     let mut result = String::with_capacity(input.len());
     let mut remain = input;
 
@@ -226,7 +312,7 @@ fn expand_windows_env_vars(input: &str) -> String {
 }
 
 #[cfg(not(windows))]
-fn expand_windows_env_vars(input: &str) -> String {
+pub(crate) fn expand_windows_env_vars(input: &str) -> String {
     input.to_string()
 }
 
@@ -296,11 +382,19 @@ fn build_icon_assets_from_path(icon_path: &str) -> Option<IconAssetBundle> {
 
     let (icon_32_path, icon_48_path) = resolve_icon_cache_paths(source_path, icon_index)?;
     if !icon_32_path.exists() || !icon_48_path.exists() {
-        generate_icon_cache_files(source_path, icon_index, &icon_32_path, &icon_48_path)?;
-    }
-
-    if !icon_32_path.exists() && !icon_48_path.exists() {
-        return None;
+        // 提取失败过的文件不再在每次刷新时重试，文件更新后缓存键随之改变
+        let failure_marker = icon_failure_marker(&icon_32_path);
+        if failure_marker.as_deref().is_some_and(Path::exists) {
+            return None;
+        }
+        let generated =
+            generate_icon_cache_files(source_path, icon_index, &icon_32_path, &icon_48_path);
+        if generated.is_none() || (!icon_32_path.exists() && !icon_48_path.exists()) {
+            if let Some(marker) = failure_marker {
+                let _ = std::fs::write(marker, b"");
+            }
+            return None;
+        }
     }
 
     Some(IconAssetBundle {
@@ -325,6 +419,33 @@ fn resolve_icon_cache_paths(source_path: &Path, icon_index: i32) -> Option<(Path
     Some((icon_32_path, icon_48_path))
 }
 
+/// 提取失败标记放在缓存根目录，不与按尺寸分目录保存的图标文件混在一起
+fn icon_failure_marker(icon_32_path: &Path) -> Option<PathBuf> {
+    let cache_root = icon_32_path.parent()?.parent()?;
+    let cache_key = icon_32_path.file_stem()?.to_string_lossy();
+    Some(cache_root.join(format!("{}.failed", cache_key)))
+}
+
+/// 生成程序的占位图标，首字母与颜色相同的程序共用缓存文件
+fn build_placeholder_icon_assets(program_name: &str) -> Option<IconAssetBundle> {
+    let cache_root = storage::get_icon_cache_dir().ok()?;
+    let icon = PlaceholderIcon::for_name(program_name);
+    let file_name = icon.cache_file_name();
+    let icon_32_path = cache_root
+        .join(ICON_SIZE_SMALL.to_string())
+        .join(&file_name);
+    let icon_48_path = cache_root
+        .join(ICON_SIZE_LARGE.to_string())
+        .join(&file_name);
+    icon.write_png(&icon_32_path, ICON_SIZE_SMALL)?;
+    icon.write_png(&icon_48_path, ICON_SIZE_LARGE)?;
+
+    Some(IconAssetBundle {
+        icon_cache_path_32: Some(icon_32_path.to_string_lossy().to_string()),
+        icon_cache_path_48: Some(icon_48_path.to_string_lossy().to_string()),
+    })
+}
+
 fn build_icon_cache_key(source_path: &Path, icon_index: i32) -> String {
     let mut hasher = DefaultHasher::new();
     ICON_CACHE_KEY_VERSION.hash(&mut hasher);
@@ -643,44 +764,21 @@ fn resolve_program_size(
         return (None, MetadataSource::Unknown, MetadataConfidence::Low);
     }
 
-    match calculate_directory_size_limited(&location, SIZE_SCAN_TIMEOUT, SIZE_SCAN_MAX_ENTRIES) {
-        Some(size) if size > 0 => (
+    // 大目录分多次刷新算完，期间沿用上次完整遍历的结果
+    match dir_size::refresh(&location, ScanBudget::INTERACTIVE) {
+        Ok(dir_size::DirectorySize {
+            size: Some(size), ..
+        }) if size > 0 => (
             Some(size),
             MetadataSource::Filesystem,
             MetadataConfidence::Medium,
         ),
-        _ => (None, MetadataSource::Unknown, MetadataConfidence::Low),
-    }
-}
-
-fn calculate_directory_size_limited(
-    directory: &Path,
-    timeout: Duration,
-    max_entries: usize,
-) -> Option<u64> {
-    let started_at = Instant::now();
-    let mut size = 0u64;
-    let mut entries = 0usize;
-
-    for entry in WalkDir::new(directory).into_iter().filter_map(Result::ok) {
-        if started_at.elapsed() > timeout {
-            return None;
-        }
-        entries += 1;
-        if entries > max_entries {
-            return None;
-        }
-
-        if !entry.file_type().is_file() {
-            continue;
-        }
-
-        if let Ok(metadata) = entry.metadata() {
-            size = size.saturating_add(metadata.len());
+        Ok(_) => (None, MetadataSource::Unknown, MetadataConfidence::Low),
+        Err(error) => {
+            tracing::debug!("计算目录大小失败 {}: {}", location.display(), error);
+            (None, MetadataSource::Unknown, MetadataConfidence::Low)
         }
     }
-
-    Some(size)
 }
 
 #[cfg(test)]
@@ -900,6 +998,10 @@ mod tests {
 
     #[test]
     fn resolve_size_falls_back_to_filesystem_when_estimated_missing() {
+        let _guard = super::storage::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let storage_root = with_storage_root("dir-size");
         let temp_root = std::env::temp_dir().join(format!("rust-yu-test-{}", uuid::Uuid::new_v4()));
         assert!(fs::create_dir_all(&temp_root).is_ok());
         let test_file = temp_root.join("data.bin");
@@ -913,5 +1015,6 @@ mod tests {
         assert_eq!(program.size_source, MetadataSource::Filesystem);
 
         let _ = fs::remove_dir_all(&temp_root);
+        cleanup_storage_root(&storage_root);
     }
 }
@@ -0,0 +1,194 @@
+//! Windows 可选功能与功能按需 (Capabilities)
+//!
+//! 这类组件不在"程序和功能"列表中，却同样占用磁盘空间。
+//! 通过 DISM 的 PowerShell 接口枚举，已启用的项以 [`InstallSource::Feature`] 程序的形式返回，
+//! 卸载命令为对应的 `dism /Disable-Feature` 或 `/Remove-Capability`。
+
+use super::models::{InstallSource, InstalledProgram};
+use crate::modules::common::error::UninstallerError;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// 功能类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeatureKind {
+    /// 可选功能 (Get-WindowsOptionalFeature)
+    OptionalFeature,
+    /// 功能按需 (Get-WindowsCapability)
+    Capability,
+}
+
+/// Windows 功能
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowsFeature {
+    /// 功能名或 Capability 名称，如 `Microsoft-Hyper-V`、`Language.Basic~~~en-US~0.0.1.0`
+    pub name: String,
+    pub kind: FeatureKind,
+    /// 已启用（可选功能）或已安装（Capability）
+    pub enabled: bool,
+    /// DISM 报告的原始状态
+    pub state: String,
+}
+
+impl WindowsFeature {
+    /// 禁用（移除）该功能的 DISM 命令
+    pub fn disable_command(&self) -> String {
+        match self.kind {
+            FeatureKind::OptionalFeature => format!(
+                "dism.exe /Online /Disable-Feature /FeatureName:{} /NoRestart",
+                self.name
+            ),
+            FeatureKind::Capability => format!(
+                "dism.exe /Online /Remove-Capability /CapabilityName:{} /NoRestart",
+                self.name
+            ),
+        }
+    }
+
+    /// 启用（添加）该功能的 DISM 命令
+    pub fn enable_command(&self) -> String {
+        match self.kind {
+            FeatureKind::OptionalFeature => format!(
+                "dism.exe /Online /Enable-Feature /FeatureName:{} /All /NoRestart",
+                self.name
+            ),
+            FeatureKind::Capability => format!(
+                "dism.exe /Online /Add-Capability /CapabilityName:{} /NoRestart",
+                self.name
+            ),
+        }
+    }
+
+    /// 转为程序列表项，卸载命令即禁用命令
+    pub fn to_program(&self) -> InstalledProgram {
+        let mut program = InstalledProgram::new(self.name.clone(), InstallSource::Feature);
+        program.publisher = Some("Microsoft Corporation".to_string());
+        program.uninstall_string = Some(self.disable_command());
+        if self.kind == FeatureKind::Capability {
+            // Capability 名称的最后一段是版本号
+            program.version = self
+                .name
+                .rsplit('~')
+                .next()
+                .filter(|version| !version.is_empty() && *version != self.name)
+                .map(str::to_string);
+        }
+        program
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct FeatureJson {
+    name: Option<String>,
+    kind: Option<String>,
+    state: Option<String>,
+}
+
+/// 列出所有可选功能和 Capability（包括未启用的），需要管理员权限
+pub fn list_windows_features() -> Result<Vec<WindowsFeature>, UninstallerError> {
+    let output = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            r#"
+            $features = Get-WindowsOptionalFeature -Online | ForEach-Object {
+                [PSCustomObject]@{ Name = $_.FeatureName; Kind = 'Feature'; State = [string]$_.State }
+            }
+            $capabilities = Get-WindowsCapability -Online | ForEach-Object {
+                [PSCustomObject]@{ Name = $_.Name; Kind = 'Capability'; State = [string]$_.State }
+            }
+            @($features) + @($capabilities) | ConvertTo-Json -Depth 2
+            "#,
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if stderr.contains("elevation") || stderr.contains("提升") {
+            return Err(UninstallerError::PermissionDenied(
+                "枚举 Windows 功能需要管理员权限".to_string(),
+            ));
+        }
+        return Err(UninstallerError::Other(format!(
+            "枚举 Windows 功能失败: {}",
+            stderr
+        )));
+    }
+
+    parse_features(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// 已启用的功能，作为程序列表的一个来源
+pub fn list_feature_programs() -> Result<Vec<InstalledProgram>, UninstallerError> {
+    Ok(list_windows_features()?
+        .iter()
+        .filter(|feature| feature.enabled)
+        .map(WindowsFeature::to_program)
+        .collect())
+}
+
+/// 按名称查找功能（不区分大小写）
+pub fn find_feature(name: &str) -> Result<Option<WindowsFeature>, UninstallerError> {
+    Ok(list_windows_features()?
+        .into_iter()
+        .find(|feature| feature.name.eq_ignore_ascii_case(name)))
+}
+
+fn parse_features(json_str: &str) -> Result<Vec<WindowsFeature>, UninstallerError> {
+    if json_str.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let entries: Vec<FeatureJson> = serde_json::from_str(json_str)
+        .or_else(|_| serde_json::from_str::<FeatureJson>(json_str).map(|entry| vec![entry]))
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let name = entry.name.filter(|name| !name.is_empty())?;
+            let state = entry.state.unwrap_or_default();
+            let kind = match entry.kind.as_deref() {
+                Some("Capability") => FeatureKind::Capability,
+                _ => FeatureKind::OptionalFeature,
+            };
+            let enabled = match kind {
+                FeatureKind::OptionalFeature => state == "Enabled",
+                FeatureKind::Capability => state == "Installed",
+            };
+            Some(WindowsFeature {
+                name,
+                kind,
+                enabled,
+                state,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_features_and_capabilities() {
+        let json = r#"[
+            {"Name":"Microsoft-Hyper-V","Kind":"Feature","State":"Enabled"},
+            {"Name":"TelnetClient","Kind":"Feature","State":"Disabled"},
+            {"Name":"Language.Basic~~~en-US~0.0.1.0","Kind":"Capability","State":"Installed"}
+        ]"#;
+
+        let features = parse_features(json).unwrap();
+        assert_eq!(features.len(), 3);
+        assert!(features[0].enabled);
+        assert!(!features[1].enabled);
+
+        let program = features[2].to_program();
+        assert_eq!(program.version.as_deref(), Some("0.0.1.0"));
+        assert_eq!(
+            program.uninstall_string.as_deref(),
+            Some("dism.exe /Online /Remove-Capability /CapabilityName:Language.Basic~~~en-US~0.0.1.0 /NoRestart")
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! 安装完整性
+//!
+//! 检查程序的主程序、卸载程序、安装目录和指向程序的快捷方式是否仍然存在。被杀毒软件误删、
+//! 手动删除过目录或更新中断的程序常处于“半损坏”状态：卸载程序缺失时无法正常卸载，只能强制移除；
+//! 主程序或快捷方式缺失时更适合修复安装。主程序取 `DisplayIcon` 指向的可执行文件，
+//! 图标增强会丢弃不存在的路径，因此在增强元数据之前检查。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::enrichment::extract_icon_path_candidate;
+use super::models::{InstallSource, InstalledProgram};
+use crate::modules::common::known_folders;
+use crate::modules::common::shell_link;
+use crate::modules::common::utils;
+use crate::modules::scanner::roots;
+use crate::modules::uninstaller::validation;
+
+/// 开始菜单中快捷方式的最大目录深度
+const SHORTCUT_MAX_DEPTH: usize = 3;
+
+/// 完整性状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// 未检查（商店应用、系统功能）
+    #[default]
+    Unknown,
+    Healthy,
+    /// 部分文件缺失，建议修复安装
+    Degraded,
+    /// 卸载程序缺失，无法正常卸载，只能强制移除
+    Broken,
+}
+
+impl std::fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HealthStatus::Unknown => write!(f, "未知"),
+            HealthStatus::Healthy => write!(f, "正常"),
+            HealthStatus::Degraded => write!(f, "不完整"),
+            HealthStatus::Broken => write!(f, "已损坏"),
+        }
+    }
+}
+
+/// 完整性问题类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthIssueKind {
+    MissingInstallLocation,
+    MissingMainExecutable,
+    MissingUninstaller,
+    BrokenShortcut,
+}
+
+/// 一项完整性问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthIssue {
+    pub kind: HealthIssueKind,
+    /// 缺失的文件或失效的快捷方式
+    pub path: String,
+    pub description: String,
+}
+
+/// 程序的完整性检查结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramHealth {
+    pub status: HealthStatus,
+    pub issues: Vec<HealthIssue>,
+}
+
+/// 一个快捷方式及其目标
+#[derive(Debug, Clone)]
+struct Shortcut {
+    path: PathBuf,
+    /// 小写的目标路径
+    target: String,
+}
+
+/// 为程序填充完整性检查结果，快捷方式只枚举一次
+pub fn enrich_health(programs: &mut [InstalledProgram]) {
+    let shortcuts = collect_shortcuts();
+    for program in programs.iter_mut() {
+        program.health = check_program(program, &shortcuts);
+    }
+}
+
+/// 检查单个程序
+fn check_program(program: &InstalledProgram, shortcuts: &[Shortcut]) -> ProgramHealth {
+    if matches!(
+        program.install_source,
+        InstallSource::Store | InstallSource::Feature
+    ) {
+        return ProgramHealth::default();
+    }
+
+    let mut issues = Vec::new();
+
+    let location = program
+        .install_location
+        .as_deref()
+        .map(|location| location.trim().trim_matches('"'))
+        .filter(|location| !location.is_empty());
+    if let Some(location) = location {
+        if !Path::new(location).exists() {
+            issues.push(HealthIssue {
+                kind: HealthIssueKind::MissingInstallLocation,
+                path: location.to_string(),
+                description: format!("安装目录不存在: {}", location),
+            });
+        }
+    }
+
+    let main_executable = main_executable(program);
+    if let Some(executable) = &main_executable {
+        if !executable.exists() {
+            issues.push(HealthIssue {
+                kind: HealthIssueKind::MissingMainExecutable,
+                path: executable.to_string_lossy().to_string(),
+                description: format!("主程序不存在: {}", executable.display()),
+            });
+        }
+    }
+
+    if let Some(uninstaller) = uninstaller(program) {
+        if !uninstaller.exists() {
+            issues.push(HealthIssue {
+                kind: HealthIssueKind::MissingUninstaller,
+                path: uninstaller.to_string_lossy().to_string(),
+                description: format!("卸载程序不存在: {}", uninstaller.display()),
+            });
+        }
+    }
+
+    let install_root = roots::install_root(program);
+    let main_executable = main_executable.map(|path| path.to_string_lossy().to_lowercase());
+    for shortcut in shortcuts {
+        let points_to_program = install_root
+            .as_deref()
+            .is_some_and(|root| shortcut.target.starts_with(root))
+            || main_executable.as_deref() == Some(shortcut.target.as_str());
+        if points_to_program && !Path::new(&shortcut.target).exists() {
+            issues.push(HealthIssue {
+                kind: HealthIssueKind::BrokenShortcut,
+                path: shortcut.path.to_string_lossy().to_string(),
+                description: format!("快捷方式目标不存在: {}", shortcut.path.display()),
+            });
+        }
+    }
+
+    let status = if issues
+        .iter()
+        .any(|issue| issue.kind == HealthIssueKind::MissingUninstaller)
+    {
+        HealthStatus::Broken
+    } else if issues.is_empty() {
+        HealthStatus::Healthy
+    } else {
+        HealthStatus::Degraded
+    };
+    ProgramHealth { status, issues }
+}
+
+/// `DisplayIcon` 指向的可执行文件，指向图标文件或 DLL 时不作为主程序
+fn main_executable(program: &InstalledProgram) -> Option<PathBuf> {
+    let candidate = program
+        .icon_path
+        .as_deref()
+        .and_then(extract_icon_path_candidate)?;
+    let path = PathBuf::from(candidate);
+    let is_exe = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"));
+    (is_exe && path.is_absolute()).then_some(path)
+}
+
+/// 卸载命令中的卸载程序；msiexec 等系统卸载程序始终存在，不参与检查
+fn uninstaller(program: &InstalledProgram) -> Option<PathBuf> {
+    let path = program
+        .uninstall_string
+        .as_deref()
+        .and_then(validation::parse_executable)?;
+    (path.is_absolute() && !validation::is_trusted_system_uninstaller(&path)).then_some(path)
+}
+
+/// 枚举桌面和开始菜单中的快捷方式，同一目标只保留第一个
+fn collect_shortcuts() -> Vec<Shortcut> {
+    let mut dirs = known_folders::desktop_dirs();
+    if let Ok(public) = std::env::var("Public") {
+        dirs.push(Path::new(&public).join("Desktop"));
+    }
+    for base in [std::env::var("APPDATA"), std::env::var("ProgramData")]
+        .into_iter()
+        .flatten()
+    {
+        dirs.push(Path::new(&base).join(r"Microsoft\Windows\Start Menu\Programs"));
+    }
+
+    let mut shortcuts: HashMap<String, Shortcut> = HashMap::new();
+    for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+        let walker = WalkDir::new(dir)
+            .max_depth(SHORTCUT_MAX_DEPTH)
+            .follow_links(false);
+        for entry in walker.into_iter().filter_map(Result::ok) {
+            let path = entry.path();
+            let is_link = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"));
+            if !is_link {
+                continue;
+            }
+            let Some(target) = shell_link::resolve_target(path) else {
+                continue;
+            };
+            let target = utils::normalize_path(&target).to_lowercase();
+            shortcuts.entry(target.clone()).or_insert(Shortcut {
+                path: path.to_path_buf(),
+                target,
+            });
+        }
+    }
+    shortcuts.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn flags_missing_files_and_broken_shortcuts() {
+        let root = std::env::temp_dir().join(format!("rust-yu-health-{}", uuid::Uuid::new_v4()));
+        assert!(fs::create_dir_all(&root).is_ok());
+        let uninstaller = root.join("uninst.exe");
+        assert!(fs::write(&uninstaller, b"binary").is_ok());
+
+        let mut program = InstalledProgram::new("Acme".to_string(), InstallSource::Registry);
+        program.install_location = Some(root.to_string_lossy().to_string());
+        program.uninstall_string = Some(format!("\"{}\" /S", uninstaller.display()));
+        program.icon_path = Some(format!("{},0", root.join("acme.exe").display()));
+        assert!(fs::write(root.join("acme.exe"), b"binary").is_ok());
+
+        let shortcuts = vec![Shortcut {
+            path: PathBuf::from(r"C:\Users\Public\Desktop\Acme Tool.lnk"),
+            target: root.join("tool.exe").to_string_lossy().to_lowercase(),
+        }];
+
+        let health = check_program(&program, &[]);
+        assert_eq!(health.status, HealthStatus::Healthy);
+
+        let health = check_program(&program, &shortcuts);
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(health.issues[0].kind, HealthIssueKind::BrokenShortcut);
+
+        assert!(fs::remove_file(&uninstaller).is_ok());
+        let health = check_program(&program, &[]);
+        assert_eq!(health.status, HealthStatus::Broken);
+        assert_eq!(health.issues[0].kind, HealthIssueKind::MissingUninstaller);
+
+        let store = InstalledProgram::new("Store App".to_string(), InstallSource::Store);
+        assert_eq!(check_program(&store, &[]).status, HealthStatus::Unknown);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,288 @@
+//! 全局忽略规则
+//!
+//! 保留的痕迹按程序保存，反复出现在不同程序扫描结果中的误报（如共用的运行库目录、
+//! 公司统一的配置项）需要对所有程序生效的规则。规则保存在用户数据库中，扫描时命中的痕迹不再报告：
+//!
+//! - `prefix`：路径或注册表项前缀，其下内容一并忽略，如 `HKCU\Software\Microsoft\Office`
+//! - `glob`：通配符，`*` 不跨越 `\`，`**` 匹配任意层目录，如 `C:\Users\*\AppData\Local\Temp\**`
+//! - `regex`：正则表达式，按小写、统一为 `\` 分隔的路径匹配
+//!
+//! 匹配均不区分大小写。
+
+use std::str::FromStr;
+
+use chrono::Utc;
+use regex::Regex;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::pool::PooledConnection;
+use super::storage::{self, map_sqlite_error};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+
+const IGNORE_RULES_TABLE_NAME: &str = "ignore_rules";
+
+/// 规则类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IgnoreRuleKind {
+    Prefix,
+    Glob,
+    Regex,
+}
+
+impl IgnoreRuleKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            IgnoreRuleKind::Prefix => "prefix",
+            IgnoreRuleKind::Glob => "glob",
+            IgnoreRuleKind::Regex => "regex",
+        }
+    }
+}
+
+impl std::fmt::Display for IgnoreRuleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for IgnoreRuleKind {
+    type Err = UninstallerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "prefix" => Ok(IgnoreRuleKind::Prefix),
+            "glob" => Ok(IgnoreRuleKind::Glob),
+            "regex" => Ok(IgnoreRuleKind::Regex),
+            other => Err(UninstallerError::Other(format!(
+                "未知的忽略规则类别: {}（可用: prefix, glob, regex）",
+                other
+            ))),
+        }
+    }
+}
+
+/// 一条忽略规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IgnoreRule {
+    pub id: i64,
+    pub kind: IgnoreRuleKind,
+    pub pattern: String,
+    pub created_at: String,
+}
+
+fn open_ignore_rules_connection() -> Result<PooledConnection, UninstallerError> {
+    let connection = storage::open_user_data_connection()?;
+
+    connection
+        .execute_batch(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                UNIQUE (kind, pattern)
+            );
+            "#,
+            table = IGNORE_RULES_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("初始化忽略规则表失败", error))?;
+
+    Ok(connection)
+}
+
+/// 添加规则，规则无效时返回错误；已存在相同规则时返回已有的规则
+pub fn add_rule(kind: IgnoreRuleKind, pattern: &str) -> Result<IgnoreRule, UninstallerError> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err(UninstallerError::Other("忽略规则不能为空".to_string()));
+    }
+    CompiledRule::compile(kind, pattern)?;
+
+    let connection = open_ignore_rules_connection()?;
+    connection
+        .execute(
+            &format!(
+                "INSERT OR IGNORE INTO {} (kind, pattern, created_at) VALUES (?1, ?2, ?3)",
+                IGNORE_RULES_TABLE_NAME
+            ),
+            params![kind.as_str(), pattern, Utc::now().to_rfc3339()],
+        )
+        .map_err(|error| map_sqlite_error("写入忽略规则失败", error))?;
+
+    list_rules()?
+        .into_iter()
+        .find(|rule| rule.kind == kind && rule.pattern == pattern)
+        .ok_or_else(|| UninstallerError::Other("写入忽略规则后未能读取".to_string()))
+}
+
+/// 删除规则，返回是否存在
+pub fn remove_rule(id: i64) -> Result<bool, UninstallerError> {
+    let connection = open_ignore_rules_connection()?;
+    let removed = connection
+        .execute(
+            &format!("DELETE FROM {} WHERE id = ?1", IGNORE_RULES_TABLE_NAME),
+            params![id],
+        )
+        .map_err(|error| map_sqlite_error("删除忽略规则失败", error))?;
+    Ok(removed > 0)
+}
+
+/// 列出全部规则，按添加顺序
+pub fn list_rules() -> Result<Vec<IgnoreRule>, UninstallerError> {
+    let connection = open_ignore_rules_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT id, kind, pattern, created_at FROM {} ORDER BY id",
+            IGNORE_RULES_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取忽略规则失败", error))?;
+
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .map_err(|error| map_sqlite_error("读取忽略规则失败", error))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| map_sqlite_error("解析忽略规则失败", error))?;
+
+    // 无法识别的类别（更新版本写入的）跳过
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, kind, pattern, created_at)| {
+            Some(IgnoreRule {
+                id,
+                kind: kind.parse().ok()?,
+                pattern,
+                created_at,
+            })
+        })
+        .collect())
+}
+
+/// 编译后的规则
+enum CompiledRule {
+    Prefix(String),
+    Glob(glob::Pattern),
+    Regex(Regex),
+}
+
+impl CompiledRule {
+    fn compile(kind: IgnoreRuleKind, pattern: &str) -> Result<Self, UninstallerError> {
+        match kind {
+            IgnoreRuleKind::Prefix => Ok(CompiledRule::Prefix(utils::path_key(pattern))),
+            // glob 在非 Windows 平台把 `\` 当作转义符，统一改写为 `/` 后匹配
+            IgnoreRuleKind::Glob => {
+                glob::Pattern::new(&utils::path_key(pattern).replace('\\', "/"))
+                    .map(CompiledRule::Glob)
+                    .map_err(|error| {
+                        UninstallerError::Other(format!("无效的通配符 {}: {}", pattern, error))
+                    })
+            }
+            IgnoreRuleKind::Regex => Regex::new(&format!("(?i){}", pattern))
+                .map(CompiledRule::Regex)
+                .map_err(|error| {
+                    UninstallerError::Other(format!("无效的正则表达式 {}: {}", pattern, error))
+                }),
+        }
+    }
+
+    /// `path` 为 [`utils::path_key`] 形式
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            CompiledRule::Prefix(prefix) => path
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\')),
+            CompiledRule::Glob(pattern) => pattern.matches_with(
+                &path.replace('\\', "/"),
+                glob::MatchOptions {
+                    case_sensitive: false,
+                    require_literal_separator: true,
+                    require_literal_leading_dot: false,
+                },
+            ),
+            CompiledRule::Regex(regex) => regex.is_match(path),
+        }
+    }
+}
+
+/// 扫描时使用的规则集，读取一次后对全部痕迹匹配
+#[derive(Default)]
+pub struct IgnoreRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl IgnoreRules {
+    /// 读取并编译全部规则，读取失败或单条规则无效时记录警告并跳过
+    pub fn load() -> Self {
+        let rules = list_rules().unwrap_or_else(|e| {
+            tracing::warn!("读取忽略规则失败: {}", e);
+            Vec::new()
+        });
+        IgnoreRules {
+            rules: rules
+                .iter()
+                .filter_map(
+                    |rule| match CompiledRule::compile(rule.kind, &rule.pattern) {
+                        Ok(compiled) => Some(compiled),
+                        Err(e) => {
+                            tracing::warn!("跳过无效的忽略规则 #{}: {}", rule.id, e);
+                            None
+                        }
+                    },
+                )
+                .collect(),
+        }
+    }
+
+    /// 路径是否被任一规则忽略
+    pub fn is_ignored(&self, path: &str) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let path = utils::path_key(path);
+        self.rules.iter().any(|rule| rule.matches(&path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(rules: &[(IgnoreRuleKind, &str)]) -> IgnoreRules {
+        IgnoreRules {
+            rules: rules
+                .iter()
+                .map(|(kind, pattern)| CompiledRule::compile(*kind, pattern).unwrap())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn matches_prefix_glob_and_regex_rules() {
+        let ignore = rules(&[
+            (IgnoreRuleKind::Prefix, r"HKCU\Software\Microsoft\Office"),
+            (IgnoreRuleKind::Glob, r"C:\Users\*\AppData\Local\Temp\**"),
+            (IgnoreRuleKind::Regex, r"\\package cache\\\{[0-9a-f-]+\}$"),
+        ]);
+
+        assert!(ignore.is_ignored(r"HKCU\SOFTWARE\Microsoft\Office\16.0"));
+        assert!(!ignore.is_ignored(r"HKCU\Software\Microsoft\OfficeTools"));
+        assert!(ignore.is_ignored(r"C:\Users\alice\AppData\Local\Temp\acme"));
+        assert!(!ignore.is_ignored(r"C:\Users\alice\AppData\Local\Acme"));
+        assert!(ignore.is_ignored(r"C:\ProgramData\Package Cache\{1234-abcd}"));
+        assert!(!IgnoreRules::default().is_ignored(r"C:\anything"));
+
+        assert!(CompiledRule::compile(IgnoreRuleKind::Regex, "(").is_err());
+        assert!("GLOB".parse::<IgnoreRuleKind>().is_ok());
+        assert!("wildcard".parse::<IgnoreRuleKind>().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! 安装程序技术识别
+//!
+//! 先按卸载命令和卸载项名称判断，无法判断时读取卸载程序文件头部，查找各打包工具留下的特征字符串。
+
+use std::io::Read;
+use std::path::Path;
+
+use super::models::{InstallSource, InstalledProgram, InstallerKind};
+use crate::modules::uninstaller::{clickonce, validation};
+
+/// 读取卸载程序头部的字节数，各打包工具的清单资源都位于文件前部
+const SNIFF_BYTES: u64 = 256 * 1024;
+
+/// 卸载程序中的特征字符串
+const BINARY_MARKERS: &[(&[u8], InstallerKind)] = &[
+    (b"Nullsoft", InstallerKind::Nsis),
+    (b"Inno Setup", InstallerKind::InnoSetup),
+    (b"InstallShield", InstallerKind::InstallShield),
+    (b"WixBundle", InstallerKind::WixBurn),
+    (
+        b"WixStandardBootstrapperApplication",
+        InstallerKind::WixBurn,
+    ),
+    (b"Squirrel", InstallerKind::Squirrel),
+];
+
+/// 识别程序使用的安装程序技术
+pub fn detect_installer_kind(program: &InstalledProgram) -> InstallerKind {
+    match program.install_source {
+        InstallSource::Store => return InstallerKind::Appx,
+        InstallSource::Msi => return InstallerKind::Msi,
+        _ => {}
+    }
+
+    let Some(uninstall_string) = program.uninstall_string.as_deref() else {
+        return InstallerKind::Unknown;
+    };
+
+    let kind = kind_from_command(uninstall_string, program.registry_key.as_deref());
+    if kind != InstallerKind::Unknown {
+        return kind;
+    }
+
+    validation::parse_executable(uninstall_string)
+        .and_then(|executable| sniff_executable(&executable))
+        .unwrap_or(InstallerKind::Unknown)
+}
+
+/// 按卸载命令和卸载项名称判断
+fn kind_from_command(uninstall_string: &str, registry_key: Option<&str>) -> InstallerKind {
+    let command = uninstall_string.to_lowercase();
+    let executable = validation::parse_executable(uninstall_string)
+        .and_then(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+        })
+        .unwrap_or_default();
+    let key_name = registry_key
+        .and_then(|key| key.rsplit('\\').next())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if executable == "msiexec.exe" || executable == "msiexec" {
+        InstallerKind::Msi
+    } else if executable == "rundll32.exe" && clickonce::is_clickonce_command(uninstall_string) {
+        InstallerKind::ClickOnce
+    } else if executable == "update.exe" && command.contains("--uninstall") {
+        InstallerKind::Squirrel
+    } else if key_name.ends_with("_is1") || is_inno_uninstaller(&executable) {
+        InstallerKind::InnoSetup
+    } else if command.contains(r"\package cache\") {
+        InstallerKind::WixBurn
+    } else if command.contains("installshield installation information")
+        || command.contains("-runfromtemp")
+    {
+        InstallerKind::InstallShield
+    } else {
+        InstallerKind::Unknown
+    }
+}
+
+/// Inno Setup 的卸载程序固定命名为 `unins000.exe`、`unins001.exe` 等
+fn is_inno_uninstaller(file_name: &str) -> bool {
+    file_name
+        .strip_prefix("unins")
+        .and_then(|rest| rest.strip_suffix(".exe"))
+        .is_some_and(|digits| digits.len() == 3 && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// 读取卸载程序头部查找特征字符串
+fn sniff_executable(executable: &Path) -> Option<InstallerKind> {
+    let file = std::fs::File::open(executable).ok()?;
+    let mut buffer = Vec::new();
+    file.take(SNIFF_BYTES).read_to_end(&mut buffer).ok()?;
+
+    BINARY_MARKERS
+        .iter()
+        .find(|(marker, _)| buffer.windows(marker.len()).any(|window| window == *marker))
+        .map(|(_, kind)| *kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_kind_from_uninstall_command() {
+        assert_eq!(
+            kind_from_command("MsiExec.exe /X{12345678-1234-1234-1234-123456789012}", None),
+            InstallerKind::Msi
+        );
+        assert_eq!(
+            kind_from_command(
+                r#""C:\Users\a\AppData\Local\Slack\Update.exe" --uninstall -s"#,
+                None
+            ),
+            InstallerKind::Squirrel
+        );
+        assert_eq!(
+            kind_from_command(
+                "rundll32.exe dfshim.dll,ShArpMaintain Foo.application, Culture=neutral",
+                None
+            ),
+            InstallerKind::ClickOnce
+        );
+        assert_eq!(
+            kind_from_command(r#""C:\Program Files\Foo\unins000.exe""#, None),
+            InstallerKind::InnoSetup
+        );
+        assert_eq!(
+            kind_from_command(
+                r#""C:\Program Files\Foo\uninstall.exe""#,
+                Some(r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\Foo_is1")
+            ),
+            InstallerKind::InnoSetup
+        );
+        assert_eq!(
+            kind_from_command(
+                r#""C:\ProgramData\Package Cache\{abc}\bundle.exe" /uninstall"#,
+                None
+            ),
+            InstallerKind::WixBurn
+        );
+        assert_eq!(
+            kind_from_command(r#""C:\Program Files\Foo\uninst.exe""#, None),
+            InstallerKind::Unknown
+        );
+    }
+}
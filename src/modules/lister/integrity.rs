@@ -0,0 +1,460 @@
+//! 存储完整性检查与修复
+//!
+//! 检查扫描缓存库与用户数据库是否损坏、缓存和程序记录中无法解析的行、
+//! 指向已删除图标文件的缓存记录、没有被引用的图标文件、安装历史文件，以及报告目录中
+//! 缺少 JSON 或 HTML 的报告。扫描缓存可以随时重建，损坏时直接删除；
+//! 用户数据库和历史文件包含无法重建的数据，损坏时改名备份后再由程序重新创建。
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::models::{InstalledProgram, ProgramHistoryEntry};
+use super::pool;
+use super::saved_programs;
+use super::storage::{
+    self, map_sqlite_error, CACHE_TABLE_NAME, HISTORY_FILE_NAME, SQLITE_SIDECAR_SUFFIXES,
+    USER_DATA_DB_FILE_NAME,
+};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::reporter::{self, models::UninstallerReport};
+
+/// 存储组成部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageComponent {
+    ScanCache,
+    UserData,
+    History,
+    IconCache,
+    Reports,
+}
+
+impl std::fmt::Display for StorageComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageComponent::ScanCache => write!(f, "扫描缓存"),
+            StorageComponent::UserData => write!(f, "用户数据"),
+            StorageComponent::History => write!(f, "安装历史"),
+            StorageComponent::IconCache => write!(f, "图标缓存"),
+            StorageComponent::Reports => write!(f, "报告"),
+        }
+    }
+}
+
+/// 发现的问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub component: StorageComponent,
+    pub description: String,
+    /// 是否已修复
+    pub repaired: bool,
+}
+
+/// 完整性检查结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub issues: Vec<IntegrityIssue>,
+    /// 是否执行了修复
+    pub repair: bool,
+}
+
+impl IntegrityReport {
+    /// 没有发现问题
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// 仍未修复的问题数
+    pub fn unresolved(&self) -> usize {
+        self.issues.iter().filter(|issue| !issue.repaired).count()
+    }
+
+    fn push(&mut self, component: StorageComponent, description: String, repaired: bool) {
+        self.issues.push(IntegrityIssue {
+            component,
+            description,
+            repaired,
+        });
+    }
+}
+
+/// 检查全部存储，`repair` 时修复发现的问题
+pub fn verify_storage(repair: bool) -> Result<IntegrityReport, UninstallerError> {
+    let mut report = IntegrityReport {
+        issues: Vec::new(),
+        repair,
+    };
+    let root = storage::get_storage_root_dir()?;
+
+    let cache_path = storage::get_scan_cache_database_path()?;
+    let referenced_icons = if check_database(&cache_path, StorageComponent::ScanCache, &mut report)
+    {
+        verify_cache_rows(&cache_path, repair, &mut report)?
+    } else {
+        None
+    };
+
+    let user_data_path = root.join(USER_DATA_DB_FILE_NAME);
+    if check_database(&user_data_path, StorageComponent::UserData, &mut report) {
+        let invalid = saved_programs::verify_snapshots(repair)?;
+        if invalid > 0 {
+            report.push(
+                StorageComponent::UserData,
+                format!("{} 条卸载前程序记录无法解析", invalid),
+                repair,
+            );
+        }
+    }
+
+    verify_history(&root.join(HISTORY_FILE_NAME), repair, &mut report);
+    verify_icon_cache(
+        &storage::get_icon_cache_dir()?,
+        referenced_icons.as_ref(),
+        repair,
+        &mut report,
+    );
+    verify_reports(&reporter::storage::reports_dir(), repair, &mut report);
+
+    Ok(report)
+}
+
+/// 运行 SQLite 完整性检查，数据库不存在视为正常；损坏时按 `report.repair` 删除或备份
+fn check_database(path: &Path, component: StorageComponent, report: &mut IntegrityReport) -> bool {
+    if !path.exists() {
+        return true;
+    }
+
+    let result = Connection::open(path).and_then(|connection| {
+        connection.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0))
+    });
+    let problem = match result {
+        Ok(status) if status.eq_ignore_ascii_case("ok") => return true,
+        Ok(status) => status,
+        Err(error) => error.to_string(),
+    };
+
+    let repaired = report.repair
+        && match component {
+            // 缓存可以重新扫描生成
+            StorageComponent::ScanCache => storage::invalidate_scan_cache().is_ok(),
+            _ => back_up_database(path).is_ok(),
+        };
+    let action = match (component, repaired) {
+        (StorageComponent::ScanCache, true) => "，已删除，下次列出程序时重建",
+        (_, true) => "，已改名备份，下次使用时重新创建",
+        _ => "",
+    };
+    report.push(
+        component,
+        format!("数据库损坏 ({}): {}{}", path.display(), problem, action),
+        repaired,
+    );
+    false
+}
+
+/// 将损坏的数据库及其 WAL 附属文件改名为 `.corrupt-<时间>`
+fn back_up_database(path: &Path) -> Result<(), UninstallerError> {
+    pool::close_idle(path);
+    let suffix = format!(".corrupt-{}", Utc::now().format("%Y%m%d%H%M%S"));
+    for file in std::iter::once(path.to_path_buf()).chain(
+        SQLITE_SIDECAR_SUFFIXES
+            .iter()
+            .map(|sidecar| storage::sidecar_path(path, sidecar)),
+    ) {
+        if file.exists() {
+            std::fs::rename(&file, storage::sidecar_path(&file, &suffix))?;
+        }
+    }
+    Ok(())
+}
+
+/// 检查缓存行：无法解析的行删除，指向已删除图标文件的路径清空
+///
+/// 返回缓存引用的图标文件（小写路径），缓存不存在时返回 None
+fn verify_cache_rows(
+    path: &Path,
+    repair: bool,
+    report: &mut IntegrityReport,
+) -> Result<Option<HashSet<String>>, UninstallerError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let connection =
+        Connection::open(path).map_err(|error| map_sqlite_error("打开缓存数据库失败", error))?;
+    let rows: Vec<(String, String)> = {
+        let mut statement = connection
+            .prepare(&format!(
+                "SELECT cache_key, payload_json FROM {}",
+                CACHE_TABLE_NAME
+            ))
+            .map_err(|error| map_sqlite_error("准备读取缓存列表失败", error))?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|error| map_sqlite_error("读取缓存列表失败", error))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| map_sqlite_error("读取缓存行失败", error))?;
+        rows
+    };
+
+    let _lock = if repair {
+        Some(pool::lock_for_write(path)?)
+    } else {
+        None
+    };
+    let mut referenced = HashSet::new();
+    let mut invalid = 0;
+    for (cache_key, payload) in rows {
+        let Ok(mut program) = serde_json::from_str::<InstalledProgram>(&payload) else {
+            invalid += 1;
+            if repair {
+                connection
+                    .execute(
+                        &format!("DELETE FROM {} WHERE cache_key = ?1", CACHE_TABLE_NAME),
+                        params![cache_key],
+                    )
+                    .map_err(|error| map_sqlite_error("删除缓存行失败", error))?;
+            }
+            continue;
+        };
+
+        let mut dangling = Vec::new();
+        for icon in [
+            &mut program.icon_cache_path_32,
+            &mut program.icon_cache_path_48,
+        ] {
+            match icon.as_deref() {
+                Some(icon_path) if Path::new(icon_path).is_file() => {
+                    referenced.insert(icon_path.to_lowercase());
+                }
+                Some(icon_path) => {
+                    dangling.push(icon_path.to_string());
+                    *icon = None;
+                }
+                None => {}
+            }
+        }
+        if dangling.is_empty() {
+            continue;
+        }
+
+        if repair {
+            let payload = serde_json::to_string(&program)
+                .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+            connection
+                .execute(
+                    &format!(
+                        "UPDATE {} SET icon_cache_path_32 = ?2, icon_cache_path_48 = ?3,
+                         payload_json = ?4 WHERE cache_key = ?1",
+                        CACHE_TABLE_NAME
+                    ),
+                    params![
+                        cache_key,
+                        program.icon_cache_path_32,
+                        program.icon_cache_path_48,
+                        payload
+                    ],
+                )
+                .map_err(|error| map_sqlite_error("更新缓存行失败", error))?;
+        }
+        report.push(
+            StorageComponent::ScanCache,
+            format!(
+                "{} 引用的图标文件不存在: {}",
+                program.name,
+                dangling.join(", ")
+            ),
+            repair,
+        );
+    }
+
+    if invalid > 0 {
+        report.push(
+            StorageComponent::ScanCache,
+            format!("{} 条缓存记录无法解析", invalid),
+            repair,
+        );
+    }
+    Ok(Some(referenced))
+}
+
+/// 安装历史文件无法解析时改名备份，下次枚举程序时重新生成
+fn verify_history(path: &Path, repair: bool, report: &mut IntegrityReport) {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return;
+    };
+    if serde_json::from_str::<Vec<ProgramHistoryEntry>>(&content).is_ok() {
+        return;
+    }
+
+    let backup = storage::sidecar_path(
+        path,
+        &format!(".corrupt-{}", Utc::now().format("%Y%m%d%H%M%S")),
+    );
+    let repaired = repair && std::fs::rename(path, &backup).is_ok();
+    report.push(
+        StorageComponent::History,
+        if repaired {
+            format!("安装历史无法解析，已备份到 {}", backup.display())
+        } else {
+            format!("安装历史无法解析: {}", path.display())
+        },
+        repaired,
+    );
+}
+
+/// 删除空图标文件；缓存完好时同时删除没有被任何缓存记录引用的图标
+fn verify_icon_cache(
+    dir: &Path,
+    referenced: Option<&HashSet<String>>,
+    repair: bool,
+    report: &mut IntegrityReport,
+) {
+    let mut empty = Vec::new();
+    let mut orphaned = Vec::new();
+    for file in icon_files(dir) {
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if size == 0 {
+            empty.push(file);
+        } else if referenced
+            .is_some_and(|referenced| !referenced.contains(&file.to_string_lossy().to_lowercase()))
+        {
+            orphaned.push(file);
+        }
+    }
+
+    for (files, label) in [(empty, "空图标文件"), (orphaned, "未被引用的图标文件")] {
+        if files.is_empty() {
+            continue;
+        }
+        let removed = if repair {
+            files
+                .iter()
+                .filter(|file| std::fs::remove_file(file).is_ok())
+                .count()
+        } else {
+            0
+        };
+        report.push(
+            StorageComponent::IconCache,
+            format!("{} {} 个", label, files.len()),
+            repair && removed == files.len(),
+        );
+    }
+}
+
+/// 图标缓存目录下按尺寸分目录保存的文件
+fn icon_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|size_dir| std::fs::read_dir(size_dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// 报告的 JSON 无法解析时删除整份报告，缺少 HTML 时重新生成，只有 HTML 时删除
+fn verify_reports(dir: &Path, repair: bool, report: &mut IntegrityReport) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        let html_path = path.with_extension("html");
+        match extension.as_deref() {
+            Some("json") => {
+                let parsed = std::fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<UninstallerReport>(&content).ok());
+                match parsed {
+                    None => {
+                        let repaired = repair
+                            && std::fs::remove_file(&path).is_ok()
+                            && (!html_path.exists() || std::fs::remove_file(&html_path).is_ok());
+                        report.push(
+                            StorageComponent::Reports,
+                            format!("报告无法解析: {}", path.display()),
+                            repaired,
+                        );
+                    }
+                    Some(parsed) if !html_path.exists() => {
+                        let repaired = repair
+                            && reporter::html::generate_html_report(&parsed)
+                                .ok()
+                                .is_some_and(|html| std::fs::write(&html_path, html).is_ok());
+                        report.push(
+                            StorageComponent::Reports,
+                            format!("报告缺少 HTML 文件: {}", parsed.id),
+                            repaired,
+                        );
+                    }
+                    Some(_) => {}
+                }
+            }
+            Some("html") if !path.with_extension("json").exists() => {
+                let repaired = repair && std::fs::remove_file(&path).is_ok();
+                report.push(
+                    StorageComponent::Reports,
+                    format!("HTML 报告缺少数据文件: {}", path.display()),
+                    repaired,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn finds_and_repairs_dangling_references() {
+        let _guard = storage::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let root = std::env::temp_dir().join(format!("rust-yu-integrity-{}", uuid::Uuid::new_v4()));
+        let reports_dir = root.join("reports");
+        std::fs::create_dir_all(&reports_dir).unwrap();
+        std::env::set_var(storage::STORAGE_DIR_ENV, &root);
+        std::env::set_var(reporter::storage::REPORTS_DIR_ENV, &reports_dir);
+
+        let icon_dir = storage::get_icon_cache_dir().unwrap().join("32");
+        std::fs::create_dir_all(&icon_dir).unwrap();
+        std::fs::write(icon_dir.join("orphan.png"), b"png").unwrap();
+        let mut program = InstalledProgram::new("Demo".to_string(), InstallSource::Registry);
+        program.icon_cache_path_32 = Some(icon_dir.join("missing.png").to_string_lossy().into());
+        storage::save_scan_cache(&[program]).unwrap();
+        std::fs::write(root.join(HISTORY_FILE_NAME), "{ not json").unwrap();
+        std::fs::write(reports_dir.join("lost.html"), "<html></html>").unwrap();
+
+        let found = verify_storage(false).unwrap();
+        let components: HashSet<_> = found.issues.iter().map(|issue| issue.component).collect();
+        assert!(components.contains(&StorageComponent::ScanCache));
+        assert!(components.contains(&StorageComponent::History));
+        assert!(components.contains(&StorageComponent::IconCache));
+        assert!(components.contains(&StorageComponent::Reports));
+        assert_eq!(found.unresolved(), found.issues.len());
+
+        let repaired = verify_storage(true).unwrap();
+        assert_eq!(repaired.unresolved(), 0);
+        assert!(verify_storage(false).unwrap().is_healthy());
+
+        std::env::remove_var(storage::STORAGE_DIR_ENV);
+        std::env::remove_var(reporter::storage::REPORTS_DIR_ENV);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
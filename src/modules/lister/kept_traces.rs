@@ -0,0 +1,150 @@
+//! 保留的痕迹
+//!
+//! 用户可以把某个程序的特定路径或注册表项标记为有意保留（例如编辑器的个人设置），
+//! 按程序名保存在用户数据库中。程序卸载后缓存键不再可用，因此以小写程序名为键。
+//! 之后扫描该程序时，保留的路径及其下的全部内容都不再报告，"清理全部"也就不会删除。
+
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::pool::PooledConnection;
+use super::storage::{self, map_sqlite_error};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+
+const KEPT_TRACES_TABLE_NAME: &str = "kept_traces";
+
+/// 用户标记保留的痕迹
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeptTrace {
+    pub program_name: String,
+    pub path: String,
+    pub kept_at: String,
+}
+
+fn open_kept_traces_connection() -> Result<PooledConnection, UninstallerError> {
+    let connection = storage::open_user_data_connection()?;
+
+    connection
+        .execute_batch(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                program_key TEXT NOT NULL,
+                program_name TEXT NOT NULL,
+                path_key TEXT NOT NULL,
+                path TEXT NOT NULL,
+                kept_at TEXT NOT NULL,
+                PRIMARY KEY (program_key, path_key)
+            );
+            "#,
+            table = KEPT_TRACES_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("初始化保留痕迹表失败", error))?;
+
+    Ok(connection)
+}
+
+/// 标记或取消标记保留的痕迹，返回状态是否发生变化
+pub fn set_kept(program_name: &str, path: &str, kept: bool) -> Result<bool, UninstallerError> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(UninstallerError::Other("保留的路径不能为空".to_string()));
+    }
+
+    let connection = open_kept_traces_connection()?;
+    let program_key = program_name.trim().to_lowercase();
+    let changed = if kept {
+        connection
+            .execute(
+                &format!(
+                    "INSERT OR IGNORE INTO {} (program_key, program_name, path_key, path, kept_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    KEPT_TRACES_TABLE_NAME
+                ),
+                params![
+                    program_key,
+                    program_name.trim(),
+                    utils::path_key(path),
+                    path,
+                    Utc::now().to_rfc3339()
+                ],
+            )
+            .map_err(|error| map_sqlite_error("写入保留痕迹失败", error))?
+    } else {
+        connection
+            .execute(
+                &format!(
+                    "DELETE FROM {} WHERE program_key = ?1 AND path_key = ?2",
+                    KEPT_TRACES_TABLE_NAME
+                ),
+                params![program_key, utils::path_key(path)],
+            )
+            .map_err(|error| map_sqlite_error("删除保留痕迹失败", error))?
+    };
+    Ok(changed > 0)
+}
+
+/// 列出保留的痕迹，`program_name` 为 None 时列出全部程序的
+pub fn list_kept(program_name: Option<&str>) -> Result<Vec<KeptTrace>, UninstallerError> {
+    let connection = open_kept_traces_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT program_name, path, kept_at FROM {}
+             WHERE ?1 IS NULL OR program_key = ?1
+             ORDER BY program_key, path_key",
+            KEPT_TRACES_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取保留痕迹失败", error))?;
+
+    let program_key = program_name.map(|name| name.trim().to_lowercase());
+    let kept = statement
+        .query_map(params![program_key], |row| {
+            Ok(KeptTrace {
+                program_name: row.get(0)?,
+                path: row.get(1)?,
+                kept_at: row.get(2)?,
+            })
+        })
+        .map_err(|error| map_sqlite_error("读取保留痕迹失败", error))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| map_sqlite_error("解析保留痕迹失败", error))?;
+    Ok(kept)
+}
+
+/// 程序的保留路径，已转换为 [`is_kept`] 使用的比较形式
+pub fn kept_paths(program_name: &str) -> Result<Vec<String>, UninstallerError> {
+    Ok(list_kept(Some(program_name))?
+        .iter()
+        .map(|kept| utils::path_key(&kept.path))
+        .collect())
+}
+
+/// 路径是否为保留路径本身或位于其下
+pub fn is_kept(path: &str, kept_paths: &[String]) -> bool {
+    let path = utils::path_key(path);
+    kept_paths.iter().any(|kept| {
+        path.strip_prefix(kept.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kept_directory_covers_its_contents() {
+        let kept = vec![utils::path_key(r"C:\Users\me\AppData\Roaming\Code\User\")];
+        assert!(is_kept(r"c:/users/me/appdata/roaming/code/user", &kept));
+        assert!(is_kept(
+            r"C:\Users\me\AppData\Roaming\Code\User\settings.json",
+            &kept
+        ));
+        assert!(!is_kept(
+            r"C:\Users\me\AppData\Roaming\Code\UserData",
+            &kept
+        ));
+        assert!(!is_kept(r"C:\Users\me\AppData\Roaming\Code", &kept));
+    }
+}
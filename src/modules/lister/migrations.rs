@@ -0,0 +1,212 @@
+//! 缓存数据库结构迁移
+//!
+//! 数据库结构版本记录在 `PRAGMA user_version` 中，打开缓存库时按顺序执行尚未应用的迁移，
+//! 在原库上升级而不是更换文件名。程序记录（`payload_json`）的版本仍由缓存元数据中的
+//! `schema_version` 表示，旧版本记录按新结构重新序列化即可补齐默认字段。
+
+use rusqlite::{params, Connection, Transaction};
+
+use super::models::InstalledProgram;
+use super::storage::map_sqlite_error;
+use crate::modules::common::error::UninstallerError;
+
+/// 单个结构迁移
+struct Migration {
+    /// 执行后数据库所处的版本
+    version: u32,
+    description: &'static str,
+    apply: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// 按版本升序排列，只能追加，不能修改已发布的迁移
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "创建缓存表和元数据表",
+        apply: create_base_tables,
+    },
+    Migration {
+        version: 2,
+        description: "增加安装程序技术和安装范围列",
+        apply: add_installer_columns,
+    },
+];
+
+/// 当前代码对应的数据库结构版本
+pub const DATABASE_VERSION: u32 = 2;
+
+/// 执行尚未应用的迁移，返回迁移后的数据库版本
+///
+/// 数据库版本高于当前代码（由新版本程序创建）时不做任何修改
+pub fn migrate(connection: &mut Connection) -> Result<u32, UninstallerError> {
+    let current: u32 = connection
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|error| map_sqlite_error("读取缓存数据库版本失败", error))?;
+    if current > DATABASE_VERSION {
+        tracing::warn!(
+            "缓存数据库版本 {} 高于当前支持的版本 {}，跳过迁移",
+            current,
+            DATABASE_VERSION
+        );
+    }
+
+    let mut version = current;
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > current)
+    {
+        let transaction = connection
+            .transaction()
+            .map_err(|error| map_sqlite_error("开启迁移事务失败", error))?;
+        (migration.apply)(&transaction).map_err(|error| {
+            map_sqlite_error(&format!("迁移失败 ({})", migration.description), error)
+        })?;
+        transaction
+            .pragma_update(None, "user_version", migration.version)
+            .map_err(|error| map_sqlite_error("更新缓存数据库版本失败", error))?;
+        transaction
+            .commit()
+            .map_err(|error| map_sqlite_error("提交迁移事务失败", error))?;
+
+        tracing::info!(
+            "缓存数据库已迁移到版本 {}: {}",
+            migration.version,
+            migration.description
+        );
+        version = migration.version;
+    }
+
+    Ok(version)
+}
+
+/// 按当前结构重新序列化程序记录，补齐旧版本缺少的字段，返回升级的记录数
+pub fn upgrade_payloads(connection: &mut Connection) -> Result<usize, UninstallerError> {
+    let transaction = connection
+        .transaction()
+        .map_err(|error| map_sqlite_error("开启缓存升级事务失败", error))?;
+
+    let rows: Vec<(String, String)> = {
+        let mut statement = transaction
+            .prepare("SELECT cache_key, payload_json FROM installed_programs_cache")
+            .map_err(|error| map_sqlite_error("准备读取缓存记录失败", error))?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|error| map_sqlite_error("读取缓存记录失败", error))?
+            .collect::<Result<_, _>>()
+            .map_err(|error| map_sqlite_error("读取缓存记录失败", error))?;
+        rows
+    };
+
+    let mut upgraded = 0;
+    for (cache_key, payload_json) in rows {
+        // 无法解析的记录直接删除，下次刷新时重新写入
+        let Ok(program) = serde_json::from_str::<InstalledProgram>(&payload_json) else {
+            transaction
+                .execute(
+                    "DELETE FROM installed_programs_cache WHERE cache_key = ?1",
+                    params![cache_key],
+                )
+                .map_err(|error| map_sqlite_error("删除无效缓存记录失败", error))?;
+            continue;
+        };
+        let payload_json = serde_json::to_string(&program)
+            .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+        transaction
+            .execute(
+                "UPDATE installed_programs_cache
+                 SET payload_json = ?2,
+                     installer_kind = json_extract(?2, '$.installer_kind'),
+                     install_scope = json_extract(?2, '$.install_scope')
+                 WHERE cache_key = ?1",
+                params![cache_key, payload_json],
+            )
+            .map_err(|error| map_sqlite_error("升级缓存记录失败", error))?;
+        upgraded += 1;
+    }
+
+    transaction
+        .commit()
+        .map_err(|error| map_sqlite_error("提交缓存升级事务失败", error))?;
+    Ok(upgraded)
+}
+
+fn create_base_tables(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS installed_programs_cache (
+            cache_key TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            publisher TEXT,
+            version TEXT,
+            install_date TEXT,
+            install_location TEXT,
+            uninstall_string TEXT,
+            install_source TEXT NOT NULL,
+            size_bytes INTEGER,
+            estimated_size_bytes INTEGER,
+            icon_path TEXT,
+            icon_cache_path_32 TEXT,
+            icon_cache_path_48 TEXT,
+            size_last_updated_at TEXT,
+            payload_json TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_installed_programs_cache_name
+            ON installed_programs_cache(name);
+        CREATE TABLE IF NOT EXISTS cache_metadata (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        "#,
+    )
+}
+
+/// 新列从已有记录的 JSON 中回填，旧记录没有这两个字段时保持为空
+fn add_installer_columns(transaction: &Transaction) -> rusqlite::Result<()> {
+    transaction.execute_batch(
+        r#"
+        ALTER TABLE installed_programs_cache ADD COLUMN installer_kind TEXT;
+        ALTER TABLE installed_programs_cache ADD COLUMN install_scope TEXT;
+        UPDATE installed_programs_cache
+        SET installer_kind = json_extract(payload_json, '$.installer_kind'),
+            install_scope = json_extract(payload_json, '$.install_scope')
+        WHERE json_valid(payload_json);
+        "#,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_legacy_database_in_place() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        // 迁移框架引入前的缓存库：已有表结构，但 user_version 为 0
+        let transaction = connection.transaction().unwrap();
+        create_base_tables(&transaction).unwrap();
+        transaction.commit().unwrap();
+        connection
+            .execute(
+                "INSERT INTO installed_programs_cache
+                 (cache_key, name, install_source, payload_json, updated_at)
+                 VALUES ('k', 'Demo', 'Registry', ?1, '')",
+                params![r#"{"installer_kind":"nsis"}"#],
+            )
+            .unwrap();
+
+        assert_eq!(migrate(&mut connection).unwrap(), DATABASE_VERSION);
+        let kind: String = connection
+            .query_row(
+                "SELECT installer_kind FROM installed_programs_cache",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(kind, "nsis");
+
+        // 再次执行不会重复迁移
+        assert_eq!(migrate(&mut connection).unwrap(), DATABASE_VERSION);
+    }
+}
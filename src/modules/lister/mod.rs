@@ -1,26 +1,87 @@
+pub mod aliases;
+pub mod bloatware;
+pub mod dir_size;
 pub mod enrichment;
+pub mod features;
+pub mod health;
+pub mod ignore_rules;
+pub mod installer;
+pub mod integrity;
+pub mod kept_traces;
+pub mod migrations;
 pub mod models;
 pub mod msi;
+pub mod notes;
+pub mod pins;
+pub mod placeholder_icon;
+pub mod pool;
+pub mod processes;
 pub mod registry;
+pub mod saved_programs;
+pub mod snapshot;
+pub mod startup;
 pub mod storage;
+pub mod storage_usage;
 pub mod store;
+pub mod suites;
+pub mod tags;
+pub mod trace_feedback;
+pub mod trust;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
 
 use chrono::Utc;
 
 use crate::modules::common::error::UninstallerError;
+use crate::modules::common::priority::{self, PriorityProfile};
+use crate::modules::common::profiling::{self, StageTiming};
+use crate::modules::common::progress;
 use crate::modules::common::utils;
+use dir_size::ScanBudget;
 use models::{
     InstallSource, InstalledProgram, ListProgramsQuery, ProgramListCacheState, ProgramListResponse,
 };
 
+/// 后台刷新完成时的回调
+type CacheRefreshListener = Box<dyn Fn(ProgramListCacheState) + Send + Sync>;
+
+static BACKGROUND_REFRESH_RUNNING: AtomicBool = AtomicBool::new(false);
+static CACHE_REFRESH_LISTENER: OnceLock<CacheRefreshListener> = OnceLock::new();
+static IDLE_SIZE_SCAN_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// 空闲时继续计算目录大小的间隔
+const IDLE_SIZE_SCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 每轮空闲计算的总耗时上限
+const IDLE_SIZE_SCAN_PASS: Duration = Duration::from_secs(10);
+
+/// 注册后台刷新完成监听器（只能注册一次，重复注册会被忽略）
+#[allow(dead_code)]
+pub fn set_cache_refresh_listener<F>(listener: F)
+where
+    F: Fn(ProgramListCacheState) + Send + Sync + 'static,
+{
+    if CACHE_REFRESH_LISTENER.set(Box::new(listener)).is_err() {
+        tracing::debug!("缓存刷新监听器已注册，忽略重复注册");
+    }
+}
+
+/// 后台刷新是否正在进行
+pub fn is_background_refresh_running() -> bool {
+    BACKGROUND_REFRESH_RUNNING.load(Ordering::SeqCst)
+}
+
 /// 列出所有已安装程序（兼容旧接口）
 pub fn list_all_programs(
     source: Option<InstallSource>,
     search: Option<&str>,
 ) -> Result<Vec<InstalledProgram>, UninstallerError> {
-    let mut all_programs = collect_programs(source);
+    let mut all_programs = collect_programs(source, &mut Vec::new());
     enrichment::enrich_programs(&mut all_programs);
     dedupe_and_sort(&mut all_programs);
+    processes::enrich_running(&mut all_programs);
     apply_search_filter(&mut all_programs, search);
     Ok(all_programs)
 }
@@ -39,31 +100,55 @@ pub fn list_programs_with_cache(
         ..ProgramListCacheState::default()
     };
 
+    let mut timings = Vec::new();
+
     if cache_eligible && !query.refresh {
-        let cached = storage::read_scan_cache(query.cache_ttl_seconds)?;
+        let cached = profiling::measure(&mut timings, "cache_read", || {
+            storage::read_scan_cache(query.cache_ttl_seconds)
+        })?;
         cache_state.schema_version = cached.schema_version;
         cache_state.generated_at = cached.generated_at.clone();
         cache_state.reason = cached.reason.clone();
 
         if cached.cache_hit && cached.cache_valid {
             let mut cached_programs = cached.entries.unwrap_or_default();
-            apply_search_filter(&mut cached_programs, query.search.as_deref());
+            apply_query_filters(&mut cached_programs, &query);
             cache_state.cache_hit = true;
             cache_state.cache_valid = true;
             cache_state.refreshed = false;
+            cache_state.refreshing = is_background_refresh_running();
             return Ok(ProgramListResponse {
                 programs: cached_programs,
                 cache: cache_state,
+                timings,
+            });
+        }
+
+        if cached.stale && query.allow_stale {
+            let mut stale_programs = cached.entries.unwrap_or_default();
+            apply_query_filters(&mut stale_programs, &query);
+            spawn_background_refresh(query.source);
+            cache_state.cache_hit = true;
+            cache_state.cache_valid = false;
+            cache_state.refreshed = false;
+            cache_state.stale = true;
+            cache_state.refreshing = is_background_refresh_running();
+            return Ok(ProgramListResponse {
+                programs: stale_programs,
+                cache: cache_state,
+                timings,
             });
         }
     }
 
-    let mut all_programs = collect_programs(query.source);
-    enrichment::enrich_programs(&mut all_programs);
+    let mut all_programs = collect_programs(query.source, &mut timings);
+    enrichment::enrich_programs_profiled(&mut all_programs, &mut timings);
     dedupe_and_sort(&mut all_programs);
 
     if cache_eligible {
-        storage::save_scan_cache(&all_programs)?;
+        profiling::measure(&mut timings, "cache_write", || {
+            storage::save_scan_cache(&all_programs)
+        })?;
         cache_state.cache_hit = false;
         cache_state.cache_valid = true;
         cache_state.refreshed = true;
@@ -81,19 +166,138 @@ pub fn list_programs_with_cache(
         cache_state.reason = Some("source_not_cacheable".to_string());
     }
 
-    apply_search_filter(&mut all_programs, query.search.as_deref());
+    apply_query_filters(&mut all_programs, &query);
 
     Ok(ProgramListResponse {
         programs: all_programs,
         cache: cache_state,
+        timings,
+    })
+}
+
+/// 在后台线程重建缓存，同一时间只允许一个刷新任务
+fn spawn_background_refresh(source: Option<InstallSource>) {
+    if BACKGROUND_REFRESH_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let _priority = priority::enter(PriorityProfile::Background);
+        let mut programs = collect_programs(source, &mut Vec::new());
+        enrichment::enrich_programs(&mut programs);
+        dedupe_and_sort(&mut programs);
+        let result = storage::save_scan_cache(&programs);
+        BACKGROUND_REFRESH_RUNNING.store(false, Ordering::SeqCst);
+
+        match result {
+            Ok(()) => {
+                tracing::info!("后台刷新程序列表缓存完成: {} 个程序", programs.len());
+                if let Some(listener) = CACHE_REFRESH_LISTENER.get() {
+                    listener(ProgramListCacheState {
+                        cache_hit: false,
+                        cache_valid: true,
+                        refreshed: true,
+                        schema_version: storage::CACHE_SCHEMA_VERSION,
+                        generated_at: Some(Utc::now().to_rfc3339()),
+                        reason: Some("background_refresh".to_string()),
+                        ..ProgramListCacheState::default()
+                    });
+                }
+            }
+            Err(error) => tracing::warn!("后台刷新程序列表缓存失败: {}", error),
+        }
+    });
+}
+
+/// 启动空闲时计算目录大小的后台线程（只启动一次）
+///
+/// 列表刷新时只为大目录计算一小段，剩余部分在这里按后台优先级逐轮推进；
+/// 有目录算完时重建程序列表缓存，完成后通过刷新监听器通知前端
+#[allow(dead_code)]
+pub fn start_idle_size_scan() {
+    if IDLE_SIZE_SCAN_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(|| {
+        let _priority = priority::enter(PriorityProfile::Background);
+        loop {
+            std::thread::sleep(IDLE_SIZE_SCAN_INTERVAL);
+            if is_background_refresh_running() || !dir_size::has_pending() {
+                continue;
+            }
+            match dir_size::advance_pending(ScanBudget::IDLE, IDLE_SIZE_SCAN_PASS) {
+                Ok(0) => {}
+                Ok(completed) => {
+                    tracing::info!("空闲时完成 {} 个目录的大小计算", completed);
+                    spawn_background_refresh(None);
+                }
+                Err(error) => tracing::warn!("空闲时计算目录大小失败: {}", error),
+            }
+        }
+    });
+}
+
+/// 按名称查找程序
+///
+/// 优先读取列表缓存（过期数据同样可用），缓存中没有时从注册表定位该程序
+pub fn find_program_by_name(program_name: &str) -> Option<InstalledProgram> {
+    let name_lower = program_name.trim().to_lowercase();
+    let is_target = |program: &InstalledProgram| program.name.to_lowercase() == name_lower;
+
+    let cached = storage::read_scan_cache(storage::DEFAULT_CACHE_TTL_SECONDS)
+        .ok()
+        .and_then(|cached| cached.entries)
+        .unwrap_or_default();
+    if let Some(program) = cached.into_iter().find(|program| is_target(program)) {
+        return Some(program);
+    }
+
+    registry::list_registry_programs()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|program| is_target(program))
+        .map(|mut program| {
+            aliases::enrich_aliases(&mut program);
+            program
+        })
+}
+
+/// 按名称查找程序记录，程序已卸载时使用保存的记录（卸载前快照、安装历史、扫描缓存）
+pub fn find_program_record(program_name: &str) -> Option<InstalledProgram> {
+    find_program_by_name(program_name).or_else(|| match storage::get_saved_program(program_name) {
+        Ok(program) => program,
+        Err(error) => {
+            tracing::warn!("读取保存的程序记录失败: {}", error);
+            None
+        }
     })
 }
 
+/// 按名称查找程序并填充标签、备注，用于详情展示
+pub fn get_program_detail(program_name: &str) -> Option<InstalledProgram> {
+    let mut program = find_program_by_name(program_name)?;
+    attach_user_data(std::slice::from_mut(&mut program));
+    processes::enrich_running(std::slice::from_mut(&mut program));
+    Some(program)
+}
+
+/// 查找程序的别名，缓存中没有时现场识别
+pub fn find_program_aliases(program_name: &str) -> Vec<String> {
+    find_program_by_name(program_name)
+        .map(|program| program.aliases)
+        .unwrap_or_default()
+}
+
 fn is_cache_eligible(source: Option<InstallSource>) -> bool {
     matches!(source, None | Some(InstallSource::Registry))
 }
 
-fn collect_programs(source: Option<InstallSource>) -> Vec<InstalledProgram> {
+/// 收集各来源的程序，并记录每个来源的枚举耗时
+pub fn collect_programs(
+    source: Option<InstallSource>,
+    timings: &mut Vec<StageTiming>,
+) -> Vec<InstalledProgram> {
     let mut all_programs = Vec::new();
 
     // None 默认仅使用 Registry，避免 MSI 调用带来的额外开销
@@ -103,18 +307,44 @@ fn collect_programs(source: Option<InstallSource>) -> Vec<InstalledProgram> {
     };
 
     for src in &sources {
+        progress::stage("list", Some(&src.to_string()));
         match src {
-            InstallSource::Registry => match registry::list_registry_programs() {
-                Ok(programs) => all_programs.extend(programs),
+            InstallSource::Registry => match profiling::measure(
+                timings,
+                "registry_enumeration",
+                registry::list_registry_programs,
+            ) {
+                Ok(programs) => {
+                    all_programs.extend(programs);
+                    // 按用户安装的 MSI 产品不在 Uninstall 键中，单独补充
+                    let user_products = profiling::measure(
+                        timings,
+                        "user_msi_enumeration",
+                        msi::list_user_msi_products,
+                    );
+                    msi::merge_user_products(&mut all_programs, user_products);
+                }
                 Err(error) => tracing::warn!("读取注册表程序失败: {}", error),
             },
-            InstallSource::Msi => match msi::list_msi_products() {
-                Ok(programs) => all_programs.extend(programs),
-                Err(error) => tracing::warn!("读取 MSI 程序失败: {}", error),
-            },
-            InstallSource::Store => match store::list_store_apps() {
+            InstallSource::Msi => {
+                match profiling::measure(timings, "msi_enumeration", msi::list_msi_products) {
+                    Ok(programs) => all_programs.extend(programs),
+                    Err(error) => tracing::warn!("读取 MSI 程序失败: {}", error),
+                }
+            }
+            InstallSource::Store => {
+                match profiling::measure(timings, "store_enumeration", store::list_store_apps) {
+                    Ok(programs) => all_programs.extend(programs),
+                    Err(error) => tracing::warn!("读取商店应用失败: {}", error),
+                }
+            }
+            InstallSource::Feature => match profiling::measure(
+                timings,
+                "feature_enumeration",
+                features::list_feature_programs,
+            ) {
                 Ok(programs) => all_programs.extend(programs),
-                Err(error) => tracing::warn!("读取商店应用失败: {}", error),
+                Err(error) => tracing::warn!("读取 Windows 功能失败: {}", error),
             },
             InstallSource::Unknown => {}
         }
@@ -123,20 +353,71 @@ fn collect_programs(source: Option<InstallSource>) -> Vec<InstalledProgram> {
     all_programs
 }
 
+/// 填充标签和备注等用户数据，读取失败只记录警告
+pub fn attach_user_data(programs: &mut [InstalledProgram]) {
+    if let Err(error) = tags::apply_tags(programs) {
+        tracing::warn!("读取程序标签失败: {}", error);
+    }
+    if let Err(error) = notes::apply_notes(programs) {
+        tracing::warn!("读取程序备注失败: {}", error);
+    }
+    if let Err(error) = pins::apply_pins(programs) {
+        tracing::warn!("读取置顶状态失败: {}", error);
+    }
+}
+
+/// 填充用户数据和运行状态，再按标签、预装软件和搜索关键词过滤，需要时把置顶程序移到最前
+fn apply_query_filters(programs: &mut Vec<InstalledProgram>, query: &ListProgramsQuery) {
+    attach_user_data(programs);
+    processes::enrich_running(programs);
+    tags::filter_by_tags(programs, &query.tags);
+    if query.bloatware_only {
+        bloatware::filter_bloatware(programs);
+    }
+    if query.unsigned_only {
+        trust::filter_unsigned(programs);
+    }
+    apply_search_filter(programs, query.search.as_deref());
+    if query.pinned_first {
+        pins::float_pinned(programs);
+    }
+}
+
+/// 按搜索关键词过滤并按相关度排序（分数相同时保持原有顺序）
 fn apply_search_filter(programs: &mut Vec<InstalledProgram>, search: Option<&str>) {
-    if let Some(query) = search {
-        let normalized_query = query.to_lowercase();
-        programs.retain(|program| {
-            utils::fuzzy_match(&program.name.to_lowercase(), &normalized_query)
-                || program
-                    .publisher
-                    .as_ref()
-                    .map(|publisher| {
-                        utils::fuzzy_match(&publisher.to_lowercase(), &normalized_query)
-                    })
-                    .unwrap_or(false)
-        });
+    let Some(query) = search else {
+        return;
+    };
+
+    let normalized_query = query.trim().to_lowercase();
+    if normalized_query.is_empty() {
+        return;
     }
+
+    let mut scored: Vec<(i64, InstalledProgram)> = std::mem::take(programs)
+        .into_iter()
+        .filter_map(|program| {
+            search_score(&program, &normalized_query).map(|score| (score, program))
+        })
+        .collect();
+
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    programs.extend(scored.into_iter().map(|(_, program)| program));
+}
+
+/// 计算程序与搜索词的相关度，名称或别名命中优先于发布者命中
+fn search_score(program: &InstalledProgram, normalized_query: &str) -> Option<i64> {
+    let name_score = std::iter::once(&program.name)
+        .chain(program.aliases.iter())
+        .filter_map(|name| utils::fuzzy_score(name, normalized_query))
+        .max();
+    let publisher_score = program
+        .publisher
+        .as_deref()
+        .and_then(|publisher| utils::fuzzy_score(publisher, normalized_query))
+        .map(|score| score / 2);
+
+    name_score.max(publisher_score)
 }
 
 fn dedupe_and_sort(programs: &mut Vec<InstalledProgram>) {
@@ -144,3 +425,37 @@ fn dedupe_and_sort(programs: &mut Vec<InstalledProgram>) {
     programs.retain(|program| seen.insert(program.name.to_lowercase()));
     programs.sort_by(|left, right| left.name.to_lowercase().cmp(&right.name.to_lowercase()));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_search_filter_ranks_name_hits_before_publisher_hits() {
+        let mut by_publisher =
+            InstalledProgram::new("Toolbox".to_string(), InstallSource::Registry);
+        by_publisher.publisher = Some("Code Corp".to_string());
+        let by_name =
+            InstalledProgram::new("Visual Studio Code".to_string(), InstallSource::Registry);
+        let unrelated = InstalledProgram::new("Zip Utility".to_string(), InstallSource::Registry);
+
+        let mut programs = vec![by_publisher, unrelated, by_name];
+        apply_search_filter(&mut programs, Some("code"));
+
+        let names: Vec<&str> = programs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Visual Studio Code", "Toolbox"]);
+    }
+
+    #[test]
+    fn apply_search_filter_matches_aliases() {
+        let mut wechat = InstalledProgram::new("微信".to_string(), InstallSource::Registry);
+        wechat.aliases = vec!["WeChat".to_string()];
+        let unrelated = InstalledProgram::new("Zip Utility".to_string(), InstallSource::Registry);
+
+        let mut programs = vec![unrelated, wechat];
+        apply_search_filter(&mut programs, Some("wechat"));
+
+        let names: Vec<&str> = programs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["微信"]);
+    }
+}
@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use super::health::ProgramHealth;
+use crate::modules::common::profiling::StageTiming;
+use crate::modules::uninstaller::signature::SignatureCheck;
+
 /// 元数据置信度
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -38,10 +42,108 @@ pub enum MetadataSource {
     Registry,
     Filesystem,
     Derived,
+    /// 本程序生成，如提取不到图标时的占位图标
+    Generated,
     #[default]
     Unknown,
 }
 
+/// 自启动项类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupKind {
+    /// Run / RunOnce 注册表键
+    RunKey,
+    /// 开始菜单启动文件夹
+    StartupFolder,
+    /// 登录或开机时触发的计划任务
+    LogonTask,
+    /// 启动类型为自动的服务
+    Service,
+}
+
+/// 自启动项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartupEntry {
+    pub kind: StartupKind,
+    pub name: String,
+    pub command: String,
+    /// 注册表项、文件或任务路径
+    pub location: String,
+}
+
+/// 对开机速度的影响
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupImpact {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+/// 安装程序技术，决定静默卸载参数和退出码含义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallerKind {
+    Msi,
+    Nsis,
+    InnoSetup,
+    InstallShield,
+    Squirrel,
+    WixBurn,
+    Appx,
+    ClickOnce,
+    #[default]
+    Unknown,
+}
+
+/// 安装范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallScope {
+    /// 为所有用户安装，卸载需要管理员权限
+    Machine,
+    /// 仅为当前用户安装，卸载无需提升权限
+    User,
+    #[default]
+    Unknown,
+}
+
+impl InstallScope {
+    /// 卸载是否需要管理员权限
+    pub fn requires_elevation(self) -> bool {
+        self == InstallScope::Machine
+    }
+}
+
+impl std::fmt::Display for InstallScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallScope::Machine => write!(f, "所有用户"),
+            InstallScope::User => write!(f, "当前用户"),
+            InstallScope::Unknown => write!(f, "未知"),
+        }
+    }
+}
+
+impl std::fmt::Display for InstallerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallerKind::Msi => write!(f, "MSI"),
+            InstallerKind::Nsis => write!(f, "NSIS"),
+            InstallerKind::InnoSetup => write!(f, "Inno Setup"),
+            InstallerKind::InstallShield => write!(f, "InstallShield"),
+            InstallerKind::Squirrel => write!(f, "Squirrel"),
+            InstallerKind::WixBurn => write!(f, "WiX Burn"),
+            InstallerKind::Appx => write!(f, "AppX"),
+            InstallerKind::ClickOnce => write!(f, "ClickOnce"),
+            InstallerKind::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
 /// 已安装程序
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstalledProgram {
@@ -54,6 +156,10 @@ pub struct InstalledProgram {
     pub uninstall_string: Option<String>,
     #[serde(default)]
     pub install_source: InstallSource,
+    /// 原始安装包所在位置（注册表的 `InstallSource`、Burn 捆绑包的缓存目录），
+    /// 卸载后据此找出厂商卸载程序遗留的安装包缓存
+    #[serde(default)]
+    pub installer_source: Option<String>,
     pub size: Option<u64>,
     pub icon_path: Option<String>,
     #[serde(default)]
@@ -86,6 +192,50 @@ pub struct InstalledProgram {
     pub size_confidence: MetadataConfidence,
     #[serde(default)]
     pub metadata_confidence: MetadataConfidence,
+    /// 产品名、内部名等与显示名不同的名称，扫描时一并匹配
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// 卸载信息所在的注册表项，如 `HKLM\SOFTWARE\...\Uninstall\{GUID}`
+    #[serde(default)]
+    pub registry_key: Option<String>,
+    /// 登记的自启动项（Run 键、启动文件夹、登录计划任务、自动启动服务）
+    #[serde(default)]
+    pub startup_entries: Vec<StartupEntry>,
+    #[serde(default)]
+    pub startup_impact: StartupImpact,
+    /// 用户添加的标签，列出时从标签库填充
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 用户备注，列出时从用户数据库填充
+    #[serde(default)]
+    pub note: Option<String>,
+    /// 用户置顶的程序
+    #[serde(default)]
+    pub pinned: bool,
+    /// 疑似预装或捆绑软件的评分（0-100）
+    #[serde(default)]
+    pub bloatware_score: u8,
+    /// 预装软件评分的命中原因
+    #[serde(default)]
+    pub bloatware_reasons: Vec<String>,
+    /// 安装程序技术
+    #[serde(default)]
+    pub installer_kind: InstallerKind,
+    /// 安装范围
+    #[serde(default)]
+    pub install_scope: InstallScope,
+    /// 主程序（或卸载程序）的 Authenticode 签名，找不到可执行文件时为 None
+    #[serde(default)]
+    pub signature: Option<SignatureCheck>,
+    /// 安装目录下有正在运行的进程，列出时现场填充
+    #[serde(default)]
+    pub is_running: bool,
+    /// 正在运行的可执行文件名
+    #[serde(default)]
+    pub running_processes: Vec<String>,
+    /// 主程序、卸载程序和快捷方式的完整性
+    #[serde(default)]
+    pub health: ProgramHealth,
 }
 
 impl InstalledProgram {
@@ -99,6 +249,7 @@ impl InstalledProgram {
             install_location: None,
             uninstall_string: None,
             install_source: source,
+            installer_source: None,
             size: None,
             icon_path: None,
             icon_cache_path_32: None,
@@ -118,6 +269,21 @@ impl InstalledProgram {
             size_source: MetadataSource::Unknown,
             size_confidence: MetadataConfidence::Unknown,
             metadata_confidence: MetadataConfidence::Unknown,
+            aliases: Vec::new(),
+            registry_key: None,
+            startup_entries: Vec::new(),
+            startup_impact: StartupImpact::None,
+            tags: Vec::new(),
+            note: None,
+            pinned: false,
+            bloatware_score: 0,
+            bloatware_reasons: Vec::new(),
+            installer_kind: InstallerKind::Unknown,
+            install_scope: InstallScope::Unknown,
+            signature: None,
+            is_running: false,
+            running_processes: Vec::new(),
+            health: ProgramHealth::default(),
         }
     }
 }
@@ -131,6 +297,8 @@ pub enum InstallSource {
     Msi,
     /// 微软商店应用 (UWP)
     Store,
+    /// Windows 可选功能或功能按需 (Capability)
+    Feature,
     /// 未知来源
     Unknown,
 }
@@ -147,6 +315,7 @@ impl std::fmt::Display for InstallSource {
             InstallSource::Registry => write!(f, "Registry"),
             InstallSource::Msi => write!(f, "MSI"),
             InstallSource::Store => write!(f, "Store"),
+            InstallSource::Feature => write!(f, "Feature"),
             InstallSource::Unknown => write!(f, "Unknown"),
         }
     }
@@ -158,6 +327,12 @@ pub struct ProgramListCacheState {
     pub cache_hit: bool,
     pub cache_valid: bool,
     pub refreshed: bool,
+    /// 返回的是过期缓存
+    #[serde(default)]
+    pub stale: bool,
+    /// 后台刷新正在进行，完成后会通过刷新监听器通知
+    #[serde(default)]
+    pub refreshing: bool,
     pub schema_version: u32,
     pub generated_at: Option<String>,
     pub reason: Option<String>,
@@ -169,6 +344,8 @@ impl Default for ProgramListCacheState {
             cache_hit: false,
             cache_valid: false,
             refreshed: false,
+            stale: false,
+            refreshing: false,
             schema_version: 0,
             generated_at: None,
             reason: None,
@@ -183,6 +360,16 @@ pub struct ListProgramsQuery {
     pub search: Option<String>,
     pub refresh: bool,
     pub cache_ttl_seconds: i64,
+    /// 缓存过期时先返回旧数据并在后台刷新（适合常驻进程，CLI 不宜开启）
+    pub allow_stale: bool,
+    /// 只返回带有全部这些标签的程序
+    pub tags: Vec<String>,
+    /// 置顶程序排在最前（其余保持原有顺序）
+    pub pinned_first: bool,
+    /// 只返回疑似预装软件，按评分从高到低排列
+    pub bloatware_only: bool,
+    /// 只返回未签名或签名无效的程序
+    pub unsigned_only: bool,
 }
 
 /// 列表查询返回
@@ -190,4 +377,15 @@ pub struct ListProgramsQuery {
 pub struct ProgramListResponse {
     pub programs: Vec<InstalledProgram>,
     pub cache: ProgramListCacheState,
+    /// 各阶段耗时（缓存命中时仅包含缓存读取）
+    #[serde(default)]
+    pub timings: Vec<StageTiming>,
+}
+
+/// 安装历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramHistoryEntry {
+    pub program: InstalledProgram,
+    pub first_seen_at: String,
+    pub last_seen_at: String,
 }
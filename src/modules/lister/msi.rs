@@ -1,17 +1,199 @@
-use super::models::{InstallSource, InstalledProgram};
+//! MSI 产品枚举
+//!
+//! `Win32_Product` 只返回机器范围的产品。按用户安装的产品不写入 `Uninstall` 键，
+//! 其卸载信息位于 `Installer\UserData\<SID>\Products\<压缩 GUID>\InstallProperties`，
+//! 需要单独读取，卸载时无需管理员权限。
+
+use super::models::{InstallScope, InstallSource, InstalledProgram, MetadataSource};
 use crate::modules::common::error::UninstallerError;
+use winreg::enums::HKEY_LOCAL_MACHINE;
+use winreg::RegKey;
+
+/// 各用户 MSI 产品安装信息所在的注册表项
+const USER_DATA_KEY: &str = r"SOFTWARE\Microsoft\Windows\CurrentVersion\Installer\UserData";
+
+/// 用户配置文件列表，用于确定当前用户的 SID
+const PROFILE_LIST_KEY: &str = r"SOFTWARE\Microsoft\Windows NT\CurrentVersion\ProfileList";
 
-/// 列出 MSI 产品
+/// 机器范围安装使用的 SID（LocalSystem）
+const MACHINE_SID: &str = "S-1-5-18";
+
+/// 列出 MSI 产品，包括当前用户按用户范围安装的产品
 pub fn list_msi_products() -> Result<Vec<InstalledProgram>, UninstallerError> {
     #[cfg(windows)]
-    {
-        list_msi_products_impl()
-    }
+    let mut programs = list_msi_products_impl()?;
 
     #[cfg(not(windows))]
-    {
-        Ok(Vec::new())
+    let mut programs = Vec::new();
+
+    for program in &mut programs {
+        program.install_scope = InstallScope::Machine;
+    }
+    merge_user_products(&mut programs, list_user_msi_products());
+    Ok(programs)
+}
+
+/// 列出当前用户按用户范围安装的 MSI 产品
+pub fn list_user_msi_products() -> Vec<InstalledProgram> {
+    let Some(sid) = current_user_sid() else {
+        return Vec::new();
+    };
+
+    let products_path = format!(r"{}\{}\Products", USER_DATA_KEY, sid);
+    let Ok(products) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(&products_path) else {
+        return Vec::new();
+    };
+
+    products
+        .enum_keys()
+        .filter_map(|packed| packed.ok())
+        .filter_map(|packed| {
+            let properties_path = format!(r"{}\{}\InstallProperties", products_path, packed);
+            let properties = products
+                .open_subkey(format!(r"{}\InstallProperties", packed))
+                .ok()?;
+            let mut program = parse_install_properties(&properties, &packed)?;
+            program.registry_key = Some(format!(r"HKLM\{}", properties_path));
+            Some(program)
+        })
+        .collect()
+}
+
+/// 把按用户安装的产品合并到列表：已存在的产品（按产品代码匹配）只更新安装范围
+pub fn merge_user_products(
+    programs: &mut Vec<InstalledProgram>,
+    user_products: Vec<InstalledProgram>,
+) {
+    for product in user_products {
+        let product_code = product.id.trim_start_matches("msi-");
+        let existing = programs.iter_mut().find(|program| {
+            program.id == product.id
+                || program
+                    .registry_key
+                    .as_deref()
+                    .is_some_and(|key| key.to_lowercase().ends_with(&format!(r"\{}", product_code)))
+        });
+        match existing {
+            Some(program) => program.install_scope = InstallScope::User,
+            None => programs.push(product),
+        }
+    }
+}
+
+/// 读取 InstallProperties 中的卸载信息
+fn parse_install_properties(properties: &RegKey, packed: &str) -> Option<InstalledProgram> {
+    let name: String = properties.get_value("DisplayName").ok()?;
+    if properties.get_value::<u32, _>("SystemComponent").ok() == Some(1) {
+        return None;
+    }
+    let product_code = unpack_guid(packed)?;
+
+    let mut program = InstalledProgram::new(name, InstallSource::Msi);
+    program.id = format!("msi-{}", product_code.to_lowercase());
+    program.publisher = properties.get_value("Publisher").ok();
+    program.version = properties.get_value("DisplayVersion").ok();
+    program.install_date = properties.get_value("InstallDate").ok();
+    program.install_location = properties
+        .get_value::<String, _>("InstallLocation")
+        .ok()
+        .filter(|location| !location.trim().is_empty());
+    program.installer_source = properties
+        .get_value::<String, _>("InstallSource")
+        .ok()
+        .filter(|source| !source.trim().is_empty());
+    program.uninstall_string = Some(format!("MsiExec.exe /X{}", product_code));
+    program.install_scope = InstallScope::User;
+
+    if program.install_date.is_some() {
+        program.install_date_source = MetadataSource::Registry;
+    }
+    if let Ok(size) = properties.get_value::<u32, _>("EstimatedSize") {
+        program.estimated_size = Some(u64::from(size) * 1024);
+        program.size = program.estimated_size;
+        program.size_source = MetadataSource::Registry;
     }
+
+    Some(program)
+}
+
+/// 还原 Installer 注册表项使用的压缩 GUID
+///
+/// 前三段整体反转，后两段按字节交换高低位：
+/// `0A1B2C3D...` -> `{D3C2B1A0-...}`
+fn unpack_guid(packed: &str) -> Option<String> {
+    if packed.len() != 32 || !packed.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let reversed = |part: &str| part.chars().rev().collect::<String>();
+    let swapped = |part: &str| {
+        part.as_bytes()
+            .chunks(2)
+            .map(|pair| format!("{}{}", pair[1] as char, pair[0] as char))
+            .collect::<String>()
+    };
+
+    Some(
+        format!(
+            "{{{}-{}-{}-{}-{}}}",
+            reversed(&packed[0..8]),
+            reversed(&packed[8..12]),
+            reversed(&packed[12..16]),
+            swapped(&packed[16..20]),
+            swapped(&packed[20..32])
+        )
+        .to_uppercase(),
+    )
+}
+
+/// 把 `{GUID}` 形式的产品代码转换为 `Installer\Products` 下使用的压缩形式，与 `unpack_guid` 互逆
+pub(crate) fn pack_guid(guid: &str) -> Option<String> {
+    let hex: String = guid
+        .trim()
+        .trim_start_matches('{')
+        .trim_end_matches('}')
+        .split('-')
+        .collect();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let reversed = |part: &str| part.chars().rev().collect::<String>();
+    let swapped = |part: &str| {
+        part.as_bytes()
+            .chunks(2)
+            .map(|pair| format!("{}{}", pair[1] as char, pair[0] as char))
+            .collect::<String>()
+    };
+
+    Some(
+        format!(
+            "{}{}{}{}{}",
+            reversed(&hex[0..8]),
+            reversed(&hex[8..12]),
+            reversed(&hex[12..16]),
+            swapped(&hex[16..20]),
+            swapped(&hex[20..32])
+        )
+        .to_uppercase(),
+    )
+}
+
+/// 当前用户的 SID：在 UserData 下找配置文件目录与 USERPROFILE 相同的账户
+fn current_user_sid() -> Option<String> {
+    let user_profile = std::env::var("USERPROFILE").ok()?;
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let user_data = hklm.open_subkey(USER_DATA_KEY).ok()?;
+
+    user_data
+        .enum_keys()
+        .filter_map(|sid| sid.ok())
+        .filter(|sid| !sid.eq_ignore_ascii_case(MACHINE_SID))
+        .find(|sid| {
+            hklm.open_subkey(format!(r"{}\{}", PROFILE_LIST_KEY, sid))
+                .and_then(|key| key.get_value::<String, _>("ProfileImagePath"))
+                .is_ok_and(|path| path.eq_ignore_ascii_case(&user_profile))
+        })
 }
 
 #[cfg(windows)]
@@ -30,6 +212,7 @@ fn list_msi_products_impl() -> Result<Vec<InstalledProgram>, UninstallerError> {
                     Vendor = $_.Vendor
                     Version = $_.Version
                     InstallLocation = $_.InstallLocation
+                    InstallSource = $_.InstallSource
                     IdentifyingNumber = $_.IdentifyingNumber
                 }
             }
@@ -82,6 +265,7 @@ fn parse_msi_products(json_str: &str) -> Result<Vec<InstalledProgram>, Uninstall
         program.publisher = product.vendor;
         program.version = product.version;
         program.install_location = product.install_location;
+        program.installer_source = product.install_source.filter(|source| !source.is_empty());
 
         // 使用 IdentifyingNumber 构建卸载命令
         if let Some(id) = product.identifying_number {
@@ -109,6 +293,27 @@ struct MsiProductJson {
     #[serde(rename = "InstallLocation")]
     install_location: Option<String>,
 
+    #[serde(rename = "InstallSource")]
+    install_source: Option<String>,
+
     #[serde(rename = "IdentifyingNumber")]
     identifying_number: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpacks_installer_guid() {
+        assert_eq!(
+            unpack_guid("00002109F10090400000000000F01FEC").as_deref(),
+            Some("{90120000-001F-0409-0000-0000000FF1CE}")
+        );
+        assert_eq!(unpack_guid("not-a-guid"), None);
+        assert_eq!(
+            pack_guid("{90120000-001F-0409-0000-0000000FF1CE}").as_deref(),
+            Some("00002109F10090400000000000F01FEC")
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! 程序备注
+//!
+//! 记录安装原因、使用人、许可证位置等自由文本，按程序缓存键保存在用户数据库中。
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+
+use super::models::InstalledProgram;
+use super::pool::PooledConnection;
+use super::storage::{self, map_sqlite_error};
+use crate::modules::common::error::UninstallerError;
+
+const NOTES_TABLE_NAME: &str = "program_notes";
+
+fn open_notes_connection() -> Result<PooledConnection, UninstallerError> {
+    let connection = storage::open_user_data_connection()?;
+
+    connection
+        .execute_batch(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                cache_key TEXT PRIMARY KEY,
+                program_name TEXT NOT NULL,
+                note TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            "#,
+            table = NOTES_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("初始化备注表失败", error))?;
+
+    Ok(connection)
+}
+
+/// 设置程序备注，内容为空时删除备注
+pub fn set_note(program: &InstalledProgram, note: &str) -> Result<(), UninstallerError> {
+    let connection = open_notes_connection()?;
+    let cache_key = storage::build_program_cache_key(program);
+    let note = note.trim();
+
+    if note.is_empty() {
+        connection
+            .execute(
+                &format!("DELETE FROM {} WHERE cache_key = ?1", NOTES_TABLE_NAME),
+                params![cache_key],
+            )
+            .map_err(|error| map_sqlite_error("删除备注失败", error))?;
+        return Ok(());
+    }
+
+    connection
+        .execute(
+            &format!(
+                "INSERT INTO {} (cache_key, program_name, note, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(cache_key) DO UPDATE SET
+                    program_name = excluded.program_name,
+                    note = excluded.note,
+                    updated_at = excluded.updated_at",
+                NOTES_TABLE_NAME
+            ),
+            params![cache_key, program.name, note, Utc::now().to_rfc3339()],
+        )
+        .map_err(|error| map_sqlite_error("写入备注失败", error))?;
+    Ok(())
+}
+
+/// 读取程序备注
+#[allow(dead_code)]
+pub fn get_note(program: &InstalledProgram) -> Result<Option<String>, UninstallerError> {
+    open_notes_connection()?
+        .query_row(
+            &format!("SELECT note FROM {} WHERE cache_key = ?1", NOTES_TABLE_NAME),
+            params![storage::build_program_cache_key(program)],
+            |row| row.get::<usize, String>(0),
+        )
+        .optional()
+        .map_err(|error| map_sqlite_error("读取备注失败", error))
+}
+
+/// 为程序填充备注（覆盖缓存中保存的旧值）
+pub fn apply_notes(programs: &mut [InstalledProgram]) -> Result<(), UninstallerError> {
+    let connection = open_notes_connection()?;
+    let mut statement = connection
+        .prepare(&format!("SELECT cache_key, note FROM {}", NOTES_TABLE_NAME))
+        .map_err(|error| map_sqlite_error("准备读取备注失败", error))?;
+
+    let notes = statement
+        .query_map([], |row| {
+            Ok((row.get::<usize, String>(0)?, row.get::<usize, String>(1)?))
+        })
+        .map_err(|error| map_sqlite_error("读取备注失败", error))?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|error| map_sqlite_error("解析备注失败", error))?;
+
+    for program in programs {
+        program.note = notes
+            .get(&storage::build_program_cache_key(program))
+            .cloned();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn empty_note_clears_existing_note() {
+        let _guard = storage::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let root =
+            std::env::temp_dir().join(format!("rust-yu-notes-test-{}", uuid::Uuid::new_v4()));
+        let _ = std::fs::create_dir_all(&root);
+        std::env::set_var(storage::STORAGE_DIR_ENV, &root);
+
+        let program = InstalledProgram::new("Editor".to_string(), InstallSource::Registry);
+        assert!(set_note(&program, "  财务部使用，许可证在共享盘 ").is_ok());
+        assert_eq!(
+            get_note(&program).unwrap_or_default().as_deref(),
+            Some("财务部使用，许可证在共享盘")
+        );
+
+        assert!(set_note(&program, "").is_ok());
+        let mut programs = vec![program];
+        assert!(apply_notes(&mut programs).is_ok());
+        assert_eq!(programs[0].note, None);
+
+        std::env::remove_var(storage::STORAGE_DIR_ENV);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,105 @@
+//! 置顶程序
+//!
+//! 用户常用的少数程序可以置顶，置顶状态按程序缓存键保存在用户数据库中。
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use rusqlite::params;
+
+use super::models::InstalledProgram;
+use super::pool::PooledConnection;
+use super::storage::{self, map_sqlite_error};
+use crate::modules::common::error::UninstallerError;
+
+const PINS_TABLE_NAME: &str = "program_pins";
+
+fn open_pins_connection() -> Result<PooledConnection, UninstallerError> {
+    let connection = storage::open_user_data_connection()?;
+
+    connection
+        .execute_batch(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                cache_key TEXT PRIMARY KEY,
+                program_name TEXT NOT NULL,
+                pinned_at TEXT NOT NULL
+            );
+            "#,
+            table = PINS_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("初始化置顶表失败", error))?;
+
+    Ok(connection)
+}
+
+/// 置顶或取消置顶程序
+pub fn set_pinned(program: &InstalledProgram, pinned: bool) -> Result<(), UninstallerError> {
+    let connection = open_pins_connection()?;
+    let cache_key = storage::build_program_cache_key(program);
+
+    if pinned {
+        connection
+            .execute(
+                &format!(
+                    "INSERT OR IGNORE INTO {} (cache_key, program_name, pinned_at)
+                     VALUES (?1, ?2, ?3)",
+                    PINS_TABLE_NAME
+                ),
+                params![cache_key, program.name, Utc::now().to_rfc3339()],
+            )
+            .map_err(|error| map_sqlite_error("写入置顶状态失败", error))?;
+    } else {
+        connection
+            .execute(
+                &format!("DELETE FROM {} WHERE cache_key = ?1", PINS_TABLE_NAME),
+                params![cache_key],
+            )
+            .map_err(|error| map_sqlite_error("删除置顶状态失败", error))?;
+    }
+    Ok(())
+}
+
+/// 为程序填充置顶状态
+pub fn apply_pins(programs: &mut [InstalledProgram]) -> Result<(), UninstallerError> {
+    let connection = open_pins_connection()?;
+    let mut statement = connection
+        .prepare(&format!("SELECT cache_key FROM {}", PINS_TABLE_NAME))
+        .map_err(|error| map_sqlite_error("准备读取置顶状态失败", error))?;
+
+    let pinned_keys = statement
+        .query_map([], |row| row.get::<usize, String>(0))
+        .map_err(|error| map_sqlite_error("读取置顶状态失败", error))?
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|error| map_sqlite_error("解析置顶状态失败", error))?;
+
+    for program in programs {
+        program.pinned = pinned_keys.contains(&storage::build_program_cache_key(program));
+    }
+    Ok(())
+}
+
+/// 置顶程序移到最前，其余顺序保持不变
+pub fn float_pinned(programs: &mut [InstalledProgram]) {
+    programs.sort_by_key(|program| !program.pinned);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn float_pinned_keeps_relative_order() {
+        let mut programs: Vec<InstalledProgram> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|name| InstalledProgram::new(name.to_string(), InstallSource::Registry))
+            .collect();
+        programs[1].pinned = true;
+        programs[3].pinned = true;
+
+        float_pinned(&mut programs);
+        let names: Vec<&str> = programs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["B", "D", "A", "C"]);
+    }
+}
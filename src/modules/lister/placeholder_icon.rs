@@ -0,0 +1,218 @@
+//! 占位图标
+//!
+//! 提取不到图标的程序使用程序名首字母加彩色底的占位图，前端不会出现空白图块。
+//! 颜色由程序名的 FNV-1a 哈希从固定色板中选取，同一程序每次生成的图片完全相同，
+//! 首字母与颜色相同的程序共用同一个缓存文件。首字母只取程序名中第一个 ASCII 字母或数字，
+//! 没有时只画底色。
+
+use std::path::Path;
+
+/// 占位图标底色
+const PALETTE: &[[u8; 3]] = &[
+    [0xE5, 0x39, 0x35],
+    [0xD8, 0x1B, 0x60],
+    [0x8E, 0x24, 0xAA],
+    [0x5E, 0x35, 0xB1],
+    [0x39, 0x49, 0xAB],
+    [0x1E, 0x88, 0xE5],
+    [0x03, 0x9B, 0xE5],
+    [0x00, 0xAC, 0xC1],
+    [0x00, 0x89, 0x7B],
+    [0x43, 0xA0, 0x47],
+    [0x7C, 0xB3, 0x42],
+    [0xF4, 0x51, 0x1E],
+    [0x6D, 0x4C, 0x41],
+    [0x54, 0x6E, 0x7A],
+];
+
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+/// 5x7 点阵字形，每行低 5 位从左到右
+const DIGIT_GLYPHS: [[u8; 7]; 10] = [
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+];
+
+const LETTER_GLYPHS: [[u8; 7]; 26] = [
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+];
+
+/// 程序的占位图标：首字母和底色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaceholderIcon {
+    /// 大写的首字母或数字，程序名中没有 ASCII 字母数字时为 None
+    pub initial: Option<char>,
+    pub color: [u8; 3],
+}
+
+impl PlaceholderIcon {
+    /// 按程序名生成占位图标
+    pub fn for_name(name: &str) -> Self {
+        let name = name.trim().to_lowercase();
+        let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        Self {
+            initial: name
+                .chars()
+                .find(char::is_ascii_alphanumeric)
+                .map(|c| c.to_ascii_uppercase()),
+            color: PALETTE[(hash % PALETTE.len() as u64) as usize],
+        }
+    }
+
+    /// 缓存文件名，首字母与颜色相同的程序共用
+    pub fn cache_file_name(&self) -> String {
+        format!(
+            "placeholder-{}-{:02x}{:02x}{:02x}.png",
+            self.initial.unwrap_or('_'),
+            self.color[0],
+            self.color[1],
+            self.color[2]
+        )
+    }
+
+    /// 渲染为 `size`×`size` 的 RGBA 像素：圆角彩色底，居中白色首字母
+    pub fn render(&self, size: u32) -> Vec<u8> {
+        let radius = size / 6;
+        let scale = (size / 10).max(1);
+        let glyph = self.initial.and_then(glyph_rows);
+        let glyph_left = size.saturating_sub(GLYPH_WIDTH * scale) / 2;
+        let glyph_top = size.saturating_sub(GLYPH_HEIGHT * scale) / 2;
+
+        let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                if outside_rounded_corner(x, y, size, radius) {
+                    pixels.extend_from_slice(&[0, 0, 0, 0]);
+                    continue;
+                }
+                let on_glyph = glyph.is_some_and(|rows| {
+                    let (gx, gy) = (x.wrapping_sub(glyph_left), y.wrapping_sub(glyph_top));
+                    gx < GLYPH_WIDTH * scale
+                        && gy < GLYPH_HEIGHT * scale
+                        && rows[(gy / scale) as usize] & (0x10 >> (gx / scale)) != 0
+                });
+                if on_glyph {
+                    pixels.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+                } else {
+                    pixels.extend_from_slice(&[self.color[0], self.color[1], self.color[2], 0xFF]);
+                }
+            }
+        }
+        pixels
+    }
+
+    /// 编码为 PNG
+    pub fn to_png(&self, size: u32) -> Option<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, size, size);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().ok()?;
+        writer.write_image_data(&self.render(size)).ok()?;
+        writer.finish().ok()?;
+        Some(bytes)
+    }
+
+    /// 写入 PNG 文件，文件已存在时不重复生成
+    pub fn write_png(&self, path: &Path, size: u32) -> Option<()> {
+        if path.is_file() {
+            return Some(());
+        }
+        if let Some(parent_dir) = path.parent() {
+            std::fs::create_dir_all(parent_dir).ok()?;
+        }
+        std::fs::write(path, self.to_png(size)?).ok()
+    }
+}
+
+fn glyph_rows(initial: char) -> Option<&'static [u8; 7]> {
+    match initial {
+        '0'..='9' => DIGIT_GLYPHS.get(initial as usize - '0' as usize),
+        'A'..='Z' => LETTER_GLYPHS.get(initial as usize - 'A' as usize),
+        _ => None,
+    }
+}
+
+/// 像素是否落在圆角之外
+fn outside_rounded_corner(x: u32, y: u32, size: u32, radius: u32) -> bool {
+    if radius == 0 {
+        return false;
+    }
+    let cx = if x < radius {
+        radius
+    } else if x >= size - radius {
+        size - radius - 1
+    } else {
+        return false;
+    };
+    let cy = if y < radius {
+        radius
+    } else if y >= size - radius {
+        size - radius - 1
+    } else {
+        return false;
+    };
+    let (dx, dy) = (x.abs_diff(cx), y.abs_diff(cy));
+    dx * dx + dy * dy > radius * radius
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_deterministic_initial_tiles() {
+        let icon = PlaceholderIcon::for_name("  Acme Tools ");
+        assert_eq!(icon, PlaceholderIcon::for_name("acme tools"));
+        assert_eq!(icon.initial, Some('A'));
+        assert_eq!(PlaceholderIcon::for_name("腾讯QQ").initial, Some('Q'));
+        assert_eq!(PlaceholderIcon::for_name("记事本").initial, None);
+
+        let pixels = icon.render(32);
+        assert_eq!(pixels.len(), 32 * 32 * 4);
+        // 圆角透明，中心为字母或底色
+        assert_eq!(pixels[3], 0);
+        let center = ((16 * 32 + 16) * 4) as usize;
+        assert_eq!(pixels[center + 3], 0xFF);
+
+        let png = icon.to_png(48).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(icon.to_png(48).unwrap(), png);
+    }
+}
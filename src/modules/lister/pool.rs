@@ -0,0 +1,210 @@
+//! SQLite 连接池与并发访问保护
+//!
+//! Tauri 界面、内置 HTTP 服务和命令行实例可能同时访问同一个数据库。连接按数据库路径复用，
+//! 每个连接都设置忙碌等待时间；批量写入前再获取跨进程的文件锁，仍然返回忙碌的操作按退避重试。
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use rusqlite::{Connection, ErrorCode};
+
+use super::storage::map_sqlite_error;
+use crate::modules::common::error::UninstallerError;
+
+/// 单条语句遇到锁时的等待时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 每个数据库保留的空闲连接数
+const MAX_IDLE_CONNECTIONS: usize = 4;
+
+/// 忙碌错误的最大重试次数
+const MAX_BUSY_RETRIES: u32 = 5;
+
+/// 首次重试前的等待时间，之后每次翻倍
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(50);
+
+/// 等待跨进程写锁的最长时间
+const WRITE_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 写锁文件后缀，与数据库文件放在同一目录
+const WRITE_LOCK_SUFFIX: &str = ".lock";
+
+type IdleConnections = Mutex<HashMap<PathBuf, Vec<Connection>>>;
+
+static IDLE_CONNECTIONS: OnceLock<IdleConnections> = OnceLock::new();
+
+fn idle_connections() -> &'static IdleConnections {
+    IDLE_CONNECTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 从池中借出的连接，释放时归还
+pub struct PooledConnection {
+    connection: Option<Connection>,
+    path: PathBuf,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.connection.as_ref().expect("连接已归还")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.connection.as_mut().expect("连接已归还")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(connection) = self.connection.take() else {
+            return;
+        };
+        // 数据库文件已被删除（缓存失效）时不再复用
+        if !self.path.exists() {
+            return;
+        }
+        let mut idle = idle_connections()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let connections = idle.entry(self.path.clone()).or_default();
+        if connections.len() < MAX_IDLE_CONNECTIONS {
+            connections.push(connection);
+        }
+    }
+}
+
+/// 借出数据库连接；没有空闲连接时新建，并执行 `init`（设置 PRAGMA、建表或迁移）
+pub fn acquire(
+    path: &Path,
+    init: impl FnOnce(&mut Connection) -> Result<(), UninstallerError>,
+) -> Result<PooledConnection, UninstallerError> {
+    let reused = idle_connections()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get_mut(path)
+        .and_then(Vec::pop);
+
+    let connection = match reused {
+        Some(connection) => connection,
+        None => {
+            let mut connection = Connection::open(path)
+                .map_err(|error| map_sqlite_error("打开数据库失败", error))?;
+            connection
+                .busy_timeout(BUSY_TIMEOUT)
+                .map_err(|error| map_sqlite_error("设置数据库等待超时失败", error))?;
+            init(&mut connection)?;
+            connection
+        }
+    };
+
+    Ok(PooledConnection {
+        connection: Some(connection),
+        path: path.to_path_buf(),
+    })
+}
+
+/// 关闭数据库的空闲连接，删除数据库文件前调用
+pub fn close_idle(path: &Path) {
+    idle_connections()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .remove(path);
+}
+
+/// 是否为数据库忙碌或被锁定的错误
+pub fn is_busy_error(error: &rusqlite::Error) -> bool {
+    matches!(
+        error.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+/// 执行操作，遇到忙碌错误时按退避重试
+pub fn retry_busy<T>(mut operation: impl FnMut() -> rusqlite::Result<T>) -> rusqlite::Result<T> {
+    let mut delay = RETRY_BASE_DELAY;
+    let mut attempt = 0;
+    loop {
+        match operation() {
+            Err(error) if is_busy_error(&error) && attempt < MAX_BUSY_RETRIES => {
+                attempt += 1;
+                tracing::debug!("数据库忙碌，{:?} 后第 {} 次重试", delay, attempt);
+                std::thread::sleep(delay);
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// 跨进程写锁，释放时自动解锁
+pub struct WriteLock {
+    _file: File,
+}
+
+/// 获取数据库的跨进程写锁，超时返回错误
+pub fn lock_for_write(db_path: &Path) -> Result<WriteLock, UninstallerError> {
+    let mut lock_path = db_path.as_os_str().to_owned();
+    lock_path.push(WRITE_LOCK_SUFFIX);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(PathBuf::from(lock_path))?;
+
+    let started_at = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(WriteLock { _file: file }),
+            Err(TryLockError::WouldBlock) if started_at.elapsed() < WRITE_LOCK_TIMEOUT => {
+                std::thread::sleep(RETRY_BASE_DELAY);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(UninstallerError::Timeout(format!(
+                    "等待数据库写锁超时: {}",
+                    db_path.display()
+                )));
+            }
+            Err(TryLockError::Error(error)) => return Err(error.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_connections_and_serializes_writers() {
+        let path =
+            std::env::temp_dir().join(format!("rust-yu-pool-{}.sqlite3", uuid::Uuid::new_v4()));
+
+        let mut init_calls = 0;
+        drop(acquire(&path, |_| {
+            init_calls += 1;
+            Ok(())
+        }));
+        drop(acquire(&path, |_| {
+            init_calls += 1;
+            Ok(())
+        }));
+        assert_eq!(init_calls, 1);
+
+        let lock = lock_for_write(&path);
+        assert!(lock.is_ok());
+        let lock_file = File::open(format!("{}{}", path.display(), WRITE_LOCK_SUFFIX));
+        assert!(
+            lock_file.is_ok_and(|file| matches!(file.try_lock(), Err(TryLockError::WouldBlock)))
+        );
+        drop(lock);
+
+        close_idle(&path);
+        let _ = std::fs::remove_file(&path);
+    }
+}
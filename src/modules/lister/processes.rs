@@ -0,0 +1,150 @@
+//! 正在运行的进程
+//!
+//! 可执行文件位于程序安装目录下的进程视为该程序正在运行。列表中据此显示“运行中”标记，
+//! 卸载前据此提醒用户先关闭程序，否则卸载程序可能无法删除被占用的文件、需要重启才能完成。
+//! 运行状态随时变化，不写入缓存，每次列出时现场填充。
+
+use serde::{Deserialize, Serialize};
+
+use super::models::InstalledProgram;
+use crate::modules::scanner::roots;
+
+/// 一个正在运行的进程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunningProcess {
+    pub pid: u32,
+    /// 可执行文件完整路径
+    pub path: String,
+}
+
+impl RunningProcess {
+    /// 可执行文件名，如 `acme.exe`
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('\\').next().unwrap_or(&self.path)
+    }
+}
+
+/// 为程序填充运行状态，进程列表只枚举一次
+pub fn enrich_running(programs: &mut [InstalledProgram]) {
+    let processes = running_processes();
+    for program in programs.iter_mut() {
+        let matched = processes_in(program, &processes);
+        program.is_running = !matched.is_empty();
+        program.running_processes = matched
+            .iter()
+            .map(|process| process.file_name().to_string())
+            .collect();
+        program.running_processes.sort();
+        program.running_processes.dedup();
+    }
+}
+
+/// 程序安装目录下正在运行的进程，没有安装目录记录时为空
+pub fn running_in(program: &InstalledProgram) -> Vec<RunningProcess> {
+    processes_in(program, &running_processes())
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/// 从进程列表中找出可执行文件位于程序安装目录下的进程
+fn processes_in<'a>(
+    program: &InstalledProgram,
+    processes: &'a [RunningProcess],
+) -> Vec<&'a RunningProcess> {
+    let Some(root) = roots::install_root(program) else {
+        return Vec::new();
+    };
+    processes
+        .iter()
+        .filter(|process| process.path.to_lowercase().starts_with(&root))
+        .collect()
+}
+
+/// 枚举当前正在运行的进程，取不到路径的进程（系统进程、其他用户的受保护进程）跳过
+#[cfg(windows)]
+pub fn running_processes() -> Vec<RunningProcess> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let mut processes = Vec::new();
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) else {
+            tracing::warn!("枚举进程失败");
+            return processes;
+        };
+
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut next = Process32FirstW(snapshot, &mut entry);
+        while next.is_ok() {
+            let pid = entry.th32ProcessID;
+            if pid != 0 {
+                if let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+                    let mut buffer = [0u16; 1024];
+                    let mut size = buffer.len() as u32;
+                    if QueryFullProcessImageNameW(
+                        handle,
+                        PROCESS_NAME_WIN32,
+                        PWSTR(buffer.as_mut_ptr()),
+                        &mut size,
+                    )
+                    .is_ok()
+                    {
+                        processes.push(RunningProcess {
+                            pid,
+                            path: String::from_utf16_lossy(&buffer[..size as usize]),
+                        });
+                    }
+                    let _ = CloseHandle(handle);
+                }
+            }
+            next = Process32NextW(snapshot, &mut entry);
+        }
+        let _ = CloseHandle(snapshot);
+    }
+    processes
+}
+
+#[cfg(not(windows))]
+pub fn running_processes() -> Vec<RunningProcess> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn matches_processes_under_install_location() {
+        let mut program = InstalledProgram::new("Acme".to_string(), InstallSource::Registry);
+        program.install_location = Some(r"D:\Apps\Acme".to_string());
+        let process = |pid, path: &str| RunningProcess {
+            pid,
+            path: path.to_string(),
+        };
+        let processes = vec![
+            process(10, r"D:\Apps\Acme\bin\Acme.exe"),
+            process(11, r"D:\Apps\AcmeTools\tool.exe"),
+            process(12, r"C:\Windows\explorer.exe"),
+        ];
+
+        let matched = processes_in(&program, &processes);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].file_name(), "Acme.exe");
+
+        program.install_location = None;
+        assert!(processes_in(&program, &processes).is_empty());
+    }
+}
@@ -1,4 +1,6 @@
-use super::models::{InstallSource, InstalledProgram, MetadataConfidence, MetadataSource};
+use super::models::{
+    InstallScope, InstallSource, InstalledProgram, MetadataConfidence, MetadataSource,
+};
 use crate::modules::common::error::UninstallerError;
 use winreg::enums::*;
 use winreg::RegKey;
@@ -11,24 +13,33 @@ pub fn list_registry_programs() -> Result<Vec<InstalledProgram>, UninstallerErro
     let paths = [
         (
             HKEY_LOCAL_MACHINE,
+            "HKLM",
             r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
         ),
         (
             HKEY_LOCAL_MACHINE,
+            "HKLM",
             r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall",
         ),
         (
             HKEY_CURRENT_USER,
+            "HKCU",
             r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
         ),
     ];
 
-    for (hkey, path) in &paths {
+    for (hkey, root, path) in &paths {
         match RegKey::predef(*hkey).open_subkey(path) {
             Ok(key) => {
                 for name in key.enum_keys().filter_map(|k| k.ok()) {
                     if let Ok(subkey) = key.open_subkey(&name) {
-                        if let Some(program) = parse_registry_entry(&subkey) {
+                        if let Some(mut program) = parse_registry_entry(&subkey) {
+                            program.registry_key = Some(format!(r"{}\{}\{}", root, path, name));
+                            program.install_scope = if *root == "HKCU" {
+                                InstallScope::User
+                            } else {
+                                InstallScope::Machine
+                            };
                             // 跳过系统组件和更新
                             if !is_system_component(&program) {
                                 programs.push(program);
@@ -67,6 +78,7 @@ fn parse_registry_entry(subkey: &RegKey) -> Option<InstalledProgram> {
     program.icon_path = subkey.get_value("DisplayIcon").ok();
     program.url_info_about = subkey.get_value("URLInfoAbout").ok();
     program.help_link = subkey.get_value("HelpLink").ok();
+    program.installer_source = read_installer_source(subkey);
 
     if program.install_date.is_some() {
         program.install_date_source = MetadataSource::Registry;
@@ -89,6 +101,21 @@ fn parse_registry_entry(subkey: &RegKey) -> Option<InstalledProgram> {
     Some(program)
 }
 
+/// 原始安装包位置：优先取 `InstallSource`，Burn 捆绑包取 `BundleCachePath` 所在目录
+fn read_installer_source(subkey: &RegKey) -> Option<String> {
+    subkey
+        .get_value::<String, _>("InstallSource")
+        .ok()
+        .or_else(|| {
+            let cache_path: String = subkey.get_value("BundleCachePath").ok()?;
+            std::path::Path::new(cache_path.trim().trim_matches('"'))
+                .parent()
+                .map(|dir| dir.to_string_lossy().to_string())
+        })
+        .map(|source| source.trim().trim_matches('"').to_string())
+        .filter(|source| !source.is_empty())
+}
+
 /// 检查是否为系统组件
 fn is_system_component(program: &InstalledProgram) -> bool {
     let system_components = [
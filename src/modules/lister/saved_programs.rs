@@ -0,0 +1,326 @@
+//! 卸载前保存的程序记录
+//!
+//! 每次保存都追加一条带时间戳的记录，按程序 id 和小写名称建索引，每个程序只保留最近几条。
+//! 记录保存在用户数据库中，扫描缓存失效时不受影响。早期版本写入的 `programs.json` 在首次打开时导入。
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::models::InstalledProgram;
+use super::pool::PooledConnection;
+use super::storage::{self, map_sqlite_error};
+use crate::modules::common::error::UninstallerError;
+
+const SNAPSHOTS_TABLE_NAME: &str = "program_snapshots";
+
+/// 早期版本使用的 JSON 快照文件
+const LEGACY_SNAPSHOT_FILE_NAME: &str = "programs.json";
+
+/// 每个程序保留的记录数
+pub const MAX_SNAPSHOTS_PER_PROGRAM: usize = 5;
+
+/// 一条保存的程序记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramSnapshotRecord {
+    pub snapshot_id: i64,
+    pub saved_at: String,
+    pub program: InstalledProgram,
+}
+
+fn open_snapshots_connection() -> Result<PooledConnection, UninstallerError> {
+    let mut connection = storage::open_user_data_connection()?;
+
+    connection
+        .execute_batch(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                snapshot_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                program_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                name_lower TEXT NOT NULL,
+                payload_json TEXT NOT NULL,
+                saved_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_program_snapshots_program_id ON {table}(program_id);
+            CREATE INDEX IF NOT EXISTS idx_program_snapshots_name ON {table}(name_lower);
+            "#,
+            table = SNAPSHOTS_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("初始化程序记录表失败", error))?;
+
+    import_legacy_snapshots(&mut connection)?;
+    Ok(connection)
+}
+
+/// 导入 `programs.json` 中的记录后删除该文件，时间戳取文件修改时间
+fn import_legacy_snapshots(connection: &mut PooledConnection) -> Result<(), UninstallerError> {
+    let path = storage::get_storage_root_dir()?.join(LEGACY_SNAPSHOT_FILE_NAME);
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = std::fs::read_to_string(&path)?;
+    let programs: Vec<InstalledProgram> = serde_json::from_str(&content).unwrap_or_default();
+    let saved_at = std::fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now())
+        .to_rfc3339();
+
+    let transaction = connection
+        .transaction()
+        .map_err(|error| map_sqlite_error("开启程序记录事务失败", error))?;
+    for program in &programs {
+        insert_snapshot(&transaction, program, &saved_at)?;
+    }
+    transaction
+        .commit()
+        .map_err(|error| map_sqlite_error("提交程序记录事务失败", error))?;
+
+    std::fs::remove_file(&path)?;
+    tracing::info!(
+        "已将 {} 条程序记录从 {} 导入数据库",
+        programs.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+fn insert_snapshot(
+    connection: &rusqlite::Connection,
+    program: &InstalledProgram,
+    saved_at: &str,
+) -> Result<(), UninstallerError> {
+    // 图标数据较大且与残留搜索无关，不写入记录
+    let mut stored = program.clone();
+    stored.icon_data_url = None;
+    stored.icon_data_url_32 = None;
+    stored.icon_data_url_48 = None;
+    let payload_json = serde_json::to_string(&stored)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    let name_lower = program.name.to_lowercase();
+
+    connection
+        .execute(
+            &format!(
+                "INSERT INTO {} (program_id, name, name_lower, payload_json, saved_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                SNAPSHOTS_TABLE_NAME
+            ),
+            params![program.id, program.name, name_lower, payload_json, saved_at],
+        )
+        .map_err(|error| map_sqlite_error("写入程序记录失败", error))?;
+
+    // 只保留每个程序最近的几条记录
+    connection
+        .execute(
+            &format!(
+                "DELETE FROM {table} WHERE name_lower = ?1 AND snapshot_id NOT IN (
+                    SELECT snapshot_id FROM {table} WHERE name_lower = ?1
+                    ORDER BY snapshot_id DESC LIMIT ?2
+                 )",
+                table = SNAPSHOTS_TABLE_NAME
+            ),
+            params![name_lower, MAX_SNAPSHOTS_PER_PROGRAM as i64],
+        )
+        .map_err(|error| map_sqlite_error("清理旧程序记录失败", error))?;
+    Ok(())
+}
+
+/// 保存程序记录
+pub fn save_snapshots(programs: &[InstalledProgram]) -> Result<(), UninstallerError> {
+    let mut connection = open_snapshots_connection()?;
+    let saved_at = Utc::now().to_rfc3339();
+
+    let transaction = connection
+        .transaction()
+        .map_err(|error| map_sqlite_error("开启程序记录事务失败", error))?;
+    for program in programs {
+        insert_snapshot(&transaction, program, &saved_at)?;
+    }
+    transaction
+        .commit()
+        .map_err(|error| map_sqlite_error("提交程序记录事务失败", error))?;
+
+    tracing::info!("已保存 {} 个程序信息到快照", programs.len());
+    Ok(())
+}
+
+/// 每个程序最新的一条记录，最近保存的在前
+pub fn latest_snapshots() -> Result<Vec<InstalledProgram>, UninstallerError> {
+    let connection = open_snapshots_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT payload_json FROM {table} WHERE snapshot_id IN (
+                SELECT MAX(snapshot_id) FROM {table} GROUP BY name_lower
+             ) ORDER BY snapshot_id DESC",
+            table = SNAPSHOTS_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取程序记录失败", error))?;
+
+    let payloads = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|error| map_sqlite_error("读取程序记录失败", error))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| map_sqlite_error("读取程序记录失败", error))?;
+
+    Ok(payloads
+        .iter()
+        .filter_map(|payload| serde_json::from_str(payload).ok())
+        .collect())
+}
+
+/// 按名称（不区分大小写）查找最新的记录
+pub fn find_latest_by_name(name: &str) -> Result<Option<InstalledProgram>, UninstallerError> {
+    find_latest("name_lower", &name.trim().to_lowercase())
+}
+
+/// 按程序 id 查找最新的记录
+#[allow(dead_code)]
+pub fn find_latest_by_id(program_id: &str) -> Result<Option<InstalledProgram>, UninstallerError> {
+    find_latest("program_id", program_id)
+}
+
+fn find_latest(column: &str, value: &str) -> Result<Option<InstalledProgram>, UninstallerError> {
+    let connection = open_snapshots_connection()?;
+    let payload: Option<String> = connection
+        .query_row(
+            &format!(
+                "SELECT payload_json FROM {} WHERE {} = ?1 ORDER BY snapshot_id DESC LIMIT 1",
+                SNAPSHOTS_TABLE_NAME, column
+            ),
+            params![value],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|error| map_sqlite_error("读取程序记录失败", error))?;
+
+    Ok(payload.and_then(|payload| serde_json::from_str(&payload).ok()))
+}
+
+/// 程序的全部历史记录，最新的在前，用于对比卸载前后的变化
+#[allow(dead_code)]
+pub fn snapshot_history(name: &str) -> Result<Vec<ProgramSnapshotRecord>, UninstallerError> {
+    let connection = open_snapshots_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT snapshot_id, saved_at, payload_json FROM {}
+             WHERE name_lower = ?1 ORDER BY snapshot_id DESC",
+            SNAPSHOTS_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取程序记录失败", error))?;
+
+    let rows = statement
+        .query_map(params![name.trim().to_lowercase()], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|error| map_sqlite_error("读取程序记录失败", error))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| map_sqlite_error("读取程序记录失败", error))?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(snapshot_id, saved_at, payload)| {
+            serde_json::from_str(&payload)
+                .ok()
+                .map(|program| ProgramSnapshotRecord {
+                    snapshot_id,
+                    saved_at,
+                    program,
+                })
+        })
+        .collect())
+}
+
+/// 删除程序的全部记录，名称需完全一致（不区分大小写）
+pub fn delete_snapshots(name: &str) -> Result<usize, UninstallerError> {
+    let connection = open_snapshots_connection()?;
+    connection
+        .execute(
+            &format!("DELETE FROM {} WHERE name_lower = ?1", SNAPSHOTS_TABLE_NAME),
+            params![name.trim().to_lowercase()],
+        )
+        .map_err(|error| map_sqlite_error("删除程序记录失败", error))
+}
+
+/// 检查无法解析的记录，`repair` 时删除，返回问题记录数
+pub fn verify_snapshots(repair: bool) -> Result<usize, UninstallerError> {
+    let connection = open_snapshots_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT snapshot_id, payload_json FROM {}",
+            SNAPSHOTS_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取程序记录失败", error))?;
+    let invalid: Vec<i64> = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|error| map_sqlite_error("读取程序记录失败", error))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| map_sqlite_error("读取程序记录失败", error))?
+        .into_iter()
+        .filter(|(_, payload)| serde_json::from_str::<InstalledProgram>(payload).is_err())
+        .map(|(snapshot_id, _)| snapshot_id)
+        .collect();
+
+    if repair {
+        for snapshot_id in &invalid {
+            connection
+                .execute(
+                    &format!(
+                        "DELETE FROM {} WHERE snapshot_id = ?1",
+                        SNAPSHOTS_TABLE_NAME
+                    ),
+                    params![snapshot_id],
+                )
+                .map_err(|error| map_sqlite_error("删除程序记录失败", error))?;
+        }
+    }
+    Ok(invalid.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn keeps_recent_snapshots_per_program() {
+        let _guard = storage::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let root =
+            std::env::temp_dir().join(format!("rust-yu-snapshots-test-{}", uuid::Uuid::new_v4()));
+        std::env::set_var(storage::STORAGE_DIR_ENV, &root);
+
+        let mut program = InstalledProgram::new("Demo App".to_string(), InstallSource::Registry);
+        for version in 0..MAX_SNAPSHOTS_PER_PROGRAM + 2 {
+            program.version = Some(version.to_string());
+            assert!(save_snapshots(std::slice::from_ref(&program)).is_ok());
+        }
+        // 名称包含 "Demo" 的其他程序不能被误删
+        let other = InstalledProgram::new("Demo".to_string(), InstallSource::Registry);
+        assert!(save_snapshots(&[other]).is_ok());
+
+        let history = snapshot_history("demo app").unwrap_or_default();
+        assert_eq!(history.len(), MAX_SNAPSHOTS_PER_PROGRAM);
+        assert_eq!(
+            history[0].program.version.as_deref(),
+            Some((MAX_SNAPSHOTS_PER_PROGRAM + 1).to_string().as_str())
+        );
+
+        assert_eq!(delete_snapshots("Demo").unwrap_or_default(), 1);
+        let latest = find_latest_by_name("DEMO APP").unwrap_or_default();
+        assert!(latest.is_some());
+        assert_eq!(latest_snapshots().unwrap_or_default().len(), 1);
+
+        std::env::remove_var(storage::STORAGE_DIR_ENV);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,161 @@
+//! 程序清单快照
+//!
+//! 将完整的程序清单（含补全的元数据）导出为带版本号的 JSON 文件，
+//! 可在另一台机器或重装系统后导入，对比得出需要重新安装的程序。
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::models::InstalledProgram;
+use crate::modules::common::error::UninstallerError;
+
+/// 快照文件格式版本，字段有不兼容变化时递增
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// 程序清单快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventorySnapshot {
+    pub format_version: u32,
+    pub generated_at: String,
+    #[serde(default)]
+    pub computer_name: Option<String>,
+    pub programs: Vec<InstalledProgram>,
+}
+
+/// 版本发生变化的程序
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionChange {
+    pub name: String,
+    pub snapshot_version: Option<String>,
+    pub current_version: Option<String>,
+}
+
+/// 快照与当前清单的差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// 快照中有、当前没有（迁移时需要重新安装）
+    pub missing: Vec<InstalledProgram>,
+    /// 当前有、快照中没有
+    pub added: Vec<InstalledProgram>,
+    pub version_changed: Vec<VersionChange>,
+    pub unchanged: usize,
+}
+
+/// 由当前程序清单生成快照；图标 Data URL 体积大且可重新生成，不写入快照
+pub fn build_snapshot(programs: &[InstalledProgram]) -> InventorySnapshot {
+    let programs = programs
+        .iter()
+        .cloned()
+        .map(|mut program| {
+            program.icon_data_url = None;
+            program.icon_data_url_32 = None;
+            program.icon_data_url_48 = None;
+            program
+        })
+        .collect();
+
+    InventorySnapshot {
+        format_version: SNAPSHOT_FORMAT_VERSION,
+        generated_at: Utc::now().to_rfc3339(),
+        computer_name: std::env::var("COMPUTERNAME").ok(),
+        programs,
+    }
+}
+
+/// 写入快照文件
+pub fn write_snapshot(path: &Path, snapshot: &InventorySnapshot) -> Result<(), UninstallerError> {
+    let content = serde_json::to_string_pretty(snapshot)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// 读取快照文件，拒绝由更新版本生成的快照
+pub fn read_snapshot(path: &Path) -> Result<InventorySnapshot, UninstallerError> {
+    let content = std::fs::read_to_string(path)?;
+    let snapshot: InventorySnapshot = serde_json::from_str(&content)
+        .map_err(|error| UninstallerError::Serde(format!("快照格式无效: {}", error)))?;
+
+    if snapshot.format_version > SNAPSHOT_FORMAT_VERSION {
+        return Err(UninstallerError::Other(format!(
+            "快照格式版本 {} 高于当前支持的版本 {}，请升级 rust-yu",
+            snapshot.format_version, SNAPSHOT_FORMAT_VERSION
+        )));
+    }
+
+    Ok(snapshot)
+}
+
+/// 按程序名（不区分大小写）对比快照与当前清单
+pub fn compare(snapshot: &[InstalledProgram], current: &[InstalledProgram]) -> SnapshotDiff {
+    let current_by_name: HashMap<String, &InstalledProgram> = current
+        .iter()
+        .map(|program| (program.name.to_lowercase(), program))
+        .collect();
+    let snapshot_by_name: HashMap<String, &InstalledProgram> = snapshot
+        .iter()
+        .map(|program| (program.name.to_lowercase(), program))
+        .collect();
+
+    let mut diff = SnapshotDiff::default();
+    for program in snapshot {
+        match current_by_name.get(&program.name.to_lowercase()) {
+            None => diff.missing.push(program.clone()),
+            Some(installed) if installed.version != program.version => {
+                diff.version_changed.push(VersionChange {
+                    name: program.name.clone(),
+                    snapshot_version: program.version.clone(),
+                    current_version: installed.version.clone(),
+                })
+            }
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+
+    diff.added = current
+        .iter()
+        .filter(|program| !snapshot_by_name.contains_key(&program.name.to_lowercase()))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    fn program(name: &str, version: &str) -> InstalledProgram {
+        let mut program = InstalledProgram::new(name.to_string(), InstallSource::Registry);
+        program.version = Some(version.to_string());
+        program
+    }
+
+    #[test]
+    fn compare_reports_missing_added_and_upgraded() {
+        let snapshot = vec![
+            program("Git", "2.40"),
+            program("7-Zip", "23.01"),
+            program("VLC", "3.0"),
+        ];
+        let current = vec![
+            program("git", "2.45"),
+            program("7-Zip", "23.01"),
+            program("Node.js", "20"),
+        ];
+
+        let diff = compare(&snapshot, &current);
+        assert_eq!(diff.missing.len(), 1);
+        assert_eq!(diff.missing[0].name, "VLC");
+        assert_eq!(diff.added[0].name, "Node.js");
+        assert_eq!(
+            diff.version_changed[0].current_version.as_deref(),
+            Some("2.45")
+        );
+        assert_eq!(diff.unchanged, 1);
+    }
+}
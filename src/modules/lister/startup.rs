@@ -0,0 +1,359 @@
+//! 自启动项识别
+//!
+//! 枚举 Run 键、启动文件夹、登录计划任务和自动启动服务，
+//! 按命令路径是否位于安装目录下归属到程序，并据此估算对开机速度的影响。
+
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::models::{InstalledProgram, StartupEntry, StartupImpact, StartupKind};
+use crate::modules::common::shell_link;
+use crate::modules::scanner::roots;
+use crate::modules::uninstaller::validation;
+
+/// Run 键（相对于所在根键）
+const RUN_KEYS: &[&str] = &[
+    r"SOFTWARE\Microsoft\Windows\CurrentVersion\Run",
+    r"SOFTWARE\Microsoft\Windows\CurrentVersion\RunOnce",
+    r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Run",
+    r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\RunOnce",
+];
+
+/// 任务管理器"启动"页的启用状态
+const STARTUP_APPROVED_KEY: &str =
+    r"SOFTWARE\Microsoft\Windows\CurrentVersion\Explorer\StartupApproved";
+
+const SERVICES_KEY: &str = r"SYSTEM\CurrentControlSet\Services";
+/// 服务启动类型：自动
+const SERVICE_AUTO_START: u32 = 2;
+/// 服务类型：独立进程或共享进程（排除驱动）
+const SERVICE_WIN32_MASK: u32 = 0x30;
+
+const STARTUP_FOLDER: &str = r"Microsoft\Windows\Start Menu\Programs\Startup";
+const TASK_MAX_DEPTH: usize = 8;
+
+/// 枚举系统中所有已启用的自启动项
+pub fn collect_startup_entries() -> Vec<StartupEntry> {
+    let mut entries = Vec::new();
+    collect_run_entries(&mut entries, false);
+    collect_startup_folder_entries(&mut entries, false);
+    collect_logon_task_entries(&mut entries);
+    collect_service_entries(&mut entries);
+    tracing::debug!("发现 {} 个自启动项", entries.len());
+    entries
+}
+
+/// 枚举 Run/RunOnce 键和启动文件夹中的全部条目，包括任务管理器中已禁用的
+///
+/// 残留扫描使用：禁用的条目同样是卸载后留下的垃圾
+pub(crate) fn collect_autorun_entries() -> Vec<StartupEntry> {
+    let mut entries = Vec::new();
+    collect_run_entries(&mut entries, true);
+    collect_startup_folder_entries(&mut entries, true);
+    entries
+}
+
+/// 为一批程序关联自启动项并计算启动影响，自启动项只枚举一次
+pub fn enrich_startup(programs: &mut [InstalledProgram]) {
+    let entries = collect_startup_entries();
+    for program in programs {
+        program.startup_entries = match_startup_entries(program, &entries);
+        program.startup_impact = startup_impact(&program.startup_entries);
+    }
+}
+
+/// 找出属于该程序的自启动项：命令位于安装目录下，或名称与程序名一致
+pub fn match_startup_entries(
+    program: &InstalledProgram,
+    entries: &[StartupEntry],
+) -> Vec<StartupEntry> {
+    let location = roots::install_root(program);
+    let name = program.name.trim();
+
+    entries
+        .iter()
+        .filter(|entry| {
+            let in_location = location.as_deref().is_some_and(|root| {
+                validation::parse_executable(&entry.command)
+                    .is_some_and(|exe| exe.to_string_lossy().to_lowercase().starts_with(root))
+            });
+            in_location || (!name.is_empty() && entry.name.eq_ignore_ascii_case(name))
+        })
+        .cloned()
+        .collect()
+}
+
+/// 按自启动项数量估算影响，自动启动服务计两份
+pub fn startup_impact(entries: &[StartupEntry]) -> StartupImpact {
+    let score: usize = entries
+        .iter()
+        .map(|entry| match entry.kind {
+            StartupKind::Service => 2,
+            _ => 1,
+        })
+        .sum();
+
+    match score {
+        0 => StartupImpact::None,
+        1 => StartupImpact::Low,
+        2 | 3 => StartupImpact::Medium,
+        _ => StartupImpact::High,
+    }
+}
+
+fn collect_run_entries(entries: &mut Vec<StartupEntry>, include_disabled: bool) {
+    for (hkey, root) in [(HKEY_LOCAL_MACHINE, "HKLM"), (HKEY_CURRENT_USER, "HKCU")] {
+        for subkey in RUN_KEYS {
+            let Ok(key) = RegKey::predef(hkey).open_subkey(subkey) else {
+                continue;
+            };
+            let approved = if subkey.contains("WOW6432Node") {
+                "Run32"
+            } else {
+                "Run"
+            };
+
+            for (name, _) in key.enum_values().filter_map(|value| value.ok()) {
+                if !include_disabled && is_disabled_in_task_manager(hkey, approved, &name) {
+                    continue;
+                }
+                let Ok(command) = key.get_value::<String, _>(&name) else {
+                    continue;
+                };
+                entries.push(StartupEntry {
+                    kind: StartupKind::RunKey,
+                    name,
+                    command,
+                    location: format!(r"{}\{}", root, subkey),
+                });
+            }
+        }
+    }
+}
+
+/// 任务管理器中被禁用的启动项：StartupApproved 值首字节为奇数
+fn is_disabled_in_task_manager(hkey: winreg::HKEY, category: &str, name: &str) -> bool {
+    RegKey::predef(hkey)
+        .open_subkey(format!(r"{}\{}", STARTUP_APPROVED_KEY, category))
+        .and_then(|key| key.get_raw_value(name))
+        .ok()
+        .and_then(|value| value.bytes.first().copied())
+        .is_some_and(|flag| flag & 1 == 1)
+}
+
+fn collect_startup_folder_entries(entries: &mut Vec<StartupEntry>, include_disabled: bool) {
+    let folders = [
+        (HKEY_CURRENT_USER, std::env::var("APPDATA")),
+        (HKEY_LOCAL_MACHINE, std::env::var("ProgramData")),
+    ];
+
+    for (hkey, base) in folders {
+        let Ok(base) = base else {
+            continue;
+        };
+        let Ok(read_dir) = std::fs::read_dir(Path::new(&base).join(STARTUP_FOLDER)) else {
+            continue;
+        };
+
+        for file in read_dir.filter_map(|entry| entry.ok()) {
+            let path = file.path();
+            let file_name = file.file_name().to_string_lossy().to_string();
+            if !path.is_file() || file_name.eq_ignore_ascii_case("desktop.ini") {
+                continue;
+            }
+            if !include_disabled && is_disabled_in_task_manager(hkey, "StartupFolder", &file_name) {
+                continue;
+            }
+
+            let is_link = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"));
+            let command = if is_link {
+                shell_link::resolve_target(&path).unwrap_or_default()
+            } else {
+                path.to_string_lossy().to_string()
+            };
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or(file_name);
+
+            entries.push(StartupEntry {
+                kind: StartupKind::StartupFolder,
+                name,
+                command,
+                location: path.to_string_lossy().to_string(),
+            });
+        }
+    }
+}
+
+/// 登录或开机触发的计划任务；系统自带的 `\Microsoft` 目录不参与
+fn collect_logon_task_entries(entries: &mut Vec<StartupEntry>) {
+    let Ok(system_root) = std::env::var("SystemRoot") else {
+        return;
+    };
+    let tasks_dir = PathBuf::from(system_root).join(r"System32\Tasks");
+
+    let walker = WalkDir::new(&tasks_dir)
+        .max_depth(TASK_MAX_DEPTH)
+        .into_iter()
+        .filter_entry(|entry| {
+            !(entry.depth() == 1
+                && entry.file_type().is_dir()
+                && entry.file_name().eq_ignore_ascii_case("Microsoft"))
+        });
+
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(xml) = read_task_xml(entry.path()) else {
+            continue;
+        };
+        if !is_enabled_logon_task(&xml) {
+            continue;
+        }
+
+        let task_path = entry
+            .path()
+            .strip_prefix(&tasks_dir)
+            .map(|relative| format!(r"\{}", relative.to_string_lossy()))
+            .unwrap_or_else(|_| entry.path().to_string_lossy().to_string());
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        for command in xml_element_texts(&xml, "Command") {
+            entries.push(StartupEntry {
+                kind: StartupKind::LogonTask,
+                name: name.clone(),
+                command,
+                location: task_path.clone(),
+            });
+        }
+    }
+}
+
+/// 任务文件通常为带 BOM 的 UTF-16 LE
+fn read_task_xml(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    if let Some(body) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let units: Vec<u16> = body
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        return Some(String::from_utf16_lossy(&units));
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn is_enabled_logon_task(xml: &str) -> bool {
+    let has_trigger = xml.contains("<LogonTrigger") || xml.contains("<BootTrigger");
+    let disabled = xml
+        .split_once("<Settings>")
+        .and_then(|(_, rest)| rest.split_once("</Settings>"))
+        .is_some_and(|(settings, _)| settings.contains("<Enabled>false</Enabled>"));
+    has_trigger && !disabled
+}
+
+fn xml_element_texts(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut values = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        let value = rest[..end]
+            .trim()
+            .replace("&amp;", "&")
+            .replace("&quot;", "\"");
+        if !value.is_empty() {
+            values.push(value);
+        }
+        rest = &rest[end + close.len()..];
+    }
+
+    values
+}
+
+fn collect_service_entries(entries: &mut Vec<StartupEntry>) {
+    let Ok(services) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(SERVICES_KEY) else {
+        return;
+    };
+
+    for name in services.enum_keys().filter_map(|name| name.ok()) {
+        let Ok(service) = services.open_subkey(&name) else {
+            continue;
+        };
+        let start = service.get_value::<u32, _>("Start").unwrap_or(u32::MAX);
+        let service_type = service.get_value::<u32, _>("Type").unwrap_or(0);
+        if start != SERVICE_AUTO_START || service_type & SERVICE_WIN32_MASK == 0 {
+            continue;
+        }
+        let Ok(command) = service.get_value::<String, _>("ImagePath") else {
+            continue;
+        };
+
+        entries.push(StartupEntry {
+            kind: StartupKind::Service,
+            location: format!(r"HKLM\{}\{}", SERVICES_KEY, name),
+            name,
+            command,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    fn entry(kind: StartupKind, name: &str, command: &str) -> StartupEntry {
+        StartupEntry {
+            kind,
+            name: name.to_string(),
+            command: command.to_string(),
+            location: String::new(),
+        }
+    }
+
+    #[test]
+    fn matches_entries_under_install_location() {
+        let mut program = InstalledProgram::new("Foo Sync".to_string(), InstallSource::Registry);
+        program.install_location = Some(r"C:\Program Files\Foo\".to_string());
+
+        let entries = vec![
+            entry(
+                StartupKind::RunKey,
+                "FooTray",
+                r#""C:\Program Files\Foo\tray.exe" /background"#,
+            ),
+            entry(
+                StartupKind::Service,
+                "FooUpdate",
+                r"C:\Program Files\Foo\update.exe -service",
+            ),
+            entry(
+                StartupKind::RunKey,
+                "Foobar",
+                r"C:\Program Files\Foobar\bar.exe",
+            ),
+            entry(StartupKind::StartupFolder, "Foo Sync", ""),
+        ];
+
+        let matched = match_startup_entries(&program, &entries);
+        let names: Vec<&str> = matched.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["FooTray", "FooUpdate", "Foo Sync"]);
+        assert_eq!(startup_impact(&matched), StartupImpact::High);
+        assert_eq!(startup_impact(&matched[..1]), StartupImpact::Low);
+
+        program.install_location = Some(r"C:\Program Files".to_string());
+        assert_eq!(match_startup_entries(&program, &entries[..3]).len(), 0);
+    }
+}
@@ -3,27 +3,45 @@
 //! 用于：
 //! - 在卸载程序前保存注册表信息（供卸载后搜索残留）
 //! - 使用 SQLite 缓存安装软件扫描结果，减少重复全量扫描
+//! - 记录曾经出现过的程序（安装历史），用于发现在 rust-yu 之外卸载的程序
 
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, TransactionBehavior};
 
 use crate::modules::common::error::UninstallerError;
-
-use super::models::InstalledProgram;
-
-const STORAGE_DIR_ENV: &str = "RUST_YU_STORAGE_DIR";
-const SNAPSHOT_FILE_NAME: &str = "programs.json";
-const SCAN_CACHE_DB_FILE_NAME: &str = "installed_programs_cache_v4.sqlite3";
+use crate::modules::common::utils;
+
+use super::integrity::{self, IntegrityReport};
+use super::migrations;
+use super::models::{InstalledProgram, ProgramHistoryEntry};
+use super::pool::{self, PooledConnection};
+use super::saved_programs;
+
+pub(super) const STORAGE_DIR_ENV: &str = "RUST_YU_STORAGE_DIR";
+pub(super) const HISTORY_FILE_NAME: &str = "program_history.json";
+pub(super) const SCAN_CACHE_DB_FILE_NAME: &str = "installed_programs_cache.sqlite3";
+/// 引入结构迁移前按版本命名的缓存库，最新的一个会被改名沿用，其余删除
+const LEGACY_SCAN_CACHE_DB_FILE_NAMES: &[&str] = &[
+    "installed_programs_cache_v4.sqlite3",
+    "installed_programs_cache_v3.sqlite3",
+    "installed_programs_cache_v2.sqlite3",
+    "installed_programs_cache_v1.sqlite3",
+];
+/// SQLite WAL 模式下与数据库文件同名的附属文件后缀
+pub(super) const SQLITE_SIDECAR_SUFFIXES: &[&str] = &["-wal", "-shm"];
+/// 标签、备注等用户数据，缓存失效时不能随缓存库一起删除
+pub(super) const USER_DATA_DB_FILE_NAME: &str = "user_data.sqlite3";
 const ICON_CACHE_DIR_NAME: &str = "icon-cache";
-const CACHE_TABLE_NAME: &str = "installed_programs_cache";
+pub(super) const CACHE_TABLE_NAME: &str = "installed_programs_cache";
 const CACHE_METADATA_TABLE_NAME: &str = "cache_metadata";
 const META_KEY_SCHEMA_VERSION: &str = "schema_version";
 const META_KEY_GENERATED_AT: &str = "generated_at";
-pub const CACHE_SCHEMA_VERSION: u32 = 4;
+pub const CACHE_SCHEMA_VERSION: u32 = 13;
 pub const DEFAULT_CACHE_TTL_SECONDS: i64 = 900;
 
 #[cfg(test)]
@@ -34,6 +52,8 @@ pub struct ScanCacheReadResult {
     pub entries: Option<Vec<InstalledProgram>>,
     pub cache_hit: bool,
     pub cache_valid: bool,
+    /// 缓存已过期但仍携带旧数据，可先返回再后台刷新
+    pub stale: bool,
     pub schema_version: u32,
     pub generated_at: Option<String>,
     pub reason: Option<String>,
@@ -45,6 +65,7 @@ impl Default for ScanCacheReadResult {
             entries: None,
             cache_hit: false,
             cache_valid: false,
+            stale: false,
             schema_version: CACHE_SCHEMA_VERSION,
             generated_at: None,
             reason: None,
@@ -71,14 +92,58 @@ pub fn get_storage_root_dir() -> Result<PathBuf, UninstallerError> {
     get_storage_dir()
 }
 
-/// 获取程序快照文件路径
-fn get_snapshot_file() -> Result<PathBuf, UninstallerError> {
-    Ok(get_storage_dir()?.join(SNAPSHOT_FILE_NAME))
+/// 获取安装历史文件路径
+fn get_history_file() -> Result<PathBuf, UninstallerError> {
+    Ok(get_storage_dir()?.join(HISTORY_FILE_NAME))
 }
 
 /// 获取扫描缓存 SQLite 文件路径
 fn get_scan_cache_file() -> Result<PathBuf, UninstallerError> {
-    Ok(get_storage_dir()?.join(SCAN_CACHE_DB_FILE_NAME))
+    let storage_dir = get_storage_dir()?;
+    adopt_legacy_cache_files(&storage_dir);
+    Ok(storage_dir.join(SCAN_CACHE_DB_FILE_NAME))
+}
+
+/// 沿用旧文件名的缓存库（打开时再迁移结构），并删除更早的版本
+fn adopt_legacy_cache_files(storage_dir: &Path) {
+    let target = storage_dir.join(SCAN_CACHE_DB_FILE_NAME);
+    let mut adopted = target.exists();
+
+    for legacy_name in LEGACY_SCAN_CACHE_DB_FILE_NAMES {
+        let legacy = storage_dir.join(legacy_name);
+        if !legacy.exists() {
+            continue;
+        }
+
+        if !adopted {
+            let renamed = std::iter::once("")
+                .chain(SQLITE_SIDECAR_SUFFIXES.iter().copied())
+                .all(|suffix| {
+                    let from = sidecar_path(&legacy, suffix);
+                    !from.exists() || std::fs::rename(&from, sidecar_path(&target, suffix)).is_ok()
+                });
+            if renamed {
+                tracing::info!("沿用旧版缓存数据库: {}", legacy_name);
+                adopted = true;
+                continue;
+            }
+        }
+
+        for suffix in std::iter::once("").chain(SQLITE_SIDECAR_SUFFIXES.iter().copied()) {
+            let path = sidecar_path(&legacy, suffix);
+            if path.exists() {
+                if let Err(error) = std::fs::remove_file(&path) {
+                    tracing::warn!("删除旧版缓存文件失败 {}: {}", path.display(), error);
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn sidecar_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut path = db_path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
 }
 
 /// 获取扫描缓存文件路径（逻辑上等价于缓存数据库路径）
@@ -93,52 +158,36 @@ pub fn get_icon_cache_dir() -> Result<PathBuf, UninstallerError> {
     Ok(icon_cache_dir)
 }
 
-fn map_sqlite_error(context: &str, error: rusqlite::Error) -> UninstallerError {
+pub(crate) fn map_sqlite_error(context: &str, error: rusqlite::Error) -> UninstallerError {
     UninstallerError::Other(format!("{context}: {error}"))
 }
 
-fn open_scan_cache_connection() -> Result<Connection, UninstallerError> {
+fn open_scan_cache_connection() -> Result<PooledConnection, UninstallerError> {
     let db_path = get_scan_cache_file()?;
-    let connection = Connection::open(&db_path)
-        .map_err(|error| map_sqlite_error("打开缓存数据库失败", error))?;
-
-    connection
-        .execute_batch(&format!(
-            r#"
-            PRAGMA journal_mode=WAL;
-            PRAGMA synchronous=NORMAL;
-            CREATE TABLE IF NOT EXISTS {cache_table} (
-                cache_key TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                publisher TEXT,
-                version TEXT,
-                install_date TEXT,
-                install_location TEXT,
-                uninstall_string TEXT,
-                install_source TEXT NOT NULL,
-                size_bytes INTEGER,
-                estimated_size_bytes INTEGER,
-                icon_path TEXT,
-                icon_cache_path_32 TEXT,
-                icon_cache_path_48 TEXT,
-                size_last_updated_at TEXT,
-                payload_json TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_installed_programs_cache_name
-                ON {cache_table}(name);
-            CREATE TABLE IF NOT EXISTS {metadata_table} (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            );
-            "#,
-            cache_table = CACHE_TABLE_NAME,
-            metadata_table = CACHE_METADATA_TABLE_NAME
-        ))
-        .map_err(|error| map_sqlite_error("初始化缓存数据库结构失败", error))?;
+    pool::acquire(&db_path, |connection| {
+        connection
+            .execute_batch(
+                r#"
+                PRAGMA journal_mode=WAL;
+                PRAGMA synchronous=NORMAL;
+                "#,
+            )
+            .map_err(|error| map_sqlite_error("初始化缓存数据库失败", error))?;
+        // 多个进程同时打开旧库时只由一个执行迁移
+        let _lock = pool::lock_for_write(&db_path)?;
+        migrations::migrate(connection)?;
+        Ok(())
+    })
+}
 
-    Ok(connection)
+/// 打开用户数据库，各模块自行建表
+pub(super) fn open_user_data_connection() -> Result<PooledConnection, UninstallerError> {
+    let db_path = get_storage_dir()?.join(USER_DATA_DB_FILE_NAME);
+    pool::acquire(&db_path, |connection| {
+        connection
+            .pragma_update(None, "journal_mode", "WAL")
+            .map_err(|error| map_sqlite_error("初始化用户数据库失败", error))
+    })
 }
 
 fn read_cache_metadata(
@@ -174,21 +223,24 @@ fn write_cache_metadata(
     key: &str,
     value: &str,
 ) -> Result<(), UninstallerError> {
-    connection
-        .execute(
-            &format!(
-                "INSERT INTO {} (key, value, updated_at)
-                 VALUES (?1, ?2, ?3)
-                 ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
-                CACHE_METADATA_TABLE_NAME
-            ),
-            params![key, value, Utc::now().to_rfc3339()],
-        )
-        .map_err(|error| map_sqlite_error("写入缓存元数据失败", error))?;
+    upsert_cache_metadata(connection, key, value)
+        .map_err(|error| map_sqlite_error("写入缓存元数据失败", error))
+}
+
+fn upsert_cache_metadata(connection: &Connection, key: &str, value: &str) -> rusqlite::Result<()> {
+    connection.execute(
+        &format!(
+            "INSERT INTO {} (key, value, updated_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            CACHE_METADATA_TABLE_NAME
+        ),
+        params![key, value, Utc::now().to_rfc3339()],
+    )?;
     Ok(())
 }
 
-fn build_program_cache_key(program: &InstalledProgram) -> String {
+pub(super) fn build_program_cache_key(program: &InstalledProgram) -> String {
     let mut hasher = DefaultHasher::new();
     program.name.to_lowercase().hash(&mut hasher);
     program
@@ -213,175 +265,255 @@ fn build_program_cache_key(program: &InstalledProgram) -> String {
     format!("{:016x}", hasher.finish())
 }
 
-/// 保存程序快照
+/// 保存程序快照（卸载前的程序记录）
 pub fn save_program_snapshot(programs: &[InstalledProgram]) -> Result<(), UninstallerError> {
-    let path = get_snapshot_file()?;
+    saved_programs::save_snapshots(programs)
+}
 
-    let mut all_programs: Vec<InstalledProgram> = if path.exists() {
-        let content = std::fs::read_to_string(&path)?;
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        Vec::new()
+/// 获取所有保存的程序，每个程序取最新的一条记录
+pub fn get_saved_programs() -> Result<Vec<InstalledProgram>, UninstallerError> {
+    saved_programs::latest_snapshots()
+}
+
+/// 根据名称获取保存的程序记录
+///
+/// 名称或别名完全一致优先，其次取模糊匹配分数最高的一条
+pub fn get_saved_program(name: &str) -> Result<Option<InstalledProgram>, UninstallerError> {
+    if let Some(program) = saved_programs::find_latest_by_name(name)? {
+        return Ok(Some(program));
+    }
+
+    let name_lower = name.trim().to_lowercase();
+    let records = get_saved_program_records()?;
+
+    let is_exact = |program: &InstalledProgram| {
+        program.name.to_lowercase() == name_lower
+            || program
+                .aliases
+                .iter()
+                .any(|alias| alias.to_lowercase() == name_lower)
     };
+    if let Some(program) = records.iter().find(|program| is_exact(program)) {
+        return Ok(Some(program.clone()));
+    }
 
-    for program in programs {
-        all_programs.retain(|p| p.name.to_lowercase() != program.name.to_lowercase());
-        all_programs.push(program.clone());
+    Ok(rank_saved_programs(records, &name_lower).into_iter().next())
+}
+
+/// 卸载前快照、安装历史和扫描缓存中保存的程序记录
+///
+/// 同名程序只保留第一条：卸载前快照最完整，其次是安装历史，最后是扫描缓存（过期数据同样可用）
+pub fn get_saved_program_records() -> Result<Vec<InstalledProgram>, UninstallerError> {
+    let mut records = get_saved_programs()?;
+    records.extend(
+        get_program_history()?
+            .into_iter()
+            .map(|entry| entry.program),
+    );
+    match read_scan_cache(i64::MAX) {
+        Ok(cached) => records.extend(cached.entries.unwrap_or_default()),
+        Err(error) => tracing::debug!("读取扫描缓存失败: {}", error),
     }
 
-    let content = serde_json::to_string_pretty(&all_programs)
-        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
-    std::fs::write(&path, content)?;
+    let mut seen = HashSet::new();
+    records.retain(|program| seen.insert(program.name.to_lowercase()));
+    Ok(records)
+}
 
-    tracing::info!("已保存 {} 个程序信息到快照", programs.len());
+/// 按名称、别名和发布者的模糊匹配分数排序，名称命中优先于发布者命中
+fn rank_saved_programs(records: Vec<InstalledProgram>, query_lower: &str) -> Vec<InstalledProgram> {
+    let mut scored: Vec<(i64, InstalledProgram)> = records
+        .into_iter()
+        .filter_map(|program| {
+            let name_score = std::iter::once(&program.name)
+                .chain(program.aliases.iter())
+                .filter_map(|name| utils::fuzzy_score(name, query_lower))
+                .max();
+            let publisher_score = program
+                .publisher
+                .as_deref()
+                .and_then(|publisher| utils::fuzzy_score(publisher, query_lower))
+                .map(|score| score / 2);
+            name_score
+                .max(publisher_score)
+                .map(|score| (score, program))
+        })
+        .collect();
+
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, program)| program).collect()
+}
+
+/// 删除保存的程序信息，名称需完全一致（不区分大小写）
+pub fn delete_saved_program(name: &str) -> Result<(), UninstallerError> {
+    saved_programs::delete_snapshots(name)?;
     Ok(())
 }
 
-/// 获取所有保存的程序
+/// 在保存的程序记录中搜索，用于已卸载程序
 #[allow(dead_code)]
-pub fn get_saved_programs() -> Result<Vec<InstalledProgram>, UninstallerError> {
-    let path = get_snapshot_file()?;
-
-    if !path.exists() {
+pub fn search_programs_with_fallback(
+    query: &str,
+) -> Result<Vec<InstalledProgram>, UninstallerError> {
+    let query_lower = query.trim().to_lowercase();
+    if query_lower.is_empty() {
         return Ok(Vec::new());
     }
-
-    let content = std::fs::read_to_string(&path)?;
-    let programs: Vec<InstalledProgram> = serde_json::from_str(&content).unwrap_or_default();
-
-    Ok(programs)
+    Ok(rank_saved_programs(
+        get_saved_program_records()?,
+        &query_lower,
+    ))
 }
 
-/// 根据名称获取保存的程序
-#[allow(dead_code)]
-pub fn get_saved_program(name: &str) -> Result<Option<InstalledProgram>, UninstallerError> {
-    let programs = get_saved_programs()?;
-    let name_lower = name.to_lowercase();
+/// 合并本次枚举到的程序到安装历史
+pub fn record_program_history(programs: &[InstalledProgram]) -> Result<(), UninstallerError> {
+    let mut history = get_program_history()?;
+    let now = Utc::now().to_rfc3339();
 
-    Ok(programs
-        .into_iter()
-        .find(|program| program.name.to_lowercase().contains(&name_lower)))
+    for program in programs {
+        // 图标数据较大且与历史无关，不写入历史文件
+        let mut stored = program.clone();
+        stored.icon_data_url = None;
+        stored.icon_data_url_32 = None;
+        stored.icon_data_url_48 = None;
+
+        let name_lower = program.name.to_lowercase();
+        match history
+            .iter_mut()
+            .find(|entry| entry.program.name.to_lowercase() == name_lower)
+        {
+            Some(entry) => {
+                entry.program = stored;
+                entry.last_seen_at = now.clone();
+            }
+            None => history.push(ProgramHistoryEntry {
+                program: stored,
+                first_seen_at: now.clone(),
+                last_seen_at: now.clone(),
+            }),
+        }
+    }
+
+    let content = serde_json::to_string_pretty(&history)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    std::fs::write(get_history_file()?, content)?;
+    Ok(())
 }
 
-/// 删除保存的程序信息
-pub fn delete_saved_program(name: &str) -> Result<(), UninstallerError> {
-    let path = get_snapshot_file()?;
+/// 读取安装历史
+pub fn get_program_history() -> Result<Vec<ProgramHistoryEntry>, UninstallerError> {
+    let path = get_history_file()?;
 
     if !path.exists() {
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     let content = std::fs::read_to_string(&path)?;
-    let mut programs: Vec<InstalledProgram> = serde_json::from_str(&content).unwrap_or_default();
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
 
+/// 从安装历史中移除程序
+pub fn delete_program_history(name: &str) -> Result<(), UninstallerError> {
     let name_lower = name.to_lowercase();
-    programs.retain(|program| !program.name.to_lowercase().contains(&name_lower));
+    let mut history = get_program_history()?;
+    history.retain(|entry| entry.program.name.to_lowercase() != name_lower);
 
-    let content = serde_json::to_string_pretty(&programs)
+    let content = serde_json::to_string_pretty(&history)
         .map_err(|error| UninstallerError::Serde(error.to_string()))?;
-    std::fs::write(&path, content)?;
-
+    std::fs::write(get_history_file()?, content)?;
     Ok(())
 }
 
-/// 搜索时优先查询保存的数据
-#[allow(dead_code)]
-pub fn search_programs_with_fallback(
-    query: &str,
-) -> Result<Vec<InstalledProgram>, UninstallerError> {
-    let saved = get_saved_programs()?;
-    let query_lower = query.to_lowercase();
-
-    let matched: Vec<InstalledProgram> = saved
-        .into_iter()
-        .filter(|program| {
-            program.name.to_lowercase().contains(&query_lower)
-                || program
-                    .publisher
-                    .as_ref()
-                    .map(|publisher| publisher.to_lowercase().contains(&query_lower))
-                    .unwrap_or(false)
-        })
-        .collect();
-
-    Ok(matched)
-}
-
 /// 保存扫描缓存（SQLite）
+///
+/// 持有跨进程写锁整表替换，其他进程或线程正在写入时等待并重试
 pub fn save_scan_cache(entries: &[InstalledProgram]) -> Result<(), UninstallerError> {
+    let payloads = entries
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    let now = Utc::now().to_rfc3339();
+
     let mut connection = open_scan_cache_connection()?;
-    let transaction = connection
-        .transaction()
-        .map_err(|error| map_sqlite_error("开启缓存事务失败", error))?;
+    let _lock = pool::lock_for_write(&get_scan_cache_file()?)?;
+    pool::retry_busy(|| replace_cache_rows(&mut connection, entries, &payloads, &now))
+        .map_err(|error| map_sqlite_error("写入缓存失败", error))?;
 
-    transaction
-        .execute(&format!("DELETE FROM {}", CACHE_TABLE_NAME), [])
-        .map_err(|error| map_sqlite_error("清空旧缓存失败", error))?;
+    // 每次全量枚举都顺带更新安装历史，历史写入失败不影响缓存
+    if let Err(error) = record_program_history(entries) {
+        tracing::warn!("更新安装历史失败: {}", error);
+    }
 
-    let now = Utc::now().to_rfc3339();
+    Ok(())
+}
+
+fn replace_cache_rows(
+    connection: &mut Connection,
+    entries: &[InstalledProgram],
+    payloads: &[String],
+    now: &str,
+) -> rusqlite::Result<()> {
+    // 立即获取写锁，避免读事务升级为写事务时与其他写入者互相等待
+    let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    transaction.execute(&format!("DELETE FROM {}", CACHE_TABLE_NAME), [])?;
 
     {
-        let mut statement = transaction
-            .prepare(&format!(
-                "INSERT INTO {} (
-                    cache_key,
-                    name,
-                    publisher,
-                    version,
-                    install_date,
-                    install_location,
-                    uninstall_string,
-                    install_source,
-                    size_bytes,
-                    estimated_size_bytes,
-                    icon_path,
-                    icon_cache_path_32,
-                    icon_cache_path_48,
-                    size_last_updated_at,
-                    payload_json,
-                    updated_at
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
-                CACHE_TABLE_NAME
-            ))
-            .map_err(|error| map_sqlite_error("准备写入缓存失败", error))?;
-
-        for program in entries {
-            let payload_json = serde_json::to_string(program)
-                .map_err(|error| UninstallerError::Serde(error.to_string()))?;
-            statement
-                .execute(params![
-                    build_program_cache_key(program),
-                    program.name,
-                    program.publisher,
-                    program.version,
-                    program.install_date,
-                    program.install_location,
-                    program.uninstall_string,
-                    program.install_source.to_string(),
-                    program.size,
-                    program.estimated_size,
-                    program.icon_path,
-                    program.icon_cache_path_32,
-                    program.icon_cache_path_48,
-                    program.size_last_updated_at,
-                    payload_json,
-                    now,
-                ])
-                .map_err(|error| map_sqlite_error("写入缓存记录失败", error))?;
+        let mut statement = transaction.prepare(&format!(
+            "INSERT INTO {} (
+                cache_key,
+                name,
+                publisher,
+                version,
+                install_date,
+                install_location,
+                uninstall_string,
+                install_source,
+                size_bytes,
+                estimated_size_bytes,
+                icon_path,
+                icon_cache_path_32,
+                icon_cache_path_48,
+                size_last_updated_at,
+                payload_json,
+                updated_at,
+                installer_kind,
+                install_scope
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                json_extract(?15, '$.installer_kind'), json_extract(?15, '$.install_scope'))",
+            CACHE_TABLE_NAME
+        ))?;
+
+        for (program, payload_json) in entries.iter().zip(payloads) {
+            statement.execute(params![
+                build_program_cache_key(program),
+                program.name,
+                program.publisher,
+                program.version,
+                program.install_date,
+                program.install_location,
+                program.uninstall_string,
+                program.install_source.to_string(),
+                program.size,
+                program.estimated_size,
+                program.icon_path,
+                program.icon_cache_path_32,
+                program.icon_cache_path_48,
+                program.size_last_updated_at,
+                payload_json,
+                now,
+            ])?;
         }
     }
 
-    write_cache_metadata(
+    upsert_cache_metadata(
         &transaction,
         META_KEY_SCHEMA_VERSION,
         &CACHE_SCHEMA_VERSION.to_string(),
     )?;
-    write_cache_metadata(&transaction, META_KEY_GENERATED_AT, &now)?;
+    upsert_cache_metadata(&transaction, META_KEY_GENERATED_AT, now)?;
 
-    transaction
-        .commit()
-        .map_err(|error| map_sqlite_error("提交缓存事务失败", error))?;
-    Ok(())
+    transaction.commit()
 }
 
 /// 读取扫描缓存（包含有效性校验）
@@ -394,13 +526,32 @@ pub fn read_scan_cache(ttl_seconds: i64) -> Result<ScanCacheReadResult, Uninstal
         });
     }
 
-    let connection = open_scan_cache_connection()?;
+    let mut connection = open_scan_cache_connection()?;
 
-    let schema_version = read_cache_metadata(&connection, META_KEY_SCHEMA_VERSION)?
+    let mut schema_version = read_cache_metadata(&connection, META_KEY_SCHEMA_VERSION)?
         .and_then(|value| value.parse::<u32>().ok())
         .unwrap_or_default();
     let generated_at = read_cache_metadata(&connection, META_KEY_GENERATED_AT)?;
 
+    // 旧版本写入的记录原地升级后按过期处理：先返回已有数据，再后台刷新派生字段
+    let upgraded = schema_version > 0 && schema_version < CACHE_SCHEMA_VERSION;
+    if upgraded {
+        let _lock = pool::lock_for_write(&cache_db_path)?;
+        let count = migrations::upgrade_payloads(&mut connection)?;
+        write_cache_metadata(
+            &connection,
+            META_KEY_SCHEMA_VERSION,
+            &CACHE_SCHEMA_VERSION.to_string(),
+        )?;
+        tracing::info!(
+            "缓存记录已从版本 {} 升级到 {}: {} 条",
+            schema_version,
+            CACHE_SCHEMA_VERSION,
+            count
+        );
+        schema_version = CACHE_SCHEMA_VERSION;
+    }
+
     if schema_version != CACHE_SCHEMA_VERSION {
         return Ok(ScanCacheReadResult {
             schema_version,
@@ -434,19 +585,60 @@ pub fn read_scan_cache(ttl_seconds: i64) -> Result<ScanCacheReadResult, Uninstal
     };
 
     let ttl = ttl_seconds.max(1);
-    if Utc::now()
-        .signed_duration_since(generated_at_time)
-        .num_seconds()
-        > ttl
-    {
+    let expired = upgraded
+        || Utc::now()
+            .signed_duration_since(generated_at_time)
+            .num_seconds()
+            > ttl;
+    let expired_reason = if upgraded {
+        "schema_upgraded"
+    } else {
+        "cache_expired"
+    };
+
+    let programs = read_cache_entries(&connection)?;
+
+    if programs.is_empty() {
         return Ok(ScanCacheReadResult {
             schema_version,
             generated_at: Some(generated_at_value),
-            reason: Some("cache_expired".to_string()),
+            reason: Some(
+                if expired {
+                    expired_reason
+                } else {
+                    "cache_empty"
+                }
+                .to_string(),
+            ),
             ..ScanCacheReadResult::default()
         });
     }
 
+    if expired {
+        // 过期数据仍然返回，由调用方决定是否先展示旧数据再后台刷新
+        return Ok(ScanCacheReadResult {
+            entries: Some(programs),
+            cache_hit: true,
+            cache_valid: false,
+            stale: true,
+            schema_version,
+            generated_at: Some(generated_at_value),
+            reason: Some(expired_reason.to_string()),
+        });
+    }
+
+    Ok(ScanCacheReadResult {
+        entries: Some(programs),
+        cache_hit: true,
+        cache_valid: true,
+        stale: false,
+        schema_version,
+        generated_at: Some(generated_at_value),
+        reason: None,
+    })
+}
+
+fn read_cache_entries(connection: &Connection) -> Result<Vec<InstalledProgram>, UninstallerError> {
     let mut statement = connection
         .prepare(&format!(
             "SELECT payload_json FROM {} ORDER BY name COLLATE NOCASE",
@@ -471,28 +663,22 @@ pub fn read_scan_cache(ttl_seconds: i64) -> Result<ScanCacheReadResult, Uninstal
         }
     }
 
-    if programs.is_empty() {
-        return Ok(ScanCacheReadResult {
-            schema_version,
-            generated_at: Some(generated_at_value),
-            reason: Some("cache_empty".to_string()),
-            ..ScanCacheReadResult::default()
-        });
-    }
+    Ok(programs)
+}
 
-    Ok(ScanCacheReadResult {
-        entries: Some(programs),
-        cache_hit: true,
-        cache_valid: true,
-        schema_version,
-        generated_at: Some(generated_at_value),
-        reason: None,
-    })
+/// 检查扫描缓存、程序记录、安装历史、图标缓存和报告目录的完整性
+///
+/// `repair` 为 true 时修复发现的问题：损坏的缓存库直接删除等待重建，
+/// 损坏的用户数据库和历史文件改名备份，悬空引用和孤立文件删除
+pub fn verify(repair: bool) -> Result<IntegrityReport, UninstallerError> {
+    integrity::verify_storage(repair)
 }
 
 /// 使扫描缓存失效
 pub fn invalidate_scan_cache() -> Result<(), UninstallerError> {
     let path = get_scan_cache_file()?;
+    // 先关闭空闲连接，否则 Windows 上无法删除仍被打开的文件
+    pool::close_idle(&path);
     if path.exists() {
         std::fs::remove_file(path)?;
     }
@@ -618,6 +804,84 @@ mod tests {
         cleanup_storage_root(&root);
     }
 
+    #[test]
+    fn legacy_cache_file_is_adopted_and_upgraded_in_place() {
+        let _guard = super::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let root = with_storage_root("legacy");
+
+        // 旧版缓存：按版本命名的文件、没有新增列、记录由旧版本写入
+        let legacy_path = root.join(LEGACY_SCAN_CACHE_DB_FILE_NAMES[0]);
+        let legacy = Connection::open(&legacy_path).unwrap_or_else(|_| panic!("open db failed"));
+        assert!(legacy
+            .execute_batch(
+                "CREATE TABLE installed_programs_cache (
+                    cache_key TEXT PRIMARY KEY, name TEXT NOT NULL, publisher TEXT, version TEXT,
+                    install_date TEXT, install_location TEXT, uninstall_string TEXT,
+                    install_source TEXT NOT NULL, size_bytes INTEGER, estimated_size_bytes INTEGER,
+                    icon_path TEXT, icon_cache_path_32 TEXT, icon_cache_path_48 TEXT,
+                    size_last_updated_at TEXT, payload_json TEXT NOT NULL, updated_at TEXT NOT NULL);
+                 CREATE TABLE cache_metadata (
+                    key TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at TEXT NOT NULL);"
+            )
+            .is_ok());
+        let mut program = InstalledProgram::new("DemoLegacy".to_string(), InstallSource::Registry);
+        program.size_last_updated_at = Some("2024-01-01T00:00:00+00:00".to_string());
+        let payload = serde_json::to_string(&program).unwrap_or_default();
+        assert!(legacy
+            .execute(
+                "INSERT INTO installed_programs_cache
+                 (cache_key, name, install_source, payload_json, updated_at)
+                 VALUES ('k', 'DemoLegacy', 'Registry', ?1, '')",
+                params![payload],
+            )
+            .is_ok());
+        assert!(write_cache_metadata(&legacy, META_KEY_SCHEMA_VERSION, "9").is_ok());
+        assert!(
+            write_cache_metadata(&legacy, META_KEY_GENERATED_AT, &Utc::now().to_rfc3339()).is_ok()
+        );
+        drop(legacy);
+
+        let result = read_scan_cache(DEFAULT_CACHE_TTL_SECONDS).unwrap_or_default();
+        assert!(result.stale);
+        assert_eq!(result.reason, Some("schema_upgraded".to_string()));
+        assert_eq!(result.schema_version, CACHE_SCHEMA_VERSION);
+        let entries = result.entries.unwrap_or_default();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].size_last_updated_at.as_deref(),
+            Some("2024-01-01T00:00:00+00:00")
+        );
+        assert!(!legacy_path.exists());
+
+        cleanup_storage_root(&root);
+    }
+
+    #[test]
+    fn read_scan_cache_returns_stale_entries_after_expiry() {
+        let _guard = super::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let root = with_storage_root("stale");
+
+        let program = InstalledProgram::new("DemoStale".to_string(), InstallSource::Registry);
+        assert!(save_scan_cache(&[program]).is_ok());
+
+        let connection = open_scan_cache_connection().unwrap_or_else(|_| panic!("open db failed"));
+        let expired_at = (Utc::now() - chrono::Duration::seconds(3600)).to_rfc3339();
+        assert!(write_cache_metadata(&connection, META_KEY_GENERATED_AT, &expired_at).is_ok());
+
+        let result = read_scan_cache(60).unwrap_or_default();
+        assert!(result.cache_hit);
+        assert!(!result.cache_valid);
+        assert!(result.stale);
+        assert_eq!(result.reason, Some("cache_expired".to_string()));
+        assert_eq!(result.entries.unwrap_or_default().len(), 1);
+
+        cleanup_storage_root(&root);
+    }
+
     #[test]
     fn force_cache_invalidation_removes_cache_file() {
         let _guard = super::TEST_STORAGE_ENV_LOCK
@@ -660,4 +924,26 @@ mod tests {
 
         cleanup_storage_root(&root);
     }
+
+    #[test]
+    fn save_scan_cache_records_program_history() {
+        let _guard = super::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let root = with_storage_root("history");
+
+        let first = InstalledProgram::new("DemoHistory".to_string(), InstallSource::Registry);
+        assert!(save_scan_cache(&[first]).is_ok());
+
+        // 后续枚举不再包含该程序时历史仍然保留
+        assert!(save_scan_cache(&[]).is_ok());
+        let history = get_program_history().unwrap_or_default();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].program.name, "DemoHistory");
+
+        assert!(delete_program_history("demohistory").is_ok());
+        assert!(get_program_history().unwrap_or_default().is_empty());
+
+        cleanup_storage_root(&root);
+    }
 }
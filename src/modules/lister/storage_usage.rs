@@ -0,0 +1,216 @@
+//! rust-yu 自身占用的磁盘空间
+//!
+//! 汇总存储目录、程序缓存库、图标缓存、隔离区和报告目录的位置与大小，
+//! 并提供逐项清空的操作，便于用户查看和管理 rust-yu 本身占用的空间。
+
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::storage::{self, SQLITE_SIDECAR_SUFFIXES};
+use crate::modules::cleaner::quarantine;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::reporter;
+
+/// 可单独清空的存储区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageArea {
+    /// 已安装程序的缓存库，清空后下次列出程序时重建
+    ScanCache,
+    /// 程序图标缓存
+    IconCache,
+    /// 卸载前导出的授权信息、注册表备份和删除前的归档
+    Quarantine,
+    /// 卸载和清理报告
+    Reports,
+}
+
+impl StorageArea {
+    pub const ALL: [StorageArea; 4] = [
+        StorageArea::ScanCache,
+        StorageArea::IconCache,
+        StorageArea::Quarantine,
+        StorageArea::Reports,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StorageArea::ScanCache => "程序缓存",
+            StorageArea::IconCache => "图标缓存",
+            StorageArea::Quarantine => "隔离区",
+            StorageArea::Reports => "报告",
+        }
+    }
+}
+
+impl FromStr for StorageArea {
+    type Err = UninstallerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().replace('-', "_").as_str() {
+            "scan_cache" | "cache" => Ok(StorageArea::ScanCache),
+            "icon_cache" | "icons" => Ok(StorageArea::IconCache),
+            "quarantine" => Ok(StorageArea::Quarantine),
+            "reports" => Ok(StorageArea::Reports),
+            other => Err(UninstallerError::Other(format!(
+                "未知的存储区域: {} (可选 cache/icons/quarantine/reports)",
+                other
+            ))),
+        }
+    }
+}
+
+/// 单个区域的占用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageAreaUsage {
+    pub area: StorageArea,
+    pub path: String,
+    pub size: u64,
+    pub file_count: usize,
+}
+
+/// rust-yu 的存储位置与占用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageInfo {
+    pub root: String,
+    /// 存储目录（含用户数据库等不可单独清空的文件）及目录外报告的总大小
+    pub total_size: u64,
+    pub areas: Vec<StorageAreaUsage>,
+}
+
+/// 统计各存储区域的占用
+pub fn storage_info() -> Result<StorageInfo, UninstallerError> {
+    let root = storage::get_storage_root_dir()?;
+    let areas = StorageArea::ALL
+        .iter()
+        .map(|area| area_usage(*area))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (mut total_size, _) = dir_usage(&root);
+    // 报告目录默认不在存储目录下，需要单独计入
+    if let Some(reports) = areas
+        .iter()
+        .find(|usage| usage.area == StorageArea::Reports)
+        .filter(|usage| !Path::new(&usage.path).starts_with(&root))
+    {
+        total_size += reports.size;
+    }
+
+    Ok(StorageInfo {
+        root: root.to_string_lossy().to_string(),
+        total_size,
+        areas,
+    })
+}
+
+/// 清空存储区域，返回释放的字节数
+pub fn clear_area(area: StorageArea) -> Result<u64, UninstallerError> {
+    let before = area_usage(area)?.size;
+    match area {
+        StorageArea::ScanCache => storage::invalidate_scan_cache()?,
+        StorageArea::IconCache => {
+            clear_dir(&storage::get_icon_cache_dir()?)?;
+            // 缓存库中记录了图标路径，一并失效以免引用已删除的文件
+            storage::invalidate_scan_cache()?;
+        }
+        StorageArea::Quarantine => clear_dir(&quarantine::get_quarantine_dir()?)?,
+        StorageArea::Reports => clear_dir(&reporter::storage::reports_dir())?,
+    }
+    let after = area_usage(area)?.size;
+    tracing::info!("已清空{}", area.label());
+    Ok(before.saturating_sub(after))
+}
+
+fn area_usage(area: StorageArea) -> Result<StorageAreaUsage, UninstallerError> {
+    let (path, (size, file_count)) = match area {
+        StorageArea::ScanCache => {
+            let path = storage::get_scan_cache_database_path()?;
+            let usage = std::iter::once("")
+                .chain(SQLITE_SIDECAR_SUFFIXES.iter().copied())
+                .filter_map(|suffix| std::fs::metadata(storage::sidecar_path(&path, suffix)).ok())
+                .fold((0, 0), |(size, count), metadata| {
+                    (size + metadata.len(), count + 1)
+                });
+            (path, usage)
+        }
+        StorageArea::IconCache => {
+            let path = storage::get_icon_cache_dir()?;
+            let usage = dir_usage(&path);
+            (path, usage)
+        }
+        StorageArea::Quarantine => {
+            let path = quarantine::get_quarantine_dir()?;
+            let usage = dir_usage(&path);
+            (path, usage)
+        }
+        StorageArea::Reports => {
+            let path = reporter::storage::reports_dir();
+            let usage = dir_usage(&path);
+            (path, usage)
+        }
+    };
+    Ok(StorageAreaUsage {
+        area,
+        path: path.to_string_lossy().to_string(),
+        size,
+        file_count,
+    })
+}
+
+/// 目录下所有文件的总大小和文件数，目录不存在时为 0
+fn dir_usage(dir: &Path) -> (u64, usize) {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .fold((0, 0), |(size, count), metadata| {
+            (size + metadata.len(), count + 1)
+        })
+}
+
+/// 删除目录中的所有内容，保留目录本身
+fn clear_dir(dir: &Path) -> Result<(), UninstallerError> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let result = if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        if let Err(error) = result {
+            tracing::warn!("删除 {} 失败: {}", path.display(), error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clears_directory_contents_and_measures_usage() {
+        let dir = std::env::temp_dir().join(format!("rust-yu-usage-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.bin"), [0u8; 10]).unwrap();
+        std::fs::write(dir.join("nested").join("b.bin"), [0u8; 5]).unwrap();
+
+        assert_eq!(dir_usage(&dir), (15, 2));
+        clear_dir(&dir).unwrap();
+        assert_eq!(dir_usage(&dir), (0, 0));
+        assert!(dir.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            "icon-cache".parse::<StorageArea>().unwrap(),
+            StorageArea::IconCache
+        );
+        assert!("logs".parse::<StorageArea>().is_err());
+    }
+}
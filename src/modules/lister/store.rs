@@ -1,5 +1,8 @@
 use super::models::{InstallSource, InstalledProgram};
 use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::remote::runner::{LocalRunner, ScriptRunner};
+use serde::{Deserialize, Serialize};
 use std::process::Command;
 
 /// 列出微软商店应用
@@ -104,3 +107,98 @@ struct StoreAppJson {
     #[serde(rename = "PackageFullName")]
     package_full_name: Option<String>,
 }
+
+/// 预配包：系统为每个新用户自动安装的商店应用，OEM 预装应用通常以此方式存在
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisionedPackage {
+    /// 包名（不含版本），如 `Microsoft.BingNews`
+    pub display_name: String,
+    /// 完整包名，`Remove-AppxProvisionedPackage` 使用
+    pub package_name: String,
+    pub version: Option<String>,
+    pub publisher_id: Option<String>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct ProvisionedPackageJson {
+    display_name: Option<String>,
+    package_name: Option<String>,
+    version: Option<String>,
+    publisher_id: Option<String>,
+}
+
+/// 列出预配包，需要管理员权限
+pub fn list_provisioned_packages() -> Result<Vec<ProvisionedPackage>, UninstallerError> {
+    if !utils::is_elevated() {
+        return Err(UninstallerError::PermissionDenied(
+            "读取预配包需要以管理员身份运行".to_string(),
+        ));
+    }
+
+    let output = LocalRunner.run(
+        r#"
+        Get-AppxProvisionedPackage -Online | ForEach-Object {
+            [PSCustomObject]@{
+                DisplayName = $_.DisplayName
+                PackageName = $_.PackageName
+                Version = [string]$_.Version
+                PublisherId = $_.PublisherId
+            }
+        } | ConvertTo-Json -Depth 2
+        "#,
+        &[],
+    )?;
+
+    Ok(parse_provisioned_packages(&output))
+}
+
+fn parse_provisioned_packages(json_str: &str) -> Vec<ProvisionedPackage> {
+    if json_str.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let packages: Vec<ProvisionedPackageJson> =
+        serde_json::from_str(json_str).unwrap_or_else(|_| {
+            serde_json::from_str::<ProvisionedPackageJson>(json_str)
+                .map(|p| vec![p])
+                .unwrap_or_default()
+        });
+
+    packages
+        .into_iter()
+        .filter_map(|package| {
+            Some(ProvisionedPackage {
+                display_name: package.display_name.filter(|n| !n.is_empty())?,
+                package_name: package.package_name.filter(|n| !n.is_empty())?,
+                version: package.version,
+                publisher_id: package.publisher_id,
+            })
+        })
+        .collect()
+}
+
+/// 按包名或完整包名查找预配包（不区分大小写）
+pub fn find_provisioned_package(
+    name: &str,
+) -> Result<Option<ProvisionedPackage>, UninstallerError> {
+    Ok(list_provisioned_packages()?.into_iter().find(|package| {
+        package.display_name.eq_ignore_ascii_case(name)
+            || package.package_name.eq_ignore_ascii_case(name)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_provisioned_package() {
+        let json = r#"{"DisplayName":"Microsoft.BingNews","PackageName":"Microsoft.BingNews_4.55.62231.0_neutral_~_8wekyb3d8bbwe","Version":"4.55.62231.0","PublisherId":"8wekyb3d8bbwe"}"#;
+
+        let packages = parse_provisioned_packages(json);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].display_name, "Microsoft.BingNews");
+        assert_eq!(packages[0].publisher_id.as_deref(), Some("8wekyb3d8bbwe"));
+    }
+}
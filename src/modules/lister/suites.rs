@@ -0,0 +1,215 @@
+//! 软件套件识别
+//!
+//! 同一发布者安装在同一目录下的多个卸载条目（如 "Foo App"、"Foo Updater"、"Foo Browser Helper"）
+//! 视为一个套件，可以整体卸载；更新程序、插件等辅助组件排在主程序之前卸载。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{InstalledProgram, ListProgramsQuery};
+use super::{list_programs_with_cache, storage};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::roots;
+
+/// 公共程序目录，套件根目录取其下的第一级子目录
+const PROGRAM_CONTAINERS: &[&str] = &[
+    r"\program files\",
+    r"\program files (x86)\",
+    r"\appdata\local\programs\",
+];
+
+/// 名称中出现这些词的条目视为辅助组件
+const HELPER_KEYWORDS: &[&str] = &[
+    "updater",
+    "update",
+    "helper",
+    "service",
+    "plugin",
+    "plug-in",
+    "add-in",
+    "addin",
+    "extension",
+    "agent",
+    "runtime",
+    "driver",
+    "maintenance",
+    "launcher",
+];
+
+/// 套件成员角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SuiteRole {
+    /// 主程序，最后卸载
+    Main,
+    /// 更新程序、插件等辅助组件，先于主程序卸载
+    Helper,
+}
+
+/// 套件成员
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuiteMember {
+    pub role: SuiteRole,
+    pub program: InstalledProgram,
+}
+
+/// 软件套件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoftwareSuite {
+    /// 套件名称，取主程序名称
+    pub name: String,
+    pub publisher: String,
+    /// 套件根目录（小写并以 `\` 结尾）
+    pub install_root: String,
+    /// 成员，按卸载顺序排列（辅助组件在前，主程序在后）
+    pub members: Vec<SuiteMember>,
+}
+
+impl SoftwareSuite {
+    /// 是否包含指定名称的程序
+    pub fn contains(&self, program_name: &str) -> bool {
+        self.members.iter().any(|member| {
+            member
+                .program
+                .name
+                .eq_ignore_ascii_case(program_name.trim())
+        })
+    }
+}
+
+/// 从程序列表中识别套件：发布者相同且安装在同一根目录下的两个及以上条目
+pub fn detect_suites(programs: &[InstalledProgram]) -> Vec<SoftwareSuite> {
+    let mut groups: BTreeMap<(String, String), Vec<&InstalledProgram>> = BTreeMap::new();
+    for program in programs {
+        let Some(publisher) = program
+            .publisher
+            .as_deref()
+            .map(|publisher| publisher.trim().to_lowercase())
+            .filter(|publisher| !publisher.is_empty())
+        else {
+            continue;
+        };
+        let Some(root) = suite_root(program) else {
+            continue;
+        };
+        groups.entry((publisher, root)).or_default().push(program);
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, members)| members.len() >= 2)
+        .map(|((_, install_root), members)| build_suite(install_root, members))
+        .collect()
+}
+
+/// 列出当前安装的所有套件
+#[allow(dead_code)]
+pub fn list_suites() -> Result<Vec<SoftwareSuite>, UninstallerError> {
+    let response = list_programs_with_cache(ListProgramsQuery {
+        cache_ttl_seconds: storage::DEFAULT_CACHE_TTL_SECONDS,
+        allow_stale: true,
+        ..Default::default()
+    })?;
+    Ok(detect_suites(&response.programs))
+}
+
+/// 查找包含指定程序的套件
+pub fn find_suite(program_name: &str) -> Result<Option<SoftwareSuite>, UninstallerError> {
+    Ok(list_suites()?
+        .into_iter()
+        .find(|suite| suite.contains(program_name)))
+}
+
+/// 套件根目录：位于 Program Files 等公共目录下时取第一级子目录，否则取安装目录本身
+fn suite_root(program: &InstalledProgram) -> Option<String> {
+    let root = roots::install_root(program)?;
+    for container in PROGRAM_CONTAINERS {
+        if let Some(index) = root.find(container) {
+            let start = index + container.len();
+            let first = root[start..].split('\\').next().unwrap_or_default();
+            if !first.is_empty() {
+                return Some(format!(r"{}{}\", &root[..start], first));
+            }
+        }
+    }
+    Some(root)
+}
+
+fn is_helper_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.split(|c: char| !c.is_alphanumeric() && c != '-')
+        .any(|word| HELPER_KEYWORDS.contains(&word))
+}
+
+/// 主程序取名称不含辅助关键词的条目中名称最短的一个，全部含关键词时取名称最短的条目
+fn build_suite(install_root: String, members: Vec<&InstalledProgram>) -> SoftwareSuite {
+    let main_index = members
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, program)| (is_helper_name(&program.name), program.name.len()))
+        .map(|(index, _)| index)
+        .unwrap_or_default();
+
+    let mut ordered: Vec<SuiteMember> = members
+        .iter()
+        .enumerate()
+        .map(|(index, program)| SuiteMember {
+            role: if index == main_index {
+                SuiteRole::Main
+            } else {
+                SuiteRole::Helper
+            },
+            program: (*program).clone(),
+        })
+        .collect();
+    ordered.sort_by(|a, b| {
+        (a.role == SuiteRole::Main)
+            .cmp(&(b.role == SuiteRole::Main))
+            .then_with(|| a.program.name.cmp(&b.program.name))
+    });
+
+    let main = &members[main_index];
+    SoftwareSuite {
+        name: main.name.clone(),
+        publisher: main.publisher.clone().unwrap_or_default(),
+        install_root,
+        members: ordered,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    fn program(name: &str, publisher: &str, location: &str) -> InstalledProgram {
+        let mut program = InstalledProgram::new(name.to_string(), InstallSource::Registry);
+        program.publisher = Some(publisher.to_string());
+        program.install_location = Some(location.to_string());
+        program
+    }
+
+    #[test]
+    fn groups_entries_by_publisher_and_root_with_helpers_first() {
+        let programs = vec![
+            program("Foo App", "Foo Inc", r"C:\Program Files\Foo\App"),
+            program("Foo Updater", "Foo Inc", r"C:\Program Files\Foo\Updater"),
+            program("Foo Browser Helper", "foo inc", r"C:\Program Files\Foo"),
+            program("Bar", "Foo Inc", r"C:\Program Files\Bar"),
+            program("Other Tool", "Other", r"C:\Program Files\Foo\Tool"),
+        ];
+
+        let suites = detect_suites(&programs);
+        assert_eq!(suites.len(), 1);
+        assert_eq!(suites[0].name, "Foo App");
+        assert_eq!(suites[0].install_root, r"c:\program files\foo\");
+
+        let order: Vec<&str> = suites[0]
+            .members
+            .iter()
+            .map(|member| member.program.name.as_str())
+            .collect();
+        assert_eq!(order, vec!["Foo Browser Helper", "Foo Updater", "Foo App"]);
+        assert!(suites[0].contains("foo updater"));
+    }
+}
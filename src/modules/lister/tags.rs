@@ -0,0 +1,246 @@
+//! 用户自定义的程序标签
+//!
+//! 标签按程序缓存键保存在用户数据库中，扫描缓存失效时不受影响。
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::models::InstalledProgram;
+use super::pool::PooledConnection;
+use super::storage::{self, map_sqlite_error};
+use crate::modules::common::error::UninstallerError;
+
+const TAGS_TABLE_NAME: &str = "program_tags";
+
+/// 标签及使用该标签的程序数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagSummary {
+    pub tag: String,
+    pub program_count: usize,
+}
+
+fn open_tags_connection() -> Result<PooledConnection, UninstallerError> {
+    let connection = storage::open_user_data_connection()?;
+
+    connection
+        .execute_batch(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                cache_key TEXT NOT NULL,
+                program_name TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (cache_key, tag)
+            );
+            CREATE INDEX IF NOT EXISTS idx_program_tags_tag ON {table}(tag);
+            "#,
+            table = TAGS_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("初始化标签表失败", error))?;
+
+    Ok(connection)
+}
+
+/// 标签统一去除首尾空白并转为小写
+fn normalize_tag(tag: &str) -> Result<String, UninstallerError> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err(UninstallerError::Other("标签不能为空".to_string()));
+    }
+    Ok(tag)
+}
+
+/// 为程序添加标签，已存在时忽略
+pub fn add_tag(program: &InstalledProgram, tag: &str) -> Result<(), UninstallerError> {
+    let tag = normalize_tag(tag)?;
+    open_tags_connection()?
+        .execute(
+            &format!(
+                "INSERT OR IGNORE INTO {} (cache_key, program_name, tag, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                TAGS_TABLE_NAME
+            ),
+            params![
+                storage::build_program_cache_key(program),
+                program.name,
+                tag,
+                Utc::now().to_rfc3339()
+            ],
+        )
+        .map_err(|error| map_sqlite_error("写入标签失败", error))?;
+    Ok(())
+}
+
+/// 移除程序的标签，返回是否确实删除了记录
+pub fn remove_tag(program: &InstalledProgram, tag: &str) -> Result<bool, UninstallerError> {
+    let tag = normalize_tag(tag)?;
+    let removed = open_tags_connection()?
+        .execute(
+            &format!(
+                "DELETE FROM {} WHERE cache_key = ?1 AND tag = ?2",
+                TAGS_TABLE_NAME
+            ),
+            params![storage::build_program_cache_key(program), tag],
+        )
+        .map_err(|error| map_sqlite_error("删除标签失败", error))?;
+    Ok(removed > 0)
+}
+
+/// 重命名标签；新标签已存在的程序合并为一条，返回受影响的程序数
+pub fn rename_tag(old_tag: &str, new_tag: &str) -> Result<usize, UninstallerError> {
+    let old_tag = normalize_tag(old_tag)?;
+    let new_tag = normalize_tag(new_tag)?;
+    let mut connection = open_tags_connection()?;
+    let transaction = connection
+        .transaction()
+        .map_err(|error| map_sqlite_error("开启标签事务失败", error))?;
+
+    let renamed = transaction
+        .execute(
+            &format!(
+                "UPDATE OR IGNORE {} SET tag = ?2 WHERE tag = ?1",
+                TAGS_TABLE_NAME
+            ),
+            params![old_tag, new_tag],
+        )
+        .map_err(|error| map_sqlite_error("重命名标签失败", error))?;
+    // 已有新标签的程序不会被更新，剩下的旧记录直接删除
+    let merged = transaction
+        .execute(
+            &format!("DELETE FROM {} WHERE tag = ?1", TAGS_TABLE_NAME),
+            params![old_tag],
+        )
+        .map_err(|error| map_sqlite_error("合并标签失败", error))?;
+
+    transaction
+        .commit()
+        .map_err(|error| map_sqlite_error("提交标签事务失败", error))?;
+    Ok(renamed + merged)
+}
+
+/// 从所有程序上删除标签，返回受影响的程序数
+pub fn delete_tag(tag: &str) -> Result<usize, UninstallerError> {
+    let tag = normalize_tag(tag)?;
+    open_tags_connection()?
+        .execute(
+            &format!("DELETE FROM {} WHERE tag = ?1", TAGS_TABLE_NAME),
+            params![tag],
+        )
+        .map_err(|error| map_sqlite_error("删除标签失败", error))
+}
+
+/// 列出所有标签及其程序数，按标签名排序
+pub fn list_tags() -> Result<Vec<TagSummary>, UninstallerError> {
+    let connection = open_tags_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT tag, COUNT(*) FROM {} GROUP BY tag ORDER BY tag",
+            TAGS_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取标签失败", error))?;
+
+    let rows = statement
+        .query_map([], |row| {
+            Ok(TagSummary {
+                tag: row.get(0)?,
+                program_count: row.get::<usize, i64>(1)? as usize,
+            })
+        })
+        .map_err(|error| map_sqlite_error("读取标签失败", error))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|error| map_sqlite_error("解析标签失败", error))
+}
+
+/// 读取全部标签，按程序缓存键分组
+fn load_tag_map() -> Result<HashMap<String, Vec<String>>, UninstallerError> {
+    let connection = open_tags_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT cache_key, tag FROM {} ORDER BY tag",
+            TAGS_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取标签失败", error))?;
+
+    let rows = statement
+        .query_map([], |row| {
+            Ok((row.get::<usize, String>(0)?, row.get::<usize, String>(1)?))
+        })
+        .map_err(|error| map_sqlite_error("读取标签失败", error))?;
+
+    let mut tag_map: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (cache_key, tag) = row.map_err(|error| map_sqlite_error("解析标签失败", error))?;
+        tag_map.entry(cache_key).or_default().push(tag);
+    }
+    Ok(tag_map)
+}
+
+/// 为程序填充标签（覆盖缓存中保存的旧值）
+pub fn apply_tags(programs: &mut [InstalledProgram]) -> Result<(), UninstallerError> {
+    let tag_map = load_tag_map()?;
+    for program in programs {
+        program.tags = tag_map
+            .get(&storage::build_program_cache_key(program))
+            .cloned()
+            .unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// 只保留带有全部指定标签的程序
+pub fn filter_by_tags(programs: &mut Vec<InstalledProgram>, tags: &[String]) {
+    let required: Vec<String> = tags
+        .iter()
+        .filter_map(|tag| normalize_tag(tag).ok())
+        .collect();
+    if required.is_empty() {
+        return;
+    }
+    programs.retain(|program| required.iter().all(|tag| program.tags.contains(tag)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn tags_survive_cache_invalidation_and_filter_programs() {
+        let _guard = storage::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let root = std::env::temp_dir().join(format!("rust-yu-tags-test-{}", uuid::Uuid::new_v4()));
+        let _ = std::fs::create_dir_all(&root);
+        std::env::set_var(storage::STORAGE_DIR_ENV, &root);
+
+        let game = InstalledProgram::new("Game".to_string(), InstallSource::Registry);
+        let editor = InstalledProgram::new("Editor".to_string(), InstallSource::Registry);
+        assert!(add_tag(&game, " Games ").is_ok());
+        assert!(add_tag(&editor, "work").is_ok());
+        assert!(add_tag(&editor, "games").is_ok());
+        assert!(storage::invalidate_scan_cache().is_ok());
+
+        let mut programs = vec![game.clone(), editor.clone()];
+        assert!(apply_tags(&mut programs).is_ok());
+        filter_by_tags(&mut programs, &["GAMES".to_string(), "work".to_string()]);
+        assert_eq!(programs.len(), 1);
+        assert_eq!(programs[0].name, "Editor");
+
+        assert_eq!(rename_tag("work", "games").unwrap_or_default(), 1);
+        assert_eq!(
+            list_tags().unwrap_or_default(),
+            vec![TagSummary {
+                tag: "games".to_string(),
+                program_count: 2
+            }]
+        );
+        assert!(remove_tag(&game, "games").unwrap_or(false));
+
+        std::env::remove_var(storage::STORAGE_DIR_ENV);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,266 @@
+//! 痕迹清理反馈
+//!
+//! 记录用户对建议痕迹的实际处理：清理时删除了哪些，取消勾选或审核拒绝了哪些。反馈按“位置”汇总，
+//! 位置取痕迹的上级目录或上级注册表项，与具体程序无关，例如 `C:\ProgramData\Package Cache\{GUID}`
+//! 归入 `c:\programdata\package cache`；用户配置目录和 `HKU\<SID>` 统一改写，不同用户的反馈合并统计。
+//! 扫描时据此校准置信度。反馈只保存在本机用户数据库中。
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::pool::PooledConnection;
+use super::storage::{self, map_sqlite_error};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+
+const TRACE_FEEDBACK_TABLE_NAME: &str = "trace_feedback";
+
+/// 一个位置的反馈统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocationFeedback {
+    pub location: String,
+    /// 被删除的次数
+    pub deleted: u32,
+    /// 建议后被取消勾选或审核拒绝的次数
+    pub skipped: u32,
+    pub updated_at: String,
+}
+
+impl LocationFeedback {
+    pub fn total(&self) -> u32 {
+        self.deleted.saturating_add(self.skipped)
+    }
+
+    /// 被保留的比例，没有反馈时为 0
+    pub fn skip_ratio(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => f64::from(self.skipped) / f64::from(total),
+        }
+    }
+}
+
+fn open_trace_feedback_connection() -> Result<PooledConnection, UninstallerError> {
+    let connection = storage::open_user_data_connection()?;
+
+    connection
+        .execute_batch(&format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {table} (
+                location TEXT PRIMARY KEY,
+                deleted INTEGER NOT NULL DEFAULT 0,
+                skipped INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT NOT NULL
+            );
+            "#,
+            table = TRACE_FEEDBACK_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("初始化清理反馈表失败", error))?;
+
+    Ok(connection)
+}
+
+/// 痕迹所在的位置：小写的上级目录或注册表项，`=` 之后的值名部分不参与
+///
+/// 上级只剩驱动器或注册表根键时返回 None
+pub fn location_key(path: &str) -> Option<String> {
+    let path = path.split_once('=').map_or(path, |(key, _)| key);
+    let path = utils::normalize_path(path.trim())
+        .trim_end_matches('\\')
+        .to_lowercase();
+    let (parent, _) = path.rsplit_once('\\')?;
+    // 只剩驱动器或注册表根键时无法代表具体位置
+    if !parent.contains('\\') {
+        return None;
+    }
+    Some(generalize_user(parent))
+}
+
+/// 许多程序共用的位置（AppData、ProgramData、Program Files、SOFTWARE 注册表项），
+/// 这些位置的反馈反映的是其他程序的痕迹，不能用来提高置信度
+const SHARED_LOCATIONS: &[&str] = &[
+    r"%userprofile%\appdata\roaming",
+    r"%userprofile%\appdata\local",
+    r"%userprofile%\appdata\locallow",
+    "programdata",
+    "program files",
+    "program files (x86)",
+    r"hkcu\software",
+    r"hkcu\software\wow6432node",
+    r"hklm\software",
+    r"hklm\software\wow6432node",
+];
+
+/// 位置是否为多个程序共用的根目录或根注册表项，参数为 [`location_key`] 的结果
+pub fn is_shared_location(location: &str) -> bool {
+    // 驱动器号不固定，文件系统位置去掉驱动器后比较
+    let location = match location.split_once(":\\") {
+        Some((drive, rest)) if drive.len() == 1 => rest,
+        _ => location,
+    };
+    SHARED_LOCATIONS.contains(&location)
+}
+
+/// 把用户配置目录和 `HKU\<SID>` 改写为与用户无关的形式
+fn generalize_user(location: &str) -> String {
+    let segments: Vec<&str> = location.split('\\').collect();
+    match segments.as_slice() {
+        [drive, "users", _user, rest @ ..] if drive.ends_with(':') => {
+            std::iter::once("%userprofile%")
+                .chain(rest.iter().copied())
+                .collect::<Vec<_>>()
+                .join("\\")
+        }
+        ["hku" | "hkey_users", sid, rest @ ..] if sid.starts_with("s-1-5-21-") => {
+            std::iter::once("hkcu")
+                .chain(rest.iter().copied())
+                .collect::<Vec<_>>()
+                .join("\\")
+        }
+        _ => location.to_string(),
+    }
+}
+
+/// 记录一次清理的反馈，返回记录的痕迹数
+///
+/// `deleted` 为删除成功的痕迹路径，`skipped` 为建议后未删除的痕迹路径
+pub fn record_feedback(deleted: &[String], skipped: &[String]) -> Result<usize, UninstallerError> {
+    let mut connection = open_trace_feedback_connection()?;
+    let transaction = connection
+        .transaction()
+        .map_err(|error| map_sqlite_error("开始写入清理反馈失败", error))?;
+    let now = Utc::now().to_rfc3339();
+
+    let mut recorded = 0;
+    for (paths, was_deleted) in [(deleted, true), (skipped, false)] {
+        for location in paths.iter().filter_map(|path| location_key(path)) {
+            transaction
+                .execute(
+                    &format!(
+                        "INSERT INTO {table} (location, deleted, skipped, updated_at)
+                         VALUES (?1, ?2, ?3, ?4)
+                         ON CONFLICT(location) DO UPDATE SET
+                             deleted = deleted + excluded.deleted,
+                             skipped = skipped + excluded.skipped,
+                             updated_at = excluded.updated_at",
+                        table = TRACE_FEEDBACK_TABLE_NAME
+                    ),
+                    params![
+                        location,
+                        i64::from(was_deleted),
+                        i64::from(!was_deleted),
+                        now
+                    ],
+                )
+                .map_err(|error| map_sqlite_error("写入清理反馈失败", error))?;
+            recorded += 1;
+        }
+    }
+
+    transaction
+        .commit()
+        .map_err(|error| map_sqlite_error("提交清理反馈失败", error))?;
+    Ok(recorded)
+}
+
+/// 列出全部位置的反馈，反馈次数多的在前
+pub fn list_feedback() -> Result<Vec<LocationFeedback>, UninstallerError> {
+    let connection = open_trace_feedback_connection()?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT location, deleted, skipped, updated_at FROM {}
+             ORDER BY deleted + skipped DESC, location",
+            TRACE_FEEDBACK_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取清理反馈失败", error))?;
+
+    let feedback = statement
+        .query_map([], |row| {
+            Ok(LocationFeedback {
+                location: row.get(0)?,
+                deleted: row.get::<_, i64>(1)?.clamp(0, i64::from(u32::MAX)) as u32,
+                skipped: row.get::<_, i64>(2)?.clamp(0, i64::from(u32::MAX)) as u32,
+                updated_at: row.get(3)?,
+            })
+        })
+        .map_err(|error| map_sqlite_error("读取清理反馈失败", error))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| map_sqlite_error("解析清理反馈失败", error))?;
+    Ok(feedback)
+}
+
+/// 按位置索引的反馈，扫描时一次读取
+pub fn feedback_by_location() -> Result<HashMap<String, LocationFeedback>, UninstallerError> {
+    Ok(list_feedback()?
+        .into_iter()
+        .map(|feedback| (feedback.location.clone(), feedback))
+        .collect())
+}
+
+/// 清空全部反馈，返回删除的位置数
+pub fn clear_feedback() -> Result<usize, UninstallerError> {
+    let connection = open_trace_feedback_connection()?;
+    connection
+        .execute(&format!("DELETE FROM {}", TRACE_FEEDBACK_TABLE_NAME), [])
+        .map_err(|error| map_sqlite_error("清空清理反馈失败", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_traces_by_parent_location() {
+        assert_eq!(
+            location_key(r"C:\ProgramData\Package Cache\{1234-5678}").as_deref(),
+            Some(r"c:\programdata\package cache")
+        );
+        assert_eq!(
+            location_key(r"C:\Users\alice\AppData\Local\Acme\").as_deref(),
+            Some(r"%userprofile%\appdata\local")
+        );
+        assert_eq!(
+            location_key(r"HKU\S-1-5-21-1-2-3-1001\Software\Acme").as_deref(),
+            Some(r"hkcu\software")
+        );
+        assert_eq!(
+            location_key(r"HKLM\SOFTWARE\Microsoft\Wbem\CIMOM\Autorecover MOFs=d:\acme.mof")
+                .as_deref(),
+            Some(r"hklm\software\microsoft\wbem\cimom")
+        );
+        assert!(location_key(r"HKLM\SOFTWARE").is_none());
+        assert!(location_key(r"D:\Acme").is_none());
+        assert!(location_key("Acme").is_none());
+    }
+
+    #[test]
+    fn shared_roots_are_recognized() {
+        for path in [
+            r"C:\Users\alice\AppData\Roaming\Acme",
+            r"D:\Program Files (x86)\Acme",
+            r"C:\ProgramData\Acme",
+            r"HKU\S-1-5-21-1-2-3-1001\Software\Acme",
+            r"HKLM\SOFTWARE\WOW6432Node\Acme",
+        ] {
+            let location = location_key(path).unwrap();
+            assert!(is_shared_location(&location), "{}", location);
+        }
+        assert!(!is_shared_location(r"c:\programdata\package cache"));
+        assert!(!is_shared_location(r"%userprofile%\appdata\local\acme"));
+    }
+
+    #[test]
+    fn skip_ratio_counts_skipped_share() {
+        let feedback = LocationFeedback {
+            deleted: 1,
+            skipped: 3,
+            ..LocationFeedback::default()
+        };
+        assert_eq!(feedback.total(), 4);
+        assert!((feedback.skip_ratio() - 0.75).abs() < f64::EPSILON);
+        assert!(LocationFeedback::default().skip_ratio().abs() < f64::EPSILON);
+    }
+}
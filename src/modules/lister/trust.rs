@@ -0,0 +1,108 @@
+//! 发布者可信度
+//!
+//! 校验程序主可执行文件的 Authenticode 签名，记录签名状态、签名者和证书有效期，
+//! 便于快速找出未签名或签名无效的软件优先审查。主程序优先取 DisplayIcon 指向的可执行文件，
+//! 找不到时退回卸载程序；msiexec 等系统卸载程序由 Windows 签名，不代表软件本身，因此跳过。
+
+use std::path::{Path, PathBuf};
+
+use super::models::{InstallSource, InstalledProgram};
+use crate::modules::uninstaller::signature::{self, SignatureStatus};
+use crate::modules::uninstaller::validation;
+
+/// 为程序填充签名信息
+pub fn enrich_trust(program: &mut InstalledProgram) {
+    program.signature = signature_target(program)
+        .map(|path| signature::check_file_signature(&path, program.publisher.as_deref()));
+}
+
+/// 只保留未签名或签名无效的程序
+pub fn filter_unsigned(programs: &mut Vec<InstalledProgram>) {
+    programs.retain(is_unsigned);
+}
+
+/// 程序的签名是否缺失或无效；无法校验的程序不计入
+pub fn is_unsigned(program: &InstalledProgram) -> bool {
+    program.signature.as_ref().is_some_and(|check| {
+        matches!(
+            check.status,
+            SignatureStatus::Unsigned | SignatureStatus::Invalid
+        )
+    })
+}
+
+/// 选择用于校验签名的可执行文件
+fn signature_target(program: &InstalledProgram) -> Option<PathBuf> {
+    // 商店应用由商店签名，系统功能属于 Windows 本身
+    if matches!(
+        program.install_source,
+        InstallSource::Store | InstallSource::Feature
+    ) {
+        return None;
+    }
+
+    let main_executable = program
+        .icon_path
+        .as_deref()
+        .map(Path::new)
+        .filter(|path| is_executable(path));
+    if let Some(path) = main_executable {
+        return Some(path.to_path_buf());
+    }
+
+    program
+        .uninstall_string
+        .as_deref()
+        .and_then(validation::parse_executable)
+        .filter(|path| is_executable(path) && !validation::is_trusted_system_uninstaller(path))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+        && path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::uninstaller::signature::SignatureCheck;
+
+    fn check(status: SignatureStatus) -> SignatureCheck {
+        SignatureCheck {
+            path: r"C:\Program Files\Demo\demo.exe".to_string(),
+            status,
+            signer: None,
+            publisher_match: None,
+            cert_not_before: None,
+            cert_not_after: None,
+        }
+    }
+
+    #[test]
+    fn filters_unsigned_and_invalid_programs() {
+        let mut programs: Vec<InstalledProgram> = [
+            Some(SignatureStatus::Valid),
+            Some(SignatureStatus::Unsigned),
+            Some(SignatureStatus::Invalid),
+            Some(SignatureStatus::Unknown),
+            None,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, status)| {
+            let mut program =
+                InstalledProgram::new(format!("Program {}", index), InstallSource::Registry);
+            program.signature = status.map(check);
+            program
+        })
+        .collect();
+
+        filter_unsigned(&mut programs);
+        let names: Vec<&str> = programs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Program 1", "Program 2"]);
+
+        let store = InstalledProgram::new("Store App".to_string(), InstallSource::Store);
+        assert!(signature_target(&store).is_none());
+    }
+}
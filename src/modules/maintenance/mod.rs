@@ -0,0 +1,198 @@
+//! 定期维护
+//!
+//! 供计划任务无人值守运行：刷新程序列表缓存、搜索已卸载程序的残留、
+//! 清理临时目录中高置信度的残留，并轮换报告目录。结果写入报告目录。
+
+pub mod scheduler;
+pub mod update;
+
+use crate::modules::cleaner::{self, models::CleanOptions, models::CleanResult};
+use crate::modules::lister::{self, models::ListProgramsQuery};
+use crate::modules::reporter::{self, models::UninstallerReport};
+use crate::modules::scanner::{leftovers, models::Confidence, models::Trace};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 默认保留的报告数量
+pub const DEFAULT_KEEP_REPORTS: usize = 30;
+
+/// 维护选项
+#[derive(Debug, Clone, Copy)]
+pub struct MaintenanceOptions {
+    /// 临时目录残留达到该置信度才会被清理
+    pub min_confidence: Confidence,
+    /// 报告目录中保留的最新报告数量
+    pub keep_reports: usize,
+}
+
+impl Default for MaintenanceOptions {
+    fn default() -> Self {
+        Self {
+            min_confidence: Confidence::High,
+            keep_reports: DEFAULT_KEEP_REPORTS,
+        }
+    }
+}
+
+/// 一次维护的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceSummary {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// 刷新后缓存中的程序数量
+    pub programs_cached: usize,
+    /// 发现残留的已卸载程序数量
+    pub leftover_programs: usize,
+    pub leftover_traces: usize,
+    /// 临时目录残留的清理结果
+    pub purged: Vec<CleanResult>,
+    pub bytes_freed: u64,
+    /// 轮换时删除的旧报告数量
+    pub reports_removed: usize,
+    pub report_path: Option<String>,
+    /// 各步骤的错误，单步失败不会中断整个维护
+    pub warnings: Vec<String>,
+}
+
+/// 执行一次维护
+pub async fn run_maintenance(options: MaintenanceOptions) -> MaintenanceSummary {
+    let started_at = Utc::now();
+    let mut warnings = Vec::new();
+
+    // 1. 刷新缓存
+    let programs_cached = match lister::list_programs_with_cache(ListProgramsQuery {
+        refresh: true,
+        ..ListProgramsQuery::default()
+    }) {
+        Ok(response) => response.programs.len(),
+        Err(e) => {
+            warnings.push(format!("刷新程序列表缓存失败: {}", e));
+            0
+        }
+    };
+
+    // 2. 搜索残留
+    let report = match leftovers::hunt_leftovers(&[]).await {
+        Ok(report) => Some(report),
+        Err(e) => {
+            warnings.push(format!("搜索残留失败: {}", e));
+            None
+        }
+    };
+
+    let all_traces: Vec<Trace> = report
+        .iter()
+        .flat_map(|report| report.programs.iter())
+        .flat_map(|removed| removed.traces.iter().cloned())
+        .collect();
+
+    // 3. 只清理临时目录中的高置信度残留，其余留给用户确认
+    let temp_roots = temp_directories();
+    let purge: Vec<Trace> = all_traces
+        .iter()
+        .filter(|trace| trace.confidence.meets(options.min_confidence))
+        .filter(|trace| is_under_any(&trace.path, &temp_roots))
+        .cloned()
+        .collect();
+
+    let purged = if purge.is_empty() {
+        Vec::new()
+    } else {
+        match cleaner::clean_traces_with_options(purge, true, CleanOptions::default()).await {
+            Ok(results) => results,
+            Err(e) => {
+                warnings.push(format!("清理临时目录残留失败: {}", e));
+                Vec::new()
+            }
+        }
+    };
+
+    // 4. 写入报告并轮换
+    let reports_dir = reporter::get_reports_dir();
+    let mut uninstaller_report = UninstallerReport::new("定期维护".to_string())
+        .with_traces(all_traces.clone())
+        .with_results(purged.clone());
+    for warning in &warnings {
+        uninstaller_report.add_warning(warning.clone());
+    }
+
+    let report_path = match reporter::storage::save_report(&uninstaller_report) {
+        Ok(stored) => stored.html_path.or(Some(stored.json_path)),
+        Err(e) => {
+            warnings.push(format!("写入维护报告失败: {}", e));
+            None
+        }
+    };
+
+    let reports_removed =
+        match reporter::storage::rotate_reports(&reports_dir, options.keep_reports) {
+            Ok(count) => count,
+            Err(e) => {
+                warnings.push(format!("轮换报告失败: {}", e));
+                0
+            }
+        };
+
+    MaintenanceSummary {
+        started_at,
+        finished_at: Utc::now(),
+        programs_cached,
+        leftover_programs: report.as_ref().map_or(0, |r| r.programs.len()),
+        leftover_traces: all_traces.len(),
+        bytes_freed: purged.iter().map(|r| r.bytes_freed).sum(),
+        purged,
+        reports_removed,
+        report_path,
+        warnings,
+    }
+}
+
+/// 系统和当前用户的临时目录（小写，无尾部分隔符）
+fn temp_directories() -> Vec<String> {
+    let mut dirs: Vec<String> = ["TEMP", "TMP"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .collect();
+    if let Ok(windir) = std::env::var("WINDIR") {
+        dirs.push(format!(r"{}\Temp", windir));
+    }
+
+    let mut dirs: Vec<String> = dirs
+        .into_iter()
+        .map(|dir| dir.trim_end_matches('\\').to_lowercase())
+        .filter(|dir| !dir.is_empty())
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// 路径是否位于任一目录之下（不含目录本身）
+fn is_under_any(path: &str, roots: &[String]) -> bool {
+    let path_lower = path.to_lowercase();
+    roots.iter().any(|root| {
+        path_lower
+            .strip_prefix(root.as_str())
+            .is_some_and(|rest| rest.len() > 1 && rest.starts_with('\\'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_under_any_excludes_temp_root_itself() {
+        let roots = vec![r"c:\users\alice\appdata\local\temp".to_string()];
+
+        assert!(is_under_any(
+            r"C:\Users\Alice\AppData\Local\Temp\FooSetup",
+            &roots
+        ));
+        assert!(!is_under_any(r"C:\Users\Alice\AppData\Local\Temp", &roots));
+        assert!(!is_under_any(
+            r"C:\Users\Alice\AppData\Local\Temporary\Foo",
+            &roots
+        ));
+    }
+}
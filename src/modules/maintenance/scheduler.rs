@@ -0,0 +1,128 @@
+//! 维护计划任务
+//!
+//! 通过 schtasks 注册或删除定期运行 `rust-yu maintain` 的计划任务。
+
+use crate::modules::common::error::UninstallerError;
+use std::path::Path;
+use std::process::Command;
+
+/// 计划任务名称
+pub const TASK_NAME: &str = r"rust-yu\Maintenance";
+
+/// 运行频率
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl ScheduleFrequency {
+    fn as_schtasks_arg(self) -> &'static str {
+        match self {
+            ScheduleFrequency::Daily => "DAILY",
+            ScheduleFrequency::Weekly => "WEEKLY",
+            ScheduleFrequency::Monthly => "MONTHLY",
+        }
+    }
+}
+
+/// 注册计划任务，已存在时覆盖
+///
+/// `start_time` 为 HH:MM 格式；`args` 为传给 `rust-yu` 的维护参数
+pub fn register_task(
+    executable: &Path,
+    frequency: ScheduleFrequency,
+    start_time: &str,
+    args: &[String],
+) -> Result<(), UninstallerError> {
+    if !is_valid_start_time(start_time) {
+        return Err(UninstallerError::Other(format!(
+            "无效的开始时间: {}（应为 HH:MM）",
+            start_time
+        )));
+    }
+
+    let task_command = build_task_command(executable, args);
+    run_schtasks(&[
+        "/Create",
+        "/TN",
+        TASK_NAME,
+        "/TR",
+        &task_command,
+        "/SC",
+        frequency.as_schtasks_arg(),
+        "/ST",
+        start_time,
+        "/RL",
+        "HIGHEST",
+        "/F",
+    ])
+}
+
+/// 删除计划任务
+pub fn unregister_task() -> Result<(), UninstallerError> {
+    if !is_task_registered() {
+        return Err(UninstallerError::NotFound(TASK_NAME.to_string()));
+    }
+    run_schtasks(&["/Delete", "/TN", TASK_NAME, "/F"])
+}
+
+/// 计划任务是否已注册
+pub fn is_task_registered() -> bool {
+    Command::new("schtasks")
+        .args(["/Query", "/TN", TASK_NAME])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// 生成计划任务执行的命令行
+fn build_task_command(executable: &Path, args: &[String]) -> String {
+    let mut command = format!("\"{}\" maintain", executable.display());
+    for arg in args {
+        command.push(' ');
+        command.push_str(arg);
+    }
+    command
+}
+
+fn is_valid_start_time(start_time: &str) -> bool {
+    let Some((hour, minute)) = start_time.split_once(':') else {
+        return false;
+    };
+    let valid = |part: &str, max: u32| {
+        part.len() == 2 && part.parse::<u32>().is_ok_and(|value| value <= max)
+    };
+    valid(hour, 23) && valid(minute, 59)
+}
+
+fn run_schtasks(args: &[&str]) -> Result<(), UninstallerError> {
+    let output = Command::new("schtasks").args(args).output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.to_lowercase().contains("access is denied") || stderr.contains("拒绝访问") {
+        return Err(UninstallerError::PermissionDenied(stderr));
+    }
+    Err(UninstallerError::Other(format!(
+        "schtasks 执行失败: {}",
+        stderr
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_start_time() {
+        assert!(is_valid_start_time("03:00"));
+        assert!(is_valid_start_time("23:59"));
+        assert!(!is_valid_start_time("3:00"));
+        assert!(!is_valid_start_time("24:00"));
+        assert!(!is_valid_start_time("0300"));
+    }
+}
@@ -0,0 +1,317 @@
+//! 程序自更新
+//!
+//! 实验室等机器上的命令行部署需要在不重新安装的情况下保持最新。这里读取发布源
+//! （默认为项目的 GitHub Releases，可在配置文件的 `update.feed` 中改为内部镜像），
+//! 比较版本并报告更新说明；安装时下载 `rust-yu*.exe` 资产，校验 Authenticode 签名后替换当前程序：
+//!
+//! - 下载的文件必须有有效签名，且签名者必须是固定的发布者（可在 `update.publisher` 中改为内部镜像的签名者），
+//!   与当前程序是否签名无关
+//! - 正在运行的程序不能覆盖但可以重命名，先改名为 `.old` 再放入新文件，失败时改回
+//!
+//! 网络请求通过 PowerShell 执行，与远程操作共用脚本执行器，不引入额外的 HTTP 依赖。
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::modules::common::config;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::remote::runner::{LocalRunner, ScriptRunner};
+use crate::modules::uninstaller::signature::{self, SignatureStatus};
+
+/// 默认发布源：最新的 GitHub Release
+pub const DEFAULT_RELEASE_FEED: &str =
+    "https://api.github.com/repos/jasoft/rust_yu/releases/latest";
+
+/// 正式发布的程序签名者
+pub const RELEASE_PUBLISHER: &str = "John Anders";
+
+/// 替换后保留的旧程序文件扩展名
+const OLD_BINARY_EXTENSION: &str = "old";
+
+/// 更新设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateSettings {
+    /// 发布源地址，返回 GitHub Releases API 格式的 JSON
+    #[serde(default)]
+    pub feed: Option<String>,
+    /// 下载的程序要求的签名者，内部镜像重新签名时设置
+    #[serde(default)]
+    pub publisher: Option<String>,
+}
+
+/// 发布信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseInfo {
+    /// 去掉前缀 `v` 的版本号
+    pub version: String,
+    pub name: String,
+    /// 更新说明
+    pub changelog: String,
+    pub published_at: Option<String>,
+    /// 可执行文件资产
+    pub asset: Option<ReleaseAsset>,
+}
+
+/// 发布中的可执行文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub url: String,
+    pub size: Option<u64>,
+}
+
+/// 更新检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    pub current_version: String,
+    pub latest: ReleaseInfo,
+    pub update_available: bool,
+}
+
+/// GitHub Releases API 的响应，只取用到的字段
+#[derive(Debug, Deserialize)]
+struct FeedRelease {
+    tag_name: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<FeedAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FeedAsset {
+    name: String,
+    browser_download_url: String,
+    #[serde(default)]
+    size: Option<u64>,
+}
+
+/// 使用的发布源：参数优先，其次是配置文件，最后是默认地址
+pub fn release_feed(feed: Option<&str>) -> String {
+    feed.map(str::to_string)
+        .or_else(|| config::config().update.feed.clone())
+        .filter(|feed| !feed.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_RELEASE_FEED.to_string())
+}
+
+/// 要求的签名者：配置文件优先，其次是正式发布的签名者
+pub fn expected_publisher() -> String {
+    config::config()
+        .update
+        .publisher
+        .clone()
+        .filter(|publisher| !publisher.trim().is_empty())
+        .unwrap_or_else(|| RELEASE_PUBLISHER.to_string())
+}
+
+/// 读取发布源并与当前版本比较
+pub fn check_for_update(feed: &str) -> Result<UpdateCheck, UninstallerError> {
+    let content = LocalRunner.run(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8;\n\
+         (Invoke-WebRequest -Uri $Arg0 -UseBasicParsing -Headers @{ 'User-Agent' = 'rust-yu'; 'Accept' = 'application/json' } -ErrorAction Stop).Content",
+        &[feed.to_string()],
+    )?;
+    let latest = parse_release(&content)?;
+    let current_version = env!("CARGO_PKG_VERSION").to_string();
+    let update_available =
+        compare_versions(&latest.version, &current_version) == Some(Ordering::Greater);
+
+    Ok(UpdateCheck {
+        current_version,
+        latest,
+        update_available,
+    })
+}
+
+/// 解析发布源返回的 JSON
+fn parse_release(content: &str) -> Result<ReleaseInfo, UninstallerError> {
+    let release: FeedRelease = serde_json::from_str(content.trim())
+        .map_err(|error| UninstallerError::Serde(format!("发布信息格式错误: {}", error)))?;
+    let version = release
+        .tag_name
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .to_string();
+    if parse_version(&version).is_none() {
+        return Err(UninstallerError::Other(format!(
+            "无法识别发布的版本号: {}",
+            release.tag_name
+        )));
+    }
+
+    // 只接受命令行程序本身，安装包和界面程序不参与替换
+    let asset = release
+        .assets
+        .into_iter()
+        .find(|asset| {
+            let name = asset.name.to_lowercase();
+            name.starts_with("rust-yu") && name.ends_with(".exe") && !name.contains("setup")
+        })
+        .map(|asset| ReleaseAsset {
+            name: asset.name,
+            url: asset.browser_download_url,
+            size: asset.size,
+        });
+
+    Ok(ReleaseInfo {
+        name: release.name.unwrap_or_else(|| release.tag_name.clone()),
+        version,
+        changelog: release.body.unwrap_or_default().trim().to_string(),
+        published_at: release.published_at,
+        asset,
+    })
+}
+
+/// 版本号的数字部分和是否为预发布版本，如 `1.2.0-beta.1` 为 `([1, 2, 0], true)`
+fn parse_version(version: &str) -> Option<(Vec<u64>, bool)> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let (release, suffix) = version.split_at(version.find(['-', '+']).unwrap_or(version.len()));
+    let pre_release = suffix.starts_with('-');
+    let parts = release
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    (!parts.is_empty()).then_some((parts, pre_release))
+}
+
+/// 比较版本号，无法解析时返回 None；数字部分相同时正式版高于预发布版
+pub fn compare_versions(left: &str, right: &str) -> Option<Ordering> {
+    let (left_parts, left_pre) = parse_version(left)?;
+    let (right_parts, right_pre) = parse_version(right)?;
+    let length = left_parts.len().max(right_parts.len());
+    let part = |parts: &[u64], index: usize| parts.get(index).copied().unwrap_or(0);
+
+    let ordering = (0..length)
+        .map(|index| part(&left_parts, index).cmp(&part(&right_parts, index)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal);
+    Some(ordering.then(right_pre.cmp(&left_pre)))
+}
+
+/// 下载发布中的程序，校验签名后替换当前程序，返回被替换的旧文件路径
+pub fn install_update(release: &ReleaseInfo) -> Result<PathBuf, UninstallerError> {
+    let asset = release.asset.as_ref().ok_or_else(|| {
+        UninstallerError::Other(format!("版本 {} 没有可用的程序文件", release.version))
+    })?;
+    let current = std::env::current_exe()?;
+    remove_old_binary(&current);
+
+    let download = std::env::temp_dir().join(format!("rust-yu-{}.exe", release.version));
+    LocalRunner.run(
+        "Invoke-WebRequest -Uri $Arg0 -OutFile $Arg1 -UseBasicParsing -Headers @{ 'User-Agent' = 'rust-yu' } -ErrorAction Stop",
+        &[asset.url.clone(), download.to_string_lossy().to_string()],
+    )?;
+
+    let result = verify_download(&download, &expected_publisher())
+        .and_then(|_| swap_binary(&current, &download));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&download);
+    }
+    result
+}
+
+/// 下载的文件必须有有效签名，且签名者与 `publisher` 一致
+///
+/// 不参考当前程序的签名：未签名的开发版本也不能被任意签名的文件替换
+fn verify_download(download: &Path, publisher: &str) -> Result<(), UninstallerError> {
+    let downloaded = signature::verify_file_signature(download);
+    if downloaded.status != SignatureStatus::Valid {
+        return Err(UninstallerError::Other(format!(
+            "下载的程序签名无效 ({:?})，已放弃更新",
+            downloaded.status
+        )));
+    }
+
+    let signer = downloaded.signer.as_deref().unwrap_or_default();
+    if !signer_is(signer, publisher) {
+        return Err(UninstallerError::Other(format!(
+            "下载的程序签名者 {} 不是 {}，已放弃更新",
+            if signer.is_empty() { "未知" } else { signer },
+            publisher
+        )));
+    }
+
+    Ok(())
+}
+
+/// 签名者与要求的发布者完全一致（忽略大小写和首尾空白），不做模糊匹配
+fn signer_is(signer: &str, publisher: &str) -> bool {
+    let signer = signer.trim();
+    !signer.is_empty() && signer.eq_ignore_ascii_case(publisher.trim())
+}
+
+/// 把当前程序改名为 `.old` 后放入新文件，放入失败时改回原名
+fn swap_binary(current: &Path, download: &Path) -> Result<PathBuf, UninstallerError> {
+    let old = current.with_extension(OLD_BINARY_EXTENSION);
+    std::fs::rename(current, &old)?;
+    if let Err(error) =
+        std::fs::rename(download, current).or_else(|_| std::fs::copy(download, current).map(|_| ()))
+    {
+        let _ = std::fs::rename(&old, current);
+        return Err(error.into());
+    }
+    let _ = std::fs::remove_file(download);
+    Ok(old)
+}
+
+/// 删除上次更新留下的旧程序，仍在运行时删除失败，忽略即可
+fn remove_old_binary(current: &Path) {
+    let old = current.with_extension(OLD_BINARY_EXTENSION);
+    if old.exists() {
+        if let Err(e) = std::fs::remove_file(&old) {
+            tracing::debug!("删除旧程序 {} 失败: {}", old.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signer_must_equal_pinned_publisher() {
+        assert!(signer_is("John Anders", RELEASE_PUBLISHER));
+        assert!(signer_is(" john anders ", RELEASE_PUBLISHER));
+        assert!(!signer_is("John Anders Ltd", RELEASE_PUBLISHER));
+        assert!(!signer_is("Anders", RELEASE_PUBLISHER));
+        assert!(!signer_is("", RELEASE_PUBLISHER));
+    }
+
+    #[test]
+    fn parses_release_and_compares_versions() {
+        let release = parse_release(
+            r#"{
+                "tag_name": "v0.2.0",
+                "name": "0.2.0",
+                "body": "- 新增自更新\n",
+                "assets": [
+                    { "name": "rust-yu-setup.exe", "browser_download_url": "https://example.com/setup.exe" },
+                    { "name": "rust-yu.exe", "browser_download_url": "https://example.com/rust-yu.exe", "size": 1024 }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(release.version, "0.2.0");
+        assert_eq!(release.changelog, "- 新增自更新");
+        assert_eq!(release.asset.unwrap().name, "rust-yu.exe");
+        assert!(parse_release(r#"{"tag_name": "nightly"}"#).is_err());
+
+        assert_eq!(compare_versions("0.2.0", "0.1.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("v1.0", "1.0.0"), Some(Ordering::Equal));
+        assert_eq!(
+            compare_versions("1.0.0-beta.1", "1.0.0"),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_versions("1.0.0+build.5", "1.0.0"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare_versions("latest", "1.0.0"), None);
+    }
+}
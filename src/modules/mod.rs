@@ -1,5 +1,9 @@
 pub mod cleaner;
 pub mod common;
 pub mod lister;
+pub mod maintenance;
+pub mod remote;
 pub mod reporter;
 pub mod scanner;
+pub mod uninstaller;
+pub mod watcher;
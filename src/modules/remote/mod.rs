@@ -0,0 +1,365 @@
+//! 远程计算机管理
+//!
+//! 通过 WinRM / PowerShell 远程处理列出、卸载远程 Windows 计算机上的程序并清理残留，
+//! 不需要在目标机器上部署任何代理。所有读写都以脚本形式经 [`runner::ScriptRunner`] 执行。
+
+pub mod runner;
+
+use crate::modules::cleaner::{models::CleanResult, safety};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::models::{InstallSource, InstalledProgram};
+use crate::modules::scanner::matching;
+use crate::modules::scanner::models::{Confidence, Trace, TraceType};
+use crate::modules::uninstaller::validation;
+use runner::ScriptRunner;
+use serde::{Deserialize, Serialize};
+
+/// 读取远程计算机 Uninstall 注册表项（64 位与 32 位视图）
+const LIST_PROGRAMS_SCRIPT: &str = r#"
+$paths = @(
+    'HKLM:\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\*',
+    'HKLM:\SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall\*'
+)
+@(Get-ItemProperty -Path $paths -ErrorAction SilentlyContinue |
+    Where-Object { $_.DisplayName -and -not $_.SystemComponent } |
+    ForEach-Object {
+        [PSCustomObject]@{
+            Name = $_.DisplayName
+            Publisher = $_.Publisher
+            Version = $_.DisplayVersion
+            InstallDate = $_.InstallDate
+            InstallLocation = $_.InstallLocation
+            UninstallString = $_.UninstallString
+            EstimatedSize = $_.EstimatedSize
+        }
+    }) | ConvertTo-Json -Depth 2
+"#;
+
+/// 在程序目录、所有用户的 AppData 和 SOFTWARE 注册表项下查找名称含关键词的候选项
+///
+/// 关键词只用于缩小返回范围，是否属于程序由本机按单词匹配判断
+const SCAN_TRACES_SCRIPT: &str = r#"
+$pattern = '*' + [WildcardPattern]::Escape($Arg0) + '*'
+$results = New-Object System.Collections.ArrayList
+$roots = @($env:ProgramFiles, ${env:ProgramFiles(x86)}, $env:ProgramData) | Where-Object { $_ }
+foreach ($root in $roots) {
+    Get-ChildItem -LiteralPath $root -Directory -ErrorAction SilentlyContinue |
+        Where-Object { $_.Name -like $pattern } |
+        ForEach-Object { [void]$results.Add([PSCustomObject]@{ Type = 'File'; Path = $_.FullName }) }
+}
+$profiles = Split-Path -Parent $env:PUBLIC
+Get-ChildItem -LiteralPath $profiles -Directory -ErrorAction SilentlyContinue | ForEach-Object {
+    foreach ($sub in @('AppData\Roaming', 'AppData\Local')) {
+        $dir = Join-Path $_.FullName $sub
+        Get-ChildItem -LiteralPath $dir -Directory -ErrorAction SilentlyContinue |
+            Where-Object { $_.Name -like $pattern } |
+            ForEach-Object { [void]$results.Add([PSCustomObject]@{ Type = 'AppData'; Path = $_.FullName }) }
+    }
+}
+foreach ($key in @('HKLM:\SOFTWARE', 'HKLM:\SOFTWARE\WOW6432Node')) {
+    Get-ChildItem -Path $key -ErrorAction SilentlyContinue |
+        Where-Object { $_.PSChildName -like $pattern } |
+        ForEach-Object { [void]$results.Add([PSCustomObject]@{ Type = 'RegistryKey'; Path = $_.Name }) }
+}
+@($results) | ConvertTo-Json -Depth 2
+"#;
+
+/// 删除一批痕迹，逐项返回结果
+const DELETE_TRACES_SCRIPT: &str = r#"
+$items = @($Arg0 | ConvertFrom-Json)
+@(foreach ($item in $items) {
+    try {
+        if ($item.Type -eq 'RegistryKey') {
+            Remove-Item -LiteralPath ('Registry::' + $item.Path) -Recurse -Force -ErrorAction Stop
+        } else {
+            Remove-Item -LiteralPath $item.Path -Recurse -Force -ErrorAction Stop
+        }
+        [PSCustomObject]@{ Path = $item.Path; Success = $true; Error = $null }
+    } catch {
+        [PSCustomObject]@{ Path = $item.Path; Success = $false; Error = $_.Exception.Message }
+    }
+}) | ConvertTo-Json -Depth 2
+"#;
+
+/// 执行卸载命令并返回退出码
+const UNINSTALL_SCRIPT: &str = r#"
+$process = Start-Process -FilePath 'cmd.exe' -ArgumentList '/c', $Arg0 -Wait -PassThru -WindowStyle Hidden
+$process.ExitCode
+"#;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RemoteProgramJson {
+    name: Option<String>,
+    publisher: Option<String>,
+    version: Option<String>,
+    install_date: Option<String>,
+    install_location: Option<String>,
+    uninstall_string: Option<String>,
+    estimated_size: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RemoteTraceJson {
+    #[serde(rename = "Type")]
+    trace_type: String,
+    path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RemoteDeleteJson {
+    path: String,
+    success: bool,
+    error: Option<String>,
+}
+
+/// 列出远程计算机上的已安装程序
+pub fn list_programs(runner: &dyn ScriptRunner) -> Result<Vec<InstalledProgram>, UninstallerError> {
+    let output = runner.run(LIST_PROGRAMS_SCRIPT, &[])?;
+    let entries: Vec<RemoteProgramJson> = parse_json_array(&output)?;
+
+    let mut programs: Vec<InstalledProgram> = entries
+        .into_iter()
+        .filter_map(|entry| {
+            let name = entry.name.filter(|name| !name.trim().is_empty())?;
+            let mut program = InstalledProgram::new(name, InstallSource::Registry);
+            program.publisher = entry.publisher;
+            program.version = entry.version;
+            program.install_date = entry.install_date;
+            program.install_location = entry.install_location;
+            program.uninstall_string = entry.uninstall_string;
+            // EstimatedSize 以 KB 为单位
+            program.estimated_size = entry.estimated_size.map(|kb| kb * 1024);
+            program.size = program.estimated_size;
+            Some(program)
+        })
+        .collect();
+
+    programs.sort_by_key(|program| program.name.to_lowercase());
+    programs.dedup_by(|a, b| a.name.eq_ignore_ascii_case(&b.name));
+    Ok(programs)
+}
+
+/// 在远程计算机上执行程序的卸载命令，返回退出码
+///
+/// 命令先在本机做文本校验，危险命令始终拒绝，可疑命令需 `allow_suspicious`
+pub fn uninstall_program(
+    runner: &dyn ScriptRunner,
+    program: &InstalledProgram,
+    allow_suspicious: bool,
+) -> Result<i32, UninstallerError> {
+    let uninstall_string = program
+        .uninstall_string
+        .as_deref()
+        .ok_or_else(|| UninstallerError::NotFound(format!("{} 没有卸载命令", program.name)))?;
+
+    validation::ensure_remote_uninstall_allowed(uninstall_string, allow_suspicious)?;
+
+    // 远程会话没有桌面，MSI 卸载必须静默执行
+    let lower = uninstall_string.to_lowercase();
+    let command = if lower.starts_with("msiexec") && !lower.contains("/q") {
+        format!("{} /quiet /norestart", uninstall_string)
+    } else {
+        uninstall_string.to_string()
+    };
+    tracing::info!("在 {} 上执行卸载命令: {}", runner.target(), command);
+
+    let output = runner.run(UNINSTALL_SCRIPT, &[command])?;
+    output
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().parse::<i32>().ok())
+        .ok_or_else(|| UninstallerError::Other(format!("无法解析卸载进程退出码: {}", output)))
+}
+
+/// 在远程计算机上按名称搜索残留
+///
+/// 远程只按名称中最长的单词初筛，候选项再用 [`matching::name_matches`] 过滤；
+/// 只有名称与程序名完全一致的项评为高置信度
+pub fn scan_traces(
+    runner: &dyn ScriptRunner,
+    program_name: &str,
+) -> Result<Vec<Trace>, UninstallerError> {
+    let output = runner.run(SCAN_TRACES_SCRIPT, &[matching::index_key(program_name)])?;
+    let entries: Vec<RemoteTraceJson> = parse_json_array(&output)?;
+    let name_tokens = matching::tokenize(program_name);
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let (trace_type, path) = match entry.trace_type.as_str() {
+                "RegistryKey" => (
+                    TraceType::RegistryKey,
+                    utils::short_registry_path(&entry.path),
+                ),
+                "AppData" => (TraceType::AppData, entry.path),
+                "File" => (TraceType::File, entry.path),
+                _ => return None,
+            };
+
+            let leaf = path.rsplit('\\').next().unwrap_or_default();
+            if !matching::name_matches(leaf, program_name) {
+                return None;
+            }
+            let confidence = if matching::tokenize(leaf) == name_tokens {
+                Confidence::High
+            } else {
+                Confidence::Medium
+            };
+
+            let mut trace = Trace::new(program_name.to_string(), trace_type, path)
+                .with_description(format!("远程计算机 {} 上的残留", runner.target()))
+                .with_confidence(confidence);
+            trace.set_match_reason("名称与程序名匹配（远程搜索）".to_string());
+            trace.is_critical = utils::is_system_critical_path(&trace.path)
+                || (trace.trace_type == TraceType::RegistryKey
+                    && utils::is_critical_registry_path(&trace.path));
+            Some(trace)
+        })
+        .collect())
+}
+
+/// 删除远程计算机上的痕迹
+///
+/// 关键系统项按本机同样的规则拦截，其余痕迹一次性发送到远程执行
+pub fn clean_traces(
+    runner: &dyn ScriptRunner,
+    traces: &[Trace],
+) -> Result<Vec<CleanResult>, UninstallerError> {
+    let mut results = Vec::new();
+    let mut to_delete = Vec::new();
+
+    for trace in traces {
+        if let Err(e) = safety::pre_delete_check(trace) {
+            results.push(CleanResult {
+                trace_id: trace.id.clone(),
+                path: trace.path.clone(),
+                success: false,
+                error: Some(format!("跳过关键系统项: {}", e)),
+                bytes_freed: 0,
+            });
+            continue;
+        }
+
+        let (trace_type, path) = match trace.trace_type {
+            TraceType::RegistryKey => ("RegistryKey", utils::full_registry_path(&trace.path)),
+            TraceType::File | TraceType::AppData => ("File", trace.path.clone()),
+            _ => {
+                results.push(CleanResult {
+                    trace_id: trace.id.clone(),
+                    path: trace.path.clone(),
+                    success: false,
+                    error: Some("远程模式不支持该痕迹类型".to_string()),
+                    bytes_freed: 0,
+                });
+                continue;
+            }
+        };
+        to_delete.push((
+            trace,
+            RemoteTraceJson {
+                trace_type: trace_type.to_string(),
+                path,
+            },
+        ));
+    }
+
+    if to_delete.is_empty() {
+        return Ok(results);
+    }
+
+    let payload: Vec<&RemoteTraceJson> = to_delete.iter().map(|(_, item)| item).collect();
+    let payload = serde_json::to_string(&payload)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    let output = runner.run(DELETE_TRACES_SCRIPT, &[payload])?;
+    let outcomes: Vec<RemoteDeleteJson> = parse_json_array(&output)?;
+
+    for (trace, item) in &to_delete {
+        let outcome = outcomes
+            .iter()
+            .find(|outcome| outcome.path.eq_ignore_ascii_case(&item.path));
+        results.push(CleanResult {
+            trace_id: trace.id.clone(),
+            path: trace.path.clone(),
+            success: outcome.is_some_and(|outcome| outcome.success),
+            error: match outcome {
+                Some(outcome) => outcome.error.clone(),
+                None => Some("远程未返回结果".to_string()),
+            },
+            bytes_freed: 0,
+        });
+    }
+
+    Ok(results)
+}
+
+/// 解析 ConvertTo-Json 输出；只有一项时 PowerShell 输出的是对象而不是数组
+fn parse_json_array<T: serde::de::DeserializeOwned>(
+    output: &str,
+) -> Result<Vec<T>, UninstallerError> {
+    let output = output.trim();
+    if output.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    serde_json::from_str::<Vec<T>>(output)
+        .or_else(|_| serde_json::from_str::<T>(output).map(|item| vec![item]))
+        .map_err(|error| UninstallerError::Serde(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 返回固定输出的执行器
+    struct FakeRunner(&'static str);
+
+    impl ScriptRunner for FakeRunner {
+        fn run(&self, _script: &str, _args: &[String]) -> Result<String, UninstallerError> {
+            Ok(self.0.to_string())
+        }
+
+        fn target(&self) -> &str {
+            "fake"
+        }
+    }
+
+    #[test]
+    fn list_programs_accepts_single_object_output() {
+        let runner = FakeRunner(
+            r#"{"Name":"Foo","Publisher":"Acme","Version":"1.0","InstallDate":null,"InstallLocation":null,"UninstallString":"C:\\Foo\\uninst.exe","EstimatedSize":2}"#,
+        );
+
+        let programs = list_programs(&runner).unwrap();
+        assert_eq!(programs.len(), 1);
+        assert_eq!(programs[0].name, "Foo");
+        assert_eq!(programs[0].estimated_size, Some(2048));
+    }
+
+    #[test]
+    fn scan_traces_shortens_registry_roots() {
+        let runner = FakeRunner(
+            r#"[{"Type":"RegistryKey","Path":"HKEY_LOCAL_MACHINE\\SOFTWARE\\Foo"},{"Type":"AppData","Path":"C:\\Users\\bob\\AppData\\Roaming\\Foo Tool"}]"#,
+        );
+
+        let traces = scan_traces(&runner, "Foo").unwrap();
+        assert_eq!(traces[0].path, r"HKLM\SOFTWARE\Foo");
+        assert_eq!(traces[0].confidence, Confidence::High);
+        assert_eq!(traces[1].confidence, Confidence::Medium);
+    }
+
+    #[test]
+    fn scan_traces_drops_substring_only_hits() {
+        let runner = FakeRunner(
+            r#"[{"Type":"File","Path":"C:\\Program Files\\Git"},{"Type":"File","Path":"C:\\Program Files\\GitHub Desktop"},{"Type":"RegistryKey","Path":"HKEY_LOCAL_MACHINE\\SOFTWARE\\Digital"}]"#,
+        );
+
+        let traces = scan_traces(&runner, "Git").unwrap();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].path, r"C:\Program Files\Git");
+        assert_eq!(traces[0].confidence, Confidence::High);
+    }
+}
@@ -0,0 +1,132 @@
+//! PowerShell 脚本执行
+//!
+//! 远程操作都以 PowerShell 脚本的形式表达，通过 [`ScriptRunner`] 在本机或经 WinRM 在远程计算机上执行，
+//! 调用方不需要关心脚本实际运行在哪里。
+
+use crate::modules::common::error::UninstallerError;
+use std::process::Command;
+
+/// 脚本执行器
+pub trait ScriptRunner {
+    /// 执行脚本并返回标准输出
+    ///
+    /// `args` 以 `$Arg0`、`$Arg1`… 变量传入脚本，值按字面量转义，不会被当作代码解析
+    fn run(&self, script: &str, args: &[String]) -> Result<String, UninstallerError>;
+
+    /// 目标计算机名称，用于日志和输出
+    fn target(&self) -> &str;
+}
+
+/// 在本机执行
+pub struct LocalRunner;
+
+impl ScriptRunner for LocalRunner {
+    fn run(&self, script: &str, args: &[String]) -> Result<String, UninstallerError> {
+        run_powershell(&format!("{}{}", bind_args(args), script))
+    }
+
+    fn target(&self) -> &str {
+        "localhost"
+    }
+}
+
+/// 通过 WinRM (Invoke-Command) 在远程计算机上执行，使用当前登录账户的凭据
+pub struct RemoteRunner {
+    computer: String,
+}
+
+impl RemoteRunner {
+    pub fn new(computer: &str) -> Result<Self, UninstallerError> {
+        let computer = computer.trim();
+        if !is_valid_computer_name(computer) {
+            return Err(UninstallerError::Other(format!(
+                "无效的计算机名: {}",
+                computer
+            )));
+        }
+        Ok(Self {
+            computer: computer.to_string(),
+        })
+    }
+}
+
+impl ScriptRunner for RemoteRunner {
+    fn run(&self, script: &str, args: &[String]) -> Result<String, UninstallerError> {
+        let wrapped = format!(
+            "Invoke-Command -ComputerName {} -ErrorAction Stop -ScriptBlock {{\n{}{}\n}}",
+            quote_literal(&self.computer),
+            bind_args(args),
+            script
+        );
+        run_powershell(&wrapped)
+    }
+
+    fn target(&self) -> &str {
+        &self.computer
+    }
+}
+
+/// 启动 powershell 执行脚本
+fn run_powershell(script: &str) -> Result<String, UninstallerError> {
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if stderr.contains("Access is denied") || stderr.contains("拒绝访问") {
+            return Err(UninstallerError::PermissionDenied(stderr));
+        }
+        return Err(UninstallerError::Other(format!(
+            "PowerShell 执行失败: {}",
+            stderr
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 生成参数变量赋值语句
+fn bind_args(args: &[String]) -> String {
+    args.iter()
+        .enumerate()
+        .map(|(index, value)| format!("$Arg{} = {};\n", index, quote_literal(value)))
+        .collect()
+}
+
+/// 转为 PowerShell 单引号字面量，单引号内只有 `'` 需要转义
+fn quote_literal(value: &str) -> String {
+    // PowerShell 也把弯引号视为单引号
+    let escaped: String = value
+        .chars()
+        .flat_map(|c| match c {
+            '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => vec![c, c],
+            _ => vec![c],
+        })
+        .collect();
+    format!("'{}'", escaped)
+}
+
+/// 计算机名只允许字母数字、`.`、`-`、`_`
+fn is_valid_computer_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 253
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments_are_bound_as_literals() {
+        assert_eq!(
+            bind_args(&["it's $(evil)".to_string()]),
+            "$Arg0 = 'it''s $(evil)';\n"
+        );
+        assert!(is_valid_computer_name("pc-01.corp.local"));
+        assert!(!is_valid_computer_name("pc; Remove-Item C:\\"));
+    }
+}
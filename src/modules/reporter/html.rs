@@ -1,17 +1,44 @@
 use super::models::UninstallerReport;
 use crate::modules::cleaner::models::CleanResult;
 use crate::modules::common::error::UninstallerError;
-use crate::modules::common::utils;
+use crate::modules::common::{format, utils};
+use crate::modules::scanner::export::{self, TraceExport};
+use crate::modules::scanner::models::{Confidence, ScannerStats, Trace, TraceType};
+use crate::modules::uninstaller::simulation::UninstallSimulation;
 
 /// 生成 HTML 报告
 pub fn generate_html_report(report: &UninstallerReport) -> Result<String, UninstallerError> {
+    // 模拟报告没有删除记录，统计栏改为预计残留与拦截数
+    let (title, success_stat, failed_stat, size_label, results_html) = match &report.simulation {
+        Some(simulation) => (
+            "卸载模拟报告",
+            (simulation.remaining.len(), "预计残留"),
+            (simulation.blocked.len(), "清理时拦截"),
+            "预计释放空间",
+            generate_simulation_section(simulation),
+        ),
+        None => (
+            "卸载报告",
+            (
+                report.traces_removed.iter().filter(|r| r.success).count(),
+                "成功删除",
+            ),
+            (
+                report.traces_removed.iter().filter(|r| !r.success).count(),
+                "删除失败",
+            ),
+            "释放空间",
+            generate_results_table(&report.traces_removed),
+        ),
+    };
+
     let html = format!(
         r#"<!DOCTYPE html>
 <html lang="zh-CN">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>卸载报告 - {}</title>
+    <title>{} - {}</title>
     <style>
         * {{ margin: 0; padding: 0; box-sizing: border-box; }}
         body {{
@@ -150,7 +177,7 @@ pub fn generate_html_report(report: &UninstallerReport) -> Result<String, Uninst
 <body>
     <div class="container">
         <div class="header">
-            <h1>卸载报告</h1>
+            <h1>{}</h1>
             <div class="meta">
                 <p>程序: <strong>{}</strong></p>
                 <p>生成时间: {}</p>
@@ -165,20 +192,23 @@ pub fn generate_html_report(report: &UninstallerReport) -> Result<String, Uninst
             </div>
             <div class="stat success">
                 <div class="value">{}</div>
-                <div class="label">成功删除</div>
+                <div class="label">{}</div>
             </div>
             <div class="stat failed">
                 <div class="value">{}</div>
-                <div class="label">删除失败</div>
+                <div class="label">{}</div>
             </div>
             <div class="stat">
                 <div class="value">{}</div>
-                <div class="label">释放空间</div>
+                <div class="label">{}</div>
             </div>
         </div>
 
         <div class="content">
             {}
+            {}
+            {}
+            {}
         </div>
 
         <div class="footer">
@@ -187,15 +217,23 @@ pub fn generate_html_report(report: &UninstallerReport) -> Result<String, Uninst
     </div>
 </body>
 </html>"#,
+        title,
         report.program_name,
+        title,
         report.program_name,
-        report.generated_at.format("%Y-%m-%d %H:%M:%S"),
+        format::format_datetime(&report.generated_at),
         report.id,
         report.traces_found.len(),
-        report.traces_removed.iter().filter(|r| r.success).count(),
-        report.traces_removed.iter().filter(|r| !r.success).count(),
+        success_stat.0,
+        success_stat.1,
+        failed_stat.0,
+        failed_stat.1,
         utils::format_size(report.total_size_freed),
-        generate_results_table(&report.traces_removed),
+        size_label,
+        generate_warnings(&report.warnings),
+        results_html,
+        generate_registry_table(&report.traces_found),
+        generate_scanner_table(&report.scanners),
     );
 
     Ok(html)
@@ -270,6 +308,500 @@ fn generate_results_table(results: &[CleanResult]) -> String {
     html
 }
 
+/// 卸载模拟：将要执行的命令、需要提升权限和会被拦截的项，以及预计残留的痕迹
+fn generate_simulation_section(simulation: &UninstallSimulation) -> String {
+    let mut html = String::from(r#"<h2 class="section-title">卸载命令</h2>"#);
+    match &simulation.command {
+        Some(command) => html.push_str(&format!(r#"<p class="path">{}</p>"#, escape_html(command))),
+        None => html.push_str("<p>不会执行卸载命令</p>"),
+    }
+    let mut command_notes: Vec<String> = simulation
+        .command_issues
+        .iter()
+        .map(|issue| issue.message.clone())
+        .collect();
+    command_notes.extend(simulation.signature_problem.clone());
+    if let Some(reason) = &simulation.command_refused {
+        command_notes.push(format!("将拒绝执行: {}", reason));
+    }
+    if !command_notes.is_empty() {
+        html.push_str(&generate_warnings(&command_notes));
+    }
+    html.push_str(&format!(
+        "<p>卸载程序预计移除 {} 项（安装目录与卸载项）</p>",
+        simulation.removed_by_uninstaller
+    ));
+
+    if let Some(trial) = &simulation.sandbox {
+        html.push_str(&format!(
+            r#"<h2 class="section-title">沙盒试运行</h2><p>{}</p>"#,
+            match trial.exit_code {
+                Some(code) => format!("退出码 {}，用时 {} 秒", code, trial.duration_secs),
+                None => format!("{} 秒内未结束", trial.duration_secs),
+            }
+        ));
+        if !trial.findings.is_empty() {
+            html.push_str(&generate_warnings(&trial.findings));
+        }
+    }
+
+    if !simulation.elevation_required.is_empty() {
+        let items: String = simulation
+            .elevation_required
+            .iter()
+            .map(|item| format!(r#"<li class="path">{}</li>"#, escape_html(item)))
+            .collect();
+        html.push_str(&format!(
+            r#"<h2 class="section-title">需要管理员权限{}</h2><ul>{}</ul>"#,
+            if simulation.elevated {
+                "（当前已提升）"
+            } else {
+                ""
+            },
+            items
+        ));
+    }
+
+    if simulation.remaining.is_empty() {
+        html.push_str("<p>预计不会残留痕迹</p>");
+        return html;
+    }
+
+    html.push_str(
+        r#"
+        <h2 class="section-title">预计残留</h2>
+        <table>
+            <thead>
+                <tr>
+                    <th>清理</th>
+                    <th>类型</th>
+                    <th>路径</th>
+                    <th>大小</th>
+                </tr>
+            </thead>
+            <tbody>
+    "#,
+    );
+
+    for trace in &simulation.remaining {
+        let verdict = simulation
+            .blocked
+            .iter()
+            .find(|verdict| verdict.trace_id == trace.id);
+        let status_html = match verdict.and_then(|verdict| verdict.reason.as_deref()) {
+            Some(reason) => format!(
+                r#"<span class="status failed">拦截</span> {}"#,
+                escape_html(reason)
+            ),
+            None => r#"<span class="status success">可清理</span>"#.to_string(),
+        };
+        let size_html = trace
+            .size
+            .map(utils::format_size)
+            .unwrap_or_else(|| "-".to_string());
+
+        html.push_str(&format!(
+            r#"
+                <tr>
+                    <td>{}</td>
+                    <td><span class="type-badge">{}</span></td>
+                    <td class="path">{}</td>
+                    <td>{}</td>
+                </tr>
+        "#,
+            status_html,
+            trace.trace_type,
+            escape_html(&trace.path),
+            size_html,
+        ));
+    }
+
+    html.push_str("</tbody></table>");
+
+    html
+}
+
+/// 注册表痕迹的内容统计，便于区分空的残留项和较大的设置树
+fn generate_registry_table(traces: &[Trace]) -> String {
+    let measured: Vec<_> = traces
+        .iter()
+        .filter_map(|trace| trace.registry_stats.map(|stats| (trace, stats)))
+        .collect();
+    if measured.is_empty() {
+        return String::new();
+    }
+
+    let mut html = String::from(
+        r#"
+        <h2 class="section-title">注册表痕迹</h2>
+        <table>
+            <thead>
+                <tr>
+                    <th>路径</th>
+                    <th>子项</th>
+                    <th>值</th>
+                    <th>数据量</th>
+                </tr>
+            </thead>
+            <tbody>
+    "#,
+    );
+
+    for (trace, stats) in measured {
+        let size_html = if stats.is_empty() {
+            r#"<span class="status success">空项</span>"#.to_string()
+        } else if stats.truncated {
+            format!("至少 {}", utils::format_size(stats.data_size))
+        } else {
+            utils::format_size(stats.data_size)
+        };
+
+        html.push_str(&format!(
+            r#"
+                <tr>
+                    <td class="path">{}</td>
+                    <td>{}</td>
+                    <td>{}</td>
+                    <td>{}</td>
+                </tr>
+        "#,
+            escape_html(&trace.path),
+            stats.subkey_count,
+            stats.value_count,
+            size_html,
+        ));
+    }
+
+    html.push_str("</tbody></table>");
+
+    html
+}
+
+fn generate_warnings(warnings: &[String]) -> String {
+    if warnings.is_empty() {
+        return String::new();
+    }
+
+    let items: String = warnings
+        .iter()
+        .map(|warning| format!("<li>{}</li>", escape_html(warning)))
+        .collect();
+    format!(
+        r#"<div class="warnings"><h3>警告</h3><ul>{}</ul></div>"#,
+        items
+    )
+}
+
+/// 各扫描器的耗时与命中数，未正常完成的扫描器标记为失败
+fn generate_scanner_table(scanners: &[ScannerStats]) -> String {
+    if scanners.is_empty() {
+        return String::new();
+    }
+
+    let mut html = String::from(
+        r#"
+        <h2 class="section-title">扫描统计</h2>
+        <table>
+            <thead>
+                <tr>
+                    <th>扫描器</th>
+                    <th>名称</th>
+                    <th>耗时</th>
+                    <th>候选</th>
+                    <th>命中</th>
+                    <th>状态</th>
+                </tr>
+            </thead>
+            <tbody>
+    "#,
+    );
+
+    for scanner in scanners {
+        let status_html = match (&scanner.error, scanner.timed_out) {
+            (Some(error), _) => format!(
+                r#"<span class="status failed">失败</span> {}"#,
+                escape_html(error)
+            ),
+            (None, true) => r#"<span class="status failed">超时</span>"#.to_string(),
+            (None, false) => r#"<span class="status success">完成</span>"#.to_string(),
+        };
+
+        html.push_str(&format!(
+            r#"
+                <tr>
+                    <td>{}</td>
+                    <td>{}</td>
+                    <td>{:.0} ms</td>
+                    <td>{}</td>
+                    <td>{}</td>
+                    <td>{}</td>
+                </tr>
+        "#,
+            escape_html(&scanner.label),
+            escape_html(&scanner.pattern),
+            scanner.duration_ms,
+            scanner.examined,
+            scanner.matched,
+            status_html,
+        ));
+    }
+
+    html.push_str("</tbody></table>");
+
+    html
+}
+
+/// 审核页面的样式和脚本，不经过 `format!`，花括号无需转义
+const REVIEW_PAGE_STYLE: &str = r#"
+        * { margin: 0; padding: 0; box-sizing: border-box; }
+        body { font-family: "Segoe UI", "Microsoft YaHei", sans-serif; background: #f0f2f5; padding: 20px; color: #2c3e50; }
+        .container { max-width: 1200px; margin: 0 auto; background: white; border-radius: 12px; box-shadow: 0 4px 20px rgba(0,0,0,0.1); overflow: hidden; }
+        .header { background: #2c3e50; color: white; padding: 24px 30px; }
+        .header h1 { font-size: 24px; margin-bottom: 8px; }
+        .header .meta { opacity: 0.8; font-size: 14px; }
+        .toolbar { display: flex; gap: 10px; align-items: center; padding: 16px 30px; background: #f8f9fa; position: sticky; top: 0; border-bottom: 1px solid #e9ecef; }
+        .toolbar button { padding: 6px 14px; border: 1px solid #ced4da; border-radius: 6px; background: white; cursor: pointer; }
+        .toolbar button.primary { background: #2c3e50; color: white; border-color: #2c3e50; }
+        .toolbar .selection { margin-left: auto; font-size: 14px; color: #6c757d; }
+        .content { padding: 20px 30px; }
+        details { margin-bottom: 16px; border: 1px solid #e9ecef; border-radius: 8px; }
+        summary { padding: 10px 14px; cursor: pointer; font-weight: 600; background: #f8f9fa; }
+        table { width: 100%; border-collapse: collapse; font-size: 13px; }
+        th, td { padding: 8px 10px; text-align: left; border-top: 1px solid #e9ecef; vertical-align: top; }
+        th { color: #6c757d; font-weight: 600; }
+        .path { font-family: Consolas, monospace; word-break: break-all; }
+        .confidence { padding: 2px 8px; border-radius: 10px; font-size: 12px; white-space: nowrap; }
+        .confidence.high { background: #d4edda; color: #155724; }
+        .confidence.medium { background: #fff3cd; color: #856404; }
+        .confidence.low { background: #f8d7da; color: #721c24; }
+        .risk { color: #6c757d; }
+        .risk .note { color: #c0392b; }
+"#;
+
+const REVIEW_PAGE_SCRIPT: &str = r#"
+        const exportData = JSON.parse(document.getElementById('rust-yu-export').textContent);
+        const boxes = () => Array.from(document.querySelectorAll('input.trace'));
+        function updateSelection() {
+            const checked = boxes().filter(box => box.checked).length;
+            document.getElementById('selection').textContent = '已选择 ' + checked + ' / ' + boxes().length + ' 项';
+        }
+        function selectAll(checked) {
+            boxes().forEach(box => { box.checked = checked; });
+            updateSelection();
+        }
+        function saveApproved() {
+            const approved = boxes().filter(box => box.checked).map(box => box.value);
+            const result = Object.assign({}, exportData, { approved: approved });
+            const blob = new Blob([JSON.stringify(result, null, 2)], { type: 'application/json' });
+            const link = document.createElement('a');
+            link.href = URL.createObjectURL(blob);
+            link.download = (exportData.program_name || 'traces') + '-approved.json';
+            link.click();
+            URL.revokeObjectURL(link.href);
+        }
+        boxes().forEach(box => box.addEventListener('change', updateSelection));
+        updateSelection();
+"#;
+
+/// 生成可离线审核的痕迹导出页面
+///
+/// 痕迹按类型分组，组内按路径排序并缩进显示父子关系。页面中勾选后另存的 JSON 带有
+/// `approved` 列表，可由 `clean --from-file` 读取。未经审核的导出默认只勾选中高置信度、
+/// 不含用户数据且不属于其他用户的痕迹。
+pub fn generate_trace_export_html(export: &TraceExport) -> Result<String, UninstallerError> {
+    let total_size: u64 = export.traces.iter().filter_map(|trace| trace.size).sum();
+    let mut groups: Vec<(TraceType, Vec<&Trace>)> = Vec::new();
+    for trace in &export.traces {
+        match groups
+            .iter_mut()
+            .find(|(kind, _)| *kind == trace.trace_type)
+        {
+            Some((_, traces)) => traces.push(trace),
+            None => groups.push((trace.trace_type, vec![trace])),
+        }
+    }
+
+    let mut sections = String::new();
+    for (kind, mut traces) in groups {
+        traces.sort_by_key(|trace| tree_key(&trace.path));
+        let group_size: u64 = traces.iter().filter_map(|trace| trace.size).sum();
+        sections.push_str(&format!(
+            r#"
+        <details open>
+            <summary>{} ({} 项{})</summary>
+            <table>
+                <thead>
+                    <tr>
+                        <th></th>
+                        <th>路径</th>
+                        <th>置信度</th>
+                        <th>大小</th>
+                        <th>风险说明</th>
+                    </tr>
+                </thead>
+                <tbody>"#,
+            kind,
+            traces.len(),
+            if group_size > 0 {
+                format!("，{}", utils::format_size(group_size))
+            } else {
+                String::new()
+            },
+        ));
+
+        // 祖先路径栈，用于计算缩进层级
+        let mut ancestors: Vec<String> = Vec::new();
+        for trace in traces {
+            let key = tree_key(&trace.path);
+            while ancestors
+                .last()
+                .is_some_and(|parent| !key.starts_with(&format!(r"{}\", parent)))
+            {
+                ancestors.pop();
+            }
+            let depth = ancestors.len();
+            ancestors.push(key);
+
+            let checked = match &export.approved {
+                Some(approved) => approved.contains(&trace.id),
+                None => {
+                    trace.confidence != Confidence::Low
+                        && trace.user_data.is_none()
+                        && trace.owner.is_none()
+                }
+            };
+            sections.push_str(&format!(
+                r#"
+                    <tr>
+                        <td><input type="checkbox" class="trace" value="{}"{}></td>
+                        <td class="path" style="padding-left: {}px">{}<div class="risk">{}</div></td>
+                        <td>{} {}</td>
+                        <td>{}</td>
+                        <td class="risk">{}</td>
+                    </tr>"#,
+                escape_html(&trace.id),
+                if checked { " checked" } else { "" },
+                10 + depth * 20,
+                escape_html(&trace.path),
+                escape_html(&trace.description),
+                confidence_badge(trace.confidence),
+                trace.score,
+                trace.size.map(utils::format_size).unwrap_or_default(),
+                risk_notes(trace),
+            ));
+        }
+        sections.push_str("</tbody></table></details>");
+    }
+    if sections.is_empty() {
+        sections.push_str("<p>没有痕迹</p>");
+    }
+
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>痕迹审核 - {program}</title>
+    <style>{style}</style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>痕迹审核: {program}</h1>
+            <div class="meta">扫描时间: {exported_at} · 共 {count} 项 · {size} · rust-yu {tool_version}</div>
+        </div>
+        <div class="toolbar">
+            <button onclick="selectAll(true)">全选</button>
+            <button onclick="selectAll(false)">全不选</button>
+            <button class="primary" onclick="saveApproved()">保存审核结果</button>
+            <span class="selection" id="selection"></span>
+        </div>
+        <div class="content">
+            <p class="risk">勾选要清理的痕迹后保存审核结果，将保存的文件交给执行人，使用 <code>clean --from-file</code> 只清理已批准的痕迹。</p>
+            {sections}
+        </div>
+    </div>
+"#,
+        program = escape_html(&export.program_name),
+        style = REVIEW_PAGE_STYLE,
+        exported_at = format::format_datetime(&export.exported_at),
+        count = export.traces.len(),
+        size = utils::format_size(total_size),
+        tool_version = escape_html(&export.tool_version),
+        sections = sections,
+    );
+    html.push_str(&format!(
+        "    <script type=\"application/json\" id=\"{}\">{}</script>\n",
+        export::HTML_EXPORT_ELEMENT_ID,
+        export::embedded_json(export)?
+    ));
+    html.push_str(&format!(
+        "    <script>{}</script>\n</body>\n</html>\n",
+        REVIEW_PAGE_SCRIPT
+    ));
+
+    Ok(html)
+}
+
+/// 排序和判断父子关系用的路径
+fn tree_key(path: &str) -> String {
+    utils::normalize_path(path)
+        .trim_end_matches('\\')
+        .to_lowercase()
+}
+
+fn confidence_badge(confidence: Confidence) -> &'static str {
+    match confidence {
+        Confidence::High => r#"<span class="confidence high">高</span>"#,
+        Confidence::Medium => r#"<span class="confidence medium">中</span>"#,
+        Confidence::Low => r#"<span class="confidence low">低</span>"#,
+    }
+}
+
+/// 命中原因、删除影响和需要注意的事项
+fn risk_notes(trace: &Trace) -> String {
+    let mut lines = Vec::new();
+    if !trace.risk.match_reason.is_empty() {
+        lines.push(format!("命中: {}", escape_html(&trace.risk.match_reason)));
+    }
+    if !trace.risk.impact.is_empty() {
+        lines.push(format!(
+            "影响: {}{}{}",
+            escape_html(&trace.risk.impact),
+            if trace.risk.shared {
+                "；可能被共用"
+            } else {
+                ""
+            },
+            if trace.risk.reversible {
+                "；可恢复"
+            } else {
+                "；不可恢复"
+            }
+        ));
+    }
+    let notes = trace
+        .risk
+        .notes
+        .iter()
+        .chain(trace.user_data.iter())
+        .chain(trace.cloud_sync.iter());
+    for note in notes {
+        lines.push(format!(
+            r#"<span class="note">{}</span>"#,
+            escape_html(note)
+        ));
+    }
+    if let Some(owner) = &trace.owner {
+        lines.push(format!(
+            r#"<span class="note">属于用户 {}</span>"#,
+            escape_html(owner)
+        ));
+    }
+    lines.join("<br>")
+}
+
 fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -0,0 +1,357 @@
+//! 终端管理平台清单导出
+//!
+//! 把程序列表和卸载结果导出为 Intune (Graph win32LobApp) JSON 或 SCCM 软件清单 CSV，
+//! 每个程序附带由注册表项、安装目录和 MSI 产品代码推导出的检测规则，
+//! 便于直接导入现有的企业管理工具核对卸载情况。
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::models::{InstalledProgram, ProgramHistoryEntry};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+
+/// 程序在导出时的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InventoryStatus {
+    /// 当前已安装
+    Installed,
+    /// 曾经安装，现已卸载
+    Removed,
+}
+
+/// 检测规则，判断程序是否仍然存在
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DetectionRule {
+    /// 注册表项存在；`value_name` 与 `expected` 都有值时比较版本
+    Registry {
+        key_path: String,
+        value_name: Option<String>,
+        expected: Option<String>,
+        /// 位于 WOW6432Node 下，即 32 位视图
+        check_32bit_on_64: bool,
+    },
+    /// 文件或目录存在
+    File {
+        path: String,
+        file_or_folder_name: String,
+    },
+    /// MSI 产品代码已安装
+    ProductCode {
+        product_code: String,
+        product_version: Option<String>,
+    },
+}
+
+/// 导出的单个程序
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryRecord {
+    pub program: InstalledProgram,
+    pub status: InventoryStatus,
+    /// 最后一次在程序列表中出现的时间，仅对已卸载程序有意义
+    pub last_seen_at: Option<String>,
+    pub detection_rules: Vec<DetectionRule>,
+}
+
+/// 合并当前程序列表和安装历史：历史中存在但当前列表没有的程序视为已卸载
+pub fn build_inventory(
+    installed: &[InstalledProgram],
+    history: &[ProgramHistoryEntry],
+) -> Vec<InventoryRecord> {
+    let installed_names: HashSet<String> =
+        installed.iter().map(|p| p.name.to_lowercase()).collect();
+
+    let mut records: Vec<InventoryRecord> = installed
+        .iter()
+        .map(|program| InventoryRecord {
+            detection_rules: detection_rules(program),
+            program: program.clone(),
+            status: InventoryStatus::Installed,
+            last_seen_at: None,
+        })
+        .collect();
+
+    records.extend(
+        history
+            .iter()
+            .filter(|entry| !installed_names.contains(&entry.program.name.to_lowercase()))
+            .map(|entry| InventoryRecord {
+                detection_rules: detection_rules(&entry.program),
+                program: entry.program.clone(),
+                status: InventoryStatus::Removed,
+                last_seen_at: Some(entry.last_seen_at.clone()),
+            }),
+    );
+
+    records
+}
+
+/// 由程序信息推导检测规则，优先级：MSI 产品代码 > 卸载注册表项 > 安装目录
+pub fn detection_rules(program: &InstalledProgram) -> Vec<DetectionRule> {
+    let mut rules = Vec::new();
+
+    if let Some(product_code) = find_product_code(program) {
+        rules.push(DetectionRule::ProductCode {
+            product_code,
+            product_version: program.version.clone(),
+        });
+    }
+
+    if let Some(key) = &program.registry_key {
+        let check_32bit_on_64 = key.to_lowercase().contains(r"\wow6432node\");
+        // 与 Intune 约定一致：32 位视图通过标志位区分，路径使用原生形式
+        let key_path = utils::full_registry_path(&key.replace(r"\WOW6432Node\", r"\"));
+        let (value_name, expected) = match &program.version {
+            Some(version) => (Some("DisplayVersion".to_string()), Some(version.clone())),
+            None => (None, None),
+        };
+        rules.push(DetectionRule::Registry {
+            key_path,
+            value_name,
+            expected,
+            check_32bit_on_64,
+        });
+    }
+
+    if let Some(location) = program
+        .install_location
+        .as_deref()
+        .map(|l| l.trim().trim_matches('"').trim_end_matches('\\'))
+        .filter(|l| !l.is_empty())
+    {
+        if let Some((parent, folder)) = location.rsplit_once('\\') {
+            if !folder.is_empty() && !parent.is_empty() {
+                rules.push(DetectionRule::File {
+                    path: parent.to_string(),
+                    file_or_folder_name: folder.to_string(),
+                });
+            }
+        }
+    }
+
+    rules
+}
+
+/// 从卸载命令或注册表项名称中提取 MSI 产品代码
+fn find_product_code(program: &InstalledProgram) -> Option<String> {
+    let uninstall = program.uninstall_string.as_deref().unwrap_or_default();
+    let from_command = uninstall
+        .to_lowercase()
+        .contains("msiexec")
+        .then(|| utils::find_guid(uninstall))
+        .flatten();
+
+    from_command
+        .or_else(|| {
+            program
+                .registry_key
+                .as_deref()
+                .and_then(|key| key.rsplit('\\').next())
+                .and_then(utils::find_guid)
+        })
+        .map(|code| code.to_uppercase())
+}
+
+/// 导出为 Microsoft Graph win32LobApp 对象数组（Intune 导入格式）
+pub fn to_intune_json(records: &[InventoryRecord]) -> Result<String, UninstallerError> {
+    let apps: Vec<serde_json::Value> = records
+        .iter()
+        .map(|record| {
+            let program = &record.program;
+            let rules: Vec<serde_json::Value> =
+                record.detection_rules.iter().map(intune_rule).collect();
+            let notes = match record.status {
+                InventoryStatus::Installed => "rust-yu: installed".to_string(),
+                InventoryStatus::Removed => format!(
+                    "rust-yu: removed (last seen {})",
+                    record.last_seen_at.as_deref().unwrap_or("unknown")
+                ),
+            };
+
+            json!({
+                "@odata.type": "#microsoft.graph.win32LobApp",
+                "displayName": program.name,
+                "publisher": program.publisher.clone().unwrap_or_default(),
+                "displayVersion": program.version,
+                "uninstallCommandLine": program.uninstall_string,
+                "notes": notes,
+                "rules": rules,
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&apps).map_err(|error| UninstallerError::Serde(error.to_string()))
+}
+
+fn intune_rule(rule: &DetectionRule) -> serde_json::Value {
+    match rule {
+        DetectionRule::Registry {
+            key_path,
+            value_name,
+            expected,
+            check_32bit_on_64,
+        } => json!({
+            "@odata.type": "#microsoft.graph.win32LobAppRegistryRule",
+            "ruleType": "detection",
+            "check32BitOn64System": check_32bit_on_64,
+            "keyPath": key_path,
+            "valueName": value_name,
+            "operationType": if expected.is_some() { "version" } else { "exists" },
+            "operator": if expected.is_some() { "equal" } else { "notConfigured" },
+            "comparisonValue": expected,
+        }),
+        DetectionRule::File {
+            path,
+            file_or_folder_name,
+        } => json!({
+            "@odata.type": "#microsoft.graph.win32LobAppFileSystemRule",
+            "ruleType": "detection",
+            "check32BitOn64System": false,
+            "path": path,
+            "fileOrFolderName": file_or_folder_name,
+            "operationType": "exists",
+            "operator": "notConfigured",
+            "comparisonValue": null,
+        }),
+        DetectionRule::ProductCode {
+            product_code,
+            product_version,
+        } => json!({
+            "@odata.type": "#microsoft.graph.win32LobAppProductCodeRule",
+            "ruleType": "detection",
+            "productCode": product_code,
+            "productVersionOperator": if product_version.is_some() { "equal" } else { "notConfigured" },
+            "productVersion": product_version,
+        }),
+    }
+}
+
+/// 导出为 SCCM 软件清单 CSV，列名沿用 Add/Remove Programs 清单类
+pub fn to_sccm_csv(records: &[InventoryRecord], computer_name: &str) -> String {
+    let mut csv = String::from(
+        "Name0,DisplayName0,Publisher0,Version0,InstallDate0,ProdID0,Status,LastSeen,DetectionMethod\r\n",
+    );
+
+    for record in records {
+        let program = &record.program;
+        let prod_id = find_product_code(program).or_else(|| {
+            program
+                .registry_key
+                .as_deref()
+                .and_then(|key| key.rsplit('\\').next())
+                .map(str::to_string)
+        });
+        let status = match record.status {
+            InventoryStatus::Installed => "Installed",
+            InventoryStatus::Removed => "Removed",
+        };
+        let detection = record
+            .detection_rules
+            .iter()
+            .map(sccm_rule)
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        let fields = [
+            computer_name,
+            &program.name,
+            program.publisher.as_deref().unwrap_or_default(),
+            program.version.as_deref().unwrap_or_default(),
+            program.install_date.as_deref().unwrap_or_default(),
+            prod_id.as_deref().unwrap_or_default(),
+            status,
+            record.last_seen_at.as_deref().unwrap_or_default(),
+            &detection,
+        ];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&line.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// SCCM 检测方法的文本描述
+fn sccm_rule(rule: &DetectionRule) -> String {
+    match rule {
+        DetectionRule::Registry {
+            key_path,
+            value_name,
+            expected,
+            check_32bit_on_64,
+        } => {
+            let mut text = format!("Registry:{}", key_path);
+            if let (Some(name), Some(value)) = (value_name, expected) {
+                text.push_str(&format!(" {}={}", name, value));
+            }
+            if *check_32bit_on_64 {
+                text.push_str(" (32-bit)");
+            }
+            text
+        }
+        DetectionRule::File {
+            path,
+            file_or_folder_name,
+        } => format!(r"File:{}\{}", path, file_or_folder_name),
+        DetectionRule::ProductCode { product_code, .. } => format!("MSI:{}", product_code),
+    }
+}
+
+/// CSV 字段转义：含逗号、引号或换行时整体加引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn derives_rules_from_msi_registry_and_location() {
+        let mut program =
+            InstalledProgram::new("Foo, Inc. Tool".to_string(), InstallSource::Registry);
+        program.version = Some("1.2.3".to_string());
+        program.uninstall_string =
+            Some("MsiExec.exe /X{12345678-ABCD-ef01-2345-6789ABCDEF01}".to_string());
+        program.registry_key = Some(
+            r"HKLM\SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall\{12345678-ABCD-ef01-2345-6789ABCDEF01}"
+                .to_string(),
+        );
+        program.install_location = Some(r"C:\Program Files (x86)\Foo\".to_string());
+
+        let rules = detection_rules(&program);
+        assert_eq!(
+            rules[0],
+            DetectionRule::ProductCode {
+                product_code: "{12345678-ABCD-EF01-2345-6789ABCDEF01}".to_string(),
+                product_version: Some("1.2.3".to_string()),
+            }
+        );
+        assert!(matches!(
+            &rules[1],
+            DetectionRule::Registry { key_path, check_32bit_on_64: true, .. }
+                if key_path.starts_with(r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\")
+        ));
+        assert_eq!(
+            rules[2],
+            DetectionRule::File {
+                path: r"C:\Program Files (x86)".to_string(),
+                file_or_folder_name: "Foo".to_string(),
+            }
+        );
+
+        let records = build_inventory(&[program], &[]);
+        let csv = to_sccm_csv(&records, "PC01");
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("PC01,\"Foo, Inc. Tool\",,1.2.3,"));
+    }
+}
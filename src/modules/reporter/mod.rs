@@ -1,2 +1,12 @@
 pub mod html;
+pub mod inventory;
 pub mod models;
+pub mod statistics;
+pub mod storage;
+
+use std::path::PathBuf;
+
+/// 报告存放目录
+pub fn get_reports_dir() -> PathBuf {
+    storage::reports_dir()
+}
@@ -1,5 +1,6 @@
 use crate::modules::cleaner::models::CleanResult;
-use crate::modules::scanner::models::Trace;
+use crate::modules::scanner::models::{ScannerStats, Trace};
+use crate::modules::uninstaller::simulation::UninstallSimulation;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +15,12 @@ pub struct UninstallerReport {
     pub total_size_freed: u64,
     pub success: bool,
     pub warnings: Vec<String>,
+    /// 扫描阶段各扫描器的统计
+    #[serde(default)]
+    pub scanners: Vec<ScannerStats>,
+    /// 卸载模拟结果，模拟报告不包含实际删除记录
+    #[serde(default)]
+    pub simulation: Option<UninstallSimulation>,
 }
 
 #[allow(dead_code)]
@@ -28,6 +35,8 @@ impl UninstallerReport {
             total_size_freed: 0,
             success: true,
             warnings: Vec::new(),
+            scanners: Vec::new(),
+            simulation: None,
         }
     }
 
@@ -43,6 +52,31 @@ impl UninstallerReport {
         self
     }
 
+    /// 记录扫描器统计，未正常完成的扫描器同时计入警告
+    pub fn with_scanner_stats(mut self, scanners: Vec<ScannerStats>) -> Self {
+        for scanner in scanners.iter().filter(|s| s.is_incomplete()) {
+            self.warnings.push(match &scanner.error {
+                Some(error) => {
+                    format!("{}扫描失败 ({}): {}", scanner.label, scanner.pattern, error)
+                }
+                None => format!(
+                    "{}扫描超时 ({})，结果可能不完整",
+                    scanner.label, scanner.pattern
+                ),
+            });
+        }
+        self.scanners = scanners;
+        self
+    }
+
+    /// 记录卸载模拟结果，预计释放的空间计入报告
+    pub fn with_simulation(mut self, simulation: UninstallSimulation) -> Self {
+        self.total_size_freed = simulation.estimated_reclaimed;
+        self.warnings.extend(simulation.notes.iter().cloned());
+        self.simulation = Some(simulation);
+        self
+    }
+
     pub fn add_warning(&mut self, warning: String) {
         self.warnings.push(warning);
     }
@@ -0,0 +1,160 @@
+//! 残留统计
+//!
+//! 按程序取最近一份报告，扣除报告中已成功删除的痕迹，按痕迹类型和置信度汇总数量与大小。
+//! 界面首页据此显示“共 27 个程序留下约 3.4 GB 残留”，不需要加载完整的痕迹列表。
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::models::UninstallerReport;
+use super::storage;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::scanner::models::{Confidence, TraceType};
+
+/// 某种痕迹类型在某个置信度下的数量和大小
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceTypeStats {
+    pub trace_type: TraceType,
+    pub confidence: Confidence,
+    pub count: usize,
+    pub total_size: u64,
+}
+
+/// 单个程序最近一次扫描剩余的残留
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramLeftoverStats {
+    pub program_name: String,
+    pub report_id: String,
+    pub generated_at: DateTime<Utc>,
+    pub trace_count: usize,
+    pub total_size: u64,
+}
+
+/// 所有程序的残留汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LeftoverStatistics {
+    /// 仍有残留的程序数
+    pub program_count: usize,
+    pub trace_count: usize,
+    pub total_size: u64,
+    /// 按痕迹类型和置信度分组，大小降序
+    pub by_type: Vec<TraceTypeStats>,
+    /// 按程序分组，大小降序
+    pub programs: Vec<ProgramLeftoverStats>,
+}
+
+/// 汇总报告目录中每个程序最近一次扫描的残留
+pub fn leftover_statistics() -> Result<LeftoverStatistics, UninstallerError> {
+    Ok(aggregate(&storage::load_reports()?))
+}
+
+/// `reports` 需按生成时间从新到旧排列，每个程序只取第一份
+fn aggregate(reports: &[UninstallerReport]) -> LeftoverStatistics {
+    let mut statistics = LeftoverStatistics::default();
+    let mut seen = HashSet::new();
+
+    for report in reports {
+        if !seen.insert(report.program_name.trim().to_lowercase()) {
+            continue;
+        }
+
+        let removed: HashSet<&str> = report
+            .traces_removed
+            .iter()
+            .filter(|result| result.success)
+            .map(|result| result.trace_id.as_str())
+            .collect();
+        let mut program = ProgramLeftoverStats {
+            program_name: report.program_name.clone(),
+            report_id: report.id.clone(),
+            generated_at: report.generated_at,
+            trace_count: 0,
+            total_size: 0,
+        };
+
+        for trace in report
+            .traces_found
+            .iter()
+            .filter(|trace| trace.exists && !removed.contains(trace.id.as_str()))
+        {
+            let size = trace.size.unwrap_or(0);
+            program.trace_count += 1;
+            program.total_size += size;
+
+            match statistics.by_type.iter_mut().find(|group| {
+                group.trace_type == trace.trace_type && group.confidence == trace.confidence
+            }) {
+                Some(group) => {
+                    group.count += 1;
+                    group.total_size += size;
+                }
+                None => statistics.by_type.push(TraceTypeStats {
+                    trace_type: trace.trace_type,
+                    confidence: trace.confidence,
+                    count: 1,
+                    total_size: size,
+                }),
+            }
+        }
+
+        if program.trace_count > 0 {
+            statistics.trace_count += program.trace_count;
+            statistics.total_size += program.total_size;
+            statistics.programs.push(program);
+        }
+    }
+
+    statistics.program_count = statistics.programs.len();
+    statistics.by_type.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then(b.count.cmp(&a.count))
+            .then(a.confidence.cmp(&b.confidence))
+    });
+    statistics
+        .programs
+        .sort_by_key(|program| std::cmp::Reverse(program.total_size));
+    statistics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::cleaner::models::CleanResult;
+    use crate::modules::scanner::models::Trace;
+
+    #[test]
+    fn counts_remaining_traces_from_latest_report_per_program() {
+        let trace = |path: &str, trace_type, size| {
+            Trace::new("Acme".to_string(), trace_type, path.to_string())
+                .with_confidence(Confidence::High)
+                .with_size(size)
+        };
+
+        let mut latest = UninstallerReport::new("Acme".to_string());
+        latest.traces_found = vec![
+            trace(r"C:\Acme\cache", TraceType::File, 300),
+            trace(r"C:\Acme\logs", TraceType::File, 200),
+            trace(r"HKCU\Software\Acme", TraceType::RegistryKey, 0),
+        ];
+        latest.traces_removed = vec![CleanResult {
+            trace_id: latest.traces_found[1].id.clone(),
+            path: r"C:\Acme\logs".to_string(),
+            success: true,
+            error: None,
+            bytes_freed: 200,
+        }];
+        let mut older = UninstallerReport::new("acme".to_string());
+        older.traces_found = vec![trace(r"C:\Acme\old", TraceType::File, 9000)];
+
+        let statistics = aggregate(&[latest, older]);
+        assert_eq!(statistics.program_count, 1);
+        assert_eq!(statistics.trace_count, 2);
+        assert_eq!(statistics.total_size, 300);
+        assert_eq!(statistics.by_type.len(), 2);
+        assert_eq!(statistics.by_type[0].trace_type, TraceType::File);
+        assert_eq!(statistics.by_type[0].total_size, 300);
+    }
+}
@@ -0,0 +1,215 @@
+//! 报告存储
+//!
+//! 命令行、定期维护和界面共用同一个报告目录：每份报告以 `<id>.json` 保存完整数据，
+//! 同时写出 `<id>.html` 便于直接查看。目录默认位于本地应用数据目录下，
+//! 可通过环境变量 `RUST_YU_REPORTS_DIR` 指定。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use super::html;
+use super::models::UninstallerReport;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::format;
+
+/// 指定报告目录的环境变量
+pub const REPORTS_DIR_ENV: &str = "RUST_YU_REPORTS_DIR";
+
+/// 已保存报告的索引信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredReport {
+    pub id: String,
+    pub program_name: String,
+    pub generated_at: String,
+    pub json_path: String,
+    /// HTML 文件不存在时为 None
+    pub html_path: Option<String>,
+}
+
+/// 报告目录
+pub fn reports_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(REPORTS_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rust-yu")
+        .join("reports")
+}
+
+/// 保存报告的 JSON 与 HTML，返回索引信息
+pub fn save_report(report: &UninstallerReport) -> Result<StoredReport, UninstallerError> {
+    let dir = reports_dir();
+    std::fs::create_dir_all(&dir)?;
+
+    let json_path = dir.join(format!("{}.json", report.id));
+    let html_path = dir.join(format!("{}.html", report.id));
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    std::fs::write(&json_path, json)?;
+    std::fs::write(&html_path, html::generate_html_report(report)?)?;
+
+    Ok(stored_report(report, &json_path))
+}
+
+/// 列出所有报告，最新的在前；无法解析的文件跳过
+pub fn list_reports() -> Result<Vec<StoredReport>, UninstallerError> {
+    Ok(read_reports()?
+        .iter()
+        .map(|(report, path)| stored_report(report, path))
+        .collect())
+}
+
+/// 读取所有报告的完整数据，最新的在前；无法解析的文件跳过
+pub fn load_reports() -> Result<Vec<UninstallerReport>, UninstallerError> {
+    Ok(read_reports()?
+        .into_iter()
+        .map(|(report, _)| report)
+        .collect())
+}
+
+fn read_reports() -> Result<Vec<(UninstallerReport, PathBuf)>, UninstallerError> {
+    let dir = reports_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut reports: Vec<(UninstallerReport, PathBuf)> = std::fs::read_dir(&dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let report = serde_json::from_str::<UninstallerReport>(&content).ok()?;
+            Some((report, path))
+        })
+        .collect();
+    reports.sort_by_key(|(report, _)| std::cmp::Reverse(report.generated_at));
+    Ok(reports)
+}
+
+/// 按报告 id 读取报告
+pub fn load_report(id: &str) -> Result<UninstallerReport, UninstallerError> {
+    let path = reports_dir().join(format!("{}.json", id));
+    if !path.exists() {
+        return Err(UninstallerError::NotFound(format!("报告不存在: {}", id)));
+    }
+    let content = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&content).map_err(|error| UninstallerError::Serde(error.to_string()))
+}
+
+/// 按 id 或程序名（不区分大小写、包含即可）查找报告，最新的在前
+pub fn find_reports(query: &str) -> Result<Vec<StoredReport>, UninstallerError> {
+    let query = query.trim().to_lowercase();
+    Ok(list_reports()?
+        .into_iter()
+        .filter(|report| {
+            report.id.to_lowercase() == query || report.program_name.to_lowercase().contains(&query)
+        })
+        .collect())
+}
+
+/// 删除报告的 JSON 与 HTML，返回是否删除了文件
+#[allow(dead_code)]
+pub fn delete_report(id: &str) -> Result<bool, UninstallerError> {
+    let dir = reports_dir();
+    let mut deleted = false;
+    for extension in ["json", "html"] {
+        let path = dir.join(format!("{}.{}", id, extension));
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+            deleted = true;
+        }
+    }
+    Ok(deleted)
+}
+
+/// 只保留最新的 `keep` 份报告（同名的 JSON 与 HTML 视为一份），返回删除的数量
+pub fn rotate_reports(dir: &Path, keep: usize) -> Result<usize, UninstallerError> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut reports: HashMap<String, (SystemTime, Vec<PathBuf>)> = HashMap::new();
+    for path in std::fs::read_dir(dir)?.flatten().map(|entry| entry.path()) {
+        if !path
+            .extension()
+            .is_some_and(|ext| ext == "html" || ext == "json")
+        {
+            continue;
+        }
+        let (Some(stem), Ok(modified)) = (
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string()),
+            path.metadata().and_then(|metadata| metadata.modified()),
+        ) else {
+            continue;
+        };
+        let entry = reports
+            .entry(stem)
+            .or_insert((SystemTime::UNIX_EPOCH, Vec::new()));
+        entry.0 = entry.0.max(modified);
+        entry.1.push(path);
+    }
+
+    // 新的在前
+    let mut reports: Vec<(SystemTime, Vec<PathBuf>)> = reports.into_values().collect();
+    reports.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    let mut removed = 0;
+    for (_, paths) in reports.into_iter().skip(keep) {
+        let mut all_removed = true;
+        for path in paths {
+            if let Err(e) = std::fs::remove_file(&path) {
+                tracing::warn!("删除旧报告失败 {}: {}", path.display(), e);
+                all_removed = false;
+            }
+        }
+        if all_removed {
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+fn stored_report(report: &UninstallerReport, json_path: &Path) -> StoredReport {
+    let html_path = json_path.with_extension("html");
+    StoredReport {
+        id: report.id.clone(),
+        program_name: report.program_name.clone(),
+        generated_at: format::format_datetime(&report.generated_at),
+        json_path: json_path.to_string_lossy().to_string(),
+        html_path: html_path
+            .exists()
+            .then(|| html_path.to_string_lossy().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_reports_keeps_newest() {
+        let dir = std::env::temp_dir().join(format!("rust-yu-rotate-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for i in 0..3 {
+            std::fs::write(dir.join(format!("report_{}.html", i)), "").unwrap();
+            std::fs::write(dir.join(format!("report_{}.json", i)), "").unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        assert_eq!(rotate_reports(&dir, 2).unwrap(), 1);
+        assert!(!dir.join("report_0.html").exists());
+        assert!(!dir.join("report_0.json").exists());
+        assert!(dir.join("report_2.html").exists());
+        assert!(dir.join("notes.txt").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,253 @@
+//! ActiveX 控件与浏览器加载项
+//!
+//! 老式企业软件常通过 IE 安装 ActiveX 控件（`Downloaded Program Files`、Code Store Database）
+//! 或注册浏览器帮助对象 (BHO) 与工具栏，卸载时很少一并清理。按 CLSID 汇总这些注册，
+//! 控件名称或控件文件路径与程序名匹配时报告一项 `ActiveX` 痕迹，清理时注销该 CLSID 的全部注册。
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+
+/// 浏览器帮助对象注册位置（HKLM）
+pub const BHO_KEYS: &[&str] = &[
+    r"SOFTWARE\Microsoft\Windows\CurrentVersion\Explorer\Browser Helper Objects",
+    r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Explorer\Browser Helper Objects",
+];
+
+/// 通过 IE 下载安装的控件（HKLM）
+pub const DISTRIBUTION_UNITS_KEY: &str =
+    r"SOFTWARE\Microsoft\Code Store Database\Distribution Units";
+
+/// IE 工具栏注册位置，值名为 CLSID（HKLM）
+pub const TOOLBAR_KEYS: &[&str] = &[
+    r"SOFTWARE\Microsoft\Internet Explorer\Toolbar",
+    r"SOFTWARE\WOW6432Node\Microsoft\Internet Explorer\Toolbar",
+];
+
+/// COM 类注册位置
+pub const CLSID_KEYS: &[(winreg::HKEY, &str)] = &[
+    (HKEY_LOCAL_MACHINE, r"SOFTWARE\Classes\CLSID"),
+    (HKEY_LOCAL_MACHINE, r"SOFTWARE\Classes\WOW6432Node\CLSID"),
+    (HKEY_CURRENT_USER, r"Software\Classes\CLSID"),
+];
+
+/// 注册类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Registration {
+    BrowserHelperObject,
+    DownloadedControl,
+    Toolbar,
+}
+
+impl Registration {
+    fn label(&self) -> &'static str {
+        match self {
+            Registration::BrowserHelperObject => "浏览器加载项 (BHO)",
+            Registration::DownloadedControl => "ActiveX 控件",
+            Registration::Toolbar => "IE 工具栏",
+        }
+    }
+}
+
+/// 扫描与程序相关的 ActiveX 控件和浏览器加载项，每发现一项即交给 `emit`
+pub fn scan_activex_traces(
+    program_name: &str,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let pattern = program_name.to_lowercase();
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let mut candidates: Vec<(Registration, String, String, Option<String>)> = Vec::new();
+
+    for bho_key in BHO_KEYS {
+        if let Ok(key) = hklm.open_subkey(bho_key) {
+            for clsid in key.enum_keys().flatten().filter(|name| is_clsid(name)) {
+                let label = key
+                    .open_subkey(&clsid)
+                    .and_then(|entry| entry.get_value::<String, _>(""))
+                    .ok();
+                let path = format!(r"HKLM\{}\{}", bho_key, clsid);
+                candidates.push((Registration::BrowserHelperObject, clsid, path, label));
+            }
+        }
+    }
+
+    if let Ok(key) = hklm.open_subkey(DISTRIBUTION_UNITS_KEY) {
+        for clsid in key.enum_keys().flatten().filter(|name| is_clsid(name)) {
+            let label = key
+                .open_subkey(&clsid)
+                .and_then(|entry| entry.get_value::<String, _>(""))
+                .ok();
+            let path = format!(r"HKLM\{}\{}", DISTRIBUTION_UNITS_KEY, clsid);
+            candidates.push((Registration::DownloadedControl, clsid, path, label));
+        }
+    }
+
+    for toolbar_key in TOOLBAR_KEYS {
+        if let Ok(key) = hklm.open_subkey(toolbar_key) {
+            for (clsid, _) in key.enum_values().flatten() {
+                if is_clsid(&clsid) {
+                    let path = format!(r"HKLM\{}\{}", toolbar_key, clsid);
+                    candidates.push((Registration::Toolbar, clsid, path, None));
+                }
+            }
+        }
+    }
+
+    // 同一控件可能同时注册为 BHO 和工具栏，只报告第一处
+    let mut seen = HashSet::new();
+    for (registration, clsid, path, label) in candidates {
+        if !seen.insert(clsid.to_lowercase()) {
+            continue;
+        }
+
+        let class_name = class_name(&clsid);
+        let server = server_path(&clsid);
+        // Windows 自带的控件
+        if server
+            .as_deref()
+            .is_some_and(|server| utils::is_system_critical_path(server) && !is_downloaded(server))
+        {
+            continue;
+        }
+
+        let name = label
+            .filter(|label| !label.trim().is_empty())
+            .or(class_name)
+            .unwrap_or_else(|| clsid.clone());
+        let (confidence, reason) = if server
+            .as_deref()
+            .is_some_and(|server| matching::name_matches(server, &pattern))
+        {
+            (Confidence::High, "控件文件路径包含程序名")
+        } else if matching::name_matches(&name, &pattern) {
+            (Confidence::Medium, "控件名称包含程序名")
+        } else {
+            continue;
+        };
+
+        let files = control_files(&clsid);
+        let size: u64 = files
+            .iter()
+            .filter_map(|file| std::fs::metadata(file).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+
+        let mut trace = Trace::new(pattern.clone(), TraceType::ActiveX, path)
+            .with_description(format!("{}: {} {}", registration.label(), name, clsid));
+        if size > 0 {
+            trace = trace.with_size(size);
+        }
+        trace.set_confidence(confidence);
+        trace.set_match_reason(reason.to_string());
+        emit(trace);
+    }
+
+    Ok(())
+}
+
+/// 从痕迹路径末尾取出 CLSID
+pub fn clsid_from_path(path: &str) -> Option<&str> {
+    path.rsplit('\\').next().filter(|last| is_clsid(last))
+}
+
+/// 是否为 `{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}` 形式的 CLSID
+pub fn is_clsid(value: &str) -> bool {
+    let Some(inner) = value
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+    else {
+        return false;
+    };
+    let groups: Vec<&str> = inner.split('-').collect();
+    groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+        && groups
+            .iter()
+            .all(|group| group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// `Downloaded Program Files` 目录
+pub fn downloaded_program_files_dir() -> PathBuf {
+    std::env::var_os("SystemRoot")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\Windows"))
+        .join("Downloaded Program Files")
+}
+
+/// 文件是否位于 `Downloaded Program Files` 中
+pub fn is_downloaded(path: &str) -> bool {
+    let dir = downloaded_program_files_dir()
+        .to_string_lossy()
+        .to_lowercase();
+    path.to_lowercase()
+        .strip_prefix(&dir)
+        .is_some_and(|rest| rest.starts_with('\\'))
+}
+
+/// 控件在 `Downloaded Program Files` 中的文件：控件本身和 Code Store Database 记录的文件
+pub fn control_files(clsid: &str) -> Vec<PathBuf> {
+    let mut files: Vec<String> = server_path(clsid).into_iter().collect();
+    if let Ok(contains) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(format!(
+        r"{}\{}\Contains\Files",
+        DISTRIBUTION_UNITS_KEY, clsid
+    )) {
+        files.extend(contains.enum_values().flatten().map(|(name, _)| name));
+    }
+
+    let mut seen = HashSet::new();
+    files
+        .into_iter()
+        .filter(|file| is_downloaded(file) && seen.insert(file.to_lowercase()))
+        .map(PathBuf::from)
+        .filter(|file| file.is_file())
+        .collect()
+}
+
+/// COM 类的显示名称
+fn class_name(clsid: &str) -> Option<String> {
+    CLSID_KEYS.iter().find_map(|(hkey, key)| {
+        RegKey::predef(*hkey)
+            .open_subkey(format!(r"{}\{}", key, clsid))
+            .and_then(|class| class.get_value::<String, _>(""))
+            .ok()
+            .filter(|name| !name.trim().is_empty())
+    })
+}
+
+/// COM 类的实现文件 (InprocServer32)
+fn server_path(clsid: &str) -> Option<String> {
+    CLSID_KEYS.iter().find_map(|(hkey, key)| {
+        RegKey::predef(*hkey)
+            .open_subkey(format!(r"{}\{}\InprocServer32", key, clsid))
+            .and_then(|server| server.get_value::<String, _>(""))
+            .ok()
+            .map(|server| server.trim().trim_matches('"').to_string())
+            .filter(|server| !server.is_empty() && Path::new(server).is_absolute())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_clsid_paths() {
+        let path = r"HKLM\SOFTWARE\Microsoft\Code Store Database\Distribution Units\{8AD9C840-044E-11D1-B3E9-00805F499D93}";
+        assert_eq!(
+            clsid_from_path(path),
+            Some("{8AD9C840-044E-11D1-B3E9-00805F499D93}")
+        );
+        assert!(clsid_from_path(r"HKLM\SOFTWARE\Vendor\App").is_none());
+        assert!(!is_clsid("{8AD9C840-044E-11D1-B3E9}"));
+
+        let dpf = downloaded_program_files_dir().join("vendor.ocx");
+        assert!(is_downloaded(&dpf.to_string_lossy()));
+        assert!(!is_downloaded(r"C:\Windows\System32\vendor.ocx"));
+    }
+}
@@ -1,12 +1,17 @@
+use super::matching;
 use super::models::{Confidence, Trace, TraceType};
+use super::options;
+use super::path_index;
 use crate::modules::common::error::UninstallerError;
 use crate::modules::common::utils;
 use std::path::Path;
 use walkdir::WalkDir;
 
-/// 扫描 AppData 痕迹
-pub fn scan_appdata_traces(program_name: &str) -> Result<Vec<Trace>, UninstallerError> {
-    let mut traces = Vec::new();
+/// 扫描AppData痕迹，每发现一项即交给 `emit`
+pub fn scan_appdata_traces(
+    program_name: &str,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
     let search_pattern = program_name.to_lowercase();
 
     // 扫描用户 AppData 目录
@@ -14,88 +19,122 @@ pub fn scan_appdata_traces(program_name: &str) -> Result<Vec<Trace>, Uninstaller
         // Roaming
         let roaming = home.join("AppData").join("Roaming");
         if roaming.exists() {
-            scan_appdata_dir(&roaming, &search_pattern, &mut traces);
+            scan_appdata_dir(&roaming, &search_pattern, emit);
         }
 
         // Local
         let local = home.join("AppData").join("Local");
         if local.exists() {
-            scan_appdata_dir(&local, &search_pattern, &mut traces);
+            scan_appdata_dir(&local, &search_pattern, emit);
         }
 
         // LocalLow
         let local_low = home.join("AppData").join("LocalLow");
         if local_low.exists() {
-            scan_appdata_dir(&local_low, &search_pattern, &mut traces);
+            scan_appdata_dir(&local_low, &search_pattern, emit);
         }
     }
 
-    Ok(traces)
+    Ok(())
 }
 
-/// 扫描 AppData 目录
-fn scan_appdata_dir(dir: &Path, pattern: &str, traces: &mut Vec<Trace>) {
+/// 扫描 AppData 目录（优先查询路径索引，索引不可用时遍历目录），深度按当前扫描选项
+pub(super) fn scan_appdata_dir(dir: &Path, pattern: &str, emit: &mut dyn FnMut(Trace)) {
+    let options = options::current();
+    if let Some(entries) =
+        path_index::indexed_matches(dir, options.appdata_depth, &matching::index_key(pattern))
+    {
+        for path in entries {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if !matching::name_matches(&name, pattern) {
+                continue;
+            }
+            if let Some(trace) = build_trace(&path, pattern) {
+                emit(trace);
+            }
+        }
+        return;
+    }
+
     let walker = WalkDir::new(dir)
-        .max_depth(4) // AppData 目录可能比较深
+        .max_depth(options.appdata_depth)
         .follow_links(false);
 
-    for entry in walker.into_iter().filter_map(|e| e.ok()) {
+    for entry in walker
+        .into_iter()
+        .take(options.entry_limit())
+        .filter_map(|e| e.ok())
+    {
         let path = entry.path();
         let name = path
             .file_name()
             .map(|n| n.to_string_lossy().to_lowercase())
             .unwrap_or_default();
 
-        // 检查名称是否包含搜索模式
-        if name.contains(pattern) {
-            // 跳过某些系统目录
-            if is_system_appdata_dir(path) {
-                continue;
+        // 按单词匹配，避免短名称命中无关目录
+        if matching::name_matches(&name, pattern) {
+            if let Some(trace) = build_trace(path, pattern) {
+                emit(trace);
             }
+        }
+    }
+}
 
-            let is_dir = path.is_dir();
-            let trace_type = if is_dir {
-                TraceType::AppData
-            } else {
-                TraceType::AppData
-            };
-
-            let size = if path.is_file() {
-                path.metadata().ok().map(|m| m.len())
-            } else {
-                // 计算目录大小
-                calculate_size(path)
-            };
-
-            let description = if is_dir {
-                format!("用户数据目录")
-            } else {
-                size.map(|s| format!("文件大小: {}", utils::format_size(s)))
-                    .unwrap_or_else(|| "用户数据文件".to_string())
-            };
-
-            let confidence =
-                if name.starts_with(pattern) || name.to_lowercase() == pattern.to_lowercase() {
-                    Confidence::High
-                } else {
-                    Confidence::Medium
-                };
-
-            let mut trace = Trace::new(
-                pattern.to_string(),
-                trace_type,
-                path.to_string_lossy().to_string(),
-            )
-            .with_description(description)
-            .with_confidence(confidence);
-
-            if let Some(s) = size {
-                trace.size = Some(s);
-            }
+/// 为名称匹配的路径构建痕迹，系统目录或已不存在的路径返回 `None`
+fn build_trace(path: &Path, pattern: &str) -> Option<Trace> {
+    // 跳过某些系统目录
+    if is_system_appdata_dir(path) || !path.exists() {
+        return None;
+    }
 
-            traces.push(trace);
-        }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let is_dir = path.is_dir();
+    let trace_type = if is_dir {
+        TraceType::AppData
+    } else {
+        TraceType::AppData
+    };
+
+    let size = if path.is_file() {
+        path.metadata().ok().map(|m| m.len())
+    } else {
+        // 计算目录大小
+        calculate_size(path)
+    };
+
+    let description = if is_dir {
+        format!("用户数据目录")
+    } else {
+        size.map(|s| format!("文件大小: {}", utils::format_size(s)))
+            .unwrap_or_else(|| "用户数据文件".to_string())
+    };
+
+    let confidence = if name.starts_with(pattern) || name.to_lowercase() == pattern.to_lowercase() {
+        Confidence::High
+    } else {
+        Confidence::Medium
+    };
+
+    let mut trace = Trace::new(
+        pattern.to_string(),
+        trace_type,
+        path.to_string_lossy().to_string(),
+    )
+    .with_description(description)
+    .with_confidence(confidence);
+
+    if let Some(s) = size {
+        trace.size = Some(s);
     }
+
+    Some(trace)
 }
 
 /// 检查是否为系统 AppData 目录
@@ -0,0 +1,354 @@
+//! 文件关联残留
+//!
+//! 安装程序注册的 ProgID（如 `Acme.Document`）和 `Applications\acme.exe` 在卸载后常被留下，
+//! 扩展名下的 `OpenWithProgids`、`OpenWithList` 仍引用它们，“打开方式”菜单里就会出现
+//! 无法启动的条目。这里先按打开命令找出属于程序的 ProgID 和应用程序注册，
+//! 再找出各扩展名下引用它们的条目：`OpenWithProgids` 中的值报告为注册表值痕迹，
+//! 只删除该值；`OpenWithList` 下的子项报告为注册表项痕迹。
+//!
+//! 带 `URL Protocol` 值的类是 URL 协议，处理命令指向程序时报告整个类，置信度与 ProgID
+//! 相同：命令都在安装目录中为高，仅按程序名匹配为中。`http`、`mailto` 等多个程序
+//! 都可能注册的公共协议以及 `ms-` 开头的系统协议不报告。
+//!
+//! 扩展名的默认值（默认打开程序）和 `UserChoice` 不处理，后者受系统哈希保护，
+//! 关联失效后 Windows 会自动让用户重新选择。
+
+use std::collections::HashMap;
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::com::CLASSES_ROOTS;
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use super::usage_history::FILE_EXTS_KEY;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::uninstaller::validation;
+
+/// 多个程序共用、不能当作 ProgID 整个删除的类
+const SHARED_CLASSES: &[&str] = &[
+    "*",
+    "AllFilesystemObjects",
+    "AppID",
+    "CLSID",
+    "DesktopBackground",
+    "Directory",
+    "Drive",
+    "exefile",
+    "Folder",
+    "Interface",
+    "lnkfile",
+    "SystemFileAssociations",
+    "TypeLib",
+    "Unknown",
+    "WOW6432Node",
+];
+
+/// 由默认浏览器、邮件客户端等轮流接管的公共 URL 协议，不能当作某个程序的协议整个删除
+const SHARED_URL_PROTOCOLS: &[&str] = &[
+    "about",
+    "callto",
+    "feed",
+    "feeds",
+    "file",
+    "ftp",
+    "ftps",
+    "http",
+    "https",
+    "irc",
+    "ircs",
+    "javascript",
+    "ldap",
+    "magnet",
+    "mailto",
+    "microsoft-edge",
+    "mk",
+    "news",
+    "nntp",
+    "res",
+    "search",
+    "search-ms",
+    "sms",
+    "snews",
+    "tel",
+    "vbscript",
+    "webcal",
+];
+
+/// 属于程序的 ProgID 或应用程序名（小写）及其命中原因
+type OwnedNames = HashMap<String, (Confidence, &'static str)>;
+
+/// 扫描程序留下的文件关联，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_association_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+    let mut emit_trace =
+        |trace_type: TraceType, path: String, description: String, hit: (Confidence, &str)| {
+            let mut trace = Trace::new(program_name.clone(), trace_type, path)
+                .with_description(description)
+                .with_confidence(hit.0);
+            trace.set_match_reason(hit.1.to_string());
+            emit(trace);
+        };
+
+    let mut prog_ids = OwnedNames::new();
+    let mut applications = OwnedNames::new();
+    for (hkey, hive, classes) in CLASSES_ROOTS {
+        let Ok(root) = RegKey::predef(*hkey).open_subkey(classes) else {
+            continue;
+        };
+
+        for name in root.enum_keys().flatten() {
+            if name.starts_with('.') || is_shared_class(&name) {
+                continue;
+            }
+            let Ok(class) = root.open_subkey(&name) else {
+                continue;
+            };
+
+            if name.eq_ignore_ascii_case("Applications") {
+                for app in class.enum_keys().flatten() {
+                    let Some(hit) = class
+                        .open_subkey(&app)
+                        .ok()
+                        .and_then(|key| match_verbs(&key, patterns, install_root))
+                    else {
+                        continue;
+                    };
+                    applications.insert(app.to_lowercase(), hit);
+                    emit_trace(
+                        TraceType::RegistryKey,
+                        format!(r"{}\{}\Applications\{}", hive, classes, app),
+                        format!("“打开方式”应用程序注册: {}", app),
+                        hit,
+                    );
+                }
+                continue;
+            }
+
+            let Some(hit) = match_verbs(&class, patterns, install_root) else {
+                continue;
+            };
+            if is_url_protocol(&class) {
+                if let Some(hit) = protocol_hit(&name, hit) {
+                    emit_trace(
+                        TraceType::RegistryKey,
+                        format!(r"{}\{}\{}", hive, classes, name),
+                        format!("URL 协议: {}:", name),
+                        hit,
+                    );
+                }
+                continue;
+            }
+            prog_ids.insert(name.to_lowercase(), hit);
+            emit_trace(
+                TraceType::RegistryKey,
+                format!(r"{}\{}\{}", hive, classes, name),
+                format!("文件类型 (ProgID): {}", name),
+                hit,
+            );
+        }
+    }
+
+    if prog_ids.is_empty() && applications.is_empty() {
+        return Ok(());
+    }
+
+    let mut extension_roots: Vec<(RegKey, String, bool)> = CLASSES_ROOTS
+        .iter()
+        .filter_map(|(hkey, hive, classes)| {
+            let key = RegKey::predef(*hkey).open_subkey(classes).ok()?;
+            Some((key, format!(r"{}\{}", hive, classes), true))
+        })
+        .collect();
+    // 资源管理器记录的 OpenWithList 是 MRU 列表，由使用记录扫描处理
+    if let Ok(file_exts) = RegKey::predef(HKEY_CURRENT_USER).open_subkey(FILE_EXTS_KEY) {
+        extension_roots.push((file_exts, format!(r"HKCU\{}", FILE_EXTS_KEY), false));
+    }
+
+    for (root, root_path, with_open_with_list) in &extension_roots {
+        for ext in root
+            .enum_keys()
+            .flatten()
+            .filter(|name| name.starts_with('.'))
+        {
+            let Ok(ext_key) = root.open_subkey(&ext) else {
+                continue;
+            };
+
+            if let Ok(prog_id_list) = ext_key.open_subkey("OpenWithProgids") {
+                for (prog_id, _) in prog_id_list.enum_values().flatten() {
+                    let Some(hit) = prog_ids.get(&prog_id.to_lowercase()) else {
+                        continue;
+                    };
+                    emit_trace(
+                        TraceType::RegistryValue,
+                        format!(r"{}\{}\OpenWithProgids\{}", root_path, ext, prog_id),
+                        format!("{} 的打开方式: {}", ext, prog_id),
+                        *hit,
+                    );
+                }
+            }
+
+            if !with_open_with_list {
+                continue;
+            }
+            if let Ok(app_list) = ext_key.open_subkey("OpenWithList") {
+                for app in app_list.enum_keys().flatten() {
+                    let Some(hit) = applications.get(&app.to_lowercase()) else {
+                        continue;
+                    };
+                    emit_trace(
+                        TraceType::RegistryKey,
+                        format!(r"{}\{}\OpenWithList\{}", root_path, ext, app),
+                        format!("{} 的打开方式: {}", ext, app),
+                        *hit,
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 带 `URL Protocol` 值的类是自定义 URL 协议（如 `acme://`），不是文件类型
+fn is_url_protocol(class: &RegKey) -> bool {
+    class.get_raw_value("URL Protocol").is_ok()
+}
+
+/// URL 协议的命中：沿用打开命令的匹配结果，公共协议和 `ms-` 开头的系统协议不报告
+fn protocol_hit(name: &str, hit: (Confidence, &'static str)) -> Option<(Confidence, &'static str)> {
+    let name = name.to_lowercase();
+    let shared = name.starts_with("ms-") || SHARED_URL_PROTOCOLS.contains(&name.as_str());
+    (!shared).then_some(hit)
+}
+
+fn is_shared_class(name: &str) -> bool {
+    SHARED_CLASSES
+        .iter()
+        .any(|shared| shared.eq_ignore_ascii_case(name))
+}
+
+/// 类的所有 `shell\<动作>\command` 都指向程序时才视为属于程序，返回置信度和命中原因
+fn match_verbs(
+    class: &RegKey,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let shell = class.open_subkey("shell").ok()?;
+    let commands: Vec<String> = shell
+        .enum_keys()
+        .flatten()
+        .filter_map(|verb| {
+            shell
+                .open_subkey(format!(r"{}\command", verb))
+                .and_then(|key| key.get_value::<String, _>(""))
+                .ok()
+        })
+        .collect();
+    match_commands(&commands, patterns, install_root)
+}
+
+fn match_commands(
+    commands: &[String],
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    if commands.is_empty() {
+        return None;
+    }
+
+    let mut all_in_root = true;
+    for command in commands {
+        let executable = validation::parse_executable(command)?
+            .to_string_lossy()
+            .to_lowercase();
+        if utils::is_system_critical_path(&executable) {
+            return None;
+        }
+        if install_root.is_some_and(|root| executable.starts_with(root)) {
+            continue;
+        }
+        if !patterns
+            .iter()
+            .any(|pattern| matching::name_matches(&executable, pattern))
+        {
+            return None;
+        }
+        all_in_root = false;
+    }
+
+    Some(if all_in_root {
+        (Confidence::High, "文件关联的打开命令指向程序安装目录")
+    } else {
+        (Confidence::Medium, "文件关联的打开命令与程序名匹配")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_every_verb_to_point_at_the_program() {
+        let patterns = vec!["acme".to_string()];
+        let root = Some(r"d:\apps\acme\");
+        let commands = |list: &[&str]| list.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+
+        let open = r#""D:\Apps\Acme\acme.exe" "%1""#;
+        assert_eq!(
+            match_commands(&commands(&[open]), &patterns, root).map(|m| m.0),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            match_commands(
+                &commands(&[open, r#""D:\Tools\Acme Viewer\viewer.exe" "%1""#]),
+                &patterns,
+                root
+            )
+            .map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+        assert!(match_commands(
+            &commands(&[open, r#"%SystemRoot%\System32\notepad.exe "%1""#]),
+            &patterns,
+            root
+        )
+        .is_none());
+        assert!(match_commands(&[], &patterns, root).is_none());
+        assert!(is_shared_class("directory"));
+    }
+
+    #[test]
+    fn url_protocols_keep_command_confidence_and_skip_shared_schemes() {
+        let patterns = vec!["acme".to_string()];
+        let root = Some(r"d:\apps\acme\");
+        // 安装目录外仅按名称匹配的处理命令保持中置信度
+        let hit = match_commands(
+            &[r#""D:\Tools\Acme Mail\mail.exe" "%1""#.to_string()],
+            &patterns,
+            root,
+        )
+        .unwrap();
+        assert_eq!(
+            protocol_hit("acme", hit).map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+
+        for shared in ["http", "HTTPS", "mailto", "tel", "ms-settings", "MS-Word"] {
+            assert!(protocol_hit(shared, hit).is_none(), "{}", shared);
+        }
+        for custom in ["zoommtg", "steam", "msacme"] {
+            assert!(protocol_hit(custom, hit).is_some(), "{}", custom);
+        }
+    }
+}
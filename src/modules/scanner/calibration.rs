@@ -0,0 +1,126 @@
+//! 按清理反馈校准置信度
+//!
+//! 同一位置的痕迹被建议了足够多次后，若用户几乎总是保留（如 `ProgramData\Package Cache`），
+//! 把置信度降一级，"清理全部"和默认勾选就不再包含它们；若几乎总是删除，低置信度的痕迹升为中置信度。
+//! 可能含有用户数据或属于用户内容的痕迹不会被升级；AppData、ProgramData、Program Files、
+//! SOFTWARE 这类多个程序共用的位置，反馈来自其他程序的痕迹，也不会据此升级。
+
+use std::collections::HashMap;
+
+use super::models::{Confidence, Trace, TraceCategory};
+use crate::modules::lister::trace_feedback::{self, LocationFeedback};
+
+/// 至少有这么多次反馈才参与校准
+const MIN_FEEDBACK_SAMPLES: u32 = 5;
+
+/// 保留比例不低于该值时降级
+const DOWNGRADE_SKIP_RATIO: f64 = 0.8;
+
+/// 保留比例不高于该值时升级
+const UPGRADE_SKIP_RATIO: f64 = 0.1;
+
+/// 扫描开始时读取的反馈，读取失败时不做校准
+pub struct Calibration {
+    feedback: HashMap<String, LocationFeedback>,
+}
+
+impl Calibration {
+    pub fn load() -> Self {
+        let feedback = trace_feedback::feedback_by_location().unwrap_or_else(|e| {
+            tracing::warn!("读取清理反馈失败: {}", e);
+            HashMap::new()
+        });
+        Calibration { feedback }
+    }
+
+    /// 按痕迹所在位置的反馈调整置信度，并在风险说明中注明原因
+    pub fn apply(&self, trace: &mut Trace) {
+        let Some(location) = trace_feedback::location_key(&trace.path) else {
+            return;
+        };
+        let Some(feedback) = self.feedback.get(&location) else {
+            return;
+        };
+        if feedback.total() < MIN_FEEDBACK_SAMPLES {
+            return;
+        }
+
+        let skip_ratio = feedback.skip_ratio();
+        if skip_ratio >= DOWNGRADE_SKIP_RATIO && trace.confidence != Confidence::Low {
+            trace.set_confidence(match trace.confidence {
+                Confidence::High => Confidence::Medium,
+                _ => Confidence::Low,
+            });
+            trace.risk.notes.push(format!(
+                "该位置的痕迹在 {} 次建议中有 {} 次被保留，已降低置信度",
+                feedback.total(),
+                feedback.skipped
+            ));
+        } else if skip_ratio <= UPGRADE_SKIP_RATIO
+            && trace.confidence == Confidence::Low
+            && trace.user_data.is_none()
+            && trace.category != TraceCategory::UserContent
+            && !trace_feedback::is_shared_location(&location)
+        {
+            trace.set_confidence(Confidence::Medium);
+            trace.risk.notes.push(format!(
+                "该位置的痕迹在 {} 次建议中有 {} 次被删除，已提高置信度",
+                feedback.total(),
+                feedback.deleted
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::scanner::models::TraceType;
+
+    fn calibration(location: &str, deleted: u32, skipped: u32) -> Calibration {
+        let feedback = LocationFeedback {
+            location: location.to_string(),
+            deleted,
+            skipped,
+            updated_at: String::new(),
+        };
+        Calibration {
+            feedback: HashMap::from([(location.to_string(), feedback)]),
+        }
+    }
+
+    fn trace(path: &str, confidence: Confidence) -> Trace {
+        Trace::new("Acme".to_string(), TraceType::File, path.to_string())
+            .with_confidence(confidence)
+    }
+
+    #[test]
+    fn adjusts_confidence_from_location_feedback() {
+        let path = r"C:\ProgramData\Package Cache\{1234}";
+
+        let mut skipped = trace(path, Confidence::High);
+        calibration(r"c:\programdata\package cache", 1, 9).apply(&mut skipped);
+        assert_eq!(skipped.confidence, Confidence::Medium);
+        assert_eq!(skipped.risk.notes.len(), 1);
+
+        let mut deleted = trace(path, Confidence::Low);
+        calibration(r"c:\programdata\package cache", 10, 0).apply(&mut deleted);
+        assert_eq!(deleted.confidence, Confidence::Medium);
+
+        let mut few_samples = trace(path, Confidence::High);
+        calibration(r"c:\programdata\package cache", 0, 2).apply(&mut few_samples);
+        assert_eq!(few_samples.confidence, Confidence::High);
+        assert!(few_samples.risk.notes.is_empty());
+    }
+
+    #[test]
+    fn never_upgrades_in_shared_locations() {
+        let mut roaming = trace(r"C:\Users\bob\AppData\Roaming\Other", Confidence::Low);
+        calibration(r"%userprofile%\appdata\roaming", 20, 0).apply(&mut roaming);
+        assert_eq!(roaming.confidence, Confidence::Low);
+
+        let mut software = trace(r"HKLM\SOFTWARE\Other", Confidence::Low);
+        calibration(r"hklm\software", 20, 0).apply(&mut software);
+        assert_eq!(software.confidence, Confidence::Low);
+    }
+}
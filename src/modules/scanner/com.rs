@@ -0,0 +1,282 @@
+//! COM 组件与外壳扩展
+//!
+//! 安装程序注册的 CLSID、类型库和外壳扩展都在 `Classes` 下，键名是 GUID，
+//! 按名称扫描注册表几乎找不到。这里按实现文件定位：`InprocServer32`/`LocalServer32`
+//! 指向程序安装目录（没有安装目录记录时为路径与程序名匹配）的 CLSID 视为属于程序，
+//! 再找出引用这些 CLSID 的外壳扩展处理程序、图标叠加和 `Approved` 列表中的条目。
+//! 每处注册报告为单独的注册表痕迹，只删除程序自己的键，不动 `*\shellex` 等公共键。
+
+use std::collections::HashMap;
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::activex::is_clsid;
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+use crate::modules::uninstaller::validation;
+
+/// 注册 COM 类的 `Classes` 根（HKCR 由这几处合并而成）
+pub(super) const CLASSES_ROOTS: &[(winreg::HKEY, &str, &str)] = &[
+    (HKEY_LOCAL_MACHINE, "HKLM", r"SOFTWARE\Classes"),
+    (HKEY_LOCAL_MACHINE, "HKLM", r"SOFTWARE\Classes\WOW6432Node"),
+    (HKEY_CURRENT_USER, "HKCU", r"Software\Classes"),
+];
+
+/// COM 服务器子项
+pub(super) const SERVER_KEYS: &[&str] = &["InprocServer32", "LocalServer32"];
+
+/// 可以挂外壳扩展的类
+const SHELLEX_CLASSES: &[&str] = &[
+    "*",
+    "AllFilesystemObjects",
+    "Directory",
+    r"Directory\Background",
+    "Drive",
+    "Folder",
+];
+
+/// 外壳扩展处理程序类别，右键菜单处理程序由 [`super::context_menu`] 扫描
+const SHELLEX_HANDLERS: &[&str] = &[
+    "CopyHookHandlers",
+    "DragDropHandlers",
+    "PropertySheetHandlers",
+];
+
+/// 已批准的外壳扩展列表，值名为 CLSID（HKLM）
+pub const APPROVED_SHELL_EXTENSIONS_KEY: &str =
+    r"SOFTWARE\Microsoft\Windows\CurrentVersion\Shell Extensions\Approved";
+
+/// 图标叠加处理程序（HKLM），子项默认值为 CLSID
+pub const ICON_OVERLAY_KEY: &str =
+    r"SOFTWARE\Microsoft\Windows\CurrentVersion\Explorer\ShellIconOverlayIdentifiers";
+
+/// 属于程序的 CLSID（小写）及其命中原因
+type OwnedClasses = HashMap<String, (Confidence, &'static str)>;
+
+/// 扫描程序注册的 COM 类、类型库和外壳扩展，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_com_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+    let mut emit_key =
+        |trace_type: TraceType, path: String, description: String, hit: (Confidence, &str)| {
+            let mut trace = Trace::new(program_name.clone(), trace_type, path)
+                .with_description(description)
+                .with_confidence(hit.0);
+            trace.set_match_reason(hit.1.to_string());
+            emit(trace);
+        };
+
+    let mut owned = OwnedClasses::new();
+    for (hkey, hive, classes) in CLASSES_ROOTS {
+        let Ok(root) = RegKey::predef(*hkey).open_subkey(classes) else {
+            continue;
+        };
+
+        if let Ok(clsids) = root.open_subkey("CLSID") {
+            for clsid in clsids.enum_keys().flatten().filter(|name| is_clsid(name)) {
+                let Some((server, hit)) = SERVER_KEYS.iter().find_map(|server_key| {
+                    let server = clsids
+                        .open_subkey(format!(r"{}\{}", clsid, server_key))
+                        .and_then(|key| key.get_value::<String, _>(""))
+                        .ok()?;
+                    match_server(&server, patterns, install_root).map(|hit| (server, hit))
+                }) else {
+                    continue;
+                };
+
+                owned.insert(clsid.to_lowercase(), hit);
+                emit_key(
+                    TraceType::RegistryKey,
+                    format!(r"{}\{}\CLSID\{}", hive, classes, clsid),
+                    format!("COM 类 {}: {}", clsid, server),
+                    hit,
+                );
+            }
+        }
+
+        if let Ok(type_libs) = root.open_subkey("TypeLib") {
+            for guid in type_libs
+                .enum_keys()
+                .flatten()
+                .filter(|name| is_clsid(name))
+            {
+                let Ok(type_lib) = type_libs.open_subkey(&guid) else {
+                    continue;
+                };
+                let Some((file, hit)) = type_lib_files(&type_lib).into_iter().find_map(|file| {
+                    match_server(&file, patterns, install_root).map(|hit| (file, hit))
+                }) else {
+                    continue;
+                };
+
+                emit_key(
+                    TraceType::RegistryKey,
+                    format!(r"{}\{}\TypeLib\{}", hive, classes, guid),
+                    format!("类型库 {}: {}", guid, file),
+                    hit,
+                );
+            }
+        }
+    }
+
+    if owned.is_empty() {
+        return Ok(());
+    }
+
+    for (hkey, hive, classes) in CLASSES_ROOTS {
+        for class in SHELLEX_CLASSES {
+            for handler in SHELLEX_HANDLERS {
+                let key_path = format!(r"{}\{}\shellex\{}", classes, class, handler);
+                let Ok(handlers) = RegKey::predef(*hkey).open_subkey(&key_path) else {
+                    continue;
+                };
+                for name in handlers.enum_keys().flatten() {
+                    let Some(hit) = handler_clsid(&handlers, &name)
+                        .and_then(|clsid| owned.get(&clsid.to_lowercase()))
+                    else {
+                        continue;
+                    };
+                    emit_key(
+                        TraceType::RegistryKey,
+                        format!(r"{}\{}\{}", hive, key_path, name),
+                        format!("外壳扩展 {} ({})", name, handler),
+                        *hit,
+                    );
+                }
+            }
+        }
+    }
+
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    if let Ok(overlays) = hklm.open_subkey(ICON_OVERLAY_KEY) {
+        for name in overlays.enum_keys().flatten() {
+            let Some(hit) =
+                handler_clsid(&overlays, &name).and_then(|clsid| owned.get(&clsid.to_lowercase()))
+            else {
+                continue;
+            };
+            emit_key(
+                TraceType::RegistryKey,
+                format!(r"HKLM\{}\{}", ICON_OVERLAY_KEY, name),
+                format!("图标叠加处理程序 {}", name.trim()),
+                *hit,
+            );
+        }
+    }
+
+    if let Ok(approved) = hklm.open_subkey(APPROVED_SHELL_EXTENSIONS_KEY) {
+        for (clsid, _) in approved.enum_values().flatten() {
+            let Some(hit) = owned.get(&clsid.to_lowercase()) else {
+                continue;
+            };
+            emit_key(
+                TraceType::RegistryValue,
+                format!(r"HKLM\{}\{}", APPROVED_SHELL_EXTENSIONS_KEY, clsid),
+                format!("已批准的外壳扩展 {}", clsid),
+                *hit,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// 判断 COM 服务器或类型库文件是否属于程序，返回置信度和命中原因
+fn match_server(
+    server: &str,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let file = match validation::parse_executable(server) {
+        Some(exe) if exe.to_string_lossy().to_lowercase().ends_with(".exe") => {
+            exe.to_string_lossy().to_string()
+        }
+        // InprocServer32 和类型库的值就是文件路径，常含未加引号的空格
+        _ => utils::normalize_path(&expand_windows_env_vars(server.trim().trim_matches('"'))),
+    }
+    .to_lowercase();
+    if file.is_empty() || utils::is_system_critical_path(&file) {
+        return None;
+    }
+
+    if install_root.is_some_and(|root| file.starts_with(root)) {
+        return Some((Confidence::High, "COM 组件文件位于程序安装目录"));
+    }
+    patterns
+        .iter()
+        .any(|pattern| matching::name_matches(&file, pattern))
+        .then_some((Confidence::Medium, "COM 组件文件路径与程序名匹配"))
+}
+
+/// 类型库各版本、各语言下 `win32`/`win64` 的文件路径
+fn type_lib_files(type_lib: &RegKey) -> Vec<String> {
+    let mut files = Vec::new();
+    for version in type_lib.enum_keys().flatten() {
+        let Ok(version_key) = type_lib.open_subkey(&version) else {
+            continue;
+        };
+        for lcid in version_key.enum_keys().flatten() {
+            for platform in ["win32", "win64"] {
+                if let Ok(file) = version_key
+                    .open_subkey(format!(r"{}\{}", lcid, platform))
+                    .and_then(|key| key.get_value::<String, _>(""))
+                {
+                    files.push(file);
+                }
+            }
+        }
+    }
+    files
+}
+
+/// 处理程序引用的 CLSID：子项默认值，或子项名本身就是 CLSID
+pub(super) fn handler_clsid(handlers: &RegKey, name: &str) -> Option<String> {
+    handlers
+        .open_subkey(name)
+        .and_then(|key| key.get_value::<String, _>(""))
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| is_clsid(value))
+        .or_else(|| is_clsid(name).then(|| name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_servers_by_install_location() {
+        let patterns = vec!["acme".to_string()];
+        let root = Some(r"d:\apps\acme\");
+
+        let hit = |server| match_server(server, &patterns, root).map(|m| m.0);
+        assert_eq!(hit(r"D:\Apps\Acme\shellext64.dll"), Some(Confidence::High));
+        assert_eq!(
+            hit(r#""D:\Apps\Acme\helper.exe" /automation"#),
+            Some(Confidence::High)
+        );
+        assert_eq!(hit(r"D:\Other\AcmeMenu.dll"), Some(Confidence::Medium));
+        assert_eq!(
+            match_server(
+                r"C:\Program Files\Acme Tools\ext.dll",
+                &patterns,
+                Some(r"c:\program files\acme tools\")
+            )
+            .map(|m| m.0),
+            Some(Confidence::High)
+        );
+        assert_eq!(hit(r"D:\Other\shell.dll"), None);
+        assert_eq!(hit(r"C:\Windows\System32\shell32.dll"), None);
+    }
+}
@@ -0,0 +1,292 @@
+//! 资源管理器右键菜单残留
+//!
+//! 程序在 `*`、`Directory`、`Folder` 等公共类下添加 `shell\<动作>\command`，
+//! 或在 `shellex\ContextMenuHandlers` 下注册处理程序。卸载后这些菜单项仍显示在右键菜单中，
+//! 点击后报错，是用户最容易看到的残留。命令或处理程序文件指向程序安装目录、
+//! 路径与程序名匹配，或文件已不存在且菜单名与程序名匹配时报告为 `ContextMenu` 痕迹，
+//! 清理时只删除菜单项本身的键，不动所在的公共类。
+
+use std::path::Path;
+
+use winreg::RegKey;
+
+use super::activex::is_clsid;
+use super::com::{handler_clsid, CLASSES_ROOTS, SERVER_KEYS};
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+use crate::modules::uninstaller::validation;
+
+/// 可以挂右键菜单的公共类，`SystemFileAssociations` 下的各类另行枚举
+const MENU_CLASSES: &[&str] = &[
+    "*",
+    "AllFilesystemObjects",
+    "Directory",
+    r"Directory\Background",
+    "Drive",
+    "Folder",
+    "DesktopBackground",
+    "LibraryFolder",
+];
+
+/// 按扩展名或感知类型（`image`、`video`）挂菜单的类
+const SYSTEM_FILE_ASSOCIATIONS: &str = "SystemFileAssociations";
+
+/// 扫描程序留下的右键菜单项，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_context_menu_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+    let mut emit_menu = |path: String, description: String, hit: (Confidence, &str)| {
+        let mut trace = Trace::new(program_name.clone(), TraceType::ContextMenu, path)
+            .with_description(description)
+            .with_confidence(hit.0);
+        trace.set_match_reason(hit.1.to_string());
+        emit(trace);
+    };
+
+    for (hkey, hive, classes) in CLASSES_ROOTS {
+        let Ok(root) = RegKey::predef(*hkey).open_subkey(classes) else {
+            continue;
+        };
+
+        let mut menu_classes: Vec<String> = MENU_CLASSES.iter().map(|c| c.to_string()).collect();
+        if let Ok(associations) = root.open_subkey(SYSTEM_FILE_ASSOCIATIONS) {
+            menu_classes.extend(
+                associations
+                    .enum_keys()
+                    .flatten()
+                    .map(|name| format!(r"{}\{}", SYSTEM_FILE_ASSOCIATIONS, name)),
+            );
+        }
+
+        for class in &menu_classes {
+            let shell_path = format!(r"{}\shell", class);
+            if let Ok(shell) = root.open_subkey(&shell_path) {
+                for verb in shell.enum_keys().flatten() {
+                    let Ok(verb_key) = shell.open_subkey(&verb) else {
+                        continue;
+                    };
+                    let Ok(command) = verb_key
+                        .open_subkey("command")
+                        .and_then(|key| key.get_value::<String, _>(""))
+                    else {
+                        continue;
+                    };
+                    let label = verb_label(&verb_key).unwrap_or_else(|| verb.clone());
+                    let Some(hit) = match_verb(&verb, &label, &command, patterns, install_root)
+                    else {
+                        continue;
+                    };
+                    emit_menu(
+                        format!(r"{}\{}\{}\{}", hive, classes, shell_path, verb),
+                        format!("右键菜单 \"{}\" ({}): {}", label, class, command),
+                        hit,
+                    );
+                }
+            }
+
+            let handlers_path = format!(r"{}\shellex\ContextMenuHandlers", class);
+            if let Ok(handlers) = root.open_subkey(&handlers_path) {
+                for name in handlers.enum_keys().flatten() {
+                    let Some(clsid) = handler_clsid(&handlers, &name) else {
+                        continue;
+                    };
+                    let server = clsid_server(&clsid);
+                    let Some(hit) = match_handler(&name, server.as_deref(), patterns, install_root)
+                    else {
+                        continue;
+                    };
+                    emit_menu(
+                        format!(r"{}\{}\{}\{}", hive, classes, handlers_path, name),
+                        format!(
+                            "右键菜单处理程序 {} ({}): {}",
+                            name.trim(),
+                            class,
+                            server.as_deref().unwrap_or("CLSID 未注册")
+                        ),
+                        hit,
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 痕迹路径是否为单个菜单项（`...\shell\<动作>` 或 `...\shellex\ContextMenuHandlers\<名称>`）
+pub fn is_context_menu_path(path: &str) -> bool {
+    let segments: Vec<String> = path
+        .trim_end_matches('\\')
+        .split('\\')
+        .map(str::to_lowercase)
+        .collect();
+    let n = segments.len();
+    if n < 4 || segments[n - 1].is_empty() {
+        return false;
+    }
+    segments[n - 2] == "shell"
+        || (segments[n - 2] == "contextmenuhandlers" && segments[n - 3] == "shellex")
+}
+
+/// 菜单显示的文字：`MUIVerb` 或默认值，`@dll,-id` 形式的资源引用不可读，忽略
+fn verb_label(verb_key: &RegKey) -> Option<String> {
+    ["MUIVerb", ""]
+        .iter()
+        .filter_map(|name| verb_key.get_value::<String, _>(name).ok())
+        .map(|label| label.replace('&', "").trim().to_string())
+        .find(|label| !label.is_empty() && !label.starts_with('@'))
+}
+
+/// 判断菜单命令是否属于程序，返回置信度和命中原因
+fn match_verb(
+    verb: &str,
+    label: &str,
+    command: &str,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let executable = validation::parse_executable(command)?
+        .to_string_lossy()
+        .to_lowercase();
+    // rundll32、cmd 等系统程序启动的菜单无法据此判断归属
+    if utils::is_system_critical_path(&executable) {
+        return None;
+    }
+    if install_root.is_some_and(|root| executable.starts_with(root)) {
+        return Some((Confidence::High, "右键菜单命令指向程序安装目录"));
+    }
+    if patterns
+        .iter()
+        .any(|pattern| matching::name_matches(&executable, pattern))
+    {
+        return Some((Confidence::Medium, "右键菜单命令路径与程序名匹配"));
+    }
+    (!Path::new(&executable).exists()
+        && patterns.iter().any(|pattern| {
+            matching::name_matches(verb, pattern) || matching::name_matches(label, pattern)
+        }))
+    .then_some((
+        Confidence::Medium,
+        "右键菜单命令指向的文件已不存在，菜单名与程序名匹配",
+    ))
+}
+
+/// 判断右键菜单处理程序是否属于程序，`server` 为 CLSID 的实现文件，未注册时为 None
+fn match_handler(
+    name: &str,
+    server: Option<&str>,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let name_matches = || {
+        patterns
+            .iter()
+            .any(|pattern| matching::name_matches(name, pattern))
+    };
+    let Some(server) = server else {
+        return name_matches().then_some((
+            Confidence::Medium,
+            "右键菜单处理程序的 CLSID 已不存在，名称与程序名匹配",
+        ));
+    };
+
+    let file = utils::normalize_path(&expand_windows_env_vars(server.trim().trim_matches('"')))
+        .to_lowercase();
+    if file.is_empty() || utils::is_system_critical_path(&file) {
+        return None;
+    }
+    if install_root.is_some_and(|root| file.starts_with(root)) {
+        return Some((Confidence::High, "右键菜单处理程序文件位于程序安装目录"));
+    }
+    if patterns
+        .iter()
+        .any(|pattern| matching::name_matches(&file, pattern))
+    {
+        return Some((Confidence::Medium, "右键菜单处理程序文件路径与程序名匹配"));
+    }
+    (!Path::new(&file).exists() && name_matches()).then_some((
+        Confidence::Medium,
+        "右键菜单处理程序文件已不存在，名称与程序名匹配",
+    ))
+}
+
+/// CLSID 的 `InprocServer32`/`LocalServer32`，在任一 `Classes` 根下注册即可
+fn clsid_server(clsid: &str) -> Option<String> {
+    if !is_clsid(clsid) {
+        return None;
+    }
+    CLASSES_ROOTS.iter().find_map(|(hkey, _, classes)| {
+        SERVER_KEYS.iter().find_map(|server_key| {
+            RegKey::predef(*hkey)
+                .open_subkey(format!(r"{}\CLSID\{}\{}", classes, clsid, server_key))
+                .and_then(|key| key.get_value::<String, _>(""))
+                .ok()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_menu_entries_and_validates_paths() {
+        let patterns = vec!["acme".to_string()];
+        let root = Some(r"d:\apps\acme\");
+
+        assert_eq!(
+            match_verb(
+                "AcmeOpen",
+                "用 Acme 打开",
+                r#""D:\Apps\Acme\acme.exe" "%1""#,
+                &patterns,
+                root
+            )
+            .map(|m| m.0),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            match_verb(
+                "AcmeScan",
+                "Scan with Acme",
+                r#""D:\Gone\scanner.exe" "%1""#,
+                &patterns,
+                None
+            )
+            .map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+        assert!(match_verb(
+            "AcmeOpen",
+            "Acme",
+            r#"C:\Windows\System32\rundll32.exe shell32.dll"#,
+            &patterns,
+            root
+        )
+        .is_none());
+        assert_eq!(
+            match_handler("AcmeMenu", None, &patterns, root).map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+        assert!(match_handler("Other", None, &patterns, root).is_none());
+
+        assert!(is_context_menu_path(
+            r"HKCU\Software\Classes\Directory\shell\AcmeOpen"
+        ));
+        assert!(is_context_menu_path(
+            r"HKLM\SOFTWARE\Classes\*\shellex\ContextMenuHandlers\AcmeMenu"
+        ));
+        assert!(!is_context_menu_path(r"HKLM\SOFTWARE\Classes\Directory"));
+        assert!(!is_context_menu_path(r"HKCU\Software\Classes\*\shell\"));
+    }
+}
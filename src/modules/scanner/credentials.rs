@@ -0,0 +1,254 @@
+//! 凭据管理器残留
+//!
+//! 程序常把登录令牌、同步账户等保存在 Windows 凭据管理器中，卸载后不会清除。
+//! 枚举当前用户的凭据，目标名称（或别名、备注）与程序名匹配的报告为 `Credential` 痕迹；
+//! 只与发布者匹配的置信度较低。Windows 自身的账户凭据不报告。
+//!
+//! 痕迹路径为 `Credential\<类型>\<目标名称>`，清理时按类型和目标名称调用 `CredDelete`。
+
+use super::matching;
+use super::metadata::publisher_dir_names;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+
+/// 痕迹路径前缀
+const CREDENTIAL_ROOT: &str = "Credential";
+
+/// 凭据类型（`CRED_TYPE_*`）与路径中使用的名称
+const CREDENTIAL_TYPES: &[(u32, &str)] = &[
+    (1, "Generic"),
+    (2, "DomainPassword"),
+    (3, "DomainCertificate"),
+    (4, "DomainVisiblePassword"),
+    (5, "GenericCertificate"),
+    (6, "DomainExtended"),
+];
+
+/// Windows 自身使用的凭据目标前缀（小写），删除后会影响系统登录或同步
+const SYSTEM_TARGET_PREFIXES: &[&str] = &[
+    "microsoftaccount:",
+    "windowslive:",
+    "virtualapp/didlogical",
+    "sso_pop_",
+    "microsoft_windows",
+    "xblgrts",
+    "xbl|",
+    "wcm",
+    "termsrv/",
+];
+
+/// 发布者只按名称匹配时不可靠的平台厂商，系统和浏览器凭据大量以其命名
+const PLATFORM_PUBLISHERS: &[&str] = &["microsoft", "microsoft corporation"];
+
+/// 凭据管理器中的一条凭据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredCredential {
+    pub credential_type: u32,
+    pub target_name: String,
+    pub target_alias: Option<String>,
+    pub comment: Option<String>,
+    pub user_name: Option<String>,
+}
+
+/// 扫描程序留下的凭据，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名
+pub fn scan_credential_traces(
+    patterns: &[String],
+    publisher: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+
+    for credential in enumerate_credentials()? {
+        let Some((confidence, reason)) = match_credential(&credential, patterns, publisher) else {
+            continue;
+        };
+        let Some(path) = credential_path(credential.credential_type, &credential.target_name)
+        else {
+            continue;
+        };
+
+        let mut description = format!("保存的凭据: {}", credential.target_name);
+        if let Some(user_name) = credential.user_name.as_deref().filter(|u| !u.is_empty()) {
+            description.push_str(&format!(" (用户 {})", user_name));
+        }
+        let mut trace = Trace::new(program_name.clone(), TraceType::Credential, path)
+            .with_description(description)
+            .with_confidence(confidence);
+        trace.set_match_reason(reason.to_string());
+        emit(trace);
+    }
+
+    Ok(())
+}
+
+/// 由凭据类型和目标名称生成痕迹路径，未知类型返回 None
+pub fn credential_path(credential_type: u32, target_name: &str) -> Option<String> {
+    let (_, type_name) = CREDENTIAL_TYPES
+        .iter()
+        .find(|(value, _)| *value == credential_type)?;
+    Some(format!(
+        r"{}\{}\{}",
+        CREDENTIAL_ROOT, type_name, target_name
+    ))
+}
+
+/// 解析痕迹路径，返回凭据类型和目标名称
+pub fn parse_credential_path(path: &str) -> Option<(u32, &str)> {
+    let mut parts = path.splitn(3, '\\');
+    if !parts.next()?.eq_ignore_ascii_case(CREDENTIAL_ROOT) {
+        return None;
+    }
+    let type_name = parts.next()?;
+    let target_name = parts.next().filter(|target| !target.is_empty())?;
+    let (credential_type, _) = CREDENTIAL_TYPES
+        .iter()
+        .find(|(_, name)| name.eq_ignore_ascii_case(type_name))?;
+    Some((*credential_type, target_name))
+}
+
+/// 目标名称是否为 Windows 自身使用的凭据
+pub fn is_system_credential(target_name: &str) -> bool {
+    let target = target_name.to_lowercase();
+    SYSTEM_TARGET_PREFIXES
+        .iter()
+        .any(|prefix| target.starts_with(prefix))
+}
+
+/// 判断凭据是否属于程序，返回置信度和命中原因
+fn match_credential(
+    credential: &StoredCredential,
+    patterns: &[String],
+    publisher: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    if is_system_credential(&credential.target_name) {
+        return None;
+    }
+
+    let fields: Vec<&str> = std::iter::once(credential.target_name.as_str())
+        .chain(credential.target_alias.as_deref())
+        .chain(credential.comment.as_deref())
+        .collect();
+    if fields.iter().any(|field| {
+        patterns
+            .iter()
+            .any(|pattern| matching::name_matches(field, pattern))
+    }) {
+        return Some((Confidence::Medium, "凭据目标名称与程序名匹配"));
+    }
+
+    let publishers: Vec<String> = publisher_dir_names(publisher)
+        .into_iter()
+        .filter(|name| !PLATFORM_PUBLISHERS.contains(&name.to_lowercase().as_str()))
+        .collect();
+    publishers
+        .iter()
+        .any(|name| matching::name_matches(&credential.target_name, name))
+        .then_some((Confidence::Low, "凭据目标名称与发布者匹配"))
+}
+
+/// 枚举当前用户保存的所有凭据
+#[cfg(windows)]
+fn enumerate_credentials() -> Result<Vec<StoredCredential>, UninstallerError> {
+    use windows::core::{PCWSTR, PWSTR};
+    use windows::Win32::Foundation::ERROR_NOT_FOUND;
+    use windows::Win32::Security::Credentials::{CredEnumerateW, CredFree, CREDENTIALW};
+
+    let read = |value: PWSTR| -> Option<String> {
+        if value.is_null() {
+            return None;
+        }
+        unsafe { value.to_string().ok() }
+    };
+
+    let mut count = 0u32;
+    let mut credentials: *mut *mut CREDENTIALW = std::ptr::null_mut();
+    if let Err(e) = unsafe { CredEnumerateW(PCWSTR::null(), None, &mut count, &mut credentials) } {
+        // 没有任何凭据时同样返回 ERROR_NOT_FOUND
+        if e.code() == ERROR_NOT_FOUND.to_hresult() {
+            return Ok(Vec::new());
+        }
+        return Err(UninstallerError::Other(format!("枚举凭据失败: {}", e)));
+    }
+
+    let mut result = Vec::with_capacity(count as usize);
+    unsafe {
+        for &credential in std::slice::from_raw_parts(credentials, count as usize) {
+            let Some(credential) = credential.as_ref() else {
+                continue;
+            };
+            let Some(target_name) = read(credential.TargetName) else {
+                continue;
+            };
+            result.push(StoredCredential {
+                credential_type: credential.Type.0,
+                target_name,
+                target_alias: read(credential.TargetAlias),
+                comment: read(credential.Comment),
+                user_name: read(credential.UserName),
+            });
+        }
+        CredFree(credentials as *const _);
+    }
+    Ok(result)
+}
+
+#[cfg(not(windows))]
+fn enumerate_credentials() -> Result<Vec<StoredCredential>, UninstallerError> {
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_program_credentials_and_round_trips_paths() {
+        let credential = |target: &str| StoredCredential {
+            credential_type: 1,
+            target_name: target.to_string(),
+            target_alias: None,
+            comment: None,
+            user_name: Some("user@example.com".to_string()),
+        };
+        let patterns = vec!["acme sync".to_string()];
+
+        assert_eq!(
+            match_credential(&credential("AcmeSync/refresh_token"), &patterns, None).map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+        assert_eq!(
+            match_credential(
+                &credential("Contoso:session"),
+                &patterns,
+                Some("Contoso Ltd.")
+            )
+            .map(|m| m.0),
+            Some(Confidence::Low)
+        );
+        assert!(match_credential(
+            &credential("MicrosoftAccount:user=acme sync"),
+            &patterns,
+            None
+        )
+        .is_none());
+        assert!(match_credential(
+            &credential("MicrosoftOffice16_Data:orgid"),
+            &patterns,
+            Some("Microsoft Corporation")
+        )
+        .is_none());
+
+        let path = credential_path(1, r"git:https://acme.example\repo").unwrap();
+        assert_eq!(path, r"Credential\Generic\git:https://acme.example\repo");
+        assert_eq!(
+            parse_credential_path(&path),
+            Some((1, r"git:https://acme.example\repo"))
+        );
+        assert!(parse_credential_path(r"Credential\Unknown\x").is_none());
+        assert!(parse_credential_path(r"HKCU\Software\Acme").is_none());
+    }
+}
@@ -0,0 +1,208 @@
+//! Windows Defender 排除项残留
+//!
+//! 安全软件、开发工具和游戏反作弊组件常在安装时把自己的目录或进程加入 Defender 排除列表
+//! （`Windows Defender\Exclusions\Paths` 与 `\Processes`，值名即排除的路径或进程）。卸载后排除项
+//! 常被留下，之后任何程序写入同一目录或使用同名进程都能绕过实时保护，属于安全漏洞。
+//!
+//! 排除的路径位于程序安装目录时报告为高置信度，路径或进程名与程序名匹配时报告为中置信度。
+//! 痕迹路径形如 `HKLM\SOFTWARE\Microsoft\Windows Defender\Exclusions\Paths=<路径>`，
+//! 清理时通过 `Remove-MpPreference` 只移除该排除项。
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+
+/// Defender 排除项注册位置（HKLM）
+pub const EXCLUSIONS_KEY: &str = r"SOFTWARE\Microsoft\Windows Defender\Exclusions";
+
+/// 排除项类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExclusionKind {
+    /// 文件或目录
+    Path,
+    /// 进程
+    Process,
+}
+
+impl ExclusionKind {
+    const ALL: [ExclusionKind; 2] = [ExclusionKind::Path, ExclusionKind::Process];
+
+    /// `Exclusions` 下的子键名
+    pub fn subkey(self) -> &'static str {
+        match self {
+            ExclusionKind::Path => "Paths",
+            ExclusionKind::Process => "Processes",
+        }
+    }
+
+    /// `Remove-MpPreference` 的参数名
+    pub fn parameter(self) -> &'static str {
+        match self {
+            ExclusionKind::Path => "-ExclusionPath",
+            ExclusionKind::Process => "-ExclusionProcess",
+        }
+    }
+}
+
+/// 扫描指向程序的 Defender 排除项，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_defender_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+    let exclusions = match RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(EXCLUSIONS_KEY) {
+        Ok(key) => key,
+        // 未安装 Defender 或没有任何排除项
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(UninstallerError::Registry(format!(
+                "无法打开 Defender 排除项注册表: {}",
+                e
+            )))
+        }
+    };
+
+    for kind in ExclusionKind::ALL {
+        let Ok(key) = exclusions.open_subkey(kind.subkey()) else {
+            continue;
+        };
+        for (exclusion, _) in key.enum_values().flatten() {
+            let Some((confidence, reason)) =
+                match_exclusion(kind, &exclusion, patterns, install_root)
+            else {
+                continue;
+            };
+            let description = match kind {
+                ExclusionKind::Path => format!("Defender 排除路径: {}", exclusion),
+                ExclusionKind::Process => format!("Defender 排除进程: {}", exclusion),
+            };
+            let mut trace = Trace::new(
+                program_name.clone(),
+                TraceType::DefenderExclusion,
+                exclusion_trace_path(kind, &exclusion),
+            )
+            .with_description(description)
+            .with_confidence(confidence);
+            trace.set_match_reason(reason.to_string());
+            emit(trace);
+        }
+    }
+
+    Ok(())
+}
+
+/// 排除项痕迹的路径
+pub fn exclusion_trace_path(kind: ExclusionKind, exclusion: &str) -> String {
+    format!(r"HKLM\{}\{}={}", EXCLUSIONS_KEY, kind.subkey(), exclusion)
+}
+
+/// 从痕迹路径中取出排除项类别和内容，不是单个排除项时返回 `None`
+pub fn parse_exclusion_path(path: &str) -> Option<(ExclusionKind, &str)> {
+    ExclusionKind::ALL.into_iter().find_map(|kind| {
+        let prefix = format!(r"HKLM\{}\{}=", EXCLUSIONS_KEY, kind.subkey());
+        let exclusion = strip_prefix_ignore_case(path, &prefix)?;
+        (!exclusion.trim().is_empty()).then_some((kind, exclusion))
+    })
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    (text.len() >= prefix.len() && text.is_char_boundary(prefix.len()))
+        .then(|| text.split_at(prefix.len()))
+        .filter(|(head, _)| head.eq_ignore_ascii_case(prefix))
+        .map(|(_, rest)| rest)
+}
+
+/// 判断排除项是否属于程序，返回置信度和命中原因
+fn match_exclusion(
+    kind: ExclusionKind,
+    exclusion: &str,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let target =
+        utils::normalize_path(&expand_windows_env_vars(exclusion.trim().trim_matches('"')))
+            .to_lowercase();
+    if target.is_empty() {
+        return None;
+    }
+    // 排除整个系统目录或系统进程的项由管理员设置，不能据此判断归属
+    if target.contains('\\') && utils::is_system_critical_path(&target) {
+        return None;
+    }
+
+    if let Some(root) = install_root {
+        // 排除项可能就是安装目录本身（不带结尾的 `\`）
+        if target.starts_with(root) || format!(r"{}\", target) == root {
+            return Some(match kind {
+                ExclusionKind::Path => (Confidence::High, "Defender 排除路径位于程序安装目录"),
+                ExclusionKind::Process => (Confidence::High, "Defender 排除进程位于程序安装目录"),
+            });
+        }
+    }
+
+    patterns
+        .iter()
+        .any(|pattern| matching::name_matches(&target, pattern))
+        .then_some(match kind {
+            ExclusionKind::Path => (Confidence::Medium, "Defender 排除路径与程序名匹配"),
+            ExclusionKind::Process => (Confidence::Medium, "Defender 排除进程名与程序名匹配"),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_exclusions_and_parses_paths() {
+        let patterns = vec!["acme".to_string()];
+        let root = Some(r"d:\apps\acme\");
+
+        assert_eq!(
+            match_exclusion(ExclusionKind::Path, r"D:\Apps\Acme", &patterns, root).map(|m| m.0),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            match_exclusion(
+                ExclusionKind::Process,
+                r"d:\apps\acme\agent.exe",
+                &patterns,
+                root
+            )
+            .map(|m| m.0),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            match_exclusion(ExclusionKind::Process, "acme-agent.exe", &patterns, root).map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+        assert!(
+            match_exclusion(ExclusionKind::Path, r"d:\projects\build", &patterns, root).is_none()
+        );
+
+        let path = exclusion_trace_path(ExclusionKind::Process, "acme-agent.exe");
+        assert_eq!(
+            parse_exclusion_path(&path),
+            Some((ExclusionKind::Process, "acme-agent.exe"))
+        );
+        assert_eq!(
+            parse_exclusion_path(
+                r"HKLM\SOFTWARE\Microsoft\Windows Defender\Exclusions\Paths=d:\apps\acme"
+            ),
+            Some((ExclusionKind::Path, r"d:\apps\acme"))
+        );
+        assert!(
+            parse_exclusion_path(r"HKLM\SOFTWARE\Microsoft\Windows Defender\Exclusions").is_none()
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! 第三方驱动程序包
+//!
+//! 硬件工具类软件卸载后常在驱动存储中留下 oem*.inf 驱动包。
+//! 通过 `pnputil /enum-drivers` 枚举，并按提供商与程序发布者、驱动包名与程序名关联。
+
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::uninstaller::signature;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// 程序名中不参与匹配的通用词
+const GENERIC_NAME_WORDS: &[&str] = &[
+    "driver",
+    "drivers",
+    "software",
+    "utility",
+    "utilities",
+    "tool",
+    "tools",
+    "center",
+    "suite",
+    "setup",
+    "service",
+    "support",
+    "update",
+    "app",
+];
+
+/// 驱动程序包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverPackage {
+    /// 驱动存储中的发布名，如 `oem12.inf`
+    pub published_name: String,
+    /// 原始 inf 文件名
+    pub original_name: String,
+    pub provider: String,
+    pub class_name: String,
+    pub version: String,
+}
+
+/// 列出所有第三方驱动包
+pub fn list_driver_packages() -> Result<Vec<DriverPackage>, UninstallerError> {
+    let output = Command::new("pnputil").arg("/enum-drivers").output()?;
+
+    if !output.status.success() {
+        return Err(UninstallerError::Other(format!(
+            "pnputil 执行失败: {}",
+            String::from_utf8_lossy(&output.stdout).trim()
+        )));
+    }
+
+    Ok(parse_enum_drivers(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 `pnputil /enum-drivers` 输出
+///
+/// 标签随系统语言变化，因此按字段顺序解析：发布名、原始名、提供商、类名，其后的字段中查找版本号
+fn parse_enum_drivers(output: &str) -> Vec<DriverPackage> {
+    let mut packages = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+
+    let mut flush = |values: &mut Vec<String>| {
+        if let Some(package) = package_from_values(values) {
+            packages.push(package);
+        }
+        values.clear();
+    };
+
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            flush(&mut values);
+            continue;
+        }
+        if let Some((_, value)) = line.split_once([':', '：']) {
+            values.push(value.trim().to_string());
+        }
+    }
+    flush(&mut values);
+
+    packages
+}
+
+fn package_from_values(values: &[String]) -> Option<DriverPackage> {
+    let published_name = values.first()?;
+    if !is_oem_inf(published_name) {
+        return None;
+    }
+
+    Some(DriverPackage {
+        published_name: published_name.to_lowercase(),
+        original_name: values.get(1).cloned().unwrap_or_default(),
+        provider: values.get(2).cloned().unwrap_or_default(),
+        class_name: values.get(3).cloned().unwrap_or_default(),
+        // 版本字段形如 `06/21/2006 10.0.19041.1`，新版 pnputil 会在前面插入扩展 ID 等字段
+        version: values
+            .iter()
+            .skip(4)
+            .filter_map(|v| v.split_whitespace().last())
+            .find(|v| {
+                v.split('.').count() == 4 && v.chars().all(|c| c.is_ascii_digit() || c == '.')
+            })
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+/// 是否为第三方驱动包的发布名 (oemN.inf)，系统自带驱动不在此列
+pub fn is_oem_inf(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower
+        .strip_prefix("oem")
+        .and_then(|rest| rest.strip_suffix(".inf"))
+        .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// 驱动包与程序的关联程度；提供商与发布者不一致时不关联
+pub fn correlate(
+    package: &DriverPackage,
+    program_name: &str,
+    publisher: Option<&str>,
+) -> Option<Confidence> {
+    let publisher = publisher.filter(|p| !p.trim().is_empty())?;
+    if !signature::publisher_matches(&package.provider, publisher) {
+        return None;
+    }
+
+    // 厂商名本身不算名称命中，否则同厂商的所有驱动都会被当作该程序的驱动
+    let publisher_lower = publisher.to_lowercase();
+    let inf_lower = package.original_name.to_lowercase();
+    let name_hit = program_name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() >= 3 && !GENERIC_NAME_WORDS.contains(word))
+        .filter(|word| !publisher_lower.contains(word))
+        .any(|word| inf_lower.contains(word));
+
+    Some(if name_hit {
+        Confidence::High
+    } else {
+        Confidence::Medium
+    })
+}
+
+/// 搜索程序的驱动包痕迹
+///
+/// 同一厂商往往还有仍在使用的其他驱动，只返回名称也能对上的高置信度驱动包
+pub fn scan_driver_traces(
+    program_name: &str,
+    publisher: Option<&str>,
+) -> Result<Vec<Trace>, UninstallerError> {
+    Ok(list_driver_packages()?
+        .into_iter()
+        .filter(|package| correlate(package, program_name, publisher) == Some(Confidence::High))
+        .map(|package| {
+            let mut trace = Trace::new(
+                program_name.to_string(),
+                TraceType::Driver,
+                package.published_name.clone(),
+            )
+            .with_description(format!(
+                "驱动程序包 {} ({}, {} {})",
+                package.original_name, package.provider, package.class_name, package.version
+            ))
+            .with_confidence(Confidence::High);
+            trace.set_match_reason("驱动提供商与发布者一致，且驱动名包含程序名".to_string());
+            trace
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_enum_drivers_and_correlates_by_publisher() {
+        let output = "Microsoft PnP Utility\n\n\
+            Published Name:     oem3.inf\n\
+            Original Name:      logi_hub_audio.inf\n\
+            Provider Name:      Logitech\n\
+            Class Name:         Sound, video and game controllers\n\
+            Class GUID:         {4d36e96c-e325-11ce-bfc1-08002be10318}\n\
+            Driver Version:     03/15/2023 1.2.3.4\n\
+            Signer Name:        Microsoft Windows Hardware Compatibility Publisher\n\n\
+            Published Name:     oem7.inf\n\
+            Original Name:      logi_joy_bus.inf\n\
+            Provider Name:      Logitech\n\
+            Class Name:         System\n\
+            Class GUID:         {4d36e97d-e325-11ce-bfc1-08002be10318}\n\
+            Driver Version:     01/01/2022 5.0.0.1\n";
+
+        let packages = parse_enum_drivers(output);
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].published_name, "oem3.inf");
+        assert_eq!(packages[0].version, "1.2.3.4");
+
+        let publisher = Some("Logitech Inc.");
+        assert_eq!(
+            correlate(&packages[0], "Logitech G HUB", publisher),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            correlate(&packages[1], "Logitech G HUB", publisher),
+            Some(Confidence::Medium)
+        );
+        assert_eq!(correlate(&packages[0], "G HUB", Some("Razer")), None);
+        assert!(!is_oem_inf("usbport.inf"));
+    }
+}
@@ -0,0 +1,160 @@
+//! 环境变量残留
+//!
+//! 安装程序常把 `bin` 目录追加到 `Path`，或写入 `ACME_HOME` 这类指向安装目录的变量，
+//! 卸载后这些条目仍留在机器和用户环境变量中。`Path` 等列表变量按 `;` 拆分，
+//! 每个指向程序的片段报告为一项痕迹，清理时只移除该片段，保留变量中的其他内容。
+//!
+//! 痕迹路径形如 `HKCU\Environment\Path=D:\Apps\Acme\bin`：变量名不能包含 `=`，
+//! 第一个 `=` 之后即为要移除的片段。
+
+use std::path::Path;
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+
+/// 当前用户的环境变量
+pub const USER_ENVIRONMENT_KEY: &str = "Environment";
+
+/// 机器环境变量（HKLM）
+pub const MACHINE_ENVIRONMENT_KEY: &str =
+    r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment";
+
+/// 扫描指向程序安装目录的环境变量片段，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_environment_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+
+    for (hkey, root, subkey) in [
+        (HKEY_CURRENT_USER, "HKCU", USER_ENVIRONMENT_KEY),
+        (HKEY_LOCAL_MACHINE, "HKLM", MACHINE_ENVIRONMENT_KEY),
+    ] {
+        let Ok(key) = RegKey::predef(hkey).open_subkey(subkey) else {
+            continue;
+        };
+
+        for (name, _) in key.enum_values().flatten() {
+            // REG_SZ 和 REG_EXPAND_SZ 都能按字符串读取，其他类型不是路径
+            let Ok(value) = key.get_value::<String, _>(&name) else {
+                continue;
+            };
+
+            for segment in split_segments(&value) {
+                let Some((confidence, reason)) = match_segment(segment, patterns, install_root)
+                else {
+                    continue;
+                };
+
+                let mut trace = Trace::new(
+                    program_name.clone(),
+                    TraceType::EnvironmentVariable,
+                    format!(r"{}\{}\{}={}", root, subkey, name, segment),
+                )
+                .with_description(format!("环境变量 {} 中的路径: {}", name, segment))
+                .with_confidence(confidence);
+                trace.set_match_reason(reason.to_string());
+                emit(trace);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 按 `;` 拆分变量值，去掉空片段
+pub fn split_segments(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+}
+
+/// 比较用的片段：展开环境变量、去掉引号和末尾的 `\`
+pub fn segment_key(segment: &str) -> String {
+    utils::normalize_path(&expand_windows_env_vars(segment.trim().trim_matches('"')))
+        .trim_end_matches('\\')
+        .to_lowercase()
+}
+
+/// 拆分痕迹路径，返回 (注册表键路径, 变量名, 片段)
+pub fn parse_environment_path(path: &str) -> Option<(&str, &str, &str)> {
+    let (variable_path, segment) = path.split_once('=')?;
+    let (key_path, name) = variable_path.rsplit_once('\\')?;
+    let known = [
+        format!(r"HKCU\{}", USER_ENVIRONMENT_KEY),
+        format!(r"HKLM\{}", MACHINE_ENVIRONMENT_KEY),
+    ];
+    (known
+        .iter()
+        .any(|known| key_path.eq_ignore_ascii_case(known))
+        && !name.is_empty()
+        && !segment.trim().is_empty())
+    .then_some((key_path, name, segment))
+}
+
+/// 判断片段是否指向程序，返回置信度和命中原因
+fn match_segment(
+    segment: &str,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let key = segment_key(segment);
+    // 只看像本地路径的片段（`C:\...`），`.COM;.EXE` 这类列表不参与
+    if key.len() < 3 || key.as_bytes()[1] != b':' || utils::is_system_critical_path(&key) {
+        return None;
+    }
+
+    if install_root.is_some_and(|root| format!(r"{}\", key).starts_with(root)) {
+        return Some((Confidence::High, "环境变量中的路径位于程序安装目录"));
+    }
+
+    // 没有安装目录记录时，只报告已经不存在、且名称与程序匹配的路径
+    (patterns
+        .iter()
+        .any(|pattern| matching::name_matches(&key, pattern))
+        && !Path::new(&key).exists())
+    .then_some((Confidence::Medium, "环境变量中的路径已不存在且与程序名匹配"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_path_segments_under_install_dir() {
+        let value = r"C:\Windows\system32;D:\Apps\Acme\bin\;;D:\Tools";
+        let segments: Vec<&str> = split_segments(value).collect();
+        assert_eq!(
+            segments,
+            vec![r"C:\Windows\system32", r"D:\Apps\Acme\bin\", r"D:\Tools"]
+        );
+
+        let patterns = vec!["acme".to_string()];
+        let root = Some(r"d:\apps\acme\");
+        assert_eq!(
+            match_segment(segments[1], &patterns, root).map(|m| m.0),
+            Some(Confidence::High)
+        );
+        assert!(match_segment(segments[0], &patterns, root).is_none());
+        assert!(match_segment(".COM;.EXE", &patterns, root).is_none());
+
+        let path = format!(r"HKCU\{}\Path=D:\Apps\Acme\bin", USER_ENVIRONMENT_KEY);
+        assert_eq!(
+            parse_environment_path(&path),
+            Some((r"HKCU\Environment", "Path", r"D:\Apps\Acme\bin"))
+        );
+        assert!(parse_environment_path(r"HKCU\Software\Acme\Path=D:\x").is_none());
+    }
+}
@@ -0,0 +1,212 @@
+//! 事件日志源残留
+//!
+//! 程序（尤其是服务和 .NET 程序）会在 `Services\EventLog\<日志>\<源>` 下注册事件源，
+//! 用 `EventMessageFile` 指向含消息资源的 DLL。卸载后注册常被留下，事件查看器中
+//! 对应事件只显示“找不到事件 ID 的描述”。消息文件指向程序安装目录、路径与程序名匹配，
+//! 或源名称与程序名匹配且消息文件已不存在时报告为 `EventLogSource` 痕迹。
+//! 清理时只删除源本身的键，日志及其他源保持不变。
+
+use std::path::Path;
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+
+/// 事件日志注册位置
+const EVENT_LOG_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog";
+
+/// 保存消息资源文件的值，可以是分号分隔的多个文件
+const MESSAGE_FILE_VALUES: &[&str] = &[
+    "EventMessageFile",
+    "CategoryMessageFile",
+    "ParameterMessageFile",
+];
+
+/// 扫描程序留下的事件日志源，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_event_log_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+    let event_log = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(EVENT_LOG_KEY)
+        .map_err(|e| UninstallerError::Registry(format!("无法打开事件日志注册表: {}", e)))?;
+
+    for log in event_log.enum_keys().flatten() {
+        let Ok(log_key) = event_log.open_subkey(&log) else {
+            continue;
+        };
+        for source in log_key.enum_keys().flatten() {
+            // 与日志同名的源是日志自身的默认源
+            if source.eq_ignore_ascii_case(&log) {
+                continue;
+            }
+            let Ok(source_key) = log_key.open_subkey(&source) else {
+                continue;
+            };
+            let files = message_files(&source_key);
+            let Some((confidence, reason)) = match_source(&source, &files, patterns, install_root)
+            else {
+                continue;
+            };
+
+            let mut trace = Trace::new(
+                program_name.clone(),
+                TraceType::EventLogSource,
+                format!(r"HKLM\{}\{}\{}", EVENT_LOG_KEY, log, source),
+            )
+            .with_description(format!(
+                "事件日志源 {} ({}): {}",
+                source,
+                log,
+                files.join("; ")
+            ))
+            .with_confidence(confidence);
+            trace.set_match_reason(reason.to_string());
+            emit(trace);
+        }
+    }
+
+    Ok(())
+}
+
+/// 痕迹路径是否为单个事件源（`...\Services\EventLog\<日志>\<源>`，源与日志不同名）
+pub fn is_event_source_path(path: &str) -> bool {
+    let Some(rest) = strip_prefix_ignore_case(path, &format!(r"HKLM\{}\", EVENT_LOG_KEY)) else {
+        return false;
+    };
+    let segments: Vec<&str> = rest.trim_end_matches('\\').split('\\').collect();
+    segments.len() == 2
+        && segments.iter().all(|segment| !segment.trim().is_empty())
+        && !segments[0].eq_ignore_ascii_case(segments[1])
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    (text.len() >= prefix.len() && text.is_char_boundary(prefix.len()))
+        .then(|| text.split_at(prefix.len()))
+        .filter(|(head, _)| head.eq_ignore_ascii_case(prefix))
+        .map(|(_, rest)| rest)
+}
+
+/// 事件源引用的消息资源文件（已展开环境变量，小写）
+fn message_files(source_key: &RegKey) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for value in MESSAGE_FILE_VALUES {
+        let Ok(raw) = source_key.get_value::<String, _>(value) else {
+            continue;
+        };
+        for file in raw.split(';') {
+            let file =
+                utils::normalize_path(&expand_windows_env_vars(file.trim().trim_matches('"')))
+                    .to_lowercase();
+            if !file.is_empty() && !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    files
+}
+
+/// 判断事件源是否属于程序，返回置信度和命中原因
+fn match_source(
+    source: &str,
+    files: &[String],
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    // .NET 等运行时的通用消息文件位于系统目录，不能据此判断归属
+    let own_files: Vec<&String> = files
+        .iter()
+        .filter(|file| !utils::is_system_critical_path(file))
+        .collect();
+
+    if install_root.is_some_and(|root| own_files.iter().any(|file| file.starts_with(root))) {
+        return Some((Confidence::High, "事件源的消息文件位于程序安装目录"));
+    }
+    if own_files.iter().any(|file| {
+        patterns
+            .iter()
+            .any(|pattern| matching::name_matches(file, pattern))
+    }) {
+        return Some((Confidence::Medium, "事件源的消息文件路径与程序名匹配"));
+    }
+
+    let name_matches = patterns
+        .iter()
+        .any(|pattern| matching::name_matches(source, pattern));
+    if !name_matches {
+        return None;
+    }
+    if !own_files.is_empty() && own_files.iter().all(|file| !Path::new(file).exists()) {
+        return Some((
+            Confidence::Medium,
+            "事件源的消息文件已不存在，源名称与程序名匹配",
+        ));
+    }
+    own_files
+        .is_empty()
+        .then_some((Confidence::Low, "事件源名称与程序名匹配"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_sources_and_validates_paths() {
+        let patterns = vec!["acme".to_string()];
+        let root = Some(r"d:\apps\acme\");
+        let files = |list: &[&str]| list.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            match_source(
+                "AcmeAgent",
+                &files(&[r"d:\apps\acme\messages.dll"]),
+                &patterns,
+                root
+            )
+            .map(|m| m.0),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            match_source(
+                "Acme",
+                &files(&[r"c:\windows\microsoft.net\framework64\v4.0.30319\eventlogmessages.dll"]),
+                &patterns,
+                root
+            )
+            .map(|m| m.0),
+            Some(Confidence::Low)
+        );
+        assert!(match_source(
+            "Other",
+            &files(&[r"c:\windows\system32\other.dll"]),
+            &patterns,
+            root
+        )
+        .is_none());
+
+        assert!(is_event_source_path(
+            r"HKLM\SYSTEM\CurrentControlSet\Services\EventLog\Application\AcmeAgent"
+        ));
+        assert!(!is_event_source_path(
+            r"HKLM\SYSTEM\CurrentControlSet\Services\EventLog\Application"
+        ));
+        assert!(!is_event_source_path(
+            r"HKLM\SYSTEM\CurrentControlSet\Services\EventLog\Acme\Acme"
+        ));
+        assert!(!is_event_source_path(
+            r"HKLM\SOFTWARE\Acme\Application\AcmeAgent"
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+//! 痕迹导出文件格式
+//!
+//! `search --output` 写出的文件由 `clean --from-file` 读取，两者可能来自不同版本的程序，
+//! 因此文件带有格式标识和版本号。早期版本直接写出痕迹数组，读取时视为版本 0；
+//! 版本号高于当前支持的文件会明确拒绝，而不是按当前结构猜测字段含义。
+//!
+//! 扩展名为 `.html` 时写出可离线打开的审核页面，导出内容以 JSON 嵌在页面中。审核人在页面中
+//! 勾选后另存的文件带有 `approved` 列表，`clean --from-file` 只清理列表中的痕迹。
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::models::Trace;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::reporter;
+
+/// 导出文件的格式标识
+pub const TRACE_EXPORT_FORMAT: &str = "rust-yu.traces";
+
+/// 当前导出格式版本，字段含义变化时递增；只新增可选字段不需要递增
+pub const TRACE_EXPORT_VERSION: u32 = 1;
+
+/// 痕迹导出文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceExport {
+    pub format: String,
+    pub version: u32,
+    /// 导出时的程序版本，仅用于排查问题
+    #[serde(default)]
+    pub tool_version: String,
+    pub exported_at: DateTime<Utc>,
+    pub program_name: String,
+    pub traces: Vec<Trace>,
+    /// 审核人批准的痕迹 ID，为 None 表示未经审核
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved: Option<Vec<String>>,
+}
+
+impl TraceExport {
+    pub fn new(program_name: &str, traces: Vec<Trace>) -> Self {
+        Self {
+            format: TRACE_EXPORT_FORMAT.to_string(),
+            version: TRACE_EXPORT_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Utc::now(),
+            program_name: program_name.to_string(),
+            traces,
+            approved: None,
+        }
+    }
+
+    /// 待清理的痕迹：经过审核时只保留已批准的痕迹
+    pub fn into_selected_traces(self) -> Vec<Trace> {
+        match self.approved {
+            Some(approved) => self
+                .traces
+                .into_iter()
+                .filter(|trace| approved.contains(&trace.id))
+                .collect(),
+            None => self.traces,
+        }
+    }
+}
+
+/// 审核页面中存放导出内容的 `<script>` 元素 ID
+pub const HTML_EXPORT_ELEMENT_ID: &str = "rust-yu-export";
+
+/// 按扩展名判断是否写出审核页面
+fn is_html_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm")
+        })
+}
+
+/// 嵌入页面的 JSON；`<` 只会出现在字符串中，转义后不会提前结束 `<script>`
+pub fn embedded_json(export: &TraceExport) -> Result<String, UninstallerError> {
+    let json = serde_json::to_string(export)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    Ok(json.replace('<', "\\u003c"))
+}
+
+/// 从审核页面中取出嵌入的导出内容
+fn extract_embedded_json(content: &str) -> Option<&str> {
+    let marker = format!("id=\"{}\"", HTML_EXPORT_ELEMENT_ID);
+    let start = content.find(&marker)?;
+    let body_start = start + content[start..].find('>')? + 1;
+    let body_end = body_start + content[body_start..].find("</script>")?;
+    Some(content[body_start..body_end].trim())
+}
+
+/// 写出导出文件，扩展名为 `.html`/`.htm` 时写出审核页面
+pub fn save_trace_export(path: &Path, export: &TraceExport) -> Result<(), UninstallerError> {
+    let content = if is_html_path(path) {
+        reporter::html::generate_trace_export_html(export)?
+    } else {
+        serde_json::to_string_pretty(export)
+            .map_err(|error| UninstallerError::Serde(error.to_string()))?
+    };
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// 读取导出文件
+///
+/// 版本 0 的文件没有记录导出时间，以文件修改时间代替
+pub fn load_trace_export(path: &Path) -> Result<TraceExport, UninstallerError> {
+    let content = std::fs::read_to_string(path)?;
+    let mut export = parse_trace_export(&content).map_err(|error| match error {
+        UninstallerError::Serde(message) => {
+            UninstallerError::Serde(format!("{}: {}", path.display(), message))
+        }
+        other => other,
+    })?;
+
+    if export.version == 0 {
+        if let Ok(modified) = std::fs::metadata(path).and_then(|metadata| metadata.modified()) {
+            export.exported_at = DateTime::<Utc>::from(modified);
+        }
+    }
+    Ok(export)
+}
+
+/// 解析导出内容（JSON 或审核页面），先检查格式与版本，再按当前结构反序列化
+pub fn parse_trace_export(content: &str) -> Result<TraceExport, UninstallerError> {
+    let content = if content.trim_start().starts_with('<') {
+        extract_embedded_json(content)
+            .ok_or_else(|| UninstallerError::Serde("页面中没有嵌入的痕迹导出内容".to_string()))?
+    } else {
+        content
+    };
+    let value: serde_json::Value = serde_json::from_str(content)
+        .map_err(|error| UninstallerError::Serde(format!("不是有效的 JSON: {}", error)))?;
+
+    // 版本 0：没有包装的痕迹数组
+    if value.is_array() {
+        let traces: Vec<Trace> = serde_json::from_value(value)
+            .map_err(|error| UninstallerError::Serde(format!("痕迹列表格式错误: {}", error)))?;
+        let program_name = traces
+            .first()
+            .map(|trace| trace.program_name.clone())
+            .unwrap_or_default();
+        return Ok(TraceExport {
+            format: TRACE_EXPORT_FORMAT.to_string(),
+            version: 0,
+            tool_version: String::new(),
+            exported_at: DateTime::<Utc>::UNIX_EPOCH,
+            program_name,
+            traces,
+            approved: None,
+        });
+    }
+
+    let format = value.get("format").and_then(|format| format.as_str());
+    if format != Some(TRACE_EXPORT_FORMAT) {
+        return Err(UninstallerError::Serde(format!(
+            "不是痕迹导出文件 (format: {})",
+            format.unwrap_or("缺失")
+        )));
+    }
+
+    let version = value
+        .get("version")
+        .and_then(|version| version.as_u64())
+        .ok_or_else(|| UninstallerError::Serde("痕迹导出文件缺少版本号".to_string()))?;
+    if version > u64::from(TRACE_EXPORT_VERSION) {
+        return Err(UninstallerError::Serde(format!(
+            "痕迹导出文件版本 {} 高于当前支持的版本 {}，请升级程序后再导入",
+            version, TRACE_EXPORT_VERSION
+        )));
+    }
+
+    serde_json::from_value(value)
+        .map_err(|error| UninstallerError::Serde(format!("痕迹导出文件格式错误: {}", error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::scanner::models::TraceType;
+
+    #[test]
+    fn round_trips_and_rejects_newer_versions() {
+        let export = TraceExport::new("Demo", Vec::new());
+        let json = serde_json::to_string(&export).unwrap();
+        let loaded = parse_trace_export(&json).unwrap();
+        assert_eq!(loaded.version, TRACE_EXPORT_VERSION);
+        assert_eq!(loaded.program_name, "Demo");
+
+        // 早期版本的裸数组
+        assert_eq!(parse_trace_export("[]").unwrap().version, 0);
+
+        let newer = json.replace(
+            &format!("\"version\":{}", TRACE_EXPORT_VERSION),
+            &format!("\"version\":{}", TRACE_EXPORT_VERSION + 1),
+        );
+        assert!(parse_trace_export(&newer).is_err());
+        assert!(parse_trace_export(r#"{"format":"other","version":1}"#).is_err());
+    }
+
+    #[test]
+    fn reimports_approved_selection_from_review_page() {
+        let keep = Trace::new(
+            "Demo".to_string(),
+            TraceType::File,
+            r"C:\Demo\</script>".to_string(),
+        );
+        let skip = Trace::new("Demo".to_string(), TraceType::File, r"C:\Other".to_string());
+        let mut export = TraceExport::new("Demo", vec![keep.clone(), skip]);
+        export.approved = Some(vec![keep.id.clone()]);
+
+        let html = reporter::html::generate_trace_export_html(&export).unwrap();
+        let loaded = parse_trace_export(&html).unwrap();
+        let selected = loaded.into_selected_traces();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].path, keep.path);
+
+        assert!(parse_trace_export("<html></html>").is_err());
+    }
+}
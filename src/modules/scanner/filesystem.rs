@@ -1,16 +1,24 @@
+use super::matching;
 use super::models::{Confidence, Trace, TraceType};
+use super::options::{self, ScanOptions};
+use super::path_index;
 use crate::modules::common::error::UninstallerError;
+use crate::modules::common::known_folders;
 use crate::modules::common::utils;
 use std::path::Path;
 use walkdir::WalkDir;
 
-/// 扫描文件系统痕迹
-pub fn scan_filesystem_traces(program_name: &str) -> Result<Vec<Trace>, UninstallerError> {
-    let mut traces = Vec::new();
+/// 扫描文件系统痕迹，每发现一项即交给 `emit`
+pub fn scan_filesystem_traces(
+    program_name: &str,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
     let search_pattern = program_name.to_lowercase();
+    let options = options::current();
 
-    // 扫描目录
-    let dirs_to_scan = get_scan_dirs();
+    // 扫描目录，附加调用方指定的目录
+    let mut dirs_to_scan = get_scan_dirs();
+    dirs_to_scan.extend(options.extra_roots.iter().cloned());
 
     for dir in dirs_to_scan {
         if !dir.exists() {
@@ -21,10 +29,10 @@ pub fn scan_filesystem_traces(program_name: &str) -> Result<Vec<Trace>, Uninstal
         tracing::debug!("扫描目录: {}", dir_str);
 
         // 扫描目录
-        scan_directory(&dir, &search_pattern, &mut traces);
+        scan_directory(&dir, &search_pattern, &options, emit);
     }
 
-    Ok(traces)
+    Ok(())
 }
 
 /// 获取需要扫描的目录
@@ -49,13 +57,12 @@ fn get_scan_dirs() -> Vec<std::path::PathBuf> {
         }
     }
 
-    // 用户桌面
-    if let Some(home) = dirs::home_dir() {
-        let desktop = home.join("Desktop");
-        if desktop.exists() {
-            dirs.push(desktop);
-        }
-    }
+    // 用户桌面，可能已重定向到 OneDrive 或网络位置
+    dirs.extend(
+        known_folders::desktop_dirs()
+            .into_iter()
+            .filter(|desktop| desktop.exists()),
+    );
 
     // ProgramData
     if let Ok(program_data) = std::env::var("ProgramData") {
@@ -65,74 +72,107 @@ fn get_scan_dirs() -> Vec<std::path::PathBuf> {
     dirs
 }
 
-/// 扫描目录
-fn scan_directory(dir: &Path, pattern: &str, traces: &mut Vec<Trace>) {
+/// 扫描目录（优先查询路径索引，索引不可用时遍历目录）
+fn scan_directory(dir: &Path, pattern: &str, options: &ScanOptions, emit: &mut dyn FnMut(Trace)) {
+    if let Some(entries) =
+        path_index::indexed_matches(dir, options.filesystem_depth, &matching::index_key(pattern))
+    {
+        for path in entries {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if !matching::name_matches(&name, pattern) {
+                continue;
+            }
+            if let Some(trace) = build_trace(&path, pattern) {
+                emit(trace);
+            }
+        }
+        return;
+    }
+
     let walker = WalkDir::new(dir)
-        .max_depth(3) // 限制深度
+        .max_depth(options.filesystem_depth) // 限制深度
         .follow_links(false);
 
-    for entry in walker.into_iter().filter_map(|e| e.ok()) {
+    for entry in walker
+        .into_iter()
+        .take(options.entry_limit())
+        .filter_map(|e| e.ok())
+    {
         let path = entry.path();
         let name = path
             .file_name()
             .map(|n| n.to_string_lossy().to_lowercase())
             .unwrap_or_default();
 
-        // 检查名称是否包含搜索模式
-        if name.contains(pattern) {
-            // 跳过系统目录
-            if is_system_dir(path) {
-                continue;
-            }
-
-            let trace_type = if path.is_dir() {
-                TraceType::File
-            } else if path.extension().map(|e| e == "lnk").unwrap_or(false) {
-                TraceType::Shortcut
-            } else {
-                TraceType::File
-            };
-
-            let size = if path.is_file() {
-                path.metadata().ok().map(|m| m.len())
-            } else {
-                None
-            };
-
-            let description = if path.is_dir() {
-                format!(
-                    "目录: {} 个项目",
-                    entry.metadata().ok().map(|m| m.len()).unwrap_or(0)
-                )
-            } else {
-                size.map(|s| format!("文件大小: {}", utils::format_size(s)))
-                    .unwrap_or_default()
-            };
-
-            let confidence = if name.starts_with(pattern) || name == pattern {
-                Confidence::High
-            } else {
-                Confidence::Medium
-            };
-
-            let trace = Trace::new(
-                pattern.to_string(),
-                trace_type,
-                path.to_string_lossy().to_string(),
-            )
-            .with_description(description)
-            .with_confidence(confidence);
-
-            // 如果是文件，设置大小
-            if let Some(s) = size {
-                traces.push(trace.with_size(s));
-            } else {
-                traces.push(trace);
+        // 按单词匹配，避免短名称命中无关目录
+        if matching::name_matches(&name, pattern) {
+            if let Some(trace) = build_trace(path, pattern) {
+                emit(trace);
             }
         }
     }
 }
 
+/// 为名称匹配的路径构建痕迹，系统目录或已不存在的路径返回 `None`
+fn build_trace(path: &Path, pattern: &str) -> Option<Trace> {
+    // 跳过系统目录
+    if is_system_dir(path) || !path.exists() {
+        return None;
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let trace_type = if path.is_dir() {
+        TraceType::File
+    } else if path.extension().map(|e| e == "lnk").unwrap_or(false) {
+        TraceType::Shortcut
+    } else {
+        TraceType::File
+    };
+
+    let size = if path.is_file() {
+        path.metadata().ok().map(|m| m.len())
+    } else {
+        None
+    };
+
+    let description = if path.is_dir() {
+        format!(
+            "目录: {} 个项目",
+            path.metadata().ok().map(|m| m.len()).unwrap_or(0)
+        )
+    } else {
+        size.map(|s| format!("文件大小: {}", utils::format_size(s)))
+            .unwrap_or_default()
+    };
+
+    let confidence = if name.starts_with(pattern) || name == pattern {
+        Confidence::High
+    } else {
+        Confidence::Medium
+    };
+
+    let trace = Trace::new(
+        pattern.to_string(),
+        trace_type,
+        path.to_string_lossy().to_string(),
+    )
+    .with_description(description)
+    .with_confidence(confidence);
+
+    // 如果是文件，设置大小
+    Some(match size {
+        Some(s) => trace.with_size(s),
+        None => trace,
+    })
+}
+
 /// 检查是否为系统目录
 fn is_system_dir(path: &Path) -> bool {
     let path_str = path.to_string_lossy().to_uppercase();
@@ -0,0 +1,138 @@
+//! 防火墙规则残留
+//!
+//! 安装程序常为自身添加入站/出站规则，卸载后规则仍在，端口一直开放。
+//! `netsh advfirewall` 和 `HNetCfg.FwPolicy2` 读取的都是 `FirewallRules` 注册表项，
+//! 这里直接枚举该项：值名为规则 ID，值内容形如 `v2.30|Action=Allow|App=...|Name=...|`。
+//! 规则的程序路径位于安装目录下时为高置信度，按程序名匹配的置信度依次降低。
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+
+/// 防火墙规则（HKLM），值名为规则 ID
+pub const FIREWALL_RULES_KEY: &str =
+    r"SYSTEM\CurrentControlSet\Services\SharedAccess\Parameters\FirewallPolicy\FirewallRules";
+
+/// 扫描属于程序的防火墙规则，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_firewall_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+    let rules = match RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(FIREWALL_RULES_KEY) {
+        Ok(rules) => rules,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(UninstallerError::Registry(format!(
+                "读取防火墙规则失败: {}",
+                e
+            )))
+        }
+    };
+
+    for (rule_id, value) in rules.enum_values().flatten() {
+        let rule = value.to_string();
+        let app = rule_field(&rule, "App");
+        let name = rule_field(&rule, "Name").unwrap_or(&rule_id);
+        let Some((confidence, reason)) = match_rule(app, name, patterns, install_root) else {
+            continue;
+        };
+
+        let mut trace = Trace::new(
+            program_name.clone(),
+            TraceType::FirewallRule,
+            format!(r"HKLM\{}\{}", FIREWALL_RULES_KEY, rule_id),
+        )
+        .with_description(match app {
+            Some(app) => format!("防火墙规则: {} ({})", name, app),
+            None => format!("防火墙规则: {}", name),
+        })
+        .with_confidence(confidence);
+        trace.set_match_reason(reason.to_string());
+        emit(trace);
+    }
+
+    Ok(())
+}
+
+/// 判断规则是否属于程序，返回置信度和命中原因
+fn match_rule(
+    app: Option<&str>,
+    name: &str,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let app = app.map(|app| utils::normalize_path(&expand_windows_env_vars(app)).to_lowercase());
+    match app.as_deref() {
+        // Windows 自带程序的规则
+        Some(app) if utils::is_system_critical_path(app) => None,
+        Some(app) if install_root.is_some_and(|root| app.starts_with(root)) => {
+            Some((Confidence::High, "防火墙规则的程序路径位于程序安装目录"))
+        }
+        Some(app) if patterns.iter().any(|p| matching::name_matches(app, p)) => {
+            Some((Confidence::Medium, "防火墙规则的程序路径与程序名匹配"))
+        }
+        _ if patterns.iter().any(|p| matching::name_matches(name, p)) => {
+            Some((Confidence::Low, "防火墙规则名称与程序名匹配"))
+        }
+        _ => None,
+    }
+}
+
+/// 防火墙规则痕迹的规则 ID
+pub fn firewall_rule_id(path: &str) -> Option<&str> {
+    let prefix = format!(r"HKLM\{}\", FIREWALL_RULES_KEY);
+    path.get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(&prefix))
+        .map(|_| &path[prefix.len()..])
+        .filter(|rule_id| !rule_id.is_empty() && !rule_id.contains('\\'))
+}
+
+/// 防火墙规则字符串形如 `v2.30|Action=Allow|Dir=In|App=C:\a.exe|Name=Foo|`
+fn rule_field<'a>(rule: &'a str, field: &str) -> Option<&'a str> {
+    rule.split('|').find_map(|part| {
+        part.split_once('=')
+            .filter(|(key, _)| key.eq_ignore_ascii_case(field))
+            .map(|(_, value)| value)
+            .filter(|value| !value.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_rules_by_install_location() {
+        let rule = r"v2.30|Action=Allow|Active=TRUE|Dir=In|App=D:\Apps\Acme\bin\agent.exe|Name=Agent Service|";
+        let app = rule_field(rule, "App");
+        assert_eq!(app, Some(r"D:\Apps\Acme\bin\agent.exe"));
+        assert_eq!(rule_field(rule, "Name"), Some("Agent Service"));
+
+        let patterns = vec!["acme".to_string()];
+        let confidence = |root| match_rule(app, "Agent Service", &patterns, root).map(|m| m.0);
+        assert_eq!(confidence(Some(r"d:\apps\acme\")), Some(Confidence::High));
+        assert_eq!(
+            confidence(Some(r"d:\apps\other\")),
+            Some(Confidence::Medium)
+        );
+        assert_eq!(
+            match_rule(None, "Acme Sync", &patterns, None).map(|m| m.0),
+            Some(Confidence::Low)
+        );
+
+        let path = format!(r"HKLM\{}\{{1A2B}}", FIREWALL_RULES_KEY);
+        assert_eq!(firewall_rule_id(&path), Some("{1A2B}"));
+        assert_eq!(firewall_rule_id(r"HKLM\SOFTWARE\Acme"), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! 残留的安装包
+//!
+//! 下载目录和临时目录中常堆着程序的安装包（每次更新一个），动辄几个 GB。
+//! 可执行文件按版本资源的 ProductName 判断归属，MSI 等其他安装包按文件名判断；
+//! 程序已卸载或已安装完成后这些安装包都可以删除，报告为文件痕迹。
+
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::aliases::read_version_string;
+
+/// 安装包扩展名
+const INSTALLER_EXTENSIONS: &[&str] = &["exe", "msi", "msix", "msixbundle", "appx"];
+
+/// 文件名中表明是安装程序的词
+const INSTALLER_HINTS: &[&str] = &["setup", "install", "installer"];
+
+/// 下载目录和临时目录的遍历深度
+const MAX_DEPTH: usize = 2;
+
+/// 每个目录最多检查的条目数
+const MAX_ENTRIES: usize = 50_000;
+
+/// 扫描下载目录和临时目录中属于程序的安装包，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`installed_version` 为程序记录中的版本
+pub fn scan_installer_traces(
+    patterns: &[String],
+    installed_version: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+
+    for (root, label) in installer_roots() {
+        for entry in WalkDir::new(&root)
+            .max_depth(MAX_DEPTH)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .take(MAX_ENTRIES)
+            .filter(|entry| entry.file_type().is_file())
+        {
+            let path = entry.path();
+            if !is_installer_file(path) {
+                continue;
+            }
+            let is_exe = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"));
+            let product = is_exe
+                .then(|| read_version_string(path, "ProductName"))
+                .flatten();
+            let file_stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let Some((confidence, reason)) =
+                match_installer(&file_stem, product.as_deref(), patterns)
+            else {
+                continue;
+            };
+
+            let version = is_exe
+                .then(|| read_version_string(path, "ProductVersion"))
+                .flatten();
+            let size = entry.metadata().ok().map(|metadata| metadata.len());
+            let mut trace = Trace::new(
+                program_name.clone(),
+                TraceType::File,
+                path.to_string_lossy().to_string(),
+            )
+            .with_description(describe(
+                label,
+                product.as_deref(),
+                version.as_deref(),
+                installed_version,
+                size,
+            ))
+            .with_confidence(confidence);
+            trace.size = size;
+            trace.set_match_reason(reason.to_string());
+            emit(trace);
+        }
+    }
+
+    Ok(())
+}
+
+/// 下载目录和临时目录
+fn installer_roots() -> Vec<(PathBuf, &'static str)> {
+    let mut roots = Vec::new();
+    if let Some(downloads) = dirs::download_dir().filter(|dir| dir.is_dir()) {
+        roots.push((downloads, "下载目录"));
+    }
+    let temp = std::env::temp_dir();
+    if temp.is_dir() {
+        roots.push((temp, "临时目录"));
+    }
+    roots
+}
+
+fn is_installer_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        INSTALLER_EXTENSIONS
+            .iter()
+            .any(|installer| ext.eq_ignore_ascii_case(installer))
+    })
+}
+
+/// 判断安装包是否属于程序，返回置信度和命中原因
+///
+/// `file_stem` 为小写的文件名（不含扩展名），`product` 为版本资源中的产品名
+fn match_installer(
+    file_stem: &str,
+    product: Option<&str>,
+    patterns: &[String],
+) -> Option<(Confidence, &'static str)> {
+    let matches = |text: &str| {
+        patterns
+            .iter()
+            .any(|pattern| matching::name_matches(text, pattern))
+    };
+
+    if product.is_some_and(|product| matches(&product.to_lowercase())) {
+        return Some((Confidence::Medium, "安装包的产品名与程序匹配"));
+    }
+    if !matches(file_stem) {
+        return None;
+    }
+    // 与程序同名、又不像安装程序的可执行文件可能是便携版本身
+    if INSTALLER_HINTS.iter().any(|hint| file_stem.contains(hint)) {
+        Some((Confidence::Medium, "安装包文件名与程序名匹配"))
+    } else {
+        Some((Confidence::Low, "下载的文件名与程序名匹配"))
+    }
+}
+
+fn describe(
+    label: &str,
+    product: Option<&str>,
+    version: Option<&str>,
+    installed_version: Option<&str>,
+    size: Option<u64>,
+) -> String {
+    let mut description = format!("{}中的安装包", label);
+    if let Some(product) = product {
+        description.push_str(&format!(": {}", product));
+    }
+    match (version, installed_version) {
+        (Some(version), Some(installed)) if version.trim() == installed.trim() => {
+            description.push_str(&format!(" {} (与已安装版本相同)", version))
+        }
+        (Some(version), _) => description.push_str(&format!(" {}", version)),
+        _ => {}
+    }
+    if let Some(size) = size {
+        description.push_str(&format!("，{}", utils::format_size(size)));
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_installers_by_product_or_file_name() {
+        let patterns = vec!["acme".to_string()];
+
+        assert_eq!(
+            match_installer("download (3)", Some("Acme Editor"), &patterns).map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+        assert_eq!(
+            match_installer("acme-setup-2.1", None, &patterns).map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+        assert_eq!(
+            match_installer("acme", None, &patterns).map(|m| m.0),
+            Some(Confidence::Low)
+        );
+        assert!(match_installer("other-setup", Some("Other Tool"), &patterns).is_none());
+        assert!(is_installer_file(Path::new(r"C:\Downloads\Acme.MSI")));
+        assert!(!is_installer_file(Path::new(r"C:\Downloads\acme.zip")));
+    }
+}
@@ -0,0 +1,293 @@
+//! 全系统残留搜索
+//!
+//! 对比安装历史、卸载前保存的快照与当前已安装程序，找出在 rust-yu 之外被卸载的程序，
+//! 逐个扫描其残留并汇总成一份报告。卸载程序已不存在的失效条目也按已卸载处理。
+//! 安装本程序之前卸载的软件由 Windows 自己的卸载记录补充（见 [`super::removal_history`]）。
+
+use super::matching;
+use super::models::{Trace, TraceType};
+use super::options::ScanOptions;
+use super::removal_history::{self, RemovalRecord};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::{self, models::InstallSource, models::InstalledProgram, storage};
+use crate::modules::uninstaller::arp;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// 已卸载程序及其残留
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovedProgramLeftovers {
+    pub program: InstalledProgram,
+    /// 最后一次在已安装列表中出现的时间
+    pub last_seen_at: Option<String>,
+    pub traces: Vec<Trace>,
+    pub total_size: u64,
+    /// 程序仍留在"程序和功能"列表中，但卸载程序已不存在
+    #[serde(default)]
+    pub broken_entry: bool,
+    /// 来自 Windows 卸载记录或快照时的原始记录
+    #[serde(default)]
+    pub removal_record: Option<RemovalRecord>,
+}
+
+/// 在当前已安装列表中找不到的程序
+#[derive(Debug, Clone)]
+pub struct RemovedProgram {
+    pub program: InstalledProgram,
+    pub last_seen_at: Option<String>,
+    pub removal_record: Option<RemovalRecord>,
+}
+
+impl RemovedProgram {
+    fn new(program: InstalledProgram, last_seen_at: Option<String>) -> Self {
+        Self {
+            program,
+            last_seen_at,
+            removal_record: None,
+        }
+    }
+}
+
+/// 汇总报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeftoverReport {
+    pub generated_at: String,
+    pub programs: Vec<RemovedProgramLeftovers>,
+    pub total_traces: usize,
+    pub total_size: u64,
+}
+
+/// 找出曾经安装、但当前已不在已安装列表中的程序
+///
+/// 依次来自安装历史、卸载前保存的快照和导入的卸载记录，同名程序只保留最先找到的一条
+pub fn find_removed_programs(
+    installed: &[InstalledProgram],
+    imported: &[RemovalRecord],
+) -> Result<Vec<RemovedProgram>, UninstallerError> {
+    let installed_names: HashSet<String> = installed
+        .iter()
+        .map(|program| program.name.to_lowercase())
+        .collect();
+
+    let mut removed = Vec::new();
+    let mut seen = HashSet::new();
+
+    // 只比对注册表来源：当前列表只枚举注册表，MSI/商店来源的历史无法可靠判断
+    for entry in storage::get_program_history()? {
+        if entry.program.install_source != InstallSource::Registry {
+            continue;
+        }
+        let name_lower = entry.program.name.to_lowercase();
+        if !installed_names.contains(&name_lower) && seen.insert(name_lower) {
+            removed.push(RemovedProgram::new(entry.program, Some(entry.last_seen_at)));
+        }
+    }
+
+    for program in storage::get_saved_programs()? {
+        let name_lower = program.name.to_lowercase();
+        if !installed_names.contains(&name_lower) && seen.insert(name_lower) {
+            removed.push(RemovedProgram::new(program, None));
+        }
+    }
+
+    for record in imported {
+        let name_lower = record.name.to_lowercase();
+        if !installed_names.contains(&name_lower) && seen.insert(name_lower) {
+            removed.push(RemovedProgram {
+                program: record.to_program(),
+                last_seen_at: None,
+                removal_record: Some(record.clone()),
+            });
+        }
+    }
+
+    Ok(removed)
+}
+
+/// 扫描所有已卸载程序的残留
+///
+/// `snapshots` 为额外导入的程序清单快照。属于仍安装程序的痕迹会被排除（见 [`InstalledFootprint`]），
+/// 避免同名前缀误伤现有程序
+pub async fn hunt_leftovers(snapshots: &[PathBuf]) -> Result<LeftoverReport, UninstallerError> {
+    let (broken, installed): (Vec<_>, Vec<_>) = lister::registry::list_registry_programs()?
+        .into_iter()
+        .partition(arp::is_broken_entry);
+    let snapshots = snapshots.to_vec();
+    let imported =
+        tokio::task::spawn_blocking(move || removal_history::collect_removal_records(&snapshots))
+            .await
+            .map_err(|e| UninstallerError::Other(format!("读取卸载记录失败: {}", e)))?;
+    let mut removed = find_removed_programs(&installed, &imported)?;
+    let broken_names: HashSet<String> = broken.iter().map(|p| p.name.to_lowercase()).collect();
+    removed.retain(|removed| !broken_names.contains(&removed.program.name.to_lowercase()));
+    removed.extend(
+        broken
+            .into_iter()
+            .map(|program| RemovedProgram::new(program, None)),
+    );
+    let footprint = InstalledFootprint::new(&installed);
+
+    let mut programs = Vec::new();
+    let mut seen_paths = HashSet::new();
+
+    for RemovedProgram {
+        program,
+        last_seen_at,
+        removal_record,
+    } in removed
+    {
+        tracing::info!("搜索已卸载程序的残留: {}", program.name);
+
+        let traces: Vec<Trace> =
+            super::scan_program_traces(&program, None, None, &ScanOptions::default(), None)
+                .await?
+                .traces
+                .into_iter()
+                .filter(|trace| !footprint.owns(trace, &program.name))
+                // 名称相近的程序可能命中同一路径，只归入第一个
+                .filter(|trace| seen_paths.insert(trace.path.to_lowercase()))
+                .collect();
+
+        // 失效条目本身就是需要清理的残留
+        let broken_entry = arp::is_broken_entry(&program);
+        if traces.is_empty() && !broken_entry {
+            continue;
+        }
+
+        let total_size = traces.iter().filter_map(|trace| trace.size).sum();
+        programs.push(RemovedProgramLeftovers {
+            program,
+            last_seen_at,
+            traces,
+            total_size,
+            broken_entry,
+            removal_record,
+        });
+    }
+
+    Ok(LeftoverReport {
+        generated_at: Utc::now().to_rfc3339(),
+        total_traces: programs.iter().map(|p| p.traces.len()).sum(),
+        total_size: programs.iter().map(|p| p.total_size).sum(),
+        programs,
+    })
+}
+
+/// 仍安装程序的目录、卸载注册表项和名称
+struct InstalledFootprint {
+    locations: Vec<String>,
+    registry_keys: Vec<String>,
+    names: Vec<String>,
+}
+
+impl InstalledFootprint {
+    fn new(installed: &[InstalledProgram]) -> Self {
+        let normalize = |value: &str| value.trim_end_matches('\\').to_lowercase();
+        Self {
+            locations: installed
+                .iter()
+                .filter_map(|program| program.install_location.as_deref())
+                .map(normalize)
+                .filter(|location| !location.is_empty())
+                .collect(),
+            registry_keys: installed
+                .iter()
+                .filter_map(|program| program.registry_key.as_deref())
+                .map(|key| normalize(&utils::short_registry_path(key)))
+                .collect(),
+            names: installed
+                .iter()
+                .map(|program| program.name.clone())
+                .collect(),
+        }
+    }
+
+    /// 已卸载程序 `removed_name` 的痕迹是否可能属于仍安装的程序
+    fn owns(&self, trace: &Trace, removed_name: &str) -> bool {
+        match trace.trace_type {
+            TraceType::RegistryKey | TraceType::RegistryValue => {
+                self.owns_registry(&trace.path, removed_name)
+            }
+            _ => belongs_to_installed(&trace.path, &self.locations),
+        }
+    }
+
+    /// 注册表痕迹没有安装目录可比：与仍安装程序的卸载项重叠、某一级项名与其名称匹配，
+    /// 或者仍安装着同系列的程序（已卸载 "Foo"、仍安装 "Foo Pro"，两者通常共用厂商项）时都视为属于它
+    fn owns_registry(&self, path: &str, removed_name: &str) -> bool {
+        let key = path.split_once('=').map_or(path, |(key, _)| key);
+        let key = utils::short_registry_path(key)
+            .trim_end_matches('\\')
+            .to_lowercase();
+        if belongs_to_installed(&key, &self.registry_keys) {
+            return true;
+        }
+
+        self.names.iter().any(|name| {
+            matching::name_matches(name, removed_name)
+                || key
+                    .split('\\')
+                    .skip(1)
+                    .any(|segment| matching::name_matches(segment, name))
+        })
+    }
+}
+
+/// 路径是否位于仍安装程序的目录内，或者包含仍安装程序的目录
+fn belongs_to_installed(path: &str, installed_locations: &[String]) -> bool {
+    let path_lower = path.trim_end_matches('\\').to_lowercase();
+    installed_locations.iter().any(|location| {
+        utils::is_same_or_child(&path_lower, location)
+            || utils::is_same_or_child(location, &path_lower)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn belongs_to_installed_checks_both_directions() {
+        let installed = vec![r"c:\program files\vendor\app".to_string()];
+
+        assert!(belongs_to_installed(
+            r"C:\Program Files\Vendor\App\data",
+            &installed
+        ));
+        assert!(belongs_to_installed(r"C:\Program Files\Vendor", &installed));
+        assert!(!belongs_to_installed(
+            r"C:\Program Files\Vendor\AppOld",
+            &installed
+        ));
+    }
+
+    #[test]
+    fn registry_traces_are_matched_against_installed_programs() {
+        let mut foo_pro = InstalledProgram::new("Foo Pro".to_string(), InstallSource::Registry);
+        foo_pro.registry_key =
+            Some(r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\FooPro".to_string());
+        let bar = InstalledProgram::new("Bar Studio".to_string(), InstallSource::Registry);
+        let footprint = InstalledFootprint::new(&[foo_pro, bar]);
+        let registry =
+            |path: &str| Trace::new("Foo".to_string(), TraceType::RegistryKey, path.to_string());
+
+        // 仍安装同系列的 Foo Pro，已卸载 Foo 的注册表项一律保留
+        assert!(footprint.owns(&registry(r"HKCU\Software\Foo"), "Foo"));
+        // 卸载项本身和名称匹配仍安装程序的项
+        assert!(footprint.owns(
+            &registry(
+                r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\FooPro"
+            ),
+            "Baz"
+        ));
+        assert!(footprint.owns(&registry(r"HKCU\Software\Bar Studio\Baz"), "Baz"));
+        // 与仍安装程序无关的项照常报告
+        assert!(!footprint.owns(&registry(r"HKCU\Software\Baz"), "Baz"));
+        // 文件痕迹仍只按安装目录判断
+        let file = Trace::new("Foo".to_string(), TraceType::File, r"D:\Foo".to_string());
+        assert!(!footprint.owns(&file, "Foo"));
+    }
+}
@@ -0,0 +1,119 @@
+//! 名称匹配策略
+//!
+//! 直接用子串匹配时，"Git"、"R" 这类短名称会命中大量无关路径。
+//! 这里按单词切分后比较：短名称必须作为完整单词出现，足够长的名称才允许子串命中，
+//! setup、app 等通用词不参与匹配。
+
+/// 不作为匹配依据的通用词
+const STOP_WORDS: &[&str] = &[
+    "app",
+    "apps",
+    "application",
+    "setup",
+    "install",
+    "installer",
+    "soft",
+    "software",
+    "update",
+    "updater",
+    "tool",
+    "tools",
+    "the",
+    "for",
+    "and",
+    "x64",
+    "x86",
+    "bit",
+];
+
+/// 低于该长度的名称必须与候选名称整体一致（忽略版本号）
+const MIN_TOKEN_PATTERN_LEN: usize = 3;
+
+/// 达到该长度的名称才允许子串命中
+const MIN_SUBSTRING_PATTERN_LEN: usize = 5;
+
+/// 按非字母数字字符切分为小写单词
+///
+/// 不按大小写边界切分：GitHub 拆成 git + hub 会让 "Git" 重新命中它
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 查询路径索引用的关键词：名称中最长的单词
+///
+/// 任何能通过 [`name_matches`] 的名称都包含该单词，索引结果再逐个用 `name_matches` 过滤
+pub fn index_key(pattern: &str) -> String {
+    tokenize(pattern)
+        .into_iter()
+        .max_by_key(|token| token.chars().count())
+        .unwrap_or_else(|| pattern.to_lowercase())
+}
+
+/// 去掉通用词后的单词
+pub fn significant_tokens(text: &str) -> Vec<String> {
+    tokenize(text)
+        .into_iter()
+        .filter(|token| !STOP_WORDS.contains(&token.as_str()))
+        .collect()
+}
+
+/// 候选名称（文件名、注册表项名或路径）是否与程序名匹配
+pub fn name_matches(candidate: &str, pattern: &str) -> bool {
+    let pattern_tokens = significant_tokens(pattern);
+    if pattern_tokens.is_empty() {
+        // 程序名全是通用词（如 "Setup"）时只接受完全一致
+        let pattern = tokenize(pattern);
+        return !pattern.is_empty() && tokenize(candidate) == pattern;
+    }
+
+    let pattern_compact: String = pattern_tokens.concat();
+    let pattern_len = pattern_compact.chars().count();
+    let candidate_tokens = tokenize(candidate);
+
+    if pattern_len < MIN_TOKEN_PATTERN_LEN {
+        let words: Vec<&String> = candidate_tokens
+            .iter()
+            .filter(|token| !token.chars().all(|c| c.is_ascii_digit()))
+            .collect();
+        return words.len() == pattern_tokens.len()
+            && words.iter().zip(&pattern_tokens).all(|(a, b)| *a == b);
+    }
+
+    if candidate_tokens
+        .windows(pattern_tokens.len())
+        .any(|window| window == pattern_tokens.as_slice())
+    {
+        return true;
+    }
+
+    // 分隔方式不同的情况（如 VisualStudioCode 与 Visual Studio Code）由子串兜底
+    pattern_len >= MIN_SUBSTRING_PATTERN_LEN && candidate_tokens.concat().contains(&pattern_compact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_names_require_whole_words() {
+        assert!(name_matches("Git", "git"));
+        assert!(name_matches(r"C:\Program Files\Git\cmd", "git"));
+        assert!(name_matches("git-lfs", "Git"));
+        assert!(!name_matches("GitHub Desktop", "git"));
+        assert!(!name_matches("digital", "git"));
+
+        assert!(name_matches("R", "R"));
+        assert!(name_matches("R-4.3.1", "R"));
+        assert!(!name_matches("Rider", "R"));
+        assert!(!name_matches("R Tools", "R"));
+
+        assert!(name_matches("VisualStudioCode", "Visual Studio Code"));
+        assert!(name_matches("JetBrains Toolbox Cache", "toolbox"));
+        assert!(name_matches("mynotepadplusplus", "Notepad"));
+        assert!(!name_matches("Some Setup Files", "Foo Setup"));
+        assert!(!name_matches("setup_logs", "Setup"));
+    }
+}
@@ -0,0 +1,207 @@
+//! 按保存的程序记录扫描痕迹
+//!
+//! 程序卸载后只剩名称时，名称扫描容易漏掉或误判。卸载前保存的安装目录、
+//! 发布者等信息可以直接定位残留：原安装目录，以及 `<发布者>\<程序名>` 形式的
+//! 注册表项和数据目录。Squirrel 安装的程序通常不记录安装目录，按 `Update.exe`
+//! 所在目录定位，ClickOnce 应用按卸载命令中的标识定位 `Apps\2.0` 下的部署目录。
+
+use super::models::{Confidence, Trace, TraceType};
+use super::roots;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::models::{InstalledProgram, InstallerKind};
+use crate::modules::uninstaller::{clickonce, squirrel};
+use std::path::{Path, PathBuf};
+use winreg::RegKey;
+
+/// 发布者名称中不作为目录名使用的公司后缀
+const PUBLISHER_SUFFIXES: &[&str] = &[
+    "inc",
+    "inc.",
+    "llc",
+    "ltd",
+    "ltd.",
+    "corp",
+    "corp.",
+    "corporation",
+    "co.",
+    "gmbh",
+    "limited",
+];
+
+/// 按程序记录中的安装目录和发布者扫描痕迹，每发现一项即交给 `emit`
+pub fn scan_metadata_traces(
+    program: &InstalledProgram,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    // 公共目录（如 Program Files 本身）不能整体作为残留
+    if let (Some(location), Some(_)) = (
+        program.install_location.as_deref(),
+        roots::install_root(program),
+    ) {
+        let location = location.trim().trim_matches('"');
+        let mut trace = Trace::new(program.name.clone(), TraceType::File, location.to_string())
+            .with_description("卸载前记录的安装目录".to_string())
+            .with_confidence(Confidence::High);
+        trace.exists = Path::new(location).exists();
+        trace.set_match_reason("程序记录中的安装目录".to_string());
+        emit(trace);
+    }
+
+    match program.installer_kind {
+        InstallerKind::Squirrel => scan_squirrel_traces(program, emit),
+        InstallerKind::ClickOnce => scan_clickonce_traces(program, emit),
+        _ => {}
+    }
+
+    let publishers = publisher_dir_names(program.publisher.as_deref());
+    if publishers.is_empty() {
+        return Ok(());
+    }
+
+    for dir in data_roots() {
+        for publisher in &publishers {
+            let path = dir.join(publisher).join(&program.name);
+            if !path.exists() {
+                continue;
+            }
+            let mut trace = Trace::new(
+                program.name.clone(),
+                TraceType::AppData,
+                path.to_string_lossy().to_string(),
+            )
+            .with_description("发布者目录下的程序数据".to_string())
+            .with_confidence(Confidence::High);
+            trace.set_match_reason(format!("位于发布者目录 {}", publisher));
+            emit(trace);
+        }
+    }
+
+    for root in ["HKLM", "HKCU"] {
+        for software in [r"SOFTWARE", r"SOFTWARE\WOW6432Node"] {
+            for publisher in &publishers {
+                let key = format!(r"{}\{}\{}\{}", root, software, publisher, program.name);
+                let exists = utils::parse_registry_path(&key)
+                    .map(|(hkey, subpath)| RegKey::predef(hkey).open_subkey(subpath).is_ok())
+                    .unwrap_or(false);
+                if !exists {
+                    continue;
+                }
+                let mut trace = Trace::new(program.name.clone(), TraceType::RegistryKey, key)
+                    .with_description("发布者注册表项下的程序配置".to_string())
+                    .with_confidence(Confidence::High);
+                trace.set_match_reason(format!("位于发布者注册表项 {}", publisher));
+                emit(trace);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Squirrel 应用目录（含 `Update.exe` 和全部版本目录）以及共用的 SquirrelTemp
+fn scan_squirrel_traces(program: &InstalledProgram, emit: &mut dyn FnMut(Trace)) {
+    if let Some(root) = program
+        .uninstall_string
+        .as_deref()
+        .and_then(squirrel::app_root)
+    {
+        let mut trace = Trace::new(
+            program.name.clone(),
+            TraceType::AppData,
+            root.to_string_lossy().to_string(),
+        )
+        .with_description("Squirrel 应用目录（含所有版本）".to_string())
+        .with_confidence(Confidence::High);
+        trace.exists = root.exists();
+        trace.set_match_reason("Update.exe 所在目录".to_string());
+        emit(trace);
+    }
+
+    if let Some(temp) = squirrel::temp_dir().filter(|dir| dir.exists()) {
+        // 其他 Squirrel 应用也会使用该目录，只作为中等置信度的候选
+        let mut trace = Trace::new(
+            program.name.clone(),
+            TraceType::AppData,
+            temp.to_string_lossy().to_string(),
+        )
+        .with_description("Squirrel 安装临时目录".to_string())
+        .with_confidence(Confidence::Medium);
+        trace.set_match_reason("Squirrel 安装程序共用的临时目录".to_string());
+        emit(trace);
+    }
+}
+
+/// ClickOnce 部署目录与清单文件
+fn scan_clickonce_traces(program: &InstalledProgram, emit: &mut dyn FnMut(Trace)) {
+    let Some(identity) = program
+        .uninstall_string
+        .as_deref()
+        .and_then(clickonce::parse_identity)
+    else {
+        return;
+    };
+
+    for path in clickonce::find_deployment_entries(&identity) {
+        let mut trace = Trace::new(
+            program.name.clone(),
+            TraceType::AppData,
+            path.to_string_lossy().to_string(),
+        )
+        .with_description("ClickOnce 部署目录".to_string())
+        .with_confidence(Confidence::High);
+        trace.set_match_reason(format!("部署目录名匹配 {}", identity.name));
+        emit(trace);
+    }
+}
+
+/// 发布者可能使用的目录名：完整名称，以及去掉公司后缀后的名称
+pub(crate) fn publisher_dir_names(publisher: Option<&str>) -> Vec<String> {
+    let Some(publisher) = publisher.map(str::trim).filter(|p| !p.is_empty()) else {
+        return Vec::new();
+    };
+
+    let mut names = vec![publisher.to_string()];
+    let words: Vec<&str> = publisher
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .collect();
+    let end = words
+        .iter()
+        .rposition(|word| !PUBLISHER_SUFFIXES.contains(&word.to_lowercase().as_str()))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let short = words[..end].join(" ");
+    if short.len() >= 3 && !names.contains(&short) {
+        names.push(short);
+    }
+
+    names
+}
+
+fn data_roots() -> Vec<PathBuf> {
+    ["APPDATA", "LOCALAPPDATA", "ProgramData"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publisher_suffixes_are_stripped() {
+        assert_eq!(
+            publisher_dir_names(Some("Mozilla Corporation")),
+            vec!["Mozilla Corporation", "Mozilla"]
+        );
+        assert_eq!(
+            publisher_dir_names(Some("Foo Software, Inc.")),
+            vec!["Foo Software, Inc.", "Foo Software"]
+        );
+        assert_eq!(publisher_dir_names(Some("JetBrains")), vec!["JetBrains"]);
+        assert!(publisher_dir_names(Some("  ")).is_empty());
+    }
+}
@@ -1,148 +1,933 @@
+pub mod activex;
 pub mod appdata;
+pub mod associations;
+pub mod calibration;
+pub mod com;
+pub mod context_menu;
+pub mod credentials;
+pub mod defender;
+pub mod drivers;
+pub mod environment;
+pub mod event_log;
+pub mod export;
 pub mod filesystem;
+pub mod firewall;
+pub mod installers;
+pub mod leftovers;
+pub mod matching;
+pub mod metadata;
 pub mod models;
+pub mod network;
+pub mod options;
+pub mod ownership;
+pub mod path_index;
+pub mod preview;
+pub mod profiles;
 pub mod registry;
+pub mod registry_values;
+pub mod removal_history;
+pub mod roots;
+pub mod scoring;
 pub mod shortcuts;
+pub mod signals;
+pub mod startup;
+pub mod usage_history;
+pub mod user_content;
+pub mod user_data;
+pub mod wmi;
 
+use crate::modules::cleaner::safety;
 use crate::modules::common::error::UninstallerError;
-use models::{Trace, TraceType};
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use crate::modules::common::known_folders;
+use crate::modules::common::priority;
+use crate::modules::common::profiling::StageTiming;
+use crate::modules::common::progress;
+use crate::modules::common::utils;
+use crate::modules::lister::aliases as lister_aliases;
+use crate::modules::lister::ignore_rules::IgnoreRules;
+use crate::modules::lister::kept_traces;
+use crate::modules::lister::models::InstalledProgram;
+use crate::modules::watcher::install_log;
+use models::{Confidence, ScanProgress, ScanSummary, ScannerStats, Trace, TraceType};
+use options::ScanOptions;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
-/// 扫描所有类型的痕迹
-pub async fn scan_all_traces(
-    program_name: &str,
-    trace_types: Option<Vec<TraceType>>,
-) -> Result<Vec<Trace>, UninstallerError> {
-    let types = trace_types.unwrap_or_else(|| {
-        vec![
-            TraceType::RegistryKey,
-            TraceType::File,
-            TraceType::AppData,
-            TraceType::Shortcut,
-        ]
-    });
+/// 扫描结果通道容量
+///
+/// 消费者跟不上时扫描线程会阻塞在发送处，深度扫描的内存占用因此保持平稳
+const TRACE_CHANNEL_CAPACITY: usize = 256;
 
-    let _all_traces: Vec<Trace> = Vec::new();
-    let program_name = program_name.to_string();
+/// 发现候选项时报告进度的最小间隔，扫描器结束时总是报告
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_millis(100);
 
-    // 使用 Arc 和 Mutex 来收集结果
-    let traces = Arc::new(Mutex::new(Vec::<Trace>::new()));
+/// 扫描进度回调，在扫描线程中调用
+pub type ProgressCallback = Arc<dyn Fn(&ScanProgress) + Send + Sync>;
 
-    let mut handles = vec![];
+/// 单个扫描器的入口签名
+type ScanFn = fn(&str, &mut dyn FnMut(Trace)) -> Result<(), UninstallerError>;
 
-    // 并行扫描不同类型
-    if types.contains(&TraceType::RegistryKey) {
-        let name = program_name.clone();
-        let t = traces.clone();
-        handles.push(tokio::spawn(async move {
-            match registry::scan_registry_traces(&name) {
-                Ok(mut traces) => {
-                    let mut guard = t.lock().await;
-                    guard.append(&mut traces);
-                }
-                Err(e) => tracing::warn!("注册表扫描失败: {}", e),
-            }
-        }));
+/// 扫描器的统计槽位
+struct ScannerSlot {
+    stats: ScannerStats,
+    started_at: Instant,
+    finished: bool,
+}
+
+/// 进度回调及上次报告候选项的时间
+#[derive(Clone)]
+struct ProgressReporter {
+    callback: ProgressCallback,
+    last_item_at: Arc<Mutex<Option<Instant>>>,
+}
+
+/// 各扫描线程共享的运行统计，结果 channel 关闭后即为最终结果
+#[derive(Clone, Default)]
+pub struct ScanStatsHandle {
+    slots: Arc<Mutex<Vec<ScannerSlot>>>,
+    progress: Option<ProgressReporter>,
+}
+
+impl ScanStatsHandle {
+    /// 扫描器发现候选项和结束时调用 `callback` 报告进度
+    pub fn with_progress(callback: Option<ProgressCallback>) -> Self {
+        ScanStatsHandle {
+            slots: Arc::default(),
+            progress: callback.map(|callback| ProgressReporter {
+                callback,
+                last_item_at: Arc::default(),
+            }),
+        }
     }
 
-    if types.contains(&TraceType::File) {
-        let name = program_name.clone();
-        let t = traces.clone();
-        handles.push(tokio::spawn(async move {
-            match filesystem::scan_filesystem_traces(&name) {
-                Ok(mut traces) => {
-                    let mut guard = t.lock().await;
-                    guard.append(&mut traces);
-                }
-                Err(e) => tracing::warn!("文件系统扫描失败: {}", e),
-            }
-        }));
-    }
-
-    if types.contains(&TraceType::AppData) {
-        let name = program_name.clone();
-        let t = traces.clone();
-        handles.push(tokio::spawn(async move {
-            match appdata::scan_appdata_traces(&name) {
-                Ok(mut traces) => {
-                    let mut guard = t.lock().await;
-                    guard.append(&mut traces);
-                }
-                Err(e) => tracing::warn!("AppData扫描失败: {}", e),
+    fn lock_slots(&self) -> std::sync::MutexGuard<'_, Vec<ScannerSlot>> {
+        self.slots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn register(&self, label: &str, stage: &str, pattern: &str) -> usize {
+        let mut slots = self.lock_slots();
+        slots.push(ScannerSlot {
+            stats: ScannerStats {
+                scanner: stage.to_string(),
+                label: label.to_string(),
+                pattern: pattern.to_string(),
+                ..ScannerStats::default()
+            },
+            started_at: Instant::now(),
+            finished: false,
+        });
+        slots.len() - 1
+    }
+
+    fn update(&self, index: usize, update: impl FnOnce(&mut ScannerStats)) {
+        let mut slots = self.lock_slots();
+        if let Some(slot) = slots.get_mut(index) {
+            update(&mut slot.stats);
+        }
+    }
+
+    /// 扫描器发现候选项，按最小间隔报告进度
+    fn found(&self, index: usize, path: &str) {
+        self.update(index, |scanner| scanner.examined += 1);
+
+        let Some(progress) = &self.progress else {
+            return;
+        };
+        {
+            let mut last_item_at = progress
+                .last_item_at
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if last_item_at.is_some_and(|at| at.elapsed() < PROGRESS_REPORT_INTERVAL) {
+                return;
             }
-        }));
+            *last_item_at = Some(Instant::now());
+        }
+        let event = progress_event(&self.lock_slots(), index, Some(path));
+        (progress.callback)(&event);
     }
 
-    if types.contains(&TraceType::Shortcut) {
-        let name = program_name.clone();
-        let t = traces.clone();
-        handles.push(tokio::spawn(async move {
-            match shortcuts::scan_shortcut_traces(&name) {
-                Ok(mut traces) => {
-                    let mut guard = t.lock().await;
-                    guard.append(&mut traces);
-                }
-                Err(e) => tracing::warn!("快捷方式扫描失败: {}", e),
+    fn finish(&self, index: usize, error: Option<String>) {
+        let event = {
+            let mut slots = self.lock_slots();
+            if let Some(slot) = slots.get_mut(index) {
+                slot.stats.duration_ms = slot.started_at.elapsed().as_secs_f64() * 1000.0;
+                slot.stats.error = error;
+                slot.finished = true;
             }
-        }));
+            progress_event(&slots, index, None)
+        };
+
+        // 按已结束的扫描器数报告进度
+        progress::advance(
+            "scan",
+            event.completed,
+            event.total,
+            Some(event.label.as_str()),
+        );
+        if let Some(progress) = &self.progress {
+            (progress.callback)(&event);
+        }
     }
 
-    // 等待所有任务完成
-    for handle in handles {
-        let _ = handle.await;
+    /// 将仍在运行的扫描器标记为超时
+    fn mark_unfinished_timed_out(&self) {
+        let mut slots = self.lock_slots();
+        for slot in slots.iter_mut().filter(|slot| !slot.finished) {
+            slot.stats.duration_ms = slot.started_at.elapsed().as_secs_f64() * 1000.0;
+            slot.stats.timed_out = true;
+        }
     }
 
-    // 获取所有结果
-    let mut result = traces.lock().await.clone();
+    /// 当前的统计结果
+    pub fn snapshot(&self) -> Vec<ScannerStats> {
+        self.lock_slots()
+            .iter()
+            .map(|slot| slot.stats.clone())
+            .collect()
+    }
+}
+
+/// 由统计槽位生成进度事件，`index` 为报告进度的扫描器
+fn progress_event(slots: &[ScannerSlot], index: usize, current_path: Option<&str>) -> ScanProgress {
+    let (scanner, label) = slots
+        .get(index)
+        .map(|slot| (slot.stats.scanner.clone(), slot.stats.label.clone()))
+        .unwrap_or_default();
+    ScanProgress {
+        scanner,
+        label,
+        current_path: current_path.map(str::to_string),
+        items_found: slots.iter().map(|slot| slot.stats.examined).sum(),
+        completed: slots.iter().filter(|slot| slot.finished).count(),
+        total: slots.len(),
+    }
+}
 
-    // 计算置信度
-    assign_confidence_scores(&program_name, &mut result);
+/// 默认扫描的痕迹类型
+fn default_trace_types() -> Vec<TraceType> {
+    vec![
+        TraceType::RegistryKey,
+        TraceType::RegistryValue,
+        TraceType::File,
+        TraceType::AppData,
+        TraceType::Shortcut,
+        TraceType::ActiveX,
+        TraceType::UsageHistory,
+        TraceType::NetworkSetting,
+        TraceType::FirewallRule,
+        TraceType::EnvironmentVariable,
+        TraceType::ContextMenu,
+        TraceType::Credential,
+        TraceType::EventLogSource,
+        TraceType::WmiProvider,
+        TraceType::DefenderExclusion,
+    ]
+}
+
+/// 扫描所有类型的痕迹，指定 `min_confidence` 时只返回达到该置信度的痕迹
+///
+/// 提供 `progress` 时，扫描器发现候选项和结束时报告进度
+#[allow(dead_code)]
+pub async fn scan_all_traces(
+    program_name: &str,
+    trace_types: Option<Vec<TraceType>>,
+    min_confidence: Option<Confidence>,
+    options: &ScanOptions,
+    progress: Option<ProgressCallback>,
+) -> Result<Vec<Trace>, UninstallerError> {
+    Ok(scan_all_traces_with_summary(
+        program_name,
+        &[],
+        trace_types,
+        min_confidence,
+        options,
+        progress,
+    )
+    .await?
+    .traces)
+}
+
+/// 扫描所有类型的痕迹，同时按别名（产品名、内部名等）匹配
+#[allow(dead_code)]
+pub async fn scan_all_traces_with_aliases(
+    program_name: &str,
+    aliases: &[String],
+    trace_types: Option<Vec<TraceType>>,
+) -> Result<Vec<Trace>, UninstallerError> {
+    Ok(scan_all_traces_with_summary(
+        program_name,
+        aliases,
+        trace_types,
+        None,
+        &ScanOptions::default(),
+        None,
+    )
+    .await?
+    .traces)
+}
+
+/// 扫描所有类型的痕迹，并记录总耗时与各扫描器耗时
+///
+/// 指定 `min_confidence` 时过滤掉置信度更低的痕迹，过滤数量记录在摘要中
+pub async fn scan_all_traces_with_summary(
+    program_name: &str,
+    aliases: &[String],
+    trace_types: Option<Vec<TraceType>>,
+    min_confidence: Option<Confidence>,
+    options: &ScanOptions,
+    progress: Option<ProgressCallback>,
+) -> Result<ScanSummary, UninstallerError> {
+    collect_scan(
+        program_name,
+        None,
+        aliases,
+        trace_types,
+        min_confidence,
+        options,
+        progress,
+    )
+    .await
+}
+
+/// 按程序记录扫描痕迹：除名称和别名外，还按保存的安装目录、发布者定位残留
+///
+/// 程序卸载后仍可使用卸载前保存的记录
+pub async fn scan_program_traces(
+    program: &InstalledProgram,
+    trace_types: Option<Vec<TraceType>>,
+    min_confidence: Option<Confidence>,
+    options: &ScanOptions,
+    progress: Option<ProgressCallback>,
+) -> Result<ScanSummary, UninstallerError> {
+    collect_scan(
+        &program.name,
+        Some(program),
+        &program.aliases,
+        trace_types,
+        min_confidence,
+        options,
+        progress,
+    )
+    .await
+}
+
+async fn collect_scan(
+    program_name: &str,
+    program: Option<&InstalledProgram>,
+    aliases: &[String],
+    trace_types: Option<Vec<TraceType>>,
+    min_confidence: Option<Confidence>,
+    options: &ScanOptions,
+    progress: Option<ProgressCallback>,
+) -> Result<ScanSummary, UninstallerError> {
+    let started_at = Instant::now();
+    let stats = ScanStatsHandle::with_progress(progress);
+    let mut receiver = start_scan(
+        program_name,
+        program,
+        aliases,
+        trace_types,
+        options,
+        stats.clone(),
+    );
+
+    let timeout = options.timeout();
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut result = Vec::new();
+    loop {
+        match tokio::time::timeout_at(deadline, receiver.recv()).await {
+            Ok(Some(trace)) => result.push(trace),
+            Ok(None) => break,
+            Err(_) => {
+                // 阻塞线程无法中断，放弃接收后其后续结果会被丢弃
+                stats.mark_unfinished_timed_out();
+                tracing::warn!("扫描超过 {:?}，部分扫描器结果不完整", timeout);
+                break;
+            }
+        }
+    }
 
     // 按置信度排序
     result.sort_by(|a, b| b.confidence.cmp(&a.confidence));
 
-    // 过滤已存在的痕迹
-    result.retain(|t| t.exists);
+    // channel 关闭时所有扫描线程都已结束并写入统计
+    let scanners = stats.snapshot();
+    let timings = scanners
+        .iter()
+        .map(|scanner| StageTiming {
+            stage: scanner.scanner.clone(),
+            duration_ms: scanner.duration_ms,
+        })
+        .collect();
+
+    let mut summary = ScanSummary {
+        program_name: program_name.to_string(),
+        traces: result,
+        duration_ms: started_at.elapsed().as_secs_f64() * 1000.0,
+        timings,
+        filtered_count: 0,
+        scanners,
+    };
+    if let Some(min_confidence) = min_confidence {
+        summary.retain_min_confidence(min_confidence);
+    }
+
+    Ok(summary)
+}
+
+/// 流式扫描所有类型的痕迹
+///
+/// 各扫描器在独立的阻塞线程中并行运行，发现的痕迹经过置信度评估、去重和存在性过滤后
+/// 立即推送到返回的 channel；全部扫描器结束后 channel 关闭。需要在 tokio 运行时内调用。
+#[allow(dead_code)]
+pub fn scan_traces_stream(
+    program_name: &str,
+    aliases: &[String],
+    trace_types: Option<Vec<TraceType>>,
+) -> mpsc::Receiver<Trace> {
+    start_scan(
+        program_name,
+        None,
+        aliases,
+        trace_types,
+        &ScanOptions::default(),
+        ScanStatsHandle::default(),
+    )
+}
+
+/// 按程序记录流式扫描痕迹，见 [`scan_program_traces`]
+#[allow(dead_code)]
+pub fn scan_program_traces_stream(
+    program: &InstalledProgram,
+    trace_types: Option<Vec<TraceType>>,
+) -> mpsc::Receiver<Trace> {
+    start_scan(
+        &program.name,
+        Some(program),
+        &program.aliases,
+        trace_types,
+        &ScanOptions::default(),
+        ScanStatsHandle::default(),
+    )
+}
 
-    Ok(result)
+/// 流式扫描并返回各扫描器的统计，channel 关闭后统计即为最终结果
+///
+/// 提供 `program` 时按程序记录扫描，见 [`scan_program_traces`]；提供 `progress` 时报告扫描进度
+pub fn scan_traces_stream_with_stats(
+    program_name: &str,
+    program: Option<&InstalledProgram>,
+    aliases: &[String],
+    trace_types: Option<Vec<TraceType>>,
+    options: &ScanOptions,
+    progress: Option<ProgressCallback>,
+) -> (mpsc::Receiver<Trace>, ScanStatsHandle) {
+    let stats = ScanStatsHandle::with_progress(progress);
+    let receiver = start_scan(
+        program_name,
+        program,
+        aliases,
+        trace_types,
+        options,
+        stats.clone(),
+    );
+    (receiver, stats)
 }
 
-/// 分配置信度分数
-fn assign_confidence_scores(program_name: &str, traces: &mut Vec<Trace>) {
-    let name_lower = program_name.to_lowercase();
+/// 启动各扫描器并返回结果 channel，每个扫描器的耗时、候选数和错误记录在 `stats` 中
+///
+/// 程序名和每个别名（含多语言名称对照表中的名称）各跑一遍名称扫描器，结果统一归到
+/// `program_name` 下；提供 `program` 时额外按其安装目录和发布者扫描。扫描线程按 `options`
+/// 中的深度和项数上限遍历
+fn start_scan(
+    program_name: &str,
+    program: Option<&InstalledProgram>,
+    aliases: &[String],
+    trace_types: Option<Vec<TraceType>>,
+    options: &ScanOptions,
+    stats: ScanStatsHandle,
+) -> mpsc::Receiver<Trace> {
+    let types = trace_types.unwrap_or_else(default_trace_types);
+    let include_low_confidence = options.include_low_confidence;
+    // 下面派生的扫描线程通过 `options::current` 沿用这些选项
+    let _options = options::enter(Arc::new(options.clone()));
 
-    for trace in traces.iter_mut() {
-        let path_lower = trace.path.to_lowercase();
+    let (raw_sender, mut raw_receiver) = mpsc::channel::<(usize, Trace)>(TRACE_CHANNEL_CAPACITY);
+    let (sender, receiver) = mpsc::channel::<Trace>(TRACE_CHANNEL_CAPACITY);
 
-        // 检查是否包含程序名
-        let name_match = path_lower.contains(&name_lower);
+    // 调用方传入的别名可能来自旧缓存，不含对照表中的名称，这里补齐并按大小写去重
+    let known_aliases = lister_aliases::known_aliases(program_name);
+    let mut seen_names = HashSet::new();
+    let names: Vec<&str> = std::iter::once(program_name)
+        .chain(aliases.iter().map(String::as_str))
+        .chain(known_aliases.iter().map(String::as_str))
+        .filter(|name| seen_names.insert(name.to_lowercase()))
+        .collect();
 
-        // 检查是否完全匹配
-        let exact_match = path_lower.contains(&format!("\\{} ", name_lower))
-            || path_lower.contains(&format!("/{} ", name_lower))
-            || path_lower.contains(&format!("\\{}.", name_lower));
+    // 并行扫描不同类型
+    for name in &names {
+        if types.contains(&TraceType::RegistryKey) {
+            spawn_scanner(
+                "注册表",
+                "registry_scan",
+                name,
+                raw_sender.clone(),
+                &stats,
+                registry::scan_registry_traces,
+            );
+        }
 
-        trace.confidence = if exact_match {
-            models::Confidence::High
-        } else if name_match {
-            models::Confidence::Medium
-        } else {
-            models::Confidence::Low
-        };
+        if types.contains(&TraceType::File) {
+            spawn_scanner(
+                "文件系统",
+                "filesystem_scan",
+                name,
+                raw_sender.clone(),
+                &stats,
+                filesystem::scan_filesystem_traces,
+            );
+        }
 
-        // 检查是否为关键系统项
-        if crate::modules::common::utils::is_system_critical_path(&trace.path) {
-            trace.is_critical = true;
+        if types.contains(&TraceType::AppData) {
+            spawn_scanner(
+                "AppData",
+                "appdata_scan",
+                name,
+                raw_sender.clone(),
+                &stats,
+                appdata::scan_appdata_traces,
+            );
         }
 
-        if matches!(
-            trace.trace_type,
-            TraceType::RegistryKey | TraceType::RegistryValue
-        ) {
-            if crate::modules::common::utils::is_critical_registry_path(&trace.path) {
-                trace.is_critical = true;
+        if types.contains(&TraceType::ActiveX) {
+            spawn_scanner(
+                "ActiveX",
+                "activex_scan",
+                name,
+                raw_sender.clone(),
+                &stats,
+                activex::scan_activex_traces,
+            );
+        }
+
+        if types.contains(&TraceType::UsageHistory) {
+            spawn_scanner(
+                "使用记录",
+                "usage_history_scan",
+                name,
+                raw_sender.clone(),
+                &stats,
+                usage_history::scan_usage_history_traces,
+            );
+        }
+
+        if types.contains(&TraceType::NetworkSetting) {
+            spawn_scanner(
+                "网络设置",
+                "network_scan",
+                name,
+                raw_sender.clone(),
+                &stats,
+                network::scan_network_traces,
+            );
+        }
+    }
+
+    // 以下扫描器一次匹配全部名称，按安装目录归属的扫描器共用同一个安装目录
+    let patterns: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+    let install_root = program.and_then(roots::install_root);
+
+    // 快捷方式按目标路径归属，一次枚举匹配全部名称
+    if types.contains(&TraceType::Shortcut) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "快捷方式",
+            "shortcut_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| shortcuts::scan_shortcut_traces(&patterns, install_root.as_deref(), emit),
+        );
+    }
+
+    // 自启动项同时产生注册表值和快捷方式痕迹，一次枚举匹配全部名称，
+    // 命令位于安装目录的条目不会因名称扫描先到而被降为中置信度
+    if types.contains(&TraceType::RegistryValue) || types.contains(&TraceType::Shortcut) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "自启动项",
+            "startup_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| startup::scan_startup_traces(&patterns, install_root.as_deref(), emit),
+        );
+    }
+
+    // 值扫描按安装目录匹配数据，没有可用的安装目录时不运行
+    if let Some(install_root) = install_root
+        .clone()
+        .filter(|_| types.contains(&TraceType::RegistryValue))
+    {
+        let patterns = patterns.clone();
+        spawn_scan_task(
+            "注册表值",
+            "registry_value_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| registry_values::scan_registry_value_traces(&patterns, &install_root, emit),
+        );
+    }
+
+    if types.contains(&TraceType::RegistryKey) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "COM 组件",
+            "com_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| com::scan_com_traces(&patterns, install_root.as_deref(), emit),
+        );
+    }
+
+    if types.contains(&TraceType::RegistryKey) || types.contains(&TraceType::RegistryValue) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "文件关联",
+            "association_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| {
+                associations::scan_association_traces(&patterns, install_root.as_deref(), emit)
+            },
+        );
+    }
+
+    if types.contains(&TraceType::FirewallRule) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "防火墙规则",
+            "firewall_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| firewall::scan_firewall_traces(&patterns, install_root.as_deref(), emit),
+        );
+    }
+
+    if types.contains(&TraceType::EnvironmentVariable) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "环境变量",
+            "environment_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| {
+                environment::scan_environment_traces(&patterns, install_root.as_deref(), emit)
+            },
+        );
+    }
+
+    if types.contains(&TraceType::ContextMenu) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "右键菜单",
+            "context_menu_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| {
+                context_menu::scan_context_menu_traces(&patterns, install_root.as_deref(), emit)
+            },
+        );
+    }
+
+    if types.contains(&TraceType::Credential) {
+        let patterns = patterns.clone();
+        let publisher = program.and_then(|program| program.publisher.clone());
+        spawn_scan_task(
+            "凭据",
+            "credential_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| credentials::scan_credential_traces(&patterns, publisher.as_deref(), emit),
+        );
+    }
+
+    if types.contains(&TraceType::EventLogSource) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "事件日志源",
+            "event_log_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| event_log::scan_event_log_traces(&patterns, install_root.as_deref(), emit),
+        );
+    }
+
+    if types.contains(&TraceType::WmiProvider) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "WMI 提供程序",
+            "wmi_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| wmi::scan_wmi_traces(&patterns, install_root.as_deref(), emit),
+        );
+    }
+
+    if types.contains(&TraceType::DefenderExclusion) {
+        let (patterns, install_root) = (patterns.clone(), install_root.clone());
+        spawn_scan_task(
+            "Defender 排除项",
+            "defender_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| defender::scan_defender_traces(&patterns, install_root.as_deref(), emit),
+        );
+    }
+
+    if types.contains(&TraceType::File) {
+        let patterns = patterns.clone();
+        let installed_version = program.and_then(|program| program.version.clone());
+        spawn_scan_task(
+            "安装包",
+            "installer_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| {
+                installers::scan_installer_traces(&patterns, installed_version.as_deref(), emit)
+            },
+        );
+    }
+
+    // 其他用户的 AppData 和注册表配置单元，只在扩大扫描范围时扫描
+    let profile_scope = profiles::current_scope();
+    if profile_scope != profiles::ProfileScope::CurrentUser
+        && (types.contains(&TraceType::AppData) || types.contains(&TraceType::RegistryKey))
+    {
+        let patterns = patterns.clone();
+        spawn_scan_task(
+            "其他用户",
+            "profiles_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| profiles::scan_other_profile_traces(&patterns, profile_scope, emit),
+        );
+    }
+
+    // 安装日志会同时产生文件和注册表痕迹，类型过滤在下面的处理任务中完成
+    spawn_scanner(
+        "安装日志",
+        "install_log_scan",
+        program_name,
+        raw_sender.clone(),
+        &stats,
+        install_log::scan_install_log_traces,
+    );
+
+    if let Some(program) = program {
+        let program = program.clone();
+        spawn_scan_task(
+            "程序记录",
+            "metadata_scan",
+            program_name,
+            raw_sender.clone(),
+            &stats,
+            move |emit| metadata::scan_metadata_traces(&program, emit),
+        );
+    }
+
+    // 所有扫描器持有各自的 sender，释放这里的副本以便扫描结束时 channel 能关闭
+    drop(raw_sender);
+
+    // 用户标记保留的路径和命中全局忽略规则的路径不再报告
+    let kept = kept_traces::kept_paths(program_name).unwrap_or_else(|e| {
+        tracing::warn!("读取保留的痕迹失败: {}", e);
+        Vec::new()
+    });
+    let ignore_rules = IgnoreRules::load();
+
+    // 按用户以往的清理选择校准置信度
+    let calibration = calibration::Calibration::load();
+
+    // 名称、别名和程序记录（安装目录、产品代码、发布者）共同决定分数
+    let scorer = scoring::Scorer::new(patterns, program);
+
+    let program_name = program_name.to_string();
+    tokio::spawn(async move {
+        // 不同扫描器可能命中同一路径（如桌面快捷方式），只保留首次出现的痕迹
+        let mut seen_paths = HashSet::new();
+
+        while let Some((index, mut trace)) = raw_receiver.recv().await {
+            if !trace.exists
+                || !types.contains(&trace.trace_type)
+                || kept_traces::is_kept(&trace.path, &kept)
+                || ignore_rules.is_ignored(&trace.path)
+                || !seen_paths.insert(trace.path.to_lowercase())
+            {
+                continue;
+            }
+
+            trace.program_name = program_name.clone();
+            scorer.apply(&mut trace);
+            mark_critical(&mut trace);
+            trace.owner = ownership::detect_other_user(&trace);
+            if matches!(
+                trace.trace_type,
+                TraceType::File | TraceType::AppData | TraceType::Shortcut
+            ) {
+                trace.cloud_sync = known_folders::cloud_sync_reason(&trace.path);
+            }
+            trace.category = user_content::classify_trace(&trace);
+
+            // 可能含有用户数据的痕迹不论名称匹配多好都降为低置信度
+            if trace.user_data.is_some() {
+                trace.set_confidence(models::Confidence::Low);
+            }
+
+            safety::explain_risk(&mut trace);
+            calibration.apply(&mut trace);
+            if !include_low_confidence && trace.confidence == Confidence::Low {
+                continue;
+            }
+            stats.update(index, |scanner| scanner.matched += 1);
+
+            if sender.send(trace).await.is_err() {
+                // 调用方已放弃接收
+                break;
             }
         }
+    });
+
+    receiver
+}
+
+/// 在阻塞线程中运行单个扫描器，并把结果发送到 channel
+fn spawn_scanner(
+    label: &'static str,
+    stage: &'static str,
+    program_name: &str,
+    sender: mpsc::Sender<(usize, Trace)>,
+    stats: &ScanStatsHandle,
+    scan: ScanFn,
+) {
+    let name = program_name.to_string();
+    spawn_scan_task(label, stage, program_name, sender, stats, move |emit| {
+        scan(&name, emit)
+    });
+}
+
+/// 在阻塞线程中运行扫描任务，并把结果连同扫描器编号发送到 channel
+fn spawn_scan_task(
+    label: &'static str,
+    stage: &'static str,
+    pattern: &str,
+    sender: mpsc::Sender<(usize, Trace)>,
+    stats: &ScanStatsHandle,
+    scan: impl FnOnce(&mut dyn FnMut(Trace)) -> Result<(), UninstallerError> + Send + 'static,
+) {
+    let profile = priority::current();
+    let scan_options = options::current();
+    let stats = stats.clone();
+    let index = stats.register(label, stage, pattern);
+    tokio::task::spawn_blocking(move || {
+        let _priority = priority::enter(profile);
+        let _options = options::enter(scan_options);
+        let mut emit = |mut trace: Trace| {
+            stats.found(index, &trace.path);
+            // 用户数据检查和注册表统计需要遍历目录或子项，放在扫描线程中完成
+            trace.user_data = user_data::detect_user_data(&trace);
+            if trace.trace_type == TraceType::RegistryKey {
+                trace.registry_stats = registry::measure_registry_key(&trace.path);
+            }
+            let _ = sender.blocking_send((index, trace));
+        };
+        let error = scan(&mut emit).err().map(|e| {
+            tracing::warn!("{}扫描失败: {}", label, e);
+            e.to_string()
+        });
+        stats.finish(index, error);
+    });
+}
+
+/// 标记关键系统项
+fn mark_critical(trace: &mut Trace) {
+    if utils::is_system_critical_path(&trace.path) {
+        trace.is_critical = true;
+    }
+
+    if matches!(
+        trace.trace_type,
+        TraceType::RegistryKey | TraceType::RegistryValue | TraceType::ActiveX
+    ) && utils::is_critical_registry_path(&trace.path)
+    {
+        trace.is_critical = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_handle_marks_unfinished_scanners_timed_out() {
+        let stats = ScanStatsHandle::default();
+        let finished = stats.register("注册表", "registry_scan", "Demo");
+        let stuck = stats.register("文件系统", "filesystem_scan", "Demo");
+
+        stats.update(finished, |scanner| scanner.examined += 2);
+        stats.finish(finished, Some("拒绝访问".to_string()));
+        stats.mark_unfinished_timed_out();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot[finished].examined, 2);
+        assert!(!snapshot[finished].timed_out);
+        assert!(snapshot[finished].is_incomplete());
+        assert!(snapshot[stuck].timed_out);
+    }
+
+    #[test]
+    fn stats_handle_reports_found_items_and_finished_scanners() {
+        let events = Arc::new(Mutex::new(Vec::<ScanProgress>::new()));
+        let recorded = events.clone();
+        let stats = ScanStatsHandle::with_progress(Some(Arc::new(move |event: &ScanProgress| {
+            recorded.lock().unwrap().push(event.clone());
+        })));
+        let registry = stats.register("注册表", "registry_scan", "Demo");
+        stats.register("文件系统", "filesystem_scan", "Demo");
+
+        stats.found(registry, r"HKCU\SOFTWARE\Demo");
+        // 间隔内的候选项只计数，不再报告
+        stats.found(registry, r"HKCU\SOFTWARE\Demo\Settings");
+        stats.finish(registry, None);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].label, "注册表");
+        assert_eq!(
+            events[0].current_path.as_deref(),
+            Some(r"HKCU\SOFTWARE\Demo")
+        );
+        assert_eq!(events[0].items_found, 1);
+        assert_eq!(events[1].current_path, None);
+        assert_eq!(events[1].items_found, 2);
+        assert_eq!((events[1].completed, events[1].total), (1, 2));
+        assert_eq!(stats.snapshot()[registry].examined, 2);
     }
 }
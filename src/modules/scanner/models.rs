@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::profiling::StageTiming;
+use crate::modules::common::utils;
+
 /// 痕迹类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TraceType {
@@ -19,6 +23,26 @@ pub enum TraceType {
     Service,
     /// 驱动程序
     Driver,
+    /// ActiveX 控件或浏览器加载项（BHO、工具栏）
+    ActiveX,
+    /// 使用记录：打开方式列表、最近打开的文件、跳转列表
+    UsageHistory,
+    /// 网络设置：代理、PAC 脚本
+    NetworkSetting,
+    /// 防火墙规则
+    FirewallRule,
+    /// 环境变量中指向程序的路径片段
+    EnvironmentVariable,
+    /// 资源管理器右键菜单项：`shell\<动作>` 命令或 `ContextMenuHandlers` 处理程序
+    ContextMenu,
+    /// Windows 凭据管理器中保存的凭据
+    Credential,
+    /// `Services\EventLog` 下注册的事件日志源
+    EventLogSource,
+    /// WMI 提供程序注册和自动恢复 MOF 条目
+    WmiProvider,
+    /// Windows Defender 中指向程序的路径或进程排除项
+    DefenderExclusion,
 }
 
 impl Default for TraceType {
@@ -38,6 +62,16 @@ impl std::fmt::Display for TraceType {
             TraceType::ScheduledTask => write!(f, "ScheduledTask"),
             TraceType::Service => write!(f, "Service"),
             TraceType::Driver => write!(f, "Driver"),
+            TraceType::ActiveX => write!(f, "ActiveX"),
+            TraceType::UsageHistory => write!(f, "UsageHistory"),
+            TraceType::NetworkSetting => write!(f, "NetworkSetting"),
+            TraceType::FirewallRule => write!(f, "FirewallRule"),
+            TraceType::EnvironmentVariable => write!(f, "EnvironmentVariable"),
+            TraceType::ContextMenu => write!(f, "ContextMenu"),
+            TraceType::Credential => write!(f, "Credential"),
+            TraceType::EventLogSource => write!(f, "EventLogSource"),
+            TraceType::WmiProvider => write!(f, "WmiProvider"),
+            TraceType::DefenderExclusion => write!(f, "DefenderExclusion"),
         }
     }
 }
@@ -59,6 +93,67 @@ impl Default for Confidence {
     }
 }
 
+/// 评为高置信度的最低分数
+const HIGH_CONFIDENCE_SCORE: u8 = 70;
+
+/// 评为中置信度的最低分数
+const MEDIUM_CONFIDENCE_SCORE: u8 = 40;
+
+impl Confidence {
+    /// 是否达到指定的最低置信度
+    pub fn meets(self, min_confidence: Confidence) -> bool {
+        self <= min_confidence
+    }
+
+    /// 分数所属的等级
+    pub fn from_score(score: u8) -> Self {
+        if score >= HIGH_CONFIDENCE_SCORE {
+            Confidence::High
+        } else if score >= MEDIUM_CONFIDENCE_SCORE {
+            Confidence::Medium
+        } else {
+            Confidence::Low
+        }
+    }
+
+    /// 把分数限制在该等级的范围内
+    pub fn clamp_score(self, score: u8) -> u8 {
+        match self {
+            Confidence::High => score.clamp(HIGH_CONFIDENCE_SCORE, 100),
+            Confidence::Medium => score.clamp(MEDIUM_CONFIDENCE_SCORE, HIGH_CONFIDENCE_SCORE - 1),
+            Confidence::Low => score.min(MEDIUM_CONFIDENCE_SCORE - 1),
+        }
+    }
+}
+
+impl std::str::FromStr for Confidence {
+    type Err = UninstallerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "high" => Ok(Confidence::High),
+            "medium" => Ok(Confidence::Medium),
+            "low" => Ok(Confidence::Low),
+            other => Err(UninstallerError::Other(format!(
+                "无效的置信度: {}（可选 high/medium/low）",
+                other
+            ))),
+        }
+    }
+}
+
+/// 痕迹类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TraceCategory {
+    /// 程序自身的文件和设置
+    #[default]
+    Program,
+    /// 用户创建的内容（游戏存档、文档、导出的配置档），清理全部时默认排除
+    UserContent,
+    /// 隐私相关的使用记录，只从列表中移除条目
+    Privacy,
+}
+
 /// 痕迹项目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trace {
@@ -70,7 +165,93 @@ pub struct Trace {
     pub size: Option<u64>,
     pub is_critical: bool,
     pub confidence: Confidence,
+    /// 0–100 的匹配分数，置信度等级由分数划分，界面可按分数排序和设定阈值
+    #[serde(default)]
+    pub score: u8,
     pub exists: bool,
+    /// 痕迹属于其他用户时记录其账户名，清理前需要单独确认
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// 可能包含用户数据时记录原因，清理前需要显式确认
+    #[serde(default)]
+    pub user_data: Option<String>,
+    /// 痕迹类别
+    #[serde(default)]
+    pub category: TraceCategory,
+    /// 风险说明，供 CLI 详细输出和 GUI 展示
+    #[serde(default)]
+    pub risk: TraceRisk,
+    /// 注册表项的子项数、值数和数据量，仅 `RegistryKey` 痕迹填充
+    #[serde(default)]
+    pub registry_stats: Option<RegistryStats>,
+    /// 位于 OneDrive 或漫游配置文件等同步目录时记录说明，删除会同步到云端或服务器
+    #[serde(default)]
+    pub cloud_sync: Option<String>,
+    /// 分数的来源，决定评分器是否按名称重新评分
+    #[serde(default)]
+    pub score_source: ScoreSource,
+}
+
+/// 痕迹分数的来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoreSource {
+    /// 名称扫描器找到的候选项，由评分器按名称和程序记录评分
+    #[default]
+    Name,
+    /// 扫描器已给出确定的匹配依据（安装日志、程序记录、指向安装目录的命令等），
+    /// 评分器以其置信度为基础分，只叠加程序记录的依据
+    Scanner,
+}
+
+/// 注册表项的内容统计（含所有子项）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryStats {
+    pub subkey_count: usize,
+    pub value_count: usize,
+    /// 值名称和数据的近似字节数
+    pub data_size: u64,
+    /// 项数超过统计上限，结果只是下限
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl RegistryStats {
+    /// 没有任何子项和值的空项
+    pub fn is_empty(&self) -> bool {
+        self.subkey_count == 0 && self.value_count == 0
+    }
+}
+
+impl std::fmt::Display for RegistryStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "空项");
+        }
+        write!(
+            f,
+            "{}{} 个子项, {} 个值, {}",
+            if self.truncated { "至少 " } else { "" },
+            self.subkey_count,
+            self.value_count,
+            utils::format_size(self.data_size)
+        )
+    }
+}
+
+/// 痕迹的风险说明
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraceRisk {
+    /// 命中原因
+    pub match_reason: String,
+    /// 删除后的影响
+    pub impact: String,
+    /// 是否可能被其他程序或用户共用
+    pub shared: bool,
+    /// 删除后能否轻易恢复
+    pub reversible: bool,
+    /// 其他需要注意的事项
+    #[serde(default)]
+    pub notes: Vec<String>,
 }
 
 impl Trace {
@@ -84,7 +265,15 @@ impl Trace {
             size: None,
             is_critical: false,
             confidence: Confidence::Low,
+            score: 0,
             exists: true,
+            owner: None,
+            user_data: None,
+            category: TraceCategory::default(),
+            risk: TraceRisk::default(),
+            registry_stats: None,
+            cloud_sync: None,
+            score_source: ScoreSource::Name,
         }
     }
 
@@ -99,7 +288,135 @@ impl Trace {
     }
 
     pub fn with_confidence(mut self, confidence: Confidence) -> Self {
-        self.confidence = confidence;
+        self.set_confidence(confidence);
         self
     }
+
+    /// 设置置信度，分数随之调整到该等级的范围内
+    pub fn set_confidence(&mut self, confidence: Confidence) {
+        self.confidence = confidence;
+        self.score = confidence.clamp_score(self.score);
+    }
+
+    /// 记录扫描器给出的匹配依据，之后评分时保留扫描器设定的置信度
+    pub fn set_match_reason(&mut self, reason: String) {
+        self.risk.match_reason = reason;
+        self.score_source = ScoreSource::Scanner;
+    }
+}
+
+/// 一次完整扫描的结果与耗时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSummary {
+    pub program_name: String,
+    pub traces: Vec<Trace>,
+    pub duration_ms: f64,
+    /// 各扫描器耗时
+    #[serde(default)]
+    pub timings: Vec<StageTiming>,
+    /// 低于最低置信度而被过滤掉的痕迹数
+    #[serde(default)]
+    pub filtered_count: usize,
+    /// 各扫描器的运行统计
+    #[serde(default)]
+    pub scanners: Vec<ScannerStats>,
+}
+
+/// 单个扫描器的运行统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScannerStats {
+    /// 扫描阶段标识，如 `registry_scan`
+    pub scanner: String,
+    /// 显示名称
+    pub label: String,
+    /// 本次使用的名称（程序名或别名）
+    pub pattern: String,
+    pub duration_ms: f64,
+    /// 扫描器产生的候选项数
+    pub examined: usize,
+    /// 经过类型过滤和去重后保留的痕迹数
+    pub matched: usize,
+    /// 扫描失败的原因
+    pub error: Option<String>,
+    /// 超过时限仍未结束，结果可能不完整
+    pub timed_out: bool,
+}
+
+impl ScannerStats {
+    /// 扫描失败或超时，结果不完整
+    pub fn is_incomplete(&self) -> bool {
+        self.error.is_some() || self.timed_out
+    }
+}
+
+/// 扫描进度，扫描器发现候选项或结束时报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanProgress {
+    /// 扫描阶段标识，如 `registry_scan`
+    pub scanner: String,
+    /// 扫描器显示名称
+    pub label: String,
+    /// 刚发现的候选项路径，扫描器结束时为 None
+    pub current_path: Option<String>,
+    /// 所有扫描器至今发现的候选项数
+    pub items_found: usize,
+    /// 已结束的扫描器数
+    pub completed: usize,
+    /// 已启动的扫描器总数
+    pub total: usize,
+}
+
+impl ScanSummary {
+    /// 只保留达到最低置信度的痕迹，并记录过滤掉的数量
+    pub fn retain_min_confidence(&mut self, min_confidence: Confidence) {
+        let before = self.traces.len();
+        self.traces
+            .retain(|trace| trace.confidence.meets(min_confidence));
+        self.filtered_count += before - self.traces.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_stats_distinguish_empty_keys() {
+        let empty = RegistryStats::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.to_string(), "空项");
+
+        let settings = RegistryStats {
+            subkey_count: 3,
+            value_count: 12,
+            data_size: 2048,
+            truncated: true,
+        };
+        assert!(!settings.is_empty());
+        assert_eq!(settings.to_string(), "至少 3 个子项, 12 个值, 2.00 KB");
+    }
+
+    #[test]
+    fn min_confidence_filter_counts_dropped_traces() {
+        let mut summary = ScanSummary {
+            program_name: "Demo".to_string(),
+            traces: [Confidence::High, Confidence::Medium, Confidence::Low]
+                .into_iter()
+                .map(|confidence| {
+                    let mut trace = Trace::new("Demo".to_string(), TraceType::File, String::new());
+                    trace.confidence = confidence;
+                    trace
+                })
+                .collect(),
+            duration_ms: 0.0,
+            timings: Vec::new(),
+            filtered_count: 0,
+            scanners: Vec::new(),
+        };
+
+        summary.retain_min_confidence("Medium".parse().unwrap_or_default());
+        assert_eq!(summary.traces.len(), 2);
+        assert_eq!(summary.filtered_count, 1);
+        assert!("certain".parse::<Confidence>().is_err());
+    }
 }
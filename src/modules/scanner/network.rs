@@ -0,0 +1,169 @@
+//! 网络设置残留：代理与 PAC 脚本
+//!
+//! VPN 客户端、抓包工具等会修改当前用户的 WinINET 代理（`Internet Settings` 中的
+//! `AutoConfigURL`、`ProxyServer`）或系统 WinHTTP 代理，卸载后常被遗留，导致无法上网。
+//! 只报告能归属于程序的设置：PAC 地址或代理地址与程序名匹配。这类痕迹影响整机联网，
+//! 统一标记为需要人工确认。防火墙规则见 [`super::firewall`]。
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+
+/// 当前用户的 WinINET 设置
+pub const WININET_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Internet Settings";
+
+/// PAC 脚本地址
+pub const AUTO_CONFIG_URL_VALUE: &str = "AutoConfigURL";
+
+/// 代理服务器地址
+pub const PROXY_SERVER_VALUE: &str = "ProxyServer";
+
+/// 代理开关
+pub const PROXY_ENABLE_VALUE: &str = "ProxyEnable";
+
+/// 系统 WinHTTP 代理（HKLM），由 `netsh winhttp set proxy` 写入
+pub const WINHTTP_KEY: &str =
+    r"SOFTWARE\Microsoft\Windows\CurrentVersion\Internet Settings\Connections";
+
+/// WinHTTP 代理设置值
+pub const WINHTTP_SETTINGS_VALUE: &str = "WinHttpSettings";
+
+/// 扫描与程序相关的代理设置，每发现一项即交给 `emit`
+pub fn scan_network_traces(
+    program_name: &str,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let pattern = program_name.to_lowercase();
+
+    if let Ok(key) = RegKey::predef(HKEY_CURRENT_USER).open_subkey(WININET_KEY) {
+        for (value, label) in [
+            (AUTO_CONFIG_URL_VALUE, "PAC 脚本"),
+            (PROXY_SERVER_VALUE, "代理服务器"),
+        ] {
+            let Ok(setting) = key.get_value::<String, _>(value) else {
+                continue;
+            };
+            if matching::name_matches(&decode_url(&setting), &pattern) {
+                emit(network_trace(
+                    &pattern,
+                    format!(r"HKCU\{}\{}", WININET_KEY, value),
+                    format!("当前用户{}: {}", label, setting),
+                    Confidence::Medium,
+                    "代理设置中的地址与程序名匹配",
+                ));
+            }
+        }
+    }
+
+    if let Ok(settings) = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(WINHTTP_KEY)
+        .and_then(|key| key.get_raw_value(WINHTTP_SETTINGS_VALUE))
+    {
+        if let Some(proxy) = parse_winhttp_proxy(&settings.bytes) {
+            if matching::name_matches(&proxy, &pattern) {
+                emit(network_trace(
+                    &pattern,
+                    format!(r"HKLM\{}\{}", WINHTTP_KEY, WINHTTP_SETTINGS_VALUE),
+                    format!("WinHTTP 系统代理: {}", proxy),
+                    Confidence::Medium,
+                    "WinHTTP 代理地址与程序名匹配",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn network_trace(
+    pattern: &str,
+    path: String,
+    description: String,
+    confidence: Confidence,
+    reason: &str,
+) -> Trace {
+    let mut trace = Trace::new(pattern.to_string(), TraceType::NetworkSetting, path)
+        .with_description(description)
+        .with_confidence(confidence);
+    trace.set_match_reason(reason.to_string());
+    trace
+}
+
+/// 痕迹路径是否指向本模块报告的网络设置
+pub fn is_network_setting_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    [
+        format!(r"hkcu\{}\{}", WININET_KEY, AUTO_CONFIG_URL_VALUE),
+        format!(r"hkcu\{}\{}", WININET_KEY, PROXY_SERVER_VALUE),
+        format!(r"hklm\{}\{}", WINHTTP_KEY, WINHTTP_SETTINGS_VALUE),
+    ]
+    .iter()
+    .any(|known| lower == known.to_lowercase())
+}
+
+/// WinHTTP 设置：u32 结构版本、u32 计数、u32 标志、u32 长度 + 代理地址、u32 长度 + 例外列表
+fn parse_winhttp_proxy(bytes: &[u8]) -> Option<String> {
+    let read_u32 = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|raw| u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize)
+    };
+    let len = read_u32(12)?;
+    let proxy = bytes.get(16..16 + len)?;
+    let proxy = String::from_utf8_lossy(proxy).trim().to_string();
+    (!proxy.is_empty()).then_some(proxy)
+}
+
+/// PAC 地址中常见 `%20` 等转义，解码后再按名称匹配
+fn decode_url(url: &str) -> String {
+    let mut decoded = Vec::with_capacity(url.len());
+    let bytes = url.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            if let Some(byte) = url
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).replace('/', "\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proxy_settings() {
+        assert_eq!(
+            decode_url("file:///C:/Program%20Files/Acme%20VPN/proxy.pac"),
+            r"file:\\\C:\Program Files\Acme VPN\proxy.pac"
+        );
+
+        let mut settings = vec![0x28, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0];
+        let proxy = b"acmevpn.local:8080";
+        settings.extend((proxy.len() as u32).to_le_bytes());
+        settings.extend(proxy);
+        settings.extend(0u32.to_le_bytes());
+        assert_eq!(
+            parse_winhttp_proxy(&settings).as_deref(),
+            Some("acmevpn.local:8080")
+        );
+
+        assert!(is_network_setting_path(&format!(
+            r"HKCU\{}\AutoConfigURL",
+            WININET_KEY
+        )));
+        assert!(!is_network_setting_path(r"HKCU\Software\Acme"));
+    }
+}
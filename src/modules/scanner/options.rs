@@ -0,0 +1,128 @@
+//! 扫描选项
+//!
+//! 各扫描器的遍历深度和项数上限原先是固定值：安装在较深目录中的程序残留扫描不到，
+//! 大型目录又会拖慢扫描。扫描入口接收 [`ScanOptions`]，派生扫描线程时通过 [`current`]
+//! 读取并在新线程上调用 [`enter`]，扫描器按当前线程的选项遍历，与优先级配置的传递方式相同。
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// 文件系统扫描的默认深度
+pub const DEFAULT_FILESYSTEM_DEPTH: usize = 3;
+
+/// AppData 扫描的默认深度（AppData 目录可能比较深）
+pub const DEFAULT_APPDATA_DEPTH: usize = 4;
+
+/// 快捷方式扫描的默认深度
+pub const DEFAULT_SHORTCUT_DEPTH: usize = 3;
+
+/// 注册表扫描的默认深度
+pub const DEFAULT_REGISTRY_DEPTH: u32 = 5;
+
+/// 完整扫描等待扫描器结束的默认时限（秒）
+pub const DEFAULT_TIMEOUT_SECS: u64 = 180;
+
+/// 扫描选项，未指定的字段使用默认值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// Program Files、桌面等目录的遍历深度
+    pub filesystem_depth: usize,
+    /// AppData 目录的遍历深度
+    pub appdata_depth: usize,
+    /// 开始菜单和桌面快捷方式的遍历深度
+    pub shortcut_depth: usize,
+    /// 注册表项的递归深度
+    pub registry_depth: u32,
+    /// 文件系统扫描额外遍历的目录，如 `D:\Tools`
+    pub extra_roots: Vec<PathBuf>,
+    /// 每个目录或注册表根最多遍历的项数，未设置时目录不限，注册表值扫描使用其内置上限
+    pub max_entries: Option<usize>,
+    /// 等待扫描器结束的时限（秒），超时的扫描器在摘要中标记
+    pub timeout_secs: u64,
+    /// 是否报告低置信度的痕迹
+    pub include_low_confidence: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            filesystem_depth: DEFAULT_FILESYSTEM_DEPTH,
+            appdata_depth: DEFAULT_APPDATA_DEPTH,
+            shortcut_depth: DEFAULT_SHORTCUT_DEPTH,
+            registry_depth: DEFAULT_REGISTRY_DEPTH,
+            extra_roots: Vec::new(),
+            max_entries: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            include_low_confidence: true,
+        }
+    }
+}
+
+impl ScanOptions {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// 目录遍历的项数上限
+    pub fn entry_limit(&self) -> usize {
+        self.max_entries.unwrap_or(usize::MAX)
+    }
+}
+
+thread_local! {
+    /// 当前线程的扫描选项
+    static THREAD_OPTIONS: RefCell<Option<Arc<ScanOptions>>> = const { RefCell::new(None) };
+}
+
+/// 当前线程的扫描选项，未设置时为默认值
+pub fn current() -> Arc<ScanOptions> {
+    THREAD_OPTIONS
+        .with(|options| options.borrow().clone())
+        .unwrap_or_default()
+}
+
+/// 扫描选项的恢复守卫
+pub struct OptionsGuard {
+    previous: Option<Arc<ScanOptions>>,
+}
+
+impl Drop for OptionsGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        THREAD_OPTIONS.with(|options| *options.borrow_mut() = previous);
+    }
+}
+
+/// 当前线程按选项扫描，守卫释放时恢复
+pub fn enter(options: Arc<ScanOptions>) -> OptionsGuard {
+    let previous = THREAD_OPTIONS.with(|current| current.borrow_mut().replace(options));
+    OptionsGuard { previous }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guard_restores_thread_options() {
+        assert_eq!(*current(), ScanOptions::default());
+        {
+            let _guard = enter(Arc::new(ScanOptions {
+                filesystem_depth: 6,
+                ..ScanOptions::default()
+            }));
+            assert_eq!(current().filesystem_depth, 6);
+        }
+        assert_eq!(current().filesystem_depth, DEFAULT_FILESYSTEM_DEPTH);
+
+        // 前端只传部分字段
+        let options: ScanOptions = serde_json::from_str(r#"{"registry_depth": 8}"#).unwrap();
+        assert_eq!(options.registry_depth, 8);
+        assert_eq!(options.timeout_secs, DEFAULT_TIMEOUT_SECS);
+    }
+}
@@ -0,0 +1,133 @@
+//! 痕迹归属用户识别
+//!
+//! 位于其他用户配置文件目录或 `HKU\<SID>` 下的痕迹会标记所属账户，清理时需要单独确认。
+
+use super::models::Trace;
+use std::path::Path;
+use winreg::enums::*;
+use winreg::RegKey;
+
+/// 用户配置文件列表注册表路径
+pub(super) const PROFILE_LIST_KEY: &str =
+    r"SOFTWARE\Microsoft\Windows NT\CurrentVersion\ProfileList";
+
+/// 所有用户共享的配置文件目录，不视为某个具体用户
+const SHARED_PROFILE_DIRS: &[&str] = &["public", "default", "default user", "all users"];
+
+/// 系统内置账户 SID
+pub(super) const SYSTEM_SIDS: &[&str] = &[".default", "s-1-5-18", "s-1-5-19", "s-1-5-20"];
+
+/// 识别痕迹所属的其他用户账户，属于当前用户或系统时返回 None
+pub fn detect_other_user(trace: &Trace) -> Option<String> {
+    let current_profile_raw = std::env::var("USERPROFILE").ok()?;
+    let current_profile = Path::new(&current_profile_raw);
+    let current_user = current_profile.file_name()?.to_string_lossy().to_string();
+    let profiles_root = current_profile.parent()?.to_string_lossy().to_string();
+
+    if let Some(owner) = owner_from_profile_path(&trace.path, &profiles_root, &current_user) {
+        return Some(owner);
+    }
+
+    let sid = user_sid_from_registry_path(&trace.path)?;
+    let profile_path = profile_path_for_sid(&sid);
+    match profile_path {
+        Some(path) if path.eq_ignore_ascii_case(&current_profile_raw) => None,
+        Some(path) => Some(
+            Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(sid),
+        ),
+        None => Some(sid),
+    }
+}
+
+/// 从文件路径中解析其他用户的账户名
+fn owner_from_profile_path(path: &str, profiles_root: &str, current_user: &str) -> Option<String> {
+    let path = path.replace('/', "\\");
+    let root = profiles_root.replace('/', "\\");
+    let root = root.trim_end_matches('\\');
+
+    let prefix = path.get(..root.len())?;
+    if !prefix.eq_ignore_ascii_case(root) {
+        return None;
+    }
+
+    let rest = path[root.len()..].strip_prefix('\\')?;
+    let user = rest.split('\\').next().filter(|name| !name.is_empty())?;
+    let user_lower = user.to_lowercase();
+
+    let is_current = user_lower == current_user.to_lowercase();
+    if is_current || SHARED_PROFILE_DIRS.contains(&user_lower.as_str()) {
+        return None;
+    }
+
+    Some(user.to_string())
+}
+
+/// 从 `HKU\<SID>\...` 路径中解析用户 SID，系统账户返回 None
+fn user_sid_from_registry_path(path: &str) -> Option<String> {
+    let rest = [r"HKU\", r"HKEY_USERS\"].iter().find_map(|prefix| {
+        path.get(..prefix.len())
+            .filter(|head| head.eq_ignore_ascii_case(prefix))
+            .map(|_| &path[prefix.len()..])
+    })?;
+
+    let sid = rest.split('\\').next().filter(|sid| !sid.is_empty())?;
+    let sid = sid.strip_suffix("_Classes").unwrap_or(sid);
+
+    if SYSTEM_SIDS.contains(&sid.to_lowercase().as_str()) {
+        return None;
+    }
+
+    Some(sid.to_string())
+}
+
+/// 通过 ProfileList 查询 SID 对应的配置文件目录
+fn profile_path_for_sid(sid: &str) -> Option<String> {
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(format!(r"{}\{}", PROFILE_LIST_KEY, sid))
+        .ok()?;
+    key.get_value::<String, _>("ProfileImagePath").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owner_from_profile_path_skips_current_and_shared_profiles() {
+        let root = r"C:\Users";
+
+        assert_eq!(
+            owner_from_profile_path(r"C:\Users\Bob\AppData\Roaming\Foo", root, "alice"),
+            Some("Bob".to_string())
+        );
+        assert_eq!(
+            owner_from_profile_path(r"C:\Users\Alice\AppData\Roaming\Foo", root, "alice"),
+            None
+        );
+        assert_eq!(
+            owner_from_profile_path(r"C:\Users\Public\Desktop\Foo.lnk", root, "alice"),
+            None
+        );
+        assert_eq!(
+            owner_from_profile_path(r"C:\Program Files\Foo", root, "alice"),
+            None
+        );
+    }
+
+    #[test]
+    fn user_sid_from_registry_path_ignores_system_accounts() {
+        assert_eq!(
+            user_sid_from_registry_path(r"HKU\S-1-5-21-1-2-3-1001\Software\Foo"),
+            Some("S-1-5-21-1-2-3-1001".to_string())
+        );
+        assert_eq!(
+            user_sid_from_registry_path(r"HKU\S-1-5-21-1-2-3-1001_Classes\Foo"),
+            Some("S-1-5-21-1-2-3-1001".to_string())
+        );
+        assert_eq!(user_sid_from_registry_path(r"HKU\.DEFAULT\Software"), None);
+        assert_eq!(user_sid_from_registry_path(r"HKCU\Software\Foo"), None);
+    }
+}
@@ -0,0 +1,449 @@
+//! 扫描根目录的路径索引
+//!
+//! 在 SQLite 中记录标准扫描根目录（Program Files、ProgramData、AppData 等）下的目录项名称，
+//! 再次扫描新的程序名时直接查询索引，无需重新遍历整个目录树。
+//!
+//! 索引按目录修改时间增量刷新：目录的子项增删会更新其修改时间，
+//! 因此只有修改时间变化的目录才会重新读取子项列表。
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::time::{Duration, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::lister::storage::{self, map_sqlite_error};
+
+const PATH_INDEX_DB_FILE_NAME: &str = "path_index.sqlite3";
+const ENTRY_TABLE_NAME: &str = "path_index_entries";
+const ROOT_TABLE_NAME: &str = "path_index_roots";
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 索引在该时间内视为新鲜，跳过增量刷新
+pub const PATH_INDEX_TTL_SECONDS: i64 = 300;
+
+/// 路径索引
+pub struct PathIndex {
+    connection: Connection,
+}
+
+fn modified_millis(path: &Path) -> Option<i64> {
+    path.metadata()
+        .ok()?
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_millis() as i64)
+}
+
+/// 子树范围查询的上界：路径分隔符的下一个字符
+fn subtree_upper_bound(path: &str) -> String {
+    let next_separator = char::from_u32(MAIN_SEPARATOR as u32 + 1).unwrap_or(MAIN_SEPARATOR);
+    format!("{}{}", path, next_separator)
+}
+
+fn subtree_lower_bound(path: &str) -> String {
+    format!("{}{}", path, MAIN_SEPARATOR)
+}
+
+impl PathIndex {
+    /// 打开（必要时创建）索引数据库
+    pub fn open() -> Result<Self, UninstallerError> {
+        let db_path = storage::get_storage_root_dir()?.join(PATH_INDEX_DB_FILE_NAME);
+        let connection = Connection::open(&db_path)
+            .map_err(|error| map_sqlite_error("打开路径索引失败", error))?;
+
+        // 文件系统与 AppData 扫描器会并行写入索引
+        connection
+            .busy_timeout(BUSY_TIMEOUT)
+            .map_err(|error| map_sqlite_error("设置路径索引等待超时失败", error))?;
+
+        connection
+            .execute_batch(&format!(
+                r#"
+                PRAGMA journal_mode=WAL;
+                PRAGMA synchronous=NORMAL;
+                CREATE TABLE IF NOT EXISTS {entry_table} (
+                    path TEXT PRIMARY KEY,
+                    parent TEXT NOT NULL,
+                    root TEXT NOT NULL,
+                    name_lower TEXT NOT NULL,
+                    is_dir INTEGER NOT NULL,
+                    depth INTEGER NOT NULL,
+                    mtime INTEGER
+                );
+                CREATE INDEX IF NOT EXISTS idx_path_index_entries_parent
+                    ON {entry_table}(parent);
+                CREATE INDEX IF NOT EXISTS idx_path_index_entries_root
+                    ON {entry_table}(root);
+                CREATE TABLE IF NOT EXISTS {root_table} (
+                    root TEXT PRIMARY KEY,
+                    max_depth INTEGER NOT NULL,
+                    mtime INTEGER,
+                    refreshed_at TEXT NOT NULL
+                );
+                "#,
+                entry_table = ENTRY_TABLE_NAME,
+                root_table = ROOT_TABLE_NAME
+            ))
+            .map_err(|error| map_sqlite_error("初始化路径索引结构失败", error))?;
+
+        Ok(Self { connection })
+    }
+
+    /// 确保根目录索引可用：不存在、深度不一致或超过 TTL 时增量刷新
+    pub fn ensure_fresh(&mut self, root: &Path, max_depth: usize) -> Result<(), UninstallerError> {
+        let root_key = root.to_string_lossy().to_string();
+        let stored: Option<(i64, String)> = self
+            .connection
+            .query_row(
+                &format!(
+                    "SELECT max_depth, refreshed_at FROM {} WHERE root = ?1",
+                    ROOT_TABLE_NAME
+                ),
+                params![root_key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|error| map_sqlite_error("读取路径索引根目录失败", error))?;
+
+        if let Some((stored_depth, refreshed_at)) = stored {
+            let fresh = DateTime::parse_from_rfc3339(&refreshed_at)
+                .map(|time| {
+                    Utc::now()
+                        .signed_duration_since(time.with_timezone(&Utc))
+                        .num_seconds()
+                        <= PATH_INDEX_TTL_SECONDS
+                })
+                .unwrap_or(false);
+
+            if stored_depth == max_depth as i64 && fresh {
+                return Ok(());
+            }
+
+            if stored_depth != max_depth as i64 {
+                self.clear_root(&root_key)?;
+            }
+        }
+
+        self.refresh_root(root, max_depth)
+    }
+
+    /// 增量刷新根目录索引
+    pub fn refresh_root(&mut self, root: &Path, max_depth: usize) -> Result<(), UninstallerError> {
+        let root_key = root.to_string_lossy().to_string();
+        let transaction = self
+            .connection
+            .transaction()
+            .map_err(|error| map_sqlite_error("开启路径索引事务失败", error))?;
+
+        let stored_root_mtime: Option<i64> = transaction
+            .query_row(
+                &format!("SELECT mtime FROM {} WHERE root = ?1", ROOT_TABLE_NAME),
+                params![root_key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| map_sqlite_error("读取根目录修改时间失败", error))?
+            .flatten();
+
+        let mut pending: Vec<(PathBuf, usize, Option<i64>)> =
+            vec![(root.to_path_buf(), 0, stored_root_mtime)];
+
+        while let Some((dir, depth, stored_mtime)) = pending.pop() {
+            if depth >= max_depth {
+                continue;
+            }
+
+            let dir_key = dir.to_string_lossy().to_string();
+            let current_mtime = modified_millis(&dir);
+
+            let children = if current_mtime.is_some() && current_mtime == stored_mtime {
+                // 子项列表未变化，只需继续检查子目录
+                read_indexed_child_dirs(&transaction, &dir_key)?
+            } else {
+                let children =
+                    relist_directory(&transaction, &root_key, &dir, &dir_key, depth + 1)?;
+                if depth == 0 {
+                    transaction
+                        .execute(
+                            &format!(
+                                "INSERT INTO {} (root, max_depth, mtime, refreshed_at)
+                                 VALUES (?1, ?2, ?3, ?4)
+                                 ON CONFLICT(root) DO UPDATE SET max_depth = excluded.max_depth, mtime = excluded.mtime",
+                                ROOT_TABLE_NAME
+                            ),
+                            params![root_key, max_depth as i64, current_mtime, Utc::now().to_rfc3339()],
+                        )
+                        .map_err(|error| map_sqlite_error("写入根目录修改时间失败", error))?;
+                } else {
+                    transaction
+                        .execute(
+                            &format!("UPDATE {} SET mtime = ?2 WHERE path = ?1", ENTRY_TABLE_NAME),
+                            params![dir_key, current_mtime],
+                        )
+                        .map_err(|error| map_sqlite_error("更新目录修改时间失败", error))?;
+                }
+                children
+            };
+
+            for (child, child_mtime) in children {
+                pending.push((child, depth + 1, child_mtime));
+            }
+        }
+
+        transaction
+            .execute(
+                &format!(
+                    "INSERT INTO {} (root, max_depth, mtime, refreshed_at)
+                     VALUES (?1, ?2, NULL, ?3)
+                     ON CONFLICT(root) DO UPDATE SET max_depth = excluded.max_depth, refreshed_at = excluded.refreshed_at",
+                    ROOT_TABLE_NAME
+                ),
+                params![root_key, max_depth as i64, Utc::now().to_rfc3339()],
+            )
+            .map_err(|error| map_sqlite_error("写入路径索引刷新时间失败", error))?;
+
+        transaction
+            .commit()
+            .map_err(|error| map_sqlite_error("提交路径索引事务失败", error))?;
+        Ok(())
+    }
+
+    /// 查询根目录下名称包含 `pattern`（小写）的目录项
+    pub fn find_matches(
+        &self,
+        root: &Path,
+        pattern: &str,
+    ) -> Result<Vec<PathBuf>, UninstallerError> {
+        let mut statement = self
+            .connection
+            .prepare(&format!(
+                "SELECT path FROM {} WHERE root = ?1 AND instr(name_lower, ?2) > 0 ORDER BY path",
+                ENTRY_TABLE_NAME
+            ))
+            .map_err(|error| map_sqlite_error("准备查询路径索引失败", error))?;
+
+        let rows = statement
+            .query_map(params![root.to_string_lossy(), pattern], |row| {
+                row.get::<usize, String>(0).map(PathBuf::from)
+            })
+            .map_err(|error| map_sqlite_error("查询路径索引失败", error))?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row.map_err(|error| map_sqlite_error("读取路径索引行失败", error))?);
+        }
+        Ok(entries)
+    }
+
+    fn clear_root(&self, root_key: &str) -> Result<(), UninstallerError> {
+        self.connection
+            .execute(
+                &format!("DELETE FROM {} WHERE root = ?1", ENTRY_TABLE_NAME),
+                params![root_key],
+            )
+            .map_err(|error| map_sqlite_error("清理路径索引失败", error))?;
+        self.connection
+            .execute(
+                &format!("DELETE FROM {} WHERE root = ?1", ROOT_TABLE_NAME),
+                params![root_key],
+            )
+            .map_err(|error| map_sqlite_error("清理路径索引根目录失败", error))?;
+        Ok(())
+    }
+}
+
+/// 读取索引中记录的子目录及其修改时间
+fn read_indexed_child_dirs(
+    connection: &Connection,
+    dir_key: &str,
+) -> Result<Vec<(PathBuf, Option<i64>)>, UninstallerError> {
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT path, mtime FROM {} WHERE parent = ?1 AND is_dir = 1",
+            ENTRY_TABLE_NAME
+        ))
+        .map_err(|error| map_sqlite_error("准备读取索引子目录失败", error))?;
+
+    let rows = statement
+        .query_map(params![dir_key], |row| {
+            Ok((
+                PathBuf::from(row.get::<usize, String>(0)?),
+                row.get::<usize, Option<i64>>(1)?,
+            ))
+        })
+        .map_err(|error| map_sqlite_error("读取索引子目录失败", error))?;
+
+    let mut children = Vec::new();
+    for row in rows {
+        children.push(row.map_err(|error| map_sqlite_error("读取索引子目录行失败", error))?);
+    }
+    Ok(children)
+}
+
+/// 重新读取目录子项，与索引做差异更新，返回子目录列表
+fn relist_directory(
+    connection: &Connection,
+    root_key: &str,
+    dir: &Path,
+    dir_key: &str,
+    child_depth: usize,
+) -> Result<Vec<(PathBuf, Option<i64>)>, UninstallerError> {
+    let mut indexed: HashSet<String> = HashSet::new();
+    {
+        let mut statement = connection
+            .prepare(&format!(
+                "SELECT path FROM {} WHERE parent = ?1",
+                ENTRY_TABLE_NAME
+            ))
+            .map_err(|error| map_sqlite_error("准备读取索引子项失败", error))?;
+        let rows = statement
+            .query_map(params![dir_key], |row| row.get::<usize, String>(0))
+            .map_err(|error| map_sqlite_error("读取索引子项失败", error))?;
+        for row in rows {
+            indexed.insert(row.map_err(|error| map_sqlite_error("读取索引子项行失败", error))?);
+        }
+    }
+
+    let mut child_dirs = Vec::new();
+    let mut present: HashSet<String> = HashSet::new();
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let path_key = path.to_string_lossy().to_string();
+            // 不跟随符号链接，与 WalkDir::follow_links(false) 保持一致
+            let is_dir = entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false);
+            present.insert(path_key.clone());
+
+            if !indexed.contains(&path_key) {
+                let name_lower = entry.file_name().to_string_lossy().to_lowercase();
+                connection
+                    .execute(
+                        &format!(
+                            "INSERT OR REPLACE INTO {} (path, parent, root, name_lower, is_dir, depth, mtime)
+                             VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL)",
+                            ENTRY_TABLE_NAME
+                        ),
+                        params![
+                            path_key,
+                            dir_key,
+                            root_key,
+                            name_lower,
+                            is_dir as i64,
+                            child_depth as i64
+                        ],
+                    )
+                    .map_err(|error| map_sqlite_error("写入路径索引失败", error))?;
+                if is_dir {
+                    child_dirs.push((path, None));
+                }
+            } else if is_dir {
+                let stored_mtime: Option<i64> = connection
+                    .query_row(
+                        &format!("SELECT mtime FROM {} WHERE path = ?1", ENTRY_TABLE_NAME),
+                        params![path_key],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .map_err(|error| map_sqlite_error("读取目录修改时间失败", error))?
+                    .flatten();
+                child_dirs.push((path, stored_mtime));
+            }
+        }
+    }
+
+    // 删除已消失的子项及其整个子树
+    for removed in indexed.difference(&present) {
+        connection
+            .execute(
+                &format!(
+                    "DELETE FROM {} WHERE path = ?1 OR (path >= ?2 AND path < ?3)",
+                    ENTRY_TABLE_NAME
+                ),
+                params![
+                    removed,
+                    subtree_lower_bound(removed),
+                    subtree_upper_bound(removed)
+                ],
+            )
+            .map_err(|error| map_sqlite_error("删除过期路径索引失败", error))?;
+    }
+
+    Ok(child_dirs)
+}
+
+/// 通过索引查找根目录下名称匹配的目录项；索引不可用时返回 `None`，由调用方回退到遍历
+pub fn indexed_matches(root: &Path, max_depth: usize, pattern: &str) -> Option<Vec<PathBuf>> {
+    let result = PathIndex::open().and_then(|mut index| {
+        index.ensure_fresh(root, max_depth)?;
+        index.find_matches(root, pattern)
+    });
+
+    match result {
+        Ok(entries) => Some(entries),
+        Err(error) => {
+            tracing::debug!(
+                "路径索引不可用，回退到目录遍历 {}: {}",
+                root.display(),
+                error
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    const STORAGE_DIR_ENV: &str = "RUST_YU_STORAGE_DIR";
+
+    fn with_storage_root(test_name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "rust-yu-path-index-test-{}-{}",
+            test_name,
+            uuid::Uuid::new_v4()
+        ));
+        let _ = fs::create_dir_all(&root);
+        std::env::set_var(STORAGE_DIR_ENV, &root);
+        root
+    }
+
+    fn cleanup_storage_root(root: &Path) {
+        std::env::remove_var(STORAGE_DIR_ENV);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn refresh_root_tracks_added_and_removed_entries() {
+        let _guard = storage::TEST_STORAGE_ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let storage_root = with_storage_root("incremental");
+        let scan_root = storage_root.join("scan-root");
+        assert!(fs::create_dir_all(scan_root.join("Vendor").join("DemoApp")).is_ok());
+        assert!(fs::create_dir_all(scan_root.join("Other")).is_ok());
+
+        let mut index = PathIndex::open().unwrap_or_else(|_| panic!("open index failed"));
+        assert!(index.refresh_root(&scan_root, 3).is_ok());
+        let matches = index.find_matches(&scan_root, "demo").unwrap_or_default();
+        assert_eq!(matches, vec![scan_root.join("Vendor").join("DemoApp")]);
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(fs::write(scan_root.join("Other").join("demo.log"), b"log").is_ok());
+        assert!(fs::remove_dir_all(scan_root.join("Vendor").join("DemoApp")).is_ok());
+
+        assert!(index.refresh_root(&scan_root, 3).is_ok());
+        let matches = index.find_matches(&scan_root, "demo").unwrap_or_default();
+        assert_eq!(matches, vec![scan_root.join("Other").join("demo.log")]);
+
+        cleanup_storage_root(&storage_root);
+    }
+}
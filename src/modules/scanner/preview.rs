@@ -0,0 +1,393 @@
+//! 痕迹内容预览
+//!
+//! 删除前只读地列出痕迹中的内容，让用户确认里面到底有什么。
+
+use super::models::{Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::uninstaller::signature::{self, SignatureCheck};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use winreg::enums::*;
+use winreg::{RegKey, RegValue};
+
+/// 预览最多列出的值和子项数
+const MAX_PREVIEW_ENTRIES: usize = 200;
+
+/// 二进制数据预览最多显示的字节数
+const MAX_BINARY_PREVIEW_BYTES: usize = 64;
+
+/// 目录预览默认列出的最大子项数
+#[allow(dead_code)]
+pub const DEFAULT_TOP_CHILDREN: usize = 10;
+
+/// 需要校验签名的文件扩展名（可执行文件、驱动、安装包等）
+const SIGNABLE_EXTENSIONS: &[&str] = &["exe", "dll", "sys", "msi", "ocx", "scr"];
+
+/// 文件属性位及其名称
+const FILE_ATTRIBUTE_NAMES: &[(u32, &str)] = &[
+    (0x1, "只读"),
+    (0x2, "隐藏"),
+    (0x4, "系统"),
+    (0x20, "存档"),
+    (0x400, "重解析点"),
+    (0x800, "压缩"),
+    (0x1000, "脱机"),
+    (0x4000, "加密"),
+];
+
+/// 注册表值预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryValuePreview {
+    /// 值名称，默认值为空字符串
+    pub name: String,
+    /// 值类型，如 REG_SZ
+    pub value_type: String,
+    /// 便于阅读的数据内容
+    pub data: String,
+    /// 数据字节数
+    pub size: usize,
+}
+
+/// 直接子项预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrySubkeyPreview {
+    pub name: String,
+    pub subkey_count: usize,
+    pub value_count: usize,
+}
+
+/// 注册表痕迹预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryTracePreview {
+    pub path: String,
+    pub values: Vec<RegistryValuePreview>,
+    pub subkeys: Vec<RegistrySubkeyPreview>,
+    /// 值或子项超过预览上限，只列出了一部分
+    pub truncated: bool,
+}
+
+/// 只读列出注册表痕迹的值和直接子项；值痕迹只列出该值本身
+#[allow(dead_code)]
+pub fn preview_registry_trace(trace: &Trace) -> Result<RegistryTracePreview, UninstallerError> {
+    let (hkey, subpath) = utils::parse_registry_path(&trace.path)
+        .ok_or_else(|| UninstallerError::Registry(format!("无效的注册表路径: {}", trace.path)))?;
+
+    let mut preview = RegistryTracePreview {
+        path: trace.path.clone(),
+        values: Vec::new(),
+        subkeys: Vec::new(),
+        truncated: false,
+    };
+
+    match trace.trace_type {
+        TraceType::RegistryKey => {
+            let key = open_read_only(hkey, subpath, &trace.path)?;
+
+            for (name, value) in key.enum_values().filter_map(|value| value.ok()) {
+                if preview.values.len() >= MAX_PREVIEW_ENTRIES {
+                    preview.truncated = true;
+                    break;
+                }
+                preview.values.push(preview_value(name, &value));
+            }
+
+            for name in key.enum_keys().filter_map(|name| name.ok()) {
+                if preview.subkeys.len() >= MAX_PREVIEW_ENTRIES {
+                    preview.truncated = true;
+                    break;
+                }
+                let info = key
+                    .open_subkey(&name)
+                    .and_then(|child| child.query_info())
+                    .ok();
+                preview.subkeys.push(RegistrySubkeyPreview {
+                    name,
+                    subkey_count: info.as_ref().map_or(0, |info| info.sub_keys as usize),
+                    value_count: info.as_ref().map_or(0, |info| info.values as usize),
+                });
+            }
+        }
+        TraceType::RegistryValue => {
+            let (key_path, value_name) = subpath.rsplit_once('\\').ok_or_else(|| {
+                UninstallerError::Registry(format!("无效的注册表值路径: {}", trace.path))
+            })?;
+            let key = open_read_only(hkey, key_path, &trace.path)?;
+            let value = key.get_raw_value(value_name).map_err(|_| {
+                UninstallerError::NotFound(format!("注册表值不存在: {}", trace.path))
+            })?;
+            preview
+                .values
+                .push(preview_value(value_name.to_string(), &value));
+        }
+        _ => {
+            return Err(UninstallerError::Other(format!(
+                "不是注册表痕迹: {}",
+                trace.path
+            )))
+        }
+    }
+
+    Ok(preview)
+}
+
+/// 目录中的子项及其大小
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChildPreview {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// 文件痕迹预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileTracePreview {
+    pub path: String,
+    pub is_dir: bool,
+    /// 文件大小或目录总大小
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    pub created: Option<DateTime<Utc>>,
+    /// 所有者账户，如 `BUILTIN\Administrators`
+    pub owner: Option<String>,
+    pub attributes: Vec<String>,
+    /// 可执行文件的签名状态
+    pub signature: Option<SignatureCheck>,
+    /// 目录的直接子项数
+    pub child_count: usize,
+    /// 目录中最大的若干子项，按大小降序
+    pub largest_children: Vec<FileChildPreview>,
+}
+
+/// 读取文件或目录痕迹的元数据；目录额外列出最大的 `top_children` 个直接子项
+#[allow(dead_code)]
+pub fn preview_file_trace(
+    trace: &Trace,
+    top_children: usize,
+) -> Result<FileTracePreview, UninstallerError> {
+    if !matches!(
+        trace.trace_type,
+        TraceType::File | TraceType::AppData | TraceType::Shortcut
+    ) {
+        return Err(UninstallerError::Other(format!(
+            "不是文件痕迹: {}",
+            trace.path
+        )));
+    }
+
+    let path = Path::new(&trace.path);
+    let metadata = std::fs::symlink_metadata(path).map_err(|error| match error.kind() {
+        std::io::ErrorKind::NotFound => {
+            UninstallerError::NotFound(format!("文件不存在: {}", trace.path))
+        }
+        _ => UninstallerError::FileSystem(error),
+    })?;
+
+    let mut preview = FileTracePreview {
+        path: trace.path.clone(),
+        is_dir: metadata.is_dir(),
+        size: metadata.len(),
+        modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+        created: metadata.created().ok().map(DateTime::<Utc>::from),
+        owner: file_owner(path),
+        attributes: Vec::new(),
+        signature: None,
+        child_count: 0,
+        largest_children: Vec::new(),
+    };
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        preview.attributes = attribute_names(metadata.file_attributes());
+    }
+
+    if preview.is_dir {
+        let mut children: Vec<FileChildPreview> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let child_path = entry.path();
+                let is_dir = entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false);
+                FileChildPreview {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    is_dir,
+                    size: utils::calculate_dir_size(&child_path).unwrap_or(0),
+                }
+            })
+            .collect();
+
+        preview.child_count = children.len();
+        preview.size = children.iter().map(|child| child.size).sum();
+        children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        children.truncate(top_children);
+        preview.largest_children = children;
+    } else if has_signable_extension(path) {
+        preview.signature = Some(signature::verify_file_signature(path));
+    }
+
+    Ok(preview)
+}
+
+/// 只按扩展名判断，不检查文件内容
+fn has_signable_extension(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| SIGNABLE_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// 将文件属性位转换为可读名称
+fn attribute_names(attributes: u32) -> Vec<String> {
+    FILE_ATTRIBUTE_NAMES
+        .iter()
+        .filter(|(flag, _)| attributes & flag != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+#[cfg(windows)]
+fn file_owner(path: &Path) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::{PCWSTR, PWSTR};
+    use windows::Win32::Foundation::{LocalFree, ERROR_SUCCESS, HLOCAL};
+    use windows::Win32::Security::Authorization::{GetNamedSecurityInfoW, SE_FILE_OBJECT};
+    use windows::Win32::Security::{
+        LookupAccountSidW, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID, SID_NAME_USE,
+    };
+
+    let wide_path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let mut owner_sid = PSID::default();
+    let mut descriptor = PSECURITY_DESCRIPTOR::default();
+
+    // SAFETY: 输出指针在调用期间有效，安全描述符使用完后由 LocalFree 释放
+    unsafe {
+        let result = GetNamedSecurityInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION,
+            Some(&mut owner_sid),
+            None,
+            None,
+            None,
+            &mut descriptor,
+        );
+        if result != ERROR_SUCCESS {
+            return None;
+        }
+
+        let mut name = [0u16; 256];
+        let mut domain = [0u16; 256];
+        let mut name_len = name.len() as u32;
+        let mut domain_len = domain.len() as u32;
+        let mut sid_use = SID_NAME_USE::default();
+        let lookup = LookupAccountSidW(
+            PCWSTR::null(),
+            owner_sid,
+            Some(PWSTR(name.as_mut_ptr())),
+            &mut name_len,
+            Some(PWSTR(domain.as_mut_ptr())),
+            &mut domain_len,
+            &mut sid_use,
+        );
+        let _ = LocalFree(Some(HLOCAL(descriptor.0)));
+        lookup.ok()?;
+
+        let name = String::from_utf16_lossy(&name[..name_len as usize]);
+        let domain = String::from_utf16_lossy(&domain[..domain_len as usize]);
+        Some(if domain.is_empty() {
+            name
+        } else {
+            format!(r"{}\{}", domain, name)
+        })
+    }
+}
+
+#[cfg(not(windows))]
+fn file_owner(_path: &Path) -> Option<String> {
+    None
+}
+
+fn open_read_only(
+    hkey: winreg::HKEY,
+    subpath: &str,
+    path: &str,
+) -> Result<RegKey, UninstallerError> {
+    RegKey::predef(hkey)
+        .open_subkey_with_flags(subpath, KEY_READ)
+        .map_err(|error| match error.kind() {
+            std::io::ErrorKind::NotFound => {
+                UninstallerError::NotFound(format!("注册表项不存在: {}", path))
+            }
+            std::io::ErrorKind::PermissionDenied => {
+                UninstallerError::PermissionDenied(format!("无权读取注册表项: {}", path))
+            }
+            _ => UninstallerError::Registry(error.to_string()),
+        })
+}
+
+fn preview_value(name: String, value: &RegValue) -> RegistryValuePreview {
+    RegistryValuePreview {
+        name,
+        value_type: format!("{:?}", value.vtype),
+        data: format_value_data(value),
+        size: value.bytes.len(),
+    }
+}
+
+/// 字符串和数字按原样显示，其余类型显示为截断的十六进制
+fn format_value_data(value: &RegValue) -> String {
+    match value.vtype {
+        REG_SZ | REG_EXPAND_SZ => value.to_string(),
+        REG_MULTI_SZ => value.to_string().trim_end_matches('\n').replace('\n', "; "),
+        REG_DWORD | REG_QWORD => value.to_string(),
+        _ => {
+            let mut hex = value
+                .bytes
+                .iter()
+                .take(MAX_BINARY_PREVIEW_BYTES)
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<_>>()
+                .join(" ");
+            if value.bytes.len() > MAX_BINARY_PREVIEW_BYTES {
+                hex.push_str(" …");
+            }
+            hex
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_value_data_for_display() {
+        let text: Vec<u8> = "C:\\App\0"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        let value = RegValue {
+            bytes: text,
+            vtype: REG_SZ,
+        };
+        assert_eq!(format_value_data(&value), "C:\\App");
+
+        let value = RegValue {
+            bytes: vec![0xab; MAX_BINARY_PREVIEW_BYTES + 1],
+            vtype: REG_BINARY,
+        };
+        assert!(format_value_data(&value).ends_with("ab …"));
+    }
+
+    #[test]
+    fn names_file_attributes_and_signable_files() {
+        assert_eq!(attribute_names(0x1 | 0x2 | 0x80), vec!["只读", "隐藏"]);
+        assert!(has_signable_extension(Path::new(r"C:\App\Setup.EXE")));
+        assert!(!has_signable_extension(Path::new(r"C:\App\readme.txt")));
+    }
+}
@@ -0,0 +1,343 @@
+//! 其他用户的配置文件
+//!
+//! 默认只扫描当前用户的 AppData 和 HKCU。在共享电脑上清理时，管理员可以扩大范围：
+//! `LoadedProfiles` 额外扫描 ProfileList 中其他用户的 AppData 以及 `HKU` 下已加载的配置单元，
+//! `AllProfiles` 还会临时加载未登录用户的 `NTUSER.DAT`（需要管理员权限），扫描后立即卸载。
+//! 配置单元以 SID 为名加载，痕迹路径与用户登录时相同，清理时按需重新加载。
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::models::Trace;
+use super::ownership::{PROFILE_LIST_KEY, SYSTEM_SIDS};
+use super::{appdata, registry};
+use crate::modules::common::error::UninstallerError;
+
+/// 用户配置单元文件
+const HIVE_FILE_NAME: &str = "NTUSER.DAT";
+
+/// 扫描的用户范围
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProfileScope {
+    /// 只扫描当前用户
+    #[default]
+    CurrentUser,
+    /// 同时扫描其他用户的 AppData 和已加载的注册表配置单元
+    LoadedProfiles,
+    /// 同时临时加载未登录用户的配置单元
+    AllProfiles,
+}
+
+impl ProfileScope {
+    /// 由命令行开关得到扫描范围
+    pub fn from_flags(all_users: bool, load_offline_hives: bool) -> Self {
+        match (all_users, load_offline_hives) {
+            (_, true) => ProfileScope::AllProfiles,
+            (true, false) => ProfileScope::LoadedProfiles,
+            (false, false) => ProfileScope::CurrentUser,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            ProfileScope::CurrentUser => 0,
+            ProfileScope::LoadedProfiles => 1,
+            ProfileScope::AllProfiles => 2,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ProfileScope::LoadedProfiles,
+            2 => ProfileScope::AllProfiles,
+            _ => ProfileScope::CurrentUser,
+        }
+    }
+}
+
+/// 其他用户的配置文件
+#[derive(Debug, Clone)]
+pub struct UserProfile {
+    pub sid: String,
+    pub path: PathBuf,
+}
+
+/// 进程整体的扫描范围
+static PROCESS_SCOPE: AtomicU8 = AtomicU8::new(0);
+
+tokio::task_local! {
+    /// 当前异步操作的扫描范围
+    static TASK_SCOPE: ProfileScope;
+}
+
+/// 当前操作的扫描范围：异步操作的设置优先，其次是进程设置
+pub fn current_scope() -> ProfileScope {
+    TASK_SCOPE
+        .try_with(|scope| *scope)
+        .unwrap_or_else(|_| ProfileScope::from_u8(PROCESS_SCOPE.load(Ordering::Relaxed)))
+}
+
+/// 以指定范围执行异步操作
+#[allow(dead_code)]
+pub async fn scope<F: std::future::Future>(scope: ProfileScope, future: F) -> F::Output {
+    TASK_SCOPE.scope(scope, future).await
+}
+
+/// 整个进程使用的扫描范围，用于命令行
+pub fn set_process_scope(scope: ProfileScope) {
+    PROCESS_SCOPE.store(scope.to_u8(), Ordering::Relaxed);
+}
+
+/// ProfileList 中除当前用户和系统账户外的配置文件
+pub fn other_user_profiles() -> Vec<UserProfile> {
+    let current_profile = std::env::var("USERPROFILE").unwrap_or_default();
+    let Ok(profiles) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(PROFILE_LIST_KEY) else {
+        return Vec::new();
+    };
+
+    profiles
+        .enum_keys()
+        .flatten()
+        .filter(|sid| is_user_sid(sid))
+        .filter_map(|sid| {
+            let image_path: String = profiles
+                .open_subkey(&sid)
+                .and_then(|key| key.get_value("ProfileImagePath"))
+                .ok()?;
+            let path = crate::modules::lister::enrichment::expand_windows_env_vars(&image_path);
+            (!path.eq_ignore_ascii_case(&current_profile) && Path::new(&path).is_dir()).then(|| {
+                UserProfile {
+                    sid,
+                    path: PathBuf::from(path),
+                }
+            })
+        })
+        .collect()
+}
+
+/// 扫描其他用户配置文件中的痕迹，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名
+pub fn scan_other_profile_traces(
+    patterns: &[String],
+    scope: ProfileScope,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    if scope == ProfileScope::CurrentUser {
+        return Ok(());
+    }
+
+    for profile in other_user_profiles() {
+        let app_data = profile.path.join("AppData");
+        for dir in ["Roaming", "Local", "LocalLow"] {
+            let dir = app_data.join(dir);
+            if dir.is_dir() {
+                for pattern in patterns {
+                    appdata::scan_appdata_dir(&dir, pattern, emit);
+                }
+            }
+        }
+
+        // 用户已登录时配置单元已在 HKU 下，否则按范围临时加载
+        let _hive = if hive_loaded(&profile.sid) {
+            None
+        } else if scope == ProfileScope::AllProfiles {
+            match MountedHive::load(&profile.sid, &profile.path.join(HIVE_FILE_NAME)) {
+                Ok(hive) => Some(hive),
+                Err(e) => {
+                    tracing::debug!("加载用户配置单元失败 {}: {}", profile.sid, e);
+                    continue;
+                }
+            }
+        } else {
+            continue;
+        };
+        for pattern in patterns {
+            let root = format!(r"{}\SOFTWARE", profile.sid);
+            if let Err(e) = registry::scan_registry_key(HKEY_USERS, &root, pattern, emit, 0) {
+                tracing::debug!("扫描用户注册表 {} 失败: {}", profile.sid, e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 清理 `HKU\<SID>\...` 痕迹前确保该用户的配置单元已加载
+///
+/// 已加载或路径不属于 HKU 时返回 `None`；返回的守卫释放时卸载配置单元
+pub fn mount_hive_for_path(path: &str) -> Result<Option<MountedHive>, UninstallerError> {
+    let Some(rest) = [r"HKU\", r"HKEY_USERS\"].iter().find_map(|prefix| {
+        path.get(..prefix.len())
+            .filter(|head| head.eq_ignore_ascii_case(prefix))
+            .map(|_| &path[prefix.len()..])
+    }) else {
+        return Ok(None);
+    };
+    let sid = rest.split('\\').next().unwrap_or_default();
+    if !is_user_sid(sid) || hive_loaded(sid) {
+        return Ok(None);
+    }
+
+    let profile = other_user_profiles()
+        .into_iter()
+        .find(|profile| profile.sid.eq_ignore_ascii_case(sid))
+        .ok_or_else(|| UninstallerError::NotFound(format!("找不到用户配置文件: {}", sid)))?;
+    MountedHive::load(sid, &profile.path.join(HIVE_FILE_NAME)).map(Some)
+}
+
+/// 普通用户账户的 SID（排除系统账户、`_Classes` 和 `.bak` 项）
+fn is_user_sid(sid: &str) -> bool {
+    let lower = sid.to_lowercase();
+    lower.starts_with("s-1-")
+        && !SYSTEM_SIDS.contains(&lower.as_str())
+        && !lower.ends_with("_classes")
+        && !lower.ends_with(".bak")
+}
+
+fn hive_loaded(sid: &str) -> bool {
+    RegKey::predef(HKEY_USERS).open_subkey(sid).is_ok()
+}
+
+/// 临时加载到 `HKU\<SID>` 的配置单元，释放时卸载
+pub struct MountedHive {
+    sid: String,
+}
+
+impl MountedHive {
+    /// 加载配置单元，需要备份和还原权限
+    #[cfg(windows)]
+    pub fn load(sid: &str, hive_file: &Path) -> Result<Self, UninstallerError> {
+        use windows::core::HSTRING;
+        use windows::Win32::Foundation::ERROR_SUCCESS;
+        use windows::Win32::Security::{SE_BACKUP_NAME, SE_RESTORE_NAME};
+        use windows::Win32::System::Registry::{RegLoadKeyW, HKEY_USERS};
+
+        if !hive_file.is_file() {
+            return Err(UninstallerError::NotFound(format!(
+                "配置单元文件不存在: {}",
+                hive_file.display()
+            )));
+        }
+        enable_privilege(SE_BACKUP_NAME)?;
+        enable_privilege(SE_RESTORE_NAME)?;
+
+        let status = unsafe {
+            RegLoadKeyW(
+                HKEY_USERS,
+                &HSTRING::from(sid),
+                &HSTRING::from(hive_file.as_os_str()),
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return Err(UninstallerError::Registry(format!(
+                "加载配置单元 {} 失败 (错误 {})，文件可能正被使用",
+                hive_file.display(),
+                status.0
+            )));
+        }
+        tracing::info!("已临时加载用户配置单元: HKU\\{}", sid);
+        Ok(Self {
+            sid: sid.to_string(),
+        })
+    }
+
+    #[cfg(not(windows))]
+    pub fn load(_sid: &str, _hive_file: &Path) -> Result<Self, UninstallerError> {
+        Err(UninstallerError::Other(
+            "仅支持在 Windows 上加载配置单元".to_string(),
+        ))
+    }
+}
+
+impl Drop for MountedHive {
+    fn drop(&mut self) {
+        #[cfg(windows)]
+        {
+            use windows::core::HSTRING;
+            use windows::Win32::Foundation::ERROR_SUCCESS;
+            use windows::Win32::System::Registry::{RegUnLoadKeyW, HKEY_USERS};
+
+            let status = unsafe { RegUnLoadKeyW(HKEY_USERS, &HSTRING::from(self.sid.as_str())) };
+            if status != ERROR_SUCCESS {
+                tracing::warn!(
+                    "卸载用户配置单元 HKU\\{} 失败 (错误 {})",
+                    self.sid,
+                    status.0
+                );
+            }
+        }
+    }
+}
+
+/// 为当前进程启用特权
+#[cfg(windows)]
+fn enable_privilege(name: windows::core::PCWSTR) -> Result<(), UninstallerError> {
+    use windows::Win32::Foundation::{
+        CloseHandle, GetLastError, ERROR_NOT_ALL_ASSIGNED, HANDLE, LUID,
+    };
+    use windows::Win32::Security::{
+        AdjustTokenPrivileges, LookupPrivilegeValueW, LUID_AND_ATTRIBUTES, SE_PRIVILEGE_ENABLED,
+        TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    let denied = |e: windows::core::Error| {
+        UninstallerError::PermissionDenied(format!("启用特权失败，需要管理员权限: {}", e))
+    };
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(
+            GetCurrentProcess(),
+            TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY,
+            &mut token,
+        )
+        .map_err(denied)?;
+
+        let mut luid = LUID::default();
+        let result = LookupPrivilegeValueW(None, name, &mut luid)
+            .and_then(|()| {
+                let privileges = TOKEN_PRIVILEGES {
+                    PrivilegeCount: 1,
+                    Privileges: [LUID_AND_ATTRIBUTES {
+                        Luid: luid,
+                        Attributes: SE_PRIVILEGE_ENABLED,
+                    }],
+                };
+                AdjustTokenPrivileges(token, false, Some(&privileges), 0, None, None)
+            })
+            // 未持有该特权时 AdjustTokenPrivileges 仍返回成功，只设置 ERROR_NOT_ALL_ASSIGNED
+            .map(|()| GetLastError() != ERROR_NOT_ALL_ASSIGNED);
+        let _ = CloseHandle(token);
+        if !result.map_err(denied)? {
+            return Err(UninstallerError::PermissionDenied(
+                "加载其他用户的配置单元需要管理员权限".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_user_sids() {
+        assert!(is_user_sid("S-1-5-21-1004336348-1177238915-682003330-1001"));
+        assert!(is_user_sid("S-1-12-1-1234-5678"));
+        assert!(!is_user_sid("S-1-5-18"));
+        assert!(!is_user_sid(".DEFAULT"));
+        assert!(!is_user_sid("S-1-5-21-1-2-3-1001_Classes"));
+        assert!(!is_user_sid("S-1-5-21-1-2-3-1001.bak"));
+
+        assert_eq!(
+            ProfileScope::from_u8(ProfileScope::AllProfiles.to_u8()),
+            ProfileScope::AllProfiles
+        );
+        assert_eq!(current_scope(), ProfileScope::CurrentUser);
+    }
+}
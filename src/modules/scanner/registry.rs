@@ -1,13 +1,19 @@
-use super::models::{Confidence, Trace, TraceType};
+use super::matching;
+use super::models::{Confidence, RegistryStats, Trace, TraceType};
+use super::options;
 use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
 use winreg::enums::*;
 use winreg::RegKey;
 
-const MAX_DEPTH: u32 = 5;
+/// 统计注册表项内容时最多遍历的项数，超出后停止并标记为不完整
+const MAX_STATS_KEYS: usize = 10_000;
 
-/// 扫描注册表痕迹
-pub fn scan_registry_traces(program_name: &str) -> Result<Vec<Trace>, UninstallerError> {
-    let mut traces = Vec::new();
+/// 扫描注册表痕迹，每发现一项即交给 `emit`
+pub fn scan_registry_traces(
+    program_name: &str,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
     let search_pattern = program_name.to_lowercase();
 
     // 主要搜索路径
@@ -22,26 +28,26 @@ pub fn scan_registry_traces(program_name: &str) -> Result<Vec<Trace>, Uninstalle
     ];
 
     for (hkey, path) in &search_paths {
-        if let Err(e) = scan_registry_key(*hkey, path, &search_pattern, &mut traces, 0) {
+        if let Err(e) = scan_registry_key(*hkey, path, &search_pattern, emit, 0) {
             tracing::debug!("扫描注册表路径 {} 失败: {}", path, e);
         }
     }
 
     // 检查 Uninstall 键中的残留
-    scan_uninstall_keys(program_name, &mut traces);
+    scan_uninstall_keys(program_name, emit);
 
-    Ok(traces)
+    Ok(())
 }
 
-/// 递归扫描注册表键
-fn scan_registry_key(
+/// 递归扫描注册表键，深度按当前扫描选项
+pub(super) fn scan_registry_key(
     hkey: winreg::HKEY,
     path: &str,
     pattern: &str,
-    traces: &mut Vec<Trace>,
+    emit: &mut dyn FnMut(Trace),
     depth: u32,
 ) -> Result<(), UninstallerError> {
-    if depth > MAX_DEPTH {
+    if depth > options::current().registry_depth {
         return Ok(());
     }
 
@@ -52,7 +58,7 @@ fn scan_registry_key(
 
     // 检查当前键名是否匹配
     let key_name = path.split('\\').last().unwrap_or("");
-    if key_name.to_lowercase().contains(pattern) {
+    if matching::name_matches(key_name, pattern) {
         let full_path = format!("{}\\{}", format_hkey(hkey), path);
 
         // 检查是否为 Uninstall 相关键
@@ -74,7 +80,7 @@ fn scan_registry_key(
             .with_description(description)
             .with_confidence(confidence);
 
-        traces.push(trace);
+        emit(trace);
     }
 
     // 枚举子键
@@ -82,14 +88,14 @@ fn scan_registry_key(
         let subpath = format!("{}\\{}", path, name);
 
         // 递归扫描子键
-        let _ = scan_registry_key(hkey, &subpath, pattern, traces, depth + 1);
+        let _ = scan_registry_key(hkey, &subpath, pattern, emit, depth + 1);
     }
 
     Ok(())
 }
 
 /// 扫描 Uninstall 相关键
-fn scan_uninstall_keys(program_name: &str, traces: &mut Vec<Trace>) {
+fn scan_uninstall_keys(program_name: &str, emit: &mut dyn FnMut(Trace)) {
     let search_pattern = program_name.to_lowercase();
 
     let paths = [
@@ -110,7 +116,7 @@ fn scan_uninstall_keys(program_name: &str, traces: &mut Vec<Trace>) {
     for (hkey, path) in &paths {
         if let Ok(key) = RegKey::predef(*hkey).open_subkey(path) {
             for name in key.enum_keys().filter_map(|k| k.ok()) {
-                if name.to_lowercase().contains(&search_pattern) {
+                if matching::name_matches(&name, &search_pattern) {
                     if let Ok(subkey) = key.open_subkey(&name) {
                         let full_path = format!("{}\\{}\\{}", format_hkey(*hkey), path, name);
 
@@ -128,7 +134,7 @@ fn scan_uninstall_keys(program_name: &str, traces: &mut Vec<Trace>) {
                                 ))
                                 .with_confidence(Confidence::High);
 
-                        traces.push(trace);
+                        emit(trace);
                     }
                 }
             }
@@ -137,6 +143,39 @@ fn scan_uninstall_keys(program_name: &str, traces: &mut Vec<Trace>) {
 }
 
 /// 格式化 HKEY 为字符串
+/// 统计注册表项（含所有子项）的子项数、值数和近似数据量，项不存在时返回 `None`
+pub fn measure_registry_key(path: &str) -> Option<RegistryStats> {
+    let (hkey, subpath) = utils::parse_registry_path(path)?;
+    let root = RegKey::predef(hkey).open_subkey(subpath).ok()?;
+
+    let mut stats = RegistryStats::default();
+    let mut pending = vec![root];
+    let mut visited = 0usize;
+
+    while let Some(key) = pending.pop() {
+        visited += 1;
+        if visited > MAX_STATS_KEYS {
+            stats.truncated = true;
+            break;
+        }
+
+        for (name, value) in key.enum_values().filter_map(|value| value.ok()) {
+            stats.value_count += 1;
+            // 值名称按 UTF-16 存储
+            stats.data_size += (name.encode_utf16().count() * 2 + value.bytes.len()) as u64;
+        }
+
+        for name in key.enum_keys().filter_map(|name| name.ok()) {
+            stats.subkey_count += 1;
+            if let Ok(child) = key.open_subkey(&name) {
+                pending.push(child);
+            }
+        }
+    }
+
+    Some(stats)
+}
+
 fn format_hkey(hkey: winreg::HKEY) -> String {
     match hkey {
         HKEY_LOCAL_MACHINE => "HKLM".to_string(),
@@ -0,0 +1,177 @@
+//! 指向安装目录的注册表值
+//!
+//! 名称扫描只比较注册表项名，其他程序或系统组件中记录的 `InstallPath`、命令行等字符串值
+//! 不会被发现，程序卸载后这些值指向已不存在的目录。这里遍历 `SOFTWARE` 下的字符串值，
+//! 数据包含程序安装目录时报告为 `RegistryValue` 痕迹，路径为 `<项>\<值名>`，沿用现有的按值删除。
+//!
+//! 项名本身与程序名匹配的项由名称扫描整项报告；卸载信息、自启动、文件关联、MSI 安装数据和
+//! Defender 排除项由各自的扫描器处理，这里跳过。值名含 `\` 的值（如 `SharedDLLs` 以路径为值名）
+//! 无法编码为痕迹路径，同样跳过。
+
+use winreg::enums::*;
+use winreg::{RegKey, RegValue};
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use super::options;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+
+/// 每个根默认最多遍历的项数，超出后停止，避免大型配置单元拖慢扫描
+const MAX_KEYS_PER_ROOT: usize = 50_000;
+
+/// 由其他扫描器处理或删除值会破坏系统状态的子树（相对于 `SOFTWARE`，小写）
+const SKIPPED_SUBTREES: &[&str] = &[
+    r"classes",
+    r"wow6432node\classes",
+    r"microsoft\windows\currentversion\uninstall",
+    r"wow6432node\microsoft\windows\currentversion\uninstall",
+    r"microsoft\windows\currentversion\run",
+    r"microsoft\windows\currentversion\runonce",
+    r"wow6432node\microsoft\windows\currentversion\run",
+    r"wow6432node\microsoft\windows\currentversion\runonce",
+    r"microsoft\windows\currentversion\installer",
+    r"microsoft\windows\currentversion\app paths",
+    r"microsoft\windows defender",
+    r"policies",
+];
+
+/// 扫描数据指向安装目录的注册表值，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_registry_value_traces(
+    patterns: &[String],
+    install_root: &str,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+    // `SOFTWARE` 以下的遍历深度和项数上限按当前扫描选项
+    let options = options::current();
+    let max_keys = options.max_entries.unwrap_or(MAX_KEYS_PER_ROOT);
+
+    for (hkey, prefix) in [(HKEY_LOCAL_MACHINE, "HKLM"), (HKEY_CURRENT_USER, "HKCU")] {
+        let Ok(software) = RegKey::predef(hkey).open_subkey("SOFTWARE") else {
+            continue;
+        };
+
+        // 深度优先遍历，`relative` 为相对于 SOFTWARE 的路径
+        let mut pending = vec![(software, String::new(), 0u32)];
+        let mut visited = 0usize;
+        while let Some((key, relative, depth)) = pending.pop() {
+            visited += 1;
+            if visited > max_keys {
+                tracing::debug!("{}\\SOFTWARE 项数超过上限，注册表值扫描提前结束", prefix);
+                break;
+            }
+
+            for (name, value) in key.enum_values().filter_map(|value| value.ok()) {
+                let Some(data) = string_data(&value) else {
+                    continue;
+                };
+                if !points_into(&data, install_root) {
+                    continue;
+                }
+                let Some(path) = value_trace_path(prefix, &relative, &name) else {
+                    continue;
+                };
+
+                let mut trace = Trace::new(program_name.clone(), TraceType::RegistryValue, path)
+                    .with_description(format!("注册表值 {} → {}", name, data.trim()))
+                    .with_confidence(Confidence::Medium);
+                trace.set_match_reason("注册表值的数据指向程序安装目录".to_string());
+                emit(trace);
+            }
+
+            if depth >= options.registry_depth {
+                continue;
+            }
+            for name in key.enum_keys().filter_map(|name| name.ok()) {
+                let child = if relative.is_empty() {
+                    name.clone()
+                } else {
+                    format!(r"{}\{}", relative, name)
+                };
+                if is_skipped(&child, &name, patterns) {
+                    continue;
+                }
+                if let Ok(subkey) = key.open_subkey(&name) {
+                    pending.push((subkey, child, depth + 1));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 字符串类值的数据，其余类型返回 None
+fn string_data(value: &RegValue) -> Option<String> {
+    match value.vtype {
+        REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// 数据（路径或命令行）是否指向安装目录或其中的文件
+fn points_into(data: &str, install_root: &str) -> bool {
+    let data = utils::normalize_path(&expand_windows_env_vars(data)).to_lowercase();
+    let root_dir = install_root.trim_end_matches('\\');
+    data.contains(install_root)
+        || data
+            .split(['\n', '"', ';'])
+            .any(|part| part.trim().trim_end_matches('\\') == root_dir)
+}
+
+/// 值痕迹的路径；默认值和值名含 `\` 的值无法按值删除，返回 None
+fn value_trace_path(prefix: &str, relative: &str, name: &str) -> Option<String> {
+    if name.is_empty() || name.contains('\\') {
+        return None;
+    }
+    Some(if relative.is_empty() {
+        format!(r"{}\SOFTWARE\{}", prefix, name)
+    } else {
+        format!(r"{}\SOFTWARE\{}\{}", prefix, relative, name)
+    })
+}
+
+/// 由其他扫描器处理的子树，以及项名与程序名匹配、由名称扫描整项报告的项
+fn is_skipped(relative: &str, name: &str, patterns: &[String]) -> bool {
+    let relative = relative.to_lowercase();
+    SKIPPED_SUBTREES.contains(&relative.as_str())
+        || patterns
+            .iter()
+            .any(|pattern| matching::name_matches(name, pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_values_pointing_into_install_root() {
+        let root = r"d:\apps\acme\";
+        assert!(points_into(r"D:\Apps\Acme", root));
+        assert!(points_into(r#""D:\Apps\Acme\acme.exe" --minimized"#, root));
+        assert!(points_into("D:/Apps/Acme/plugins", root));
+        assert!(!points_into(r"D:\Apps\AcmeTools\tool.exe", root));
+
+        assert_eq!(
+            value_trace_path("HKCU", r"Vendor\Launcher", "InstallPath").as_deref(),
+            Some(r"HKCU\SOFTWARE\Vendor\Launcher\InstallPath")
+        );
+        assert!(value_trace_path("HKLM", "Vendor", "").is_none());
+        assert!(value_trace_path("HKLM", "SharedDLLs", r"d:\apps\acme\a.dll").is_none());
+
+        let patterns = vec!["acme".to_string()];
+        assert!(is_skipped(
+            r"Microsoft\Windows\CurrentVersion\Run",
+            "Run",
+            &patterns
+        ));
+        assert!(is_skipped(r"Acme Corp", "Acme Corp", &patterns));
+        assert!(!is_skipped(r"Vendor\Launcher", "Launcher", &patterns));
+    }
+}
@@ -0,0 +1,299 @@
+//! Windows 自身记录的卸载历史
+//!
+//! rust-yu 的安装历史只覆盖安装本程序之后的变化。更早卸载的软件从 Windows 自己留下的证据中找回：
+//! 应用程序事件日志中 MsiInstaller 的"已删除产品"事件（1034）、`setupapi.dev.log` 中删除驱动包的记录，
+//! 以及用户指定的程序清单快照（如重装前导出的快照）。这些记录只提供名称、发布者和路径线索，
+//! 交给残留搜索按名称扫描，是否仍安装由调用方判断。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::modules::lister::models::{InstallSource, InstalledProgram};
+use crate::modules::lister::snapshot;
+
+/// MsiInstaller 的"已删除产品"事件
+const MSI_PRODUCT_REMOVED_EVENT_ID: u32 = 1034;
+
+/// 驱动安装日志中删除驱动包的段落标题
+const SETUPAPI_REMOVAL_SECTIONS: &[&str] = &["Delete Driver Package", "Uninstall Driver Package"];
+
+/// 卸载证据的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalSource {
+    /// 应用程序事件日志中的 MsiInstaller 事件
+    MsiEventLog,
+    /// `setupapi.dev.log` 驱动安装日志
+    SetupApiLog,
+    /// 程序清单快照
+    Snapshot,
+}
+
+impl RemovalSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RemovalSource::MsiEventLog => "MSI 事件日志",
+            RemovalSource::SetupApiLog => "驱动安装日志",
+            RemovalSource::Snapshot => "程序清单快照",
+        }
+    }
+}
+
+/// 一条卸载记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovalRecord {
+    pub name: String,
+    pub publisher: Option<String>,
+    pub version: Option<String>,
+    /// 卸载时间（RFC 3339），快照无法得知卸载时间
+    pub removed_at: Option<String>,
+    /// 曾经的安装目录或相关文件
+    #[serde(default)]
+    pub paths: Vec<String>,
+    pub source: RemovalSource,
+}
+
+impl RemovalRecord {
+    /// 转换为可供扫描的程序条目
+    pub fn to_program(&self) -> InstalledProgram {
+        let source = match self.source {
+            RemovalSource::MsiEventLog => InstallSource::Msi,
+            _ => InstallSource::Registry,
+        };
+        let mut program = InstalledProgram::new(self.name.clone(), source);
+        program.publisher = self.publisher.clone();
+        program.version = self.version.clone();
+        program.install_location = self.paths.first().cloned();
+        program
+    }
+}
+
+/// 收集 Windows 记录的卸载历史及指定快照中的程序，同名记录只保留最近的一条
+pub fn collect_removal_records(snapshot_paths: &[PathBuf]) -> Vec<RemovalRecord> {
+    let mut records = read_msi_removal_events();
+    records.extend(read_setupapi_removals());
+    for path in snapshot_paths {
+        match snapshot::read_snapshot(path) {
+            Ok(snapshot) => records.extend(snapshot.programs.iter().map(snapshot_record)),
+            Err(e) => tracing::warn!("读取程序清单快照失败 {}: {}", path.display(), e),
+        }
+    }
+
+    // 时间为 None 的排在最后，同名时保留带时间的最近记录
+    records.sort_by(|left, right| right.removed_at.cmp(&left.removed_at));
+    let mut seen = std::collections::HashSet::new();
+    records.retain(|record| seen.insert(record.name.to_lowercase()));
+    records
+}
+
+fn snapshot_record(program: &InstalledProgram) -> RemovalRecord {
+    RemovalRecord {
+        name: program.name.clone(),
+        publisher: program.publisher.clone(),
+        version: program.version.clone(),
+        removed_at: None,
+        paths: program.install_location.iter().cloned().collect(),
+        source: RemovalSource::Snapshot,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MsiEventJson {
+    time: Option<String>,
+    #[serde(default)]
+    properties: Vec<Option<String>>,
+}
+
+/// 读取 MsiInstaller 的"已删除产品"事件
+fn read_msi_removal_events() -> Vec<RemovalRecord> {
+    #[cfg(windows)]
+    {
+        use std::process::Command;
+
+        let script = format!(
+            r#"
+            $events = Get-WinEvent -FilterHashtable @{{LogName='Application'; ProviderName='MsiInstaller'; Id={}}} -ErrorAction SilentlyContinue
+            ConvertTo-Json -Compress -Depth 3 -InputObject @($events | ForEach-Object {{
+                [PSCustomObject]@{{
+                    time = $_.TimeCreated.ToUniversalTime().ToString('o')
+                    properties = @($_.Properties | ForEach-Object {{ [string]$_.Value }})
+                }}
+            }})
+            "#,
+            MSI_PRODUCT_REMOVED_EVENT_ID
+        );
+
+        match Command::new("powershell")
+            .args(["-NoProfile", "-Command", &script])
+            .output()
+        {
+            Ok(output) if output.status.success() => {
+                parse_msi_removal_events(&String::from_utf8_lossy(&output.stdout))
+            }
+            Ok(output) => {
+                tracing::warn!(
+                    "读取 MsiInstaller 事件失败: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Vec::new()
+            }
+            Err(e) => {
+                tracing::warn!("执行 PowerShell 失败: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        Vec::new()
+    }
+}
+
+/// 解析事件 JSON
+///
+/// 1034 事件的参数依次为：产品名称、版本、语言、卸载结果（0 为成功）、制造商
+fn parse_msi_removal_events(json: &str) -> Vec<RemovalRecord> {
+    let events: Vec<MsiEventJson> = serde_json::from_str(json.trim()).unwrap_or_default();
+
+    events
+        .into_iter()
+        .filter_map(|event| {
+            let property = |index: usize| {
+                event
+                    .properties
+                    .get(index)
+                    .cloned()
+                    .flatten()
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            if property(3).is_some_and(|status| status != "0") {
+                return None;
+            }
+            Some(RemovalRecord {
+                name: property(0)?,
+                publisher: property(4),
+                version: property(1),
+                removed_at: event.time.clone(),
+                paths: Vec::new(),
+                source: RemovalSource::MsiEventLog,
+            })
+        })
+        .collect()
+}
+
+/// 读取驱动安装日志中删除的驱动包
+fn read_setupapi_removals() -> Vec<RemovalRecord> {
+    let windows_dir = std::env::var_os("SystemRoot")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\Windows"));
+    let log_path = windows_dir.join("INF").join("setupapi.dev.log");
+
+    match std::fs::read(&log_path) {
+        Ok(content) => parse_setupapi_log(&String::from_utf8_lossy(&content)),
+        Err(e) => {
+            tracing::debug!("读取驱动安装日志失败 {}: {}", log_path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+/// 解析 `setupapi.dev.log`
+///
+/// 每个段落以 `>>>  [标题 - 参数]` 开头，下一行为 `>>>  Section start yyyy/mm/dd hh:mm:ss.fff`。
+/// 系统分配的 `oemNN.inf` 名称不含厂商信息，跳过
+fn parse_setupapi_log(content: &str) -> Vec<RemovalRecord> {
+    let mut records = Vec::new();
+    let mut lines = content.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let Some(header) = line
+            .trim_start()
+            .strip_prefix(">>>")
+            .map(str::trim)
+            .and_then(|header| header.strip_prefix('['))
+            .and_then(|header| header.strip_suffix(']'))
+        else {
+            continue;
+        };
+        let Some((title, argument)) = header.split_once(" - ") else {
+            continue;
+        };
+        if !SETUPAPI_REMOVAL_SECTIONS
+            .iter()
+            .any(|section| title.trim().eq_ignore_ascii_case(section))
+        {
+            continue;
+        }
+
+        let inf_path = argument.trim();
+        let Some(name) = Path::new(inf_path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .filter(|stem| !is_generic_oem_inf(stem))
+        else {
+            continue;
+        };
+
+        let removed_at = lines
+            .peek()
+            .and_then(|next| next.trim_start().strip_prefix(">>>"))
+            .and_then(|next| next.trim().strip_prefix("Section start"))
+            .and_then(|time| {
+                chrono::NaiveDateTime::parse_from_str(time.trim(), "%Y/%m/%d %H:%M:%S%.f").ok()
+            })
+            .map(|time| time.and_utc().to_rfc3339());
+
+        records.push(RemovalRecord {
+            name,
+            publisher: None,
+            version: None,
+            removed_at,
+            paths: vec![inf_path.to_string()],
+            source: RemovalSource::SetupApiLog,
+        });
+    }
+
+    records
+}
+
+fn is_generic_oem_inf(stem: &str) -> bool {
+    stem.len() > 3
+        && stem[..3].eq_ignore_ascii_case("oem")
+        && stem[3..].chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_msi_events_and_setupapi_log() {
+        let json = r#"[
+            {"time":"2023-05-01T08:00:00.0000000Z","properties":["Demo Tool","1.2.0","1033","0","Demo Corp"]},
+            {"time":"2023-05-02T08:00:00.0000000Z","properties":["Failed Tool","1.0","1033","1603","Demo Corp"]}
+        ]"#;
+        let records = parse_msi_removal_events(json);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "Demo Tool");
+        assert_eq!(records[0].publisher.as_deref(), Some("Demo Corp"));
+
+        let log = "\
+>>>  [Delete Driver Package - C:\\Windows\\System32\\DriverStore\\FileRepository\\vendorhid.inf_amd64_1234\\vendorhid.inf]
+>>>  Section start 2023/05/12 10:11:12.345
+<<<  Section end 2023/05/12 10:11:13.000
+>>>  [Delete Driver Package - C:\\Windows\\INF\\oem42.inf]
+>>>  Section start 2023/05/12 10:12:00.000
+>>>  [Device Install (Hardware initiated) - USB\\VID_1234]
+";
+        let records = parse_setupapi_log(log);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "vendorhid");
+        assert_eq!(
+            records[0].removed_at.as_deref(),
+            Some("2023-05-12T10:11:12.345+00:00")
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! 安装目录归属根
+//!
+//! 多个扫描器按"路径是否位于程序安装目录下"判断归属（快捷方式目标、自启动命令、COM 服务器等），
+//! 程序列表的健康检查、进程和套件识别也沿用同一规则。驱动器根目录和 Program Files 这类公共目录
+//! 下的任何东西都会落在其中，不能作为归属根。
+
+use crate::modules::common::utils;
+use crate::modules::lister::models::InstalledProgram;
+
+/// 安装目录（小写并以 `\` 结尾）；驱动器根目录、Program Files 等公共目录不可用于归属
+pub(crate) fn install_root(program: &InstalledProgram) -> Option<String> {
+    directory_root(program.install_location.as_deref()?)
+}
+
+/// 目录的归属根（小写并以 `\` 结尾），规则同 [`install_root`]
+pub(crate) fn directory_root(location: &str) -> Option<String> {
+    let location = utils::normalize_path(location.trim().trim_matches('"'))
+        .trim_end_matches('\\')
+        .to_lowercase();
+
+    let segments = location.split('\\').filter(|s| !s.is_empty()).count();
+    let last = location.rsplit('\\').next().unwrap_or_default();
+    let shared_dir = matches!(
+        last,
+        "program files" | "program files (x86)" | "programdata" | "appdata" | "local" | "roaming"
+    );
+    if segments < 2 || shared_dir || utils::is_system_critical_path(&location) {
+        return None;
+    }
+
+    Some(format!(r"{}\", location))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_and_shallow_directories_are_not_roots() {
+        assert_eq!(
+            directory_root(r#""D:\Apps\Acme\""#).as_deref(),
+            Some(r"d:\apps\acme\")
+        );
+        assert!(directory_root(r"D:\").is_none());
+        assert!(directory_root(r"C:\Program Files").is_none());
+        assert!(directory_root(r"C:\Users\bob\AppData\Roaming").is_none());
+    }
+}
@@ -0,0 +1,254 @@
+//! 痕迹评分
+//!
+//! 各项匹配依据按权重累加为 0–100 的分数，再按阈值划分为高、中、低置信度。
+//! 名称依据（完全一致、按单词包含、末级名称相似）取最好的一项，再加上名称单词的覆盖比例；
+//! 按程序记录扫描时，位于安装目录或卸载程序目录、注册表项名为产品代码、路径中有发布者目录
+//! 作为额外依据叠加。界面按分数排序和设定阈值，CLI 仍按等级筛选。
+
+use super::models::{Confidence, ScoreSource, Trace};
+use super::signals::MatchSignals;
+use super::{matching, metadata};
+use crate::modules::common::utils;
+use crate::modules::lister::models::InstalledProgram;
+
+/// 路径中有与程序名完全一致的名称
+const EXACT_NAME_WEIGHT: u8 = 80;
+
+/// 路径按单词包含程序名
+const NAME_MATCH_WEIGHT: u8 = 50;
+
+/// 程序名单词全部出现在路径中时的加分，部分出现时按比例
+const TOKEN_OVERLAP_WEIGHT: u8 = 15;
+
+/// 位于安装目录或卸载程序所在目录
+const INSTALL_DIR_WEIGHT: u8 = 70;
+
+/// 注册表项名为程序的产品代码
+const PRODUCT_CODE_WEIGHT: u8 = 70;
+
+/// 路径中有发布者目录
+const PUBLISHER_DIR_WEIGHT: u8 = 15;
+
+/// 末级名称相似度达到该值时视为高置信度
+const HIGH_CONFIDENCE_SIMILARITY: f64 = 0.9;
+
+/// 末级名称相似度达到该值时视为中置信度
+const MEDIUM_CONFIDENCE_SIMILARITY: f64 = 0.6;
+
+/// 按程序名、别名和程序记录为痕迹评分
+#[derive(Debug, Clone, Default)]
+pub struct Scorer {
+    /// 小写的程序名及别名，第一项为程序名
+    names: Vec<String>,
+    /// 小写的发布者目录名
+    publisher_dirs: Vec<String>,
+    signals: Option<MatchSignals>,
+}
+
+impl Scorer {
+    pub fn new(names_lower: Vec<String>, program: Option<&InstalledProgram>) -> Self {
+        Scorer {
+            names: names_lower,
+            publisher_dirs: program
+                .map(|program| {
+                    metadata::publisher_dir_names(program.publisher.as_deref())
+                        .into_iter()
+                        .map(|name| name.to_lowercase())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            signals: program.map(MatchSignals::from_program),
+        }
+    }
+
+    /// 计算分数和置信度，并记录命中原因
+    ///
+    /// 安装日志等来源已给出确定的匹配依据（[`ScoreSource::Scanner`]）时，以扫描器给出的置信度为基础分，
+    /// 不再按名称评分
+    pub fn apply(&self, trace: &mut Trace) {
+        let mut reasons = Vec::new();
+        let mut score = match trace.score_source {
+            ScoreSource::Name => {
+                let path_lower = trace.path.to_lowercase();
+                let (name_score, reason) = self.name_score(&path_lower);
+                reasons.push(reason);
+                name_score.saturating_add(self.publisher_score(&path_lower))
+            }
+            ScoreSource::Scanner => {
+                reasons.push(trace.risk.match_reason.clone());
+                trace.score
+            }
+        };
+
+        if let Some(signals) = &self.signals {
+            if let Some(reason) = signals.directory_reason(trace) {
+                score = score.saturating_add(INSTALL_DIR_WEIGHT);
+                reasons.push(reason);
+            }
+            if let Some(reason) = signals.product_code_reason(trace) {
+                score = score.saturating_add(PRODUCT_CODE_WEIGHT);
+                reasons.push(reason);
+            }
+        }
+
+        trace.score = score.min(100);
+        trace.confidence = Confidence::from_score(trace.score);
+        reasons.retain(|reason| !reason.is_empty());
+        trace.risk.match_reason = reasons.join("；");
+    }
+
+    /// 名称依据的分数，取匹配最好的名称
+    fn name_score(&self, path_lower: &str) -> (u8, String) {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let (score, reason) = score_name_match(path_lower, name);
+                let reason = if index == 0 {
+                    reason
+                } else {
+                    format!("{}（别名 {}）", reason, name)
+                };
+                (score, reason)
+            })
+            .max_by_key(|(score, _)| *score)
+            .unwrap_or_default()
+    }
+
+    /// 发布者目录只作为附加依据，不单独构成命中
+    fn publisher_score(&self, path_lower: &str) -> u8 {
+        let has_publisher_dir = path_lower
+            .split(['\\', '/'])
+            .any(|segment| self.publisher_dirs.iter().any(|dir| dir == segment));
+        if has_publisher_dir {
+            PUBLISHER_DIR_WEIGHT
+        } else {
+            0
+        }
+    }
+}
+
+/// 按路径与名称的匹配程度计算分数和匹配原因
+fn score_name_match(path_lower: &str, name_lower: &str) -> (u8, String) {
+    // 检查是否按单词包含程序名
+    let name_match = matching::name_matches(path_lower, name_lower);
+
+    // 检查是否完全匹配
+    let exact_match = path_lower.contains(&format!("\\{} ", name_lower))
+        || path_lower.contains(&format!("/{} ", name_lower))
+        || path_lower.contains(&format!("\\{}.", name_lower));
+
+    // 路径末级名称与程序名的模糊相似度，用于区分前缀命中与中间零散命中
+    let leaf = path_lower
+        .rsplit(['\\', '/'])
+        .find(|part| !part.is_empty())
+        .unwrap_or_default();
+    let similarity = utils::fuzzy_similarity(leaf, name_lower);
+
+    let base = if exact_match {
+        EXACT_NAME_WEIGHT
+    } else if name_match {
+        NAME_MATCH_WEIGHT.max(similarity_score(similarity))
+    } else {
+        similarity_score(similarity)
+    };
+    let score = base.saturating_add(token_overlap_score(path_lower, name_lower));
+
+    let reason = if exact_match {
+        "路径中有与程序名完全一致的名称".to_string()
+    } else if name_match {
+        "路径包含程序名".to_string()
+    } else {
+        format!("末级名称与程序名相似 ({:.0}%)", similarity * 100.0)
+    };
+
+    (score, reason)
+}
+
+/// 相似度换算为分数，阈值处与置信度等级的边界对齐
+fn similarity_score(similarity: f64) -> u8 {
+    let score = if similarity >= HIGH_CONFIDENCE_SIMILARITY {
+        70.0 + (similarity - HIGH_CONFIDENCE_SIMILARITY) * 100.0
+    } else if similarity >= MEDIUM_CONFIDENCE_SIMILARITY {
+        40.0 + (similarity - MEDIUM_CONFIDENCE_SIMILARITY) * 100.0
+    } else {
+        similarity * 60.0
+    };
+    score.round().clamp(0.0, 100.0) as u8
+}
+
+/// 程序名的有效单词在路径中出现的比例
+fn token_overlap_score(path_lower: &str, name_lower: &str) -> u8 {
+    let name_tokens = matching::significant_tokens(name_lower);
+    if name_tokens.is_empty() {
+        return 0;
+    }
+    let path_tokens = matching::tokenize(path_lower);
+    let found = name_tokens
+        .iter()
+        .filter(|token| path_tokens.contains(token))
+        .count();
+    (f64::from(TOKEN_OVERLAP_WEIGHT) * found as f64 / name_tokens.len() as f64).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+    use crate::modules::scanner::models::TraceType;
+
+    fn scored(scorer: &Scorer, trace_type: TraceType, path: &str) -> Trace {
+        let mut trace = Trace::new("Acme Studio".to_string(), trace_type, path.to_string());
+        scorer.apply(&mut trace);
+        trace
+    }
+
+    #[test]
+    fn combines_name_and_program_signals() {
+        let mut program = InstalledProgram::new("Acme Studio".to_string(), InstallSource::Registry);
+        program.publisher = Some("Acme Corp".to_string());
+        program.install_location = Some(r"D:\Tools\xyz".to_string());
+        let scorer = Scorer::new(vec!["acme studio".to_string()], Some(&program));
+
+        let exact = scored(
+            &scorer,
+            TraceType::File,
+            r"C:\Program Files\Acme Studio.exe",
+        );
+        assert_eq!(exact.confidence, Confidence::High);
+
+        // 只命中部分单词时仍为低置信度，但分数高于无关路径，有发布者目录时更高
+        let partial = scored(
+            &scorer,
+            TraceType::AppData,
+            r"C:\Users\a\AppData\Roaming\Acme",
+        );
+        assert_eq!(partial.confidence, Confidence::Low);
+        let publisher = scored(
+            &scorer,
+            TraceType::AppData,
+            r"C:\Users\a\AppData\Roaming\Acme Corp\Studio",
+        );
+        assert!(publisher.score > partial.score);
+
+        let install_dir = scored(&scorer, TraceType::File, r"D:\Tools\xyz\cache");
+        assert_eq!(install_dir.confidence, Confidence::High);
+        assert!(install_dir.risk.match_reason.contains("程序目录"));
+
+        let unrelated = scored(&scorer, TraceType::File, r"C:\Windows\Temp\log.txt");
+        assert_eq!(unrelated.confidence, Confidence::Low);
+        assert!(unrelated.score < partial.score);
+
+        // 扫描器给出的依据保留，以其置信度为基础分
+        let mut preset = Trace::new(
+            "Acme Studio".to_string(),
+            TraceType::RegistryValue,
+            r"HKCU\SOFTWARE\Vendor\InstallPath".to_string(),
+        )
+        .with_confidence(Confidence::Medium);
+        preset.set_match_reason("注册表值的数据指向程序安装目录".to_string());
+        scorer.apply(&mut preset);
+        assert_eq!(preset.confidence, Confidence::Medium);
+        assert_eq!(preset.risk.match_reason, "注册表值的数据指向程序安装目录");
+    }
+}
@@ -1,38 +1,50 @@
+use super::matching;
 use super::models::{Confidence, Trace, TraceType};
+use super::options;
 use crate::modules::common::error::UninstallerError;
-use std::path::Path;
+use crate::modules::common::known_folders;
+use crate::modules::common::shell_link;
+use crate::modules::common::utils;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-/// 扫描快捷方式痕迹
-pub fn scan_shortcut_traces(program_name: &str) -> Result<Vec<Trace>, UninstallerError> {
-    let mut traces = Vec::new();
-    let search_pattern = program_name.to_lowercase();
-
-    // 扫描位置
-    let dirs_to_scan = get_shortcut_dirs();
-
-    for dir in dirs_to_scan {
+/// 扫描快捷方式痕迹，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录。
+/// 快捷方式名称与程序名匹配，或其目标位于安装目录、目标路径与程序名匹配时报告
+pub fn scan_shortcut_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+
+    // 快速启动目录包含任务栏固定目录，同一快捷方式只报告一次
+    let mut seen = HashSet::new();
+    for dir in get_shortcut_dirs() {
         if !dir.exists() {
             continue;
         }
 
-        scan_shortcuts_in_dir(&dir, &search_pattern, &mut traces);
+        scan_shortcuts_in_dir(&dir, program_name, patterns, install_root, &mut seen, emit);
     }
 
-    Ok(traces)
+    Ok(())
 }
 
 /// 获取快捷方式扫描目录
-fn get_shortcut_dirs() -> Vec<std::path::PathBuf> {
+fn get_shortcut_dirs() -> Vec<PathBuf> {
     let mut dirs = Vec::new();
 
-    // 用户桌面
-    if let Some(home) = dirs::home_dir() {
-        let desktop = home.join("Desktop");
-        if desktop.exists() {
-            dirs.push(desktop);
-        }
-    }
+    // 用户桌面，可能已重定向到 OneDrive 或网络位置
+    dirs.extend(
+        known_folders::desktop_dirs()
+            .into_iter()
+            .filter(|desktop| desktop.exists()),
+    );
 
     // 公共桌面
     if let Ok(public) = std::env::var("Public") {
@@ -42,19 +54,32 @@ fn get_shortcut_dirs() -> Vec<std::path::PathBuf> {
         }
     }
 
-    // 开始菜单 - 当前用户
     if let Some(home) = dirs::home_dir() {
-        let start_menu = home
-            .join("AppData")
-            .join("Roaming")
-            .join("Microsoft")
-            .join("Windows")
-            .join("Start Menu")
-            .join("Programs");
+        let roaming = home.join("AppData").join("Roaming").join("Microsoft");
 
+        // 开始菜单 - 当前用户
+        let start_menu = roaming.join("Windows").join("Start Menu").join("Programs");
         if start_menu.exists() {
             dirs.push(start_menu);
         }
+
+        // 任务栏固定项
+        let quick_launch = roaming.join("Internet Explorer").join("Quick Launch");
+        let taskbar = quick_launch.join("User Pinned").join("TaskBar");
+        if taskbar.exists() {
+            dirs.push(taskbar);
+        }
+
+        // 快速启动
+        if quick_launch.exists() {
+            dirs.push(quick_launch);
+        }
+
+        // 发送到菜单
+        let send_to = roaming.join("Windows").join("SendTo");
+        if send_to.exists() {
+            dirs.push(send_to);
+        }
     }
 
     // 开始菜单 - 所有用户
@@ -74,47 +99,152 @@ fn get_shortcut_dirs() -> Vec<std::path::PathBuf> {
 }
 
 /// 在目录中扫描快捷方式
-fn scan_shortcuts_in_dir(dir: &Path, pattern: &str, traces: &mut Vec<Trace>) {
-    let walker = WalkDir::new(dir).max_depth(3).follow_links(false);
-
-    for entry in walker.into_iter().filter_map(|e| e.ok()) {
+fn scan_shortcuts_in_dir(
+    dir: &Path,
+    program_name: &str,
+    patterns: &[String],
+    install_root: Option<&str>,
+    seen: &mut HashSet<PathBuf>,
+    emit: &mut dyn FnMut(Trace),
+) {
+    let options = options::current();
+    let walker = WalkDir::new(dir)
+        .max_depth(options.shortcut_depth)
+        .follow_links(false);
+
+    for entry in walker
+        .into_iter()
+        .take(options.entry_limit())
+        .filter_map(|e| e.ok())
+    {
         let path = entry.path();
 
         // 只处理 .lnk 文件
-        if path.extension().map(|e| e == "lnk").unwrap_or(false) {
-            let name = path
-                .file_stem()
-                .map(|n| n.to_string_lossy().to_lowercase())
-                .unwrap_or_default();
-
-            // 检查名称是否包含搜索模式
-            if name.contains(pattern) {
-                let description = get_shortcut_description(path);
-
-                let confidence = if name.starts_with(pattern) {
-                    Confidence::High
-                } else {
-                    Confidence::Medium
-                };
-
-                let trace = Trace::new(
-                    pattern.to_string(),
-                    TraceType::Shortcut,
-                    path.to_string_lossy().to_string(),
-                )
-                .with_description(description)
-                .with_confidence(confidence);
-
-                traces.push(trace);
-            }
+        let is_link = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"));
+        if !is_link || !seen.insert(path.to_path_buf()) {
+            continue;
         }
+
+        let name = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        // 目标按 LinkInfo、环境变量数据块、相对路径的顺序解析，都没有时交给 Shell
+        let raw_target = shell_link::resolve_target(path).filter(|target| !target.is_empty());
+        let target = raw_target
+            .as_deref()
+            .map(|target| utils::normalize_path(target).to_lowercase());
+
+        let Some(confidence) = match_shortcut(&name, target.as_deref(), patterns, install_root)
+        else {
+            continue;
+        };
+
+        let trace = Trace::new(
+            program_name.to_string(),
+            TraceType::Shortcut,
+            path.to_string_lossy().to_string(),
+        )
+        .with_description(get_shortcut_description(path, raw_target.as_deref()))
+        .with_confidence(confidence);
+
+        emit(trace);
     }
 }
 
-/// 获取快捷方式描述
-fn get_shortcut_description(path: &Path) -> String {
-    // 简单返回文件名
-    path.file_name()
+/// 判断快捷方式是否属于程序：目标位于安装目录时为高置信度，
+/// 否则按名称或目标路径与程序名匹配
+fn match_shortcut(
+    name: &str,
+    target: Option<&str>,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<Confidence> {
+    if let (Some(root), Some(target)) = (install_root, target) {
+        if target.starts_with(root) {
+            return Some(Confidence::High);
+        }
+    }
+
+    // 按单词匹配，避免短名称命中无关快捷方式
+    if let Some(pattern) = patterns
+        .iter()
+        .find(|pattern| matching::name_matches(name, pattern))
+    {
+        return Some(if name.starts_with(pattern.as_str()) {
+            Confidence::High
+        } else {
+            Confidence::Medium
+        });
+    }
+
+    // 名称不同但目标路径与程序名匹配（如 "Code.lnk" 指向 ...\Microsoft VS Code\Code.exe）
+    let target = target.filter(|target| !utils::is_system_critical_path(target))?;
+    patterns
+        .iter()
+        .any(|pattern| matching::name_matches(target, pattern))
+        .then_some(Confidence::Medium)
+}
+
+/// 获取快捷方式描述：文件名及实际目标，目标无法解析时只返回文件名
+fn get_shortcut_description(path: &Path, target: Option<&str>) -> String {
+    let name = path
+        .file_name()
         .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| "快捷方式".to_string())
+        .unwrap_or_else(|| "快捷方式".to_string());
+    match target {
+        Some(target) => format!("{} -> {}", name, target),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_shortcuts_by_name_or_target() {
+        let patterns = vec!["acme studio".to_string(), "acmestudio".to_string()];
+        let root = Some(r"d:\apps\acme\");
+
+        assert_eq!(
+            match_shortcut(
+                "launcher",
+                Some(r"d:\apps\acme\bin\studio.exe"),
+                &patterns,
+                root
+            ),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            match_shortcut("acme studio", None, &patterns, None),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            match_shortcut(
+                "launcher",
+                Some(r"e:\tools\acmestudio\studio.exe"),
+                &patterns,
+                root
+            ),
+            Some(Confidence::Medium)
+        );
+        assert!(match_shortcut(
+            "notepad",
+            Some(r"c:\windows\system32\notepad.exe"),
+            &patterns,
+            root
+        )
+        .is_none());
+
+        assert_eq!(
+            get_shortcut_description(
+                Path::new(r"C:\Users\Public\Desktop\Launcher.lnk"),
+                Some(r"D:\Apps\Acme\studio.exe")
+            ),
+            r"Launcher.lnk -> D:\Apps\Acme\studio.exe"
+        );
+    }
 }
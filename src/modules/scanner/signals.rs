@@ -0,0 +1,139 @@
+//! 程序记录中的匹配依据
+//!
+//! 名称扫描按显示名评分，安装目录不以显示名命名（如 `D:\Tools\xyz`）时，目录中的痕迹
+//! 只能得到中低置信度。按程序记录扫描时，安装目录、卸载程序所在目录和 MSI 产品代码作为
+//! 额外依据：位于这些目录中的文件、路径含产品代码（或其压缩形式）的注册表项由评分叠加为高置信度。
+
+use super::models::{Trace, TraceType};
+use super::roots;
+use crate::modules::common::utils;
+use crate::modules::lister::models::InstalledProgram;
+use crate::modules::lister::msi;
+use crate::modules::uninstaller::validation;
+
+/// 多个程序共用的卸载程序目录，不能作为归属依据
+const SHARED_UNINSTALLER_DIRS: &[&str] = &[r"\common files\", r"\installshield\"];
+
+/// 按程序记录匹配痕迹的依据
+#[derive(Debug, Clone, Default)]
+pub struct MatchSignals {
+    /// 小写并以 `\` 结尾的目录
+    roots: Vec<String>,
+    /// 小写的 `{GUID}` 产品代码及其压缩形式
+    product_codes: Vec<String>,
+}
+
+impl MatchSignals {
+    pub fn from_program(program: &InstalledProgram) -> Self {
+        let mut roots: Vec<String> = roots::install_root(program).into_iter().collect();
+        if let Some(root) = uninstaller_root(program) {
+            if !roots
+                .iter()
+                .any(|existing| root.starts_with(existing.as_str()))
+            {
+                roots.push(root);
+            }
+        }
+
+        let mut product_codes = Vec::new();
+        if let Some(code) = product_code(program) {
+            if let Some(packed) = msi::pack_guid(&code) {
+                product_codes.push(packed.to_lowercase());
+            }
+            product_codes.push(code.to_lowercase());
+        }
+
+        MatchSignals {
+            roots,
+            product_codes,
+        }
+    }
+
+    /// 文件或 AppData 痕迹位于安装目录或卸载程序所在目录时返回原因
+    pub fn directory_reason(&self, trace: &Trace) -> Option<String> {
+        if !matches!(trace.trace_type, TraceType::File | TraceType::AppData) {
+            return None;
+        }
+        let path = utils::normalize_path(&trace.path).to_lowercase();
+        self.roots
+            .iter()
+            .find(|root| path.starts_with(root.as_str()) || format!(r"{}\", path) == **root)
+            .map(|root| format!("位于程序目录 {}", root.trim_end_matches('\\')))
+    }
+
+    /// 注册表项路径中有产品代码（或其压缩形式）时返回原因
+    pub fn product_code_reason(&self, trace: &Trace) -> Option<String> {
+        if trace.trace_type != TraceType::RegistryKey {
+            return None;
+        }
+        let path = utils::normalize_path(&trace.path).to_lowercase();
+        let key = path.split_once('=').map_or(path.as_str(), |(key, _)| key);
+        self.product_codes
+            .iter()
+            .find(|code| key.split('\\').any(|segment| segment == code.as_str()))
+            .map(|_| "注册表项名为程序的产品代码".to_string())
+    }
+}
+
+/// 卸载程序所在目录；系统卸载程序和共用目录不参与
+fn uninstaller_root(program: &InstalledProgram) -> Option<String> {
+    let executable = program
+        .uninstall_string
+        .as_deref()
+        .and_then(validation::parse_executable)?;
+    if !executable.is_absolute() || validation::is_trusted_system_uninstaller(&executable) {
+        return None;
+    }
+    let root = roots::directory_root(&executable.parent()?.to_string_lossy())?;
+    (!SHARED_UNINSTALLER_DIRS
+        .iter()
+        .any(|shared| root.contains(shared)))
+    .then_some(root)
+}
+
+/// MSI 产品代码：取自卸载信息项名或 `msiexec /x{GUID}` 形式的卸载命令
+pub(crate) fn product_code(program: &InstalledProgram) -> Option<String> {
+    let from_key = program
+        .registry_key
+        .as_deref()
+        .and_then(|key| key.rsplit('\\').next())
+        .and_then(utils::find_guid);
+    from_key.or_else(|| {
+        program
+            .uninstall_string
+            .as_deref()
+            .filter(|command| command.to_lowercase().contains("msiexec"))
+            .and_then(utils::find_guid)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn matches_traces_against_program_record() {
+        let mut program = InstalledProgram::new("Acme Studio".to_string(), InstallSource::Registry);
+        program.install_location = Some(r"D:\Tools\xyz".to_string());
+        program.uninstall_string =
+            Some("MsiExec.exe /X{90120000-001F-0409-0000-0000000FF1CE}".to_string());
+        let signals = MatchSignals::from_program(&program);
+
+        let trace =
+            |trace_type, path: &str| Trace::new(program.name.clone(), trace_type, path.to_string());
+
+        assert!(signals
+            .directory_reason(&trace(TraceType::File, r"D:\Tools\xyz\cache"))
+            .is_some());
+        assert!(signals
+            .product_code_reason(&trace(
+                TraceType::RegistryKey,
+                r"HKCR\Installer\Products\00002109F10090400000000000F01FEC"
+            ))
+            .is_some());
+        assert!(signals
+            .directory_reason(&trace(TraceType::File, r"D:\Tools\xyz2\cache"))
+            .is_none());
+    }
+}
@@ -0,0 +1,138 @@
+//! 自启动项残留
+//!
+//! 卸载程序最常漏掉的是 `Run`/`RunOnce` 键中的值和启动文件夹中的快捷方式，
+//! 程序删除后每次登录都会弹出"找不到文件"。命令指向程序安装目录的条目报告为高置信度，
+//! 只有名称或可执行文件名与程序名匹配的报告为中置信度。Run 键中的条目报告为单个注册表值，
+//! 清理时只删除该值，不动 Run 键本身。
+
+use std::path::Path;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::models::{StartupEntry, StartupKind};
+use crate::modules::lister::startup;
+use crate::modules::uninstaller::validation;
+
+/// 扫描指向程序的自启动项，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为 [`super::roots::install_root`] 给出的安装目录
+pub fn scan_startup_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+
+    for entry in startup::collect_autorun_entries() {
+        let Some((confidence, reason)) = match_entry(&entry, patterns, install_root) else {
+            continue;
+        };
+
+        let (trace_type, path, description) = match entry.kind {
+            StartupKind::RunKey => (
+                TraceType::RegistryValue,
+                format!(r"{}\{}", entry.location, entry.name),
+                format!("自启动注册表值: {} → {}", entry.name, entry.command),
+            ),
+            _ => {
+                let is_link = Path::new(&entry.location)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"));
+                (
+                    if is_link {
+                        TraceType::Shortcut
+                    } else {
+                        TraceType::File
+                    },
+                    entry.location.clone(),
+                    format!("启动文件夹: {} → {}", entry.name, entry.command),
+                )
+            }
+        };
+
+        let mut trace = Trace::new(program_name.clone(), trace_type, path)
+            .with_description(description)
+            .with_confidence(confidence);
+        trace.set_match_reason(reason.to_string());
+        emit(trace);
+    }
+
+    Ok(())
+}
+
+/// 判断自启动项是否属于程序，返回置信度和命中原因
+fn match_entry(
+    entry: &StartupEntry,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let executable =
+        validation::parse_executable(&entry.command).map(|path| path.to_string_lossy().to_string());
+
+    if let (Some(root), Some(executable)) = (install_root, executable.as_deref()) {
+        if executable.to_lowercase().starts_with(root) {
+            return Some((Confidence::High, "自启动命令位于程序安装目录"));
+        }
+    }
+
+    // Windows 自带的自启动项（如 SecurityHealth）不按名称归属
+    if executable
+        .as_deref()
+        .is_some_and(utils::is_system_critical_path)
+    {
+        return None;
+    }
+
+    let stem = executable
+        .as_deref()
+        .and_then(|executable| Path::new(executable).file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    patterns
+        .iter()
+        .any(|pattern| {
+            matching::name_matches(&entry.name, pattern)
+                || (!stem.is_empty() && matching::name_matches(&stem, pattern))
+        })
+        .then_some((Confidence::Medium, "自启动项名称或命令与程序名匹配"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, command: &str) -> StartupEntry {
+        StartupEntry {
+            kind: StartupKind::RunKey,
+            name: name.to_string(),
+            command: command.to_string(),
+            location: r"HKCU\SOFTWARE\Microsoft\Windows\CurrentVersion\Run".to_string(),
+        }
+    }
+
+    #[test]
+    fn install_dir_commands_outrank_name_matches() {
+        let patterns = vec!["acme sync".to_string()];
+        let root = Some(r"d:\apps\acme\");
+
+        let tray = entry("Tray", r#""D:\Apps\Acme\tray.exe" /minimized"#);
+        assert_eq!(
+            match_entry(&tray, &patterns, root).map(|(confidence, _)| confidence),
+            Some(Confidence::High)
+        );
+
+        let named = entry("Acme Sync", r"D:\Other\helper.exe");
+        assert_eq!(
+            match_entry(&named, &patterns, root).map(|(confidence, _)| confidence),
+            Some(Confidence::Medium)
+        );
+
+        let unrelated = entry("Tray", r"D:\Apps\AcmeOther\tray.exe");
+        assert!(match_entry(&unrelated, &patterns, root).is_none());
+        assert!(match_entry(&tray, &patterns, None).is_none());
+    }
+}
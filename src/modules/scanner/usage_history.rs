@@ -0,0 +1,266 @@
+//! 使用记录（隐私类痕迹）
+//!
+//! 资源管理器为每种扩展名记录"打开方式"列表（`FileExts\<ext>\OpenWithList`），
+//! 为最近打开的文件记录 `RecentDocs`，任务栏跳转列表保存在 `AutomaticDestinations`
+//! 和 `CustomDestinations` 中。程序卸载后这些条目仍会出现在右键菜单和跳转列表里。
+//! 每个条目报告为一项 `UsageHistory` 痕迹，清理时只从列表中移除该条目，不删除整个注册表项。
+
+use std::path::{Path, PathBuf};
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+
+/// 资源管理器按扩展名记录的打开方式
+pub const FILE_EXTS_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Explorer\FileExts";
+
+/// 最近打开的文件
+pub const RECENT_DOCS_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Explorer\RecentDocs";
+
+/// 字母顺序列表的排序值（OpenWithList）
+pub const MRU_LIST_VALUE: &str = "MRUList";
+
+/// 数字索引列表的排序值（RecentDocs）
+pub const MRU_LIST_EX_VALUE: &str = "MRUListEx";
+
+/// 跳转列表文件的扩展名
+pub const JUMP_LIST_EXTENSIONS: &[&str] = &["automaticdestinations-ms", "customdestinations-ms"];
+
+/// 跳转列表文件的大小上限，超过的文件不读取
+const MAX_JUMP_LIST_SIZE: u64 = 8 * 1024 * 1024;
+
+/// 从跳转列表中提取的字符串最短长度
+const MIN_JUMP_LIST_STRING_LEN: usize = 6;
+
+/// 扫描与程序相关的使用记录，每发现一项即交给 `emit`
+pub fn scan_usage_history_traces(
+    program_name: &str,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let pattern = program_name.to_lowercase();
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+
+    if let Ok(file_exts) = hkcu.open_subkey(FILE_EXTS_KEY) {
+        for ext in file_exts.enum_keys().flatten() {
+            scan_open_with_list(&file_exts, &ext, &pattern, emit);
+        }
+    }
+
+    if let Ok(recent_docs) = hkcu.open_subkey(RECENT_DOCS_KEY) {
+        scan_recent_docs(&recent_docs, RECENT_DOCS_KEY, &pattern, emit);
+        for ext in recent_docs.enum_keys().flatten() {
+            if let Ok(key) = recent_docs.open_subkey(&ext) {
+                let path = format!(r"{}\{}", RECENT_DOCS_KEY, ext);
+                scan_recent_docs(&key, &path, &pattern, emit);
+            }
+        }
+    }
+
+    for dir in jump_list_dirs() {
+        scan_jump_lists(&dir, &pattern, emit);
+    }
+
+    Ok(())
+}
+
+fn scan_open_with_list(file_exts: &RegKey, ext: &str, pattern: &str, emit: &mut dyn FnMut(Trace)) {
+    let key_path = format!(r"{}\OpenWithList", ext);
+    let Ok(key) = file_exts.open_subkey(&key_path) else {
+        return;
+    };
+
+    for (name, _) in key.enum_values().flatten() {
+        if name.eq_ignore_ascii_case(MRU_LIST_VALUE) {
+            continue;
+        }
+        let Ok(executable) = key.get_value::<String, _>(&name) else {
+            continue;
+        };
+        let stem = Path::new(&executable)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !matching::name_matches(&stem, pattern) {
+            continue;
+        }
+
+        let path = format!(r"HKCU\{}\{}\{}", FILE_EXTS_KEY, key_path, name);
+        emit(usage_trace(
+            pattern,
+            path,
+            format!("打开方式列表: {} → {}", ext, executable),
+            Confidence::High,
+            "打开方式中的可执行文件名与程序名匹配",
+        ));
+    }
+}
+
+fn scan_recent_docs(key: &RegKey, key_path: &str, pattern: &str, emit: &mut dyn FnMut(Trace)) {
+    for (name, value) in key.enum_values().flatten() {
+        if name.eq_ignore_ascii_case(MRU_LIST_EX_VALUE) {
+            continue;
+        }
+        let Some(document) = recent_doc_name(&value.bytes) else {
+            continue;
+        };
+        if !matching::name_matches(&document, pattern) {
+            continue;
+        }
+
+        let path = format!(r"HKCU\{}\{}", key_path, name);
+        emit(usage_trace(
+            pattern,
+            path,
+            format!("最近打开的文件: {}", document),
+            Confidence::Medium,
+            "最近打开的文件名与程序名匹配",
+        ));
+    }
+}
+
+fn scan_jump_lists(dir: &Path, pattern: &str, emit: &mut dyn FnMut(Trace)) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if !is_jump_list_file(&path) {
+            continue;
+        }
+        let Ok(metadata) = path.metadata() else {
+            continue;
+        };
+        if metadata.len() > MAX_JUMP_LIST_SIZE {
+            continue;
+        }
+        let Ok(content) = std::fs::read(&path) else {
+            continue;
+        };
+
+        let Some(executable) = referenced_executables(&content)
+            .into_iter()
+            .find(|executable| matching::name_matches(executable, pattern))
+        else {
+            continue;
+        };
+
+        let trace = usage_trace(
+            pattern,
+            path.to_string_lossy().to_string(),
+            format!("跳转列表: {}", executable),
+            Confidence::Medium,
+            "跳转列表引用了与程序名匹配的可执行文件",
+        )
+        .with_size(metadata.len());
+        emit(trace);
+    }
+}
+
+fn usage_trace(
+    pattern: &str,
+    path: String,
+    description: String,
+    confidence: Confidence,
+    reason: &str,
+) -> Trace {
+    let mut trace = Trace::new(pattern.to_string(), TraceType::UsageHistory, path)
+        .with_description(description)
+        .with_confidence(confidence);
+    trace.set_match_reason(reason.to_string());
+    trace
+}
+
+/// 当前用户的跳转列表目录
+pub fn jump_list_dirs() -> Vec<PathBuf> {
+    let Some(recent) =
+        dirs::data_dir().map(|dir| dir.join("Microsoft").join("Windows").join("Recent"))
+    else {
+        return Vec::new();
+    };
+    vec![
+        recent.join("AutomaticDestinations"),
+        recent.join("CustomDestinations"),
+    ]
+}
+
+/// 是否为跳转列表文件
+pub fn is_jump_list_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| JUMP_LIST_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// RecentDocs 条目开头是以 NUL 结尾的 UTF-16 文件名，其后是外壳项数据
+fn recent_doc_name(bytes: &[u8]) -> Option<String> {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    let name = String::from_utf16_lossy(&units);
+    (!name.trim().is_empty()).then_some(name)
+}
+
+/// 跳转列表中引用的可执行文件路径
+///
+/// 跳转列表是复合文档，其中的快捷方式以 UTF-16 保存目标路径；这里不解析结构，
+/// 只提取以 `.exe` 结尾的 UTF-16 字符串
+fn referenced_executables(content: &[u8]) -> Vec<String> {
+    let mut executables = Vec::new();
+    for offset in [0, 1] {
+        let mut current = Vec::new();
+        let units = content
+            .get(offset..)
+            .unwrap_or_default()
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+        for unit in units.chain(std::iter::once(0)) {
+            match char::from_u32(u32::from(unit)) {
+                Some(c) if unit != 0 && !c.is_control() => current.push(unit),
+                _ => {
+                    if current.len() >= MIN_JUMP_LIST_STRING_LEN {
+                        let text = String::from_utf16_lossy(&current);
+                        if text.to_lowercase().ends_with(".exe") && !executables.contains(&text) {
+                            executables.push(text);
+                        }
+                    }
+                    current.clear();
+                }
+            }
+        }
+    }
+    executables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn decodes_recent_docs_and_jump_list_strings() {
+        let mut value = utf16("Vendor Report.vdoc");
+        value.extend([0, 0, 0x14, 0x00, 0x1f, 0x50]);
+        assert_eq!(
+            recent_doc_name(&value).as_deref(),
+            Some("Vendor Report.vdoc")
+        );
+
+        let mut content = vec![0xd0, 0xcf, 0x11, 0xe0, 0, 0];
+        content.extend(utf16(r"C:\Program Files\Vendor\vendor.exe"));
+        content.extend([0, 0, 1, 2, 3]);
+        assert_eq!(
+            referenced_executables(&content),
+            vec![r"C:\Program Files\Vendor\vendor.exe".to_string()]
+        );
+        assert!(is_jump_list_file(Path::new(
+            "5f7b5f1e01b83767.automaticDestinations-ms"
+        )));
+    }
+}
@@ -0,0 +1,109 @@
+//! 用户创建内容分类
+//!
+//! 游戏存档、文档目录下的子文件夹、导出的配置档等由用户产生，卸载程序后通常仍需保留。
+//! 按位置和名称把这类痕迹归为单独的类别，清理全部时默认排除。
+
+use super::models::{Trace, TraceCategory, TraceType};
+use crate::modules::common::known_folders;
+
+/// 视为用户内容的目录名（路径中的任一段）
+const USER_CONTENT_SEGMENTS: &[&str] = &[
+    "saved games",
+    "my games",
+    "savegames",
+    "savedgames",
+    "saves",
+    "savedata",
+    "screenshots",
+    "exports",
+    "backups",
+];
+
+/// 视为导出档或存档的文件扩展名
+const USER_CONTENT_EXTENSIONS: &[&str] = &["sav", "save", "profile", "export"];
+
+/// 对痕迹分类，使用记录归为隐私类，只有文件类痕迹可能属于用户内容
+pub fn classify_trace(trace: &Trace) -> TraceCategory {
+    if trace.trace_type == TraceType::UsageHistory {
+        return TraceCategory::Privacy;
+    }
+    if !matches!(trace.trace_type, TraceType::File | TraceType::AppData) {
+        return TraceCategory::Program;
+    }
+
+    let roots: Vec<String> = user_content_roots()
+        .into_iter()
+        .map(|root| root.to_lowercase())
+        .collect();
+
+    if is_user_content(&trace.path, &roots) {
+        TraceCategory::UserContent
+    } else {
+        TraceCategory::Program
+    }
+}
+
+/// 用户内容所在的根目录：文档、存档目录
+fn user_content_roots() -> Vec<String> {
+    let mut roots: Vec<String> = known_folders::documents_dirs()
+        .into_iter()
+        .map(|documents| documents.to_string_lossy().to_string())
+        .collect();
+    if let Some(documents) = dirs::document_dir() {
+        roots.push(documents.to_string_lossy().to_string());
+    }
+    if let Ok(profile) = std::env::var("USERPROFILE") {
+        roots.push(format!(r"{}\Documents", profile));
+        roots.push(format!(r"{}\Saved Games", profile));
+    }
+    roots
+}
+
+/// 路径位于用户内容根目录之下，或包含存档类目录名、扩展名
+fn is_user_content(path: &str, roots_lower: &[String]) -> bool {
+    let path_lower = path.replace('/', "\\").to_lowercase();
+
+    let under_root = roots_lower.iter().any(|root| {
+        path_lower
+            .strip_prefix(root.trim_end_matches('\\'))
+            .is_some_and(|rest| rest.len() > 1 && rest.starts_with('\\'))
+    });
+    if under_root {
+        return true;
+    }
+
+    let mut segments = path_lower.split('\\').filter(|s| !s.is_empty());
+    if segments
+        .clone()
+        .any(|segment| USER_CONTENT_SEGMENTS.contains(&segment))
+    {
+        return true;
+    }
+
+    segments
+        .next_back()
+        .and_then(|leaf| leaf.rsplit_once('.'))
+        .is_some_and(|(_, ext)| USER_CONTENT_EXTENSIONS.contains(&ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_saves_documents_and_exports() {
+        let roots = vec![r"c:\users\alice\documents".to_string()];
+
+        assert!(is_user_content(r"C:\Users\Alice\Documents\FooGame", &roots));
+        assert!(is_user_content(
+            r"C:\Users\Alice\AppData\Roaming\FooGame\Saves",
+            &roots
+        ));
+        assert!(is_user_content(
+            r"C:\Users\Alice\AppData\Roaming\Foo\settings.profile",
+            &roots
+        ));
+        assert!(!is_user_content(r"C:\Users\Alice\Documents", &roots));
+        assert!(!is_user_content(r"C:\Program Files\Foo\bin", &roots));
+    }
+}
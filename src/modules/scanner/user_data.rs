@@ -0,0 +1,102 @@
+//! 用户数据启发式检查
+//!
+//! 程序目录里常混有用户自己保存的文档或存档。名称匹配的文件或目录如果包含这类文件，
+//! 或最近刚被修改过，就标记为可能含有用户数据，降低置信度并要求显式确认后才删除。
+
+use super::models::{Trace, TraceType};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// 视为用户文档的扩展名
+const USER_DOCUMENT_EXTENSIONS: &[&str] = &[
+    "doc", "docx", "xls", "xlsx", "ppt", "pptx", "pdf", "odt", "ods", "psd", "ai", "sav", "kdbx",
+];
+
+/// 在该时间范围内修改过的文件视为近期使用
+const RECENT_MODIFICATION_WINDOW: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// 目录检查的深度上限
+const MAX_DEPTH: usize = 4;
+
+/// 目录检查最多查看的条目数，避免大目录拖慢扫描
+const MAX_ENTRIES: usize = 2000;
+
+/// 检查痕迹是否可能包含用户数据，返回原因：文件类痕迹按内容判断，凭据总是视为用户数据
+pub fn detect_user_data(trace: &Trace) -> Option<String> {
+    // 凭据删除后需要重新登录，始终要求单独确认
+    if trace.trace_type == TraceType::Credential {
+        return Some("保存的登录凭据，删除后需要重新登录".to_string());
+    }
+    if !matches!(trace.trace_type, TraceType::File | TraceType::AppData) {
+        return None;
+    }
+
+    let path = Path::new(&trace.path);
+    let now = SystemTime::now();
+
+    if path.is_file() {
+        return check_file(path, now);
+    }
+
+    WalkDir::new(path)
+        .max_depth(MAX_DEPTH)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .take(MAX_ENTRIES)
+        .filter(|entry| entry.file_type().is_file())
+        .find_map(|entry| check_file(entry.path(), now))
+}
+
+/// 检查单个文件的扩展名和修改时间
+fn check_file(path: &Path, now: SystemTime) -> Option<String> {
+    if is_user_document(path) {
+        return Some(format!("包含用户文档: {}", path.display()));
+    }
+
+    let modified = path.metadata().and_then(|m| m.modified()).ok()?;
+    if is_recently_modified(modified, now) {
+        return Some(format!("近期修改过: {}", path.display()));
+    }
+
+    None
+}
+
+/// 是否为用户文档扩展名
+fn is_user_document(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| USER_DOCUMENT_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// 修改时间是否落在近期窗口内
+fn is_recently_modified(modified: SystemTime, now: SystemTime) -> bool {
+    match now.duration_since(modified) {
+        Ok(elapsed) => elapsed < RECENT_MODIFICATION_WINDOW,
+        // 修改时间晚于当前时间（时钟偏差），同样视为近期
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_user_documents_by_extension() {
+        assert!(is_user_document(Path::new(r"C:\Games\Foo\saves\slot1.SAV")));
+        assert!(is_user_document(Path::new("report.docx")));
+        assert!(!is_user_document(Path::new("foo.dll")));
+    }
+
+    #[test]
+    fn recent_modification_window() {
+        let now = SystemTime::now();
+        assert!(is_recently_modified(now - Duration::from_secs(60), now));
+        assert!(!is_recently_modified(
+            now - RECENT_MODIFICATION_WINDOW - Duration::from_secs(1),
+            now
+        ));
+    }
+}
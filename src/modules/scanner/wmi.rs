@@ -0,0 +1,277 @@
+//! WMI 提供程序残留
+//!
+//! 监控、驱动和管理类软件常在 `root\cimv2` 下注册 WMI 提供程序（`__Win32Provider` 实例），
+//! 其 CLSID 的 `InprocServer32` 指向程序的 DLL；安装时编译的 MOF 文件还会登记在
+//! `Wbem\CIMOM` 的 `Autorecover MOFs` 列表中，WMI 仓库重建时会重新编译。卸载后这些注册常被留下，
+//! 每次查询相关类都会尝试加载已不存在的 DLL。
+//!
+//! 提供程序 DLL 位于程序安装目录、路径与程序名匹配，或 DLL 已不存在且提供程序名称与程序名匹配时
+//! 报告为 `WmiProvider` 痕迹，路径形如 `WMI\root\cimv2\__Win32Provider.Name="<名称>"`；
+//! `Autorecover MOFs` 中指向程序的条目报告为 `...\CIMOM\Autorecover MOFs=<MOF 路径>`，
+//! 清理时只从列表中移除该条目。
+
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::matching;
+use super::models::{Confidence, Trace, TraceType};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+
+/// 扫描的 WMI 命名空间
+pub const WMI_NAMESPACE: &str = r"root\cimv2";
+
+/// 提供程序痕迹路径前缀
+const PROVIDER_PATH_PREFIX: &str = r"WMI\root\cimv2\__Win32Provider.Name=";
+
+/// 记录需要自动重新编译的 MOF 文件（HKLM）
+pub const CIMOM_KEY: &str = r"SOFTWARE\Microsoft\Wbem\CIMOM";
+
+/// `CIMOM` 下的 MOF 列表值（REG_MULTI_SZ）
+pub const AUTORECOVER_MOFS_VALUE: &str = "Autorecover MOFs";
+
+/// 提供程序 CLSID 所在位置
+const CLSID_KEYS: &[&str] = &[
+    r"SOFTWARE\Classes\CLSID",
+    r"SOFTWARE\Classes\WOW6432Node\CLSID",
+];
+
+/// `Get-CimInstance` 输出的提供程序
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ProviderJson {
+    name: Option<String>,
+    #[serde(rename = "CLSID")]
+    clsid: Option<String>,
+}
+
+/// 扫描程序留下的 WMI 提供程序和自动恢复 MOF 条目，每发现一项即交给 `emit`
+///
+/// `patterns` 为小写的程序名及别名，`install_root` 为小写并以 `\` 结尾的安装目录
+pub fn scan_wmi_traces(
+    patterns: &[String],
+    install_root: Option<&str>,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(program_name) = patterns.first() else {
+        return Ok(());
+    };
+    let mut emit_trace = |path: String, description: String, hit: (Confidence, &str)| {
+        let mut trace = Trace::new(program_name.clone(), TraceType::WmiProvider, path)
+            .with_description(description)
+            .with_confidence(hit.0);
+        trace.set_match_reason(hit.1.to_string());
+        emit(trace);
+    };
+
+    for provider in query_providers() {
+        let (Some(name), Some(clsid)) = (provider.name, provider.clsid) else {
+            continue;
+        };
+        let dll = provider_dll(&clsid);
+        let Some(hit) = match_provider(&name, dll.as_deref(), patterns, install_root) else {
+            continue;
+        };
+        emit_trace(
+            provider_trace_path(&name),
+            format!(
+                "WMI 提供程序 {} ({}): {}",
+                name,
+                clsid,
+                dll.as_deref().unwrap_or("未注册 DLL")
+            ),
+            hit,
+        );
+    }
+
+    let mofs: Vec<String> = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(CIMOM_KEY)
+        .and_then(|key| key.get_value(AUTORECOVER_MOFS_VALUE))
+        .unwrap_or_default();
+    for mof in mofs {
+        let Some(hit) = match_mof(&mof, patterns, install_root) else {
+            continue;
+        };
+        emit_trace(
+            format!(r"HKLM\{}\{}={}", CIMOM_KEY, AUTORECOVER_MOFS_VALUE, mof),
+            format!("WMI 自动恢复 MOF: {}", mof),
+            hit,
+        );
+    }
+
+    Ok(())
+}
+
+/// 提供程序痕迹的路径
+pub fn provider_trace_path(name: &str) -> String {
+    format!("{}\"{}\"", PROVIDER_PATH_PREFIX, name)
+}
+
+/// 从痕迹路径中取出提供程序名称，不是提供程序路径时返回 `None`
+pub fn parse_provider_path(path: &str) -> Option<&str> {
+    let name = path
+        .strip_prefix(PROVIDER_PATH_PREFIX)?
+        .strip_prefix('"')?
+        .strip_suffix('"')?;
+    (!name.trim().is_empty() && !name.contains('"')).then_some(name)
+}
+
+/// 从痕迹路径中取出自动恢复 MOF 条目，不是该列表中的条目时返回 `None`
+pub fn parse_mof_path(path: &str) -> Option<&str> {
+    let prefix = format!(r"HKLM\{}\{}=", CIMOM_KEY, AUTORECOVER_MOFS_VALUE);
+    let mof = strip_prefix_ignore_case(path, &prefix)?;
+    (!mof.trim().is_empty()).then_some(mof)
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    (text.len() >= prefix.len() && text.is_char_boundary(prefix.len()))
+        .then(|| text.split_at(prefix.len()))
+        .filter(|(head, _)| head.eq_ignore_ascii_case(prefix))
+        .map(|(_, rest)| rest)
+}
+
+/// 枚举 `root\cimv2` 下注册的提供程序
+fn query_providers() -> Vec<ProviderJson> {
+    let script = format!(
+        "ConvertTo-Json -Compress -InputObject @(Get-CimInstance -Namespace '{}' \
+         -ClassName __Win32Provider -ErrorAction SilentlyContinue | Select-Object Name, CLSID)",
+        WMI_NAMESPACE
+    );
+    match Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            serde_json::from_str(String::from_utf8_lossy(&output.stdout).trim()).unwrap_or_default()
+        }
+        Ok(output) => {
+            tracing::warn!(
+                "枚举 WMI 提供程序失败: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            Vec::new()
+        }
+        Err(e) => {
+            tracing::warn!("执行 PowerShell 失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 提供程序 CLSID 的 `InprocServer32`（已展开环境变量，小写）
+fn provider_dll(clsid: &str) -> Option<String> {
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    CLSID_KEYS.iter().find_map(|clsids| {
+        let server: String = hklm
+            .open_subkey(format!(r"{}\{}\InprocServer32", clsids, clsid.trim()))
+            .and_then(|key| key.get_value(""))
+            .ok()?;
+        let dll = utils::normalize_path(&expand_windows_env_vars(server.trim().trim_matches('"')))
+            .to_lowercase();
+        (!dll.is_empty()).then_some(dll)
+    })
+}
+
+/// 判断提供程序是否属于程序，返回置信度和命中原因
+fn match_provider(
+    name: &str,
+    dll: Option<&str>,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    // 系统自带的提供程序都在系统目录，不能据此判断归属
+    if let Some(dll) = dll {
+        if utils::is_system_critical_path(dll) {
+            return None;
+        }
+        if install_root.is_some_and(|root| dll.starts_with(root)) {
+            return Some((Confidence::High, "WMI 提供程序的 DLL 位于程序安装目录"));
+        }
+        if patterns
+            .iter()
+            .any(|pattern| matching::name_matches(dll, pattern))
+        {
+            return Some((Confidence::Medium, "WMI 提供程序的 DLL 路径与程序名匹配"));
+        }
+    }
+
+    let name_matches = patterns
+        .iter()
+        .any(|pattern| matching::name_matches(name, pattern));
+    (name_matches && dll.is_none_or(|dll| !Path::new(dll).exists())).then_some((
+        Confidence::Medium,
+        "WMI 提供程序的 DLL 已不存在，提供程序名称与程序名匹配",
+    ))
+}
+
+/// 判断自动恢复 MOF 条目是否属于程序
+fn match_mof(
+    mof: &str,
+    patterns: &[String],
+    install_root: Option<&str>,
+) -> Option<(Confidence, &'static str)> {
+    let file = utils::normalize_path(&expand_windows_env_vars(mof.trim())).to_lowercase();
+    if file.is_empty() || utils::is_system_critical_path(&file) {
+        return None;
+    }
+    if install_root.is_some_and(|root| file.starts_with(root)) {
+        return Some((Confidence::High, "自动恢复 MOF 位于程序安装目录"));
+    }
+    patterns
+        .iter()
+        .any(|pattern| matching::name_matches(&file, pattern))
+        .then_some((Confidence::Medium, "自动恢复 MOF 路径与程序名匹配"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_providers_and_parses_paths() {
+        let patterns = vec!["acme".to_string()];
+        let root = Some(r"d:\apps\acme\");
+
+        assert_eq!(
+            match_provider(
+                "AcmeMonitor",
+                Some(r"d:\apps\acme\wmiprov.dll"),
+                &patterns,
+                root
+            )
+            .map(|m| m.0),
+            Some(Confidence::High)
+        );
+        assert_eq!(
+            match_provider("Acme.WmiProvider", None, &patterns, root).map(|m| m.0),
+            Some(Confidence::Medium)
+        );
+        assert!(match_provider(
+            "CIMWin32",
+            Some(r"c:\windows\system32\wbem\cimwin32.dll"),
+            &patterns,
+            root
+        )
+        .is_none());
+        assert_eq!(
+            match_mof(r"D:\Apps\Acme\acme.mof", &patterns, root).map(|m| m.0),
+            Some(Confidence::High)
+        );
+
+        let path = provider_trace_path("AcmeMonitor");
+        assert_eq!(parse_provider_path(&path), Some("AcmeMonitor"));
+        assert!(parse_provider_path(r#"WMI\root\cimv2\__Win32Provider.Name="""#).is_none());
+        assert_eq!(
+            parse_mof_path(
+                r"HKLM\SOFTWARE\Microsoft\Wbem\CIMOM\Autorecover MOFs=d:\apps\acme\acme.mof"
+            ),
+            Some(r"d:\apps\acme\acme.mof")
+        );
+        assert!(parse_mof_path(r"HKLM\SOFTWARE\Microsoft\Wbem\CIMOM").is_none());
+    }
+}
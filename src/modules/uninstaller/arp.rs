@@ -0,0 +1,123 @@
+//! 失效的"程序和功能"条目
+//!
+//! 卸载程序已被删除的条目无法再从系统设置中卸载，却会一直显示在应用列表里。
+//! 强制移除或残留清理完成后删除其 Uninstall 注册表项，删除前整项导出到隔离区。
+
+use super::validation;
+use crate::modules::cleaner::{quarantine, reg_export::RegFileBuilder};
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::models::InstalledProgram;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use winreg::RegKey;
+
+/// 备份文件名
+const REG_FILE_NAME: &str = "uninstall_entry.reg";
+
+/// 删除条目的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArpEntryRemoval {
+    pub registry_key: String,
+    /// 导出的 .reg 文件
+    pub backup_path: String,
+}
+
+/// 条目是否已失效：卸载程序和安装目录都已不存在
+pub fn is_broken_entry(program: &InstalledProgram) -> bool {
+    let Some(key) = program.registry_key.as_deref() else {
+        return false;
+    };
+    if !is_uninstall_key(key) {
+        return false;
+    }
+
+    let Some(uninstall_string) = program.uninstall_string.as_deref() else {
+        return false;
+    };
+    let Some(executable) = validation::validate_uninstall_string(uninstall_string).executable
+    else {
+        return false;
+    };
+
+    let executable = Path::new(&executable);
+    // MSI 等系统卸载程序始终存在，条目是否有效由安装程序自身判断
+    if validation::is_trusted_system_uninstaller(executable) || !executable.is_absolute() {
+        return false;
+    }
+
+    let location_exists = program
+        .install_location
+        .as_deref()
+        .map(|location| location.trim().trim_matches('"'))
+        .filter(|location| !location.is_empty())
+        .is_some_and(|location| Path::new(location).exists());
+
+    !executable.exists() && !location_exists
+}
+
+/// 是否为 `...\CurrentVersion\Uninstall\<条目>` 形式的注册表项
+pub fn is_uninstall_key(key: &str) -> bool {
+    let lower = key.to_lowercase();
+    lower
+        .split_once(r"\microsoft\windows\currentversion\uninstall\")
+        .is_some_and(|(_, entry)| !entry.is_empty() && !entry.contains('\\'))
+}
+
+/// 导出并删除程序的 Uninstall 注册表项，条目不存在时返回 None
+pub fn remove_arp_entry(
+    program: &InstalledProgram,
+) -> Result<Option<ArpEntryRemoval>, UninstallerError> {
+    let key = program
+        .registry_key
+        .as_deref()
+        .ok_or_else(|| UninstallerError::NotFound(format!("{} 没有注册表条目", program.name)))?;
+
+    if !is_uninstall_key(key) {
+        return Err(UninstallerError::CriticalSystemItem(format!(
+            "不是卸载条目: {}",
+            key
+        )));
+    }
+
+    let (hkey, subpath) = utils::parse_registry_path(key)
+        .ok_or_else(|| UninstallerError::Registry(format!("无效的注册表路径: {}", key)))?;
+
+    let mut reg_file = RegFileBuilder::new();
+    if !reg_file.push_key_recursive(key) {
+        return Ok(None);
+    }
+
+    let archive_dir = quarantine::create_entry_dir(&program.name, "arp")?;
+    let backup_path = archive_dir.join(REG_FILE_NAME);
+    reg_file.write_to(&backup_path)?;
+
+    RegKey::predef(hkey)
+        .delete_subkey_all(subpath)
+        .map_err(|error| UninstallerError::Registry(error.to_string()))?;
+    tracing::info!("已删除失效的卸载条目: {}", key);
+
+    Ok(Some(ArpEntryRemoval {
+        registry_key: key.to_string(),
+        backup_path: backup_path.to_string_lossy().to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_uninstall_entries_qualify() {
+        assert!(is_uninstall_key(
+            r"HKLM\SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall\Foo"
+        ));
+        assert!(!is_uninstall_key(
+            r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall"
+        ));
+        assert!(!is_uninstall_key(
+            r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\Foo\Sub"
+        ));
+        assert!(!is_uninstall_key(r"HKLM\SOFTWARE\Foo"));
+    }
+}
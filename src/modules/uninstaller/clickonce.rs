@@ -0,0 +1,154 @@
+//! ClickOnce 应用支持
+//!
+//! ClickOnce 应用按用户部署在 `%LOCALAPPDATA%\Apps\2.0` 下的混淆目录中，卸载项的命令形如
+//! `rundll32.exe dfshim.dll,ShArpMaintain Foo.application, Culture=neutral, PublicKeyToken=...`。
+//! 部署目录名由缩短的程序名和公钥标记组成（如 `foot..tion_1234567890abcdef_0001.0000_...`），
+//! 据此定位卸载后残留的部署目录与清单文件。
+
+use std::path::{Path, PathBuf};
+
+use crate::modules::common::error::UninstallerError;
+
+/// 部署目录相对 LOCALAPPDATA 的位置
+const APPS_ROOT: &str = r"Apps\2.0";
+
+/// 部署目录的最大深度：`<随机>\<随机>\<程序目录>` 以及 `Data\<随机>\<随机>\<程序目录>`
+const MAX_DEPLOYMENT_DEPTH: usize = 4;
+
+/// 清理联机应用缓存的命令
+pub const CLEAN_CACHE_COMMAND: &str = "rundll32.exe dfshim.dll,CleanOnlineAppCache";
+
+/// 从卸载命令中解析出的 ClickOnce 应用标识
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClickOnceIdentity {
+    /// 部署清单名称，如 `Foo.application`
+    pub name: String,
+    /// 公钥标记（小写），未签名的应用为全 0
+    pub public_key_token: Option<String>,
+}
+
+/// 是否为 ClickOnce 的卸载命令
+pub fn is_clickonce_command(uninstall_string: &str) -> bool {
+    uninstall_string.to_lowercase().contains("dfshim")
+}
+
+/// 解析 `ShArpMaintain` 后的应用标识
+pub fn parse_identity(uninstall_string: &str) -> Option<ClickOnceIdentity> {
+    let lower = uninstall_string.to_lowercase();
+    let start = lower.find("sharpmaintain")? + "sharpmaintain".len();
+    let mut parts = uninstall_string[start..].split(',');
+
+    let name = parts.next()?.trim().trim_matches('"').to_string();
+    if name.is_empty() {
+        return None;
+    }
+
+    let public_key_token = parts.find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("PublicKeyToken")
+            .then(|| value.trim().trim_matches('"').to_lowercase())
+    });
+
+    Some(ClickOnceIdentity {
+        name,
+        public_key_token,
+    })
+}
+
+/// `%LOCALAPPDATA%\Apps\2.0`
+pub fn apps_root() -> Option<PathBuf> {
+    std::env::var("LOCALAPPDATA")
+        .ok()
+        .map(|dir| PathBuf::from(dir).join(APPS_ROOT))
+}
+
+/// 查找应用的部署目录和清单文件，命中的目录不再向下遍历
+pub fn find_deployment_entries(identity: &ClickOnceIdentity) -> Vec<PathBuf> {
+    let Some(root) = apps_root().filter(|root| root.is_dir()) else {
+        return Vec::new();
+    };
+
+    let mut found = Vec::new();
+    collect_deployment_entries(&root, identity, 1, &mut found);
+    found
+}
+
+fn collect_deployment_entries(
+    dir: &Path,
+    identity: &ClickOnceIdentity,
+    depth: usize,
+    found: &mut Vec<PathBuf>,
+) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if matches_deployment_name(&name, identity) {
+            found.push(path);
+        } else if depth < MAX_DEPLOYMENT_DEPTH && path.is_dir() {
+            collect_deployment_entries(&path, identity, depth + 1, found);
+        }
+    }
+}
+
+/// 部署目录名以程序名前 4 个字符开头，并包含 `_<公钥标记>_`
+fn matches_deployment_name(entry_name: &str, identity: &ClickOnceIdentity) -> bool {
+    let Some(token) = identity.public_key_token.as_deref() else {
+        return false;
+    };
+    let entry_name = entry_name.to_lowercase();
+    let prefix: String = identity.name.to_lowercase().chars().take(4).collect();
+
+    entry_name.starts_with(&prefix) && entry_name.contains(&format!("_{}_", token))
+}
+
+/// 清理已不在卸载列表中的联机 ClickOnce 应用缓存
+pub fn clean_online_app_cache() -> Result<(), UninstallerError> {
+    #[cfg(windows)]
+    {
+        let status = std::process::Command::new("cmd")
+            .args(["/C", CLEAN_CACHE_COMMAND])
+            .status()?;
+        if !status.success() {
+            return Err(UninstallerError::Other(format!(
+                "清理 ClickOnce 应用缓存失败: {}",
+                status
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_identity_and_matches_deployment_dirs() {
+        let uninstall = "rundll32.exe dfshim.dll,ShArpMaintain FooTool.application, \
+                         Culture=neutral, PublicKeyToken=1A2B3C4D5E6F7081, processorArchitecture=msil";
+        assert!(is_clickonce_command(uninstall));
+
+        let identity = parse_identity(uninstall).unwrap();
+        assert_eq!(identity.name, "FooTool.application");
+        assert_eq!(
+            identity.public_key_token.as_deref(),
+            Some("1a2b3c4d5e6f7081")
+        );
+
+        assert!(matches_deployment_name(
+            "foot..tion_1a2b3c4d5e6f7081_0001.0002_0123456789abcdef",
+            &identity
+        ));
+        assert!(!matches_deployment_name(
+            "bart..tion_1a2b3c4d5e6f7081_0001.0002_0123456789abcdef",
+            &identity
+        ));
+        assert!(!matches_deployment_name("ZX4CQ2LM.TKD", &identity));
+    }
+}
@@ -0,0 +1,130 @@
+//! 按安装程序技术准备卸载命令
+//!
+//! 不同打包工具的静默参数和退出码约定不同，这里统一处理，避免把需要重启的成功卸载当成失败。
+
+use super::squirrel;
+use crate::modules::lister::models::InstallerKind;
+use serde::{Deserialize, Serialize};
+
+/// 卸载程序退出码的含义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExitOutcome {
+    Success,
+    /// 卸载成功，需要重启完成
+    RebootRequired,
+    /// 产品已不存在
+    AlreadyRemoved,
+    /// 用户取消了卸载
+    Cancelled,
+    Failed,
+}
+
+/// 各安装程序技术的静默卸载参数，没有可靠参数时返回 None
+pub fn silent_args(kind: InstallerKind) -> Option<&'static str> {
+    match kind {
+        InstallerKind::Msi | InstallerKind::WixBurn => Some("/quiet /norestart"),
+        InstallerKind::Nsis => Some("/S"),
+        InstallerKind::InnoSetup => Some("/VERYSILENT /SUPPRESSMSGBOXES /NORESTART"),
+        // Squirrel 的 --uninstall 本身即静默；InstallShield 需要录制的应答文件；
+        // ClickOnce 的维护对话框没有静默模式
+        InstallerKind::Squirrel
+        | InstallerKind::InstallShield
+        | InstallerKind::ClickOnce
+        | InstallerKind::Appx
+        | InstallerKind::Unknown => None,
+    }
+}
+
+/// 生成实际执行的卸载命令
+///
+/// MSI 始终静默执行，并把安装/修复模式的 `/I` 换成卸载的 `/X`；
+/// Squirrel 统一改写为 `Update.exe --uninstall`；其他技术仅在 `silent` 时追加静默参数，命令中已带有相同参数时不再重复
+pub fn prepare_uninstall_command(
+    uninstall_string: &str,
+    kind: InstallerKind,
+    silent: bool,
+) -> String {
+    let command = uninstall_string.trim();
+    let lower = command.to_lowercase();
+    let is_msiexec = lower.starts_with("msiexec");
+
+    if kind == InstallerKind::Msi || is_msiexec {
+        let command = match lower.find("/i{") {
+            Some(index) if is_msiexec => {
+                format!("{}/X{}", &command[..index], &command[index + 2..])
+            }
+            _ => command.to_string(),
+        };
+        return append_args(&command, "/quiet /norestart");
+    }
+
+    if kind == InstallerKind::Squirrel {
+        if let Some(command) = squirrel::uninstall_command(command, silent) {
+            return command;
+        }
+    }
+
+    match silent_args(kind) {
+        Some(args) if silent => append_args(command, args),
+        _ => command.to_string(),
+    }
+}
+
+/// 解释卸载程序的退出码
+pub fn classify_exit_code(kind: InstallerKind, code: i32) -> ExitOutcome {
+    match (kind, code) {
+        (_, 0) => ExitOutcome::Success,
+        (InstallerKind::Msi | InstallerKind::WixBurn, 3010 | 1641) => ExitOutcome::RebootRequired,
+        (InstallerKind::Msi | InstallerKind::WixBurn, 1605 | 1614) => ExitOutcome::AlreadyRemoved,
+        (InstallerKind::Msi | InstallerKind::WixBurn, 1602) => ExitOutcome::Cancelled,
+        // NSIS 和 Inno Setup 的卸载程序被用户取消时返回 1 / 5
+        (InstallerKind::Nsis, 1) | (InstallerKind::InnoSetup, 5) => ExitOutcome::Cancelled,
+        _ => ExitOutcome::Failed,
+    }
+}
+
+/// 追加参数，命令中已包含第一个参数时视为已设置
+fn append_args(command: &str, args: &str) -> String {
+    let first = args.split_whitespace().next().unwrap_or_default();
+    let already_set = command
+        .split_whitespace()
+        .any(|part| part.eq_ignore_ascii_case(first));
+    if already_set {
+        command.to_string()
+    } else {
+        format!("{} {}", command, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepares_silent_commands_per_installer() {
+        assert_eq!(
+            prepare_uninstall_command("MsiExec.exe /I{ABC}", InstallerKind::Msi, false),
+            "MsiExec.exe /X{ABC} /quiet /norestart"
+        );
+        assert_eq!(
+            prepare_uninstall_command(r#""C:\Foo\uninst.exe""#, InstallerKind::Nsis, true),
+            r#""C:\Foo\uninst.exe" /S"#
+        );
+        assert_eq!(
+            prepare_uninstall_command(r#""C:\Foo\uninst.exe" /S"#, InstallerKind::Nsis, true),
+            r#""C:\Foo\uninst.exe" /S"#
+        );
+        assert_eq!(
+            prepare_uninstall_command(r#""C:\Foo\uninst.exe""#, InstallerKind::Nsis, false),
+            r#""C:\Foo\uninst.exe""#
+        );
+        assert_eq!(
+            classify_exit_code(InstallerKind::Msi, 3010),
+            ExitOutcome::RebootRequired
+        );
+        assert_eq!(
+            classify_exit_code(InstallerKind::Nsis, 3010),
+            ExitOutcome::Failed
+        );
+    }
+}
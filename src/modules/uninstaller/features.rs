@@ -0,0 +1,65 @@
+//! 启用、禁用 Windows 功能
+//!
+//! 通过 DISM 执行，命令与程序列表中功能项的卸载命令一致。
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::lister::features::WindowsFeature;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// DISM 退出码：操作成功但需要重启
+const EXIT_RESTART_REQUIRED: i32 = 3010;
+
+/// 功能状态变更结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureChange {
+    pub name: String,
+    pub enabled: bool,
+    /// 需要重启才能完成
+    pub restart_required: bool,
+}
+
+/// 启用或禁用功能，需要管理员权限
+pub fn set_feature_state(
+    feature: &WindowsFeature,
+    enable: bool,
+) -> Result<FeatureChange, UninstallerError> {
+    let command = if enable {
+        feature.enable_command()
+    } else {
+        feature.disable_command()
+    };
+    tracing::info!("执行 DISM 命令: {}", command);
+
+    // 命令由固定格式拼接，按空白拆分即可得到参数
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or("dism.exe");
+    let output = Command::new(program).args(parts).output()?;
+
+    let code = output.status.code().unwrap_or(-1);
+    if code != 0 && code != EXIT_RESTART_REQUIRED {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // DISM 把错误信息写到标准输出
+        let message = stdout
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .unwrap_or_default()
+            .to_string();
+        if code == 740 {
+            return Err(UninstallerError::PermissionDenied(
+                "修改 Windows 功能需要管理员权限".to_string(),
+            ));
+        }
+        return Err(UninstallerError::Other(format!(
+            "DISM 执行失败 (退出码 {}): {}",
+            code, message
+        )));
+    }
+
+    Ok(FeatureChange {
+        name: feature.name.clone(),
+        enabled: enable,
+        restart_required: code == EXIT_RESTART_REQUIRED,
+    })
+}
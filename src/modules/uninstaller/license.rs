@@ -0,0 +1,258 @@
+//! 授权与激活数据备份
+//!
+//! 卸载前在程序的注册表项和目录中查找授权、序列号等激活数据，导出到隔离区：
+//! 注册表值写成可直接导入的 `.reg` 文件，授权文件原样复制，重新安装后即可恢复激活状态。
+
+use crate::modules::cleaner::quarantine;
+use crate::modules::cleaner::reg_export::RegFileBuilder;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::scanner::models::{Trace, TraceType};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use walkdir::WalkDir;
+use winreg::{RegKey, RegValue};
+
+/// 注册表值名包含这些关键字时视为授权数据
+const LICENSE_VALUE_KEYWORDS: &[&str] = &[
+    "license",
+    "licence",
+    "serial",
+    "activation",
+    "productkey",
+    "product key",
+    "regkey",
+    "regcode",
+    "registration",
+];
+
+/// 授权文件扩展名（不含 .txt，安装目录中的 LICENSE.txt 通常只是许可协议）
+const LICENSE_FILE_EXTENSIONS: &[&str] = &["lic"];
+
+/// 授权文件名
+const LICENSE_FILE_NAMES: &[&str] = &[
+    "key.dat",
+    "license.dat",
+    "licence.dat",
+    "activation.dat",
+    "serial.dat",
+    "license.key",
+    "licence.key",
+];
+
+/// 注册表递归深度上限
+const MAX_REGISTRY_DEPTH: usize = 3;
+
+/// 目录遍历深度与条目上限
+const MAX_FILE_DEPTH: usize = 4;
+const MAX_FILE_ENTRIES: usize = 5000;
+
+/// 导出的 .reg 文件名
+const REG_FILE_NAME: &str = "license.reg";
+
+/// 授权数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LicenseItemKind {
+    File,
+    RegistryValue,
+}
+
+/// 一项授权数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseItem {
+    pub kind: LicenseItemKind,
+    /// 文件路径或注册表项路径
+    pub source: String,
+    /// 注册表值名，文件为 None
+    pub value_name: Option<String>,
+    /// 在备份目录中的相对路径
+    pub archived_as: Option<String>,
+}
+
+/// 一次备份的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseBackup {
+    pub program_name: String,
+    pub created_at: String,
+    pub archive_dir: String,
+    pub items: Vec<LicenseItem>,
+}
+
+/// 在痕迹涉及的注册表项和目录中查找授权数据
+pub fn find_license_data(traces: &[Trace]) -> Vec<LicenseItem> {
+    let mut items = Vec::new();
+
+    for trace in traces {
+        match trace.trace_type {
+            TraceType::RegistryKey => {
+                if let Some((hkey, subpath)) = utils::parse_registry_path(&trace.path) {
+                    if let Ok(key) = RegKey::predef(hkey).open_subkey(subpath) {
+                        collect_registry_values(&key, &trace.path, 0, &mut items);
+                    }
+                }
+            }
+            TraceType::File | TraceType::AppData => collect_files(&trace.path, &mut items),
+            _ => {}
+        }
+    }
+
+    // 不同痕迹可能互相包含，去掉重复项
+    let mut seen = std::collections::HashSet::new();
+    items.retain(|item| {
+        seen.insert(format!(
+            "{}|{}",
+            item.source.to_lowercase(),
+            item.value_name
+                .as_deref()
+                .unwrap_or_default()
+                .to_lowercase()
+        ))
+    });
+    items
+}
+
+/// 查找并导出授权数据到隔离区，未找到时返回 None
+pub fn backup_license_data(
+    program_name: &str,
+    traces: &[Trace],
+) -> Result<Option<LicenseBackup>, UninstallerError> {
+    let mut items = find_license_data(traces);
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    let archive_dir = quarantine::create_entry_dir(program_name, "license")?;
+    let mut reg_file = RegFileBuilder::new();
+
+    for (index, item) in items.iter_mut().enumerate() {
+        match item.kind {
+            LicenseItemKind::File => {
+                let source = Path::new(&item.source);
+                let file_name = source
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "license".to_string());
+                // 加序号避免不同目录下的同名文件互相覆盖
+                let archived_as = format!("files/{}_{}", index + 1, file_name);
+                let target = archive_dir.join(&archived_as);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                match std::fs::copy(source, &target) {
+                    Ok(_) => item.archived_as = Some(archived_as),
+                    Err(e) => tracing::warn!("备份授权文件失败 {}: {}", item.source, e),
+                }
+            }
+            LicenseItemKind::RegistryValue => {
+                let Some((name, value)) = read_registry_value(item) else {
+                    continue;
+                };
+                reg_file.push_value(&item.source, &name, &value);
+                item.archived_as = Some(REG_FILE_NAME.to_string());
+            }
+        }
+    }
+
+    if !reg_file.is_empty() {
+        reg_file.write_to(&archive_dir.join(REG_FILE_NAME))?;
+    }
+
+    let backup = LicenseBackup {
+        program_name: program_name.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        archive_dir: archive_dir.to_string_lossy().to_string(),
+        items,
+    };
+    let manifest = serde_json::to_string_pretty(&backup)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    std::fs::write(archive_dir.join("manifest.json"), manifest)?;
+
+    Ok(Some(backup))
+}
+
+/// 递归收集名称像授权数据的注册表值
+fn collect_registry_values(key: &RegKey, path: &str, depth: usize, items: &mut Vec<LicenseItem>) {
+    for (name, _) in key.enum_values().filter_map(|value| value.ok()) {
+        if is_license_value_name(&name) {
+            items.push(LicenseItem {
+                kind: LicenseItemKind::RegistryValue,
+                source: path.to_string(),
+                value_name: Some(name),
+                archived_as: None,
+            });
+        }
+    }
+
+    if depth >= MAX_REGISTRY_DEPTH {
+        return;
+    }
+
+    for child in key.enum_keys().filter_map(|name| name.ok()) {
+        let child_path = format!(r"{}\{}", path, child);
+        // 子项名本身像授权数据时（如 ...\License），导出其全部值
+        if let Ok(child_key) = key.open_subkey(&child) {
+            if is_license_value_name(&child) {
+                for (name, _) in child_key.enum_values().filter_map(|value| value.ok()) {
+                    items.push(LicenseItem {
+                        kind: LicenseItemKind::RegistryValue,
+                        source: child_path.clone(),
+                        value_name: Some(name),
+                        archived_as: None,
+                    });
+                }
+            }
+            collect_registry_values(&child_key, &child_path, depth + 1, items);
+        }
+    }
+}
+
+/// 在文件或目录中查找授权文件
+fn collect_files(path: &str, items: &mut Vec<LicenseItem>) {
+    for entry in WalkDir::new(path)
+        .max_depth(MAX_FILE_DEPTH)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .take(MAX_FILE_ENTRIES)
+        .filter(|entry| entry.file_type().is_file())
+    {
+        if is_license_file(entry.path()) {
+            items.push(LicenseItem {
+                kind: LicenseItemKind::File,
+                source: entry.path().to_string_lossy().to_string(),
+                value_name: None,
+                archived_as: None,
+            });
+        }
+    }
+}
+
+fn is_license_value_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    LICENSE_VALUE_KEYWORDS
+        .iter()
+        .any(|keyword| lower.contains(keyword))
+}
+
+fn is_license_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if LICENSE_FILE_NAMES.contains(&name.as_str()) {
+        return true;
+    }
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| LICENSE_FILE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// 读取授权项对应的注册表值
+fn read_registry_value(item: &LicenseItem) -> Option<(String, RegValue)> {
+    let (hkey, subpath) = utils::parse_registry_path(&item.source)?;
+    let key = RegKey::predef(hkey).open_subkey(subpath).ok()?;
+    let name = item.value_name.clone()?;
+    let value = key.get_raw_value(&name).ok()?;
+    Some((name, value))
+}
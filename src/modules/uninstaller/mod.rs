@@ -0,0 +1,12 @@
+pub mod arp;
+pub mod clickonce;
+pub mod command;
+pub mod features;
+pub mod license;
+pub mod package_cache;
+pub mod sandbox;
+pub mod signature;
+pub mod simulation;
+pub mod squirrel;
+pub mod store;
+pub mod validation;
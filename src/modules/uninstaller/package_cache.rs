@@ -0,0 +1,170 @@
+//! 遗留的安装包缓存
+//!
+//! WiX Burn 捆绑包以及经其安装的 MSI 会把安装包复制到 `%ProgramData%\Package Cache\<代码>`，
+//! 目录名为捆绑包代码或 `{产品代码}v<版本>`。厂商卸载程序经常不删除这些目录，
+//! 卸载完成后按程序记录的原始安装包位置和产品代码找出仍存在的缓存目录，由用户确认后删除。
+//! 程序仍已安装时修复和卸载都依赖这些缓存，因此只在卸载项已消失后报告。
+
+use std::path::PathBuf;
+
+use winreg::RegKey;
+
+use crate::modules::common::utils;
+use crate::modules::lister::models::InstalledProgram;
+use crate::modules::scanner::models::{Confidence, Trace, TraceType};
+use crate::modules::scanner::signals;
+use crate::modules::watcher::install_log;
+
+/// `%ProgramData%` 下的安装包缓存目录名
+pub const PACKAGE_CACHE_DIR_NAME: &str = "Package Cache";
+
+/// 安装包缓存根目录
+pub fn package_cache_root() -> Option<PathBuf> {
+    std::env::var("ProgramData")
+        .ok()
+        .map(|program_data| PathBuf::from(program_data).join(PACKAGE_CACHE_DIR_NAME))
+}
+
+/// 程序的卸载信息项是否仍然存在；没有记录注册表项时无法判断，按已卸载处理
+pub fn is_still_installed(program: &InstalledProgram) -> bool {
+    program
+        .registry_key
+        .as_deref()
+        .and_then(utils::parse_registry_path)
+        .is_some_and(|(hkey, path)| RegKey::predef(hkey).open_subkey(path).is_ok())
+}
+
+/// 程序遗留的安装包缓存目录，作为文件痕迹返回
+///
+/// 原始安装包位置取自程序记录，缺失时取安装监视记录的位置
+pub fn cached_installer_traces(program: &InstalledProgram) -> Vec<Trace> {
+    let Some(root) = package_cache_root() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&root) else {
+        return Vec::new();
+    };
+    let entries: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+
+    let mut sources: Vec<String> = program.installer_source.iter().cloned().collect();
+    if sources.is_empty() {
+        if let Ok(Some(log)) = install_log::get_install_log(&program.name) {
+            sources.extend(log.installer_source);
+        }
+    }
+    let product_code = signals::product_code(program);
+
+    matching_entries(
+        &root.to_string_lossy(),
+        &entries,
+        &sources,
+        product_code.as_deref(),
+    )
+    .into_iter()
+    .map(|entry| {
+        let path = root.join(&entry);
+        let mut trace = Trace::new(
+            program.name.clone(),
+            TraceType::File,
+            path.to_string_lossy().to_string(),
+        )
+        .with_description("卸载程序遗留的安装包缓存".to_string())
+        .with_confidence(Confidence::High);
+        trace.size = utils::calculate_dir_size(&path).ok();
+        trace.set_match_reason("安装包缓存目录与程序的安装来源或产品代码一致".to_string());
+        trace
+    })
+    .collect()
+}
+
+/// 缓存根目录下属于程序的目录名：原始安装包位于其中，或目录名以产品代码开头
+fn matching_entries(
+    root: &str,
+    entries: &[String],
+    sources: &[String],
+    product_code: Option<&str>,
+) -> Vec<String> {
+    let root = normalize(root);
+    let source_entries: Vec<String> = sources
+        .iter()
+        .filter_map(|source| {
+            let source = normalize(source);
+            let relative = source.strip_prefix(&root)?.strip_prefix('\\')?;
+            relative
+                .split('\\')
+                .next()
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+        })
+        .collect();
+    let product_code = product_code.map(str::to_lowercase);
+
+    entries
+        .iter()
+        .filter(|entry| {
+            let entry = entry.to_lowercase();
+            source_entries.contains(&entry)
+                || product_code
+                    .as_deref()
+                    .is_some_and(|code| entry.starts_with(code))
+        })
+        .cloned()
+        .collect()
+}
+
+/// 小写、统一分隔符并去掉末尾分隔符和引号
+fn normalize(path: &str) -> String {
+    path.trim()
+        .trim_matches('"')
+        .replace('/', "\\")
+        .trim_end_matches('\\')
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_cache_entries_by_source_and_product_code() {
+        let root = r"C:\ProgramData\Package Cache";
+        let entries = vec![
+            "{11111111-2222-3333-4444-555555555555}v2.1.0".to_string(),
+            "{AAAAAAAA-BBBB-CCCC-DDDD-EEEEEEEEEEEE}".to_string(),
+            "{99999999-2222-3333-4444-555555555555}v1.0.0".to_string(),
+            "Unrelated".to_string(),
+        ];
+
+        // 安装来源指向缓存中的捆绑包目录
+        let sources = vec![
+            r"c:\programdata\package cache\{aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee}\".to_string(),
+            r"D:\Downloads\setup".to_string(),
+        ];
+        assert_eq!(
+            matching_entries(root, &entries, &sources, None),
+            vec!["{AAAAAAAA-BBBB-CCCC-DDDD-EEEEEEEEEEEE}".to_string()]
+        );
+
+        // MSI 按产品代码匹配带版本后缀的目录
+        assert_eq!(
+            matching_entries(
+                root,
+                &entries,
+                &[],
+                Some("{11111111-2222-3333-4444-555555555555}")
+            ),
+            vec!["{11111111-2222-3333-4444-555555555555}v2.1.0".to_string()]
+        );
+
+        // 缓存目录本身或缓存以外的位置不匹配任何目录
+        let outside = vec![
+            root.to_string(),
+            r"C:\ProgramData\Package Cache2\x".to_string(),
+        ];
+        assert!(matching_entries(root, &entries, &outside, None).is_empty());
+    }
+}
@@ -0,0 +1,392 @@
+//! 沙盒试运行（实验性）
+//!
+//! 对来历可疑的软件，先在 Windows 沙盒中运行一次卸载命令，观察退出码、是否在限定时间内结束、
+//! 安装目录中删除了多少文件、结束时留下了哪些新进程，再决定是否在本机执行。
+//! 沙盒中没有本机的安装记录，安装目录以副本的形式映射到原路径，卸载程序位于安装目录以外时
+//! 只读映射其所在目录；沙盒禁用网络和剪贴板，试运行不会改动本机上的任何文件。
+//!
+//! 不提供受限令牌方式：受限令牌下的进程仍运行在本机上，卸载程序能删除当前用户可写的所有内容。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::command::{self as uninstall_command, ExitOutcome};
+use super::validation;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::models::{InstalledProgram, InstallerKind};
+
+/// 复制到沙盒的安装目录大小上限
+const MAX_COPY_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// 沙盒内的工作目录，存放脚本和结果
+const SANDBOX_WORK_DIR: &str = r"C:\RustYuTrial";
+
+/// 等待沙盒启动和登录的额外时间
+const STARTUP_GRACE: Duration = Duration::from_secs(180);
+
+/// 检查结果文件的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 沙盒试运行的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SandboxTrial {
+    /// 在沙盒中执行的命令
+    pub command: String,
+    /// 卸载程序的退出码，超时未结束时为 None
+    pub exit_code: Option<i32>,
+    /// 按安装程序技术解释的退出码
+    pub outcome: Option<ExitOutcome>,
+    /// 是否在限定时间内未结束
+    pub timed_out: bool,
+    pub duration_secs: u64,
+    /// 运行前后安装目录副本中的文件数
+    pub files_before: usize,
+    pub files_after: usize,
+    /// 卸载结束时仍在运行的新进程
+    #[serde(default)]
+    pub spawned_processes: Vec<String>,
+    /// 根据以上结果得出的结论
+    #[serde(default)]
+    pub findings: Vec<String>,
+}
+
+/// 沙盒脚本写出的原始结果
+#[derive(Debug, Deserialize)]
+struct TrialOutput {
+    exit_code: Option<i32>,
+    timed_out: bool,
+    duration_secs: u64,
+    files_before: usize,
+    files_after: usize,
+    #[serde(default)]
+    spawned_processes: Vec<String>,
+}
+
+/// Windows 沙盒是否可用（需要 Windows 10/11 专业版或企业版并启用“Windows 沙盒”功能）
+pub fn sandbox_available() -> bool {
+    sandbox_executable().is_some_and(|path| path.is_file())
+}
+
+/// 在 Windows 沙盒中运行 `command`，最多等待 `timeout` 让卸载程序结束
+///
+/// 沙盒一次只能运行一个实例，调用方需要等待返回；本机上的文件不会被修改
+pub fn run_sandbox_trial(
+    program: &InstalledProgram,
+    command: &str,
+    timeout: Duration,
+) -> Result<SandboxTrial, UninstallerError> {
+    let sandbox = sandbox_executable()
+        .filter(|path| path.is_file())
+        .ok_or_else(|| {
+            UninstallerError::NotFound(
+                "未找到 Windows 沙盒，请在“启用或关闭 Windows 功能”中开启".to_string(),
+            )
+        })?;
+
+    let work_dir = std::env::temp_dir().join(format!("rust_yu_sandbox_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&work_dir)?;
+    let result = run_trial(&sandbox, &work_dir, program, command, timeout);
+    if let Err(e) = std::fs::remove_dir_all(&work_dir) {
+        tracing::warn!("删除沙盒工作目录失败 {}: {}", work_dir.display(), e);
+    }
+    result
+}
+
+fn run_trial(
+    sandbox: &Path,
+    work_dir: &Path,
+    program: &InstalledProgram,
+    command: &str,
+    timeout: Duration,
+) -> Result<SandboxTrial, UninstallerError> {
+    let mut mapped = Vec::new();
+    let install_location = program
+        .install_location
+        .as_deref()
+        .map(|location| location.trim().trim_end_matches('\\'))
+        .filter(|location| !location.is_empty() && Path::new(location).is_dir());
+
+    if let Some(location) = install_location {
+        let size = utils::calculate_dir_size(Path::new(location))?;
+        if size > MAX_COPY_SIZE {
+            return Err(UninstallerError::Other(format!(
+                "安装目录过大 ({})，不适合复制到沙盒",
+                utils::format_size(size)
+            )));
+        }
+        let copy = work_dir.join("install");
+        copy_dir(Path::new(location), &copy)?;
+        mapped.push(MappedFolder::new(&copy, location, false));
+    }
+
+    // 卸载程序不在安装目录中时只读映射其所在目录
+    let executable = validation::parse_executable(command);
+    if let Some(parent) = executable
+        .as_deref()
+        .filter(|exe| exe.is_absolute() && exe.is_file())
+        .filter(|exe| !validation::is_trusted_system_uninstaller(exe))
+        .and_then(Path::parent)
+    {
+        let parent_str = parent.to_string_lossy().to_string();
+        let inside_install = install_location.is_some_and(|location| {
+            parent_str
+                .to_lowercase()
+                .starts_with(&location.to_lowercase())
+        });
+        if !inside_install {
+            mapped.push(MappedFolder::new(parent, &parent_str, true));
+        }
+    }
+
+    std::fs::write(work_dir.join("uninstall.cmd"), uninstall_script(command))?;
+    std::fs::write(
+        work_dir.join("trial.ps1"),
+        trial_script(install_location, timeout),
+    )?;
+    let config_path = work_dir.join("trial.wsb");
+    std::fs::write(&config_path, sandbox_config(work_dir, &mapped))?;
+
+    tracing::info!("在 Windows 沙盒中试运行: {}", command);
+    Command::new(sandbox)
+        .arg(&config_path)
+        .spawn()
+        .map_err(|e| UninstallerError::Other(format!("启动 Windows 沙盒失败: {}", e)))?;
+
+    let result_path = work_dir.join("result.json");
+    let deadline = Instant::now() + timeout + STARTUP_GRACE;
+    while Instant::now() < deadline {
+        if let Ok(content) = std::fs::read_to_string(&result_path) {
+            // PowerShell 5 写出的 UTF-8 带 BOM；文件写到一半时解析失败，下一轮再读
+            if let Ok(output) =
+                serde_json::from_str::<TrialOutput>(content.trim_start_matches('\u{feff}'))
+            {
+                return Ok(build_trial(command, program.installer_kind, output));
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    Err(UninstallerError::Timeout(
+        "等待沙盒试运行结果超时，请手动关闭沙盒窗口".to_string(),
+    ))
+}
+
+/// 由沙盒结果得出结论
+fn build_trial(command: &str, kind: InstallerKind, output: TrialOutput) -> SandboxTrial {
+    let outcome = output
+        .exit_code
+        .map(|code| uninstall_command::classify_exit_code(kind, code));
+    let mut findings = Vec::new();
+
+    if output.timed_out {
+        findings.push(format!(
+            "卸载程序在 {} 秒内没有结束，可能在等待界面操作或静默参数无效",
+            output.duration_secs
+        ));
+    }
+    match (output.exit_code, outcome) {
+        (Some(code), Some(ExitOutcome::Failed)) => {
+            findings.push(format!("卸载程序返回失败 (退出码 {})", code))
+        }
+        (_, Some(ExitOutcome::AlreadyRemoved))
+            if matches!(kind, InstallerKind::Msi | InstallerKind::WixBurn) =>
+        {
+            findings.push("沙盒中没有本机的 MSI 安装记录，试运行无法反映真实卸载过程".to_string())
+        }
+        (_, Some(ExitOutcome::Cancelled)) => findings.push("卸载程序报告已被取消".to_string()),
+        _ => {}
+    }
+
+    if output.files_before > 0 {
+        if output.files_after == output.files_before {
+            findings.push("安装目录中没有任何文件被删除".to_string());
+        } else if output.files_after > output.files_before {
+            findings.push(format!(
+                "安装目录中的文件反而增加了 {} 个",
+                output.files_after - output.files_before
+            ));
+        } else if output.files_after == 0 {
+            findings.push("安装目录中的文件已全部删除".to_string());
+        } else {
+            findings.push(format!(
+                "安装目录中删除了 {} 个文件，剩余 {} 个",
+                output.files_before - output.files_after,
+                output.files_after
+            ));
+        }
+    }
+
+    if !output.spawned_processes.is_empty() {
+        findings.push(format!(
+            "卸载结束后仍有新进程在运行: {}",
+            output.spawned_processes.join(", ")
+        ));
+    }
+
+    SandboxTrial {
+        command: command.to_string(),
+        exit_code: output.exit_code,
+        outcome,
+        timed_out: output.timed_out,
+        duration_secs: output.duration_secs,
+        files_before: output.files_before,
+        files_after: output.files_after,
+        spawned_processes: output.spawned_processes,
+        findings,
+    }
+}
+
+/// 映射到沙盒的本机目录
+struct MappedFolder {
+    host: String,
+    sandbox: String,
+    read_only: bool,
+}
+
+impl MappedFolder {
+    fn new(host: &Path, sandbox: &str, read_only: bool) -> Self {
+        Self {
+            host: host.to_string_lossy().to_string(),
+            sandbox: sandbox.to_string(),
+            read_only,
+        }
+    }
+}
+
+fn sandbox_executable() -> Option<PathBuf> {
+    let system_root = std::env::var("SystemRoot").ok()?;
+    Some(
+        Path::new(&system_root)
+            .join("System32")
+            .join("WindowsSandbox.exe"),
+    )
+}
+
+/// 沙盒配置：禁用网络和剪贴板，映射工作目录和安装目录副本，登录后运行试运行脚本
+fn sandbox_config(work_dir: &Path, mapped: &[MappedFolder]) -> String {
+    let mut folders = vec![MappedFolder::new(work_dir, SANDBOX_WORK_DIR, false)];
+    folders.extend(mapped.iter().map(|folder| MappedFolder {
+        host: folder.host.clone(),
+        sandbox: folder.sandbox.clone(),
+        read_only: folder.read_only,
+    }));
+
+    let folders_xml: String = folders
+        .iter()
+        .map(|folder| {
+            format!(
+                "    <MappedFolder>\n      <HostFolder>{}</HostFolder>\n      <SandboxFolder>{}</SandboxFolder>\n      <ReadOnly>{}</ReadOnly>\n    </MappedFolder>\n",
+                escape_xml(&folder.host),
+                escape_xml(&folder.sandbox),
+                folder.read_only
+            )
+        })
+        .collect();
+
+    format!(
+        "<Configuration>\n  <Networking>Disable</Networking>\n  <ClipboardRedirection>Disable</ClipboardRedirection>\n  <MappedFolders>\n{}  </MappedFolders>\n  <LogonCommand>\n    <Command>powershell.exe -NoProfile -ExecutionPolicy Bypass -File {}\\trial.ps1</Command>\n  </LogonCommand>\n</Configuration>\n",
+        folders_xml, SANDBOX_WORK_DIR
+    )
+}
+
+/// 批处理文件中执行卸载命令，保留其退出码
+fn uninstall_script(command: &str) -> String {
+    format!(
+        "@echo off\r\nchcp 65001 >nul\r\n{}\r\nexit /b %errorlevel%\r\n",
+        command
+    )
+}
+
+/// 试运行脚本：记录运行前的文件数和进程，运行卸载命令，写出结果后关闭沙盒
+fn trial_script(install_location: Option<&str>, timeout: Duration) -> String {
+    let location = install_location
+        .map(|location| format!("'{}'", location.replace('\'', "''")))
+        .unwrap_or_else(|| "$null".to_string());
+    format!(
+        r#"$location = {location}
+function Count-Files {{
+    if (-not $location -or -not (Test-Path -LiteralPath $location)) {{ return 0 }}
+    return @(Get-ChildItem -LiteralPath $location -Recurse -File -Force -ErrorAction SilentlyContinue).Count
+}}
+$before = Count-Files
+$existing = @(Get-Process | ForEach-Object {{ $_.Id }})
+$start = Get-Date
+$process = Start-Process -FilePath cmd.exe -ArgumentList '/c', '{work}\uninstall.cmd' -PassThru
+$finished = $process.WaitForExit({timeout_ms})
+$exitCode = $null
+if ($finished) {{ $exitCode = $process.ExitCode }}
+Start-Sleep -Seconds 5
+$spawned = @(Get-Process | Where-Object {{ $existing -notcontains $_.Id -and $_.Path }} | ForEach-Object {{ $_.Path }} | Sort-Object -Unique)
+$result = [ordered]@{{
+    exit_code = $exitCode
+    timed_out = -not $finished
+    duration_secs = [int]((Get-Date) - $start).TotalSeconds
+    files_before = $before
+    files_after = Count-Files
+    spawned_processes = $spawned
+}}
+$result | ConvertTo-Json | Set-Content -LiteralPath '{work}\result.json' -Encoding UTF8
+shutdown.exe /s /t 0
+"#,
+        location = location,
+        work = SANDBOX_WORK_DIR,
+        timeout_ms = timeout.as_millis()
+    )
+}
+
+fn copy_dir(source: &Path, target: &Path) -> Result<(), UninstallerError> {
+    for entry in WalkDir::new(source).follow_links(false) {
+        let entry = entry.map_err(|e| UninstallerError::Other(e.to_string()))?;
+        let Ok(relative) = entry.path().strip_prefix(source) else {
+            continue;
+        };
+        let destination = target.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&destination)?;
+        } else if entry.file_type().is_file() {
+            std::fs::copy(entry.path(), &destination)?;
+        }
+    }
+    Ok(())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_trial_output_into_findings() {
+        let output: TrialOutput = serde_json::from_str(
+            "\u{feff}{\"exit_code\":2,\"timed_out\":false,\"duration_secs\":12,\"files_before\":40,\"files_after\":40,\"spawned_processes\":[\"C:\\\\Temp\\\\x.exe\"]}"
+                .trim_start_matches('\u{feff}'),
+        )
+        .unwrap();
+        let trial = build_trial(r#""D:\Acme\uninst.exe" /S"#, InstallerKind::Nsis, output);
+
+        assert_eq!(trial.outcome, Some(ExitOutcome::Failed));
+        assert_eq!(trial.findings.len(), 3);
+        assert!(trial.findings[1].contains("没有任何文件被删除"));
+
+        let config = sandbox_config(
+            Path::new(r"C:\Temp\work"),
+            &[MappedFolder::new(
+                Path::new(r"C:\Temp\work\install"),
+                r"D:\Acme & Co",
+                false,
+            )],
+        );
+        assert!(config.contains("<Networking>Disable</Networking>"));
+        assert!(config.contains(r"<SandboxFolder>D:\Acme &amp; Co</SandboxFolder>"));
+    }
+}
@@ -0,0 +1,336 @@
+//! 卸载程序数字签名校验
+//!
+//! 启动卸载程序前通过 WinVerifyTrust 校验 Authenticode 签名，并将签名者与注册的发布者比对，
+//! 防止卸载项被劫持为其他程序。
+
+use super::validation::{self, UninstallValidation};
+use crate::modules::common::error::UninstallerError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 比对发布者时忽略的公司后缀
+const COMPANY_SUFFIXES: &[&str] = &[
+    "inc",
+    "incorporated",
+    "corp",
+    "corporation",
+    "co",
+    "company",
+    "ltd",
+    "limited",
+    "llc",
+    "gmbh",
+    "ag",
+    "sa",
+    "bv",
+];
+
+/// 签名状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureStatus {
+    /// 签名有效且受信任
+    Valid,
+    /// 文件未签名
+    Unsigned,
+    /// 签名存在但无效或不受信任
+    Invalid,
+    /// 无法校验（非 Windows 平台或文件不可访问）
+    Unknown,
+}
+
+/// 签名校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureCheck {
+    pub path: String,
+    pub status: SignatureStatus,
+    /// 签名证书的显示名称
+    pub signer: Option<String>,
+    /// 签名者与注册发布者是否一致，缺少任一方时为 None
+    pub publisher_match: Option<bool>,
+    /// 签名证书的有效期起始时间
+    #[serde(default)]
+    pub cert_not_before: Option<DateTime<Utc>>,
+    /// 签名证书的有效期截止时间
+    #[serde(default)]
+    pub cert_not_after: Option<DateTime<Utc>>,
+}
+
+impl SignatureCheck {
+    /// 未签名、签名无效或签名者不一致时返回问题描述
+    pub fn problem(&self) -> Option<String> {
+        match self.status {
+            SignatureStatus::Unsigned => Some(format!("卸载程序未签名: {}", self.path)),
+            SignatureStatus::Invalid => Some(format!("卸载程序签名无效: {}", self.path)),
+            SignatureStatus::Valid if self.publisher_match == Some(false) => Some(format!(
+                "卸载程序签名者 \"{}\" 与发布者不一致: {}",
+                self.signer.as_deref().unwrap_or_default(),
+                self.path
+            )),
+            _ => None,
+        }
+    }
+
+    /// 签名证书在指定时间是否已过期
+    ///
+    /// 带时间戳的签名在证书过期后仍然有效，过期只作为提示，不改变签名状态
+    pub fn certificate_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.cert_not_after.is_some_and(|not_after| not_after < now)
+    }
+}
+
+/// 校验卸载程序签名
+///
+/// 非严格模式下问题只记录警告；严格模式下未签名、签名无效或签名者不一致都会拒绝执行。
+pub fn check_uninstaller_signature(
+    executable: &Path,
+    publisher: Option<&str>,
+    strict: bool,
+) -> Result<SignatureCheck, UninstallerError> {
+    let check = check_file_signature(executable, publisher);
+
+    if let Some(problem) = check.problem() {
+        if strict {
+            return Err(UninstallerError::UnsafeUninstallCommand(problem));
+        }
+        tracing::warn!("{}", problem);
+    }
+
+    Ok(check)
+}
+
+/// 校验已通过命令校验的卸载程序签名
+///
+/// msiexec 等系统卸载程序由 Windows 签名，与软件发布者必然不一致，因此跳过
+pub fn check_command_signature(
+    command: &UninstallValidation,
+    publisher: Option<&str>,
+    strict: bool,
+) -> Result<Option<SignatureCheck>, UninstallerError> {
+    let Some(executable) = command.executable.as_deref().map(Path::new) else {
+        return Ok(None);
+    };
+
+    if validation::is_trusted_system_uninstaller(executable) {
+        return Ok(None);
+    }
+
+    check_uninstaller_signature(executable, publisher, strict).map(Some)
+}
+
+/// 校验文件签名并与注册的发布者比对，只返回结果，不记录警告
+pub fn check_file_signature(path: &Path, publisher: Option<&str>) -> SignatureCheck {
+    let mut check = verify_file_signature(path);
+    check.publisher_match = match (check.signer.as_deref(), publisher) {
+        (Some(signer), Some(publisher)) if !publisher.trim().is_empty() => {
+            Some(publisher_matches(signer, publisher))
+        }
+        _ => None,
+    };
+    check
+}
+
+/// 校验文件的 Authenticode 签名
+pub fn verify_file_signature(path: &Path) -> SignatureCheck {
+    let mut check = SignatureCheck {
+        path: path.to_string_lossy().to_string(),
+        status: SignatureStatus::Unknown,
+        signer: None,
+        publisher_match: None,
+        cert_not_before: None,
+        cert_not_after: None,
+    };
+
+    if !path.is_file() {
+        return check;
+    }
+
+    #[cfg(windows)]
+    {
+        let signer = verify_file_signature_impl(path);
+        check.status = signer.status;
+        check.signer = signer.name;
+        check.cert_not_before = signer.not_before;
+        check.cert_not_after = signer.not_after;
+    }
+
+    check
+}
+
+/// WinVerifyTrust 的校验结果与签名证书信息
+#[cfg(windows)]
+struct SignerInfo {
+    status: SignatureStatus,
+    name: Option<String>,
+    not_before: Option<DateTime<Utc>>,
+    not_after: Option<DateTime<Utc>>,
+}
+
+#[cfg(windows)]
+fn verify_file_signature_impl(path: &Path) -> SignerInfo {
+    use crate::modules::common::utils;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{HWND, TRUST_E_NOSIGNATURE};
+    use windows::Win32::Security::Cryptography::{
+        CertGetNameStringW, CERT_NAME_SIMPLE_DISPLAY_TYPE,
+    };
+    use windows::Win32::Security::WinTrust::{
+        WTHelperGetProvSignerFromChain, WTHelperProvDataFromStateData, WinVerifyTrust,
+        WINTRUST_ACTION_GENERIC_VERIFY_V2, WINTRUST_DATA, WINTRUST_DATA_0, WINTRUST_FILE_INFO,
+        WTD_CHOICE_FILE, WTD_REVOKE_NONE, WTD_STATEACTION_CLOSE, WTD_STATEACTION_VERIFY,
+        WTD_UI_NONE,
+    };
+
+    let wide_path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let mut file_info = WINTRUST_FILE_INFO {
+        cbStruct: std::mem::size_of::<WINTRUST_FILE_INFO>() as u32,
+        pcwszFilePath: PCWSTR(wide_path.as_ptr()),
+        ..Default::default()
+    };
+
+    let mut data = WINTRUST_DATA {
+        cbStruct: std::mem::size_of::<WINTRUST_DATA>() as u32,
+        dwUIChoice: WTD_UI_NONE,
+        fdwRevocationChecks: WTD_REVOKE_NONE,
+        dwUnionChoice: WTD_CHOICE_FILE,
+        Anonymous: WINTRUST_DATA_0 {
+            pFile: &mut file_info,
+        },
+        dwStateAction: WTD_STATEACTION_VERIFY,
+        ..Default::default()
+    };
+
+    let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+
+    // SAFETY: data 与 file_info 在调用期间有效，校验结束后以 STATEACTION_CLOSE 释放状态
+    unsafe {
+        let result = WinVerifyTrust(
+            HWND::default(),
+            &mut action,
+            &mut data as *mut WINTRUST_DATA as *mut _,
+        );
+
+        let status = match result {
+            0 => SignatureStatus::Valid,
+            code if code == TRUST_E_NOSIGNATURE.0 => SignatureStatus::Unsigned,
+            _ => SignatureStatus::Invalid,
+        };
+
+        let mut signer = SignerInfo {
+            status,
+            name: None,
+            not_before: None,
+            not_after: None,
+        };
+        let provider = WTHelperProvDataFromStateData(data.hWVTStateData);
+        if !provider.is_null() {
+            let signer_info = WTHelperGetProvSignerFromChain(provider, 0, false, 0);
+            if !signer_info.is_null()
+                && (*signer_info).csCertChain > 0
+                && !(*signer_info).pasCertChain.is_null()
+            {
+                let cert = (*(*signer_info).pasCertChain).pCert;
+                if !cert.is_null() {
+                    let mut buffer = [0u16; 256];
+                    let len = CertGetNameStringW(
+                        cert,
+                        CERT_NAME_SIMPLE_DISPLAY_TYPE,
+                        0,
+                        None,
+                        Some(&mut buffer),
+                    ) as usize;
+                    if len > 1 {
+                        signer.name = Some(String::from_utf16_lossy(&buffer[..len - 1]));
+                    }
+
+                    let info = (*cert).pCertInfo;
+                    if !info.is_null() {
+                        signer.not_before = utils::filetime_to_utc(
+                            (*info).NotBefore.dwLowDateTime,
+                            (*info).NotBefore.dwHighDateTime,
+                        );
+                        signer.not_after = utils::filetime_to_utc(
+                            (*info).NotAfter.dwLowDateTime,
+                            (*info).NotAfter.dwHighDateTime,
+                        );
+                    }
+                }
+            }
+        }
+
+        data.dwStateAction = WTD_STATEACTION_CLOSE;
+        WinVerifyTrust(
+            HWND::default(),
+            &mut action,
+            &mut data as *mut WINTRUST_DATA as *mut _,
+        );
+
+        signer
+    }
+}
+
+/// 判断签名者与发布者是否指向同一家公司
+///
+/// 忽略大小写、标点和常见公司后缀，一方包含另一方即视为一致
+pub fn publisher_matches(signer: &str, publisher: &str) -> bool {
+    let signer = normalize_company_name(signer);
+    let publisher = normalize_company_name(publisher);
+
+    if signer.is_empty() || publisher.is_empty() {
+        return false;
+    }
+
+    signer.contains(&publisher) || publisher.contains(&signer)
+}
+
+/// 规范化公司名称：小写、去标点、去除公司后缀
+fn normalize_company_name(name: &str) -> String {
+    let cleaned: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+
+    cleaned
+        .split_whitespace()
+        .filter(|word| !COMPANY_SUFFIXES.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publisher_matches_ignores_case_and_company_suffix() {
+        assert!(publisher_matches(
+            "Microsoft Corporation",
+            "Microsoft Corp."
+        ));
+        assert!(publisher_matches("Google LLC", "Google"));
+        assert!(publisher_matches("Mozilla Corporation", "Mozilla"));
+        assert!(!publisher_matches("Evil Software Ltd", "Mozilla"));
+    }
+
+    #[test]
+    fn signature_problem_reports_signer_mismatch() {
+        let check = SignatureCheck {
+            path: r"C:\Program Files\Foo\uninstall.exe".to_string(),
+            status: SignatureStatus::Valid,
+            signer: Some("Evil Software Ltd".to_string()),
+            publisher_match: Some(false),
+            cert_not_before: None,
+            cert_not_after: None,
+        };
+
+        assert!(check.problem().is_some());
+        assert!(!check.certificate_expired_at(Utc::now()));
+    }
+}
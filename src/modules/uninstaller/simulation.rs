@@ -0,0 +1,274 @@
+//! 卸载模拟
+//!
+//! 不执行任何命令，只汇总真正卸载时会发生什么：将要执行的卸载命令及其校验结果、
+//! 卸载程序自身会移除的内容、卸载后可能残留的痕迹、预计释放的空间、需要管理员权限的项，
+//! 以及清理时会被安全检查拦截的项。结果写入报告，与实际运行的报告一起查看。
+
+use serde::{Deserialize, Serialize};
+use winreg::enums::HKEY_CURRENT_USER;
+
+use super::command as uninstall_command;
+use super::sandbox::SandboxTrial;
+use super::validation::{self, ValidationIssue};
+use super::{arp, signature};
+use crate::modules::cleaner::{
+    self,
+    models::{CleanOptions, SafetyVerdict},
+};
+use crate::modules::common::utils;
+use crate::modules::lister::models::InstalledProgram;
+use crate::modules::scanner::models::{Trace, TraceType};
+
+/// 模拟选项，与 uninstall 命令的参数对应
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimulationOptions {
+    pub silent: bool,
+    pub allow_suspicious: bool,
+    pub strict_signature: bool,
+    pub clean: CleanOptions,
+}
+
+/// 卸载模拟结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UninstallSimulation {
+    /// 将要执行的命令（已按安装程序技术追加静默参数），没有卸载命令时为 None
+    pub command: Option<String>,
+    /// 卸载命令的校验问题
+    #[serde(default)]
+    pub command_issues: Vec<ValidationIssue>,
+    /// 实际运行时会拒绝执行卸载命令的原因
+    pub command_refused: Option<String>,
+    /// 卸载程序的签名问题
+    pub signature_problem: Option<String>,
+    /// 卸载程序预计自行移除的痕迹数（安装目录内的文件和卸载项）
+    pub removed_by_uninstaller: usize,
+    /// 卸载后可能残留的痕迹
+    pub remaining: Vec<Trace>,
+    /// 卸载并清理残留后预计释放的空间（字节）
+    pub estimated_reclaimed: u64,
+    /// 需要管理员权限才能完成的项
+    pub elevation_required: Vec<String>,
+    /// 生成模拟时是否已以管理员身份运行
+    pub elevated: bool,
+    /// 清理残留时会被拦截的项
+    pub blocked: Vec<SafetyVerdict>,
+    /// 其他提示
+    #[serde(default)]
+    pub notes: Vec<String>,
+    /// 在 Windows 沙盒中试运行卸载命令的结果（实验性，需显式开启）
+    #[serde(default)]
+    pub sandbox: Option<SandboxTrial>,
+}
+
+/// 模拟卸载 `program`，`traces` 为卸载前扫描到的痕迹
+pub fn simulate_uninstall(
+    program: &InstalledProgram,
+    traces: &[Trace],
+    options: SimulationOptions,
+) -> UninstallSimulation {
+    let mut simulation = UninstallSimulation {
+        command: None,
+        command_issues: Vec::new(),
+        command_refused: None,
+        signature_problem: None,
+        removed_by_uninstaller: 0,
+        remaining: Vec::new(),
+        estimated_reclaimed: 0,
+        elevation_required: Vec::new(),
+        elevated: utils::is_elevated(),
+        blocked: Vec::new(),
+        notes: Vec::new(),
+        sandbox: None,
+    };
+
+    let broken_entry = arp::is_broken_entry(program);
+    match program.uninstall_string.as_deref() {
+        _ if broken_entry => simulation
+            .notes
+            .push("卸载程序已不存在，将跳过卸载命令，按强制移除处理".to_string()),
+        Some(uninstall_string) => {
+            check_command(program, uninstall_string, options, &mut simulation)
+        }
+        None => simulation
+            .notes
+            .push("未找到卸载命令，只能清理残留".to_string()),
+    }
+
+    if program.install_scope.requires_elevation() {
+        simulation
+            .elevation_required
+            .push(format!("卸载程序（{}安装）", program.install_scope));
+    }
+
+    // 卸载命令不会运行时所有痕迹都会保留
+    let uninstaller_runs =
+        !broken_entry && simulation.command.is_some() && simulation.command_refused.is_none();
+    let (removed, remaining): (Vec<&Trace>, Vec<&Trace>) = traces
+        .iter()
+        .filter(|trace| trace.exists)
+        .partition(|trace| uninstaller_runs && removed_by_uninstaller(trace, program));
+    simulation.removed_by_uninstaller = removed.len();
+    simulation.remaining = remaining.into_iter().cloned().collect();
+
+    simulation.blocked = cleaner::simulate_clean(&simulation.remaining, options.clean)
+        .into_iter()
+        .filter(|verdict| verdict.blocked)
+        .collect();
+
+    for trace in &simulation.remaining {
+        if trace_requires_elevation(trace) {
+            simulation.elevation_required.push(trace.path.clone());
+        }
+    }
+
+    let removed_size = if uninstaller_runs {
+        program
+            .size
+            .or(program.estimated_size)
+            .unwrap_or_else(|| removed.iter().filter_map(|trace| trace.size).sum())
+    } else {
+        0
+    };
+    let cleaned_size: u64 = simulation
+        .remaining
+        .iter()
+        .filter(|trace| {
+            !simulation
+                .blocked
+                .iter()
+                .any(|verdict| verdict.trace_id == trace.id)
+        })
+        .filter_map(|trace| trace.size)
+        .sum();
+    simulation.estimated_reclaimed = removed_size + cleaned_size;
+
+    simulation
+}
+
+/// 校验卸载命令与签名，与实际运行时的检查一致
+fn check_command(
+    program: &InstalledProgram,
+    uninstall_string: &str,
+    options: SimulationOptions,
+    simulation: &mut UninstallSimulation,
+) {
+    simulation.command = Some(uninstall_command::prepare_uninstall_command(
+        uninstall_string,
+        program.installer_kind,
+        options.silent,
+    ));
+
+    let checked = validation::validate_uninstall_string(uninstall_string);
+    simulation.command_issues = checked.issues.clone();
+    if let Err(e) = validation::ensure_uninstall_allowed(uninstall_string, options.allow_suspicious)
+    {
+        simulation.command_refused = Some(e.to_string());
+        return;
+    }
+
+    match signature::check_command_signature(
+        &checked,
+        program.publisher.as_deref(),
+        options.strict_signature,
+    ) {
+        Ok(check) => {
+            simulation.signature_problem = check.and_then(|check| check.problem());
+        }
+        Err(e) => {
+            simulation.signature_problem = Some(e.to_string());
+            simulation.command_refused = Some(e.to_string());
+        }
+    }
+}
+
+/// 卸载程序通常会删除安装目录和自己的卸载项，其余痕迹视为可能残留
+fn removed_by_uninstaller(trace: &Trace, program: &InstalledProgram) -> bool {
+    let path = trace.path.to_lowercase().replace('/', "\\");
+    let path = path.trim_end_matches('\\');
+
+    if let Some(key) = program.registry_key.as_deref() {
+        let key = utils::full_registry_path(key).to_lowercase();
+        if utils::full_registry_path(path).to_lowercase() == key {
+            return true;
+        }
+    }
+
+    matches!(trace.trace_type, TraceType::File | TraceType::Shortcut)
+        && program
+            .install_location
+            .as_deref()
+            .map(|location| location.to_lowercase().replace('/', "\\"))
+            .map(|location| location.trim_end_matches('\\').to_string())
+            .filter(|location| !location.is_empty())
+            .is_some_and(|location| {
+                path == location
+                    || path
+                        .strip_prefix(&location)
+                        .is_some_and(|rest| rest.starts_with('\\'))
+            })
+}
+
+/// 删除该痕迹是否需要管理员权限
+fn trace_requires_elevation(trace: &Trace) -> bool {
+    match trace.trace_type {
+        TraceType::Service | TraceType::Driver => return true,
+        TraceType::ScheduledTask | TraceType::UsageHistory => return false,
+        _ => {}
+    }
+
+    if let Some((hkey, _)) = utils::parse_registry_path(&trace.path) {
+        return hkey != HKEY_CURRENT_USER;
+    }
+
+    let path = trace.path.to_lowercase();
+    [
+        "ProgramFiles",
+        "ProgramFiles(x86)",
+        "ProgramData",
+        "SystemRoot",
+    ]
+    .iter()
+    .filter_map(|var| std::env::var(var).ok())
+    .map(|dir| dir.to_lowercase().trim_end_matches('\\').to_string())
+    .filter(|dir| !dir.is_empty())
+    .any(|dir| {
+        path.strip_prefix(&dir)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::lister::models::InstallSource;
+
+    #[test]
+    fn install_dir_and_uninstall_key_are_removed_by_uninstaller() {
+        let mut program = InstalledProgram::new("Acme".to_string(), InstallSource::Registry);
+        program.install_location = Some(r"D:\Apps\Acme\".to_string());
+        program.registry_key = Some(
+            r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\Acme"
+                .to_string(),
+        );
+
+        let file = Trace::new(
+            "acme".to_string(),
+            TraceType::File,
+            r"D:\Apps\Acme\bin".to_string(),
+        );
+        let sibling = Trace::new(
+            "acme".to_string(),
+            TraceType::File,
+            r"D:\Apps\AcmeData".to_string(),
+        );
+        let key = Trace::new(
+            "acme".to_string(),
+            TraceType::RegistryKey,
+            r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\Acme".to_string(),
+        );
+        assert!(removed_by_uninstaller(&file, &program));
+        assert!(!removed_by_uninstaller(&sibling, &program));
+        assert!(removed_by_uninstaller(&key, &program));
+        assert!(trace_requires_elevation(&key));
+    }
+}
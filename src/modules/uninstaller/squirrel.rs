@@ -0,0 +1,67 @@
+//! Squirrel（Electron 应用常用）安装程序支持
+//!
+//! Squirrel 把应用装在 `%LOCALAPPDATA%\<app>` 下，`Update.exe` 与各版本目录 `app-x.y.z` 并列。
+//! 卸载命令必须通过 `Update.exe --uninstall` 执行，卸载后常会留下整个应用目录，
+//! 以及安装过程使用的 `%LOCALAPPDATA%\SquirrelTemp`。
+
+use std::path::PathBuf;
+
+use super::validation;
+
+/// Squirrel 安装过程使用的临时目录名
+const SQUIRREL_TEMP_DIR: &str = "SquirrelTemp";
+
+/// 应用根目录：`Update.exe` 所在目录
+pub fn app_root(uninstall_string: &str) -> Option<PathBuf> {
+    let executable = validation::parse_executable(uninstall_string)?;
+    let is_update = executable
+        .file_name()
+        .is_some_and(|name| name.eq_ignore_ascii_case("update.exe"));
+    if !is_update {
+        return None;
+    }
+    executable.parent().map(|parent| parent.to_path_buf())
+}
+
+/// 生成卸载命令：始终通过 `Update.exe --uninstall` 执行，`silent` 时追加 `-s`
+pub fn uninstall_command(uninstall_string: &str, silent: bool) -> Option<String> {
+    let update_exe = app_root(uninstall_string)?.join("Update.exe");
+    let mut command = format!("\"{}\" --uninstall", update_exe.display());
+    let already_silent = uninstall_string
+        .split_whitespace()
+        .any(|part| part == "-s" || part.eq_ignore_ascii_case("--silent"));
+    if silent || already_silent {
+        command.push_str(" -s");
+    }
+    Some(command)
+}
+
+/// `%LOCALAPPDATA%\SquirrelTemp`，不存在 LOCALAPPDATA 时返回 None
+pub fn temp_dir() -> Option<PathBuf> {
+    std::env::var("LOCALAPPDATA")
+        .ok()
+        .map(|dir| PathBuf::from(dir).join(SQUIRREL_TEMP_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_update_exe_uninstall_command() {
+        let uninstall = r#""C:\Users\a\AppData\Local\Slack\Update.exe" --uninstall -s"#;
+        assert_eq!(
+            app_root(uninstall),
+            Some(PathBuf::from(r"C:\Users\a\AppData\Local\Slack"))
+        );
+        assert_eq!(
+            uninstall_command(uninstall, false).as_deref(),
+            Some(r#""C:\Users\a\AppData\Local\Slack\Update.exe" --uninstall -s"#)
+        );
+        assert_eq!(
+            uninstall_command(r#""C:\Users\a\AppData\Local\Foo\Update.exe""#, false).as_deref(),
+            Some(r#""C:\Users\a\AppData\Local\Foo\Update.exe" --uninstall"#)
+        );
+        assert_eq!(app_root(r#""C:\Program Files\Foo\uninst.exe""#), None);
+    }
+}
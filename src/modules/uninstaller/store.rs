@@ -0,0 +1,109 @@
+//! 为所有用户移除商店应用
+//!
+//! `Remove-AppxPackage` 只对当前用户生效，预配包仍会在新用户登录时重新安装。
+//! 永久移除 OEM 预装应用需要同时删除预配包和各用户已安装的副本，两者都需要管理员权限。
+//! 商店、应用安装程序、运行库框架和 Windows 安全中心等系统依赖的包始终拒绝移除。
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::store::ProvisionedPackage;
+use crate::modules::remote::runner::{LocalRunner, ScriptRunner};
+
+/// 删除预配包；`$Arg1` 为 1 时同时移除所有用户已安装的副本
+const REMOVE_PROVISIONED_SCRIPT: &str = r#"
+Remove-AppxProvisionedPackage -Online -PackageName $Arg0 -ErrorAction Stop | Out-Null
+if ($Arg1 -eq '1') {
+    Get-AppxPackage -AllUsers -Name $Arg2 | Remove-AppxPackage -AllUsers -ErrorAction Stop
+}
+"#;
+
+/// 不允许移除的预配包：移除后无法再安装或更新应用，或者其他应用依赖它们运行
+///
+/// 框架包名后带版本（如 `Microsoft.VCLibs.140.00`），按 `<名称>.` 前缀匹配
+const PROTECTED_PACKAGES: &[&str] = &[
+    "microsoft.windowsstore",
+    "microsoft.storepurchaseapp",
+    "microsoft.desktopappinstaller",
+    "microsoft.vclibs",
+    "microsoft.ui.xaml",
+    "microsoft.net.native.framework",
+    "microsoft.net.native.runtime",
+    "microsoft.windowsappruntime",
+    "microsoft.sechealthui",
+];
+
+/// 是否为受保护的系统包，参数为不含版本的包名
+pub fn is_protected_package(display_name: &str) -> bool {
+    let name = display_name.trim().to_lowercase();
+    PROTECTED_PACKAGES.iter().any(|protected| {
+        name.strip_prefix(protected)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+/// 移除前检查，受保护的系统包始终拒绝
+pub fn check_removable(package: &ProvisionedPackage) -> Result<(), UninstallerError> {
+    if is_protected_package(&package.display_name) {
+        return Err(UninstallerError::CriticalSystemItem(format!(
+            "{} 是系统依赖的包，不能移除",
+            package.display_name
+        )));
+    }
+    Ok(())
+}
+
+/// 移除预配包，`remove_installed` 为 true 时同时为所有用户卸载已安装的副本
+pub fn remove_provisioned_package(
+    package: &ProvisionedPackage,
+    remove_installed: bool,
+) -> Result<(), UninstallerError> {
+    check_removable(package)?;
+    if !utils::is_elevated() {
+        return Err(UninstallerError::PermissionDenied(
+            "移除预配包需要以管理员身份运行".to_string(),
+        ));
+    }
+
+    tracing::info!(
+        "移除预配包: {} (所有用户: {})",
+        package.package_name,
+        remove_installed
+    );
+
+    LocalRunner
+        .run(
+            REMOVE_PROVISIONED_SCRIPT,
+            &[
+                package.package_name.clone(),
+                if remove_installed { "1" } else { "0" }.to_string(),
+                package.display_name.clone(),
+            ],
+        )
+        .map_err(|error| match error {
+            UninstallerError::Other(message) => UninstallerError::StoreApp(message),
+            other => other,
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protects_store_and_framework_packages() {
+        for name in [
+            "Microsoft.WindowsStore",
+            "Microsoft.DesktopAppInstaller",
+            "Microsoft.VCLibs.140.00",
+            "Microsoft.UI.Xaml.2.8",
+            "Microsoft.NET.Native.Framework.2.2",
+            "Microsoft.SecHealthUI",
+        ] {
+            assert!(is_protected_package(name), "{}", name);
+        }
+        assert!(!is_protected_package("Microsoft.BingNews"));
+        assert!(!is_protected_package("Microsoft.WindowsStoreLite"));
+    }
+}
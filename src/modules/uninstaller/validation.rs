@@ -0,0 +1,425 @@
+//! 卸载命令校验
+//!
+//! 注册表中的 `UninstallString` 会原样交给 `cmd /C` 执行，被篡改或写错的条目可能造成破坏。
+//! 执行前先解析出可执行文件并检查危险特征。
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::enrichment::expand_windows_env_vars;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 明显具有破坏性的命令片段（小写，已压缩空白）
+const DANGEROUS_PATTERNS: &[&str] = &[
+    "format ",
+    "diskpart",
+    "bcdedit",
+    "vssadmin delete",
+    "wbadmin delete",
+    "cipher /w",
+    "reg delete hklm\\system",
+    "reg delete hklm\\software\\microsoft\\windows",
+];
+
+/// 递归删除命令，目标为盘符根目录或 Windows 目录时视为危险
+const RECURSIVE_DELETE_COMMANDS: &[&str] = &["rd /s", "rmdir /s", "del /s", "erase /s"];
+
+/// 允许位于系统目录中的卸载程序
+const TRUSTED_SYSTEM_UNINSTALLERS: &[&str] = &["msiexec.exe", "dism.exe"];
+
+/// 命令串联与重定向符号
+const SHELL_OPERATORS: &[char] = &['&', '|', '>', '<'];
+
+/// 校验问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    /// 可疑，需要显式确认才执行
+    Suspicious,
+    /// 危险，始终拒绝执行
+    Dangerous,
+}
+
+/// 单条校验问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+/// 卸载命令校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UninstallValidation {
+    pub command: String,
+    /// 解析出的可执行文件（已展开环境变量）
+    pub executable: Option<String>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl UninstallValidation {
+    pub fn is_dangerous(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == IssueSeverity::Dangerous)
+    }
+
+    pub fn is_suspicious(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == IssueSeverity::Suspicious)
+    }
+
+    fn push(&mut self, severity: IssueSeverity, message: impl Into<String>) {
+        self.issues.push(ValidationIssue {
+            severity,
+            message: message.into(),
+        });
+    }
+
+    fn describe(&self, severity: IssueSeverity) -> String {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .map(|issue| issue.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// 校验卸载命令
+pub fn validate_uninstall_string(uninstall_string: &str) -> UninstallValidation {
+    validate_command(uninstall_string, true)
+}
+
+/// 校验卸载命令；`check_local_files` 为 false 时跳过本机文件检查（用于远程计算机）
+fn validate_command(uninstall_string: &str, check_local_files: bool) -> UninstallValidation {
+    let command = uninstall_string.trim().to_string();
+    let mut validation = UninstallValidation {
+        command: command.clone(),
+        executable: None,
+        issues: Vec::new(),
+    };
+
+    if command.is_empty() {
+        validation.push(IssueSeverity::Dangerous, "卸载命令为空");
+        return validation;
+    }
+
+    check_dangerous_patterns(&command, &mut validation);
+
+    if command.contains(SHELL_OPERATORS) {
+        validation.push(
+            IssueSeverity::Suspicious,
+            "包含命令串联或重定向符号 (&, |, >, <)",
+        );
+    }
+
+    match parse_executable(&command) {
+        Some(executable) => {
+            if check_local_files {
+                check_executable(&executable, &mut validation);
+            }
+            validation.executable = Some(executable.to_string_lossy().to_string());
+        }
+        None => validation.push(IssueSeverity::Suspicious, "无法解析可执行文件"),
+    }
+
+    validation
+}
+
+/// 校验卸载命令，危险命令始终拒绝，可疑命令仅在 `allow_suspicious` 时放行
+pub fn ensure_uninstall_allowed(
+    uninstall_string: &str,
+    allow_suspicious: bool,
+) -> Result<UninstallValidation, UninstallerError> {
+    check_allowed(
+        validate_uninstall_string(uninstall_string),
+        allow_suspicious,
+    )
+}
+
+/// 校验将在远程计算机上执行的卸载命令
+///
+/// 可执行文件位于远程计算机，本机无法确认其是否存在，只检查命令文本
+pub fn ensure_remote_uninstall_allowed(
+    uninstall_string: &str,
+    allow_suspicious: bool,
+) -> Result<UninstallValidation, UninstallerError> {
+    check_allowed(validate_command(uninstall_string, false), allow_suspicious)
+}
+
+/// 危险命令始终拒绝，可疑命令仅在 `allow_suspicious` 时放行
+fn check_allowed(
+    validation: UninstallValidation,
+    allow_suspicious: bool,
+) -> Result<UninstallValidation, UninstallerError> {
+    if validation.is_dangerous() {
+        return Err(UninstallerError::UnsafeUninstallCommand(
+            validation.describe(IssueSeverity::Dangerous),
+        ));
+    }
+
+    if validation.is_suspicious() {
+        let reason = validation.describe(IssueSeverity::Suspicious);
+        if !allow_suspicious {
+            return Err(UninstallerError::UnsafeUninstallCommand(format!(
+                "{}（确认无误后可显式允许执行）",
+                reason
+            )));
+        }
+        tracing::warn!(
+            "已允许执行可疑卸载命令: {} ({})",
+            validation.command,
+            reason
+        );
+    }
+
+    Ok(validation)
+}
+
+/// 检查危险命令片段
+fn check_dangerous_patterns(command: &str, validation: &mut UninstallValidation) {
+    let normalized = command
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    for pattern in DANGEROUS_PATTERNS {
+        let hit = normalized.starts_with(pattern)
+            || normalized.contains(&format!(" {}", pattern))
+            || normalized.contains(&format!("&{}", pattern));
+        if hit {
+            validation.push(
+                IssueSeverity::Dangerous,
+                format!("包含危险命令: {}", pattern.trim()),
+            );
+        }
+    }
+
+    for delete in RECURSIVE_DELETE_COMMANDS {
+        let mut rest = normalized.as_str();
+        while let Some(index) = rest.find(delete) {
+            rest = &rest[index + delete.len()..];
+            let target = rest
+                .split_whitespace()
+                .find(|arg| !arg.starts_with('/'))
+                .unwrap_or_default()
+                .trim_matches('"');
+            if is_protected_delete_target(target) {
+                validation.push(
+                    IssueSeverity::Dangerous,
+                    format!("递归删除受保护目录: {} {}", delete, target),
+                );
+            }
+        }
+    }
+}
+
+/// 判断递归删除的目标是否为盘符根目录或系统目录
+fn is_protected_delete_target(target: &str) -> bool {
+    let target = target.trim_end_matches(['\\', '/']);
+    if target.is_empty() || target == "*" {
+        return false;
+    }
+
+    let is_drive_root = target.len() == 2 && target.ends_with(':');
+    let is_env_root = ["%systemdrive%", "%systemroot%", "%windir%"].contains(&target);
+
+    is_drive_root || is_env_root || utils::is_system_critical_path(target)
+}
+
+/// 从命令行中解析可执行文件路径
+///
+/// 支持带引号路径、未加引号但含空格的 `.exe` 路径，以及只写文件名的命令（如 `MsiExec.exe`）；
+/// 驱动程序和服务常用的 `\??\` 前缀会被去掉
+pub fn parse_executable(command: &str) -> Option<PathBuf> {
+    let command = command.trim_start().trim_start_matches(r"\??\");
+
+    let raw = if let Some(rest) = command.strip_prefix('"') {
+        let end = rest.find('"')?;
+        rest[..end].to_string()
+    } else {
+        let lower = command.to_ascii_lowercase();
+        match lower.find(".exe") {
+            // 逗号见于图标资源等 `<文件>,<序号>` 形式的值
+            Some(index)
+                if lower[index + 4..].is_empty()
+                    || lower[index + 4..].starts_with([' ', '/', ',']) =>
+            {
+                command[..index + 4].to_string()
+            }
+            _ => command.split_whitespace().next()?.to_string(),
+        }
+    };
+
+    let expanded = expand_windows_env_vars(raw.trim());
+    if expanded.is_empty() {
+        return None;
+    }
+
+    Some(PathBuf::from(utils::normalize_path(&expanded)))
+}
+
+/// 检查可执行文件是否存在以及是否位于系统目录
+fn check_executable(executable: &Path, validation: &mut UninstallValidation) {
+    let trusted = is_trusted_system_uninstaller(executable);
+
+    let has_directory = executable
+        .parent()
+        .map(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(false);
+
+    if !has_directory {
+        // 只写了文件名，交由 PATH 查找
+        if !trusted && find_in_path(executable).is_none() {
+            validation.push(
+                IssueSeverity::Suspicious,
+                format!("可执行文件不存在: {}", executable.display()),
+            );
+        }
+        return;
+    }
+
+    if !executable.exists() {
+        validation.push(
+            IssueSeverity::Suspicious,
+            format!("可执行文件不存在: {}", executable.display()),
+        );
+    }
+
+    if !trusted && utils::is_system_critical_path(&executable.to_string_lossy()) {
+        validation.push(
+            IssueSeverity::Suspicious,
+            format!("可执行文件位于系统目录: {}", executable.display()),
+        );
+    }
+}
+
+/// 是否为允许位于系统目录中的卸载程序（如 msiexec）
+///
+/// 只看文件名会把 `D:\Evil\msiexec.exe` 当成系统程序：带目录的路径必须直接位于
+/// `%SystemRoot%\System32` 或 `SysWOW64`，只写文件名时按 PATH 找到的位置判断
+pub fn is_trusted_system_uninstaller(executable: &Path) -> bool {
+    let file_name = executable
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let with_extension = format!("{}.exe", file_name);
+    if !TRUSTED_SYSTEM_UNINSTALLERS.contains(&file_name.as_str())
+        && !TRUSTED_SYSTEM_UNINSTALLERS.contains(&with_extension.as_str())
+    {
+        return false;
+    }
+
+    let has_directory = executable
+        .parent()
+        .is_some_and(|parent| !parent.as_os_str().is_empty());
+    let resolved = if has_directory {
+        Some(executable.to_path_buf())
+    } else {
+        find_in_path(executable).or_else(|| find_in_path(Path::new(&with_extension)))
+    };
+    resolved.is_some_and(|path| is_in_system_directory(&path.to_string_lossy()))
+}
+
+/// 路径是否直接位于 `%SystemRoot%\System32` 或 `%SystemRoot%\SysWOW64` 下
+fn is_in_system_directory(path: &str) -> bool {
+    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+    let system_root = utils::normalize_path(&system_root)
+        .trim_end_matches('\\')
+        .to_lowercase();
+    let path = utils::normalize_path(path).to_lowercase();
+    let Some((parent, _)) = path.rsplit_once('\\') else {
+        return false;
+    };
+
+    ["system32", "syswow64"]
+        .iter()
+        .any(|dir| parent == format!("{}\\{}", system_root, dir))
+}
+
+/// 在 PATH 中查找可执行文件
+fn find_in_path(executable: &Path) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(executable))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_executable_handles_quoted_and_spaced_paths() {
+        assert_eq!(
+            parse_executable(r#""C:\Program Files\Foo\unins000.exe" /SILENT"#),
+            Some(PathBuf::from(r"C:\Program Files\Foo\unins000.exe"))
+        );
+        assert_eq!(
+            parse_executable(r"C:\Program Files\Foo\uninstall.exe /S"),
+            Some(PathBuf::from(r"C:\Program Files\Foo\uninstall.exe"))
+        );
+        assert_eq!(
+            parse_executable("MsiExec.exe /X{12345678-1234-1234-1234-123456789012}"),
+            Some(PathBuf::from("MsiExec.exe"))
+        );
+        assert_eq!(
+            parse_executable(r"\??\C:\Program Files\Foo\foo.exe,0"),
+            Some(PathBuf::from(r"C:\Program Files\Foo\foo.exe"))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_destructive_commands() {
+        let cases = [
+            r"format c: /q",
+            r#""C:\Foo\uninstall.exe" & rd /s /q c:\"#,
+            r"cmd /c rmdir /s /q %SystemRoot%",
+            r"diskpart /s script.txt",
+        ];
+
+        for case in cases {
+            assert!(validate_uninstall_string(case).is_dangerous(), "{}", case);
+        }
+    }
+
+    #[test]
+    fn validate_flags_missing_executable_as_suspicious() {
+        let validation = validate_uninstall_string(r#""Z:\missing\unins000.exe" /SILENT"#);
+
+        assert!(!validation.is_dangerous());
+        assert!(validation.is_suspicious());
+        assert!(ensure_uninstall_allowed(&validation.command, false).is_err());
+        assert!(ensure_uninstall_allowed(&validation.command, true).is_ok());
+    }
+
+    #[test]
+    fn validate_accepts_msiexec() {
+        let validation =
+            validate_uninstall_string("MsiExec.exe /X{12345678-1234-1234-1234-123456789012}");
+
+        assert!(validation.issues.is_empty(), "{:?}", validation.issues);
+    }
+
+    #[test]
+    fn trusts_system_uninstallers_only_in_system_directories() {
+        let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+
+        for dir in ["System32", "SysWOW64"] {
+            let path = format!(r"{}\{}\msiexec.exe", system_root, dir);
+            assert!(is_trusted_system_uninstaller(Path::new(&path)), "{}", path);
+        }
+        assert!(!is_trusted_system_uninstaller(Path::new(
+            r"D:\Downloads\msiexec.exe"
+        )));
+        assert!(!is_trusted_system_uninstaller(Path::new(&format!(
+            r"{}\System32\Tasks\msiexec.exe",
+            system_root
+        ))));
+        assert!(!is_trusted_system_uninstaller(Path::new(&format!(
+            r"{}\System32\notepad.exe",
+            system_root
+        ))));
+    }
+}
@@ -0,0 +1,209 @@
+//! 安装日志持久化
+//!
+//! 每个程序一个 JSON 文件，卸载时可直接按日志还原安装内容，不依赖名称匹配。
+
+use super::models::InstallLog;
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::storage;
+use crate::modules::scanner::models::{Confidence, Trace, TraceType};
+use crate::modules::scanner::{matching, metadata};
+use std::path::{Path, PathBuf};
+use winreg::RegKey;
+
+const INSTALL_LOG_DIR_NAME: &str = "install-logs";
+
+/// 获取安装日志目录
+fn get_install_log_dir() -> Result<PathBuf, UninstallerError> {
+    let dir = storage::get_storage_root_dir()?.join(INSTALL_LOG_DIR_NAME);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 根据程序名生成日志文件名
+fn log_file_name(program_name: &str) -> String {
+    let sanitized: String = program_name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}.json", sanitized)
+}
+
+/// 保存安装日志，同名程序的旧日志会被覆盖
+pub fn save_install_log(log: &InstallLog) -> Result<PathBuf, UninstallerError> {
+    let path = get_install_log_dir()?.join(log_file_name(&log.program_name));
+    let content = serde_json::to_string_pretty(log)
+        .map_err(|error| UninstallerError::Serde(error.to_string()))?;
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// 读取程序的安装日志
+pub fn get_install_log(program_name: &str) -> Result<Option<InstallLog>, UninstallerError> {
+    let path = get_install_log_dir()?.join(log_file_name(program_name));
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&content).ok())
+}
+
+/// 按安装日志生成痕迹，每发现一项即交给 `emit`
+///
+/// 已归属到程序的内容是安装时实际新增的，直接视为高置信度；同一时段新增但无法确认归属的内容
+/// 名称或发布者目录与程序一致时为中置信度，否则为低置信度，不会被默认选中
+pub fn scan_install_log_traces(
+    program_name: &str,
+    emit: &mut dyn FnMut(Trace),
+) -> Result<(), UninstallerError> {
+    let Some(log) = get_install_log(program_name)? else {
+        return Ok(());
+    };
+
+    let appdata_roots: Vec<String> = ["APPDATA", "LOCALAPPDATA"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|root| root.to_lowercase())
+        .collect();
+
+    for (path, unattributed) in log_entries(&log, &log.new_paths, &log.unattributed_paths) {
+        let path_lower = path.to_lowercase();
+        let trace_type = if appdata_roots
+            .iter()
+            .any(|root| path_lower.starts_with(root))
+        {
+            TraceType::AppData
+        } else {
+            TraceType::File
+        };
+
+        let mut trace = log_trace(&log, trace_type, path, "目录", unattributed);
+        trace.exists = Path::new(path).exists();
+        emit(trace);
+    }
+
+    for (key, unattributed) in log_entries(
+        &log,
+        &log.new_registry_keys,
+        &log.unattributed_registry_keys,
+    ) {
+        let mut trace = log_trace(&log, TraceType::RegistryKey, key, "注册表项", unattributed);
+        trace.exists = utils::parse_registry_path(key)
+            .map(|(hkey, subpath)| RegKey::predef(hkey).open_subkey(subpath).is_ok())
+            .unwrap_or(false);
+        emit(trace);
+    }
+
+    Ok(())
+}
+
+/// 日志中的项及无法确认归属时的置信度，已归属的项为 None；旧版日志的所有项都按无法确认处理
+fn log_entries<'a>(
+    log: &InstallLog,
+    owned: &'a [String],
+    others: &'a [String],
+) -> Vec<(&'a String, Option<Confidence>)> {
+    owned
+        .iter()
+        .map(|item| (item, log.attributed))
+        .chain(others.iter().map(|item| (item, false)))
+        .map(|(item, attributed)| {
+            (
+                item,
+                (!attributed).then(|| unattributed_confidence(log, item)),
+            )
+        })
+        .collect()
+}
+
+/// 安装日志中的一项，`unattributed` 为无法确认归属时的置信度
+fn log_trace(
+    log: &InstallLog,
+    trace_type: TraceType,
+    path: &str,
+    kind: &str,
+    unattributed: Option<Confidence>,
+) -> Trace {
+    let (confidence, description, reason) = match unattributed {
+        None => (
+            Confidence::High,
+            format!("安装监视记录的{}", kind),
+            "安装时新增（来自安装日志）",
+        ),
+        Some(confidence) => (
+            confidence,
+            format!("安装期间新增的{}，可能属于其他程序", kind),
+            "安装期间新增，但不在安装目录内且名称与程序不一致（来自安装日志）",
+        ),
+    };
+    let mut trace = Trace::new(log.program_name.clone(), trace_type, path.to_string())
+        .with_description(description)
+        .with_confidence(confidence);
+    trace.set_match_reason(reason.to_string());
+    trace
+}
+
+/// 无法确认归属的项：路径按单词包含程序名或有发布者目录时为中置信度，否则为低置信度
+fn unattributed_confidence(log: &InstallLog, path: &str) -> Confidence {
+    let path_lower = path.to_lowercase();
+    let publisher_dirs: Vec<String> = metadata::publisher_dir_names(log.publisher.as_deref())
+        .into_iter()
+        .map(|name| name.to_lowercase())
+        .collect();
+    let related = matching::name_matches(&path_lower, &log.program_name.to_lowercase())
+        || path_lower
+            .split('\\')
+            .any(|segment| publisher_dirs.iter().any(|dir| dir == segment));
+    if related {
+        Confidence::Medium
+    } else {
+        Confidence::Low
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unattributed_changes_are_never_high_confidence() {
+        let log = InstallLog {
+            program_name: "Demo Studio".to_string(),
+            publisher: Some("Acme Corp".to_string()),
+            version: None,
+            install_location: None,
+            installer_source: None,
+            detected_at: String::new(),
+            new_paths: Vec::new(),
+            new_registry_keys: Vec::new(),
+            unattributed_paths: Vec::new(),
+            unattributed_registry_keys: Vec::new(),
+            attributed: true,
+        };
+
+        assert_eq!(
+            unattributed_confidence(&log, r"C:\ProgramData\Demo Studio Cache"),
+            Confidence::Medium
+        );
+        assert_eq!(
+            unattributed_confidence(&log, r"HKLM\SOFTWARE\Acme"),
+            Confidence::Medium
+        );
+        assert_eq!(
+            unattributed_confidence(&log, r"C:\ProgramData\Other"),
+            Confidence::Low
+        );
+
+        let trace = log_trace(
+            &log,
+            TraceType::File,
+            r"C:\ProgramData\Other",
+            "目录",
+            Some(Confidence::Low),
+        );
+        assert_eq!(trace.confidence, Confidence::Low);
+    }
+}
@@ -0,0 +1,384 @@
+//! 安装监视
+//!
+//! 定期对比 Uninstall 注册表项、Program Files 等目录和软件注册表项，发现新安装的程序时
+//! 把安装前后的差异保存为安装日志，之后卸载该程序即可按日志精确清理。
+
+pub mod install_log;
+pub mod models;
+
+use crate::modules::common::error::UninstallerError;
+use crate::modules::common::utils;
+use crate::modules::lister::{self, models::InstalledProgram};
+use crate::modules::scanner::matching;
+use chrono::Utc;
+use models::InstallLog;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+use winreg::enums::*;
+use winreg::RegKey;
+
+/// 默认轮询间隔（秒）
+pub const DEFAULT_POLL_INTERVAL_SECONDS: u64 = 5;
+
+/// 新增内容等待归属到程序的最长时间，超过后视为与安装无关
+pub const PENDING_WINDOW: Duration = Duration::from_secs(600);
+
+/// 监视的软件注册表根（只记录两级：厂商\产品）
+const SOFTWARE_REGISTRY_ROOTS: &[(&str, &str)] = &[
+    ("HKLM", r"SOFTWARE"),
+    ("HKLM", r"SOFTWARE\WOW6432Node"),
+    ("HKCU", r"SOFTWARE"),
+];
+
+/// 某一时刻的系统状态
+#[derive(Debug, Default)]
+pub struct SystemSnapshot {
+    programs: HashMap<String, InstalledProgram>,
+    paths: HashSet<String>,
+    registry_keys: HashSet<String>,
+}
+
+impl SystemSnapshot {
+    /// 采集当前快照
+    pub fn capture() -> Result<Self, UninstallerError> {
+        let programs = lister::registry::list_registry_programs()?
+            .into_iter()
+            .map(|program| (program.name.to_lowercase(), program))
+            .collect();
+
+        let mut paths = HashSet::new();
+        for (root, depth) in watched_directories() {
+            collect_paths(&root, depth, &mut paths);
+        }
+
+        let mut registry_keys = HashSet::new();
+        for (hive, path) in SOFTWARE_REGISTRY_ROOTS {
+            collect_registry_keys(hive, path, &mut registry_keys);
+        }
+
+        Ok(Self {
+            programs,
+            paths,
+            registry_keys,
+        })
+    }
+
+    /// 与上一次的快照对比，为每个新出现的程序生成安装日志
+    ///
+    /// 本次新增的目录和注册表项先并入 `pending`：安装程序通常先写文件，最后才写卸载信息，
+    /// 程序出现时再从中挑出位于安装目录内或名称与程序一致的项；其余项只作为无法确认的变化记录。
+    /// 已归属的项从 `pending` 中移除，无法归属的项超过 [`PENDING_WINDOW`] 后丢弃。
+    pub fn diff_installs(
+        &self,
+        before: &SystemSnapshot,
+        pending: &mut PendingChanges,
+    ) -> Vec<InstallLog> {
+        pending.record(
+            self.paths.difference(&before.paths),
+            self.registry_keys.difference(&before.registry_keys),
+        );
+        pending.prune(self);
+
+        let detected_at = Utc::now().to_rfc3339();
+        let mut logs = Vec::new();
+
+        for program in self
+            .programs
+            .iter()
+            .filter(|(name, _)| !before.programs.contains_key(*name))
+            .map(|(_, program)| program)
+        {
+            let (new_paths, unattributed_paths) =
+                split_attributed(pending.paths.keys(), |path| owns_path(program, path));
+            let (new_registry_keys, unattributed_registry_keys) =
+                split_attributed(pending.registry_keys.keys(), |key| owns_key(program, key));
+            pending.remove(&new_paths, &new_registry_keys);
+
+            logs.push(InstallLog {
+                program_name: program.name.clone(),
+                publisher: program.publisher.clone(),
+                version: program.version.clone(),
+                install_location: program.install_location.clone(),
+                installer_source: program.installer_source.clone(),
+                detected_at: detected_at.clone(),
+                new_paths: collapse_nested(new_paths),
+                new_registry_keys: collapse_nested(new_registry_keys),
+                unattributed_paths: collapse_nested(unattributed_paths),
+                unattributed_registry_keys: collapse_nested(unattributed_registry_keys),
+                attributed: true,
+            });
+        }
+
+        logs
+    }
+}
+
+/// 尚未归属到程序的新增目录和注册表项，记录首次出现的时间
+#[derive(Debug, Default)]
+pub struct PendingChanges {
+    paths: HashMap<String, Instant>,
+    registry_keys: HashMap<String, Instant>,
+}
+
+impl PendingChanges {
+    fn record<'a>(
+        &mut self,
+        paths: impl Iterator<Item = &'a String>,
+        registry_keys: impl Iterator<Item = &'a String>,
+    ) {
+        let now = Instant::now();
+        for path in paths {
+            self.paths.entry(path.clone()).or_insert(now);
+        }
+        for key in registry_keys {
+            self.registry_keys.entry(key.clone()).or_insert(now);
+        }
+    }
+
+    /// 丢弃已被删除（如安装程序的临时目录）或等待过久的项
+    fn prune(&mut self, current: &SystemSnapshot) {
+        self.paths.retain(|path, seen_at| {
+            current.paths.contains(path) && seen_at.elapsed() < PENDING_WINDOW
+        });
+        self.registry_keys.retain(|key, seen_at| {
+            current.registry_keys.contains(key) && seen_at.elapsed() < PENDING_WINDOW
+        });
+    }
+
+    fn remove(&mut self, paths: &[String], registry_keys: &[String]) {
+        for path in paths {
+            self.paths.remove(path);
+        }
+        for key in registry_keys {
+            self.registry_keys.remove(key);
+        }
+    }
+}
+
+/// 按 `owned` 拆分为属于程序的项和无法归属的项，均已排序
+fn split_attributed<'a>(
+    items: impl Iterator<Item = &'a String>,
+    owned: impl Fn(&str) -> bool,
+) -> (Vec<String>, Vec<String>) {
+    let (mut attributed, mut others): (Vec<String>, Vec<String>) =
+        items.cloned().partition(|item| owned(item));
+    attributed.sort();
+    others.sort();
+    (attributed, others)
+}
+
+/// 目录位于程序安装目录内（或是安装目录的上级，如新建的厂商目录），或末级名称与程序名一致
+fn owns_path(program: &InstalledProgram, path: &str) -> bool {
+    let path_lower = path.trim_end_matches('\\').to_lowercase();
+    let in_location = program
+        .install_location
+        .as_deref()
+        .map(|location| {
+            location
+                .trim()
+                .trim_matches('"')
+                .trim_end_matches('\\')
+                .to_lowercase()
+        })
+        .filter(|location| !location.is_empty())
+        .is_some_and(|location| {
+            utils::is_same_or_child(&path_lower, &location)
+                || utils::is_same_or_child(&location, &path_lower)
+        });
+    in_location || leaf_matches_name(program, &path_lower)
+}
+
+/// 注册表项（厂商或厂商\产品）的末级名称与程序名一致
+fn owns_key(program: &InstalledProgram, key: &str) -> bool {
+    leaf_matches_name(program, &key.to_lowercase())
+}
+
+fn leaf_matches_name(program: &InstalledProgram, path_lower: &str) -> bool {
+    let leaf = path_lower.rsplit('\\').next().unwrap_or_default();
+    matching::name_matches(leaf, &program.name.to_lowercase())
+}
+
+/// 持续监视新安装，直到 `stop` 被置位
+///
+/// 每次轮询都与上一次的快照对比，发现新程序后保存安装日志并回调 `on_install`。
+/// 安装过程可能跨越多个轮询周期，期间新增的内容暂存在 [`PendingChanges`] 中等待归属。
+pub fn watch_installations(
+    interval: Duration,
+    stop: &AtomicBool,
+    mut on_install: impl FnMut(&InstallLog, &Path),
+) -> Result<(), UninstallerError> {
+    let mut baseline = SystemSnapshot::capture()?;
+    let mut pending = PendingChanges::default();
+    tracing::info!(
+        "安装监视已启动，基准包含 {} 个程序",
+        baseline.programs.len()
+    );
+
+    while !stop.load(Ordering::Relaxed) {
+        std::thread::sleep(interval);
+
+        let current = match SystemSnapshot::capture() {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::warn!("采集系统快照失败: {}", e);
+                continue;
+            }
+        };
+
+        for log in &current.diff_installs(&baseline, &mut pending) {
+            match install_log::save_install_log(log) {
+                Ok(path) => on_install(log, &path),
+                Err(e) => tracing::warn!("保存 {} 的安装日志失败: {}", log.program_name, e),
+            }
+        }
+
+        // 每次都以当前状态为新基准，之前的变化已并入待归属的项
+        baseline = current;
+    }
+
+    Ok(())
+}
+
+/// 监视的目录及遍历深度
+fn watched_directories() -> Vec<(PathBuf, usize)> {
+    let mut dirs = Vec::new();
+
+    for name in ["ProgramFiles", "ProgramFiles(x86)"] {
+        if let Ok(path) = std::env::var(name) {
+            dirs.push((PathBuf::from(path), 2));
+        }
+    }
+
+    for name in ["ProgramData", "APPDATA", "LOCALAPPDATA"] {
+        if let Ok(path) = std::env::var(name) {
+            dirs.push((PathBuf::from(path), 1));
+        }
+    }
+
+    dirs
+}
+
+/// 收集目录下指定深度内的子目录
+fn collect_paths(root: &Path, depth: usize, paths: &mut HashSet<String>) {
+    for entry in WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(depth)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir())
+    {
+        paths.insert(entry.path().to_string_lossy().to_string());
+    }
+}
+
+/// 收集注册表根下两级子项
+fn collect_registry_keys(hive: &str, path: &str, keys: &mut HashSet<String>) {
+    let hkey = match hive {
+        "HKLM" => HKEY_LOCAL_MACHINE,
+        _ => HKEY_CURRENT_USER,
+    };
+    let Ok(root) = RegKey::predef(hkey).open_subkey(path) else {
+        return;
+    };
+
+    for vendor in root.enum_keys().filter_map(|k| k.ok()) {
+        let vendor_path = format!(r"{}\{}\{}", hive, path, vendor);
+        if let Ok(vendor_key) = root.open_subkey(&vendor) {
+            for product in vendor_key.enum_keys().filter_map(|k| k.ok()) {
+                keys.insert(format!(r"{}\{}", vendor_path, product));
+            }
+        }
+        keys.insert(vendor_path);
+    }
+}
+
+/// 去掉已被父路径覆盖的子路径（输入需已排序）
+fn collapse_nested(sorted: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for path in sorted {
+        let covered = result.last().is_some_and(|parent| {
+            path.len() > parent.len()
+                && path[parent.len()..].starts_with('\\')
+                && path[..parent.len()].eq_ignore_ascii_case(parent)
+        });
+        if !covered {
+            result.push(path);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_installs_attributes_only_program_paths() {
+        let before = SystemSnapshot {
+            paths: [r"C:\Program Files\Old".to_string()].into_iter().collect(),
+            ..SystemSnapshot::default()
+        };
+        let mut pending = PendingChanges::default();
+
+        // 安装程序先写文件，同时另一个程序在更新自己的目录
+        let files_written = SystemSnapshot {
+            paths: [
+                r"C:\Program Files\Old",
+                r"C:\Program Files\Demo",
+                r"C:\Program Files\Demo\bin",
+                r"C:\ProgramData\Other",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+            registry_keys: [r"HKLM\SOFTWARE\Demo", r"HKLM\SOFTWARE\Other"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            ..SystemSnapshot::default()
+        };
+        assert!(files_written
+            .diff_installs(&before, &mut pending)
+            .is_empty());
+
+        // 下一次轮询才出现卸载信息
+        let mut registered = SystemSnapshot {
+            paths: files_written.paths.clone(),
+            registry_keys: files_written.registry_keys.clone(),
+            ..SystemSnapshot::default()
+        };
+        let mut program =
+            InstalledProgram::new("Demo".to_string(), lister::models::InstallSource::Registry);
+        program.install_location = Some(r"C:\Program Files\Demo\".to_string());
+        registered.programs.insert("demo".to_string(), program);
+
+        let logs = registered.diff_installs(&files_written, &mut pending);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].program_name, "Demo");
+        assert!(logs[0].attributed);
+        assert_eq!(
+            logs[0].new_paths,
+            vec![r"C:\Program Files\Demo".to_string()]
+        );
+        assert_eq!(
+            logs[0].new_registry_keys,
+            vec![r"HKLM\SOFTWARE\Demo".to_string()]
+        );
+        assert_eq!(
+            logs[0].unattributed_paths,
+            vec![r"C:\ProgramData\Other".to_string()]
+        );
+        assert_eq!(
+            logs[0].unattributed_registry_keys,
+            vec![r"HKLM\SOFTWARE\Other".to_string()]
+        );
+
+        // 已归属的项不会再记入之后安装的程序
+        assert!(!pending.paths.contains_key(r"C:\Program Files\Demo"));
+        assert!(pending.paths.contains_key(r"C:\ProgramData\Other"));
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// 一次安装的前后快照差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallLog {
+    pub program_name: String,
+    pub publisher: Option<String>,
+    pub version: Option<String>,
+    pub install_location: Option<String>,
+    /// 安装包所在位置，卸载后用于清理遗留的安装包缓存
+    #[serde(default)]
+    pub installer_source: Option<String>,
+    pub detected_at: String,
+    /// 安装期间新增、位于安装目录内或名称与程序一致的目录
+    pub new_paths: Vec<String>,
+    /// 安装期间新增、名称与程序一致的注册表项
+    pub new_registry_keys: Vec<String>,
+    /// 同一时段新增但无法确认属于该程序的目录
+    #[serde(default)]
+    pub unattributed_paths: Vec<String>,
+    /// 同一时段新增但无法确认属于该程序的注册表项
+    #[serde(default)]
+    pub unattributed_registry_keys: Vec<String>,
+    /// `new_paths` 和 `new_registry_keys` 是否已按程序归属；旧版日志未归属，内容按无法确认处理
+    #[serde(default)]
+    pub attributed: bool,
+}